@@ -0,0 +1,62 @@
+use std::{env, process::Command};
+
+/// Passes the information that is printed by `--version-json` to the compiler.
+fn main() {
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    // not available when the crate is build from a source archive
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|f| f.status.success())
+        .and_then(|f| String::from_utf8(f.stdout).ok());
+    if let Some(git_hash) = git_hash {
+        println!("cargo:rustc-env=ALPHA_TUI_GIT_HASH={}", git_hash.trim());
+    }
+
+    // the current date is not used, because this script is not rerun on every build and the date would go stale,
+    // SOURCE_DATE_EPOCH is set for reproducible builds
+    let timestamp = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|f| f.parse::<u64>().ok());
+    if let Some(timestamp) = timestamp {
+        println!(
+            "cargo:rustc-env=ALPHA_TUI_BUILD_DATE={}",
+            date_from_timestamp(timestamp)
+        );
+    }
+
+    // cargo replaces `-` in the names of features with `_`, e.g. CARGO_FEATURE_TEST_UTILS for `test-utils`
+    let mut features = env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|f| f.to_lowercase().replace('_', "-"))
+        })
+        .collect::<Vec<_>>();
+    features.sort();
+    println!("cargo:rustc-env=ALPHA_TUI_FEATURES={}", features.join(","));
+    println!(
+        "cargo:rustc-env=ALPHA_TUI_TARGET={}",
+        env::var("TARGET").unwrap_or_default()
+    );
+}
+
+/// Formats the unix timestamp as `YYYY-MM-DD`.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn date_from_timestamp(timestamp: u64) -> String {
+    let days = timestamp / 86_400 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
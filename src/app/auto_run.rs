@@ -0,0 +1,99 @@
+use std::time::{Duration, Instant};
+
+/// Maximum number of steps that are run at once, if the auto-run fell behind, e.g. because stepping is slower than
+/// the configured speed, the remaining steps are dropped instead of being caught up.
+const MAX_STEPS_PER_TICK: u32 = 1000;
+
+/// Auto-run mode, in which the program is stepped automatically with a fixed speed.
+///
+/// Steps are scheduled independently of the rendering of the ui: if more steps are due than frames are drawn, multiple
+/// steps are run between two frames, so that a low tick rate does not slow down the execution.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AutoRun {
+    /// Time between two steps.
+    step_interval: Duration,
+    /// Time at which the next step is due.
+    next_step: Instant,
+    /// Maximum number of steps that are run at once.
+    max_steps: u32,
+}
+
+impl AutoRun {
+    /// Starts the auto-run at `now` with `steps_per_second` steps, 0 is treated as 1.
+    pub fn new(steps_per_second: u32, now: Instant) -> Self {
+        let step_interval = Duration::from_secs(1) / steps_per_second.max(1);
+        Self {
+            step_interval,
+            next_step: now + step_interval,
+            max_steps: MAX_STEPS_PER_TICK,
+        }
+    }
+
+    /// Starts the auto-run at `now` with a delay of `delay` between two steps, used in presentation mode.
+    ///
+    /// At most one step is run at once, so that each step is shown for at least `delay`, even when a frame was
+    /// drawn late.
+    pub fn with_delay(delay: Duration, now: Instant) -> Self {
+        Self {
+            step_interval: delay,
+            next_step: now + delay,
+            max_steps: 1,
+        }
+    }
+
+    /// Returns the number of steps that are due at `now` and schedules the next step.
+    pub fn due_steps(&mut self, now: Instant) -> u32 {
+        let mut steps = 0;
+        while self.next_step <= now {
+            steps += 1;
+            self.next_step += self.step_interval;
+            if steps == self.max_steps {
+                self.next_step = now + self.step_interval;
+                break;
+            }
+        }
+        steps
+    }
+
+    /// Returns the time until the next step is due.
+    pub fn until_next_step(&self, now: Instant) -> Duration {
+        self.next_step.saturating_duration_since(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{AutoRun, MAX_STEPS_PER_TICK};
+
+    #[test]
+    fn test_auto_run_due_steps() {
+        let start = Instant::now();
+        let mut auto_run = AutoRun::new(10, start);
+        assert_eq!(auto_run.due_steps(start), 0);
+        assert_eq!(auto_run.until_next_step(start), Duration::from_millis(100));
+        assert_eq!(auto_run.due_steps(start + Duration::from_millis(100)), 1);
+        // steps are not bound to the frames that are drawn
+        assert_eq!(auto_run.due_steps(start + Duration::from_millis(550)), 4);
+        assert_eq!(
+            auto_run.until_next_step(start + Duration::from_millis(550)),
+            Duration::from_millis(50)
+        );
+        // steps that can not be caught up are dropped
+        let now = start + Duration::from_secs(1000);
+        assert_eq!(auto_run.due_steps(now), MAX_STEPS_PER_TICK);
+        assert_eq!(auto_run.due_steps(now), 0);
+    }
+
+    #[test]
+    fn test_auto_run_with_delay() {
+        let start = Instant::now();
+        let mut auto_run = AutoRun::with_delay(Duration::from_secs(2), start);
+        assert_eq!(auto_run.due_steps(start + Duration::from_secs(1)), 0);
+        // steps are not caught up, each step is shown for the full delay
+        let now = start + Duration::from_secs(7);
+        assert_eq!(auto_run.due_steps(now), 1);
+        assert_eq!(auto_run.until_next_step(now), Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,52 @@
+use miette::Result;
+
+use crate::cli::{GlobalArgs, LoadArgs};
+
+/// Watches the session that is shared at the socket `--attach`.
+#[cfg(unix)]
+pub fn attach(global_args: &GlobalArgs, load_args: &LoadArgs) -> Result<()> {
+    use std::{rc::Rc, time::Duration};
+
+    use crate::app::share;
+
+    let path = load_args
+        .attach
+        .as_ref()
+        .expect("attach should only be called when --attach is set");
+    let (repro, keys, actions) = share::attach(path)?;
+    let theme = Rc::new(super::load_theme(&load_args.load_playground_args)?);
+    let mut app = super::repro::app_from_repro(global_args, &repro, theme)?;
+    app.attach_viewer();
+    app.set_tick_rate(Duration::from_millis(load_args.tick_rate));
+
+    println!("Attached to shared session, launching tui");
+    let mut terminal = super::setup_terminal()?;
+
+    // catch up with the actions that were performed before attaching
+    let mut res = Ok(false);
+    for key in repro.keys()? {
+        res = app.handle_key_event(key);
+        if !matches!(res, Ok(false)) {
+            break;
+        }
+    }
+    let res = match res {
+        Ok(false) => app.run_attached(&mut terminal, &keys, &actions),
+        Ok(true) => Ok(()),
+        Err(e) => Err(e),
+    };
+
+    // restore terminal
+    super::restore_terminal(&mut terminal)?;
+
+    println!("Detached from shared session");
+    res
+}
+
+/// Watches the session that is shared at the socket `--attach`.
+#[cfg(not(unix))]
+pub fn attach(_global_args: &GlobalArgs, _load_args: &LoadArgs) -> Result<()> {
+    Err(miette::miette!(
+        "Attaching to a shared session is only supported on unix systems"
+    ))
+}
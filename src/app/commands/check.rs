@@ -1,23 +1,50 @@
-use std::process::exit;
+use std::{path::Path, process::exit};
 
-use miette::miette;
+use miette::{miette, Diagnostic};
 
 use crate::{
-    cli::{CheckArgs, CheckCommand, GlobalArgs},
-    runtime::builder::RuntimeBuilder,
+    cli::{CheckArgs, CheckCommand, CheckRunArgs, GlobalArgs},
+    exercise,
+    progress::Progress,
+    report::{RunReport, RunResult},
+    runtime::{
+        autofix, builder::RuntimeBuilder, error_handling::BuildError, metrics::Metrics,
+        state_dump::StateDump, Runtime,
+    },
+    utils::write_file_if_unchanged,
 };
 
+use super::progress::progress_file;
+
 pub fn check(
     global_args: &GlobalArgs,
     check_args: &CheckArgs,
-    instructions: Vec<String>,
+    mut instructions: Vec<String>,
     input: &str,
 ) {
+    if check_args.fix {
+        instructions = fix(check_args, instructions, input);
+    }
+
     // create runtime builder and apply cli args
     println!("Building instructions");
-    let mut rb = match RuntimeBuilder::new(&instructions, input) {
+    // in robust mode panics while the instructions are build are reported as error as well
+    let rb = if check_args.check_load_args.robust {
+        RuntimeBuilder::new_robust(&instructions, input)
+    } else {
+        RuntimeBuilder::new(&instructions, input).map_err(BuildError::from)
+    };
+    let mut rb = match rb {
         Ok(rb) => rb,
         Err(e) => {
+            send_report(
+                check_args,
+                &instructions,
+                input,
+                RunResult::BuildError,
+                0,
+                Some(&e),
+            );
             println!(
                 "Check unsuccessful, program did not compile.\nError: {:?}",
                 miette!(e)
@@ -36,9 +63,35 @@ pub fn check(
         );
         exit(10);
     }
+    // the program header could extend the memory that is set by the exercise settings
+    if !check_args.check_load_args.enforce_settings {
+        if let Err(e) = rb.apply_program_header(&instructions) {
+            send_report(
+                check_args,
+                &instructions,
+                input,
+                RunResult::BuildError,
+                0,
+                Some(&e),
+            );
+            println!(
+                "Check unsuccessful: {:?}",
+                miette!("Unable to create RuntimeBuilder:\n{:?}", e)
+            );
+            exit(1);
+        }
+    }
     if let Err(e) =
         rb.apply_instruction_limiting_args(&check_args.check_load_args.instruction_limiting_args)
     {
+        send_report(
+            check_args,
+            &instructions,
+            input,
+            RunResult::BuildError,
+            0,
+            Some(&e),
+        );
         println!(
             "Check unsuccessful: {:?}",
             miette!("Unable to create RuntimeBuilder:\n{:?}", e)
@@ -46,6 +99,14 @@ pub fn check(
         exit(1);
     }
     if let Err(e) = rb.apply_check_load_args(&check_args.check_load_args) {
+        send_report(
+            check_args,
+            &instructions,
+            input,
+            RunResult::BuildError,
+            0,
+            Some(&e),
+        );
         println!(
             "Check unsuccessful: {:?}",
             miette!("Unable to create RuntimeBuilder:\n{:?}", e)
@@ -56,6 +117,14 @@ pub fn check(
     let mut rt = match rb.build() {
         Ok(rt) => rt,
         Err(e) => {
+            send_report(
+                check_args,
+                &instructions,
+                input,
+                RunResult::BuildError,
+                0,
+                Some(&e),
+            );
             println!(
                 "Check unsuccessful, program did not compile.\nError: {:?}",
                 miette!(e)
@@ -64,16 +133,64 @@ pub fn check(
         }
     };
 
+    // list contracts of functions
+    let mut contracts = rt.control_flow().contracts.iter().collect::<Vec<_>>();
+    if !contracts.is_empty() {
+        contracts.sort_by_key(|(label, _)| *label);
+        println!("Contracts:");
+        for (label, contract) in contracts {
+            println!("  {label}: {contract}");
+        }
+    }
+
+    // analyze stack usage, problems are reported as warnings only
+    let warnings = rt.stack_analysis();
+    if !warnings.is_empty() {
+        println!("Stack analysis found {} warning(s):", warnings.len());
+        for warning in warnings {
+            println!("Warning: {warning}");
+        }
+    }
+
     match check_args.command {
         CheckCommand::Compile => {
             println!("Check successful");
             return;
         }
-        CheckCommand::Run => (),
+        CheckCommand::Run(_) => (),
     }
 
     // run runtime
-    if let Err(e) = rt.run() {
+    let res = match &check_args.command {
+        CheckCommand::Run(CheckRunArgs {
+            metrics_file: Some(path),
+            sample_every,
+            ..
+        }) => {
+            let mut metrics = Metrics::new(*sample_every);
+            let res = rt.run_with_metrics(&mut metrics);
+            if let Err(e) = metrics.write_to_file(path) {
+                println!("Check unsuccessful: {e:?}");
+                exit(10);
+            }
+            println!("Metrics written to {path}");
+            res
+        }
+        _ => rt.run(),
+    };
+    // the printed values are the output of the program, they are written even when the program failed
+    for value in &rt.control_flow().output {
+        println!("{value}");
+    }
+    if let Err(e) = res {
+        send_report(
+            check_args,
+            &instructions,
+            input,
+            RunResult::RuntimeError,
+            rt.control_flow().steps,
+            Some(&e.reason),
+        );
         println!(
             "Check unsuccessful, runtime error while running program.\nError: {:?}",
             miette!(e)
@@ -81,5 +198,171 @@ pub fn check(
         exit(1);
     }
 
+    // report the result of the called function
+    if let Some(label) = &check_args.check_load_args.call {
+        let memory = rt.runtime_memory();
+        let accumulator = match memory.accumulators.get(&0).and_then(|f| f.data) {
+            Some(value) => value.to_string(),
+            None => "None".to_string(),
+        };
+        let stack = memory
+            .stack
+            .iter()
+            .rev()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("Result of {label}: α0 = {accumulator}, stack (top first): [{stack}]");
+    }
+
+    if let Some(path) = &check_args.check_load_args.dump_state {
+        if let Err(e) = StateDump::from(rt.runtime_memory())
+            .write_to_file(path, check_args.check_load_args.format)
+        {
+            println!("Check unsuccessful: {e:?}");
+            exit(10);
+        }
+        println!("State written to {path}");
+    }
+
+    check_exercise(global_args, check_args, &instructions, input, &rt);
+
+    send_report(
+        check_args,
+        &instructions,
+        input,
+        RunResult::Finished,
+        rt.control_flow().steps,
+        None,
+    );
     println!("Check successful");
 }
+
+/// Compares the final state with the expected state of the exercise the program belongs to and records the exercise
+/// as completed, if the state matches. Does nothing if the exercise has no expected state.
+fn check_exercise(
+    global_args: &GlobalArgs,
+    check_args: &CheckArgs,
+    instructions: &[String],
+    input: &str,
+    rt: &Runtime,
+) {
+    let dir = Path::new(input).parent().unwrap_or(Path::new(""));
+    let settings = match exercise::read_exercise_settings(input) {
+        Ok(Some(settings)) => settings,
+        Ok(None) => return,
+        Err(e) => {
+            println!("Check unsuccessful: {e:?}");
+            exit(10);
+        }
+    };
+    let differences = match settings.check_result(&StateDump::from(rt.runtime_memory()), dir) {
+        Ok(Some(differences)) => differences,
+        Ok(None) => return,
+        Err(e) => {
+            println!("Check unsuccessful: {e:?}");
+            exit(10);
+        }
+    };
+    let name = settings.name.as_deref().unwrap_or("exercise");
+    if !differences.is_empty() {
+        send_report(
+            check_args,
+            instructions,
+            input,
+            RunResult::WrongResult,
+            rt.control_flow().steps,
+            None,
+        );
+        println!("Check unsuccessful, the result does not match the expected state of {name}:");
+        for difference in differences {
+            println!(
+                "{}: {} (expected {})",
+                difference.location, difference.right, difference.left
+            );
+        }
+        exit(3);
+    }
+    println!("Result matches the expected state of {name}");
+    // exercises without name can't be told apart in the progress file
+    let Some(name) = &settings.name else {
+        return;
+    };
+    let record = progress_file(global_args).and_then(|path| {
+        let mut progress = Progress::try_from_file(&path)?;
+        let first = progress.complete(name, input, rt.control_flow().steps);
+        progress.write_to_file(&path)?;
+        Ok(first)
+    });
+    match record {
+        Ok(true) => {
+            println!("Exercise {name} completed for the first time, see \"alpha_tui progress\"")
+        }
+        Ok(false) => (),
+        // the check itself was successful
+        Err(e) => println!("Warning: unable to record progress: {e:?}"),
+    }
+}
+
+/// Posts an anonymized summary of the run to `--report-url`, if it is set.
+///
+/// `error` is the runtime error that stopped the run or the error that occurred when the program was build.
+/// The check itself is not affected, if the summary can not be sent, a warning is printed.
+fn send_report(
+    check_args: &CheckArgs,
+    instructions: &[String],
+    input: &str,
+    result: RunResult,
+    steps: usize,
+    error: Option<&dyn Diagnostic>,
+) {
+    let CheckCommand::Run(CheckRunArgs {
+        report_url: Some(url),
+        ..
+    }) = &check_args.command
+    else {
+        return;
+    };
+    let exercise = exercise::read_exercise_settings(input)
+        .ok()
+        .flatten()
+        .and_then(|f| f.name);
+    let report = RunReport::new(instructions, exercise, result, steps, error);
+    match report.send(url) {
+        Ok(()) => println!("Summary of the run sent to {url}"),
+        Err(e) => println!("Warning: unable to send summary of the run: {e:?}"),
+    }
+}
+
+/// Applies the fixes that are suggested for the program and writes it back to `input`, the changed lines are printed.
+///
+/// Returns the fixed program.
+fn fix(check_args: &CheckArgs, instructions: Vec<String>, input: &str) -> Vec<String> {
+    let limiting_args = &check_args.check_load_args.instruction_limiting_args;
+    let fixes = autofix::suggest_fixes(
+        &instructions,
+        limiting_args.spec,
+        limiting_args.strict || limiting_args.spec.is_some(),
+    );
+    if fixes.is_empty() {
+        println!("No fixes found");
+        return instructions;
+    }
+    println!("Applying {} fix(es):", fixes.len());
+    for fix in &fixes {
+        println!("{}", fix.diff());
+    }
+    let fixed = autofix::apply_fixes(&instructions, &fixes);
+    match write_file_if_unchanged(&fixed, input, &instructions) {
+        Ok(true) => (),
+        Ok(false) => {
+            println!("File [{input}] was modified by another program while it was checked, the fixes were not written.\nRun the command again to fix the new content.");
+            exit(10);
+        }
+        Err(e) => {
+            println!("Unable to write file [{input}]: {e:?}");
+            exit(10);
+        }
+    }
+    fixed
+}
@@ -0,0 +1,103 @@
+use std::{process::exit, rc::Rc};
+
+use miette::{miette, Result};
+
+use crate::{
+    app::ui::{style::SyntaxHighlightingTheme, syntax_highlighting::SyntaxHighlighter},
+    cli::FormatArgs,
+    utils::{read_file, write_file_if_unchanged},
+};
+
+pub fn format(format_args: &FormatArgs) -> ! {
+    let path = &format_args.file;
+    let program = match read_file(path) {
+        Ok(program) => program,
+        Err(e) => {
+            println!("Unable to read file [{path}]: {e:?}");
+            exit(10);
+        }
+    };
+    let formatted = match format_program(&program) {
+        Ok(formatted) => formatted,
+        Err(e) => {
+            println!("Unable to format program, program did not compile.\nError: {e:?}");
+            exit(1);
+        }
+    };
+
+    if format_args.check {
+        for line in &formatted {
+            println!("{line}");
+        }
+        exit(i32::from(formatted != program));
+    }
+    if formatted != program {
+        match write_file_if_unchanged(&formatted, path, &program) {
+            Ok(true) => (),
+            Ok(false) => {
+                println!("File [{path}] was modified by another program while it was formatted, it was not overwritten.\nRun the command again to format the new content.");
+                exit(10);
+            }
+            Err(e) => {
+                println!("Unable to write file [{path}]: {e:?}");
+                exit(10);
+            }
+        }
+    }
+    exit(0);
+}
+
+/// Aligns labels, instructions and comments of the program in columns.
+///
+/// Lines that start with `#` (e.g. the program header) are kept unchanged.
+fn format_program(program: &[String]) -> Result<Vec<String>> {
+    let mut formatted = SyntaxHighlighter::new(&Rc::new(SyntaxHighlightingTheme::new_disabled()))
+        .input_to_lines(program, true)?
+        .into_iter()
+        .map(|f| f.to_string());
+    // lines that start with `#` are not included in the formatted lines
+    program
+        .iter()
+        .map(|line| {
+            if line.trim_start().starts_with('#') {
+                Ok(line.clone())
+            } else {
+                formatted
+                    .next()
+                    .ok_or_else(|| miette!("formatted program has less lines than the input"))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_program;
+
+    #[test]
+    fn test_format_program() {
+        let program = [
+            "# alpha_tui: max-steps=100",
+            "a := 5 // set a",
+            "loop: a := a - 1",
+            "",
+            "// full line comment",
+            "if a > 0 then goto loop",
+        ]
+        .map(String::from);
+        assert_eq!(
+            format_program(&program).unwrap(),
+            vec![
+                "# alpha_tui: max-steps=100",
+                "       α0 := 5                   // set a",
+                "loop:  α0 := α0 - 1",
+                "",
+                "// full line comment",
+                "       if α0 > 0 then goto loop",
+            ]
+        );
+        // formatting is idempotent
+        let formatted = format_program(&program).unwrap();
+        assert_eq!(format_program(&formatted).unwrap(), formatted);
+    }
+}
@@ -0,0 +1,62 @@
+use std::process::exit;
+
+use miette::{miette, Report};
+
+use crate::{
+    cli::{GlobalArgs, LintArgs},
+    runtime::builder::RuntimeBuilder,
+    utils::read_file,
+};
+
+pub fn lint(global_args: &GlobalArgs, lint_args: &LintArgs) -> ! {
+    let path = &lint_args.file;
+    let program = match read_file(path) {
+        Ok(program) => program,
+        Err(e) => {
+            println!("Unable to read file [{path}]: {e:?}");
+            exit(10);
+        }
+    };
+    let mut rb = match RuntimeBuilder::new(&program, path) {
+        Ok(rb) => rb,
+        Err(e) => {
+            println!(
+                "Unable to lint program, program did not compile.\nError: {:?}",
+                miette!(e)
+            );
+            exit(1);
+        }
+    };
+    if let Err(e) = rb.apply_global_cli_args(global_args) {
+        println!(
+            "Unable to lint program: {:?}",
+            miette!("memory config could not be loaded from file:\n{e}")
+        );
+        exit(10);
+    }
+    if let Err(e) = rb.apply_program_header(&program) {
+        println!("Unable to lint program: {e:?}");
+        exit(1);
+    }
+    let rt = match rb.build() {
+        Ok(rt) => rt,
+        Err(e) => {
+            println!("Unable to lint program, program did not compile.\nError: {e:?}");
+            exit(1);
+        }
+    };
+
+    let warnings = rt.lint();
+    if warnings.is_empty() {
+        println!("No warnings found");
+        exit(0);
+    }
+    for warning in &warnings {
+        println!(
+            "{:?}",
+            Report::new(warning.clone().into_diagnostic(&program, path))
+        );
+    }
+    println!("Lint found {} warning(s)", warnings.len());
+    exit(1);
+}
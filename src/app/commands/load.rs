@@ -1,17 +1,30 @@
-use std::rc::Rc;
+use std::{
+    io::{self, BufRead, Write},
+    rc::Rc,
+    time::Duration,
+};
 
 use miette::Result;
 
+#[cfg(unix)]
+use std::fs::File;
+
+#[cfg(unix)]
+use crate::app::{control::ShareLog, share::SharedSession};
 use crate::{
     app::{
         commands::load_instruction_history,
+        plain_ui::PlainUi,
+        remote_control,
+        repro::Repro,
+        session::{session_file_path, Session},
         ui::{style::SyntaxHighlightingTheme, syntax_highlighting::SyntaxHighlighter},
         App,
     },
     cli::{GlobalArgs, LoadArgs},
     instructions::instruction_config::InstructionConfig,
-    runtime::builder,
-    utils::write_file,
+    runtime::{builder, state_dump::StateDump, RuntimeMemory},
+    utils::{read_file, write_file, write_file_atomic, write_file_if_unchanged},
 };
 
 #[allow(clippy::match_wildcard_for_single_variants)]
@@ -26,15 +39,47 @@ pub fn load(
 
     // create runtime builder and apply cli args
     println!("Building instructions");
-    let mut rb = builder::RuntimeBuilder::new(&instructions, &input)?;
-    rb.apply_global_cli_args(global_args)?
-        .apply_check_load_args(&load_args.check_load_args)?
+    // in robust mode panics while the instructions are build are reported as error as well
+    let mut rb = if load_args.check_load_args.robust {
+        builder::RuntimeBuilder::new_robust(&instructions, &input)?
+    } else {
+        builder::RuntimeBuilder::new(&instructions, &input)?
+    };
+    rb.apply_global_cli_args(global_args)?;
+    // the program header could extend the memory that is set by the exercise settings
+    if !load_args.check_load_args.enforce_settings {
+        rb.apply_program_header(&instructions)?;
+    }
+    rb.apply_check_load_args(&load_args.check_load_args)?
         .apply_instruction_limiting_args(&load_args.check_load_args.instruction_limiting_args)?;
+    // the effective configuration is stored in repro files
+    let build_config = rb.config();
     // build runtime
     println!("Building runtime");
     let rt = rb.build()?;
+    // the seed is stored in repro files, so that the same random numbers are generated when they are replayed
+    let seed = rt.control_flow().random.seed();
+
+    let mut use_plain_ui = load_args.plain_ui;
+    // fall back to the linear interface, instead of printing escape sequences the terminal does not understand
+    if !use_plain_ui && load_args.share.is_none() {
+        if let Some(reason) = super::unsupported_terminal() {
+            println!(
+                "The tui can not be used in this terminal ({reason}), falling back to --plain-ui"
+            );
+            use_plain_ui = true;
+        }
+    }
+    if use_plain_ui {
+        // use linear interface instead of tui
+        let mut plain_ui = PlainUi::new(rt, &instructions, load_args.breakpoints.clone());
+        plain_ui.run(io::stdin().lock(), io::stdout())?;
+        return dump_state(load_args, plain_ui.finished_memory());
+    }
 
     let theme = Rc::new(super::load_theme(&load_args.load_playground_args)?);
+    let hooks = super::load_hooks()?;
+    let keymap = super::load_keymap()?;
 
     // format instructions pretty if cli flag is set
     let syntax_highlighting_theme = if load_args.load_playground_args.disable_syntax_highlighting {
@@ -42,55 +87,250 @@ pub fn load(
     } else {
         theme.syntax_highlighting_theme()
     };
+    // keep unformatted program, it is stored in repro files
+    let program = instructions.clone();
     let instructions = SyntaxHighlighter::new(&syntax_highlighting_theme)
         .input_to_lines(&instructions, !load_args.disable_alignment)?;
 
     if load_args.write_alignment {
         // write new formatting to file if enabled
         println!("Writing alignment to source file");
-        write_file(
-            &instructions.iter().map(|f| f.to_string()).collect(),
-            &input,
-        )?;
+        let aligned = instructions.iter().map(|f| f.to_string()).collect();
+        // the file might be open in an editor, changes made there are not overwritten without asking
+        if !write_file_if_unchanged(&aligned, &input, &program)? {
+            match ask_modified_source_action(&input, io::stdin().lock()) {
+                ModifiedSourceAction::Reload => {
+                    println!("Reloading {input}");
+                    return load(global_args, load_args, read_file(&input)?, input);
+                }
+                ModifiedSourceAction::Overwrite => write_file(&aligned, &input)?,
+                ModifiedSourceAction::Skip => println!("Alignment was not written"),
+            }
+        }
     }
 
     // check if allowed instructions are restricted
-    let allowed_instructions = match &load_args
-        .check_load_args
-        .instruction_limiting_args
-        .allowed_instructions_file
-    {
+    let instruction_limiting_args = &load_args.check_load_args.instruction_limiting_args;
+    let mut allowed_instructions = match &instruction_limiting_args.allowed_instructions_file {
         Some(path) => match InstructionConfig::try_from_file(path) {
             Ok(config) => Some(config),
             Err(e) => return Err(e),
         },
         None => None,
     };
+    // custom instructions have to follow the selected spec and grammar too
+    if let Some(spec) = instruction_limiting_args.spec {
+        allowed_instructions
+            .get_or_insert_with(InstructionConfig::default)
+            .spec = Some(spec);
+    }
+    if instruction_limiting_args.strict {
+        allowed_instructions
+            .get_or_insert_with(InstructionConfig::default)
+            .strict = true;
+    }
 
-    // tui
-    // setup terminal
-    println!("Ready to run, launching tui");
-    let mut terminal = super::setup_terminal()?;
+    // breakpoints are always restored, the rest of the debugging setup only if enabled
+    let stored_session = Session::try_from_file(&session_file_path(&input))?;
 
     // create app
     let mut app = App::from_runtime(
         rt,
-        input,
+        input.clone(),
         //&remove_special_commented_lines(instructions),
         &instructions,
         &load_args.breakpoints,
         instruction_history,
-        allowed_instructions,
+        allowed_instructions.clone(),
         load_args.custom_instruction_history_file.clone(),
         false,
         !load_args.load_playground_args.disable_syntax_highlighting,
         theme,
     );
+    if let Some(session) = &stored_session {
+        app.apply_session(&session.restored(load_args.breakpoints.as_ref(), load_args.session));
+    }
+    app.set_hooks(hooks);
+    app.set_keymap(keymap);
+    if load_args.load_playground_args.profile_ui {
+        app.enable_render_profile();
+        app.enable_line_profile();
+    }
+    if load_args.load_playground_args.show_key_events {
+        app.enable_key_event_log();
+    }
+    app.set_tick_rate(Duration::from_millis(load_args.tick_rate));
+    app.set_auto_run_speed(load_args.auto_run_speed);
+    if load_args.present {
+        app.enable_presentation(Duration::from_millis(load_args.present_delay));
+    }
+    // breakpoints of the session have already been applied
+    let session_breakpoints = app.session();
+    let repro = Repro::new(
+        &input,
+        &program,
+        build_config,
+        allowed_instructions,
+        (
+            session_breakpoints.breakpoints,
+            session_breakpoints.disabled_breakpoints,
+        ),
+        seed,
+    );
+    if let Some(path) = &load_args.record_repro {
+        app.record_repro(path, repro.clone());
+    }
+    if let Some(addr) = &load_args.control_socket {
+        let (addr, requests) = remote_control::start(addr, load_args.allow_remote_control)?;
+        app.set_remote_control(requests);
+        println!("Remote control listening at {addr}");
+    }
+    let shared_session = match &load_args.share {
+        Some(path) => Some(share_session(
+            &mut app,
+            path,
+            load_args.share_log.as_ref(),
+            repro,
+        )?),
+        None => None,
+    };
+
+    // tui
+    // setup terminal
+    println!("Ready to run, launching tui");
+    let mut terminal = super::setup_terminal()?;
+
     let res = app.run(&mut terminal);
+    // viewers are detached
+    drop(shared_session);
 
     // restore terminal
     super::restore_terminal(&mut terminal)?;
 
+    // breakpoints are saved automatically, so that they are restored when the program is loaded again
+    if let Some(session) =
+        Session::to_store(app.session(), stored_session.as_ref(), load_args.session)
+    {
+        println!("Writing session to {}", session_file_path(&input));
+        session.write_to_file(&session_file_path(&input))?;
+    }
+
+    if let Some(path) = app.write_repro()? {
+        println!("Repro file written to {path}");
+    }
+
+    if let (Some(path), Some(csv)) = (&load_args.profile_export, app.line_profile_csv()) {
+        write_file_atomic(path, csv.as_bytes())?;
+        println!("Line profile written to {path}");
+    }
+
+    dump_state(load_args, app.finished_memory())?;
+
     res?;
     Ok(())
 }
+
+/// What is done when the source file was modified by another program, before the alignment is written.
+#[derive(Debug, PartialEq)]
+enum ModifiedSourceAction {
+    /// Load the modified file again and align its content, this keeps the changes.
+    Reload,
+    /// Write the alignment of the program that was loaded, the changes are lost.
+    Overwrite,
+    /// Don't write the alignment.
+    Skip,
+}
+
+/// Asks what should be done, because the source file at `path` was modified since it was read.
+///
+/// Returns [`ModifiedSourceAction::Skip`] if no answer can be read from `input`, e.g. when it is not a terminal.
+fn ask_modified_source_action(path: &str, mut input: impl BufRead) -> ModifiedSourceAction {
+    println!(
+        "The file [{path}] was modified by another program since it was read, e.g. by an editor."
+    );
+    loop {
+        print!("[r]eload the file and align the new content, [o]verwrite the changes or [s]kip writing the alignment? ");
+        _ = io::stdout().flush();
+        let mut answer = String::new();
+        if !matches!(input.read_line(&mut answer), Ok(read) if read > 0) {
+            println!();
+            return ModifiedSourceAction::Skip;
+        }
+        match answer.trim() {
+            "r" => return ModifiedSourceAction::Reload,
+            "o" => return ModifiedSourceAction::Overwrite,
+            "s" => return ModifiedSourceAction::Skip,
+            _ => (),
+        }
+    }
+}
+
+/// Writes `memory` to the file set with `--dump-state`, if any.
+fn dump_state(load_args: &LoadArgs, memory: Option<&RuntimeMemory>) -> Result<()> {
+    let Some(path) = &load_args.check_load_args.dump_state else {
+        return Ok(());
+    };
+    match memory {
+        Some(memory) => {
+            StateDump::from(memory).write_to_file(path, load_args.check_load_args.format)?;
+            println!("State written to {path}");
+        }
+        None => println!("The program did not finish, state was not written to {path}"),
+    }
+    Ok(())
+}
+
+/// Shares the session at the socket `path`, returns the shared session that has to be kept until the app is closed.
+///
+/// All actions are logged to `log_file`, or to `path` with the extension `.log` if not set.
+#[cfg(unix)]
+fn share_session(
+    app: &mut App,
+    path: &str,
+    log_file: Option<&String>,
+    repro: Repro,
+) -> Result<SharedSession> {
+    let log_file = log_file.cloned().unwrap_or_else(|| format!("{path}.log"));
+    let log = File::create(&log_file)
+        .map_err(|e| miette::miette!("Unable to create log file {log_file}: {e}"))?;
+    let mut shared_session = SharedSession::start(path, repro)?;
+    let remote_actions = shared_session
+        .take_remote_actions()
+        .expect("remote actions should not have been taken");
+    app.share_session(
+        shared_session.sender(),
+        remote_actions,
+        ShareLog::new(Box::new(log)),
+    );
+    println!("Sharing session at {path}, actions are logged to {log_file}");
+    Ok(shared_session)
+}
+
+#[cfg(not(unix))]
+fn share_session(
+    _app: &mut App,
+    _path: &str,
+    _log_file: Option<&String>,
+    _repro: Repro,
+) -> Result<()> {
+    Err(miette::miette!(
+        "Sharing a session is only supported on unix systems"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{ask_modified_source_action, ModifiedSourceAction};
+
+    #[test]
+    fn test_ask_modified_source_action() {
+        let ask = |answer: &str| ask_modified_source_action("sum.alpha", Cursor::new(answer));
+        assert_eq!(ask("r\n"), ModifiedSourceAction::Reload);
+        assert_eq!(ask("o\n"), ModifiedSourceAction::Overwrite);
+        // invalid answers are asked again
+        assert_eq!(ask("x\n\ns\n"), ModifiedSourceAction::Skip);
+        assert_eq!(ask(""), ModifiedSourceAction::Skip);
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     fs::File,
-    io::{self, Stdout},
+    io::{self, IsTerminal, Stdout},
     path::Path,
 };
 
@@ -18,14 +18,32 @@ use crate::{
     utils::{self, remove_comment},
 };
 
-use super::ui::style::Theme;
+use super::{hooks::Hooks, keybindings::Keymap, ui::style::Theme};
 
+/// Attaching to a shared session
+pub mod attach;
 /// Check command
 pub mod check;
+/// Format command
+pub mod format;
+/// Lint command
+pub mod lint;
 /// Load command
 pub mod load;
 /// Playground command
 pub mod playground;
+/// Profile command
+pub mod profile;
+/// Progress command
+pub mod progress;
+/// Repro command
+pub mod repro;
+/// Session command
+pub mod session;
+/// State command
+pub mod state;
+/// Test command
+pub mod test;
 
 fn load_instruction_history(
     custom_instruction_history_file: &Option<String>,
@@ -77,8 +95,37 @@ fn load_instruction_history(
     Ok(instruction_history)
 }
 
+/// Returns the reason why the tui can not be used in the current terminal, `None` if the tui can be used.
+///
+/// The tui needs an interactive terminal that supports the alternate screen, this is not the case when the input or
+/// output is redirected or when `TERM` is set to `dumb`, as it is done in CI environments and in some terminals of IDEs.
+fn unsupported_terminal() -> Option<&'static str> {
+    unsupported_terminal_reason(
+        std::env::var("TERM").ok().as_deref(),
+        io::stdin().is_terminal() && io::stdout().is_terminal(),
+    )
+}
+
+fn unsupported_terminal_reason(term: Option<&str>, is_terminal: bool) -> Option<&'static str> {
+    if !is_terminal {
+        Some("input or output is not a terminal")
+    } else if term == Some("dumb") {
+        Some("TERM is set to dumb")
+    } else {
+        None
+    }
+}
+
 /// Setup the terminal and returns it.
+///
+/// Returns an error if the terminal does not support the tui, see [`unsupported_terminal`].
 fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>> {
+    if let Some(reason) = unsupported_terminal() {
+        return Err(miette!(
+            help = "Use \"load --plain-ui\" to debug the program with a line oriented interface or \"check run\" to run it",
+            "The tui can not be used in this terminal: {reason}"
+        ));
+    }
     terminal::enable_raw_mode().into_diagnostic()?;
     let mut stdout = io::stdout();
     crossterm::execute!(stdout, EnterAlternateScreen, EnableMouseCapture).into_diagnostic()?;
@@ -121,11 +168,13 @@ fn load_theme(load_playground_args: &LoadPlaygroundArgs) -> miette::Result<Theme
     // check if theme file exists
     if let Some(user_dirs) = UserDirs::new() {
         if let Some(base_dir) = user_dirs.home_dir().to_str() {
-            let file = format!("{base_dir}/.config/alpha_tui/theme.json");
-            let path = Path::new(&file);
-            // check if file exists
-            if path.exists() && path.is_file() {
-                return load_theme_file(path.to_str().expect(" path should be valid unicode"));
+            for name in ["theme.json", "theme.toml"] {
+                let file = format!("{base_dir}/.config/alpha_tui/{name}");
+                let path = Path::new(&file);
+                // check if file exists
+                if path.exists() && path.is_file() {
+                    return load_theme_file(path.to_str().expect(" path should be valid unicode"));
+                }
             }
         }
     }
@@ -135,12 +184,122 @@ fn load_theme(load_playground_args: &LoadPlaygroundArgs) -> miette::Result<Theme
 
 /// Loads the content of the file located at `path` and tries to parse it into a theme.
 ///
+/// Files with the extension `toml` are parsed as toml, all other files as json.
+///
 /// Returns error if file does not exist or content can not be parsed into a theme.
 fn load_theme_file(path: &str) -> miette::Result<Theme> {
-    match serde_json::from_str(&utils::read_file(path)?.join("\n")) {
+    let content = utils::read_file(path)?.join("\n");
+    if Path::new(path).extension().is_some_and(|f| f == "toml") {
+        return toml::from_str(&content)
+            .map_err(|e| miette::miette!("toml parse error while loading theme file {path}: {e}"));
+    }
+    match serde_json::from_str(&content) {
         Ok(theme) => Ok(theme),
         Err(e) => Err(miette::miette!(
             "json parse error while loading theme file {path}: {e}"
         )),
     }
 }
+
+/// Loads the hooks from the file `hooks.json` in the config directory (`~/.config/alpha_tui` in linux
+/// or `%APPDATA%/alpha_tui` in windows).
+///
+/// If the file does not exist, no hooks are set.
+///
+/// Returns error if the file exists but can not be parsed.
+fn load_hooks() -> miette::Result<Hooks> {
+    if let Some(user_dirs) = UserDirs::new() {
+        if let Some(base_dir) = user_dirs.home_dir().to_str() {
+            let file = format!("{base_dir}/.config/alpha_tui/hooks.json");
+            let path = Path::new(&file);
+            if path.exists() && path.is_file() {
+                return match serde_json::from_str(&utils::read_file(&file)?.join("\n")) {
+                    Ok(hooks) => Ok(hooks),
+                    Err(e) => Err(miette::miette!(
+                        "json parse error while loading hooks file {file}: {e}"
+                    )),
+                };
+            }
+        }
+    }
+    Ok(Hooks::default())
+}
+
+/// Loads the remapped keys from the file `keybindings.toml` in the config directory (`~/.config/alpha_tui` in linux
+/// or `%APPDATA%/alpha_tui` in windows).
+///
+/// If the file does not exist, the default keys are used.
+///
+/// Returns error if the file exists but can not be parsed.
+fn load_keymap() -> miette::Result<Keymap> {
+    if let Some(user_dirs) = UserDirs::new() {
+        if let Some(base_dir) = user_dirs.home_dir().to_str() {
+            let file = format!("{base_dir}/.config/alpha_tui/keybindings.toml");
+            let path = Path::new(&file);
+            if path.exists() && path.is_file() {
+                return Keymap::parse(&utils::read_file(&file)?.join("\n")).map_err(|e| {
+                    miette::miette!("error while loading keybindings file {file}: {e}")
+                });
+            }
+        }
+    }
+    Ok(Keymap::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::ui::style::{BuildInTheme, Theme};
+
+    use super::{load_theme_file, unsupported_terminal_reason};
+
+    #[test]
+    fn test_load_theme_file() {
+        let dir = std::env::temp_dir().join(format!("alpha_tui_theme_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let toml = dir.join("theme.toml").to_str().unwrap().to_string();
+        std::fs::write(
+            &toml,
+            "background = \"White\"\n\n[sh_theme]\ncomment = \"Gray\"\n",
+        )
+        .unwrap();
+        let json = dir.join("theme.json").to_str().unwrap().to_string();
+        std::fs::write(
+            &json,
+            r#"{"background": "White", "sh_theme": {"comment": "Gray"}}"#,
+        )
+        .unwrap();
+        let theme = load_theme_file(&toml).unwrap();
+        assert_eq!(theme, load_theme_file(&json).unwrap());
+        assert_ne!(theme, Theme::default());
+        std::fs::write(&toml, "background = 5").unwrap();
+        assert!(load_theme_file(&toml).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_build_in_themes() {
+        // the light and high contrast themes are loaded from the files in `themes`
+        assert_ne!(Theme::from(&BuildInTheme::Light), Theme::default());
+        assert_ne!(
+            Theme::from(&BuildInTheme::HighContrast),
+            Theme::from(&BuildInTheme::Light)
+        );
+    }
+
+    #[test]
+    fn test_unsupported_terminal_reason() {
+        assert_eq!(
+            unsupported_terminal_reason(Some("xterm-256color"), true),
+            None
+        );
+        assert_eq!(unsupported_terminal_reason(None, true), None);
+        assert_eq!(
+            unsupported_terminal_reason(Some("dumb"), true),
+            Some("TERM is set to dumb")
+        );
+        assert_eq!(
+            unsupported_terminal_reason(Some("xterm"), false),
+            Some("input or output is not a terminal")
+        );
+    }
+}
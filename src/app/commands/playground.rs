@@ -19,8 +19,10 @@ pub fn playground(global_args: &GlobalArgs, playground_args: &PlaygroundArgs) ->
 
     let dummy_instructions = Vec::new();
     let mut rb = RuntimeBuilder::new(&dummy_instructions, "playground")?;
-    rb.apply_global_cli_args(global_args)?;
+    rb.apply_global_cli_args(global_args)?
+        .apply_memory_args(&playground_args.memory_args);
     let rt = rb.build()?;
+    let keymap = super::load_keymap()?;
 
     // setup terminal
     println!("Ready to run, launching tui");
@@ -40,6 +42,14 @@ pub fn playground(global_args: &GlobalArgs, playground_args: &PlaygroundArgs) ->
             .disable_syntax_highlighting,
         Rc::new(super::load_theme(&playground_args.load_playground_args)?),
     );
+    if playground_args.load_playground_args.profile_ui {
+        app.enable_render_profile();
+    }
+    if playground_args.load_playground_args.show_key_events {
+        app.enable_key_event_log();
+    }
+    app.set_playground_file(&playground_args.save_file);
+    app.set_keymap(keymap);
     let res = app.run(&mut terminal);
 
     // restore terminal
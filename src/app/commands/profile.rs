@@ -0,0 +1,127 @@
+use std::process::exit;
+
+use miette::miette;
+
+use crate::{
+    cli::{GlobalArgs, ProfileArgs},
+    utils::read_file,
+};
+
+use super::test::build_runtime;
+
+pub fn profile(global_args: &GlobalArgs, profile_args: &ProfileArgs) -> ! {
+    let path = &profile_args.file;
+    let program = match read_file(path) {
+        Ok(program) => program,
+        Err(e) => {
+            println!("Unable to read file [{path}]: {e:?}");
+            exit(10);
+        }
+    };
+    let mut rt = match build_runtime(global_args, &program, path) {
+        Ok(rt) => rt,
+        Err(e) => {
+            println!("Unable to profile program, program did not compile.\nError: {e:?}");
+            exit(1);
+        }
+    };
+    let result = rt.run();
+    // the lines are printed for runs that ended with a runtime error as well, as they can help to find the cause
+    for line in summary(
+        &program,
+        &rt.control_flow().source_lines,
+        rt.line_hits(),
+        rt.control_flow().steps,
+        profile_args.top,
+    ) {
+        println!("{line}");
+    }
+    if let Err(e) = result {
+        println!(
+            "Runtime error while running program.\nError: {:?}",
+            miette!(e)
+        );
+        exit(2);
+    }
+    exit(0);
+}
+
+/// Returns the lines of the profile summary, the total number of steps followed by a table of the `top` lines that
+/// were run most often.
+///
+/// `source_lines` contains the line of each instruction in `program`, the lines that start with `#` are not build
+/// into instructions.
+fn summary(
+    program: &[String],
+    source_lines: &[usize],
+    line_hits: &[u64],
+    steps: usize,
+    top: usize,
+) -> Vec<String> {
+    let mut lines = vec![format!("Total steps: {steps}"), String::new()];
+    let mut hot_lines = line_hits
+        .iter()
+        .enumerate()
+        .filter(|(_, hits)| **hits > 0)
+        .collect::<Vec<_>>();
+    // lines with the same number of hits stay in the order of the program
+    hot_lines.sort_by(|a, b| b.1.cmp(a.1));
+    hot_lines.truncate(top);
+    if hot_lines.is_empty() {
+        lines.push("No line was run".to_string());
+        return lines;
+    }
+    lines.push(format!(
+        "{:>5}  {:>8}  {:>6}  Instruction",
+        "Line", "Hits", "%"
+    ));
+    for (idx, hits) in hot_lines {
+        #[allow(clippy::cast_precision_loss)]
+        let percent = *hits as f64 / steps.max(1) as f64 * 100.0;
+        let line = source_lines.get(idx).copied().unwrap_or(idx + 1);
+        lines.push(format!(
+            "{line:>5}  {hits:>8}  {percent:>6.2}  {}",
+            program.get(line - 1).map_or("", |f| f.trim())
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::summary;
+
+    #[test]
+    fn test_profile_summary() {
+        let program = [
+            "a := 3".to_string(),
+            "loop: a := a - 1".to_string(),
+            "  if a > 0 then goto loop".to_string(),
+        ];
+        let lines = summary(&program, &[1, 2, 3], &[1, 3, 3], 7, 2);
+        assert_eq!(
+            lines,
+            [
+                "Total steps: 7",
+                "",
+                " Line      Hits       %  Instruction",
+                "    2         3   42.86  loop: a := a - 1",
+                "    3         3   42.86  if a > 0 then goto loop",
+            ]
+        );
+        assert_eq!(
+            summary(&program, &[1, 2, 3], &[0, 0, 0], 0, 10)[2],
+            "No line was run"
+        );
+        // lines that start with # are not instructions, the lines of the file are printed
+        let program = [
+            "# alpha_tui: accumulators=1".to_string(),
+            "a := 3".to_string(),
+            "a := a + 1".to_string(),
+        ];
+        assert_eq!(
+            summary(&program, &[2, 3], &[1, 2], 3, 1)[3],
+            "    3         2   66.67  a := a + 1"
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use miette::{miette, IntoDiagnostic, Result};
+
+use crate::{
+    cli::{GlobalArgs, ProgressArgs},
+    progress::Progress,
+};
+
+pub fn progress(global_args: &GlobalArgs, progress_args: &ProgressArgs) -> Result<()> {
+    let path = progress_file(global_args)?;
+    if progress_args.reset {
+        if std::path::Path::new(&path).exists() {
+            std::fs::remove_file(&path).into_diagnostic()?;
+        }
+        println!("Progress reset");
+        return Ok(());
+    }
+    for line in Progress::try_from_file(&path)?.overview() {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Returns the path of the progress file, see `--progress-file`.
+pub fn progress_file(global_args: &GlobalArgs) -> Result<String> {
+    match &global_args.progress_file {
+        Some(path) => Ok(path.clone()),
+        None => Progress::default_path().ok_or_else(|| {
+            miette!("Unable to locate the progress file, the home directory is unknown")
+        }),
+    }
+}
@@ -0,0 +1,213 @@
+use std::{io::Stdout, rc::Rc};
+
+use crossterm::event::KeyEvent;
+use miette::Result;
+use ratatui::{backend::CrosstermBackend, Terminal};
+
+use crate::{
+    app::{
+        repro::Repro,
+        session::{PanelSession, Session},
+        ui::{
+            style::{SharedTheme, Theme},
+            syntax_highlighting::SyntaxHighlighter,
+        },
+        App,
+    },
+    cli::{GlobalArgs, ReproArgs},
+    runtime::builder::RuntimeBuilder,
+};
+
+pub fn repro(global_args: &GlobalArgs, repro_args: &ReproArgs) -> Result<()> {
+    let repro = Repro::try_from_file(&repro_args.file)?;
+    if repro.version != env!("CARGO_PKG_VERSION") {
+        println!(
+            "Warning: repro file was recorded with version {}, current version is {}, the session might not be reproduced exactly!",
+            repro.version,
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+    let keys = repro.keys()?;
+    let mut app = app_from_repro(global_args, &repro, Rc::new(Theme::default()))?;
+
+    println!("Replaying {} actions, launching tui", keys.len());
+    let mut terminal = super::setup_terminal()?;
+
+    let res = replay_and_run(&mut app, &mut terminal, keys);
+
+    // restore terminal
+    super::restore_terminal(&mut terminal)?;
+
+    res
+}
+
+/// Builds the runtime and the app the same way as they were built when the repro was recorded.
+///
+/// The seed of the repro replaces the seed of `global_args`, so that `rand` returns the same numbers. The
+/// configuration stored in the repro is used instead of `global_args`, only repro files of older versions, that don't
+/// contain the configuration, are built with `global_args` and the program header. The actions of the repro are not
+/// performed.
+pub fn app_from_repro(global_args: &GlobalArgs, repro: &Repro, theme: SharedTheme) -> Result<App> {
+    println!("Building instructions");
+    let robust = repro
+        .config
+        .as_ref()
+        .and_then(|config| config.runtime_settings.as_ref())
+        .is_some_and(|settings| settings.robust);
+    let mut rb = if robust {
+        RuntimeBuilder::new_robust(&repro.program, &repro.file_name)?
+    } else {
+        RuntimeBuilder::new(&repro.program, &repro.file_name)?
+    };
+    match &repro.config {
+        Some(config) => {
+            let mut config = config.clone();
+            if let Some(seed) = repro.seed {
+                config
+                    .runtime_settings
+                    .get_or_insert_with(Default::default)
+                    .seed = Some(seed);
+            }
+            rb.set_config(config);
+        }
+        None => {
+            if let Some(memory_config) = &repro.memory_config {
+                rb.set_memory_config(memory_config.clone());
+            }
+            let global_args = GlobalArgs {
+                seed: repro.seed.or(global_args.seed),
+                ..global_args.clone()
+            };
+            rb.apply_global_cli_args(&global_args)?
+                .apply_program_header(&repro.program)?;
+        }
+    }
+    println!("Building runtime");
+    let rt = rb.build()?;
+
+    let instructions = SyntaxHighlighter::new(&theme.syntax_highlighting_theme())
+        .input_to_lines(&repro.program, true)?;
+
+    let mut app = App::from_runtime(
+        rt,
+        repro.file_name.clone(),
+        &instructions,
+        &repro.breakpoints,
+        None,
+        repro.custom_instruction_config.clone(),
+        None,
+        false,
+        true,
+        theme,
+    );
+    app.apply_session(&Session {
+        breakpoints: repro.breakpoints.clone().unwrap_or_default(),
+        disabled_breakpoints: repro.disabled_breakpoints.clone(),
+        panels: PanelSession::default(),
+    });
+    Ok(app)
+}
+
+/// Performs all actions in the app and continues to run the app normally afterwards.
+///
+/// Returns early, if an action closes the app.
+fn replay_and_run(
+    app: &mut App,
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    keys: Vec<KeyEvent>,
+) -> Result<()> {
+    for key in keys {
+        if app.handle_key_event(key)? {
+            return Ok(());
+        }
+    }
+    app.run(terminal)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::{
+        app::{repro::Repro, ui::style::Theme},
+        cli::GlobalArgs,
+        instructions::{instruction_config::InstructionConfig, spec::Spec},
+        runtime::builder::{BuildConfig, RuntimeBuilder},
+    };
+
+    use super::app_from_repro;
+
+    #[test]
+    fn test_app_from_repro_seed() {
+        let program = ["a := rand(1, 1000000)".to_string()];
+        let mut repro = Repro::new(
+            "test",
+            &program,
+            BuildConfig::default(),
+            None,
+            (Vec::new(), Vec::new()),
+            42,
+        );
+        let global_args = GlobalArgs {
+            seed: Some(7),
+            ..Default::default()
+        };
+        // the seed of the repro generates the same numbers as in the recorded session
+        let app = app_from_repro(&global_args, &repro, Rc::new(Theme::default())).unwrap();
+        assert_eq!(app.runtime.control_flow().random.seed(), 42);
+        // repro files of older versions don't contain a seed
+        repro.seed = None;
+        repro.config = None;
+        let app = app_from_repro(&global_args, &repro, Rc::new(Theme::default())).unwrap();
+        assert_eq!(app.runtime.control_flow().random.seed(), 7);
+    }
+
+    #[test]
+    fn test_repro_round_trip() {
+        let program = [
+            "a := 5".to_string(),
+            "p(h1) := a".to_string(),
+            "a := a + 1".to_string(),
+        ];
+        let mut rb = RuntimeBuilder::new(&program, "test").unwrap();
+        let global_args = GlobalArgs {
+            seed: Some(3),
+            ..Default::default()
+        };
+        rb.apply_global_cli_args(&global_args).unwrap();
+        let mut config = rb.config();
+        let settings = config.runtime_settings.as_mut().unwrap();
+        settings.max_steps = Some(2);
+        settings.strict_calls = true;
+        config.instruction_config.strict = true;
+        let custom_instruction_config = InstructionConfig {
+            spec: Some(Spec::V2022),
+            ..Default::default()
+        };
+        let repro = Repro::new(
+            "test",
+            &program,
+            config.clone(),
+            Some(custom_instruction_config.clone()),
+            (vec![1], vec![2]),
+            3,
+        );
+        let path = std::env::temp_dir().join(format!("alpha_tui_repro_{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        repro.write_to_file(path).unwrap();
+        let read = Repro::try_from_file(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(read, repro);
+
+        // the stored configuration is used instead of the global args
+        let app = app_from_repro(&GlobalArgs::default(), &read, Rc::new(Theme::default())).unwrap();
+        config.runtime_settings.as_mut().unwrap().seed = Some(3);
+        assert_eq!(
+            app.runtime.settings(),
+            config.runtime_settings.as_ref().unwrap()
+        );
+        assert_eq!(app.session().breakpoints, vec![1]);
+        assert_eq!(app.session().disabled_breakpoints, vec![2]);
+        assert_eq!(app.instruction_config, Some(custom_instruction_config));
+    }
+}
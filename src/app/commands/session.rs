@@ -0,0 +1,89 @@
+use std::process::exit;
+
+use crate::{
+    app::session::{session_file_path, Session, SessionSetup},
+    cli::{SessionArgs, SessionCommand, SessionExportArgs, SessionImportArgs},
+    utils::read_file,
+};
+
+pub fn session(session_args: &SessionArgs) -> ! {
+    match &session_args.command {
+        SessionCommand::Export(export_args) => export(export_args),
+        SessionCommand::Import(import_args) => import(import_args),
+    }
+}
+
+fn export(export_args: &SessionExportArgs) -> ! {
+    let program = read_program(&export_args.file);
+    let path = session_file_path(&export_args.file);
+    let session = match Session::try_from_file(&path) {
+        Ok(Some(session)) => session,
+        Ok(None) => {
+            println!(
+                "No session stored for {}, use \"load --session\" to store the session",
+                export_args.file
+            );
+            exit(10);
+        }
+        Err(e) => {
+            println!("{e:?}");
+            exit(10);
+        }
+    };
+    let setup = SessionSetup::from_session(&session, &program);
+    if let Err(e) = setup.write_to_file(&export_args.output) {
+        println!("Unable to export session: {e:?}");
+        exit(10);
+    }
+    println!(
+        "Session with {} breakpoint(s) exported to {}",
+        setup.breakpoints.len(),
+        export_args.output
+    );
+    exit(0);
+}
+
+fn import(import_args: &SessionImportArgs) -> ! {
+    let program = read_program(&import_args.file);
+    let setup = match SessionSetup::try_from_file(&import_args.setup) {
+        Ok(setup) => setup,
+        Err(e) => {
+            println!("{e:?}");
+            exit(10);
+        }
+    };
+    let path = session_file_path(&import_args.file);
+    let mut session = match Session::try_from_file(&path) {
+        Ok(session) => session.unwrap_or_default(),
+        Err(e) => {
+            println!("{e:?}");
+            exit(10);
+        }
+    };
+    let missing = setup.apply(&mut session, &program);
+    for breakpoint in &missing {
+        println!(
+            "Warning: breakpoint in line {} skipped, the program does not contain the instruction '{}'",
+            breakpoint.line, breakpoint.instruction
+        );
+    }
+    if let Err(e) = session.write_to_file(&path) {
+        println!("Unable to import session: {e:?}");
+        exit(10);
+    }
+    println!(
+        "Session with {} breakpoint(s) imported to {path}",
+        setup.breakpoints.len() - missing.len()
+    );
+    exit(i32::from(!missing.is_empty()));
+}
+
+fn read_program(path: &str) -> Vec<String> {
+    match read_file(path) {
+        Ok(program) => program,
+        Err(e) => {
+            println!("Unable to read file [{path}]: {e:?}");
+            exit(10);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::process::exit;
+
+use crate::{
+    cli::{StateArgs, StateCommand},
+    runtime::state_dump::StateDump,
+};
+
+pub fn state(state_args: &StateArgs) -> ! {
+    match &state_args.command {
+        StateCommand::Diff(diff_args) => {
+            let read = |path: &str| match StateDump::try_from_file(path) {
+                Ok(state) => state,
+                Err(e) => {
+                    println!("{e:?}");
+                    exit(10);
+                }
+            };
+            let differences = read(&diff_args.left).diff(&read(&diff_args.right));
+            if diff_args.json {
+                match serde_json::to_string_pretty(&differences) {
+                    Ok(json) => println!("{json}"),
+                    Err(e) => {
+                        println!("Unable to serialize differences: {e}");
+                        exit(10);
+                    }
+                }
+            } else if differences.is_empty() {
+                println!("States are equal");
+            } else {
+                for difference in &differences {
+                    println!(
+                        "{}: {} -> {}",
+                        difference.location,
+                        format_value(&difference.left),
+                        format_value(&difference.right)
+                    );
+                }
+            }
+            exit(i32::from(!differences.is_empty()));
+        }
+    }
+}
+
+/// Formats values like the timeline does, values that are not set are printed as `None`.
+fn format_value(value: &serde_json::Value) -> String {
+    if value.is_null() {
+        "None".to_string()
+    } else {
+        value.to_string()
+    }
+}
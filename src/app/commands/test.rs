@@ -0,0 +1,188 @@
+use std::process::exit;
+
+use miette::{miette, Result};
+
+use crate::{
+    cli::{GlobalArgs, TestArgs, TestCasesArgs, TestCommand, TestRecordArgs, TestRunArgs},
+    runtime::{
+        builder::RuntimeBuilder,
+        state_dump::{StateDifference, StateDump},
+        test_case::{default_test_case_path, relative_program_path, SpecCase, TestCase, TestSpec},
+        Runtime, RuntimeMemory,
+    },
+    utils::read_file,
+};
+
+pub fn test(global_args: &GlobalArgs, test_args: &TestArgs) -> ! {
+    match &test_args.command {
+        TestCommand::Record(record_args) => record(global_args, record_args),
+        TestCommand::Run(run_args) => run(global_args, run_args),
+        TestCommand::Cases(cases_args) => cases(global_args, cases_args),
+    }
+}
+
+fn record(global_args: &GlobalArgs, record_args: &TestRecordArgs) -> ! {
+    // the program is read from the project manifest, if it is not provided
+    let path = record_args.file.as_ref().unwrap();
+    let program = match read_file(path) {
+        Ok(program) => program,
+        Err(e) => {
+            println!("Unable to read file [{path}]: {e:?}");
+            exit(10);
+        }
+    };
+    let mut rt = match build_runtime(global_args, &program, path) {
+        Ok(rt) => rt,
+        Err(e) => {
+            println!("Unable to record test case, program did not compile.\nError: {e:?}");
+            exit(1);
+        }
+    };
+    let initial_state = StateDump::from(rt.runtime_memory());
+    if let Err(e) = rt.run() {
+        println!(
+            "Unable to record test case, runtime error while running program.\nError: {:?}",
+            miette!(e)
+        );
+        exit(2);
+    }
+    let output = record_args
+        .output
+        .clone()
+        .unwrap_or_else(|| default_test_case_path(path));
+    let test_case = relative_program_path(&output, path).map(|program| TestCase {
+        program,
+        initial_state,
+        expected_state: StateDump::from(rt.runtime_memory()),
+        steps: rt.control_flow().steps,
+    });
+    if let Err(e) = test_case.and_then(|f| f.write_to_file(&output)) {
+        println!("Unable to record test case: {e:?}");
+        exit(10);
+    }
+    println!("Test case written to {output}");
+    exit(0);
+}
+
+fn run(global_args: &GlobalArgs, run_args: &TestRunArgs) -> ! {
+    let mut passed = 0;
+    for path in &run_args.files {
+        let test_case = match TestCase::try_from_file(path) {
+            Ok(test_case) => test_case,
+            Err(e) => {
+                println!("{e:?}");
+                exit(10);
+            }
+        };
+        if print_result(path, run_test_case(global_args, &test_case, path)) {
+            passed += 1;
+        }
+    }
+    println!("{passed} of {} test case(s) passed", run_args.files.len());
+    exit(i32::from(passed != run_args.files.len()));
+}
+
+fn cases(global_args: &GlobalArgs, cases_args: &TestCasesArgs) -> ! {
+    // the program and the test cases are read from the project manifest, if they are not provided
+    let path = cases_args.file.as_ref().unwrap();
+    let program = match read_file(path) {
+        Ok(program) => program,
+        Err(e) => {
+            println!("Unable to read file [{path}]: {e:?}");
+            exit(10);
+        }
+    };
+    let spec = match TestSpec::try_from_file(cases_args.cases.as_ref().unwrap()) {
+        Ok(spec) => spec,
+        Err(e) => {
+            println!("{e:?}");
+            exit(10);
+        }
+    };
+    // the program is built once before the cases are run, so that a compilation error is not reported for each case
+    if let Err(e) = build_runtime(global_args, &program, path) {
+        println!("Unable to run test cases, program did not compile.\nError: {e:?}");
+        exit(1);
+    }
+    let mut passed = 0;
+    for (idx, case) in spec.cases.iter().enumerate() {
+        let name = match &case.name {
+            Some(name) => format!("case {} ({name})", idx + 1),
+            None => format!("case {}", idx + 1),
+        };
+        if print_result(&name, run_spec_case(global_args, &program, path, case)) {
+            passed += 1;
+        }
+    }
+    println!("{passed} of {} test case(s) passed", spec.cases.len());
+    exit(i32::from(passed != spec.cases.len()));
+}
+
+/// Prints the result of the test case `name`, returns true if the test case passed.
+fn print_result(name: &str, result: Result<Vec<StateDifference>>) -> bool {
+    match result {
+        Ok(differences) if differences.is_empty() => {
+            println!("{name}: passed");
+            return true;
+        }
+        Ok(differences) => {
+            println!("{name}: failed, the result does not match the expected state:");
+            for difference in differences {
+                println!(
+                    "  {}: {} (expected {})",
+                    difference.location, difference.right, difference.left
+                );
+            }
+        }
+        Err(e) => println!("{name}: failed\n{e:?}"),
+    }
+    false
+}
+
+/// Runs the program of the test case, starting with the initial state, and returns the differences of the final
+/// state to the expected state.
+fn run_test_case(
+    global_args: &GlobalArgs,
+    test_case: &TestCase,
+    path: &str,
+) -> Result<Vec<StateDifference>> {
+    let program_path = test_case.program_path(path);
+    let program = read_file(&program_path)?;
+    let mut rt = build_runtime(global_args, &program, &program_path)?;
+    rt.set_runtime_memory(RuntimeMemory::from(&test_case.initial_state));
+    rt.run().map_err(|e| miette!(e))?;
+    Ok(test_case
+        .expected_state
+        .diff(&StateDump::from(rt.runtime_memory())))
+}
+
+/// Runs the program, starting with the initial values of the case, and returns the differences of the final state
+/// to the expected values of the case.
+fn run_spec_case(
+    global_args: &GlobalArgs,
+    program: &[String],
+    path: &str,
+    case: &SpecCase,
+) -> Result<Vec<StateDifference>> {
+    let mut rt = build_runtime(global_args, program, path)?;
+    let mut initial_state = StateDump::from(rt.runtime_memory());
+    initial_state.update(&case.initial);
+    rt.set_runtime_memory(RuntimeMemory::from(&initial_state));
+    rt.run().map_err(|e| miette!(e))?;
+    Ok(case
+        .expected
+        .expected_differences(&StateDump::from(rt.runtime_memory())))
+}
+
+/// Builds the runtime like `check run` does, without the settings of an exercise.
+pub(super) fn build_runtime(
+    global_args: &GlobalArgs,
+    program: &[String],
+    path: &str,
+) -> Result<Runtime> {
+    let mut rb = RuntimeBuilder::new(program, path).map_err(|e| miette!(e))?;
+    rb.apply_global_cli_args(global_args)
+        .map_err(|e| miette!("memory config could not be loaded from file:\n{e}"))?;
+    rb.apply_program_header(program)?;
+    rb.build().map_err(|e| miette!(e))
+}
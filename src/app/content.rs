@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range};
 
 use ratatui::{
     style::Style,
@@ -8,16 +8,29 @@ use ratatui::{
 
 use crate::runtime::{Runtime, RuntimeMemory};
 
-use super::ui::style::SharedTheme;
+use super::{memory_edit::MemoryLocation, ui::style::SharedTheme, undo::UndoHistory};
+
+/// State of the breakpoint in a single line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakpointState {
+    /// No breakpoint is set in this line.
+    NotSet,
+    /// A breakpoint is set and execution stops at this line.
+    Enabled,
+    /// A breakpoint is set but is currently ignored.
+    Disabled,
+}
 
 /// Used to store the instructions and to remember what instruction should currently be highlighted.
 #[derive(Debug, Clone)]
 pub struct InstructionListStates {
     instruction_list_state: ListState,
     breakpoint_list_state: ListState,
-    instructions: Vec<(usize, Line<'static>, bool)>, // index, line content, is a breakpoint present
+    instructions: Vec<(usize, Line<'static>, BreakpointState)>, // index, line content, state of the breakpoint in this line
     last_index: i32,
     current_index: i32,
+    /// Lines that were selected before a jump target was followed, see [`Self::jump_to`].
+    jump_history: UndoHistory<usize>,
 }
 
 #[allow(clippy::cast_sign_loss)]
@@ -28,12 +41,12 @@ impl InstructionListStates {
         for (index, s) in instructions.iter().enumerate() {
             if let Some(v) = set_breakpoints {
                 if v.contains(&(index + 1)) {
-                    i.push((index, s.clone(), true));
+                    i.push((index, s.clone(), BreakpointState::Enabled));
                 } else {
-                    i.push((index, s.clone(), false));
+                    i.push((index, s.clone(), BreakpointState::NotSet));
                 }
             } else {
-                i.push((index, s.clone(), false));
+                i.push((index, s.clone(), BreakpointState::NotSet));
             }
         }
         InstructionListStates {
@@ -42,6 +55,7 @@ impl InstructionListStates {
             instructions: i,
             last_index: -1,
             current_index: -1,
+            jump_history: UndoHistory::new(),
         }
     }
 
@@ -121,43 +135,190 @@ impl InstructionListStates {
         list_prev(&mut self.breakpoint_list_state, self.instructions.len());
     }
 
+    /// Selects the line `index`, the line that was selected before can be returned to with [`Self::jump_back`].
+    pub fn jump_to(&mut self, index: usize) {
+        if let Some(selected) = self.instruction_list_state.selected() {
+            self.jump_history.record(selected);
+        }
+        self.force_set(index);
+    }
+
+    /// Selects the line that was selected before the last jump.
+    pub fn jump_back(&mut self) {
+        if let Some(selected) = self.instruction_list_state.selected() {
+            if let Some(index) = self.jump_history.undo(selected) {
+                self.force_set(index);
+            }
+        }
+    }
+
+    /// Selects the line that was left with the last [`Self::jump_back`].
+    pub fn jump_forward(&mut self) {
+        if let Some(selected) = self.instruction_list_state.selected() {
+            if let Some(index) = self.jump_history.redo(selected) {
+                self.force_set(index);
+            }
+        }
+    }
+
+    /// Returns the index of the line that is displayed in the row `row` of the instruction list, the first row
+    /// below the border is row 0. `None` if no line is displayed in that row.
+    pub fn line_in_row(&self, row: usize) -> Option<usize> {
+        let index = self.instruction_list_state.offset() + row;
+        (index < self.instructions.len()).then_some(index)
+    }
+
+    /// Scrolls the instruction list and breakpoint list by `rows` lines, without changing the selected line.
+    ///
+    /// The lists are scrolled back when they are drawn, if the selected line would not be visible.
+    pub fn scroll(&mut self, rows: isize) {
+        let max_offset = self.instructions.len().saturating_sub(1);
+        for state in [
+            &mut self.instruction_list_state,
+            &mut self.breakpoint_list_state,
+        ] {
+            *state.offset_mut() = state.offset().saturating_add_signed(rows).min(max_offset);
+        }
+    }
+
     pub fn set_instruction_list_state(&mut self, index: Option<usize>) {
         self.instruction_list_state.select(index);
     }
 
-    /// Toggles the breakpoint in the current line
+    /// Toggles the breakpoint in the current line.
+    ///
+    /// If a disabled breakpoint is set in the current line, it is removed.
     pub fn toggle_breakpoint(&mut self) {
-        let val = self.instructions[self.instruction_list_state.selected().unwrap()].2;
-        self.instructions[self.instruction_list_state.selected().unwrap()].2 = !val;
+        self.toggle_breakpoint_in_line(self.instruction_list_state.selected().unwrap());
+    }
+
+    /// Toggles the breakpoint in the line `index`, like [`Self::toggle_breakpoint`].
+    pub fn toggle_breakpoint_in_line(&mut self, index: usize) {
+        let Some((_, _, state)) = self.instructions.get_mut(index) else {
+            return;
+        };
+        *state = match state {
+            BreakpointState::NotSet => BreakpointState::Enabled,
+            BreakpointState::Enabled | BreakpointState::Disabled => BreakpointState::NotSet,
+        };
+    }
+
+    /// Enables the breakpoint in the current line, if it is disabled, and disables it, if it is enabled.
+    ///
+    /// Does nothing if no breakpoint is set in the current line.
+    pub fn toggle_breakpoint_enabled(&mut self) {
+        let state = &mut self.instructions[self.instruction_list_state.selected().unwrap()].2;
+        *state = match state {
+            BreakpointState::NotSet => BreakpointState::NotSet,
+            BreakpointState::Enabled => BreakpointState::Disabled,
+            BreakpointState::Disabled => BreakpointState::Enabled,
+        };
+    }
+
+    /// Disables all breakpoints, if at least one breakpoint is enabled,
+    /// otherwise all breakpoints are enabled.
+    ///
+    /// Breakpoints are not removed by this.
+    pub fn toggle_all_breakpoints(&mut self) {
+        let new_state = if self.breakpoints_set() {
+            BreakpointState::Disabled
+        } else {
+            BreakpointState::Enabled
+        };
+        for instruction in &mut self.instructions {
+            if instruction.2 != BreakpointState::NotSet {
+                instruction.2 = new_state;
+            }
+        }
+    }
+
+    /// Sets the breakpoints in the lines `lines` to `state`.
+    ///
+    /// Line numbers start at 1, lines that do not exist are ignored.
+    pub fn set_breakpoints(&mut self, lines: &[usize], state: BreakpointState) {
+        for line in lines {
+            if let Some(instruction) = line
+                .checked_sub(1)
+                .and_then(|idx| self.instructions.get_mut(idx))
+            {
+                instruction.2 = state;
+            }
+        }
+    }
+
+    /// Sets breakpoints in all lines of `range` (indices start at 0), if breakpoints are already set in all of
+    /// these lines, they are removed instead.
+    pub fn toggle_breakpoints(&mut self, range: Range<usize>) {
+        let new_state = if self.breakpoints_in_range(range.clone()) {
+            BreakpointState::NotSet
+        } else {
+            BreakpointState::Enabled
+        };
+        for instruction in self
+            .instructions
+            .iter_mut()
+            .skip(range.start)
+            .take(range.len())
+        {
+            instruction.2 = new_state;
+        }
+    }
+
+    /// Returns true if a breakpoint, enabled or disabled, is set in all lines of `range` (indices start at 0).
+    pub fn breakpoints_in_range(&self, range: Range<usize>) -> bool {
+        !range.is_empty()
+            && self
+                .instructions
+                .iter()
+                .skip(range.start)
+                .take(range.len())
+                .all(|f| f.2 != BreakpointState::NotSet)
+    }
+
+    /// Returns the state of the breakpoint of each line.
+    pub fn breakpoint_states(&self) -> Vec<BreakpointState> {
+        self.instructions.iter().map(|f| f.2).collect()
+    }
+
+    /// Sets the state of the breakpoint of each line, `states` should be created by [`Self::breakpoint_states`].
+    pub fn set_breakpoint_states(&mut self, states: &[BreakpointState]) {
+        for (instruction, state) in self.instructions.iter_mut().zip(states) {
+            instruction.2 = *state;
+        }
+    }
+
+    /// Returns the line numbers (starting at 1) of all breakpoints that are in state `state`.
+    pub fn breakpoint_lines(&self, state: BreakpointState) -> Vec<usize> {
+        self.instructions
+            .iter()
+            .filter(|f| f.2 == state)
+            .map(|f| f.0 + 1)
+            .collect()
     }
 
-    /// Checks if the current line contains a breakpoint
+    /// Checks if the current line contains an enabled breakpoint
     pub fn is_breakpoint(&self) -> bool {
         if let Some(idx) = self.instruction_list_state.selected() {
             match self.instructions.get(idx) {
-                Some(i) => return i.2,
+                Some(i) => return i.2 == BreakpointState::Enabled,
                 None => return false,
             };
         }
         false
-        // self.instructions[self.instruction_list_state.selected().unwrap()].2
     }
 
-    /// Returns true if at least one breakpoint is set.
+    /// Returns true if at least one enabled breakpoint is set.
     pub fn breakpoints_set(&self) -> bool {
-        for state in &self.instructions {
-            if state.2 {
-                return true;
-            }
-        }
-        false
+        self.instructions
+            .iter()
+            .any(|f| f.2 == BreakpointState::Enabled)
     }
 
     pub fn selected_line(&self) -> Option<usize> {
         self.instruction_list_state.selected()
     }
 
-    pub fn instructions(&self) -> &Vec<(usize, Line<'static>, bool)> {
+    pub fn instructions(&self) -> &Vec<(usize, Line<'static>, BreakpointState)> {
         &self.instructions
     }
 
@@ -171,7 +332,15 @@ impl InstructionListStates {
 
     /// Adds a new instruction to the list (display only)
     pub fn add_instruction(&mut self, line: Line<'static>) {
-        self.instructions.push((0, line, false))
+        self.instructions.push((0, line, BreakpointState::NotSet))
+    }
+
+    /// Replaces all instructions in the list (display only)
+    pub fn set_instructions(&mut self, lines: &[Line<'static>]) {
+        self.instructions.clear();
+        for line in lines {
+            self.add_instruction(line.clone());
+        }
     }
 }
 
@@ -211,36 +380,199 @@ fn list_prev(list_state: &mut ListState, max_index: usize) {
     list_state.select(Some(i));
 }
 
+/// Number system in which the values of the memory lists and the stack are displayed.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum ValueFormat {
+    #[default]
+    Decimal,
+    Hexadecimal,
+    Binary,
+}
+
+impl ValueFormat {
+    /// Returns the format that is selected after this format, when the formats are cycled.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Decimal => Self::Hexadecimal,
+            Self::Hexadecimal => Self::Binary,
+            Self::Binary => Self::Decimal,
+        }
+    }
+
+    /// Formats `value`, negative values are displayed in hexadecimal and binary in their 32 bit two's complement,
+    /// e.g. `-1` is displayed as `0xffffffff`.
+    pub fn format(self, value: i32) -> String {
+        match self {
+            Self::Decimal => value.to_string(),
+            Self::Hexadecimal => format!("{value:#x}"),
+            Self::Binary => format!("{value:#b}"),
+        }
+    }
+}
+
+/// Memory panel whose entries can be scrolled, when they don't fit into the panel.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MemoryPanel {
+    Stack,
+    MemoryCells,
+    Accumulators,
+    /// Values printed by the program, see [`ControlFlow::output`](crate::runtime::ControlFlow::output).
+    Output,
+}
+
+impl MemoryPanel {
+    /// All panels, in the order in which they are switched through to select the scrolled panel.
+    pub const ALL: [Self; 4] = [
+        Self::Stack,
+        Self::MemoryCells,
+        Self::Accumulators,
+        Self::Output,
+    ];
+}
+
+/// Scroll position of a memory list, whose entries might not fit into its panel.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct ListScroll {
+    /// Number of entries, counted from the start of the list, that are scrolled out of view.
+    offset: usize,
+    /// Number of rows the panel had when it was last drawn.
+    height: usize,
+}
+
+impl Default for ListScroll {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            height: usize::MAX,
+        }
+    }
+}
+
+impl ListScroll {
+    /// Returns true if a list with `len` entries does not fit into the panel.
+    fn overflows(self, len: usize) -> bool {
+        // panels that are too small for the ellipsis rows are not scrolled
+        self.height >= 3 && len > self.height
+    }
+
+    /// Returns the offset at which the last entry of a list with `len` entries is shown in the last row.
+    fn max_offset(self, len: usize) -> usize {
+        if self.overflows(len) {
+            // one row is used by the ellipsis of the entries above
+            len + 1 - self.height
+        } else {
+            0
+        }
+    }
+
+    /// Returns the entries of `list` that are visible at the scroll position, the hidden entries above and below
+    /// are replaced by a row with their number. The row above is styled with `above_style`, if it is set.
+    fn visible(
+        self,
+        list: Vec<ListItem<'static>>,
+        above_style: Option<Style>,
+    ) -> Vec<ListItem<'static>> {
+        if !self.overflows(list.len()) {
+            return list;
+        }
+        let mut rows = self.height;
+        let mut items = Vec::new();
+        if self.offset > 0 {
+            let mut above = ListItem::new(format!("… {} more", self.offset));
+            if let Some(style) = above_style {
+                above = above.style(style);
+            }
+            items.push(above);
+            rows -= 1;
+        }
+        if list.len() > self.offset + rows {
+            rows -= 1;
+        }
+        let below = list.len().saturating_sub(self.offset + rows);
+        items.extend(list.into_iter().skip(self.offset).take(rows));
+        if below > 0 {
+            items.push(ListItem::new(format!("… {below} more")));
+        }
+        items
+    }
+}
+
 /// Used to update and set the lists for accumulators, memory cells, stack and call stack.
 pub struct MemoryListsManager {
     accumulators: HashMap<usize, (String, bool)>,
     gamma: Option<(Option<i32>, bool)>,
     memory_cells: HashMap<String, (String, bool)>,
     index_memory_cells: HashMap<usize, (String, bool)>,
-    stack: Vec<ListItem<'static>>,
+    stack: Vec<i32>,
+    /// Set when the top of the stack changed with the last update.
+    stack_changed: bool,
+    /// Scroll position of the stack, counted from the top of the stack.
+    stack_scroll: ListScroll,
+    /// Scroll position of the memory cells.
+    memory_cell_scroll: ListScroll,
+    /// Scroll position of the accumulators.
+    accumulator_scroll: ListScroll,
+    /// Values printed by the program, the first printed value is the first entry.
+    output: Vec<i32>,
+    /// Set when a value was printed with the last update.
+    output_changed: bool,
+    /// Scroll position of the output.
+    output_scroll: ListScroll,
     call_stack: Vec<ListItem<'static>>,
+    /// Memory when the manager was created, it is restored when the program is reset.
+    initial_memory: RuntimeMemory,
+    /// Values of the last run, of the locations whose value was restored by the last reset.
+    ///
+    /// They are displayed next to the restored value until the location is overwritten, to show what the reset
+    /// changed.
+    reset_values: HashMap<MemoryLocation, Option<i32>>,
+    /// Names under which memory locations are displayed, see [`Runtime::aliases`].
+    aliases: HashMap<String, String>,
+    /// Number system in which the values are displayed.
+    value_format: ValueFormat,
     theme: SharedTheme,
 }
 
 impl MemoryListsManager {
     /// Creates a new `MemoryListsManager` with the current values of the runtime arguments.
-    pub fn new(runtime_args: &RuntimeMemory, theme: &SharedTheme) -> Self {
+    ///
+    /// `aliases` are the names under which memory locations are displayed, see [`Runtime::aliases`].
+    pub fn new(
+        runtime_args: &RuntimeMemory,
+        aliases: &HashMap<String, String>,
+        theme: &SharedTheme,
+    ) -> Self {
         let mut accumulators = HashMap::new();
         for acc in &runtime_args.accumulators {
-            accumulators.insert(*acc.0, (format!("{}", acc.1), false));
+            let text = location_text(
+                aliases,
+                &MemoryLocation::Accumulator(*acc.0),
+                acc.1.data,
+                ValueFormat::Decimal,
+            );
+            accumulators.insert(*acc.0, (text, false));
         }
         //accumulators.sort_by(|a, b| a.0.cmp(&b.0));
         let mut memory_cells = HashMap::new();
-        for cell in &runtime_args.memory_cells {
-            memory_cells.insert(cell.1.label.clone(), (format!("{}", cell.1), false));
+        for cell in runtime_args.program_memory_cells() {
+            let location = MemoryLocation::MemoryCell(cell.label.clone());
+            memory_cells.insert(
+                cell.label.clone(),
+                (
+                    location_text(aliases, &location, cell.data, ValueFormat::Decimal),
+                    false,
+                ),
+            );
         }
         let mut index_memory_cells = HashMap::new();
         for cell in &runtime_args.index_memory_cells {
-            if let Some(v) = cell.1 {
-                index_memory_cells.insert(*cell.0, (format!("[{:2}]: {}", *cell.0, *v), false));
-            } else {
-                index_memory_cells.insert(*cell.0, (format!("[{:2}]: None", *cell.0), false));
-            }
+            let text = location_text(
+                aliases,
+                &MemoryLocation::IndexMemoryCell(*cell.0),
+                *cell.1,
+                ValueFormat::Decimal,
+            );
+            index_memory_cells.insert(*cell.0, (text, false));
         }
         let gamma = runtime_args.gamma.map(|value| (value, false));
         Self {
@@ -249,11 +581,72 @@ impl MemoryListsManager {
             memory_cells,
             index_memory_cells,
             stack: Vec::new(),
+            stack_changed: false,
+            stack_scroll: ListScroll::default(),
+            memory_cell_scroll: ListScroll::default(),
+            accumulator_scroll: ListScroll::default(),
+            output: Vec::new(),
+            output_changed: false,
+            output_scroll: ListScroll::default(),
             call_stack: Vec::new(),
+            initial_memory: runtime_args.clone(),
+            reset_values: HashMap::new(),
+            aliases: aliases.clone(),
+            value_format: ValueFormat::Decimal,
             theme: theme.clone(),
         }
     }
 
+    /// Restores the lists to the initial memory, `last_run` is the memory before the program was reset.
+    ///
+    /// Locations whose value differs in `last_run` are marked with their value of the last run.
+    pub fn reset(&mut self, last_run: &RuntimeMemory) {
+        let initial_memory = self.initial_memory.clone();
+        let heights = MemoryPanel::ALL.map(|panel| self.list_scroll(panel).height);
+        let value_format = self.value_format;
+        *self = Self::new(&initial_memory, &self.aliases, &self.theme);
+        for (panel, height) in MemoryPanel::ALL.into_iter().zip(heights) {
+            self.set_height(panel, height);
+        }
+        self.set_value_format(value_format, &initial_memory);
+        self.reset_values = MemoryLocation::all(&initial_memory)
+            .into_iter()
+            .filter_map(|location| {
+                let value = location.value(last_run);
+                (value != location.value(&initial_memory)).then_some((location, value))
+            })
+            .collect();
+    }
+
+    /// Returns the number system in which the values are displayed.
+    pub fn value_format(&self) -> ValueFormat {
+        self.value_format
+    }
+
+    /// Sets the number system in which the values are displayed, `memory` is the current memory of the runtime.
+    ///
+    /// The displayed values are converted without marking them as changed.
+    pub fn set_value_format(&mut self, value_format: ValueFormat, memory: &RuntimeMemory) {
+        self.value_format = value_format;
+        let text = |location: MemoryLocation| {
+            location_text(
+                &self.aliases,
+                &location,
+                location.value(memory),
+                value_format,
+            )
+        };
+        for (id, (value, _)) in &mut self.accumulators {
+            *value = text(MemoryLocation::Accumulator(*id));
+        }
+        for (label, (value, _)) in &mut self.memory_cells {
+            *value = text(MemoryLocation::MemoryCell(label.clone()));
+        }
+        for (index, (value, _)) in &mut self.index_memory_cells {
+            *value = text(MemoryLocation::IndexMemoryCell(*index));
+        }
+    }
+
     /// Updates the lists values.
     /// The old values are compared against the new values, if a value has changed the background color
     /// of that list item is changed.
@@ -269,7 +662,12 @@ impl MemoryListsManager {
                     self.accumulators.get_mut(acc.0).unwrap()
                 }
             };
-            let update = format!("{}", acc.1);
+            let update = location_text(
+                &self.aliases,
+                &MemoryLocation::Accumulator(*acc.0),
+                acc.1.data,
+                self.value_format,
+            );
             if update == *a.0 {
                 a.1 = false;
             } else {
@@ -277,16 +675,21 @@ impl MemoryListsManager {
             }
         }
         // Update memory_cells
-        for cell in &runtime.runtime_memory().memory_cells {
-            let a = match self.memory_cells.get_mut(&cell.1.label) {
+        for cell in runtime.runtime_memory().program_memory_cells() {
+            let a = match self.memory_cells.get_mut(&cell.label) {
                 Some(value) => value,
                 None => {
                     self.memory_cells
-                        .insert(cell.1.label.clone(), ("0".to_string(), true));
-                    self.memory_cells.get_mut(&cell.1.label).unwrap()
+                        .insert(cell.label.clone(), ("0".to_string(), true));
+                    self.memory_cells.get_mut(&cell.label).unwrap()
                 }
             };
-            let update = format!("{}", cell.1);
+            let update = location_text(
+                &self.aliases,
+                &MemoryLocation::MemoryCell(cell.label.clone()),
+                cell.data,
+                self.value_format,
+            );
             if update == *a.0 {
                 a.1 = false;
             } else {
@@ -295,21 +698,17 @@ impl MemoryListsManager {
         }
         // Update index memory cells
         for cell in &runtime.runtime_memory().index_memory_cells {
+            let update = location_text(
+                &self.aliases,
+                &MemoryLocation::IndexMemoryCell(*cell.0),
+                *cell.1,
+                self.value_format,
+            );
             if !self.index_memory_cells.contains_key(cell.0) {
-                if let Some(v) = cell.1 {
-                    self.index_memory_cells
-                        .insert(*cell.0, (format!("[{:2}]: {}", cell.0, v), true));
-                } else {
-                    self.index_memory_cells
-                        .insert(*cell.0, (format!("[{:2}]: None", cell.0), true));
-                }
+                self.index_memory_cells.insert(*cell.0, (update, true));
                 continue;
             }
             let a = self.index_memory_cells.get_mut(cell.0).unwrap();
-            let update = match cell.1 {
-                Some(v) => format!("[{:2}]: {}", cell.0, v),
-                None => format!("[{:2}]: None", cell.0),
-            };
             if update == *a.0 {
                 a.1 = false;
             } else {
@@ -329,21 +728,28 @@ impl MemoryListsManager {
             }
         }
         // Update stack
-        let stack_changed = self.stack.len() != runtime.runtime_memory().stack.len();
-        let mut new_stack: Vec<ListItem<'_>> = runtime
-            .runtime_memory()
-            .stack
-            .iter()
-            .map(|f| ListItem::new(f.to_string()))
-            .collect();
-        if stack_changed && !new_stack.is_empty() {
-            let last_stack = new_stack
-                .pop()
-                .unwrap()
-                .style(self.theme.list_item_highlight(false));
-            new_stack.push(last_stack);
+        let new_stack = runtime.runtime_memory().stack.clone();
+        let stack_changed = self.stack.len() != new_stack.len();
+        // a scrolled stack keeps showing the same entries, when entries are pushed or popped
+        if self.stack_scroll.offset > 0 {
+            self.stack_scroll.offset =
+                (self.stack_scroll.offset + new_stack.len()).saturating_sub(self.stack.len());
         }
         self.stack = new_stack;
+        self.stack_changed = stack_changed;
+        // Update output, it follows the printed values while it is scrolled to the end
+        let new_output = &runtime.control_flow().output;
+        let follow_output =
+            self.output_scroll.offset == self.output_scroll.max_offset(self.output.len());
+        self.output_changed = self.output.len() != new_output.len();
+        self.output.clone_from(new_output);
+        if follow_output {
+            self.scroll_to_bottom(MemoryPanel::Output);
+        }
+        // the scroll positions are moved, if locations were added or entries popped
+        for panel in MemoryPanel::ALL {
+            self.scroll(panel, 0);
+        }
         // update call stack
         let call_stack_changed = self.call_stack.len() != runtime.control_flow().call_stack.len();
         let mut new_call_stack: Vec<ListItem<'_>> = runtime
@@ -360,48 +766,81 @@ impl MemoryListsManager {
             new_call_stack.push(last_stack);
         }
         self.call_stack = new_call_stack;
+        // the mark is removed once the location is overwritten
+        let memory = runtime.runtime_memory();
+        self.reset_values
+            .retain(|location, _| location.value(memory) == location.value(&self.initial_memory));
     }
 
-    /// Returns the current accumulators as list
-    pub fn accumulator_list(&self) -> Vec<ListItem<'static>> {
-        let mut list = Vec::new();
-        for acc in &self.accumulators {
-            let mut item = ListItem::new(acc.1 .0.clone());
-            if acc.1 .1 {
-                item = item.style(self.theme.list_item_highlight(false));
-            }
-            list.push((item, acc.0));
+    /// Creates the list item of a memory location, `changed` highlights the item.
+    fn list_item(
+        &self,
+        text: String,
+        changed: bool,
+        location: &MemoryLocation,
+    ) -> ListItem<'static> {
+        let mut spans = vec![Span::raw(text)];
+        if let Some(value) = self.reset_values.get(location) {
+            spans.push(Span::styled(
+                format!(
+                    " (was {})",
+                    value.map_or("None".to_string(), |f| self.value_format.format(f))
+                ),
+                self.theme.syntax_highlighting_theme().comment(),
+            ));
         }
-        list.sort_by(|a, b| a.1.cmp(b.1));
-        list.reverse();
-        // Insert gamma accumulator if it is in use
-        if let Some(value) = self.gamma {
-            if let Some(inner_value) = value.0 {
-                let mut item = ListItem::new(format!("  γ: {inner_value}"));
-                if value.1 {
-                    item = item.style(self.theme.list_item_highlight(false));
-                }
-                list.push((item, &0));
-            } else {
-                let mut item = ListItem::new("  γ: None".to_string());
-                if value.1 {
-                    item = item.style(self.theme.list_item_highlight(false));
-                }
-                list.push((item, &0));
-            }
+        let mut item = ListItem::new(Line::from(spans));
+        if changed {
+            item = item.style(self.theme.list_item_highlight(false));
         }
-        list.reverse(); // reverse list to make gamma appear at top of list
-        list.iter().map(|f| f.0.clone()).collect()
+        item
+    }
+
+    /// Returns the current accumulators as list, only the accumulators at the scroll position are returned, see
+    /// [`Self::stack_list`].
+    pub fn accumulator_list(&self) -> Vec<ListItem<'static>> {
+        let mut accumulators = self.accumulators.iter().collect::<Vec<_>>();
+        accumulators.sort_by_key(|f| f.0);
+        let list = accumulators
+            .into_iter()
+            .map(|(id, (text, changed))| {
+                self.list_item(text.clone(), *changed, &MemoryLocation::Accumulator(*id))
+            })
+            .collect();
+        self.accumulator_scroll.visible(list, None)
+    }
+
+    /// Returns the gamma accumulator as list, the list is empty if the gamma accumulator is disabled
+    pub fn gamma_list(&self) -> Vec<ListItem<'static>> {
+        self.gamma
+            .iter()
+            .map(|(value, changed)| {
+                let text = location_text(
+                    &self.aliases,
+                    &MemoryLocation::Gamma,
+                    *value,
+                    self.value_format,
+                );
+                self.list_item(text, *changed, &MemoryLocation::Gamma)
+            })
+            .collect()
     }
 
-    /// Returns the current memory cells as list (also contains index memory cells)
+    /// Returns true if the gamma accumulator is enabled.
+    pub fn gamma_enabled(&self) -> bool {
+        self.gamma.is_some()
+    }
+
+    /// Returns the current memory cells as list (also contains index memory cells), only the memory cells at the
+    /// scroll position are returned, see [`Self::stack_list`].
     pub fn memory_cell_list(&self) -> Vec<ListItem<'static>> {
         let mut list = Vec::new();
         for cell in &self.memory_cells {
-            let mut item = ListItem::new(cell.1 .0.clone());
-            if cell.1 .1 {
-                item = item.style(self.theme.list_item_highlight(false));
-            }
+            let item = self.list_item(
+                cell.1 .0.clone(),
+                cell.1 .1,
+                &MemoryLocation::MemoryCell(cell.0.clone()),
+            );
             list.push((item, cell.0.clone()));
         }
         list.sort_by(|a, b| a.1.cmp(&b.1));
@@ -412,20 +851,123 @@ impl MemoryListsManager {
         }
         imc.sort(); // Make sure that index memory cells are properly sorted by index
         for cell in imc {
-            let mut item = ListItem::new(cell.2.clone());
-            if cell.1 {
-                item = item.style(self.theme.list_item_highlight(false));
-            }
+            let item = self.list_item(
+                cell.2.clone(),
+                cell.1,
+                &MemoryLocation::IndexMemoryCell(cell.0),
+            );
             list.push((item, format!("{}", cell.0)));
         }
-        list.iter().map(|f| f.0.clone()).collect()
+        let list = list.into_iter().map(|f| f.0).collect();
+        self.memory_cell_scroll.visible(list, None)
     }
 
-    /// Returns the stack items as list
+    /// Returns the stack items as list, the top of the stack is the first item.
+    ///
+    /// If the stack has more entries than fit into the stack panel (see [`Self::set_height`]), only the entries at
+    /// the scroll position are returned, the hidden entries above and below are replaced by a row with their number.
+    /// The row above is highlighted, if the top of the stack changed while it is scrolled out of view.
     pub fn stack_list(&self) -> Vec<ListItem<'static>> {
-        let mut list = self.stack.clone();
+        let mut list = self
+            .stack
+            .iter()
+            .map(|f| ListItem::new(self.value_format.format(*f)))
+            .collect::<Vec<_>>();
+        if self.stack_changed {
+            // the top of the stack is highlighted, when it changed
+            if let Some(top) = list.pop() {
+                list.push(top.style(self.theme.list_item_highlight(false)));
+            }
+        }
         list.reverse();
-        list
+        let highlight = self.theme.list_item_highlight(false);
+        self.stack_scroll
+            .visible(list, self.stack_changed.then_some(highlight))
+    }
+
+    /// Returns the values printed by the program as list, in the order in which they were printed, only the
+    /// values at the scroll position are returned, see [`Self::stack_list`].
+    ///
+    /// The last value is highlighted, when it was printed with the last update.
+    pub fn output_list(&self) -> Vec<ListItem<'static>> {
+        let mut list = self
+            .output
+            .iter()
+            .map(|f| ListItem::new(self.value_format.format(*f)))
+            .collect::<Vec<_>>();
+        if self.output_changed {
+            if let Some(last) = list.pop() {
+                list.push(last.style(self.theme.list_item_highlight(false)));
+            }
+        }
+        self.output_scroll.visible(list, None)
+    }
+
+    /// Sets the number of rows of the panel, the scroll position is moved so that the panel is filled.
+    pub fn set_height(&mut self, panel: MemoryPanel, height: usize) {
+        self.list_scroll_mut(panel).height = height;
+        self.scroll(panel, 0);
+    }
+
+    /// Returns true if the panel has more entries than fit into it, so that it can be scrolled.
+    pub fn overflows(&self, panel: MemoryPanel) -> bool {
+        self.list_scroll(panel).overflows(self.len(panel))
+    }
+
+    /// Scrolls the panel by `rows` entries, positive values scroll towards the end of the list, which is the bottom
+    /// of the stack.
+    pub fn scroll(&mut self, panel: MemoryPanel, rows: isize) {
+        let len = self.len(panel);
+        let scroll = self.list_scroll_mut(panel);
+        scroll.offset = scroll
+            .offset
+            .saturating_add_signed(rows)
+            .min(scroll.max_offset(len));
+    }
+
+    /// Scrolls the panel to the start of the list, the top of the stack.
+    pub fn scroll_to_top(&mut self, panel: MemoryPanel) {
+        self.list_scroll_mut(panel).offset = 0;
+    }
+
+    /// Scrolls the panel to the end of the list, the entry of the stack that was pushed first.
+    pub fn scroll_to_bottom(&mut self, panel: MemoryPanel) {
+        let len = self.len(panel);
+        let scroll = self.list_scroll_mut(panel);
+        scroll.offset = scroll.max_offset(len);
+    }
+
+    /// Returns the number of rows that are scrolled, when a page of the panel is scrolled.
+    pub fn page(&self, panel: MemoryPanel) -> usize {
+        self.list_scroll(panel).height.saturating_sub(2).max(1)
+    }
+
+    fn list_scroll(&self, panel: MemoryPanel) -> ListScroll {
+        match panel {
+            MemoryPanel::Stack => self.stack_scroll,
+            MemoryPanel::MemoryCells => self.memory_cell_scroll,
+            MemoryPanel::Accumulators => self.accumulator_scroll,
+            MemoryPanel::Output => self.output_scroll,
+        }
+    }
+
+    fn list_scroll_mut(&mut self, panel: MemoryPanel) -> &mut ListScroll {
+        match panel {
+            MemoryPanel::Stack => &mut self.stack_scroll,
+            MemoryPanel::MemoryCells => &mut self.memory_cell_scroll,
+            MemoryPanel::Accumulators => &mut self.accumulator_scroll,
+            MemoryPanel::Output => &mut self.output_scroll,
+        }
+    }
+
+    /// Returns the number of entries of the list of the panel.
+    fn len(&self, panel: MemoryPanel) -> usize {
+        match panel {
+            MemoryPanel::Stack => self.stack.len(),
+            MemoryPanel::MemoryCells => self.memory_cells.len() + self.index_memory_cells.len(),
+            MemoryPanel::Accumulators => self.accumulators.len(),
+            MemoryPanel::Output => self.output.len(),
+        }
     }
 
     /// Returns the call stack items as list
@@ -434,4 +976,102 @@ impl MemoryListsManager {
         list.reverse();
         list
     }
+
+    /// Returns the number of entries in all memory lists, including stack and call stack.
+    pub fn entry_count(&self) -> usize {
+        self.accumulators.len()
+            + usize::from(self.gamma.is_some())
+            + self.memory_cells.len()
+            + self.index_memory_cells.len()
+            + self.stack.len()
+            + self.call_stack.len()
+    }
+}
+
+/// Returns the text of a memory location in the memory lists, e.g. ` α0: 5`.
+///
+/// The alias of the location is displayed instead of its name, if one is set. The value is displayed in the number
+/// system `value_format`.
+fn location_text(
+    aliases: &HashMap<String, String>,
+    location: &MemoryLocation,
+    value: Option<i32>,
+    value_format: ValueFormat,
+) -> String {
+    let value = value.map_or("None".to_string(), |f| value_format.format(f));
+    let alias = aliases.get(&location.to_string());
+    match (location, alias) {
+        (_, Some(alias)) => format!("{alias}: {value}"),
+        (MemoryLocation::Accumulator(id), None) => format!("{:>3}: {value}", format!("α{id}")),
+        (MemoryLocation::Gamma, None) => format!("γ: {value}"),
+        (MemoryLocation::MemoryCell(label), None) => format!("{label:2}: {value}"),
+        (MemoryLocation::IndexMemoryCell(index), None) => format!("[{index:2}]: {value}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::text::Line;
+
+    use super::{BreakpointState, InstructionListStates, ValueFormat};
+
+    fn test_instruction_list_states() -> InstructionListStates {
+        let lines = vec![
+            Line::from("a := 5"),
+            Line::from("a := 6"),
+            Line::from("a := 7"),
+        ];
+        InstructionListStates::new(&lines, Some(&vec![1, 3]))
+    }
+
+    #[test]
+    fn test_toggle_breakpoint() {
+        let mut ils = test_instruction_list_states();
+        ils.force_set(1);
+        ils.toggle_breakpoint();
+        assert_eq!(ils.instructions()[1].2, BreakpointState::Enabled);
+        ils.toggle_breakpoint_enabled();
+        assert_eq!(ils.instructions()[1].2, BreakpointState::Disabled);
+        assert!(!ils.is_breakpoint());
+        ils.toggle_breakpoint();
+        assert_eq!(ils.instructions()[1].2, BreakpointState::NotSet);
+        ils.toggle_breakpoint_enabled();
+        assert_eq!(ils.instructions()[1].2, BreakpointState::NotSet);
+    }
+
+    #[test]
+    fn test_toggle_all_breakpoints() {
+        let mut ils = test_instruction_list_states();
+        assert!(ils.breakpoints_set());
+        ils.toggle_all_breakpoints();
+        assert!(!ils.breakpoints_set());
+        assert_eq!(ils.instructions()[0].2, BreakpointState::Disabled);
+        assert_eq!(ils.instructions()[1].2, BreakpointState::NotSet);
+        assert_eq!(ils.instructions()[2].2, BreakpointState::Disabled);
+        ils.toggle_all_breakpoints();
+        assert_eq!(ils.instructions()[0].2, BreakpointState::Enabled);
+        assert_eq!(ils.instructions()[1].2, BreakpointState::NotSet);
+        assert_eq!(ils.instructions()[2].2, BreakpointState::Enabled);
+    }
+
+    #[test]
+    fn test_set_breakpoints() {
+        let mut ils = test_instruction_list_states();
+        ils.set_breakpoints(&[0, 2, 3, 10], BreakpointState::Disabled);
+        assert_eq!(ils.breakpoint_lines(BreakpointState::Enabled), vec![1]);
+        assert_eq!(ils.breakpoint_lines(BreakpointState::Disabled), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_value_format() {
+        assert_eq!(ValueFormat::Decimal.format(-1), "-1");
+        assert_eq!(ValueFormat::Hexadecimal.format(255), "0xff");
+        assert_eq!(ValueFormat::Hexadecimal.format(-1), "0xffffffff");
+        assert_eq!(ValueFormat::Binary.format(5), "0b101");
+        assert_eq!(
+            ValueFormat::Binary.format(-2),
+            format!("0b{}0", "1".repeat(31))
+        );
+        assert_eq!(ValueFormat::Binary.next(), ValueFormat::Decimal);
+    }
 }
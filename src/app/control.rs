@@ -0,0 +1,121 @@
+use std::{io::Write, time::Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::{repro::action_from_key, State};
+
+/// Role of the app in a shared session.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ShareRole {
+    /// The session is shared by this app.
+    Host,
+    /// This app is attached to a shared session.
+    Viewer,
+}
+
+impl ShareRole {
+    /// Returns the status of the shared session, it is displayed in the title of the code block.
+    pub fn status(self, control_granted: bool) -> &'static str {
+        match (self, control_granted) {
+            (Self::Host, false) => "shared, [g] to grant control",
+            (Self::Host, true) => "shared, viewers have control, [g] to revoke",
+            (Self::Viewer, false) => "read-only",
+            (Self::Viewer, true) => "you have control",
+        }
+    }
+}
+
+/// Returns true if the key controls stepping or breakpoints, only these keys can be used by viewers
+/// that have been granted control.
+pub fn is_control_key(key: &KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => true,
+        _ if !key.modifiers.is_empty() => false,
+        KeyCode::Char(
+            's' | 'n' | 'o' | 'f' | 'r' | 't' | 'd' | 'b' | 'e' | 'a' | 'u' | 'j' | 'w',
+        )
+        | KeyCode::Up
+        | KeyCode::Down => true,
+        _ => false,
+    }
+}
+
+/// Returns true if keys of viewers can be handled in this state.
+///
+/// In all other states the keys have a different meaning, e.g. [s] saves the session and quits in the quit prompt.
+pub fn accepts_control_keys(state: &State) -> bool {
+    matches!(
+        state,
+        State::Default | State::Running(_) | State::DebugSelect(_, _) | State::Finished(_)
+    )
+}
+
+/// Log of all actions that were performed in a shared session, written by the host.
+pub struct ShareLog {
+    writer: Box<dyn Write>,
+    start: Instant,
+}
+
+impl ShareLog {
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self {
+            writer,
+            start: Instant::now(),
+        }
+    }
+
+    /// Writes the message to the log, prefixed with the seconds since the session was shared.
+    ///
+    /// Errors are ignored, the session should not be interrupted if the log can not be written.
+    pub fn write(&mut self, message: &str) {
+        _ = writeln!(
+            self.writer,
+            "[{:>8.1}s] {message}",
+            self.start.elapsed().as_secs_f32()
+        );
+        _ = self.writer.flush();
+    }
+
+    /// Writes the action of the key to the log, `by` is the participant that pressed the key.
+    pub fn write_action(&mut self, by: &str, key: KeyEvent, note: Option<&str>) {
+        let Some(action) = action_from_key(key) else {
+            return;
+        };
+        match note {
+            Some(note) => self.write(&format!("{by}: {action} ({note})")),
+            None => self.write(&format!("{by}: {action}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::is_control_key;
+
+    #[test]
+    fn test_is_control_key() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        for code in [KeyCode::Char('n'), KeyCode::Char('b'), KeyCode::Down] {
+            assert!(is_control_key(&key(code)));
+        }
+        for code in [
+            KeyCode::Char('q'),
+            KeyCode::Char('i'),
+            KeyCode::Char('g'),
+            KeyCode::Esc,
+            KeyCode::Enter,
+        ] {
+            assert!(!is_control_key(&key(code)));
+        }
+        assert!(is_control_key(&KeyEvent::new(
+            KeyCode::Char('r'),
+            KeyModifiers::CONTROL
+        )));
+        assert!(!is_control_key(&KeyEvent::new(
+            KeyCode::Char('n'),
+            KeyModifiers::CONTROL
+        )));
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+use serde::Deserialize;
+
+use crate::runtime::error_handling::{RuntimeError, RuntimeErrorType};
+
+/// Event of the tui on which a hook can be triggered.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum HookEvent {
+    /// Running to the next breakpoint stopped at a breakpoint.
+    BreakpointHit,
+    /// A condition of a contract did not hold, see `@requires` and `@ensures`.
+    AssertionFailed,
+    /// The program finished.
+    ProgramFinished,
+}
+
+impl HookEvent {
+    /// Returns the event that is caused by the runtime error, if any.
+    pub fn from_runtime_error(e: &RuntimeError) -> Option<Self> {
+        match e.reason {
+            RuntimeErrorType::PreconditionViolated(_, _)
+            | RuntimeErrorType::PostconditionViolated(_, _)
+            | RuntimeErrorType::AssertionFailed(_, _, _) => Some(Self::AssertionFailed),
+            _ => None,
+        }
+    }
+}
+
+impl Display for HookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BreakpointHit => write!(f, "breakpoint_hit"),
+            Self::AssertionFailed => write!(f, "assertion_failed"),
+            Self::ProgramFinished => write!(f, "program_finished"),
+        }
+    }
+}
+
+/// Action that is performed when a hook is triggered.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookAction {
+    /// Rings the terminal bell.
+    Bell,
+    /// Runs the command with the shell of the system, the output of the command is discarded.
+    ///
+    /// The name of the event is available in the environment variable `ALPHA_TUI_EVENT`.
+    Command(String),
+}
+
+impl HookAction {
+    /// Performs the action, errors are ignored as they can not be displayed while the tui is running.
+    fn perform(&self, event: HookEvent) {
+        match self {
+            Self::Bell => {
+                let mut stdout = io::stdout();
+                _ = stdout.write_all(b"\x07");
+                _ = stdout.flush();
+            }
+            Self::Command(command) => {
+                let (shell, arg) = if cfg!(windows) {
+                    ("cmd", "/C")
+                } else {
+                    ("sh", "-c")
+                };
+                let child = Command::new(shell)
+                    .args([arg, command])
+                    .env("ALPHA_TUI_EVENT", event.to_string())
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn();
+                if let Ok(mut child) = child {
+                    // wait in the background so that the tui is not blocked
+                    std::thread::spawn(move || child.wait());
+                }
+            }
+        }
+    }
+}
+
+/// Hooks that are triggered on selected events of the tui.
+///
+/// Loaded from `hooks.json` in the config directory, for example:
+///
+/// ```json
+/// {
+///     "breakpoint_hit": "bell",
+///     "program_finished": { "command": "notify-send alpha_tui finished" }
+/// }
+/// ```
+#[derive(Debug, Default, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hooks {
+    pub breakpoint_hit: Option<HookAction>,
+    pub assertion_failed: Option<HookAction>,
+    pub program_finished: Option<HookAction>,
+}
+
+impl Hooks {
+    /// Returns the action that is configured for the event.
+    pub fn action(&self, event: HookEvent) -> Option<&HookAction> {
+        match event {
+            HookEvent::BreakpointHit => self.breakpoint_hit.as_ref(),
+            HookEvent::AssertionFailed => self.assertion_failed.as_ref(),
+            HookEvent::ProgramFinished => self.program_finished.as_ref(),
+        }
+    }
+
+    /// Performs the action that is configured for the event, if any.
+    pub fn trigger(&self, event: HookEvent) {
+        if let Some(action) = self.action(event) {
+            action.perform(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::error_handling::{RuntimeError, RuntimeErrorType};
+
+    use super::{HookAction, HookEvent, Hooks};
+
+    #[test]
+    fn test_parse_hooks() {
+        let hooks: Hooks = serde_json::from_str(
+            r#"{"breakpoint_hit": "bell", "program_finished": {"command": "echo done"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            hooks.action(HookEvent::BreakpointHit),
+            Some(&HookAction::Bell)
+        );
+        assert_eq!(hooks.action(HookEvent::AssertionFailed), None);
+        assert_eq!(
+            hooks.action(HookEvent::ProgramFinished),
+            Some(&HookAction::Command("echo done".to_string()))
+        );
+        assert!(serde_json::from_str::<Hooks>(r#"{"step": "bell"}"#).is_err());
+    }
+
+    #[test]
+    fn test_hook_event_from_runtime_error() {
+        let error = |reason| RuntimeError {
+            reason,
+            line_number: 1,
+        };
+        assert_eq!(
+            HookEvent::from_runtime_error(&error(RuntimeErrorType::PreconditionViolated(
+                "f".to_string(),
+                "a1 > 0".to_string()
+            ))),
+            Some(HookEvent::AssertionFailed)
+        );
+        assert_eq!(
+            HookEvent::from_runtime_error(&error(RuntimeErrorType::AssertionFailed(
+                "a0 == 1".to_string(),
+                0,
+                1
+            ))),
+            Some(HookEvent::AssertionFailed)
+        );
+        assert_eq!(
+            HookEvent::from_runtime_error(&error(RuntimeErrorType::PopFail)),
+            None
+        );
+    }
+}
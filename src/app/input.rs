@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+/// Number of key events that are displayed in the key event overlay.
+const KEY_EVENT_LOG_SIZE: usize = 8;
+
+/// Normalizes key events that are reported differently depending on the platform.
+///
+/// Returns `None` if the key event should be ignored:
+/// - releases are only reported on windows, handling them would trigger every action twice
+/// - repeats, that are sent while a key is held down, are only handled for keys that move the selection or edit text
+///   (`typing` is true when text is entered), holding [b] should not toggle the breakpoint over and over
+///
+/// On windows AltGr is reported as Ctrl+Alt, these modifiers are removed from characters, so that characters like `|`
+/// can be typed on keyboard layouts that require AltGr and are not handled as shortcut.
+pub fn normalize_key_event(mut key: KeyEvent, typing: bool) -> Option<KeyEvent> {
+    match key.kind {
+        KeyEventKind::Release => return None,
+        KeyEventKind::Repeat if !accepts_repeat(&key, typing) => return None,
+        _ => (),
+    }
+    if matches!(key.code, KeyCode::Char(_))
+        && key
+            .modifiers
+            .contains(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    {
+        key.modifiers
+            .remove(KeyModifiers::CONTROL | KeyModifiers::ALT);
+    }
+    Some(key)
+}
+
+/// Returns true if the key can be handled repeatedly while it is held down.
+fn accepts_repeat(key: &KeyEvent, typing: bool) -> bool {
+    match key.code {
+        KeyCode::Up
+        | KeyCode::Down
+        | KeyCode::Left
+        | KeyCode::Right
+        | KeyCode::PageUp
+        | KeyCode::PageDown
+        | KeyCode::Backspace
+        | KeyCode::Delete => true,
+        KeyCode::Char(_) => typing,
+        _ => false,
+    }
+}
+
+/// Last key events as they were received from the terminal, displayed in the key event overlay.
+///
+/// Used to diagnose keybindings that don't work or that are triggered twice on some platforms or keyboard layouts.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct KeyEventLog {
+    events: VecDeque<KeyEvent>,
+}
+
+impl KeyEventLog {
+    /// Records the key event, before it is normalized.
+    pub fn record(&mut self, key: KeyEvent) {
+        if self.events.len() == KEY_EVENT_LOG_SIZE {
+            self.events.pop_front();
+        }
+        self.events.push_back(key);
+    }
+
+    /// Returns the lines displayed in the overlay, the latest event is the last line.
+    pub fn lines(&self) -> Vec<String> {
+        self.events
+            .iter()
+            .map(|key| {
+                let modifiers = key
+                    .modifiers
+                    .iter_names()
+                    .map(|f| f.0)
+                    .collect::<Vec<_>>()
+                    .join("+");
+                format!("{:?} {:?} {modifiers}", key.kind, key.code)
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+
+    use super::{normalize_key_event, KeyEventLog, KEY_EVENT_LOG_SIZE};
+
+    fn key(code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers,
+            kind,
+            state: KeyEventState::NONE,
+        }
+    }
+
+    #[test]
+    fn test_normalize_key_event() {
+        let press = key(KeyCode::Char('b'), KeyModifiers::NONE, KeyEventKind::Press);
+        assert_eq!(normalize_key_event(press, false), Some(press));
+        let release = key(
+            KeyCode::Char('b'),
+            KeyModifiers::NONE,
+            KeyEventKind::Release,
+        );
+        assert_eq!(normalize_key_event(release, false), None);
+        let repeat = key(KeyCode::Char('b'), KeyModifiers::NONE, KeyEventKind::Repeat);
+        assert_eq!(normalize_key_event(repeat, false), None);
+        assert_eq!(normalize_key_event(repeat, true), Some(repeat));
+        let repeat = key(KeyCode::Down, KeyModifiers::NONE, KeyEventKind::Repeat);
+        assert_eq!(normalize_key_event(repeat, false), Some(repeat));
+        // AltGr
+        let alt_gr = key(
+            KeyCode::Char('|'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+            KeyEventKind::Press,
+        );
+        assert_eq!(
+            normalize_key_event(alt_gr, true).unwrap().modifiers,
+            KeyModifiers::NONE
+        );
+        let ctrl = key(
+            KeyCode::Char('r'),
+            KeyModifiers::CONTROL,
+            KeyEventKind::Press,
+        );
+        assert_eq!(normalize_key_event(ctrl, false), Some(ctrl));
+    }
+
+    #[test]
+    fn test_key_event_log() {
+        let mut log = KeyEventLog::default();
+        log.record(key(
+            KeyCode::Char('r'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+            KeyEventKind::Press,
+        ));
+        log.record(key(
+            KeyCode::Enter,
+            KeyModifiers::NONE,
+            KeyEventKind::Release,
+        ));
+        assert_eq!(
+            log.lines(),
+            vec!["Press Char('r') CONTROL+ALT", "Release Enter"]
+        );
+        for _ in 0..KEY_EVENT_LOG_SIZE {
+            log.record(key(KeyCode::Up, KeyModifiers::NONE, KeyEventKind::Repeat));
+        }
+        assert_eq!(log.lines().len(), KEY_EVENT_LOG_SIZE);
+        assert_eq!(log.lines()[0], "Repeat Up");
+    }
+}
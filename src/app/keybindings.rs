@@ -1,17 +1,57 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
 
 use anyhow::{anyhow, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     text::{Line, Span},
     widgets::Paragraph,
 };
 
-use super::{ui::style::SharedTheme, State};
+use super::{
+    content::{MemoryPanel, ValueFormat},
+    ui::style::SharedTheme,
+    State,
+};
+
+/// Maximum number of lines that the keybinding hints can take up, if more lines are required the hints are split into pages.
+const MAX_HINT_LINES: usize = 2;
+/// Width that is reserved for the page indicator, when the keybinding hints are split into pages.
+const PAGE_INDICATOR_WIDTH: usize = 18;
 
 /// Manages all keybinding hints.
 pub struct KeybindingHints {
     hints: HashMap<String, KeybindingHint>,
     theme: SharedTheme,
+    /// Page of keybinding hints that is currently shown.
+    page: usize,
+    /// If true the keybinding hints can be split into pages.
+    ///
+    /// Is false when text is entered, because the key to switch pages can't be used then.
+    pageable: bool,
+    /// `None` if the auto-run is not available, otherwise true if it is active.
+    auto_run: Option<bool>,
+    /// If false [o] and [f] are hidden, because the program does not contain functions.
+    call_stepping: bool,
+    /// If false [y] is hidden, because the gamma accumulator is disabled.
+    gamma: bool,
+    /// Memory panel that is scrolled with [PgUp] and [PgDn], `None` hides the keys to scroll, because all entries
+    /// of the memory panels are shown.
+    scrolled_panel: Option<MemoryPanel>,
+    /// If false [z] is hidden, because at most one memory panel can be scrolled.
+    panel_switching: bool,
+    /// Number system in which the values are displayed, determines the label of [#].
+    value_format: ValueFormat,
+    /// Keys that are remapped by the user, the hints show the remapped keys.
+    keymap: Keymap,
+    /// If true the help overlay is open, only the keys to scroll and close it are shown.
+    help: bool,
+    /// If false [s] is hidden in the quit prompt, because none of the unsaved changes can be saved.
+    savable: bool,
+    /// Keys and labels of the actions that are available in the current state, listed in the help overlay.
+    help_entries: Vec<(String, String)>,
 }
 
 impl KeybindingHints {
@@ -19,46 +59,101 @@ impl KeybindingHints {
         Ok(Self {
             hints: default_keybindings()?,
             theme,
+            page: 0,
+            pageable: true,
+            auto_run: Some(false),
+            call_stepping: true,
+            gamma: false,
+            scrolled_panel: None,
+            panel_switching: false,
+            value_format: ValueFormat::Decimal,
+            keymap: Keymap::default(),
+            help: false,
+            savable: true,
+            help_entries: Vec::new(),
         })
     }
 
+    /// Sets the keys that are remapped by the user, so that the hints show the remapped keys.
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
     /// Returns the keybinding hint paragraph ready to be printed.
     ///
     /// `width` is used to determine how many keybinding hints can be printed in one line.
+    /// If the hints don't fit into `MAX_HINT_LINES` lines, they are split into pages and only the current page is returned.
     ///
     /// Return value `u16` is the amount of lines that this paragraph contains.
-    pub fn keybinding_hint_paragraph(&self, width: u16) -> (Paragraph, u16) {
+    pub fn keybinding_hint_paragraph(&self, width: u16) -> (Paragraph<'_>, u16) {
         let mut active_hints = self.active_keybinds();
-        active_hints.sort_by_key(|f| f.order());
+        // enabled hints are more relevant, so they are displayed first
+        active_hints
+            .sort_by(|a, b| (!a.enabled, a.order(), &a.key).cmp(&(!b.enabled, b.order(), &b.key)));
+        let mut lines = self.hint_lines(&active_hints, width as usize);
+        if self.pageable && lines.len() > MAX_HINT_LINES {
+            // reserve space for page indicator
+            lines = self.hint_lines(
+                &active_hints,
+                (width as usize).saturating_sub(PAGE_INDICATOR_WIDTH),
+            );
+            let pages = lines.len().div_ceil(MAX_HINT_LINES);
+            let page = self.page % pages;
+            lines = lines
+                .into_iter()
+                .skip(page * MAX_HINT_LINES)
+                .take(MAX_HINT_LINES)
+                .collect();
+            if let Some(last) = lines.last_mut() {
+                last.push(Span::from(" ").style(self.theme.keybinding_hint_paragraph()));
+                last.push(
+                    Span::from(format!(
+                        "More [{}] ({}/{})",
+                        self.keymap.display("h"),
+                        page + 1,
+                        pages
+                    ))
+                    .style(self.theme.keybinding_hints(true)),
+                );
+            }
+        }
+
+        let hight = lines.len() as u16;
+        let keybinding_hints =
+            Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+                .style(self.theme.keybinding_hint_paragraph());
+        (keybinding_hints, hight)
+    }
+
+    /// Splits the hints into lines, so that each line is at most `width` characters long.
+    fn hint_lines(&self, hints: &[KeybindingHint], width: usize) -> Vec<Vec<Span<'static>>> {
         let mut styled_keybinds_row = Vec::new();
         let mut styled_keybinds = Vec::new();
-        let mut first_hint = true;
         let mut line_length = 0;
-        for hint in &active_hints {
-            if first_hint {
-                first_hint = false;
-            } else {
-                styled_keybinds_row
-                    .push(Span::from(" ").style(self.theme.keybinding_hint_paragraph()));
-                line_length += 1;
-            }
-            let text = format!("{} [{}]", hint.label(), hint.key);
-            let remaining_space = usize::saturating_sub(width as usize, text.len());
-            if line_length > remaining_space {
-                line_length = 0;
-                styled_keybinds.push(Line::from(styled_keybinds_row));
-                styled_keybinds_row = Vec::new();
+        for hint in hints {
+            let text = format!("{} [{}]", hint.label(), self.keymap.display(&hint.key));
+            if !styled_keybinds_row.is_empty() {
+                if line_length + 1 > usize::saturating_sub(width, text.len()) {
+                    line_length = 0;
+                    styled_keybinds.push(styled_keybinds_row);
+                    styled_keybinds_row = Vec::new();
+                } else {
+                    styled_keybinds_row
+                        .push(Span::from(" ").style(self.theme.keybinding_hint_paragraph()));
+                    line_length += 1;
+                }
             }
             line_length += text.len();
             styled_keybinds_row
                 .push(Span::from(text).style(self.theme.keybinding_hints(hint.enabled)));
         }
-        styled_keybinds.push(Line::from(styled_keybinds_row));
+        styled_keybinds.push(styled_keybinds_row);
+        styled_keybinds
+    }
 
-        let hight = styled_keybinds.len() as u16;
-        let keybinding_hints =
-            Paragraph::new(styled_keybinds).style(self.theme.keybinding_hint_paragraph());
-        (keybinding_hints, hight)
+    /// Shows the next page of keybinding hints, if the hints are split into multiple pages.
+    pub fn next_page(&mut self) {
+        self.page = self.page.wrapping_add(1);
     }
 
     /// Returns a list of keybinding hints that are currently active.
@@ -118,6 +213,38 @@ impl KeybindingHints {
         }
     }
 
+    /// Shows the keybinding hint to switch the number system of the values, the label names the next number system.
+    fn show_value_format(&mut self) -> Result<()> {
+        let state = match self.value_format {
+            ValueFormat::Decimal => 0,
+            ValueFormat::Hexadecimal => 1,
+            ValueFormat::Binary => 2,
+        };
+        self.show_and_enable("#");
+        self.set_state("#", state)
+    }
+
+    /// Shows the keybinding hints to scroll the memory panels, if one can be scrolled.
+    fn show_scrolling(&mut self) -> Result<()> {
+        let Some(panel) = self.scrolled_panel else {
+            return Ok(());
+        };
+        let state = match panel {
+            MemoryPanel::Stack => 0,
+            MemoryPanel::MemoryCells => 1,
+            MemoryPanel::Accumulators => 2,
+            MemoryPanel::Output => 3,
+        };
+        self.show_and_enable("PgUp|PgDn");
+        self.set_state("PgUp|PgDn", state)?;
+        self.show_and_enable("Home|End");
+        self.set_state("Home|End", state)?;
+        if self.panel_switching {
+            self.show_and_enable("z");
+        }
+        Ok(())
+    }
+
     /// Checks the status of the keybinding hint.
     ///
     /// Returns `true` if the keybinding hint is shown or `false` if the keybinding hint is hidden or was not found.
@@ -139,10 +266,73 @@ impl KeybindingHints {
         Ok(())
     }
 
+    /// Sets whether the auto-run is active, `None` if it is not available.
+    ///
+    /// Takes effect on the next call of [`Self::update`].
+    pub fn set_auto_run(&mut self, auto_run: Option<bool>) {
+        self.auto_run = auto_run;
+    }
+
+    /// Sets whether the program contains functions that can be stepped over or out of.
+    ///
+    /// Takes effect on the next call of [`Self::update`].
+    pub fn set_call_stepping(&mut self, call_stepping: bool) {
+        self.call_stepping = call_stepping;
+    }
+
+    /// Sets whether the gamma accumulator is enabled, its panel can only be toggled if it is.
+    ///
+    /// Takes effect on the next call of [`Self::update`].
+    pub fn set_gamma(&mut self, gamma: bool) {
+        self.gamma = gamma;
+    }
+
+    /// Sets whether the help overlay is open.
+    ///
+    /// Takes effect on the next call of [`Self::update`].
+    pub fn set_help(&mut self, help: bool) {
+        self.help = help;
+    }
+
+    /// Sets whether the unsaved changes can be saved from the quit prompt.
+    ///
+    /// Takes effect on the next call of [`Self::update`].
+    pub fn set_savable(&mut self, savable: bool) {
+        self.savable = savable;
+    }
+
+    /// Returns the keys and labels of the actions that are available in the current state, in the order in which
+    /// they are shown in the keybinding hints.
+    pub fn help_entries(&self) -> &[(String, String)] {
+        &self.help_entries
+    }
+
+    /// Sets the number system in which the values are displayed.
+    ///
+    /// Takes effect on the next call of [`Self::update`].
+    pub fn set_value_format(&mut self, value_format: ValueFormat) {
+        self.value_format = value_format;
+    }
+
+    /// Sets the memory panel that is scrolled with [PgUp] and [PgDn], `None` if no panel has more entries than fit
+    /// into it. `panel_switching` is true if more than one panel can be scrolled.
+    ///
+    /// Takes effect on the next call of [`Self::update`].
+    pub fn set_scrolled_panel(&mut self, panel: Option<MemoryPanel>, panel_switching: bool) {
+        self.scrolled_panel = panel;
+        self.panel_switching = panel_switching;
+    }
+
     /// Sets all keybinding hints depending on the current state of the application.
     pub fn update(&mut self, state: &State) -> Result<()> {
+        let shown_before = self.shown_keys();
         // reset keybinding hints to be able to configure them properly for current app state
         self.hints.values_mut().for_each(|x| x.reset());
+        self.pageable = match state {
+            State::CustomInstruction(_) | State::Playground(_) => false,
+            State::EditMemory(_, edit) => !edit.is_typing(),
+            _ => true,
+        };
 
         // set more specific keybinding hints
         match state {
@@ -153,32 +343,109 @@ impl KeybindingHints {
                 self.show_and_enable("d");
                 self.show_and_enable("i");
                 self.show_and_enable("c");
+                self.show_and_enable("v");
+                self.show_and_enable("x");
+                self.show_and_enable("k");
+                self.show_value_format()?;
+                if self.gamma {
+                    self.show_and_enable("y");
+                }
             }
             State::Running(breakpoint_set) => {
                 self.show_and_enable("q");
                 self.show_and_enable("n");
+                if self.call_stepping {
+                    self.show_and_enable("o");
+                    self.show_and_enable("f");
+                }
                 self.show_and_enable("d");
                 self.show_and_enable("t");
                 self.show_and_enable("i");
                 self.show_and_enable("c");
+                self.show_and_enable("v");
+                self.show_and_enable("x");
+                self.show_and_enable("k");
+                self.show_value_format()?;
+                if self.gamma {
+                    self.show_and_enable("y");
+                }
+                self.show_and_enable("m");
+                self.set_state("m", 0)?;
                 self.show_and_enable("r");
+                if let Some(active) = self.auto_run {
+                    self.show_and_enable("p");
+                    self.set_state("p", usize::from(active))?;
+                }
+                self.show_and_enable("a");
+                self.show_and_enable("u");
+                self.show_and_enable("C-r");
                 if *breakpoint_set {
                     self.set_state("r", 1)?;
                 }
+                self.show_scrolling()?;
             }
             State::DebugSelect(_, _) => {
                 self.show_and_enable("q");
                 self.show_and_enable("d");
                 self.show_and_enable("c");
+                self.show_and_enable("v");
+                self.show_and_enable("x");
+                self.show_and_enable("k");
+                self.show_value_format()?;
+                if self.gamma {
+                    self.show_and_enable("y");
+                }
                 self.show_and_enable("b");
+                self.show_and_enable("e");
+                self.show_and_enable("a");
                 self.show_and_enable("j");
+                self.show_and_enable("r");
+                self.set_state("r", 2)?;
+                self.show_and_enable("l");
+                self.show_and_enable("u");
+                self.show_and_enable("C-r");
                 self.show_and_enable(&KeySymbol::ArrowUp.to_string());
                 self.show_and_enable(&KeySymbol::ArrowDown.to_string());
+                self.show_and_enable(&KeySymbol::Enter.to_string());
+                self.set_state(&KeySymbol::Enter.to_string(), 7)?;
+                self.show_and_enable("C-o");
+                self.show_and_enable("C-i");
                 self.set_state("d", 1)?;
+                self.show_scrolling()?;
+            }
+            State::Labels(_, _) => {
+                self.show_and_enable(&KeySymbol::Enter.to_string());
+                self.set_state(&KeySymbol::Enter.to_string(), 4)?;
+                self.show_and_enable(&KeySymbol::Escape.to_string());
+                self.set_state(&KeySymbol::Escape.to_string(), 0)?;
+                self.show_and_enable(&KeySymbol::ArrowUp.to_string());
+                self.show_and_enable(&KeySymbol::ArrowDown.to_string());
+                self.show_and_enable("u");
+                self.show_and_enable("C-r");
+                self.show_and_enable("l");
+                self.set_state("l", 1)?;
+                self.show_and_enable("j");
+                self.set_state("j", 1)?;
+            }
+            State::EditMemory(_, edit) => {
+                self.show_and_enable(&KeySymbol::Enter.to_string());
+                self.show_and_enable(&KeySymbol::Escape.to_string());
+                self.set_state(&KeySymbol::Escape.to_string(), 0)?;
+                if edit.is_typing() {
+                    self.set_state(&KeySymbol::Enter.to_string(), 6)?;
+                } else {
+                    self.set_state(&KeySymbol::Enter.to_string(), 5)?;
+                    self.show_and_enable(&KeySymbol::ArrowUp.to_string());
+                    self.show_and_enable(&KeySymbol::ArrowDown.to_string());
+                    self.show_and_enable("m");
+                    self.set_state("m", 1)?;
+                }
             }
             State::Finished(message_shown) => {
                 self.show_and_enable("q");
                 self.show_and_enable("t");
+                self.show_value_format()?;
+                self.show_scrolling()?;
                 if *message_shown {
                     self.show_and_enable("d");
                 } else {
@@ -203,6 +470,13 @@ impl KeybindingHints {
                 self.set_state(&KeySymbol::Enter.to_string(), 2)?;
                 self.show(&KeySymbol::Enter.to_string());
             }
+            State::QuitPrompt(_) => {
+                if self.savable {
+                    self.show_and_enable("s");
+                    self.set_state("s", 1)?;
+                }
+                self.show_and_enable(&KeySymbol::Escape.to_string());
+            }
             State::CustomInstruction(state) => {
                 self.show_and_enable(&KeySymbol::Enter.to_string());
                 self.show_and_enable(&KeySymbol::Escape.to_string());
@@ -264,10 +538,53 @@ impl KeybindingHints {
                 } else {
                     self.disable(&KeySymbol::ArrowUp.to_string());
                 }
+                self.show_and_enable("C-t");
+                self.show_and_enable("C-s");
+            }
+            State::Timeline(_, _) => {
+                self.show_and_enable(&KeySymbol::Enter.to_string());
+                self.set_state(&KeySymbol::Enter.to_string(), 3)?;
+                self.show_and_enable(&KeySymbol::Escape.to_string());
+                self.set_state(&KeySymbol::Escape.to_string(), 0)?;
+                self.show_and_enable(&KeySymbol::ArrowUp.to_string());
+                self.show_and_enable(&KeySymbol::ArrowDown.to_string());
+                self.show_and_enable("u");
+                self.show_and_enable("C-r");
+                self.show_and_enable("C-t");
+                self.set_state("C-t", 1)?;
+                self.show_and_enable("C-s");
             }
         }
+        // the help overlay lists the actions of the state, it is generated from the hints so that it can't drift
+        let mut active_hints = self.active_keybinds();
+        active_hints.retain(|f| f.enabled);
+        active_hints.sort_by(|a, b| (a.order(), &a.key).cmp(&(b.order(), &b.key)));
+        self.help_entries = active_hints
+            .iter()
+            .map(|f| (self.keymap.display(&f.key), f.label()))
+            .collect();
+        if self.help {
+            self.hints.values_mut().for_each(|x| x.reset());
+            self.show_and_enable(&KeySymbol::ArrowUp.to_string());
+            self.show_and_enable(&KeySymbol::ArrowDown.to_string());
+            self.show_and_enable("?");
+            self.set_state("?", 1)?;
+        } else if self.pageable && !matches!(state, State::QuitPrompt(_)) {
+            self.show_and_enable("?");
+        }
+        // show first page again, when different hints are shown
+        if shown_before != self.shown_keys() {
+            self.page = 0;
+        }
         Ok(())
     }
+
+    /// Returns the keys of all keybinding hints that are shown.
+    fn shown_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.active_keybinds().into_iter().map(|f| f.key).collect();
+        keys.sort();
+        keys
+    }
 }
 
 /// Returns the default keybindings.
@@ -277,19 +594,28 @@ fn default_keybindings() -> Result<HashMap<String, KeybindingHint>> {
         "q".to_string(),
         KeybindingHint::new(0, &format!("q|{}", KeySymbol::Escape), "Quit"),
     );
-    hints.insert("s".to_string(), KeybindingHint::new(2, "s", "Start"));
+    hints.insert(
+        "s".to_string(),
+        KeybindingHint::new_many(vec![2, 2], "s", vec!["Start", "Save and quit"])?,
+    );
     hints.insert(
         "n".to_string(),
         KeybindingHint::new_many(vec![4], "n", vec!["Run next instruction"])?,
     );
+    hints.insert("o".to_string(), KeybindingHint::new(4, "o", "Step over"));
+    hints.insert("f".to_string(), KeybindingHint::new(4, "f", "Step out"));
     hints.insert(
         "r".to_string(),
         KeybindingHint::new_many(
-            vec![2, 2],
+            vec![2, 2, 12],
             "r",
-            vec!["Run to end", "Run to next breakpoint"],
+            vec!["Run to end", "Run to next breakpoint", "Run to line"],
         )?,
     );
+    hints.insert(
+        "p".to_string(),
+        KeybindingHint::new_many(vec![3, 3], "p", vec!["Start auto-run", "Pause auto-run"])?,
+    );
     hints.insert(
         "d".to_string(),
         KeybindingHint::new_many(
@@ -307,22 +633,118 @@ fn default_keybindings() -> Result<HashMap<String, KeybindingHint>> {
         "b".to_string(),
         KeybindingHint::new(8, "b", "Toggle breakpoint"),
     );
+    hints.insert(
+        "e".to_string(),
+        KeybindingHint::new(9, "e", "Enable/disable breakpoint"),
+    );
+    hints.insert(
+        "a".to_string(),
+        KeybindingHint::new(11, "a", "Enable/disable all breakpoints"),
+    );
     hints.insert(
         "j".to_string(),
-        KeybindingHint::new(11, "j", "Jump to line"),
+        KeybindingHint::new_many(vec![12, 5], "j", vec!["Jump to line", "Jump to label"])?,
+    );
+    hints.insert("u".to_string(), KeybindingHint::new(15, "u", "Undo"));
+    hints.insert("C-r".to_string(), KeybindingHint::new(16, "C-r", "Redo"));
+    hints.insert(
+        "C-o".to_string(),
+        KeybindingHint::new(17, "C-o", "Jump back"),
+    );
+    hints.insert(
+        "C-i".to_string(),
+        KeybindingHint::new(18, "C-i", "Jump forward"),
+    );
+    hints.insert(
+        "l".to_string(),
+        KeybindingHint::new_many(vec![10, 10], "l", vec!["Show labels", "Hide labels"])?,
+    );
+    hints.insert(
+        "y".to_string(),
+        KeybindingHint::new(10, "y", "Toggle gamma"),
+    );
+    hints.insert(
+        "v".to_string(),
+        KeybindingHint::new(10, "v", "Toggle hit counts"),
+    );
+    hints.insert(
+        "x".to_string(),
+        KeybindingHint::new(10, "x", "Toggle machine view"),
+    );
+    hints.insert(
+        "m".to_string(),
+        KeybindingHint::new_many(
+            vec![10, 10],
+            "m",
+            vec!["Edit memory", "Close memory editor"],
+        )?,
+    );
+    hints.insert(
+        "C-t".to_string(),
+        KeybindingHint::new_many(vec![8, 8], "C-t", vec!["Show timeline", "Hide timeline"])?,
+    );
+    hints.insert(
+        "C-s".to_string(),
+        KeybindingHint::new(6, "C-s", "Save as program"),
     );
     hints.insert(
         KeySymbol::ArrowUp.to_string(),
-        KeybindingHint::new(12, &KeySymbol::ArrowUp.to_string(), "Up"),
+        KeybindingHint::new(13, &KeySymbol::ArrowUp.to_string(), "Up"),
     );
     hints.insert(
         KeySymbol::ArrowDown.to_string(),
-        KeybindingHint::new(13, &KeySymbol::ArrowDown.to_string(), "Down"),
+        KeybindingHint::new(14, &KeySymbol::ArrowDown.to_string(), "Down"),
     );
     hints.insert(
         "i".to_string(),
         KeybindingHint::new(9, "i", "Run custom instruction"),
     );
+    hints.insert(
+        "PgUp|PgDn".to_string(),
+        KeybindingHint::new_many(
+            vec![19, 19, 19, 19],
+            "PgUp|PgDn",
+            vec![
+                "Scroll stack",
+                "Scroll memory cells",
+                "Scroll accumulators",
+                "Scroll output",
+            ],
+        )?,
+    );
+    hints.insert(
+        "Home|End".to_string(),
+        KeybindingHint::new_many(
+            vec![20, 20, 20, 20],
+            "Home|End",
+            vec![
+                "Stack top/bottom",
+                "Memory cells top/bottom",
+                "Accumulators top/bottom",
+                "Output top/bottom",
+            ],
+        )?,
+    );
+    hints.insert(
+        "k".to_string(),
+        KeybindingHint::new(22, "k", "Toggle value history"),
+    );
+    hints.insert(
+        "#".to_string(),
+        KeybindingHint::new_many(
+            vec![22, 22, 22],
+            "#",
+            vec![
+                "Show hexadecimal values",
+                "Show binary values",
+                "Show decimal values",
+            ],
+        )?,
+    );
+    hints.insert(
+        "z".to_string(),
+        KeybindingHint::new(21, "z", "Switch scrolled panel"),
+    );
     hints.insert(
         "c".to_string(),
         KeybindingHint::new(10, "c", "Toggle call stack"),
@@ -338,12 +760,17 @@ fn default_keybindings() -> Result<HashMap<String, KeybindingHint>> {
     hints.insert(
         KeySymbol::Enter.to_string(),
         KeybindingHint::new_many(
-            vec![5, 5, 5],
+            vec![5, 5, 5, 5, 5, 5, 5, 13],
             &KeySymbol::Enter.to_string(),
             vec![
                 "Run entered instruction",
                 "Run selected instruction",
                 "Close",
+                "Roll back to selected",
+                "Toggle breakpoints of label",
+                "Edit value",
+                "Set value",
+                "Go to jump target",
             ],
         )?,
     );
@@ -355,6 +782,10 @@ fn default_keybindings() -> Result<HashMap<String, KeybindingHint>> {
             vec!["Cancel", "Exit"],
         )?,
     );
+    hints.insert(
+        "?".to_string(),
+        KeybindingHint::new_many(vec![1, 0], "?", vec!["Help", "Close help"])?,
+    );
     hints.insert(
         KeySymbol::Tab.to_string(),
         KeybindingHint::new(9, &KeySymbol::Tab.to_string(), "Fill in selected"),
@@ -461,6 +892,149 @@ impl KeybindingHint {
     }
 }
 
+/// Keys that are remapped by the user, loaded from `keybindings.toml` in the config directory.
+///
+/// Each entry maps the default key of an action to the key that is used instead, keys are written like in the
+/// keybinding hints: `s` for a character and `C-s` for a character pressed with Ctrl.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Keymap {
+    /// Remapped keys, by the default key.
+    keys: BTreeMap<Key, Key>,
+}
+
+impl Keymap {
+    /// Parses the keymap from the toml content of `keybindings.toml`, e.g. `C-t = "C-y"`.
+    ///
+    /// Returns an error if a key can not be remapped or if two actions are mapped to the same key, this includes
+    /// the default key of another action that is not remapped itself.
+    pub fn parse(content: &str) -> Result<Self> {
+        let entries = toml::from_str::<BTreeMap<String, String>>(content)?;
+        let mut keys = BTreeMap::new();
+        for (default, remapped) in entries {
+            let default = Key::parse(&default)?;
+            let remapped = Key::parse(&remapped)?;
+            if let Some((other, _)) = keys.iter().find(|(_, key)| **key == remapped) {
+                return Err(anyhow!(
+                    "{default} and {other} can not both be remapped to {remapped}"
+                ));
+            }
+            keys.insert(default, remapped);
+        }
+        let default_keys = default_keys()?;
+        for (default, remapped) in &keys {
+            if remapped != default
+                && !keys.contains_key(remapped)
+                && default_keys.contains(remapped)
+            {
+                return Err(anyhow!(
+                    "{default} can not be remapped to {remapped}, because {remapped} is the key of another action"
+                ));
+            }
+        }
+        Ok(Self { keys })
+    }
+
+    /// Translates the pressed key into the default key of the action it is mapped to.
+    ///
+    /// Returns `None` if the key is a default key that was remapped, so that it does not trigger its action anymore.
+    /// When text is entered (`typing` is true), only keys pressed with Ctrl are translated.
+    pub fn translate(&self, key: KeyEvent, typing: bool) -> Option<KeyEvent> {
+        let Some(pressed) = Key::from_key_event(&key) else {
+            return Some(key);
+        };
+        if typing && !pressed.ctrl {
+            return Some(key);
+        }
+        if let Some((default, _)) = self.keys.iter().find(|(_, remapped)| **remapped == pressed) {
+            return Some(KeyEvent::new_with_kind(
+                KeyCode::Char(default.char),
+                default.modifiers(),
+                key.kind,
+            ));
+        }
+        if self.keys.contains_key(&pressed) {
+            return None;
+        }
+        Some(key)
+    }
+
+    /// Returns the key that is displayed in a keybinding hint for the default key `key`.
+    ///
+    /// Hints that list multiple keys, separated by `|`, are translated key by key.
+    pub fn display(&self, key: &str) -> String {
+        key.split('|')
+            .map(
+                |part| match Key::parse(part).ok().and_then(|f| self.keys.get(&f)) {
+                    Some(remapped) => remapped.to_string(),
+                    None => part.to_string(),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+}
+
+/// Returns the default keys of all actions that can be remapped.
+fn default_keys() -> Result<Vec<Key>> {
+    // keys of actions that have no keybinding hint
+    let mut keys = vec![Key::parse("h")?, Key::parse("w")?, Key::parse("g")?];
+    for hint in default_keybindings()?.values() {
+        keys.extend(hint.key.split('|').filter_map(|key| Key::parse(key).ok()));
+    }
+    Ok(keys)
+}
+
+/// Key that can be remapped in the [`Keymap`], a character that is optionally pressed with Ctrl.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+struct Key {
+    char: char,
+    ctrl: bool,
+}
+
+impl Key {
+    fn parse(key: &str) -> Result<Self> {
+        let (ctrl, rest) = match key.strip_prefix("C-") {
+            Some(rest) => (true, rest),
+            None => (false, key),
+        };
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some(char), None) if !char.is_whitespace() => Ok(Self { char, ctrl }),
+            _ => Err(anyhow!(
+                "Unsupported key '{key}', only characters like 's' or 'C-s' can be remapped"
+            )),
+        }
+    }
+
+    fn from_key_event(key: &KeyEvent) -> Option<Self> {
+        match key.code {
+            KeyCode::Char(char) => Some(Self {
+                char,
+                ctrl: key.modifiers.contains(KeyModifiers::CONTROL),
+            }),
+            _ => None,
+        }
+    }
+
+    fn modifiers(self) -> KeyModifiers {
+        if self.ctrl {
+            KeyModifiers::CONTROL
+        } else {
+            KeyModifiers::NONE
+        }
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl {
+            write!(f, "C-{}", self.char)
+        } else {
+            write!(f, "{}", self.char)
+        }
+    }
+}
+
 pub enum KeySymbol {
     ArrowUp,
     ArrowDown,
@@ -489,9 +1063,14 @@ impl Display for KeySymbol {
 mod tests {
     use std::collections::HashMap;
 
-    use crate::app::ui::style::{SharedTheme, Theme};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::app::{
+        content::ValueFormat,
+        ui::style::{SharedTheme, Theme},
+    };
 
-    use super::{KeybindingHint, KeybindingHints};
+    use super::{KeybindingHint, KeybindingHints, Keymap};
 
     fn test_keybinding_hints() -> KeybindingHints {
         let mut hints = HashMap::new();
@@ -502,6 +1081,18 @@ mod tests {
         let mut hints = KeybindingHints {
             hints,
             theme: SharedTheme::new(Theme::default()),
+            page: 0,
+            pageable: true,
+            auto_run: Some(false),
+            call_stepping: true,
+            gamma: false,
+            scrolled_panel: None,
+            panel_switching: false,
+            value_format: ValueFormat::Decimal,
+            keymap: Keymap::default(),
+            help: false,
+            savable: true,
+            help_entries: Vec::new(),
         };
         hints.show_and_enable("a");
         hints.enable("c");
@@ -635,4 +1226,55 @@ mod tests {
                 true
             )));
     }
+
+    #[test]
+    fn test_keybinding_hints_pages() {
+        let mut hints = test_keybinding_hints();
+        hints.show_and_enable("b");
+        hints.show_and_enable("c");
+        assert_eq!(hints.keybinding_hint_paragraph(100).1, 1);
+        assert_eq!(hints.hint_lines(&hints.active_keybinds(), 20).len(), 4);
+        assert_eq!(hints.keybinding_hint_paragraph(30).1, 2);
+        hints.next_page();
+        assert_eq!(hints.keybinding_hint_paragraph(30).1, 2);
+        hints.pageable = false;
+        assert_eq!(hints.keybinding_hint_paragraph(30).1, 4);
+    }
+
+    #[test]
+    fn test_keymap() {
+        let keymap = Keymap::parse("s = \"S\"\nC-t = \"C-y\"").unwrap();
+        let key = |code, modifiers| KeyEvent::new(code, modifiers);
+        assert_eq!(
+            keymap.translate(key(KeyCode::Char('S'), KeyModifiers::NONE), false),
+            Some(key(KeyCode::Char('s'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            keymap.translate(key(KeyCode::Char('s'), KeyModifiers::NONE), false),
+            None
+        );
+        assert_eq!(
+            keymap.translate(key(KeyCode::Char('y'), KeyModifiers::CONTROL), true),
+            Some(key(KeyCode::Char('t'), KeyModifiers::CONTROL))
+        );
+        // characters are entered as text while typing
+        assert_eq!(
+            keymap.translate(key(KeyCode::Char('S'), KeyModifiers::NONE), true),
+            Some(key(KeyCode::Char('S'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            keymap.translate(key(KeyCode::Enter, KeyModifiers::NONE), false),
+            Some(key(KeyCode::Enter, KeyModifiers::NONE))
+        );
+        assert_eq!(keymap.display("C-t"), "C-y");
+        assert_eq!(keymap.display("s|n"), "S|n");
+        assert!(Keymap::parse("s = \"S\"\nn = \"S\"").is_err());
+        // default keys of other actions can only be used when the action is remapped as well
+        assert!(Keymap::parse("s = \"n\"").is_err());
+        assert!(Keymap::parse("s = \"h\"").is_err());
+        assert!(Keymap::parse("s = \"n\"\nn = \"s\"").is_ok());
+        assert!(Keymap::parse("s = \"s\"").is_ok());
+        assert!(Keymap::parse("s = \"PgUp\"").is_err());
+        assert!(Keymap::parse("s = 1").is_err());
+    }
 }
@@ -0,0 +1,282 @@
+use crate::{
+    instructions::{Instruction, Value},
+    runtime::{error_handling::RuntimeErrorType, ControlFlow, Runtime, RuntimeMemory},
+};
+
+/// Last step of the program, presented like the machine model of the lecture, displayed in the machine view panel.
+///
+/// The runtime runs an instruction as a single step, so the view is assembled around it: the instruction and its
+/// operands are fetched before the step is run and the program counter is read after it.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct MachineView {
+    /// Index of the instruction that was run in the last step.
+    fetched: Option<usize>,
+    /// Instruction register, contains the instruction that was run in the last step.
+    instruction_register: Option<String>,
+    /// Calculation or comparison done by the alu in the last step, e.g. `5 + 1 = 6`.
+    alu: Option<String>,
+    /// Locations that were read in the last step.
+    reads: Vec<String>,
+    /// Locations that were written in the last step.
+    writes: Vec<String>,
+}
+
+impl MachineView {
+    /// Fetches the instruction that is run next into the instruction register and calculates the alu inputs and
+    /// output with the current memory, has to be called before the step is run.
+    pub fn fetch(&mut self, runtime: &Runtime) {
+        let idx = runtime.next_instruction_index();
+        *self = Self::default();
+        let Some(instruction) = runtime.instruction(idx) else {
+            return;
+        };
+        self.fetched = Some(idx);
+        self.instruction_register = Some(instruction.to_string());
+        self.alu = alu(
+            instruction,
+            runtime.runtime_memory(),
+            runtime.control_flow(),
+        );
+        self.reads = reads(instruction);
+        self.writes = writes(instruction);
+    }
+
+    /// Completes the step after it was run, the program counter is marked as written if the step jumped.
+    pub fn execute(&mut self, runtime: &Runtime) {
+        if let Some(idx) = self.fetched {
+            if runtime.next_instruction_index() != idx + 1 {
+                self.writes.push("PC".to_string());
+            }
+        }
+    }
+
+    /// Returns the lines displayed in the panel, the program counter contains the line of the instruction that is
+    /// run next.
+    pub fn lines(&self, runtime: &Runtime) -> Vec<String> {
+        let or_none = |f: &Option<String>| f.clone().unwrap_or_else(|| "-".to_string());
+        let or_empty = |f: &[String]| {
+            if f.is_empty() {
+                "-".to_string()
+            } else {
+                f.join(", ")
+            }
+        };
+        vec![
+            format!("PC:    {}", runtime.next_instruction_index() + 1),
+            format!("IR:    {}", or_none(&self.instruction_register)),
+            format!("ALU:   {}", or_none(&self.alu)),
+            format!("Read:  {}", or_empty(&self.reads)),
+            format!("Write: {}", or_empty(&self.writes)),
+        ]
+    }
+}
+
+/// Returns the calculation or comparison of the alu, `None` if the instruction does not use the alu or if one of
+/// the inputs is not set.
+fn alu(
+    instruction: &Instruction,
+    memory: &RuntimeMemory,
+    control_flow: &ControlFlow,
+) -> Option<String> {
+    let value = |v: &Value| v.value(memory, control_flow).ok();
+    let result = |res: Result<i32, RuntimeErrorType>| {
+        res.map_or_else(|_| "error".to_string(), |v| v.to_string())
+    };
+    match instruction {
+        Instruction::Calc(_, a, op, b) => {
+            let (a, b) = (value(a)?, value(b)?);
+            Some(format!("{a} {op} {b} = {}", result(op.calc(a, b))))
+        }
+        Instruction::CalcChain(_, first, operations) => {
+            let first = value(first)?;
+            let mut calculation = first.to_string();
+            let mut res = Ok(first);
+            for (op, v) in operations {
+                let v = value(v)?;
+                calculation.push_str(&format!(" {op} {v}"));
+                res = res.and_then(|x| op.calc(x, v));
+            }
+            Some(format!("{calculation} = {}", result(res)))
+        }
+        Instruction::AssignBuiltIn(_, built_in, args) => {
+            let values = args.iter().map(value).collect::<Option<Vec<_>>>()?;
+            Some(format!(
+                "{built_in}({}) = {}",
+                values
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                result(built_in.call(memory, control_flow, args))
+            ))
+        }
+        Instruction::StackOp(op) => {
+            // the top value of the stack is the right operand
+            let (b, a) = match memory.stack.as_slice() {
+                [.., a, b] => (*b, *a),
+                _ => return None,
+            };
+            Some(format!("{a} {op} {b} = {}", result(op.calc(a, b))))
+        }
+        Instruction::JumpIf(a, cmp, b, _) | Instruction::Assert(a, cmp, b) => {
+            let (a, b) = (value(a)?, value(b)?);
+            Some(format!("{a} {cmp} {b} = {}", cmp.cmp(a, b)))
+        }
+        Instruction::JumpIfCompound(condition, _) | Instruction::JumpIfElse(condition, _, _) => {
+            condition
+                .evaluate(memory, control_flow)
+                .ok()
+                .map(|res| format!("{condition} = {res}"))
+        }
+        // the comparisons of counting loops use the counter, that is changed in the same step
+        Instruction::Loop(line) if line.lowered.len() == 1 => {
+            alu(&line.lowered[0], memory, control_flow)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the locations that are read by the instruction, constants are not listed.
+fn reads(instruction: &Instruction) -> Vec<String> {
+    let values: Vec<&Value> = match instruction {
+        Instruction::Assign(_, v) | Instruction::Print(v) | Instruction::ComputedGoto(v) => {
+            vec![v]
+        }
+        Instruction::Calc(_, a, _, b)
+        | Instruction::JumpIf(a, _, b, _)
+        | Instruction::Assert(a, _, b) => vec![a, b],
+        Instruction::CalcChain(_, first, operations) => std::iter::once(first)
+            .chain(operations.iter().map(|(_, v)| v))
+            .collect(),
+        Instruction::AssignBuiltIn(_, _, args) => args.iter().collect(),
+        Instruction::JumpIfCompound(condition, _) | Instruction::JumpIfElse(condition, _, _) => {
+            condition
+                .comparisons()
+                .into_iter()
+                .flat_map(|(a, _, b)| [a, b])
+                .collect()
+        }
+        Instruction::Push => return vec![Value::Accumulator(0).to_string()],
+        Instruction::Pop
+        | Instruction::StackOp(_)
+        | Instruction::StackCommand(_)
+        | Instruction::Peek(_) => return vec!["stack".to_string()],
+        Instruction::Return => return vec!["call stack".to_string()],
+        Instruction::Macro(call) => return unique(call.body.iter().flat_map(reads)),
+        Instruction::Loop(line) => return unique(line.lowered.iter().flat_map(reads)),
+        Instruction::Goto(_) | Instruction::Call(_) | Instruction::Noop => Vec::new(),
+    };
+    let mut reads: Vec<String> = Vec::new();
+    for value in values {
+        let mut value = value;
+        // the negated value is read
+        while let Value::Negated(v) = value {
+            value = v;
+        }
+        if let Value::Constant(_) = value {
+            continue;
+        }
+        let location = value.to_string();
+        if !reads.contains(&location) {
+            reads.push(location);
+        }
+    }
+    reads
+}
+
+/// Returns the locations that are written by the instruction, without the program counter.
+fn writes(instruction: &Instruction) -> Vec<String> {
+    match instruction {
+        Instruction::Assign(target, _)
+        | Instruction::Calc(target, _, _, _)
+        | Instruction::CalcChain(target, _, _)
+        | Instruction::AssignBuiltIn(target, _, _) => vec![target.to_string()],
+        Instruction::Push | Instruction::StackCommand(_) => vec!["stack".to_string()],
+        Instruction::Peek(target) => vec![target.to_string()],
+        Instruction::Pop | Instruction::StackOp(_) => {
+            vec![Value::Accumulator(0).to_string(), "stack".to_string()]
+        }
+        Instruction::Call(_) | Instruction::Return => vec!["call stack".to_string()],
+        Instruction::Print(_) => vec!["output".to_string()],
+        Instruction::Macro(call) => unique(call.body.iter().flat_map(writes)),
+        Instruction::Loop(line) => unique(line.lowered.iter().flat_map(writes)),
+        Instruction::JumpIf(_, _, _, _)
+        | Instruction::JumpIfCompound(_, _)
+        | Instruction::JumpIfElse(_, _, _)
+        | Instruction::Assert(_, _, _)
+        | Instruction::Goto(_)
+        | Instruction::ComputedGoto(_)
+        | Instruction::Noop => Vec::new(),
+    }
+}
+
+/// Removes the locations that are listed more than once, the first occurrence is kept.
+fn unique(locations: impl Iterator<Item = String>) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::new();
+    for location in locations {
+        if !unique.contains(&location) {
+            unique.push(location);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils;
+
+    use super::MachineView;
+
+    /// Runs `steps` steps of `program` and returns the lines of the machine view after the last step.
+    fn lines_after(program: &str, steps: usize) -> Vec<String> {
+        let mut runtime = test_utils::runtime_from_str(program).unwrap();
+        let mut view = MachineView::default();
+        for _ in 0..steps {
+            view.fetch(&runtime);
+            runtime.step().unwrap();
+            view.execute(&runtime);
+        }
+        view.lines(&runtime)
+    }
+
+    #[test]
+    fn test_machine_view() {
+        assert_eq!(
+            lines_after("a := 5\na := a + 1", 0),
+            ["PC:    1", "IR:    -", "ALU:   -", "Read:  -", "Write: -"]
+        );
+        assert_eq!(
+            lines_after("a := 5\na := a + 1", 2),
+            [
+                "PC:    3",
+                "IR:    a0 := a0 + 1",
+                "ALU:   5 + 1 = 6",
+                "Read:  a0",
+                "Write: a0"
+            ]
+        );
+        assert_eq!(
+            lines_after("a := 5\np(h1) := a * -a", 2)[2..],
+            ["ALU:   5 * -5 = -25", "Read:  a0", "Write: p(h1)"]
+        );
+    }
+
+    #[test]
+    fn test_machine_view_stack_and_jumps() {
+        let program =
+            "a := 2\npush\na := 3\npush\nstack-\nif a < 5 then goto done\na := 0\ndone: a := 1";
+        assert_eq!(
+            lines_after(program, 5)[2..],
+            ["ALU:   2 - 3 = -1", "Read:  stack", "Write: a0, stack"]
+        );
+        assert_eq!(
+            lines_after(program, 6)[..3],
+            [
+                "PC:    8",
+                "IR:    if a0 < 5 then goto done",
+                "ALU:   -1 < 5 = true"
+            ]
+        );
+        assert_eq!(lines_after(program, 6)[4], "Write: PC");
+    }
+}
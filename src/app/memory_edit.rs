@@ -0,0 +1,168 @@
+use std::fmt::Display;
+
+use crate::runtime::RuntimeMemory;
+
+/// Memory location whose value can be edited while a program is debugged.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum MemoryLocation {
+    Accumulator(usize),
+    Gamma,
+    MemoryCell(String),
+    IndexMemoryCell(usize),
+}
+
+impl MemoryLocation {
+    /// Returns all locations of `memory`, in the order in which they are displayed in the ui.
+    pub fn all(memory: &RuntimeMemory) -> Vec<Self> {
+        let mut accumulators = memory.accumulators.keys().copied().collect::<Vec<_>>();
+        accumulators.sort_unstable();
+        let mut memory_cells = memory
+            .program_memory_cells()
+            .map(|f| f.label.clone())
+            .collect::<Vec<_>>();
+        memory_cells.sort();
+        let mut index_memory_cells = memory
+            .index_memory_cells
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        index_memory_cells.sort_unstable();
+        // the gamma accumulator is displayed in its own panel above the accumulators
+        let mut locations = Vec::new();
+        if memory.gamma.is_some() {
+            locations.push(Self::Gamma);
+        }
+        locations.extend(accumulators.into_iter().map(Self::Accumulator));
+        locations.extend(memory_cells.into_iter().map(Self::MemoryCell));
+        locations.extend(index_memory_cells.into_iter().map(Self::IndexMemoryCell));
+        locations
+    }
+
+    /// Returns the value of this location in `memory`, `None` if it is not set or does not exist.
+    pub fn value(&self, memory: &RuntimeMemory) -> Option<i32> {
+        match self {
+            Self::Accumulator(id) => memory.accumulators.get(id).and_then(|f| f.data),
+            Self::Gamma => memory.gamma.flatten(),
+            Self::MemoryCell(label) => memory.memory_cells.get(label).and_then(|f| f.data),
+            Self::IndexMemoryCell(index) => memory.index_memory_cells.get(index).copied().flatten(),
+        }
+    }
+
+    /// Sets the value of this location in `memory`, does nothing if the location does not exist.
+    pub fn set_value(&self, memory: &mut RuntimeMemory, value: Option<i32>) {
+        match self {
+            Self::Accumulator(id) => {
+                if let Some(accumulator) = memory.accumulators.get_mut(id) {
+                    accumulator.data = value;
+                }
+            }
+            Self::Gamma => {
+                if memory.gamma.is_some() {
+                    memory.gamma = Some(value);
+                }
+            }
+            Self::MemoryCell(label) => {
+                if let Some(memory_cell) = memory.memory_cells.get_mut(label) {
+                    memory_cell.data = value;
+                }
+            }
+            Self::IndexMemoryCell(index) => {
+                if let Some(data) = memory.index_memory_cells.get_mut(index) {
+                    *data = value;
+                }
+            }
+        }
+    }
+}
+
+impl Display for MemoryLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Accumulator(id) => write!(f, "α{id}"),
+            Self::Gamma => write!(f, "γ"),
+            Self::MemoryCell(label) => write!(f, "{label}"),
+            Self::IndexMemoryCell(index) => write!(f, "[{index}]"),
+        }
+    }
+}
+
+/// State of the memory editor, in which the values of the memory can be changed while a program is debugged.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct MemoryEdit {
+    /// Index of the selected location, see [`MemoryLocation::all`].
+    pub selected: usize,
+    /// Value that is entered for the selected location, `None` if no value is being entered.
+    pub input: Option<String>,
+}
+
+impl MemoryEdit {
+    /// Returns true if a value is being entered.
+    pub fn is_typing(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /// Adds the character to the input, only characters that can be part of a number are accepted.
+    pub fn push_char(&mut self, c: char) {
+        if let Some(input) = &mut self.input {
+            if c.is_ascii_digit() || (c == '-' && input.is_empty()) {
+                input.push(c);
+            }
+        }
+    }
+
+    /// Parses the input, an empty input clears the value.
+    ///
+    /// Returns `None` if the input is not a valid value.
+    pub fn parse_input(&self) -> Option<Option<i32>> {
+        match self.input.as_deref()? {
+            "" => Some(None),
+            input => input.parse().ok().map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils::runtime_from_str;
+
+    use super::{MemoryEdit, MemoryLocation};
+
+    #[test]
+    fn test_memory_location() {
+        let mut rt = runtime_from_str("a0 := 1\np(h1) := 2\np(3) := 4\ny := 5").unwrap();
+        rt.run().unwrap();
+        let mut memory = rt.runtime_memory().clone();
+        let locations = MemoryLocation::all(&memory);
+        assert_eq!(
+            locations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["γ", "α0", "α1", "α2", "α3", "h0", "h1", "h2", "h3", "[3]"]
+        );
+        assert_eq!(locations[6].value(&memory), Some(2));
+        locations[6].set_value(&mut memory, Some(-7));
+        assert_eq!(memory.memory_cells["h1"].data, Some(-7));
+        MemoryLocation::Gamma.set_value(&mut memory, None);
+        assert_eq!(memory.gamma, Some(None));
+        // locations that don't exist are not created
+        MemoryLocation::IndexMemoryCell(8).set_value(&mut memory, Some(1));
+        assert!(!memory.index_memory_cells.contains_key(&8));
+    }
+
+    #[test]
+    fn test_memory_edit_input() {
+        let mut edit = MemoryEdit {
+            input: Some(String::new()),
+            ..MemoryEdit::default()
+        };
+        assert_eq!(edit.parse_input(), Some(None));
+        for c in "-1x2-".chars() {
+            edit.push_char(c);
+        }
+        assert_eq!(edit.input.as_deref(), Some("-12"));
+        assert_eq!(edit.parse_input(), Some(Some(-12)));
+        edit.input = Some("-".to_string());
+        assert_eq!(edit.parse_input(), None);
+    }
+}
@@ -1,10 +1,18 @@
-use std::borrow::BorrowMut;
+use std::{
+    borrow::BorrowMut,
+    path::Path,
+    sync::mpsc::{Receiver, Sender, TryRecvError},
+    time::{Duration, Instant},
+};
 
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
+};
 use miette::{miette, IntoDiagnostic, Result};
 use ratatui::{
     backend::Backend,
-    layout::{Constraint, Layout, Rect},
+    layout::{Constraint, Layout, Position, Rect},
     text::Line,
     widgets::ListState,
     Terminal,
@@ -12,34 +20,86 @@ use ratatui::{
 
 use crate::{
     instructions::{
-        error_handling::{BuildProgramError, ParseSingleInstructionError},
+        error_handling::{BuildProgramError, BuildProgramErrorTypes, ParseSingleInstructionError},
         instruction_config::InstructionConfig,
+        spec::check_operands,
         Instruction,
     },
-    runtime::{self, error_handling::RuntimeError, Runtime},
+    runtime::{self, error_handling::RuntimeError, state_dump::StateDump, Runtime, RuntimeMemory},
     utils,
 };
 
 use self::{
-    content::{InstructionListStates, MemoryListsManager},
-    keybindings::KeybindingHints,
+    auto_run::AutoRun,
+    content::{BreakpointState, InstructionListStates, MemoryListsManager, MemoryPanel},
+    control::{accepts_control_keys, is_control_key, ShareLog, ShareRole},
+    hooks::{HookEvent, Hooks},
+    input::KeyEventLog,
+    keybindings::{KeybindingHints, Keymap},
+    machine_view::MachineView,
+    memory_edit::{MemoryEdit, MemoryLocation},
+    profiling::{LineProfile, RenderProfile},
+    remote_control::{ControlRequest, ControlResponse, ControlStatus, PendingRequest},
+    repro::Repro,
     run_instruction::SingleInstruction,
+    session::{session_file_path, PanelSession, Session},
+    timeline::Timeline,
     ui::{
         style::SharedTheme,
         syntax_highlighting::{SyntaxHighlighter, ToSpans},
+        MouseAreas,
     },
+    undo::{Snapshot, UndoHistory},
+    value_history::ValueHistory,
 };
 
+/// Stepping of the program with a fixed speed.
+mod auto_run;
 /// Contains all commands that this app can run
 pub mod commands;
 /// Content used to fill the tui elements.
 mod content;
+/// Handoff of the control of a shared session to its viewers.
+mod control;
+/// Commands or the terminal bell that are triggered on selected events.
+pub mod hooks;
+/// Normalization of key events and the key event overlay.
+mod input;
 /// Everything related to keybindings.
 mod keybindings;
+/// Panel that presents the last step like the machine model of the lecture.
+mod machine_view;
+/// Editing of the values of the memory while a program is debugged.
+mod memory_edit;
+/// Linear interface that can be used instead of the tui.
+pub mod plain_ui;
+/// Timings displayed in the profiling overlay.
+mod profiling;
+/// Server that lets external tools drive the debugger.
+mod remote_control;
+/// Recording and replaying of sessions to reproduce bugs.
+mod repro;
 /// Everything related to running a single instruction while a program is loaded.
 mod run_instruction;
+/// Saving and restoring of the debugging setup.
+mod session;
+/// Sharing of a session with viewers that watch it read-only.
+#[cfg(unix)]
+mod share;
+/// Headless driver for the tui, used to test the app, compiled in tests as well so that the feature is not required
+/// for the tests of this crate.
+#[cfg(any(feature = "test-utils", test))]
+pub mod test_utils;
+#[cfg(test)]
+mod tests;
+/// Timeline of the instructions that were run in the playground.
+mod timeline;
 /// Drawing of the ui.
 pub mod ui;
+/// Undo and redo of actions.
+mod undo;
+/// History of the changes of the memory values.
+mod value_history;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum State {
@@ -56,7 +116,7 @@ pub enum State {
     /// Boolean value indicates if this error originates in the playground mode.
     CustomInstructionError(ParseSingleInstructionError, bool),
     /// Indicates that the custom instruction could not be build, because instructions, operations or comparisons
-    /// where used that are not allowed.
+    /// were used that are not allowed.
     BuildProgramError(BuildProgramError),
     // 0 = state to restore to when debug mode is exited
     // 1 = index of instruction that was selected before debug mode was started
@@ -69,6 +129,22 @@ pub enum State {
     RuntimeError(RuntimeError, bool),
     /// Indicates that this app is in playground mode.
     Playground(SingleInstruction),
+    /// Indicates that the user is asked if the session should be saved before the app is exited.
+    ///
+    /// Contains the state to restore to, when quitting is canceled.
+    QuitPrompt(Box<State>),
+    /// Indicates that the timeline of the playground is open.
+    // 0 = state to restore to when the timeline is closed
+    // 1 = index of the selected entry, 0 selects the state before the first instruction was run
+    Timeline(Box<State>, usize),
+    /// Indicates that the list of labels is open, to set breakpoints in the block of a label.
+    // 0 = state to restore to when the list is closed
+    // 1 = index of the selected label
+    Labels(Box<State>, usize),
+    /// Indicates that the memory editor is open, to change the values of the memory while the program is running.
+    // 0 = state to restore to when the editor is closed
+    // 1 = selected memory location and the value that is entered
+    EditMemory(Box<State>, MemoryEdit),
 }
 
 /// App holds the state of the application
@@ -83,11 +159,25 @@ pub struct App {
     /// Manages accumulators, memory_cells and stack in the ui.
     memory_lists_manager: MemoryListsManager,
     state: State,
-    /// Contains instructions that where already executed using the custom instructions feature.
+    /// Contains instructions that were already executed using the custom instructions feature.
     executed_custom_instructions: Vec<String>,
     command_history_file: Option<String>,
     /// Determines if the call stack should be displayed in the tui
     show_call_stack: bool,
+    /// Determines if the panel of the gamma accumulator should be displayed in the tui, the panel is only displayed
+    /// if the gamma accumulator is enabled.
+    show_gamma: bool,
+    /// Determines if the column with the number of times each line was run should be displayed in the tui
+    show_hit_counts: bool,
+    /// Machine view panel, `None` if the panel is hidden.
+    machine_view: Option<MachineView>,
+    /// Memory panel that is scrolled with [PgUp] and [PgDn], if it has more entries than fit into it, see
+    /// [`Self::scrolled_panel`].
+    selected_memory_panel: MemoryPanel,
+    /// Last changes of the memory values, displayed in the value history panel.
+    value_history: ValueHistory,
+    /// Determines if the value history panel should be displayed in the tui
+    show_value_history: bool,
     /// Stores ids of instructions that are allowed and allowed comparisons/operations.
     ///
     /// Used to prevent forbidden instructions from getting executed in run custom instruction popup.
@@ -96,8 +186,79 @@ pub struct App {
     enable_syntax_highlighting: bool,
     /// Theme of the application.
     theme: SharedTheme,
+    /// If set, all keys that are pressed are recorded, used to create repro files.
+    recorded_actions: Option<Vec<KeyEvent>>,
+    /// Path and content of the repro file that is written when a runtime error occurs, the recorded actions are
+    /// added when the file is written.
+    repro_file: Option<(String, Repro)>,
+    /// If set, all keys that are pressed are sent to the viewers of the shared session.
+    shared_actions: Option<Sender<KeyEvent>>,
+    /// Keys that were pressed by the viewers of the shared session.
+    remote_actions: Option<Receiver<KeyEvent>>,
+    /// Requests of the remote control, see [`remote_control::start`].
+    control_requests: Option<Receiver<PendingRequest>>,
+    /// Set if the session is shared or if this app is attached to a shared session.
+    share_role: Option<ShareRole>,
+    /// If true, viewers of the shared session can control stepping and breakpoints.
+    control_granted: bool,
+    /// Log of all actions in the shared session, only written by the host.
+    share_log: Option<ShareLog>,
+    /// If set, the profiling overlay is displayed, contains the collected timings.
+    render_profile: Option<RenderProfile>,
+    /// If set, the profile column is displayed next to the instructions, contains the runs and run times of the lines.
+    line_profile: Option<LineProfile>,
+    /// If set, the key event overlay is displayed, contains the last key events that were received.
+    key_event_log: Option<KeyEventLog>,
+    /// If set, the help overlay is displayed, contains the index of the selected action.
+    help: Option<usize>,
+    /// Areas of the panels that react to the mouse, when the ui was last drawn.
+    mouse_areas: MouseAreas,
+    /// Stores the breakpoints and memory values before they were changed, to be able to undo the changes.
+    undo_history: UndoHistory<Snapshot>,
+    /// Breakpoints when the app was started, used to check if breakpoints were changed.
+    saved_breakpoints: Vec<BreakpointState>,
+    /// If true the user is asked to save the session before quitting, when breakpoints have been changed.
+    confirm_quit: bool,
+    /// True if values were changed in the memory editor since the program was reset, the user is asked before
+    /// quitting, because the changed values are lost.
+    memory_edited: bool,
+    /// Hooks that are triggered when a breakpoint is hit, a contract is violated or the program finishes.
+    hooks: Hooks,
+    /// Keys that are remapped by the user, pressed keys are translated to the default keys before they are handled.
+    keymap: Keymap,
+    /// Instructions that were run in the playground and the changes they caused.
+    timeline: Timeline,
+    /// Memory after the last run of the program that finished, written with `--dump-state`.
+    finished_memory: Option<RuntimeMemory>,
+    /// Minimum time between two frames, keys of viewers of a shared session are checked in the same interval.
+    tick_rate: Duration,
+    /// Number of steps that are run per second in auto-run mode.
+    auto_run_speed: u32,
+    /// Set while the program is stepped automatically.
+    auto_run: Option<AutoRun>,
+    /// Delay between two steps of the auto-run, set when the app runs in presentation mode, see `--present`.
+    present_delay: Option<Duration>,
+    /// File to which the instructions of the playground are written with [Ctrl+s].
+    playground_file: String,
+    /// Result of the last time the playground was saved, displayed until the next instruction is run.
+    playground_saved: Option<String>,
+    /// If true an existing playground file is overwritten by [Ctrl+s], set once the user confirmed to overwrite
+    /// the file or when the file was written by this session.
+    playground_overwrite: bool,
+    /// Instructions that were last written to the playground file, used to check if the playground has unsaved
+    /// instructions.
+    saved_playground_program: Vec<String>,
 }
 
+/// Default minimum time between two frames in milliseconds, see `--tick-rate`.
+pub const DEFAULT_TICK_RATE: u64 = 50;
+/// Default number of steps that are run per second in auto-run mode, see `--auto-run-speed`.
+pub const DEFAULT_AUTO_RUN_SPEED: u32 = 10;
+/// Default delay between two steps in presentation mode in milliseconds, see `--present-delay`.
+pub const DEFAULT_PRESENT_DELAY: u64 = 1000;
+/// Default file to which the instructions of the playground are written, see `--save-file`.
+pub const DEFAULT_PLAYGROUND_FILE: &str = "playground.alpha";
+
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::cast_possible_wrap)]
 #[allow(clippy::cast_possible_truncation)]
@@ -120,9 +281,16 @@ impl App {
         enable_syntax_highlighting: bool,
         theme: SharedTheme,
     ) -> App {
-        let mlm = MemoryListsManager::new(runtime.runtime_memory(), &theme);
+        let mlm = MemoryListsManager::new(runtime.runtime_memory(), runtime.aliases(), &theme);
+        let mut keybinding_hints = KeybindingHints::new(theme.clone())
+            .expect("Keybinding hints should be properly initialized");
+        keybinding_hints.set_gamma(mlm.gamma_enabled());
+        let timeline = Timeline::new(runtime.runtime_memory().clone(), runtime.aliases().clone());
         let show_call_stack = runtime.contains_call_instruction();
         let executed_custom_instructions = custom_instructions.unwrap_or_default();
+        let instruction_list_states =
+            InstructionListStates::new(instructions, set_breakpoints.as_ref());
+        let saved_breakpoints = instruction_list_states.breakpoint_states();
         let state = if playground {
             State::Playground(SingleInstruction::new(
                 &executed_custom_instructions,
@@ -134,195 +302,1170 @@ impl App {
         Self {
             runtime,
             filename,
-            instruction_list_states: InstructionListStates::new(
-                instructions,
-                set_breakpoints.as_ref(),
-            ),
-            keybinding_hints: KeybindingHints::new(theme.clone())
-                .expect("Keybinding hints should be properly initialized"),
+            instruction_list_states,
+            keybinding_hints,
             memory_lists_manager: mlm,
             state,
             executed_custom_instructions,
             command_history_file,
             show_call_stack,
+            show_gamma: true,
+            show_hit_counts: false,
+            machine_view: None,
+            selected_memory_panel: MemoryPanel::Stack,
+            value_history: ValueHistory::default(),
+            show_value_history: false,
             instruction_config,
             enable_syntax_highlighting,
             theme,
+            recorded_actions: None,
+            repro_file: None,
+            shared_actions: None,
+            remote_actions: None,
+            control_requests: None,
+            share_role: None,
+            control_granted: false,
+            share_log: None,
+            render_profile: None,
+            line_profile: None,
+            key_event_log: None,
+            help: None,
+            mouse_areas: MouseAreas::default(),
+            undo_history: UndoHistory::new(),
+            saved_breakpoints,
+            confirm_quit: false,
+            memory_edited: false,
+            hooks: Hooks::default(),
+            keymap: Keymap::default(),
+            timeline,
+            finished_memory: None,
+            tick_rate: Duration::from_millis(DEFAULT_TICK_RATE),
+            auto_run_speed: DEFAULT_AUTO_RUN_SPEED,
+            auto_run: None,
+            present_delay: None,
+            playground_file: DEFAULT_PLAYGROUND_FILE.to_string(),
+            playground_saved: None,
+            playground_overwrite: false,
+            saved_playground_program: Vec::new(),
         }
     }
 
-    #[allow(clippy::single_match)]
+    /// Starts to record all keys that are pressed from now on.
+    pub fn record_actions(&mut self) {
+        self.recorded_actions = Some(Vec::new());
+    }
+
+    /// Returns the keys that were pressed since recording was started.
+    pub fn recorded_actions(&self) -> Option<&Vec<KeyEvent>> {
+        self.recorded_actions.as_ref()
+    }
+
+    /// Starts to record all keys that are pressed from now on, the actions are written together with `repro` to
+    /// `path`, when a runtime error occurs or when [`App::write_repro`] is called.
+    pub fn record_repro(&mut self, path: &str, repro: Repro) {
+        self.record_actions();
+        self.repro_file = Some((path.to_string(), repro));
+    }
+
+    /// Writes the repro file with the actions that were recorded so far, does nothing if no repro is recorded.
+    ///
+    /// Returns the path of the file that was written.
+    pub fn write_repro(&self) -> Result<Option<&str>> {
+        let (Some((path, repro)), Some(actions)) = (&self.repro_file, &self.recorded_actions)
+        else {
+            return Ok(None);
+        };
+        let mut repro = repro.clone();
+        repro.set_actions(actions);
+        repro.write_to_file(path)?;
+        Ok(Some(path))
+    }
+
+    /// Writes the repro file after a runtime error, so that the error can be reproduced even if the session is
+    /// not closed normally.
+    fn write_repro_on_error(&self) {
+        // the file is written again when the app is closed, where failures are reported
+        _ = self.write_repro();
+    }
+
+    /// Returns the memory after the last run of the program that finished, `None` if the program never finished.
+    pub fn finished_memory(&self) -> Option<&RuntimeMemory> {
+        self.finished_memory.as_ref()
+    }
+
+    /// Shares the session: all keys that are pressed from now on are sent to `sender`,
+    /// keys of viewers are received from `remote_actions` and all actions are written to `log`.
+    pub fn share_session(
+        &mut self,
+        sender: Sender<KeyEvent>,
+        remote_actions: Receiver<KeyEvent>,
+        log: ShareLog,
+    ) {
+        self.shared_actions = Some(sender);
+        self.remote_actions = Some(remote_actions);
+        self.share_role = Some(ShareRole::Host);
+        self.share_log = Some(log);
+    }
+
+    /// Lets the remote control drive the debugger, the requests are answered while the app runs.
+    pub fn set_remote_control(&mut self, control_requests: Receiver<PendingRequest>) {
+        self.control_requests = Some(control_requests);
+    }
+
+    /// Marks this app as viewer of a shared session, has to be called before the actions of the session are replayed.
+    pub fn attach_viewer(&mut self) {
+        self.share_role = Some(ShareRole::Viewer);
+    }
+
+    /// Enables the quit prompt, the user is then asked to save the session before quitting, when breakpoints have been changed.
+    pub fn enable_quit_prompt(&mut self) {
+        self.confirm_quit = true;
+    }
+
+    /// Sets the hooks that are triggered on selected events.
+    pub fn set_hooks(&mut self, hooks: Hooks) {
+        self.hooks = hooks;
+    }
+
+    /// Sets the keys that are remapped by the user, the keybinding hints show the remapped keys.
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keybinding_hints.set_keymap(keymap.clone());
+        self.keymap = keymap;
+    }
+
+    /// Enables the profiling overlay that displays render times and list sizes.
+    pub fn enable_render_profile(&mut self) {
+        self.render_profile = Some(RenderProfile::default());
+    }
+
+    /// Enables the profile column that displays how often each instruction line was run and how long it took in
+    /// auto-run mode.
+    pub fn enable_line_profile(&mut self) {
+        self.line_profile = Some(LineProfile::new(
+            self.instruction_list_states.instructions().len(),
+        ));
+    }
+
+    /// Returns the collected line profile as csv, `None` if the line profile is not enabled.
+    pub fn line_profile_csv(&self) -> Option<String> {
+        let instructions = self
+            .instruction_list_states
+            .instructions()
+            .iter()
+            .map(|f| f.1.to_string())
+            .collect::<Vec<_>>();
+        Some(
+            self.line_profile
+                .as_ref()?
+                .to_csv(&instructions, self.runtime.line_hits()),
+        )
+    }
+
+    /// Enables the overlay that displays the last key events as they were received from the terminal.
+    pub fn enable_key_event_log(&mut self) {
+        self.key_event_log = Some(KeyEventLog::default());
+    }
+
+    /// Sets the minimum time between two frames, see [`DEFAULT_TICK_RATE`].
+    pub fn set_tick_rate(&mut self, tick_rate: Duration) {
+        self.tick_rate = tick_rate;
+    }
+
+    /// Sets the number of steps that are run per second in auto-run mode, see [`DEFAULT_AUTO_RUN_SPEED`].
+    pub fn set_auto_run_speed(&mut self, steps_per_second: u32) {
+        self.auto_run_speed = steps_per_second;
+    }
+
+    /// Enables the presentation mode: the auto-run is started when the program is started and steps the program with
+    /// a delay of `delay` between two steps, the current instruction is highlighted more prominently and the
+    /// keybinding hints are hidden while the auto-run is active.
+    pub fn enable_presentation(&mut self, delay: Duration) {
+        self.present_delay = Some(delay);
+    }
+
+    /// Sets the file to which the instructions of the playground are written with [Ctrl+s].
+    pub fn set_playground_file(&mut self, path: &str) {
+        self.playground_file = path.to_string();
+    }
+
     pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
         // update keybinding hints one to make sure that start keybinding hints are displayed properly
+        if let Err(e) = self.keybinding_hints.update(&self.state) {
+            return Err(miette!("Error while updating keybinding hints:\n{e}"));
+        }
+        // frames are only drawn when something changed and at most once per tick,
+        // so that slow terminals don't slow down the auto-run
+        let mut redraw = true;
+        let mut last_frame: Option<Instant> = None;
+        loop {
+            let start = Instant::now();
+            if redraw && last_frame.is_none_or(|f| start >= f + self.tick_rate) {
+                terminal.draw(|f| self.draw(f)).into_diagnostic()?;
+                if let Some(profile) = self.render_profile.as_mut() {
+                    profile.record_frame(start.elapsed());
+                }
+                redraw = false;
+                last_frame = Some(start);
+            }
+            // wait for the next event, until a frame or step is due or keys of viewers have to be checked
+            let now = Instant::now();
+            let timeout = [
+                redraw.then(|| {
+                    last_frame.map_or(Duration::ZERO, |f| {
+                        (f + self.tick_rate).saturating_duration_since(now)
+                    })
+                }),
+                self.remote_actions.as_ref().map(|_| self.tick_rate),
+                self.control_requests.as_ref().map(|_| self.tick_rate),
+                self.auto_run.as_ref().map(|f| f.until_next_step(now)),
+            ]
+            .into_iter()
+            .flatten()
+            .min();
+            let event = match timeout {
+                Some(timeout) if !event::poll(timeout).into_diagnostic()? => None,
+                _ => Some(event::read().into_diagnostic()?),
+            };
+            if self.remote_actions.is_some() {
+                // check if a viewer pressed a key
+                if self.handle_remote_actions()? {
+                    return Ok(());
+                }
+                redraw = true;
+            }
+            if self.control_requests.is_some() && self.handle_control_requests()? {
+                redraw = true;
+            }
+            if self.run_auto_run_steps(Instant::now())? {
+                redraw = true;
+            }
+            let Some(event) = event else {
+                continue;
+            };
+            redraw = true;
+            if let Event::Key(key) = event {
+                if let Some(key_event_log) = self.key_event_log.as_mut() {
+                    key_event_log.record(key);
+                }
+                if let Some(log) = self.share_log.as_mut() {
+                    if key.kind != KeyEventKind::Release {
+                        log.write_action("host", key, None);
+                    }
+                }
+                let start = Instant::now();
+                let quit = self.handle_key_event(key)?;
+                if let Some(profile) = self.render_profile.as_mut() {
+                    profile.record_event(start.elapsed());
+                }
+                if quit {
+                    return Ok(());
+                }
+            }
+            if let Event::Mouse(mouse) = event {
+                self.handle_mouse_event(mouse)?;
+            }
+        }
+    }
+
+    /// Handles a mouse event.
+    ///
+    /// Clicking a line in the code area selects it in debug select mode and toggles its breakpoint otherwise,
+    /// clicking a line in the breakpoint column always toggles its breakpoint. The scroll wheel scrolls the list
+    /// below the mouse, in debug select mode the selected line is moved instead.
+    ///
+    /// Mouse events are ignored while a popup is open, in shared sessions and while a repro is recorded, because
+    /// only keys are sent to the viewers and replayed.
+    pub fn handle_mouse_event(&mut self, mouse: MouseEvent) -> Result<bool> {
+        let accepted = self.share_role.is_none()
+            && self.recorded_actions.is_none()
+            && self.help.is_none()
+            && matches!(
+                self.state,
+                State::Default | State::Running(_) | State::DebugSelect(_, _)
+            );
+        if !accepted {
+            return Ok(false);
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        let areas = self.mouse_areas;
+        let rows = match mouse.kind {
+            MouseEventKind::ScrollUp => -1,
+            MouseEventKind::ScrollDown => 1,
+            MouseEventKind::Down(MouseButton::Left) => 0,
+            _ => return Ok(false),
+        };
+        let in_code = areas.code.contains(position);
+        let in_lines = in_code || areas.breakpoints.contains(position);
+        if in_lines && rows != 0 {
+            match self.state {
+                State::DebugSelect(_, _) if rows < 0 => {
+                    self.instruction_list_states.set_prev_visual();
+                }
+                State::DebugSelect(_, _) => self.instruction_list_states.set_next_visual(),
+                _ => self.instruction_list_states.scroll(rows),
+            }
+        } else if in_lines {
+            // the breakpoint column and the code area start in the same row
+            let line = (position.y > areas.code.y && position.y + 1 < areas.code.bottom())
+                .then(|| (position.y - areas.code.y - 1) as usize)
+                .and_then(|row| self.instruction_list_states.line_in_row(row));
+            match (line, &self.state) {
+                (None, _) => (),
+                (Some(line), State::DebugSelect(_, _)) if in_code => {
+                    self.instruction_list_states.force_set(line);
+                }
+                (Some(line), _) => {
+                    self.record_breakpoints();
+                    self.instruction_list_states.toggle_breakpoint_in_line(line);
+                    if let State::Running(_) = self.state {
+                        self.state = State::Running(self.instruction_list_states.breakpoints_set());
+                    }
+                }
+            }
+        } else if rows != 0 {
+            let panel = [
+                (areas.accumulators, MemoryPanel::Accumulators),
+                (areas.memory_cells, MemoryPanel::MemoryCells),
+                (areas.stack, MemoryPanel::Stack),
+                (areas.output, MemoryPanel::Output),
+            ]
+            .into_iter()
+            .find(|(area, _)| area.contains(position));
+            if let Some((_, panel)) = panel {
+                self.memory_lists_manager.scroll(panel, rows);
+            }
+        }
+        self.update_after_key_event()
+    }
+
+    /// Handles the keys that were pressed by viewers of the shared session.
+    ///
+    /// Keys are only handled if control was granted to the viewers, all keys are logged.
+    ///
+    /// Returns true if the app should be closed.
+    fn handle_remote_actions(&mut self) -> Result<bool> {
+        let Some(remote_actions) = &self.remote_actions else {
+            return Ok(false);
+        };
+        let keys = remote_actions.try_iter().collect::<Vec<_>>();
+        for key in keys {
+            let accepted =
+                self.control_granted && is_control_key(&key) && accepts_control_keys(&self.state);
+            if let Some(log) = self.share_log.as_mut() {
+                log.write_action("viewer", key, (!accepted).then_some("ignored"));
+            }
+            // viewers send the default keys of the actions, they are not translated with the keymap of the host
+            let Some(key) = input::normalize_key_event(key, false) else {
+                continue;
+            };
+            if accepted && self.handle_action(key)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Answers the requests of the remote control, the program is controlled with the same keys as in the tui.
+    ///
+    /// Returns true if a request was handled.
+    fn handle_control_requests(&mut self) -> Result<bool> {
+        let Some(control_requests) = &self.control_requests else {
+            return Ok(false);
+        };
+        let requests = control_requests.try_iter().collect::<Vec<_>>();
+        let handled = !requests.is_empty();
+        for pending in requests {
+            let error = self.handle_control_request(&pending.request)?;
+            pending.respond(self.control_response(error));
+        }
+        Ok(handled)
+    }
+
+    /// Performs the request of the remote control, returns the reason why it could not be performed.
+    fn handle_control_request(&mut self, request: &ControlRequest) -> Result<Option<String>> {
+        if !accepts_control_keys(&self.state) || self.help.is_some() {
+            return Ok(Some(
+                "The program can not be controlled in the current mode of the tui".to_string(),
+            ));
+        }
+        // the actions are run directly instead of pressing their keys, which might be remapped by the keymap, they
+        // are not recorded as pressed keys either
+        match (request, &self.state) {
+            (ControlRequest::ReadMemory, _) => (),
+            (ControlRequest::Step, State::Default) => self.start(),
+            (ControlRequest::Step, State::Running(_)) => _ = self.step(),
+            (ControlRequest::Run, State::Default | State::Running(_)) => {
+                if self.state == State::Default {
+                    self.start();
+                }
+                if let State::Running(_) = self.state {
+                    self.run_to_breakpoint();
+                }
+            }
+            (
+                ControlRequest::Reset,
+                State::Running(_)
+                | State::Finished(_)
+                | State::RuntimeError(_, false)
+                | State::CustomInstructionError(_, false),
+            ) => self.reset(),
+            (ControlRequest::Reset, _) => (),
+            (ControlRequest::WriteMemory { memory }, State::Default | State::Running(_)) => {
+                let mut state = StateDump::from(self.runtime.runtime_memory());
+                state.update(memory);
+                self.runtime.set_runtime_memory(RuntimeMemory::from(&state));
+            }
+            (ControlRequest::Step | ControlRequest::Run, _) => {
+                return Ok(Some("The program is not running".to_string()));
+            }
+            (ControlRequest::WriteMemory { .. }, _) => {
+                return Ok(Some(
+                    "The memory can only be written before or while the program is running"
+                        .to_string(),
+                ));
+            }
+        }
+        self.update_after_key_event()?;
+        Ok(None)
+    }
+
+    /// Returns the response to a request of the remote control, with the current state of the program.
+    fn control_response(&self, error: Option<String>) -> ControlResponse {
+        let status = match self.state {
+            State::Default => ControlStatus::NotStarted,
+            State::Running(_) => ControlStatus::Running,
+            State::Finished(_) => ControlStatus::Finished,
+            State::RuntimeError(_, _) => ControlStatus::RuntimeError,
+            _ => ControlStatus::Busy,
+        };
+        ControlResponse {
+            error,
+            status,
+            line: self.runtime.next_instruction_index() + 1,
+            steps: self.runtime.control_flow().steps,
+            memory: StateDump::from(self.runtime.runtime_memory()),
+        }
+    }
+
+    /// Runs the app as viewer, the keys that are pressed in the shared session are received from `keys`.
+    ///
+    /// While control is granted, keys that control stepping and breakpoints are sent to the host with `actions`,
+    /// they take effect when the host sends them back.
+    ///
+    /// Returns when [q] or [ESC] is pressed, when the shared session is closed or when the connection is lost.
+    pub fn run_attached<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        keys: &Receiver<KeyEvent>,
+        actions: &Sender<KeyEvent>,
+    ) -> Result<()> {
         if let Err(e) = self.keybinding_hints.update(&self.state) {
             return Err(miette!("Error while updating keybinding hints:\n{e}"));
         }
         loop {
             terminal.draw(|f| self.draw(f)).into_diagnostic()?;
-            if let Event::Key(key) = event::read().into_diagnostic()? {
-                if key.kind == KeyEventKind::Release {
-                    // ignore when key is released, to prevent dual input
-                    continue;
+            if event::poll(self.tick_rate).into_diagnostic()? {
+                match event::read().into_diagnostic()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Release => (),
+                    Event::Key(key) if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) => {
+                        return Ok(());
+                    }
+                    // all other keys are ignored, if control is not granted
+                    Event::Key(key) if self.control_granted && is_control_key(&key) => {
+                        _ = actions.send(key);
+                    }
+                    _ => (),
                 }
-                match &self.state {
-                    State::CustomInstruction(_) | State::Playground(_) => {
-                        if let KeyCode::Char(to_insert) = key.code {
-                            self.any_char(to_insert)
+            }
+            loop {
+                match keys.try_recv() {
+                    Ok(key) => {
+                        // the host sends keys that were already translated with its keymap
+                        if self.handle_action(key)? {
+                            return Ok(());
                         }
                     }
-                    _ => {
-                        match key.code {
-                            KeyCode::Up => {
-                                if let State::DebugSelect(_s, _i) = &self.state {
-                                    self.instruction_list_states.set_prev_visual();
-                                }
-                            }
-                            KeyCode::Down => {
-                                if let State::DebugSelect(_s, _i) = &self.state {
-                                    self.instruction_list_states.set_next_visual();
-                                }
-                            }
-                            KeyCode::Char('b') => {
-                                if let State::DebugSelect(_, _) = &self.state {
-                                    self.instruction_list_states.toggle_breakpoint();
-                                }
-                            }
-                            KeyCode::Char('j') => {
-                                if let State::DebugSelect(_, _) = &self.state {
-                                    self.state = State::Running(
-                                        self.instruction_list_states.breakpoints_set(),
-                                    );
-                                    let idx = self
-                                        .instruction_list_states
-                                        .instruction_list_state_mut()
-                                        .selected()
-                                        .unwrap();
-                                    self.runtime.set_next_instruction(idx);
-                                    _ = self.step();
-                                }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return Ok(()),
+                }
+            }
+        }
+    }
+
+    /// Performs the action that is bound to the key of the key event.
+    ///
+    /// Returns true if the app should be closed.
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Result<bool> {
+        let typing = match &self.state {
+            State::CustomInstruction(_) | State::Playground(_) => true,
+            State::EditMemory(_, edit) => edit.is_typing(),
+            _ => false,
+        };
+        let Some(key) = input::normalize_key_event(key, typing)
+            .and_then(|key| self.keymap.translate(key, typing))
+        else {
+            return Ok(false);
+        };
+        self.handle_action(key)
+    }
+
+    /// Performs the action of `key`, which is the default key of the action, keys that were remapped by the keymap
+    /// have to be translated with [`Keymap::translate`] first.
+    ///
+    /// Returns true if the app should be closed.
+    #[allow(clippy::single_match)]
+    fn handle_action(&mut self, key: KeyEvent) -> Result<bool> {
+        if let Some(actions) = &mut self.recorded_actions {
+            actions.push(key);
+        }
+        if let Some(sender) = &self.shared_actions {
+            // viewers are not required for the session to continue
+            _ = sender.send(key);
+        }
+        // the help overlay takes all keys while it is open
+        if let Some(selected) = self.help {
+            let len = self.keybinding_hints.help_entries().len();
+            match key.code {
+                KeyCode::Up => self.help = Some(selected.saturating_sub(1)),
+                KeyCode::Down => self.help = Some((selected + 1).min(len.saturating_sub(1))),
+                KeyCode::Esc | KeyCode::Char('?') => self.help = None,
+                _ => (),
+            }
+            return self.update_after_key_event();
+        }
+        if key.code == KeyCode::Char('r') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            match self.state {
+                State::Default
+                | State::Running(_)
+                | State::DebugSelect(_, _)
+                | State::Labels(_, _) => {
+                    self.redo();
+                }
+                State::Timeline(_, _) => {
+                    let memory = self.timeline.redo();
+                    self.restore_timeline(memory);
+                }
+                _ => (),
+            }
+            return self.update_after_key_event();
+        }
+        if key.code == KeyCode::Char('o') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            if let State::DebugSelect(_, _) = self.state {
+                self.instruction_list_states.jump_back();
+            }
+            return self.update_after_key_event();
+        }
+        // most terminals report Ctrl+i as Tab, so the jump forward is handled in the tab key as well
+        if key.code == KeyCode::Char('i') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            if let State::DebugSelect(_, _) = self.state {
+                self.instruction_list_states.jump_forward();
+            }
+            return self.update_after_key_event();
+        }
+        if key.code == KeyCode::Char('s') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            if let State::Playground(_) | State::Timeline(_, _) = self.state {
+                self.save_playground();
+            }
+            return self.update_after_key_event();
+        }
+        if key.code == KeyCode::Char('t') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            match &self.state {
+                State::Playground(_) => {
+                    self.state = State::Timeline(
+                        Box::new(self.state.clone()),
+                        self.timeline.entries().len(),
+                    );
+                }
+                State::Timeline(state, _) => self.state = *state.clone(),
+                _ => (),
+            }
+            return self.update_after_key_event();
+        }
+        match &self.state {
+            State::CustomInstruction(_) | State::Playground(_) => {
+                if let KeyCode::Char(to_insert) = key.code {
+                    self.any_char(to_insert)
+                }
+            }
+            State::EditMemory(_, edit) if edit.is_typing() => {
+                if let KeyCode::Char(to_insert) = key.code {
+                    self.any_char(to_insert)
+                }
+            }
+            _ => {
+                match key.code {
+                    KeyCode::Up => {
+                        if let State::DebugSelect(_s, _i) = &self.state {
+                            self.instruction_list_states.set_prev_visual();
+                        }
+                    }
+                    KeyCode::Down => {
+                        if let State::DebugSelect(_s, _i) = &self.state {
+                            self.instruction_list_states.set_next_visual();
+                        }
+                    }
+                    KeyCode::Char('b') => {
+                        if let State::DebugSelect(_, _) = &self.state {
+                            self.record_breakpoints();
+                            self.instruction_list_states.toggle_breakpoint();
+                        }
+                    }
+                    KeyCode::Char('e') => {
+                        if let State::DebugSelect(_, _) = &self.state {
+                            self.record_breakpoints();
+                            self.instruction_list_states.toggle_breakpoint_enabled();
+                        }
+                    }
+                    KeyCode::Char('a') => match &self.state {
+                        State::Running(_) => {
+                            self.record_breakpoints();
+                            self.instruction_list_states.toggle_all_breakpoints();
+                            self.state =
+                                State::Running(self.instruction_list_states.breakpoints_set());
+                        }
+                        State::DebugSelect(_, _) => {
+                            self.record_breakpoints();
+                            self.instruction_list_states.toggle_all_breakpoints();
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('u') => match &self.state {
+                        State::Default
+                        | State::Running(_)
+                        | State::DebugSelect(_, _)
+                        | State::Labels(_, _) => {
+                            self.undo();
+                        }
+                        State::Timeline(_, _) => {
+                            let memory = self.timeline.undo();
+                            self.restore_timeline(memory);
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('j') => match &self.state {
+                        State::DebugSelect(_, _) => {
+                            self.state =
+                                State::Running(self.instruction_list_states.breakpoints_set());
+                            let idx = self
+                                .instruction_list_states
+                                .instruction_list_state_mut()
+                                .selected()
+                                .unwrap();
+                            self.runtime.set_next_instruction(idx);
+                            _ = self.step();
+                        }
+                        State::Labels(state, selected) => {
+                            // the line that was selected before can be returned to with [Ctrl+o]
+                            if let Some((_, block)) = self.runtime.label_blocks().get(*selected) {
+                                self.instruction_list_states.jump_to(block.start);
                             }
-                            KeyCode::Char('i') => match self.state {
-                                State::Running(_) => {
-                                    self.state = State::CustomInstruction(SingleInstruction::new(
-                                        &self.executed_custom_instructions,
-                                        &self.theme,
-                                    ))
-                                }
-                                _ => (),
-                            },
-                            KeyCode::Char('q') => match &self.state {
-                                State::RuntimeError(e, _) => Err(e.clone())?,
-                                State::CustomInstructionError(e, _) => Err(e.clone())?,
-                                State::BuildProgramError(e) => Err(e.clone())?,
-                                State::CustomInstruction(_) => (),
-                                _ => return Ok(()),
-                            },
-                            KeyCode::Char('w') => {
-                                if let State::DebugSelect(_, _) = self.state {
-                                    self.instruction_list_states.set_prev_visual();
-                                }
+                            self.state = *state.clone();
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('i') => match self.state {
+                        State::Running(_) => {
+                            self.state = State::CustomInstruction(SingleInstruction::new(
+                                &self.executed_custom_instructions,
+                                &self.theme,
+                            ))
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('q') => match &self.state {
+                        State::RuntimeError(e, _) => Err(e.clone())?,
+                        State::CustomInstructionError(e, _) => Err(e.clone())?,
+                        State::BuildProgramError(e) => Err(e.clone())?,
+                        State::CustomInstruction(_) => (),
+                        State::QuitPrompt(_) => return Ok(true),
+                        _ => return Ok(self.quit()),
+                    },
+                    KeyCode::Char('h') => self.keybinding_hints.next_page(),
+                    KeyCode::Char('?') if !matches!(self.state, State::QuitPrompt(_)) => {
+                        self.help = Some(0);
+                    }
+                    KeyCode::Char('g') if self.share_role.is_some() => {
+                        self.control_granted = !self.control_granted;
+                        if let Some(log) = self.share_log.as_mut() {
+                            log.write(if self.control_granted {
+                                "control granted to viewers"
+                            } else {
+                                "control revoked"
+                            });
+                        }
+                    }
+                    KeyCode::Char('w') => {
+                        if let State::DebugSelect(_, _) = self.state {
+                            self.instruction_list_states.set_prev_visual();
+                        }
+                    }
+                    KeyCode::Char('t') => match self.state {
+                        State::Running(_)
+                        | State::Finished(_)
+                        | State::RuntimeError(_, false)
+                        | State::CustomInstructionError(_, false) => self.reset(),
+                        State::DebugSelect(_, _) => {
+                            self.instruction_list_states.set_next_visual();
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('s') => match self.state {
+                        State::Default => self.start(),
+                        State::DebugSelect(_, _) => {
+                            self.instruction_list_states.set_next_visual();
+                        }
+                        State::QuitPrompt(_)
+                            if self.breakpoints_changed() || self.playground_changed() =>
+                        {
+                            if self.breakpoints_changed() {
+                                self.session()
+                                    .write_to_file(&session_file_path(&self.filename))?;
                             }
-                            KeyCode::Char('t') => match self.state {
-                                State::Running(_) | State::Finished(_) => self.reset(),
-                                State::RuntimeError(_, false)
-                                | State::CustomInstructionError(_, false) => {
-                                    self.reset();
-                                }
-                                State::DebugSelect(_, _) => {
-                                    self.instruction_list_states.set_next_visual();
-                                }
-                                _ => (),
-                            },
-                            KeyCode::Char('s') => match self.state {
-                                State::Default => {
-                                    self.instruction_list_states
-                                        .set_start(self.runtime.next_instruction_index() as i32);
-                                    self.state = State::Running(
-                                        self.instruction_list_states.breakpoints_set(),
-                                    );
-                                    _ = self.step();
-                                }
-                                State::DebugSelect(_, _) => {
-                                    self.instruction_list_states.set_next_visual();
-                                }
-                                _ => (),
-                            },
-                            KeyCode::Char('n') => {
-                                match self.state {
-                                    State::Running(_) => {
-                                        _ = self.step();
-                                    }
-                                    _ => (),
-                                };
+                            // confirming the prompt overwrites an existing playground file without asking again
+                            if self.playground_changed() {
+                                utils::write_file(&self.timeline.program(), &self.playground_file)?;
                             }
-                            KeyCode::Char('r') => {
-                                // run to the next breakpoint
-                                if self.state == State::Running(true)
-                                    || self.state == State::Running(false)
-                                {
-                                    _ = self.step();
-                                    while !self.instruction_list_states.is_breakpoint() {
-                                        match self.step() {
-                                            Ok(bool) => {
-                                                if bool {
-                                                    break;
-                                                }
-                                            }
-                                            Err(_) => break,
-                                        }
-                                    }
-                                }
+                            return Ok(true);
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('p')
+                        if matches!(self.state, State::Running(_)) && self.auto_run_available() =>
+                    {
+                        self.auto_run = match self.auto_run {
+                            Some(_) => None,
+                            None => Some(self.new_auto_run(Instant::now())),
+                        };
+                    }
+                    KeyCode::Char('o') if matches!(self.state, State::Running(_)) => {
+                        // the function is run completely, if the next instruction is a call
+                        self.step_until_call_depth(self.runtime.control_flow().call_stack.len());
+                    }
+                    KeyCode::Char('f') if matches!(self.state, State::Running(_)) => {
+                        // nothing to step out of, if no function is running
+                        if let Some(depth) =
+                            self.runtime.control_flow().call_stack.len().checked_sub(1)
+                        {
+                            self.step_until_call_depth(depth);
+                        }
+                    }
+                    KeyCode::Char('n') => {
+                        match self.state {
+                            State::Running(_) => {
+                                _ = self.step();
                             }
-                            KeyCode::Char('d') => match &self.state {
-                                State::DebugSelect(_, i) => {
-                                    self.instruction_list_states.set_instruction_list_state(*i);
-                                    self.state = State::Running(
-                                        self.instruction_list_states.breakpoints_set(),
-                                    );
-                                }
-                                State::Default | State::Running(_) => {
-                                    self.start_debug_select_mode()
-                                }
-                                State::Finished(b) => {
-                                    if *b {
-                                        self.state = State::Finished(false);
-                                    }
-                                }
-                                _ => (),
-                            },
-                            KeyCode::Char('c') => match &self.state {
-                                State::Default | State::Running(_) | State::DebugSelect(_, _) => {
-                                    self.show_call_stack = !self.show_call_stack;
-                                }
-                                _ => (),
-                            },
                             _ => (),
-                        }
+                        };
                     }
-                }
-                // keybinding actions that are always checked
-                match key.code {
-                    KeyCode::Esc => {
-                        if self.escape_key()? {
-                            return Ok(());
+                    KeyCode::Char('r') if matches!(self.state, State::DebugSelect(_, _)) => {
+                        self.run_to_selected_line();
+                    }
+                    KeyCode::Char('r') if matches!(self.state, State::Running(_)) => {
+                        self.run_to_breakpoint();
+                    }
+                    KeyCode::Char('d') => match &self.state {
+                        State::DebugSelect(_, i) => {
+                            self.instruction_list_states.set_instruction_list_state(*i);
+                            self.state =
+                                State::Running(self.instruction_list_states.breakpoints_set());
+                        }
+                        State::Default | State::Running(_) => self.start_debug_select_mode(),
+                        State::Finished(true) => {
+                            self.state = State::Finished(false);
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('l') => match &self.state {
+                        State::DebugSelect(_, _) => {
+                            self.state = State::Labels(Box::new(self.state.clone()), 0);
+                        }
+                        State::Labels(state, _) => self.state = *state.clone(),
+                        _ => (),
+                    },
+                    KeyCode::Char('m') => match &self.state {
+                        State::Running(_) => {
+                            self.state = State::EditMemory(
+                                Box::new(self.state.clone()),
+                                MemoryEdit::default(),
+                            );
+                        }
+                        State::EditMemory(state, _) => self.state = *state.clone(),
+                        _ => (),
+                    },
+                    KeyCode::Char('c') => match &self.state {
+                        State::Default | State::Running(_) | State::DebugSelect(_, _) => {
+                            self.show_call_stack = !self.show_call_stack;
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('y') => match &self.state {
+                        State::Default | State::Running(_) | State::DebugSelect(_, _) => {
+                            self.show_gamma = !self.show_gamma;
+                        }
+                        _ => (),
+                    },
+                    KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End
+                        if matches!(
+                            self.state,
+                            State::Running(_) | State::DebugSelect(_, _) | State::Finished(_)
+                        ) =>
+                    {
+                        if let Some(panel) = self.scrolled_panel() {
+                            let page = self.memory_lists_manager.page(panel) as isize;
+                            match key.code {
+                                KeyCode::PageUp => self.memory_lists_manager.scroll(panel, -page),
+                                KeyCode::PageDown => self.memory_lists_manager.scroll(panel, page),
+                                KeyCode::Home => self.memory_lists_manager.scroll_to_top(panel),
+                                _ => self.memory_lists_manager.scroll_to_bottom(panel),
+                            }
                         }
                     }
-                    KeyCode::Backspace => self.backspace_key(),
-                    KeyCode::Delete => self.delete_key(),
-                    KeyCode::Left => self.left_key(),
-                    KeyCode::Right => self.right_key(),
-                    KeyCode::Down => self.down_key(),
-                    KeyCode::Up => self.up_key(),
-                    KeyCode::Enter => self.enter_key()?,
-                    KeyCode::Tab => self.tab_key(),
+                    KeyCode::Char('z') => match &self.state {
+                        State::Running(_) | State::DebugSelect(_, _) | State::Finished(_) => {
+                            let overflowing = self.overflowing_panels();
+                            if let Some(panel) = self.scrolled_panel() {
+                                let idx = overflowing.iter().position(|f| *f == panel);
+                                let next = idx.map_or(0, |idx| (idx + 1) % overflowing.len());
+                                self.selected_memory_panel = overflowing[next];
+                            }
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('v') => match &self.state {
+                        State::Default | State::Running(_) | State::DebugSelect(_, _) => {
+                            self.show_hit_counts = !self.show_hit_counts;
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('#') => match &self.state {
+                        State::Default
+                        | State::Running(_)
+                        | State::DebugSelect(_, _)
+                        | State::Finished(_) => {
+                            let value_format = self.memory_lists_manager.value_format().next();
+                            self.memory_lists_manager
+                                .set_value_format(value_format, self.runtime.runtime_memory());
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('k') => match &self.state {
+                        State::Default | State::Running(_) | State::DebugSelect(_, _) => {
+                            self.show_value_history = !self.show_value_history;
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('x') => match &self.state {
+                        State::Default | State::Running(_) | State::DebugSelect(_, _) => {
+                            self.machine_view = match self.machine_view {
+                                Some(_) => None,
+                                None => Some(MachineView::default()),
+                            };
+                        }
+                        _ => (),
+                    },
                     _ => (),
                 }
             }
+        }
+        // keybinding actions that are always checked
+        match key.code {
+            KeyCode::Esc if self.escape_key()? => return Ok(true),
+            KeyCode::Backspace => self.backspace_key(),
+            KeyCode::Delete => self.delete_key(),
+            KeyCode::Left => self.left_key(),
+            KeyCode::Right => self.right_key(),
+            KeyCode::Down => self.down_key(),
+            KeyCode::Up => self.up_key(),
+            KeyCode::Enter => self.enter_key()?,
+            KeyCode::Tab => self.tab_key(),
+            _ => (),
+        }
+
+        self.update_after_key_event()
+    }
+
+    /// Returns the memory panels that have more entries than fit into them.
+    fn overflowing_panels(&self) -> Vec<MemoryPanel> {
+        MemoryPanel::ALL
+            .into_iter()
+            .filter(|f| self.memory_lists_manager.overflows(*f))
+            .collect()
+    }
+
+    /// Returns the memory panel that is scrolled with [PgUp] and [PgDn], this is the selected panel if it can be
+    /// scrolled, otherwise the first panel that can be scrolled.
+    fn scrolled_panel(&self) -> Option<MemoryPanel> {
+        std::iter::once(self.selected_memory_panel)
+            .chain(MemoryPanel::ALL)
+            .find(|f| self.memory_lists_manager.overflows(*f))
+    }
+
+    /// Updates memory lists and keybinding hints after a key event was handled.
+    fn update_after_key_event(&mut self) -> Result<bool> {
+        // the auto-run only continues while the program is running
+        if !matches!(self.state, State::Running(_)) {
+            self.auto_run = None;
+        }
+        self.memory_lists_manager.update(&self.runtime);
+        // update keybinding hints for next loop
+        self.keybinding_hints
+            .set_auto_run(self.auto_run_available().then_some(self.auto_run.is_some()));
+        self.keybinding_hints
+            .set_call_stepping(self.runtime.contains_call_instruction());
+        self.keybinding_hints
+            .set_gamma(self.memory_lists_manager.gamma_enabled());
+        self.keybinding_hints
+            .set_value_format(self.memory_lists_manager.value_format());
+        self.keybinding_hints
+            .set_scrolled_panel(self.scrolled_panel(), self.overflowing_panels().len() > 1);
+        self.keybinding_hints.set_help(self.help.is_some());
+        self.keybinding_hints
+            .set_savable(self.breakpoints_changed() || self.playground_changed());
+        if let Err(e) = self.keybinding_hints.update(&self.state) {
+            return Err(miette!("Error while updating keybinding hints:\n{e}"));
+        }
+        Ok(false)
+    }
+
+    /// The auto-run is not available in shared sessions and while a repro is recorded, because the steps it runs
+    /// depend on timing and can't be reproduced from the pressed keys.
+    fn auto_run_available(&self) -> bool {
+        self.share_role.is_none() && self.recorded_actions.is_none()
+    }
+
+    /// Returns a new auto-run that starts at `now`, in presentation mode the steps are run with the delay of the
+    /// presentation instead of the auto-run speed.
+    fn new_auto_run(&self, now: Instant) -> AutoRun {
+        match self.present_delay {
+            Some(delay) => AutoRun::with_delay(delay, now),
+            None => AutoRun::new(self.auto_run_speed, now),
+        }
+    }
+
+    /// Returns true if the keybinding hints are hidden, which is the case while the auto-run is active in
+    /// presentation mode.
+    fn hints_hidden(&self) -> bool {
+        self.present_delay.is_some() && self.auto_run.is_some()
+    }
+
+    /// Runs the steps of the auto-run that are due at `now`.
+    ///
+    /// The auto-run is stopped when a breakpoint is reached, when the program finished or when a runtime error occurred.
+    ///
+    /// Returns true if at least one step was run.
+    fn run_auto_run_steps(&mut self, now: Instant) -> Result<bool> {
+        let Some(auto_run) = self.auto_run.as_mut() else {
+            return Ok(false);
+        };
+        let steps = auto_run.due_steps(now);
+        if steps == 0 {
+            return Ok(false);
+        }
+        for _ in 0..steps {
+            if !matches!(self.step(), Ok(false)) {
+                break;
+            }
+            if self.instruction_list_states.is_breakpoint() {
+                self.hooks.trigger(HookEvent::BreakpointHit);
+                self.auto_run = None;
+                break;
+            }
+        }
+        self.update_after_key_event()?;
+        Ok(true)
+    }
+
+    /// Records the current breakpoints, so that the following change can be undone.
+    fn record_breakpoints(&mut self) {
+        self.undo_history.record(Snapshot::Breakpoints(
+            self.instruction_list_states.breakpoint_states(),
+        ));
+    }
+
+    /// Undoes the last change of breakpoints or of a memory value.
+    fn undo(&mut self) {
+        let Some(next) = self.undo_history.next_undo() else {
+            return;
+        };
+        let current = self.current_snapshot(next);
+        if let Some(snapshot) = self.undo_history.undo(current) {
+            self.restore_snapshot(snapshot);
+        }
+    }
+
+    /// Redoes the last change of breakpoints or of a memory value that was undone.
+    fn redo(&mut self) {
+        let Some(next) = self.undo_history.next_redo() else {
+            return;
+        };
+        let current = self.current_snapshot(next);
+        if let Some(snapshot) = self.undo_history.redo(current) {
+            self.restore_snapshot(snapshot);
+        }
+    }
+
+    /// Returns the current state of the part of the app that `snapshot` contains.
+    fn current_snapshot(&self, snapshot: &Snapshot) -> Snapshot {
+        match snapshot {
+            Snapshot::Breakpoints(_) => {
+                Snapshot::Breakpoints(self.instruction_list_states.breakpoint_states())
+            }
+            Snapshot::Memory(location, _) => Snapshot::Memory(
+                location.clone(),
+                location.value(self.runtime.runtime_memory()),
+            ),
+        }
+    }
+
+    fn restore_snapshot(&mut self, snapshot: Snapshot) {
+        match snapshot {
+            Snapshot::Breakpoints(states) => self.restore_breakpoints(&states),
+            Snapshot::Memory(location, value) => self.set_memory_value(&location, value),
+        }
+    }
+
+    /// Sets the value of `location`, the change is recorded in the value history like an edit in the memory editor.
+    fn set_memory_value(&mut self, location: &MemoryLocation, value: Option<i32>) {
+        let mut memory = self.runtime.runtime_memory().clone();
+        location.set_value(&mut memory, value);
+        if self.show_value_history {
+            self.value_history
+                .record(None, self.runtime.runtime_memory(), &memory);
+        }
+        self.runtime.set_runtime_memory(memory);
+        self.memory_edited = true;
+    }
+
+    fn restore_breakpoints(&mut self, states: &[BreakpointState]) {
+        self.instruction_list_states.set_breakpoint_states(states);
+        if let State::Running(_) = self.state {
+            self.state = State::Running(self.instruction_list_states.breakpoints_set());
+        }
+    }
+
+    /// Restores the memory and the list of executed instructions after the timeline was rolled back,
+    /// does nothing if `memory` is `None`.
+    fn restore_timeline(&mut self, memory: Option<RuntimeMemory>) {
+        let Some(memory) = memory else {
+            return;
+        };
+        self.runtime.set_runtime_memory(memory);
+        let lines = self
+            .timeline
+            .entries()
+            .iter()
+            .map(|f| f.line.clone())
+            .collect::<Vec<_>>();
+        self.instruction_list_states.set_instructions(&lines);
+        // recreate memory lists manager to remove index memory cells that don't exist anymore
+        let value_format = self.memory_lists_manager.value_format();
+        self.memory_lists_manager = MemoryListsManager::new(
+            self.runtime.runtime_memory(),
+            self.runtime.aliases(),
+            &self.theme,
+        );
+        self.memory_lists_manager
+            .set_value_format(value_format, self.runtime.runtime_memory());
+        if let State::Timeline(_, selected) = &mut self.state {
+            *selected = lines.len();
+        }
+    }
+
+    /// Writes the instructions that were run in the playground to the playground file, so that they can be loaded
+    /// as program. Instructions that were rolled back in the timeline are not written.
+    ///
+    /// A file that was not written by this session is only overwritten, if [Ctrl+s] is pressed a second time.
+    fn save_playground(&mut self) {
+        if !self.playground_overwrite && Path::new(&self.playground_file).exists() {
+            self.playground_overwrite = true;
+            self.playground_saved = Some(format!(
+                "{} exists, press [{}] again to overwrite it",
+                self.playground_file,
+                self.keymap.display("C-s")
+            ));
+            return;
+        }
+        let program = self.timeline.program();
+        self.playground_saved = Some(match utils::write_file(&program, &self.playground_file) {
+            Ok(()) => {
+                self.playground_overwrite = true;
+                self.saved_playground_program.clone_from(&program);
+                format!(
+                    "{} instruction(s) saved to {}",
+                    program.len(),
+                    self.playground_file
+                )
+            }
+            Err(e) => format!("unable to save to {}: {e}", self.playground_file),
+        });
+    }
+
+    /// Returns the position in the program at which the next instruction is run, e.g. `sum.alpha: line 4, step 12`.
+    ///
+    /// The line is the line that is run next, the step counts the instructions that have been run so far.
+    fn program_position(&self) -> String {
+        format!(
+            "{}: line {}, step {}",
+            self.filename,
+            self.runtime.next_instruction_index() + 1,
+            self.runtime.control_flow().steps
+        )
+    }
+
+    /// Runs instructions until at most `depth` functions are on the call stack, used to step over and out of functions.
+    ///
+    /// At least one instruction is run. Stops early when a breakpoint is reached inside the function,
+    /// when the program finished or when a runtime error occurred.
+    fn step_until_call_depth(&mut self, depth: usize) {
+        loop {
+            if !matches!(self.step(), Ok(false)) {
+                return;
+            }
+            if self.runtime.control_flow().call_stack.len() <= depth {
+                return;
+            }
+            if self.instruction_list_states.is_breakpoint() {
+                self.hooks.trigger(HookEvent::BreakpointHit);
+                return;
+            }
+        }
+    }
 
-            self.memory_lists_manager.update(&self.runtime);
-            // update keybinding hints for next loop
-            if let Err(e) = self.keybinding_hints.update(&self.state) {
-                return Err(miette!("Error while updating keybinding hints:\n{e}"));
+    /// Runs the program until the line that is selected in debug select mode is reached, as if a temporary
+    /// breakpoint was set in that line.
+    ///
+    /// At least one instruction is run. Stops early when another breakpoint is reached,
+    /// when the program finished or when a runtime error occurred.
+    fn run_to_selected_line(&mut self) {
+        let State::DebugSelect(state, line) = &self.state else {
+            return;
+        };
+        let Some(target) = self
+            .instruction_list_states
+            .instruction_list_state_mut()
+            .selected()
+        else {
+            return;
+        };
+        if let State::Default = **state {
+            // the program has not been started yet
+            self.instruction_list_states
+                .set_start(self.runtime.next_instruction_index() as i32);
+        } else {
+            self.instruction_list_states
+                .set_instruction_list_state(*line);
+        }
+        self.state = State::Running(self.instruction_list_states.breakpoints_set());
+        loop {
+            if !matches!(self.step(), Ok(false)) {
+                return;
+            }
+            if self.instruction_list_states.selected_line() == Some(target) {
+                return;
+            }
+            if self.instruction_list_states.is_breakpoint() {
+                self.hooks.trigger(HookEvent::BreakpointHit);
+                return;
             }
         }
     }
@@ -334,9 +1477,36 @@ impl App {
         self.instruction_list_states
             .set(self.runtime.next_instruction_index() as i32);
 
+        let idx = self.runtime.next_instruction_index();
+        if let Some(machine_view) = self.machine_view.as_mut() {
+            machine_view.fetch(&self.runtime);
+        }
+        // the memory is only compared while the value history is shown
+        let before = self
+            .show_value_history
+            .then(|| self.runtime.runtime_memory().clone());
+        let start = Instant::now();
         let res = self.runtime.step();
+        if let Some(before) = before {
+            self.value_history.record(
+                Some(self.runtime.control_flow().steps),
+                &before,
+                self.runtime.runtime_memory(),
+            );
+        }
+        if let Some(machine_view) = self.machine_view.as_mut() {
+            machine_view.execute(&self.runtime);
+        }
+        if let (Some(profile), true) = (self.line_profile.as_mut(), self.auto_run.is_some()) {
+            // only the steps of the auto-run are timed, the runs are counted by the runtime
+            profile.record(idx, start.elapsed());
+        }
         if let Err(e) = res {
+            if let Some(event) = HookEvent::from_runtime_error(&e) {
+                self.hooks.trigger(event);
+            }
             self.state = State::RuntimeError(e, false);
+            self.write_repro_on_error();
             return Err(());
         }
         self.instruction_list_states
@@ -346,6 +1516,8 @@ impl App {
                 State::RuntimeError(_, _) => (),
                 _ => {
                     self.state = State::Finished(true);
+                    self.finished_memory = Some(self.runtime.runtime_memory().clone());
+                    self.hooks.trigger(HookEvent::ProgramFinished);
                 }
             }
             return Ok(true);
@@ -353,6 +1525,39 @@ impl App {
         Ok(false)
     }
 
+    /// Restores breakpoints and panel states from the session.
+    ///
+    /// Breakpoints that are already set are kept.
+    pub fn apply_session(&mut self, session: &Session) {
+        self.instruction_list_states
+            .set_breakpoints(&session.breakpoints, BreakpointState::Enabled);
+        self.instruction_list_states
+            .set_breakpoints(&session.disabled_breakpoints, BreakpointState::Disabled);
+        if let Some(show_call_stack) = session.panels.show_call_stack {
+            self.show_call_stack = show_call_stack;
+        }
+        if let Some(show_gamma) = session.panels.show_gamma {
+            self.show_gamma = show_gamma;
+        }
+        self.saved_breakpoints = self.instruction_list_states.breakpoint_states();
+    }
+
+    /// Returns the current debugging setup as session.
+    pub fn session(&self) -> Session {
+        Session {
+            breakpoints: self
+                .instruction_list_states
+                .breakpoint_lines(BreakpointState::Enabled),
+            disabled_breakpoints: self
+                .instruction_list_states
+                .breakpoint_lines(BreakpointState::Disabled),
+            panels: PanelSession {
+                show_call_stack: Some(self.show_call_stack),
+                show_gamma: Some(self.show_gamma),
+            },
+        }
+    }
+
     fn start_debug_select_mode(&mut self) {
         let state = State::DebugSelect(
             Box::new(self.state.clone()),
@@ -369,35 +1574,124 @@ impl App {
         self.state = state;
     }
 
+    /// Starts the program and runs its first instruction, like [s].
+    fn start(&mut self) {
+        self.instruction_list_states
+            .set_start(self.runtime.next_instruction_index() as i32);
+        self.state = State::Running(self.instruction_list_states.breakpoints_set());
+        _ = self.step();
+        if self.present_delay.is_some() && self.auto_run_available() {
+            self.auto_run = Some(self.new_auto_run(Instant::now()));
+        }
+    }
+
+    /// Runs the program until the next breakpoint or the end, like [r].
+    fn run_to_breakpoint(&mut self) {
+        _ = self.step();
+        while !self.instruction_list_states.is_breakpoint() {
+            match self.step() {
+                Ok(bool) => {
+                    if bool {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        if matches!(self.state, State::Running(_)) && self.instruction_list_states.is_breakpoint() {
+            self.hooks.trigger(HookEvent::BreakpointHit);
+        }
+    }
+
     fn reset(&mut self) {
+        let last_run = self.runtime.runtime_memory().clone();
         self.runtime.reset();
+        // the edited values are discarded
+        self.memory_edited = false;
+        if let Some(profile) = self.line_profile.as_mut() {
+            profile.reset();
+        }
+        if self.machine_view.is_some() {
+            self.machine_view = Some(MachineView::default());
+        }
+        self.value_history.clear();
         self.instruction_list_states.set(-1);
         self.instruction_list_states.deselect();
         self.state = State::Default;
-        // recreate memory lists manager to remove set index memory cells from tui
-        self.memory_lists_manager =
-            MemoryListsManager::new(self.runtime.runtime_memory(), &self.theme);
+        // reset memory lists manager to remove set index memory cells from tui
+        self.memory_lists_manager.reset(&last_run);
     }
 
     /// Performs an action. Action depends on current app state.
     ///
-    /// CustomInstruction: exit custom instruction popup and resume running state
-    /// Playground: exit the program
+    /// Closes the top layer if it can be closed with escape (see [`ui::layers::Layer::closed_by_escape`]),
+    /// otherwise the program is exited.
     ///
     /// Return value indicates if the program should be closed.
     fn escape_key(&mut self) -> Result<bool> {
-        match &self.state {
-            State::CustomInstruction(_) => {
-                self.state = State::Running(self.instruction_list_states.breakpoints_set())
-            }
-            State::RuntimeError(e, _) => return Err(e.clone())?,
-            State::CustomInstructionError(e, _) => return Err(e.clone())?,
-            State::BuildProgramError(e) => return Err(e.clone())?,
-            _ => return Ok(true),
+        // escape closes the top layer, if that layer can't be closed with escape the app is exited
+        match self.layers().last() {
+            Some(layer) if layer.closed_by_escape() => self.close_layer(layer),
+            _ => match &self.state {
+                State::RuntimeError(e, _) => return Err(e.clone())?,
+                State::CustomInstructionError(e, _) => return Err(e.clone())?,
+                State::BuildProgramError(e) => return Err(e.clone())?,
+                _ => return Ok(self.quit()),
+            },
         }
         Ok(false)
     }
 
+    /// Opens the quit prompt if there are changes that would be lost by quitting.
+    ///
+    /// Returns true if the app can be closed right away.
+    fn quit(&mut self) -> bool {
+        if !self.unsaved_changes().is_empty() {
+            self.state = State::QuitPrompt(Box::new(self.state.clone()));
+            return false;
+        }
+        true
+    }
+
+    /// Returns true if breakpoints were changed and the session is not saved automatically.
+    fn breakpoints_changed(&self) -> bool {
+        self.confirm_quit
+            && self.saved_breakpoints != self.instruction_list_states.breakpoint_states()
+    }
+
+    /// Returns true if instructions were run in the playground since it was last saved, instructions in other modes
+    /// are not recorded in the timeline.
+    fn playground_changed(&self) -> bool {
+        self.timeline.program() != self.saved_playground_program
+    }
+
+    /// Returns true if an instruction has been entered in the playground but was not run yet.
+    fn playground_input_entered(&self) -> bool {
+        let state = match &self.state {
+            State::QuitPrompt(state) => state.as_ref(),
+            state => state,
+        };
+        matches!(state, State::Playground(state) if !state.input.trim().is_empty())
+    }
+
+    /// Returns descriptions of the changes that are lost when the app is closed, listed in the quit prompt.
+    fn unsaved_changes(&self) -> Vec<&'static str> {
+        let mut changes = Vec::new();
+        if self.breakpoints_changed() {
+            changes.push("Breakpoints have been changed.");
+        }
+        if self.memory_edited {
+            changes.push("Memory values have been edited.");
+        }
+        if self.playground_changed() {
+            changes.push("Instructions of the playground have not been saved.");
+        }
+        if self.playground_input_entered() {
+            changes.push("The entered instruction has not been run.");
+        }
+        changes
+    }
+
     /// Performs an action. Action depends on current app state.
     ///
     /// CustomInstruction: Enter a char
@@ -422,6 +1716,7 @@ impl App {
 
                 self.right_key();
             }
+            State::EditMemory(_, edit) => edit.push_char(to_insert),
             _ => (),
         }
     }
@@ -453,6 +1748,11 @@ impl App {
                     self.left_key()
                 }
             }
+            State::EditMemory(_, edit) => {
+                if let Some(input) = &mut edit.input {
+                    input.pop();
+                }
+            }
             _ => (),
         }
     }
@@ -518,6 +1818,17 @@ impl App {
                 let len = state.items_to_display().len();
                 list_down(&mut state.allowed_values_state, &len);
             }
+            State::Timeline(_, selected) => {
+                *selected = (*selected + 1).min(self.timeline.entries().len());
+            }
+            State::Labels(_, selected) => {
+                *selected =
+                    (*selected + 1).min(self.runtime.label_blocks().len().saturating_sub(1));
+            }
+            State::EditMemory(_, edit) if !edit.is_typing() => {
+                let len = MemoryLocation::all(self.runtime.runtime_memory()).len();
+                edit.selected = (edit.selected + 1).min(len.saturating_sub(1));
+            }
             _ => (),
         }
     }
@@ -530,6 +1841,12 @@ impl App {
             State::CustomInstruction(state) | State::Playground(state) => {
                 list_up(&mut state.allowed_values_state, true);
             }
+            State::Timeline(_, selected) | State::Labels(_, selected) => {
+                *selected = selected.saturating_sub(1);
+            }
+            State::EditMemory(_, edit) if !edit.is_typing() => {
+                edit.selected = edit.selected.saturating_sub(1);
+            }
             _ => (),
         }
     }
@@ -537,6 +1854,7 @@ impl App {
     /// Performs an action. Action depends on current app state.
     ///
     /// CustomInstruction | Playground: If an element is selected in the list, it is filled in to the text area
+    /// DebugSelect: Returns to the line that was left with the last jump back
     fn tab_key(&mut self) {
         match self.state.borrow_mut() {
             State::CustomInstruction(state) | State::Playground(state) => {
@@ -547,6 +1865,7 @@ impl App {
                     state.allowed_values_state.select(None);
                 }
             }
+            State::DebugSelect(_, _) => self.instruction_list_states.jump_forward(),
             _ => (),
         }
     }
@@ -555,6 +1874,7 @@ impl App {
     ///
     /// CustomInstruction: Try to parse the text currently stored in the input field as instruction and run it
     /// CustomInstructionError: App state is set to running
+    /// DebugSelect: Selects the line the selected jump instruction jumps to
     fn enter_key(&mut self) -> Result<()> {
         match &self.state.clone() {
             State::CustomInstruction(state) => self.custom_instruction_enter(state, false)?,
@@ -578,11 +1898,67 @@ impl App {
                     &self.theme,
                 ));
             }
+            State::Timeline(_, selected) => {
+                let memory = self.timeline.rollback(*selected);
+                self.restore_timeline(memory);
+            }
+            State::Labels(_, selected) => {
+                if let Some((_, block)) = self.runtime.label_blocks().get(*selected) {
+                    self.record_breakpoints();
+                    self.instruction_list_states
+                        .toggle_breakpoints(block.clone());
+                }
+            }
+            State::EditMemory(state, edit) => self.edit_memory_enter(state, edit),
+            State::DebugSelect(_, _) => {
+                if let Some(target) = self
+                    .instruction_list_states
+                    .selected_line()
+                    .and_then(|f| self.runtime.jump_target(f))
+                {
+                    self.instruction_list_states.jump_to(target);
+                }
+            }
             _ => (),
         }
         Ok(())
     }
 
+    /// Starts entering a value for the selected memory location, if no value is entered yet, otherwise the entered
+    /// value is written to the memory.
+    ///
+    /// The input is kept if it is not a valid value.
+    fn edit_memory_enter(&mut self, state: &State, edit: &MemoryEdit) {
+        let Some(location) = MemoryLocation::all(self.runtime.runtime_memory())
+            .get(edit.selected)
+            .cloned()
+        else {
+            return;
+        };
+        let mut edit = edit.clone();
+        match edit.input {
+            None => {
+                edit.input = Some(
+                    location
+                        .value(self.runtime.runtime_memory())
+                        .map_or(String::new(), |f| f.to_string()),
+                );
+            }
+            Some(_) => {
+                let Some(value) = edit.parse_input() else {
+                    return;
+                };
+                self.undo_history.record(Snapshot::Memory(
+                    location.clone(),
+                    location.value(self.runtime.runtime_memory()),
+                ));
+                self.set_memory_value(&location, value);
+                edit.input = None;
+            }
+        }
+        self.state = State::EditMemory(Box::new(state.clone()), edit);
+    }
+
     fn custom_instruction_enter(
         &mut self,
         state: &SingleInstruction,
@@ -597,6 +1973,29 @@ impl App {
             return Ok(());
         }
         let instruction = match Instruction::try_from(instruction_str.as_str()) {
+            // macros that are defined in the program can be called
+            Ok(Instruction::Macro(mut call)) => {
+                match self.runtime.control_flow().macros.expand(&call) {
+                    Ok(body) => call.body = body,
+                    Err(reason) => {
+                        self.state = State::BuildProgramError(BuildProgramError {
+                            reason: BuildProgramErrorTypes::MacroInvalid(1, reason),
+                        });
+                        return Ok(());
+                    }
+                }
+                Instruction::Macro(call)
+            }
+            // a loop consists of multiple lines, so it can not be run as a single instruction
+            Ok(Instruction::Loop(line)) => {
+                self.state = State::BuildProgramError(BuildProgramError {
+                    reason: BuildProgramErrorTypes::LoopInvalid(
+                        1,
+                        format!("'{}' can only be used inside of a program", line.kind),
+                    ),
+                });
+                return Ok(());
+            }
             Ok(instruction) => instruction,
             Err(e) => {
                 self.state = State::CustomInstructionError(
@@ -612,42 +2011,83 @@ impl App {
         };
         // check if instruction is allowed
         if let Some(ic) = &self.instruction_config {
-            if let Err(e) = runtime::builder::check_instructions(&[instruction.clone()], ic) {
+            if let Err(e) =
+                runtime::builder::check_instructions(std::slice::from_ref(&instruction), ic)
+            {
                 // instruction could not be build, because instruction is forbidden
                 self.state = State::BuildProgramError(*e);
                 return Ok(());
             }
+            if ic.enforces_grammar() {
+                let parts = instruction_str.split_whitespace().collect::<Vec<&str>>();
+                if let Err(e) = check_operands(&instruction, &parts, ic.spec) {
+                    self.state = State::CustomInstructionError(
+                        e.into_parse_single_instruction_error(
+                            instruction_str.to_string(),
+                            "input_field",
+                            1,
+                        ),
+                        is_playground,
+                    );
+                    return Ok(());
+                }
+            }
         }
 
-        let instruction_line = Line::from(instruction.to_spans(&SyntaxHighlighter::new(
-            &self.theme.syntax_highlighting_theme(),
-        )));
+        let instruction_line = if self.enable_syntax_highlighting {
+            Line::from(instruction.to_spans(&SyntaxHighlighter::new(
+                &self.theme.syntax_highlighting_theme(),
+            )))
+        } else {
+            Line::from(instruction_str.clone())
+        };
+        let memory_before = self.runtime.runtime_memory().clone();
+        // position in the program, before the instruction is run
+        let position = self.program_position();
         if let Err(e) = self.runtime.run_foreign_instruction(instruction) {
             self.state = State::RuntimeError(e, is_playground);
+            if !is_playground {
+                self.write_repro_on_error();
+            }
             return Ok(());
         }
         // instruction was executed successfully
+        if self.show_value_history {
+            self.value_history
+                .record(None, &memory_before, self.runtime.runtime_memory());
+        }
         let instruction_run = state.input.clone();
+        if !is_playground {
+            // instructions that are run while debugging are always written to the file, together with the position
+            // in the program, so that the intervention can be reproduced
+            if let Some(path) = &self.command_history_file {
+                utils::write_line_to_file(&format!("{instruction_str} // {position}"), path)?;
+            }
+        }
         // add instruction to executed instructions, if it is not contained already and if it is not empty
         if !self.executed_custom_instructions.contains(&instruction_run)
             && !instruction_run.is_empty()
         {
-            // write instruction to file, if it is set
+            // write instruction to file, if it is set and it was not already written while debugging
             if let Some(path) = &self.command_history_file {
-                utils::write_line_to_file(&instruction_run, path)?;
+                if is_playground {
+                    utils::write_line_to_file(&instruction_run, path)?;
+                }
             }
             self.executed_custom_instructions.push(instruction_run);
         }
         // set new state
         if is_playground {
-            // if in playground mode, add instruction to main window
-            if self.enable_syntax_highlighting {
-                self.instruction_list_states
-                    .add_instruction(instruction_line);
-            } else {
-                self.instruction_list_states
-                    .add_instruction(Line::from(instruction_str));
-            }
+            // if in playground mode, add instruction to main window and timeline
+            self.timeline.push(
+                instruction_line.clone(),
+                instruction_str.clone(),
+                &memory_before,
+                self.runtime.runtime_memory().clone(),
+            );
+            self.playground_saved = None;
+            self.instruction_list_states
+                .add_instruction(instruction_line);
             self.state = State::Playground(SingleInstruction::new(
                 &self.executed_custom_instructions,
                 &self.theme,
@@ -0,0 +1,430 @@
+use std::io::{BufRead, Write};
+
+use miette::{IntoDiagnostic, Result};
+
+use crate::runtime::{builder::remove_special_commented_lines, Runtime, RuntimeMemory};
+
+/// Linear, line oriented interface that can be used instead of the tui.
+///
+/// Commands are read line by line and every output is a complete line of text,
+/// this makes the interface usable with screen readers and braille displays.
+pub struct PlainUi {
+    runtime: Runtime,
+    /// Lines of the program without the lines that start with `#`, used to print instructions.
+    program: Vec<String>,
+    /// Lines (1-based) in which a breakpoint is set.
+    breakpoints: Vec<usize>,
+    /// Set when a runtime error occurred, the program can not continue until it is reset.
+    stopped: bool,
+    /// Memory after the last run of the program that finished, written with `--dump-state`.
+    finished_memory: Option<RuntimeMemory>,
+}
+
+impl PlainUi {
+    /// Creates a new plain ui, breakpoints are set in the lines `breakpoints`.
+    ///
+    /// `program` are the lines from which the runtime was build, the lines that start with `#` are removed like
+    /// when the instructions are build, so that the lines match the instructions.
+    pub fn new(runtime: Runtime, program: &[String], breakpoints: Option<Vec<usize>>) -> Self {
+        Self {
+            runtime,
+            program: remove_special_commented_lines(program),
+            breakpoints: breakpoints.unwrap_or_default(),
+            stopped: false,
+            finished_memory: None,
+        }
+    }
+
+    /// Returns the memory after the last run of the program that finished, `None` if the program never finished.
+    pub fn finished_memory(&self) -> Option<&RuntimeMemory> {
+        self.finished_memory.as_ref()
+    }
+
+    /// Reads commands from `input` until `quit` is entered or the input ends, all output is written to `output`.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> Result<()> {
+        writeln!(
+            output,
+            "Program loaded, {} lines. Type help to list all commands.",
+            self.program.len()
+        )
+        .into_diagnostic()?;
+        self.print_next_instruction(&mut output)?;
+        let mut lines = input.lines();
+        loop {
+            write!(output, "> ").into_diagnostic()?;
+            output.flush().into_diagnostic()?;
+            let Some(line) = lines.next() else {
+                writeln!(output).into_diagnostic()?;
+                return Ok(());
+            };
+            let line = line.into_diagnostic()?;
+            if !self.handle_command(line.trim(), &mut output)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Performs the action of the command.
+    ///
+    /// Returns false if the plain ui should be closed.
+    fn handle_command<W: Write>(&mut self, command: &str, output: &mut W) -> Result<bool> {
+        let mut parts = command.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (None, _) => (),
+            (Some("q" | "quit" | "exit"), _) => return Ok(false),
+            (Some("h" | "help"), _) => self.print_help(output)?,
+            (Some("s" | "n" | "step" | "next"), _) => {
+                if self.can_continue(output)? {
+                    self.step(output)?;
+                    self.print_next_instruction(output)?;
+                }
+            }
+            (Some("r" | "run"), _) => {
+                if self.can_continue(output)? {
+                    // run to the next breakpoint
+                    while self.step(output)? {
+                        let idx = self.runtime.next_instruction_index();
+                        if self.is_breakpoint(idx) {
+                            writeln!(output, "Breakpoint reached: {}.", self.line_name(idx))
+                                .into_diagnostic()?;
+                            break;
+                        }
+                    }
+                    self.print_next_instruction(output)?;
+                }
+            }
+            (Some("t" | "reset"), _) => {
+                self.runtime.reset();
+                self.stopped = false;
+                writeln!(output, "Program reset.").into_diagnostic()?;
+                self.print_next_instruction(output)?;
+            }
+            (Some("b" | "break"), None) => self.print_breakpoints(output)?,
+            (Some("b" | "break"), line) => {
+                match line.and_then(|f| f.parse::<usize>().ok()) {
+                    Some(line) if line >= 1 && line <= self.program.len() => {
+                        let name = self.line_name(line - 1);
+                        if let Some(idx) = self.breakpoints.iter().position(|f| *f == line) {
+                            self.breakpoints.remove(idx);
+                            writeln!(output, "Breakpoint removed from {name}.")
+                        } else {
+                            self.breakpoints.push(line);
+                            writeln!(output, "Breakpoint set in {name}.")
+                        }
+                    }
+                    _ => writeln!(
+                        output,
+                        "Usage: break LINE, where LINE is between 1 and {}.",
+                        self.program.len()
+                    ),
+                }
+                .into_diagnostic()?;
+            }
+            (Some("l" | "list"), _) => self.print_program(output)?,
+            (Some("m" | "memory"), _) => self.print_memory(output)?,
+            (Some("stack"), _) => self.print_stack(output)?,
+            (Some(command), _) => {
+                writeln!(
+                    output,
+                    "Unknown command: {command}. Type help to list all commands."
+                )
+                .into_diagnostic()?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns true if the program can continue, otherwise the reason why it can't is printed.
+    fn can_continue<W: Write>(&self, output: &mut W) -> Result<bool> {
+        if self.stopped {
+            writeln!(
+                output,
+                "The program stopped because of a runtime error. Type reset to start again."
+            )
+            .into_diagnostic()?;
+            return Ok(false);
+        }
+        if self.runtime.finished() {
+            writeln!(output, "Execution finished. Type reset to start again.").into_diagnostic()?;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Runs the next instruction and prints it.
+    ///
+    /// Returns true if execution can continue.
+    fn step<W: Write>(&mut self, output: &mut W) -> Result<bool> {
+        let idx = self.runtime.next_instruction_index();
+        let printed = self.runtime.control_flow().output.len();
+        let res = self.runtime.step();
+        if self.runtime.finished() {
+            self.finished_memory = Some(self.runtime.runtime_memory().clone());
+        }
+        match res {
+            Ok(true) => return Ok(false),
+            Ok(false) => {
+                writeln!(output, "Executed line {}: {}", idx + 1, self.line(idx))
+                    .into_diagnostic()?;
+                if let Some(value) = self.runtime.control_flow().output.get(printed) {
+                    writeln!(output, "Output: {value}").into_diagnostic()?;
+                }
+            }
+            Err(e) => {
+                self.stopped = true;
+                writeln!(
+                    output,
+                    "Runtime error in line {}: {}",
+                    e.line_number, e.reason
+                )
+                .into_diagnostic()?;
+                return Ok(false);
+            }
+        }
+        Ok(!self.runtime.finished())
+    }
+
+    /// Returns true if a breakpoint is set in the line of the instruction with index `idx`.
+    fn is_breakpoint(&self, idx: usize) -> bool {
+        self.breakpoints.contains(&(idx + 1))
+    }
+
+    /// Returns the name of the line of the instruction with index `idx`, used to identify breakpoints.
+    ///
+    /// Contains the label of the instruction, if it has one, e.g. `line 3 (loop)`.
+    fn line_name(&self, idx: usize) -> String {
+        match self.runtime.instruction_label(idx) {
+            Some(label) => format!("line {} ({label})", idx + 1),
+            None => format!("line {}", idx + 1),
+        }
+    }
+
+    /// Returns the trimmed content of the line of the instruction with index `idx`.
+    fn line(&self, idx: usize) -> &str {
+        self.program.get(idx).map_or("", |f| f.trim())
+    }
+
+    fn print_next_instruction<W: Write>(&self, output: &mut W) -> Result<()> {
+        if self.stopped {
+            return Ok(());
+        }
+        if self.runtime.finished() {
+            writeln!(output, "Execution finished.")
+        } else {
+            let idx = self.runtime.next_instruction_index();
+            writeln!(output, "Next line {}: {}", idx + 1, self.line(idx))
+        }
+        .into_diagnostic()
+    }
+
+    fn print_program<W: Write>(&self, output: &mut W) -> Result<()> {
+        for (idx, line) in self.program.iter().enumerate() {
+            let mut markers = Vec::new();
+            if !self.runtime.finished() && idx == self.runtime.next_instruction_index() {
+                markers.push("next");
+            }
+            if self.is_breakpoint(idx) {
+                markers.push("breakpoint");
+            }
+            if markers.is_empty() {
+                writeln!(output, "Line {}: {}", idx + 1, line.trim())
+            } else {
+                writeln!(
+                    output,
+                    "Line {} ({}): {}",
+                    idx + 1,
+                    markers.join(", "),
+                    line.trim()
+                )
+            }
+            .into_diagnostic()?;
+        }
+        Ok(())
+    }
+
+    fn print_breakpoints<W: Write>(&self, output: &mut W) -> Result<()> {
+        if self.breakpoints.is_empty() {
+            return writeln!(output, "No breakpoints set.").into_diagnostic();
+        }
+        let mut breakpoints = self.breakpoints.clone();
+        breakpoints.sort_unstable();
+        writeln!(
+            output,
+            "Breakpoints: {}",
+            breakpoints
+                .iter()
+                .map(|f| self.line_name(f - 1))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+        .into_diagnostic()
+    }
+
+    fn print_memory<W: Write>(&self, output: &mut W) -> Result<()> {
+        let memory = self.runtime.runtime_memory();
+        let mut accumulators = memory.accumulators.values().collect::<Vec<_>>();
+        accumulators.sort_by_key(|f| f.id);
+        for acc in accumulators {
+            writeln!(output, "Accumulator {}", acc.to_string().trim()).into_diagnostic()?;
+        }
+        if let Some(gamma) = memory.gamma {
+            match gamma {
+                Some(v) => writeln!(output, "Gamma: {v}"),
+                None => writeln!(output, "Gamma: None"),
+            }
+            .into_diagnostic()?;
+        }
+        let mut memory_cells = memory.program_memory_cells().collect::<Vec<_>>();
+        memory_cells.sort_by(|a, b| a.label.cmp(&b.label));
+        for cell in memory_cells {
+            writeln!(output, "Memory cell {}", cell.to_string().trim()).into_diagnostic()?;
+        }
+        let mut index_memory_cells = memory.index_memory_cells.iter().collect::<Vec<_>>();
+        index_memory_cells.sort_by_key(|f| f.0);
+        for (idx, value) in index_memory_cells {
+            match value {
+                Some(v) => writeln!(output, "Index memory cell {idx}: {v}"),
+                None => writeln!(output, "Index memory cell {idx}: None"),
+            }
+            .into_diagnostic()?;
+        }
+        Ok(())
+    }
+
+    fn print_stack<W: Write>(&self, output: &mut W) -> Result<()> {
+        let stack = &self.runtime.runtime_memory().stack;
+        if stack.is_empty() {
+            writeln!(output, "Stack is empty.")
+        } else {
+            writeln!(
+                output,
+                "Stack, top first: {}",
+                stack
+                    .iter()
+                    .rev()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+        .into_diagnostic()?;
+        let call_stack = &self.runtime.control_flow().call_stack;
+        if call_stack.is_empty() {
+            writeln!(output, "Call stack is empty.")
+        } else {
+            writeln!(
+                output,
+                "Call stack, top first: {}",
+                call_stack
+                    .iter()
+                    .rev()
+                    .map(|f| format!("line {}", f + 1))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+        .into_diagnostic()
+    }
+
+    fn print_help<W: Write>(&self, output: &mut W) -> Result<()> {
+        for line in [
+            "step (s, n): run the next instruction",
+            "run (r): run to the next breakpoint or to the end of the program",
+            "reset (t): reset the program to the start",
+            "break LINE (b): set or remove a breakpoint in line LINE",
+            "break (b): list all breakpoints",
+            "list (l): print the program",
+            "memory (m): print accumulators and memory cells",
+            "stack: print stack and call stack",
+            "quit (q): exit",
+        ] {
+            writeln!(output, "{line}").into_diagnostic()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils::{runtime_from_str, string_literal_to_vec};
+
+    use super::PlainUi;
+
+    const PROGRAM: &str = "a := 5
+a := a + 1
+p(h1) := a
+a := a - 2";
+
+    fn run(program: &str, breakpoints: Option<Vec<usize>>, input: &str) -> String {
+        let mut plain_ui = PlainUi::new(
+            runtime_from_str(program).unwrap(),
+            &string_literal_to_vec(program),
+            breakpoints,
+        );
+        let mut output = Vec::new();
+        plain_ui.run(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_plain_ui_step() {
+        let output = run(PROGRAM, None, "s\nn\nm\n");
+        assert!(output.contains("Next line 1: a := 5"));
+        assert!(output.contains("Executed line 2: a := a + 1"));
+        assert!(output.contains("Next line 3: p(h1) := a"));
+        assert!(output.contains("Accumulator α0: 6"));
+        assert!(output.contains("Memory cell h1: None"));
+    }
+
+    #[test]
+    fn test_plain_ui_header() {
+        // the header is not build into an instruction, so it is not a line of the program
+        let program = format!("# alpha_tui: accumulators=1\n{PROGRAM}");
+        let output = run(&program, Some(vec![3]), "s\nr\n");
+        assert!(output.contains("Program loaded, 4 lines."));
+        assert!(output.contains("Executed line 1: a := 5"));
+        assert!(output.contains("Breakpoint reached: line 3.\nNext line 3: p(h1) := a"));
+    }
+
+    #[test]
+    fn test_plain_ui_run_to_breakpoint() {
+        let output = run(PROGRAM, Some(vec![3]), "r\nr\nr\n");
+        assert!(output.contains("Breakpoint reached: line 3.\nNext line 3: p(h1) := a"));
+        assert!(output.contains("Execution finished.\n"));
+        assert!(output.contains("Execution finished. Type reset to start again."));
+    }
+
+    #[test]
+    fn test_plain_ui_runtime_error() {
+        let output = run("a := p(h1)", None, "s\ns\nreset\nq\ns\n");
+        assert!(output.contains("Runtime error in line 1"));
+        assert!(output.contains("The program stopped because of a runtime error."));
+        assert!(output.contains("Program reset.\nNext line 1: a := p(h1)"));
+        assert!(!output.contains("Executed"));
+    }
+
+    #[test]
+    fn test_plain_ui_print() {
+        let output = run("a := 5\nprint a\na := 7", None, "r\n");
+        assert!(output.contains("Executed line 2: print a\nOutput: 5\n"));
+        assert_eq!(output.matches("Output:").count(), 1);
+    }
+
+    #[test]
+    fn test_plain_ui_breakpoints() {
+        let output = run(PROGRAM, None, "b 2\nl\nb 2\nb 9\n");
+        assert!(output.contains("Breakpoint set in line 2."));
+        assert!(output.contains("Line 1 (next): a := 5\nLine 2 (breakpoint): a := a + 1"));
+        assert!(output.contains("Breakpoint removed from line 2."));
+        assert!(output.contains("Usage: break LINE, where LINE is between 1 and 4."));
+    }
+
+    #[test]
+    fn test_plain_ui_breakpoint_labels() {
+        let program = "a := 5\nloop:\na := a - 1\nif a > 0 then goto loop\ndone: a := 0";
+        let output = run(program, Some(vec![5]), "b 3\nb\nr\n");
+        assert!(output.contains("Breakpoint set in line 3 (loop)."));
+        assert!(output.contains("Breakpoints: line 3 (loop), line 5 (done)"));
+        assert!(output.contains("Breakpoint reached: line 3 (loop)."));
+    }
+}
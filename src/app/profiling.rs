@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+/// Collects timings of the app, displayed in the profiling overlay.
+///
+/// Used to diagnose a sluggish ui when large programs or a large number of memory cells are loaded.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct RenderProfile {
+    /// Time it took to render the last frame.
+    pub frame_time: Duration,
+    /// Longest time it took to render a frame.
+    pub max_frame_time: Duration,
+    /// Time it took to handle the last key event.
+    pub event_time: Duration,
+    /// Number of frames rendered.
+    pub frames: u64,
+}
+
+impl RenderProfile {
+    /// Records the render time of a frame.
+    pub fn record_frame(&mut self, time: Duration) {
+        self.frame_time = time;
+        self.max_frame_time = self.max_frame_time.max(time);
+        self.frames += 1;
+    }
+
+    /// Records the time it took to handle a key event.
+    pub fn record_event(&mut self, time: Duration) {
+        self.event_time = time;
+    }
+
+    /// Returns the lines displayed in the overlay.
+    ///
+    /// `instructions` and `memory_entries` are the sizes of the instruction list and of the memory lists.
+    pub fn lines(&self, instructions: usize, memory_entries: usize) -> Vec<String> {
+        vec![
+            format!(
+                "frame: {} (max {})",
+                format_duration(self.frame_time),
+                format_duration(self.max_frame_time)
+            ),
+            format!("event: {}", format_duration(self.event_time)),
+            format!("frames: {}", self.frames),
+            format!("instructions: {instructions}"),
+            format!("memory entries: {memory_entries}"),
+        ]
+    }
+}
+
+/// Collects how long each instruction line took to run, displayed together with the hit counts of the runtime
+/// (see [`crate::runtime::Runtime::line_hits`]) in the profile column next to the instructions and written to the
+/// file set with `--profile-export`.
+///
+/// Only the steps of the auto-run are timed, single steps are dominated by the time the user takes to press a key.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LineProfile {
+    times: Vec<Duration>,
+}
+
+impl LineProfile {
+    pub fn new(instructions: usize) -> Self {
+        Self {
+            times: vec![Duration::ZERO; instructions],
+        }
+    }
+
+    /// Records that the run of the instruction at `idx` took `time`.
+    pub fn record(&mut self, idx: usize, time: Duration) {
+        if let Some(line) = self.times.get_mut(idx) {
+            *line += time;
+        }
+    }
+
+    /// Returns the time that was spent running the instruction at `idx`.
+    pub fn time(&self, idx: usize) -> Duration {
+        self.times.get(idx).copied().unwrap_or_default()
+    }
+
+    /// Clears the times, called when the runtime is reset and its hit counts are cleared.
+    pub fn reset(&mut self) {
+        self.times.fill(Duration::ZERO);
+    }
+
+    /// Returns the text of the profile column for the instruction at `idx`, empty if the line was never run.
+    ///
+    /// `line_hits` are the hit counts of the runtime.
+    pub fn column(&self, idx: usize, line_hits: &[u64]) -> String {
+        let hits = line_hits.get(idx).copied().unwrap_or_default();
+        if hits == 0 {
+            return String::new();
+        }
+        format!("{:>6} {:>9}", hits, format_duration(self.time(idx)))
+    }
+
+    /// Returns the profile as csv, with one row for each line of `instructions`.
+    ///
+    /// `line_hits` are the hit counts of the runtime.
+    pub fn to_csv(&self, instructions: &[String], line_hits: &[u64]) -> String {
+        let mut csv = String::from("line,hits,time_ms,instruction\n");
+        for (idx, instruction) in instructions.iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{},{:.3},\"{}\"\n",
+                idx + 1,
+                line_hits.get(idx).copied().unwrap_or_default(),
+                self.time(idx).as_secs_f64() * 1000.0,
+                instruction.trim().replace('"', "\"\"")
+            ));
+        }
+        csv
+    }
+}
+
+/// Formats the duration in milliseconds with two decimal places.
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{LineProfile, RenderProfile};
+
+    #[test]
+    fn test_record_frame() {
+        let mut profile = RenderProfile::default();
+        profile.record_frame(Duration::from_millis(5));
+        profile.record_frame(Duration::from_millis(2));
+        assert_eq!(profile.frame_time, Duration::from_millis(2));
+        assert_eq!(profile.max_frame_time, Duration::from_millis(5));
+        assert_eq!(profile.frames, 2);
+        assert_eq!(profile.lines(10, 3)[0], "frame: 2.00ms (max 5.00ms)");
+    }
+
+    #[test]
+    fn test_line_profile() {
+        let mut profile = LineProfile::new(2);
+        profile.record(0, Duration::from_millis(3));
+        profile.record(0, Duration::from_millis(2));
+        // instructions outside of the program are ignored
+        profile.record(5, Duration::from_millis(1));
+        assert_eq!(profile.time(0), Duration::from_millis(5));
+        // the hits are read from the runtime
+        let line_hits = [3, 0];
+        assert_eq!(profile.column(0, &line_hits), "     3    5.00ms");
+        assert_eq!(profile.column(1, &line_hits), "");
+        assert_eq!(
+            profile.to_csv(
+                &["a := \"1\"".to_string(), "  b := 2".to_string()],
+                &line_hits
+            ),
+            "line,hits,time_ms,instruction\n1,3,5.000,\"a := \"\"1\"\"\"\n2,0,0.000,\"b := 2\"\n"
+        );
+        profile.reset();
+        assert_eq!(profile.time(0), Duration::ZERO);
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use miette::{miette, IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::state_dump::StateDump;
+
+/// Command that is sent to the remote control, one json object per line, e.g. `{"command": "step"}`.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ControlRequest {
+    /// Starts the program or runs the next instruction, like [s] and [n].
+    Step,
+    /// Runs the program until the next breakpoint or the end, like [r].
+    Run,
+    /// Resets the program, like [t].
+    Reset,
+    /// Only returns the state, every response contains the memory.
+    ReadMemory,
+    /// Sets the values of the memory locations in `memory`, the other locations keep their values.
+    WriteMemory { memory: StateDump },
+}
+
+/// State of the program, that is sent as response to every request.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ControlResponse {
+    /// Reason why the request was not performed, the other fields contain the unchanged state.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub status: ControlStatus,
+    /// Line of the instruction that is run next.
+    pub line: usize,
+    /// Number of steps that were run since the program was started.
+    pub steps: usize,
+    pub memory: StateDump,
+}
+
+/// Status of the program in a [`ControlResponse`].
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlStatus {
+    /// The program was not started yet.
+    NotStarted,
+    Running,
+    Finished,
+    RuntimeError,
+    /// The tui shows a popup or a mode, in which the program can not be controlled remotely.
+    Busy,
+}
+
+/// Request that was received by the remote control and waits for its response.
+pub struct PendingRequest {
+    pub request: ControlRequest,
+    responder: Sender<ControlResponse>,
+}
+
+impl PendingRequest {
+    /// Creates a request, its response is received from the returned receiver.
+    pub fn new(request: ControlRequest) -> (Self, Receiver<ControlResponse>) {
+        let (responder, response) = mpsc::channel();
+        (Self { request, responder }, response)
+    }
+
+    /// Sends the response to the client that sent the request.
+    ///
+    /// Errors are ignored, the client might have disconnected.
+    pub fn respond(self, response: ControlResponse) {
+        _ = self.responder.send(response);
+    }
+}
+
+/// Starts the remote control at `addr`, e.g. `127.0.0.1:4000`, that lets external tools drive the debugger over tcp.
+///
+/// Clients send one request per line and receive one response per line, both are json (see [`ControlRequest`] and
+/// [`ControlResponse`]). Requests that can not be parsed are answered with an error, without being sent to the app,
+/// and the connection is closed.
+///
+/// The requests are not authenticated, so addresses that are reachable from other machines are refused, unless
+/// `allow_remote` is set.
+///
+/// Returns the address the remote control listens at and the receiver of the requests, that have to be answered
+/// by the app.
+pub fn start(addr: &str, allow_remote: bool) -> Result<(SocketAddr, Receiver<PendingRequest>)> {
+    let addrs = addr
+        .to_socket_addrs()
+        .map_err(|e| miette!("Unable to start remote control at {addr}: {e}"))?
+        .collect::<Vec<_>>();
+    if let (false, Some(remote)) = (allow_remote, addrs.iter().find(|f| !f.ip().is_loopback())) {
+        return Err(miette!(
+            help = "Use a loopback address, e.g. 127.0.0.1:4000, or set --allow-remote-control",
+            "Unable to start remote control at {addr}: {} is reachable from other machines and the commands are not authenticated",
+            remote.ip()
+        ));
+    }
+    let listener = TcpListener::bind(&addrs[..])
+        .map_err(|e| miette!("Unable to start remote control at {addr}: {e}"))?;
+    let local_addr = listener.local_addr().into_diagnostic()?;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let sender = sender.clone();
+            thread::spawn(move || handle_client(stream, &sender));
+        }
+    });
+    Ok((local_addr, receiver))
+}
+
+/// Answers the requests of a client, until the connection is closed, the app stopped or the client sent an invalid
+/// request.
+fn handle_client(stream: TcpStream, sender: &Sender<PendingRequest>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<ControlRequest>(&line) {
+            Ok(request) => {
+                let (pending, response) = PendingRequest::new(request);
+                if sender.send(pending).is_err() {
+                    return;
+                }
+                let Ok(response) = response.recv() else {
+                    return;
+                };
+                serde_json::to_string(&response)
+            }
+            Err(e) => {
+                // the client does not speak the protocol, e.g. a browser, so the connection is not kept open
+                if let Ok(response) = serde_json::to_string(&serde_json::json!({
+                    "error": format!("Invalid request: {e}")
+                })) {
+                    _ = writeln!(writer, "{response}");
+                }
+                return;
+            }
+        };
+        let Ok(response) = response else {
+            return;
+        };
+        if writeln!(writer, "{response}").is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpStream,
+        thread,
+    };
+
+    use crate::runtime::state_dump::StateDump;
+
+    use super::{start, ControlRequest, ControlResponse, ControlStatus};
+
+    #[test]
+    fn test_control_request() {
+        let parse = |json: &str| serde_json::from_str::<ControlRequest>(json);
+        assert_eq!(
+            parse(r#"{"command": "step"}"#).unwrap(),
+            ControlRequest::Step
+        );
+        assert_eq!(
+            parse(r#"{"command": "read_memory"}"#).unwrap(),
+            ControlRequest::ReadMemory
+        );
+        let ControlRequest::WriteMemory { memory } =
+            parse(r#"{"command": "write_memory", "memory": {"memory_cells": {"h1": 5}}}"#).unwrap()
+        else {
+            panic!("expected write_memory");
+        };
+        assert_eq!(memory.memory_cells["h1"], Some(5));
+        assert!(parse(r#"{"command": "jump"}"#).is_err());
+    }
+
+    #[test]
+    fn test_remote_control() {
+        let (addr, requests) = start("127.0.0.1:0", false).unwrap();
+        thread::spawn(move || {
+            for request in requests {
+                assert_eq!(request.request, ControlRequest::Step);
+                request.respond(ControlResponse {
+                    error: None,
+                    status: ControlStatus::Running,
+                    line: 2,
+                    steps: 1,
+                    memory: StateDump::default(),
+                });
+            }
+        });
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+        writeln!(stream, "{{\"command\": \"step\"}}").unwrap();
+        reader.read_line(&mut line).unwrap();
+        assert!(line.starts_with(r#"{"status":"running","line":2,"steps":1,"memory":"#));
+        line.clear();
+        writeln!(stream, "step").unwrap();
+        reader.read_line(&mut line).unwrap();
+        assert!(line.starts_with(r#"{"error":"Invalid request"#));
+        // the connection is closed after an invalid request
+        line.clear();
+        _ = writeln!(stream, "{{\"command\": \"step\"}}");
+        assert_eq!(reader.read_line(&mut line).unwrap_or(0), 0);
+        assert!(line.is_empty());
+    }
+
+    #[test]
+    fn test_remote_control_remote_address() {
+        // the commands are not authenticated, so the remote control is only reachable from other machines if allowed
+        let err = start("0.0.0.0:0", false).err().unwrap();
+        assert!(err.to_string().contains("reachable from other machines"));
+        assert!(start("0.0.0.0:0", true).is_ok());
+        assert!(start("localhost:0", false).is_ok());
+    }
+}
@@ -0,0 +1,211 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use miette::{miette, IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    instructions::instruction_config::InstructionConfig,
+    runtime::{builder::BuildConfig, memory_config::MemoryConfig},
+    utils,
+};
+
+/// Contains everything that is required to reproduce a session exactly.
+///
+/// Can be written when a program is loaded with `--record-repro` and is replayed with the `repro` command.
+#[derive(PartialEq, Debug, Deserialize, Serialize, Clone)]
+pub struct Repro {
+    /// Version of alpha_tui with which this repro was recorded.
+    pub version: String,
+    /// Name of the file from which the program was loaded.
+    pub file_name: String,
+    /// Content of the program file.
+    pub program: Vec<String>,
+    /// Memory config that was used to build the runtime, only read from repro files of older versions, newer
+    /// versions store it in `config`.
+    #[serde(default, skip_serializing)]
+    pub memory_config: Option<MemoryConfig>,
+    /// Configuration with which the runtime was built, contains the memory config, the limits and the allowed
+    /// instructions, not contained in repro files of older versions.
+    #[serde(default)]
+    pub config: Option<BuildConfig>,
+    /// Restrictions of the instructions that can be run with `run custom instruction`.
+    #[serde(default)]
+    pub custom_instruction_config: Option<InstructionConfig>,
+    /// Enabled breakpoints when recording was started.
+    pub breakpoints: Option<Vec<usize>>,
+    /// Disabled breakpoints when recording was started.
+    #[serde(default)]
+    pub disabled_breakpoints: Vec<usize>,
+    /// Seed of the numbers returned by `rand`, not contained in repro files of older versions.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// All keys that were pressed, in the order in which they were pressed.
+    pub actions: Vec<String>,
+}
+
+impl Repro {
+    /// Creates a new repro for the current version of alpha_tui, without actions.
+    ///
+    /// `breakpoints` are the enabled and the disabled breakpoints.
+    pub fn new(
+        file_name: &str,
+        program: &[String],
+        config: BuildConfig,
+        custom_instruction_config: Option<InstructionConfig>,
+        breakpoints: (Vec<usize>, Vec<usize>),
+        seed: u64,
+    ) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            file_name: file_name.to_string(),
+            program: program.to_vec(),
+            memory_config: None,
+            config: Some(config),
+            custom_instruction_config,
+            breakpoints: Some(breakpoints.0),
+            disabled_breakpoints: breakpoints.1,
+            seed: Some(seed),
+            actions: Vec::new(),
+        }
+    }
+
+    /// Sets the actions of this repro to `keys`, keys that are not used by the app are left out.
+    pub fn set_actions(&mut self, keys: &[KeyEvent]) {
+        self.actions = keys.iter().filter_map(|f| action_from_key(*f)).collect();
+    }
+
+    /// Tries to load the repro from the file located at `path`.
+    pub fn try_from_file(path: &str) -> Result<Self> {
+        match serde_json::from_str(&utils::read_file(path)?.join("\n")) {
+            Ok(repro) => Ok(repro),
+            Err(e) => Err(miette!(
+                "json parse error while loading repro file {path}: {e}"
+            )),
+        }
+    }
+
+    /// Writes this repro to the file located at `path`, the file is created if it does not exist.
+    pub fn write_to_file(&self, path: &str) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).into_diagnostic()?;
+        utils::write_file_atomic(path, content.as_bytes())
+    }
+
+    /// Returns the recorded actions as key events.
+    ///
+    /// Returns an error if an action is unknown.
+    pub fn keys(&self) -> Result<Vec<KeyEvent>> {
+        self.actions
+            .iter()
+            .map(|f| key_from_action(f).ok_or_else(|| miette!("unknown action in repro file: {f}")))
+            .collect()
+    }
+}
+
+/// Converts the key into the representation used in repro files.
+///
+/// Returns `None` if the key is not used by the app.
+pub fn action_from_key(key: KeyEvent) -> Option<String> {
+    let action = match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => format!("<C-{c}>"),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Up => "<up>".to_string(),
+        KeyCode::Down => "<down>".to_string(),
+        KeyCode::Left => "<left>".to_string(),
+        KeyCode::Right => "<right>".to_string(),
+        KeyCode::Enter => "<enter>".to_string(),
+        KeyCode::Esc => "<esc>".to_string(),
+        KeyCode::Tab => "<tab>".to_string(),
+        KeyCode::Backspace => "<backspace>".to_string(),
+        KeyCode::Delete => "<delete>".to_string(),
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// Converts an action from a repro file back into the key.
+pub fn key_from_action(action: &str) -> Option<KeyEvent> {
+    if let Some(c) = action
+        .strip_prefix("<C-")
+        .and_then(|f| f.strip_suffix('>'))
+        .and_then(single_char)
+    {
+        return Some(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+    }
+    let key = match action {
+        "<up>" => KeyCode::Up,
+        "<down>" => KeyCode::Down,
+        "<left>" => KeyCode::Left,
+        "<right>" => KeyCode::Right,
+        "<enter>" => KeyCode::Enter,
+        "<esc>" => KeyCode::Esc,
+        "<tab>" => KeyCode::Tab,
+        "<backspace>" => KeyCode::Backspace,
+        "<delete>" => KeyCode::Delete,
+        _ => KeyCode::Char(single_char(action)?),
+    };
+    Some(KeyEvent::new(key, KeyModifiers::NONE))
+}
+
+/// Returns the character if `s` consists of exactly one character.
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::runtime::builder::BuildConfig;
+
+    use super::{action_from_key, key_from_action, Repro};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_action_conversion() {
+        for key in [
+            key(KeyCode::Char('s')),
+            key(KeyCode::Char('<')),
+            key(KeyCode::Up),
+            key(KeyCode::Enter),
+            key(KeyCode::Esc),
+            key(KeyCode::Delete),
+            KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+        ] {
+            assert_eq!(key_from_action(&action_from_key(key).unwrap()), Some(key));
+        }
+        assert_eq!(
+            action_from_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL)),
+            Some("<C-r>".to_string())
+        );
+        assert_eq!(action_from_key(key(KeyCode::F(1))), None);
+        assert_eq!(key_from_action("<unknown>"), None);
+    }
+
+    #[test]
+    fn test_repro_keys() {
+        let mut repro = Repro::new(
+            "test",
+            &["a := 5".to_string()],
+            BuildConfig::default(),
+            None,
+            (Vec::new(), Vec::new()),
+            7,
+        );
+        repro.set_actions(&[
+            key(KeyCode::Char('s')),
+            key(KeyCode::F(1)),
+            key(KeyCode::Char('n')),
+        ]);
+        assert_eq!(repro.actions, vec!["s", "n"]);
+        assert_eq!(
+            repro.keys().unwrap(),
+            vec![key(KeyCode::Char('s')), key(KeyCode::Char('n'))]
+        );
+    }
+}
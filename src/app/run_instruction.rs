@@ -16,7 +16,7 @@ pub struct SingleInstruction {
     ///
     /// If None, currently no item is selected, if some an item is selected.
     pub allowed_values_state: ListState,
-    /// List of instructions that where already entered manually.
+    /// List of instructions that were already entered manually.
     ///
     /// Used to populate the list.
     pub executed_instructions: Vec<String>,
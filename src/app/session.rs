@@ -0,0 +1,315 @@
+use std::path::Path;
+
+use miette::{miette, IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::utils;
+
+/// Stores the debugging setup of a program file, so that it can be restored when the program is loaded again.
+///
+/// The session is stored in a sidecar file next to the program file, see [`session_file_path`].
+/// New fields should be added with a default value, to make sure that older session files can still be loaded.
+#[derive(PartialEq, Debug, Deserialize, Serialize, Default, Clone)]
+#[serde(default)]
+pub struct Session {
+    /// Lines in which an enabled breakpoint is set.
+    pub breakpoints: Vec<usize>,
+    /// Lines in which a disabled breakpoint is set.
+    pub disabled_breakpoints: Vec<usize>,
+    /// Panels that were manually shown or hidden.
+    pub panels: PanelSession,
+}
+
+/// Stores which panels are shown or hidden.
+///
+/// `None` indicates that the default of the app should be used.
+#[derive(PartialEq, Debug, Deserialize, Serialize, Default, Clone)]
+#[serde(default)]
+pub struct PanelSession {
+    pub show_call_stack: Option<bool>,
+    pub show_gamma: Option<bool>,
+}
+
+impl Session {
+    /// Tries to load the session from the file located at `path`.
+    ///
+    /// Returns `None` if the file does not exist.
+    pub fn try_from_file(path: &str) -> Result<Option<Self>> {
+        if !Path::new(path).is_file() {
+            return Ok(None);
+        }
+        match serde_json::from_str(&utils::read_file(path)?.join("\n")) {
+            Ok(session) => Ok(Some(session)),
+            Err(e) => Err(miette!(
+                "json parse error while loading session file {path}: {e}"
+            )),
+        }
+    }
+
+    /// Writes this session to the file located at `path`, the file is created if it does not exist.
+    pub fn write_to_file(&self, path: &str) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).into_diagnostic()?;
+        utils::write_file_atomic(path, content.as_bytes())
+    }
+
+    /// Returns the part of this stored session that is restored when the program is loaded.
+    ///
+    /// The breakpoints are always restored and merged with the breakpoints set with `--breakpoints`, which stay
+    /// enabled even if they were disabled in the stored session. The panels are only restored if `restore_panels`
+    /// is true.
+    pub fn restored(&self, breakpoints: Option<&Vec<usize>>, restore_panels: bool) -> Self {
+        let mut session = self.clone();
+        if let Some(breakpoints) = breakpoints {
+            session
+                .disabled_breakpoints
+                .retain(|line| !breakpoints.contains(line));
+        }
+        if !restore_panels {
+            session.panels = PanelSession::default();
+        }
+        session
+    }
+
+    /// Returns the session that is stored when the program is closed, `current` is the session of the app and
+    /// `stored` the session that was read when the program was loaded.
+    ///
+    /// The breakpoints are always stored, the panels are only stored if `save_panels` is true, otherwise the stored
+    /// panels are kept. Returns `None` if nothing has to be written, because no breakpoints are set and no session
+    /// was stored before.
+    pub fn to_store(current: Session, stored: Option<&Session>, save_panels: bool) -> Option<Self> {
+        if !save_panels
+            && stored.is_none()
+            && current.breakpoints.is_empty()
+            && current.disabled_breakpoints.is_empty()
+        {
+            return None;
+        }
+        let panels = match stored {
+            Some(stored) if !save_panels => stored.panels.clone(),
+            None if !save_panels => PanelSession::default(),
+            _ => current.panels,
+        };
+        Some(Self { panels, ..current })
+    }
+}
+
+/// Debugging setup that can be handed out to other users, e.g. by a lecturer together with an exercise.
+///
+/// Written by `session export` and read by `session import`. In contrast to the session file, breakpoints are stored
+/// together with the instruction of their line, so that they can be placed in programs in which lines were added or
+/// removed.
+#[derive(PartialEq, Debug, Deserialize, Serialize, Default, Clone)]
+#[serde(default)]
+pub struct SessionSetup {
+    pub breakpoints: Vec<SetupBreakpoint>,
+    pub panels: PanelSession,
+}
+
+/// Breakpoint of a [`SessionSetup`].
+#[derive(PartialEq, Debug, Deserialize, Serialize, Clone)]
+pub struct SetupBreakpoint {
+    /// Line of the breakpoint (starting at 1) in the program the setup was exported from.
+    pub line: usize,
+    /// Instruction in the line of the breakpoint, without comment.
+    pub instruction: String,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl SessionSetup {
+    /// Creates the setup from the session of `program`.
+    pub fn from_session(session: &Session, program: &[String]) -> Self {
+        let breakpoint = |line: usize, enabled: bool| SetupBreakpoint {
+            line,
+            instruction: normalize(program.get(line.wrapping_sub(1)).map_or("", String::as_str)),
+            enabled,
+        };
+        let mut breakpoints = session
+            .breakpoints
+            .iter()
+            .map(|line| breakpoint(*line, true))
+            .chain(
+                session
+                    .disabled_breakpoints
+                    .iter()
+                    .map(|line| breakpoint(*line, false)),
+            )
+            .collect::<Vec<_>>();
+        breakpoints.sort_by_key(|f| f.line);
+        Self {
+            breakpoints,
+            panels: session.panels.clone(),
+        }
+    }
+
+    /// Places the breakpoints of the setup in `program` and applies them, and the panels that are set, to `session`.
+    ///
+    /// A breakpoint is placed in its line, if the line still contains its instruction, otherwise in the nearest line
+    /// that contains the instruction. Returns the breakpoints whose instruction is not contained in the program.
+    pub fn apply(&self, session: &mut Session, program: &[String]) -> Vec<&SetupBreakpoint> {
+        let lines = program.iter().map(|f| normalize(f)).collect::<Vec<_>>();
+        let mut missing = Vec::new();
+        session.breakpoints.clear();
+        session.disabled_breakpoints.clear();
+        for breakpoint in &self.breakpoints {
+            let line = (1..=lines.len())
+                .filter(|line| lines[line - 1] == breakpoint.instruction)
+                .min_by_key(|line| line.abs_diff(breakpoint.line));
+            match line {
+                Some(line) if breakpoint.enabled => session.breakpoints.push(line),
+                Some(line) => session.disabled_breakpoints.push(line),
+                None => missing.push(breakpoint),
+            }
+        }
+        if self.panels.show_call_stack.is_some() {
+            session.panels.show_call_stack = self.panels.show_call_stack;
+        }
+        if self.panels.show_gamma.is_some() {
+            session.panels.show_gamma = self.panels.show_gamma;
+        }
+        missing
+    }
+
+    /// Tries to read the setup from the json file at `path`.
+    pub fn try_from_file(path: &str) -> Result<Self> {
+        serde_json::from_str(&utils::read_file(path)?.join("\n"))
+            .map_err(|e| miette!("json parse error while loading session setup {path}: {e}"))
+    }
+
+    /// Writes the setup as json to the file at `path`.
+    pub fn write_to_file(&self, path: &str) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).into_diagnostic()?;
+        utils::write_file_atomic(path, content.as_bytes())
+    }
+}
+
+/// Returns the line without comment and with single spaces between its parts, so that lines can be compared even if
+/// they were formatted.
+fn normalize(line: &str) -> String {
+    utils::remove_comment(line)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the path of the sidecar file in which the session of the program file at `program_path` is stored.
+pub fn session_file_path(program_path: &str) -> String {
+    format!("{program_path}.session.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{session_file_path, PanelSession, Session, SessionSetup};
+
+    #[test]
+    fn test_session_file_path() {
+        assert_eq!(
+            session_file_path("examples/programs/faculty.alpha"),
+            "examples/programs/faculty.alpha.session.json"
+        );
+    }
+
+    #[test]
+    fn test_session_restored_and_stored() {
+        let stored = Session {
+            breakpoints: vec![1],
+            disabled_breakpoints: vec![2, 3],
+            panels: PanelSession {
+                show_call_stack: Some(false),
+                show_gamma: None,
+            },
+        };
+        // breakpoints of the cli are merged and stay enabled, panels are only restored with --session
+        let restored = stored.restored(Some(&vec![3, 4]), false);
+        assert_eq!(restored.breakpoints, vec![1]);
+        assert_eq!(restored.disabled_breakpoints, vec![2]);
+        assert_eq!(restored.panels, PanelSession::default());
+        assert_eq!(stored.restored(None, true), stored);
+
+        let current = Session {
+            breakpoints: vec![1, 3, 4],
+            disabled_breakpoints: vec![2],
+            panels: PanelSession {
+                show_call_stack: Some(true),
+                show_gamma: Some(true),
+            },
+        };
+        // without --session the stored panels are kept
+        assert_eq!(
+            Session::to_store(current.clone(), Some(&stored), false),
+            Some(Session {
+                panels: stored.panels.clone(),
+                ..current.clone()
+            })
+        );
+        assert_eq!(
+            Session::to_store(current.clone(), Some(&stored), true),
+            Some(current)
+        );
+        // no file is created for programs without breakpoints
+        assert_eq!(Session::to_store(Session::default(), None, false), None);
+        assert!(Session::to_store(Session::default(), Some(&stored), false).is_some());
+    }
+
+    #[test]
+    fn test_session_partial_file() {
+        let session: Session = serde_json::from_str(r#"{"breakpoints":[1,5]}"#).unwrap();
+        assert_eq!(
+            session,
+            Session {
+                breakpoints: vec![1, 5],
+                disabled_breakpoints: Vec::new(),
+                panels: PanelSession::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_session_setup() {
+        let program = [
+            "a := 5",
+            "loop: a := a - 1 // count down",
+            "if a > 0 then goto loop",
+        ]
+        .map(String::from);
+        let session = Session {
+            breakpoints: vec![2],
+            disabled_breakpoints: vec![3],
+            panels: PanelSession {
+                show_call_stack: Some(true),
+                show_gamma: None,
+            },
+        };
+        let setup = SessionSetup::from_session(&session, &program);
+        assert_eq!(setup.breakpoints[0].instruction, "loop: a := a - 1");
+        assert!(!setup.breakpoints[1].enabled);
+        // the breakpoints are moved with their instruction
+        let moved = [
+            "# alpha_tui: max-steps=100",
+            "a := 5",
+            "p(h1) := 0",
+            "loop:  a := a - 1",
+            "if a > 0 then goto loop",
+        ]
+        .map(String::from);
+        let mut imported = Session::default();
+        assert!(setup.apply(&mut imported, &moved).is_empty());
+        assert_eq!(
+            imported,
+            Session {
+                breakpoints: vec![4],
+                disabled_breakpoints: vec![5],
+                panels: session.panels.clone(),
+            }
+        );
+        // breakpoints whose instruction does not exist anymore are not placed
+        let missing = setup.apply(&mut imported, &program[..2]);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].line, 3);
+        assert_eq!(imported.breakpoints, vec![2]);
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    fs::{self, DirBuilder, Permissions},
+    io::{BufRead, BufReader, Write},
+    os::unix::{
+        fs::{DirBuilderExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use crossterm::event::KeyEvent;
+use miette::{miette, IntoDiagnostic, Result};
+
+use super::repro::{action_from_key, key_from_action, Repro};
+
+/// Session that is shared over a local socket.
+///
+/// Viewers that attach receive a repro of the session up to that point, followed by every key that is
+/// pressed afterwards, one action per line. Replaying these keys mirrors the session exactly.
+///
+/// Viewers can send keys in the same format, the host decides if they are handled.
+///
+/// Only the user that shares the session can attach to it, the socket is created with the permissions `0600`.
+///
+/// The socket file is removed when the shared session is dropped.
+pub struct SharedSession {
+    path: PathBuf,
+    sender: Sender<KeyEvent>,
+    /// Keys that were sent by viewers, `None` if the receiver was already taken.
+    remote_actions: Option<Receiver<KeyEvent>>,
+}
+
+/// State that is shared between the thread that accepts viewers and the thread that forwards the keys.
+struct Viewers {
+    /// Repro of the session, new viewers receive it when they attach.
+    repro: Repro,
+    /// Connections of all attached viewers.
+    streams: Vec<UnixStream>,
+}
+
+impl SharedSession {
+    /// Creates the socket at `path` and starts to accept viewers.
+    ///
+    /// `repro` has to describe the session before any key was pressed.
+    ///
+    /// Returns an error if the file at `path` already exists or the socket can not be created.
+    pub fn start(path: &str, repro: Repro) -> Result<Self> {
+        if Path::new(path).exists() {
+            return Err(miette!(
+                help = "Remove the file if no other session is shared with it",
+                "Unable to share session: file {path} already exists"
+            ));
+        }
+        let listener = bind_private(Path::new(path)).map_err(|e| {
+            miette!("Unable to share session: socket {path} could not be created: {e}")
+        })?;
+        let viewers = Arc::new(Mutex::new(Viewers {
+            repro,
+            streams: Vec::new(),
+        }));
+
+        let (remote_sender, remote_actions) = mpsc::channel::<KeyEvent>();
+        let accepting = Arc::clone(&viewers);
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                if let Ok(reading) = stream.try_clone() {
+                    let remote_sender = remote_sender.clone();
+                    thread::spawn(move || forward_keys(reading, &remote_sender));
+                }
+                let Ok(mut viewers) = accepting.lock() else {
+                    return;
+                };
+                let Ok(repro) = serde_json::to_string(&viewers.repro) else {
+                    return;
+                };
+                if writeln!(stream, "{repro}").is_ok() {
+                    viewers.streams.push(stream);
+                }
+            }
+        });
+
+        let (sender, receiver) = mpsc::channel::<KeyEvent>();
+        thread::spawn(move || {
+            for key in receiver {
+                let Some(action) = action_from_key(key) else {
+                    continue;
+                };
+                let Ok(mut viewers) = viewers.lock() else {
+                    return;
+                };
+                viewers.repro.actions.push(action.clone());
+                // viewers that detached are removed
+                viewers
+                    .streams
+                    .retain_mut(|stream| writeln!(stream, "{action}").is_ok());
+            }
+        });
+
+        Ok(Self {
+            path: PathBuf::from(path),
+            sender,
+            remote_actions: Some(remote_actions),
+        })
+    }
+
+    /// Returns a sender to which all keys that are pressed in the session have to be sent.
+    pub fn sender(&self) -> Sender<KeyEvent> {
+        self.sender.clone()
+    }
+
+    /// Returns the receiver for the keys that are sent by viewers, can only be taken once.
+    pub fn take_remote_actions(&mut self) -> Option<Receiver<KeyEvent>> {
+        self.remote_actions.take()
+    }
+}
+
+impl Drop for SharedSession {
+    fn drop(&mut self) {
+        _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Binds a socket at `path` that only the current user can connect to.
+///
+/// The socket is created in a directory that only the current user can access and moved to `path` after its
+/// permissions are set to `0600`, so that nobody else can connect to it in the meantime.
+fn bind_private(path: &Path) -> std::io::Result<UnixListener> {
+    let mut dir_name = path.file_name().unwrap_or_default().to_os_string();
+    dir_name.push(".tmp");
+    let dir = path.with_file_name(dir_name);
+    DirBuilder::new().mode(0o700).create(&dir)?;
+    let socket = dir.join("socket");
+    let listener = UnixListener::bind(&socket)
+        .and_then(|listener| {
+            fs::set_permissions(&socket, Permissions::from_mode(0o600))?;
+            fs::rename(&socket, path)?;
+            Ok(listener)
+        })
+        .inspect_err(|_| _ = fs::remove_file(&socket));
+    _ = fs::remove_dir(&dir);
+    listener
+}
+
+/// Reads the actions from `stream` line by line and sends the keys to `sender`, until the stream is closed.
+fn forward_keys(stream: UnixStream, sender: &Sender<KeyEvent>) {
+    for line in BufReader::new(stream).lines() {
+        let Some(key) = line.ok().as_deref().and_then(key_from_action) else {
+            return;
+        };
+        if sender.send(key).is_err() {
+            return;
+        }
+    }
+}
+
+/// Attaches to the session that is shared at `path`.
+///
+/// Returns the repro of the session up to now, a receiver for the keys that are pressed from now on
+/// and a sender for keys that should be sent to the host.
+/// The receiver is disconnected when the shared session ends.
+pub fn attach(path: &str) -> Result<(Repro, Receiver<KeyEvent>, Sender<KeyEvent>)> {
+    let stream = UnixStream::connect(path)
+        .map_err(|e| miette!("Unable to attach to shared session {path}: {e}"))?;
+    let mut writer = stream.try_clone().into_diagnostic()?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).into_diagnostic()?;
+    let repro: Repro = serde_json::from_str(&line)
+        .map_err(|e| miette!("Unable to attach to shared session {path}: {e}"))?;
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let Some(key) = line.ok().as_deref().and_then(key_from_action) else {
+                return;
+            };
+            if sender.send(key).is_err() {
+                return;
+            }
+        }
+    });
+    let (actions, to_send) = mpsc::channel::<KeyEvent>();
+    thread::spawn(move || {
+        for action in to_send.into_iter().filter_map(action_from_key) {
+            if writeln!(writer, "{action}").is_err() {
+                return;
+            }
+        }
+    });
+    Ok((repro, receiver, actions))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{os::unix::fs::PermissionsExt, time::Duration};
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::{app::repro::Repro, runtime::builder::BuildConfig};
+
+    use super::{attach, SharedSession};
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_attach_to_shared_session() {
+        let path = std::env::temp_dir().join(format!("alpha_tui_share_{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut session = SharedSession::start(
+            path,
+            Repro::new(
+                "test",
+                &["a := 5".to_string()],
+                BuildConfig::default(),
+                None,
+                (vec![1], vec![2]),
+                0,
+            ),
+        )
+        .unwrap();
+        let repro = Repro::new(
+            "test",
+            &[],
+            BuildConfig::default(),
+            None,
+            (Vec::new(), Vec::new()),
+            0,
+        );
+        assert!(SharedSession::start(path, repro).is_err());
+        // only the user that shares the session can attach
+        let mode = std::fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        session.sender().send(key('s')).unwrap();
+        let (repro, receiver, actions) = attach(path).unwrap();
+        session.sender().send(key('n')).unwrap();
+        // the first key is either part of the repro or is received afterwards
+        let mut keys = repro.keys().unwrap();
+        while keys.len() < 2 {
+            keys.push(receiver.recv_timeout(Duration::from_secs(5)).unwrap());
+        }
+        assert_eq!(keys, vec![key('s'), key('n')]);
+        assert_eq!(repro.program, vec!["a := 5"]);
+        assert_eq!(repro.breakpoints, Some(vec![1]));
+        assert_eq!(repro.disabled_breakpoints, vec![2]);
+        // keys of the viewer are received by the host
+        let remote_actions = session.take_remote_actions().unwrap();
+        actions.send(key('b')).unwrap();
+        assert_eq!(
+            remote_actions.recv_timeout(Duration::from_secs(5)),
+            Ok(key('b'))
+        );
+        drop(session);
+        assert!(!std::path::Path::new(path).exists());
+    }
+}
@@ -0,0 +1,233 @@
+use std::rc::Rc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use miette::Result;
+use ratatui::{backend::TestBackend, style::Style, Terminal};
+
+use crate::{
+    runtime::{builder::RuntimeBuilder, Runtime},
+    utils::test_utils::{runtime_from_str_with_program_header, string_literal_to_vec},
+};
+
+use super::{
+    content::BreakpointState,
+    ui::{style::Theme, syntax_highlighting::SyntaxHighlighter},
+    App, State,
+};
+
+/// Width of the terminal the test app is rendered to.
+const WIDTH: u16 = 120;
+/// Height of the terminal the test app is rendered to.
+const HEIGHT: u16 = 40;
+
+/// Headless driver for the tui.
+///
+/// Key events are fed directly into the app and the ui is rendered to a `TestBackend`,
+/// the rendered screen can then be checked for its contents.
+pub struct TestApp {
+    pub app: App,
+    terminal: Terminal<TestBackend>,
+}
+
+impl TestApp {
+    /// Loads the program and creates a new test app from it, the program header is applied.
+    pub fn new(program: &str) -> Self {
+        Self::with_breakpoints(program, None)
+    }
+
+    /// Loads the program and creates a new test app from it, breakpoints are set in the lines `breakpoints`.
+    ///
+    /// Panics if the program can not be built.
+    pub fn with_breakpoints(program: &str, breakpoints: Option<Vec<usize>>) -> Self {
+        let runtime = runtime_from_str_with_program_header(program)
+            .unwrap_or_else(|e| panic!("test program should build: {e:?}"));
+        Self::with_runtime(runtime, program, breakpoints)
+    }
+
+    /// Creates a new test app from a runtime that was built from `program`.
+    pub fn with_runtime(runtime: Runtime, program: &str, breakpoints: Option<Vec<usize>>) -> Self {
+        let theme = Rc::new(Theme::default());
+        let lines = SyntaxHighlighter::new(&theme.syntax_highlighting_theme())
+            .input_to_lines(&string_literal_to_vec(program), true)
+            .expect("test program should be highlighted");
+        let mut app = App::from_runtime(
+            runtime,
+            "test".to_string(),
+            &lines,
+            &breakpoints,
+            None,
+            None,
+            None,
+            false,
+            true,
+            theme,
+        );
+        app.keybinding_hints
+            .update(&app.state)
+            .expect("keybinding hints should be updated for the initial state");
+        Self {
+            app,
+            terminal: Terminal::new(TestBackend::new(WIDTH, HEIGHT))
+                .expect("test terminal should be created"),
+        }
+    }
+
+    /// Creates a new test app in playground mode, the default memory is available.
+    pub fn playground() -> Self {
+        let runtime = RuntimeBuilder::new(&[], "playground")
+            .expect("empty program should be built")
+            .build()
+            .expect("playground runtime should build");
+        let mut app = App::from_runtime(
+            runtime,
+            "Playground".to_string(),
+            &[],
+            &None,
+            None,
+            None,
+            None,
+            true,
+            true,
+            Rc::new(Theme::default()),
+        );
+        app.keybinding_hints
+            .update(&app.state)
+            .expect("keybinding hints should be updated for the initial state");
+        Self {
+            app,
+            terminal: Terminal::new(TestBackend::new(WIDTH, HEIGHT))
+                .expect("test terminal should be created"),
+        }
+    }
+
+    /// Types `text` into the input field and presses enter.
+    pub fn enter_instruction(&mut self, text: &str) -> Result<()> {
+        self.press_all(text)?;
+        self.press_key(KeyCode::Enter)?;
+        Ok(())
+    }
+
+    /// Presses the key with the character `c`.
+    ///
+    /// Returns true if the app would have been closed.
+    pub fn press(&mut self, c: char) -> Result<bool> {
+        self.press_key(KeyCode::Char(c))
+    }
+
+    /// Presses each character of `keys` in order.
+    pub fn press_all(&mut self, keys: &str) -> Result<()> {
+        for c in keys.chars() {
+            self.press(c)?;
+        }
+        Ok(())
+    }
+
+    /// Presses the key `code`.
+    ///
+    /// Returns true if the app would have been closed.
+    pub fn press_key(&mut self, code: KeyCode) -> Result<bool> {
+        self.app
+            .handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    /// Sends a mouse event of kind `kind` at the position `column`, `row`, the ui is rendered before, so that the
+    /// areas of the panels are known.
+    ///
+    /// Returns true if the app would have been closed.
+    pub fn mouse(&mut self, kind: MouseEventKind, column: u16, row: u16) -> Result<bool> {
+        self.screen();
+        self.app.handle_mouse_event(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    /// Resizes the terminal, the new size is used when the app is rendered the next time.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.terminal.backend_mut().resize(width, height);
+    }
+
+    /// Presses the key with the character `c` while control is held down.
+    ///
+    /// Returns true if the app would have been closed.
+    pub fn press_ctrl(&mut self, c: char) -> Result<bool> {
+        self.app
+            .handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+    }
+
+    /// Renders the app and returns the screen content, each row of the terminal is a line.
+    ///
+    /// Panics if the app can not be drawn to the test terminal.
+    pub fn screen(&mut self) -> String {
+        self.terminal
+            .draw(|f| self.app.draw(f))
+            .expect("app should be drawn to the test terminal");
+        let buffer = self.terminal.backend().buffer();
+        let mut screen = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                screen.push_str(buffer.get(x, y).symbol());
+            }
+            screen.push('\n');
+        }
+        screen
+    }
+
+    /// Renders the app and checks if the screen contains `text`.
+    pub fn screen_contains(&mut self, text: &str) -> bool {
+        self.screen().contains(text)
+    }
+
+    /// Renders the app and returns the style of the first cell of `text`, `None` if the screen does not contain
+    /// `text`.
+    pub fn style_of(&mut self, text: &str) -> Option<Style> {
+        self.screen();
+        let buffer = self.terminal.backend().buffer();
+        for y in 0..buffer.area.height {
+            let symbols = (0..buffer.area.width)
+                .map(|x| buffer.get(x, y).symbol())
+                .collect::<Vec<_>>();
+            let Some(idx) = symbols.concat().find(text) else {
+                continue;
+            };
+            let mut len = 0;
+            for (x, symbol) in symbols.iter().enumerate() {
+                if len == idx {
+                    return Some(buffer.get(x as u16, y).style());
+                }
+                len += symbol.len();
+            }
+        }
+        None
+    }
+
+    /// Renders the app and returns the rows of the memory cells panel, without the border and trailing whitespace.
+    ///
+    /// Allows to check for a memory cell exactly, `h1: None` would otherwise also match `h11: None`.
+    pub fn memory_cell_rows(&mut self) -> Vec<String> {
+        self.screen();
+        let area = self.app.mouse_areas.memory_cells;
+        let buffer = self.terminal.backend().buffer();
+        (area.y + 1..area.bottom().saturating_sub(1))
+            .map(|y| {
+                (area.x + 1..area.right().saturating_sub(1))
+                    .map(|x| buffer.get(x, y).symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// Returns the lines (starting at 1) of all breakpoints that are in state `state`.
+    pub fn breakpoints(&self, state: BreakpointState) -> Vec<usize> {
+        self.app.instruction_list_states.breakpoint_lines(state)
+    }
+
+    /// Returns the current state of the app.
+    pub fn state(&self) -> &State {
+        &self.app.state
+    }
+}
@@ -0,0 +1,1191 @@
+use std::{
+    collections::HashMap,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind};
+
+use crate::{
+    runtime::{
+        builder::{BuildConfig, RuntimeBuilder},
+        memory_config::MemoryConfig,
+        state_dump::StateDump,
+    },
+    utils::test_utils::string_literal_to_vec,
+};
+
+use super::{
+    content::BreakpointState,
+    control::ShareLog,
+    keybindings::Keymap,
+    remote_control::{ControlRequest, ControlStatus, PendingRequest},
+    repro::Repro,
+    session::{session_file_path, Session},
+    test_utils::TestApp,
+    ui::layers::Layer,
+    State,
+};
+
+const PROGRAM: &str = "a := 5
+a := a + 1
+p(h1) := a
+a := a - 2";
+
+#[test]
+fn test_app_step() {
+    let mut app = TestApp::new(PROGRAM);
+    assert_eq!(app.state(), &State::Default);
+    assert!(app.screen_contains("α0: None"));
+    app.press('s').unwrap();
+    assert_eq!(app.state(), &State::Running(false));
+    assert!(app.screen_contains("α0: 5"));
+    app.press_all("nn").unwrap();
+    assert!(app.screen_contains("α0: 6"));
+    assert!(app.screen_contains("h1: 6"));
+}
+
+#[test]
+fn test_app_run_to_breakpoint() {
+    let mut app = TestApp::with_breakpoints(PROGRAM, Some(vec![3]));
+    assert!(app.screen_contains("*"));
+    app.press('s').unwrap();
+    assert_eq!(app.state(), &State::Running(true));
+    app.press('r').unwrap();
+    assert!(app.screen_contains("α0: 6"));
+    assert!(app.screen_contains("h1: 6"));
+    assert_eq!(app.state(), &State::Running(true));
+}
+
+#[test]
+fn test_app_auto_run() {
+    let mut app = TestApp::with_breakpoints(PROGRAM, Some(vec![3]));
+    app.press_all("sp").unwrap();
+    assert!(app.screen_contains("Pause auto-run"));
+    // all steps that are due are run at once, independent of the frames that are drawn
+    let now = Instant::now() + Duration::from_secs(1);
+    assert!(app.app.run_auto_run_steps(now).unwrap());
+    // auto-run stops at the breakpoint
+    assert!(app.screen_contains("h1: 6"));
+    assert!(app.screen_contains("Start auto-run"));
+    assert!(!app.app.run_auto_run_steps(now).unwrap());
+    app.press('p').unwrap();
+    assert!(app
+        .app
+        .run_auto_run_steps(now + Duration::from_secs(1))
+        .unwrap());
+    assert_eq!(app.state(), &State::Finished(true));
+}
+
+#[test]
+fn test_app_presentation() {
+    let mut app = TestApp::new(PROGRAM);
+    app.app.enable_presentation(Duration::from_secs(2));
+    assert!(app.screen_contains("Start"));
+    // the auto-run starts with the program and the hints are hidden while it is active
+    app.press('s').unwrap();
+    assert!(app.screen_contains("\u{25b6}\u{25b6} "));
+    assert!(!app.screen_contains("Pause auto-run"));
+    let now = Instant::now();
+    assert!(!app.app.run_auto_run_steps(now).unwrap());
+    // a single step is run, even if multiple steps are due
+    assert!(app
+        .app
+        .run_auto_run_steps(now + Duration::from_secs(10))
+        .unwrap());
+    assert!(app.screen_contains("α0: 6"));
+    assert!(app.memory_cell_rows().contains(&"h1: None".to_string()));
+    app.press('p').unwrap();
+    assert!(app.screen_contains("Start auto-run"));
+}
+
+const CALL_PROGRAM: &str = "a := 1
+call inc
+a := a * 10
+goto done
+inc: a := a + 1
+a := a + 1
+return
+done: p(h1) := a";
+
+#[test]
+fn test_app_edit_memory() {
+    let mut app = TestApp::new(PROGRAM);
+    app.press_all("sn").unwrap();
+    app.press('m').unwrap();
+    assert_eq!(app.app.layers(), vec![Layer::EditMemory]);
+    assert!(app.screen_contains("Edit value [\u{23ce}]"));
+    // the current value is filled in
+    app.press_key(KeyCode::Enter).unwrap();
+    assert!(app.screen_contains("6_"));
+    app.press_key(KeyCode::Backspace).unwrap();
+    app.press_all("-4x2").unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    assert!(app.screen_contains("α0: -42"));
+    // escape cancels the value that is entered, before the editor is closed
+    app.press_key(KeyCode::Enter).unwrap();
+    app.press('7').unwrap();
+    app.press_key(KeyCode::Esc).unwrap();
+    assert_eq!(app.app.layers(), vec![Layer::EditMemory]);
+    app.press_key(KeyCode::Esc).unwrap();
+    assert_eq!(app.state(), &State::Running(false));
+    app.press('n').unwrap();
+    assert!(app.screen_contains("h1: -42"));
+    // an empty value clears the memory cell
+    app.press('m').unwrap();
+    for _ in 0..5 {
+        app.press_key(KeyCode::Down).unwrap();
+    }
+    app.press_key(KeyCode::Enter).unwrap();
+    for _ in 0..3 {
+        app.press_key(KeyCode::Backspace).unwrap();
+    }
+    // invalid values are not written
+    app.press('-').unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    assert!(app.screen_contains("-_"));
+    app.press_key(KeyCode::Backspace).unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    app.press('m').unwrap();
+    assert!(app.screen_contains("Edit memory [m]"));
+    assert!(app.memory_cell_rows().contains(&"h1: None".to_string()));
+    app.press('n').unwrap();
+    assert!(app.screen_contains("α0: -44"));
+}
+
+#[test]
+fn test_app_reset_marks_values_of_last_run() {
+    let mut app = TestApp::new(PROGRAM);
+    app.press_all("srd").unwrap();
+    app.press('t').unwrap();
+    assert!(app.screen_contains("α0: None (was 4)"));
+    assert!(app.screen_contains("h1: None (was 6)"));
+    assert!(!app.screen_contains("α1: None (was"));
+    // the mark is removed once the location is overwritten
+    app.press('s').unwrap();
+    assert!(app.screen_contains("α0: 5 "));
+    assert!(!app.screen_contains("(was 4)"));
+    assert!(app.screen_contains("h1: None (was 6)"));
+}
+
+#[test]
+fn test_app_memory_aliases() {
+    let program = "a := 5\np(h1) := a * 2\np(3) := 1";
+    let mut rb = RuntimeBuilder::new(&string_literal_to_vec(program), "test").unwrap();
+    rb.set_memory_config(MemoryConfig {
+        aliases: HashMap::from([
+            ("a0".to_string(), "result".to_string()),
+            ("p(h1)".to_string(), "n".to_string()),
+        ]),
+        ..Default::default()
+    });
+    let mut app = TestApp::with_runtime(rb.build().unwrap(), program, None);
+    app.press_all("snn").unwrap();
+    assert!(app.screen_contains("result: 5 "));
+    assert!(app.screen_contains("n: 10 "));
+    assert!(!app.screen_contains("h1: 10"));
+    // locations without alias keep their name
+    assert!(app.screen_contains("[ 3]: 1 "));
+}
+
+#[test]
+fn test_app_gamma_panel() {
+    let mut app = TestApp::new("y := 5\na := y");
+    assert!(app.screen_contains("Gamma"));
+    assert!(app.screen_contains("Toggle gamma [y]"));
+    app.press('s').unwrap();
+    assert!(app.screen_contains("│γ: 5 "));
+    // the panel can be hidden
+    app.press('y').unwrap();
+    assert!(!app.screen_contains("γ: 5"));
+    assert_eq!(app.app.session().panels.show_gamma, Some(false));
+    // the panel is not displayed, if the gamma accumulator is disabled
+    let mut app = TestApp::new(PROGRAM);
+    assert!(!app.screen_contains("Gamma"));
+    assert!(!app.screen_contains("Toggle gamma"));
+}
+
+#[test]
+fn test_app_step_over_and_out() {
+    let mut app = TestApp::new(CALL_PROGRAM);
+    app.press('s').unwrap();
+    assert!(app.screen_contains("Step over [o]"));
+    // the function is run completely
+    app.press('o').unwrap();
+    assert!(app.screen_contains("α0: 3"));
+    assert!(app.app.runtime.control_flow().call_stack.is_empty());
+    // outside of functions step over is a single step
+    app.press('o').unwrap();
+    assert!(app.screen_contains("α0: 30"));
+    // nothing to step out of
+    app.press('f').unwrap();
+    assert_eq!(app.app.runtime.next_instruction_index(), 3);
+    app.press_all("tsn").unwrap();
+    assert_eq!(app.app.runtime.control_flow().call_stack.len(), 1);
+    app.press('f').unwrap();
+    assert!(app.screen_contains("α0: 3"));
+    assert_eq!(app.app.runtime.next_instruction_index(), 2);
+    // hints are hidden, if the program does not contain functions
+    let mut app = TestApp::new(PROGRAM);
+    app.press('s').unwrap();
+    assert!(!app.screen_contains("Step over"));
+}
+
+#[test]
+fn test_app_step_over_stops_at_breakpoint() {
+    let mut app = TestApp::with_breakpoints(CALL_PROGRAM, Some(vec![5]));
+    app.press_all("so").unwrap();
+    assert!(app.screen_contains("α0: 2"));
+    assert_eq!(app.app.runtime.next_instruction_index(), 5);
+    app.press('f').unwrap();
+    assert!(app.screen_contains("α0: 3"));
+}
+
+#[test]
+fn test_app_toggle_breakpoint_in_debug_select_mode() {
+    let mut app = TestApp::new(PROGRAM);
+    app.press('d').unwrap();
+    assert!(app.screen_contains("Debug select mode"));
+    app.press_all("sb").unwrap();
+    assert!(app.screen_contains("*"));
+    app.press('e').unwrap();
+    assert!(!app.screen_contains("*"));
+    app.press('d').unwrap();
+    assert!(!app.screen_contains("Debug select mode"));
+}
+
+#[test]
+fn test_app_jump_history() {
+    let mut app = TestApp::new(CALL_PROGRAM);
+    let selected = |app: &TestApp| app.app.instruction_list_states.selected_line();
+    app.press('d').unwrap();
+    app.press('s').unwrap();
+    assert_eq!(selected(&app), Some(1));
+    // follows the call to the function
+    app.press_key(KeyCode::Enter).unwrap();
+    assert_eq!(selected(&app), Some(4));
+    app.press_ctrl('o').unwrap();
+    assert_eq!(selected(&app), Some(1));
+    app.press_key(KeyCode::Tab).unwrap();
+    assert_eq!(selected(&app), Some(4));
+    app.press_ctrl('o').unwrap();
+    app.press_ctrl('i').unwrap();
+    assert_eq!(selected(&app), Some(4));
+    // lines that are not a jump are not followed
+    app.press('s').unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    assert_eq!(selected(&app), Some(5));
+    app.press_all("ww").unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    assert_eq!(selected(&app), Some(7));
+    app.press_ctrl('o').unwrap();
+    assert_eq!(selected(&app), Some(3));
+    app.press_ctrl('o').unwrap();
+    assert_eq!(selected(&app), Some(1));
+    app.press_ctrl('o').unwrap();
+    assert_eq!(selected(&app), Some(1));
+}
+
+#[test]
+fn test_app_instruction_description() {
+    let mut app = TestApp::new(CALL_PROGRAM);
+    app.resize(200, 40);
+    assert!(!app.screen_contains("Jumps to the label and remembers"));
+    app.press('d').unwrap();
+    assert!(app.screen_contains("Assigns the value on the right to the location on the left."));
+    app.press('s').unwrap();
+    assert!(app.screen_contains("Jumps to the label and remembers the next line"));
+    app.press('d').unwrap();
+    assert!(!app.screen_contains("Jumps to the label and remembers"));
+}
+
+#[test]
+fn test_app_machine_view() {
+    let mut app = TestApp::new(PROGRAM);
+    assert!(!app.screen_contains("Machine view"));
+    app.press('x').unwrap();
+    assert!(app.screen_contains("Machine view"));
+    assert!(app.screen_contains("IR:    -"));
+    app.press_all("sn").unwrap();
+    assert!(app.screen_contains("PC:    3"));
+    assert!(app.screen_contains("IR:    a0 := a0 + 1"));
+    assert!(app.screen_contains("ALU:   5 + 1 = 6"));
+    app.press('t').unwrap();
+    assert!(app.screen_contains("IR:    -"));
+    app.press('x').unwrap();
+    assert!(!app.screen_contains("Machine view"));
+}
+
+#[test]
+fn test_app_mouse() {
+    let mut app = TestApp::new(PROGRAM);
+    let click = MouseEventKind::Down(MouseButton::Left);
+    app.screen();
+    let code = app.app.mouse_areas.code;
+    let breakpoints = app.app.mouse_areas.breakpoints;
+    // clicking a line toggles its breakpoint
+    app.mouse(click, code.x + 5, code.y + 2).unwrap();
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![2]);
+    app.mouse(click, breakpoints.x + 1, code.y + 2).unwrap();
+    assert!(app.breakpoints(BreakpointState::Enabled).is_empty());
+    app.press('u').unwrap();
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![2]);
+    // the border and rows below the last line are ignored
+    app.mouse(click, code.x + 5, code.y).unwrap();
+    app.mouse(click, code.x + 5, code.y + 10).unwrap();
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![2]);
+    // in debug select mode clicking selects the line and the scroll wheel moves the selection
+    let selected = |app: &TestApp| app.app.instruction_list_states.selected_line();
+    app.press('d').unwrap();
+    app.mouse(click, code.x + 5, code.y + 3).unwrap();
+    assert_eq!(selected(&app), Some(2));
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![2]);
+    app.mouse(MouseEventKind::ScrollDown, code.x + 5, code.y + 1)
+        .unwrap();
+    assert_eq!(selected(&app), Some(3));
+    app.mouse(click, breakpoints.x + 1, code.y + 1).unwrap();
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![1, 2]);
+    assert_eq!(selected(&app), Some(3));
+}
+
+#[test]
+fn test_app_mouse_scroll_memory_cells() {
+    let program = (1..=20)
+        .map(|f| format!("p(h{f}) := {f}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut app = TestApp::new(&program);
+    app.resize(120, 20);
+    app.screen();
+    let memory_cells = app.app.mouse_areas.memory_cells;
+    assert!(app.memory_cell_rows().contains(&"h0: None".to_string()));
+    app.mouse(
+        MouseEventKind::ScrollDown,
+        memory_cells.x + 2,
+        memory_cells.y + 2,
+    )
+    .unwrap();
+    assert!(!app.memory_cell_rows().contains(&"h0: None".to_string()));
+    assert!(app.screen_contains("… 1 more"));
+    for _ in 0..30 {
+        app.mouse(
+            MouseEventKind::ScrollDown,
+            memory_cells.x + 2,
+            memory_cells.y + 2,
+        )
+        .unwrap();
+    }
+    // the last memory cell stays in the last row
+    assert!(app.memory_cell_rows().contains(&"h9: None".to_string()));
+    for _ in 0..30 {
+        app.mouse(
+            MouseEventKind::ScrollUp,
+            memory_cells.x + 2,
+            memory_cells.y + 2,
+        )
+        .unwrap();
+    }
+    assert!(app.memory_cell_rows().contains(&"h0: None".to_string()));
+}
+
+#[test]
+fn test_app_execution_finished_popup() {
+    let mut app = TestApp::new(PROGRAM);
+    app.press_all("sr").unwrap();
+    assert_eq!(app.state(), &State::Finished(true));
+    assert!(app.screen_contains("Execution finished!"));
+    app.press('d').unwrap();
+    assert!(!app.screen_contains("Execution finished!"));
+    app.press('t').unwrap();
+    assert_eq!(app.state(), &State::Default);
+    assert!(app.screen_contains("α0: None"));
+}
+
+#[test]
+fn test_app_runtime_error_popup() {
+    let mut app = TestApp::new("a := p(h1)");
+    app.press('s').unwrap();
+    assert!(app.screen_contains("Runtime error!"));
+    assert!(app.press('q').is_err());
+}
+
+#[test]
+fn test_app_repro_written_on_runtime_error() {
+    let program = vec!["a := 5".to_string(), "a := p(h1)".to_string()];
+    let mut app = TestApp::new(&program.join("\n"));
+    let path = std::env::temp_dir().join(format!("alpha_tui_error_repro_{}", std::process::id()));
+    let path = path.to_str().unwrap();
+    let repro = Repro::new(
+        "test",
+        &program,
+        BuildConfig::default(),
+        None,
+        (Vec::new(), Vec::new()),
+        0,
+    );
+    app.app.record_repro(path, repro);
+    app.press('s').unwrap();
+    assert!(!std::path::Path::new(path).exists());
+    // the repro is written as soon as the error occurs, without closing the app
+    app.press('n').unwrap();
+    assert!(app.screen_contains("Runtime error!"));
+    let written = Repro::try_from_file(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(written.actions, vec!["s", "n"]);
+}
+
+#[test]
+fn test_app_quit() {
+    let mut app = TestApp::new(PROGRAM);
+    assert!(!app.press('n').unwrap());
+    assert!(app.press('q').unwrap());
+    assert!(app.press_key(KeyCode::Esc).unwrap());
+}
+
+#[test]
+fn test_app_profiling_overlay() {
+    let mut app = TestApp::new(PROGRAM);
+    assert!(!app.screen_contains("Profiling"));
+    app.app.enable_render_profile();
+    assert!(app.screen_contains("Profiling"));
+    assert!(app.screen_contains("instructions: 4"));
+}
+
+#[test]
+fn test_app_line_profile() {
+    let mut app = TestApp::new(PROGRAM);
+    assert!(app.app.line_profile_csv().is_none());
+    app.app.enable_line_profile();
+    assert!(app.screen_contains("Runs / Time"));
+    app.press_all("sn").unwrap();
+    // single steps are counted, but not timed
+    assert!(app.screen_contains("     1    0.00ms"));
+    // the profile column contains the hit counts, so no second column is shown
+    app.press('v').unwrap();
+    assert!(!app.screen_contains("Hits"));
+    app.press('p').unwrap();
+    assert!(app
+        .app
+        .run_auto_run_steps(Instant::now() + Duration::from_secs(1))
+        .unwrap());
+    assert_eq!(app.state(), &State::Finished(true));
+    let csv = app.app.line_profile_csv().unwrap();
+    let lines = csv.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "line,hits,time_ms,instruction");
+    assert!(lines[1].starts_with("1,1,0.000,"));
+    assert!(lines[4].starts_with("4,1,"));
+    assert!(lines[4].ends_with(",\"α0 := α0 - 2\""));
+}
+
+#[test]
+fn test_app_hit_counts() {
+    let mut app = TestApp::new(
+        "a := 0
+loop: a := a + 1
+if a < 3 then goto loop",
+    );
+    assert!(!app.screen_contains("Hits"));
+    app.press('v').unwrap();
+    assert!(app.screen_contains("Hits"));
+    app.press_all("snnnn").unwrap();
+    assert_eq!(app.app.runtime.line_hits(), [1, 2, 2]);
+    assert!(app.screen_contains("│      2│"));
+    app.press('v').unwrap();
+    assert!(!app.screen_contains("Hits"));
+}
+
+#[test]
+fn test_app_stack_scrolling() {
+    let mut app = TestApp::new(
+        "a := 0
+loop: a := a + 1
+push
+if a < 30 then goto loop",
+    );
+    app.resize(120, 20);
+    // 20 values are pushed, 15 fit into the stack panel
+    app.press('s').unwrap();
+    app.press_all(&"n".repeat(60)).unwrap();
+    assert!(app.screen_contains("│20        │"));
+    assert!(app.screen_contains("│… 5 more  │"));
+    app.press_key(KeyCode::End).unwrap();
+    assert!(app.screen_contains("│… 5 more  │"));
+    assert!(app.screen_contains("│1         │"));
+    assert!(!app.screen_contains("│20        │"));
+    app.press('h').unwrap();
+    assert!(app.screen_contains("Scroll stack [PgUp|PgDn]"));
+    // the change of the top of the stack is shown on the row of the hidden entries
+    let highlight = app.app.theme.list_item_highlight(false).bg;
+    assert_ne!(app.style_of("… 5 more").unwrap().bg, highlight);
+    app.press_all("nn").unwrap();
+    assert!(app.screen_contains("│… 6 more  │"));
+    assert_eq!(app.style_of("… 6 more").unwrap().bg, highlight);
+    app.press('n').unwrap();
+    assert_ne!(app.style_of("… 6 more").unwrap().bg, highlight);
+    app.press_key(KeyCode::PageUp).unwrap();
+    assert!(app.screen_contains("│21        │"));
+    app.press_key(KeyCode::End).unwrap();
+    app.press_key(KeyCode::Home).unwrap();
+    assert!(app.screen_contains("│21        │"));
+    assert!(app.screen_contains("│… 6 more  │"));
+}
+
+#[test]
+fn test_app_memory_panel_scrolling() {
+    let program = (1..=20)
+        .map(|f| format!("p(h{f}) := {f}\na{f} := {f}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut app = TestApp::new(&program);
+    app.resize(120, 20);
+    app.press('s').unwrap();
+    // the stack is empty, so the memory cells are scrolled first
+    assert!(app.screen_contains("… 19 more"));
+    assert!(app.screen_contains("… 17 more"));
+    app.press_key(KeyCode::End).unwrap();
+    assert!(!app.memory_cell_rows().contains(&"h0: None".to_string()));
+    assert!(app.memory_cell_rows().contains(&"h9: None".to_string()));
+    assert!(app.screen_contains("α0: None"));
+    app.press('h').unwrap();
+    assert!(app.screen_contains("Scroll memory cells [PgUp|PgDn]"));
+    assert!(app.screen_contains("Switch scrolled panel [z]"));
+    app.press('z').unwrap();
+    app.press_key(KeyCode::End).unwrap();
+    assert!(!app.screen_contains("α0: None"));
+    assert!(app.screen_contains("α20: None"));
+    assert!(app.screen_contains("Scroll accumulators [PgUp|PgDn]"));
+    app.press_key(KeyCode::Home).unwrap();
+    assert!(app.screen_contains("α0: None"));
+    assert!(app.memory_cell_rows().contains(&"h9: None".to_string()));
+    // switching panels wraps around
+    app.press('z').unwrap();
+    assert!(app.screen_contains("Scroll memory cells [PgUp|PgDn]"));
+}
+
+#[test]
+fn test_app_value_format() {
+    let mut app = TestApp::new("a := -1\npush\na := 10\np(h1) := a");
+    app.press_all("snnn").unwrap();
+    assert!(app.screen_contains("α0: 10"));
+    assert!(app.screen_contains("Show hexadecimal values [#]"));
+    app.press('#').unwrap();
+    assert!(app.screen_contains("α0: 0xa"));
+    assert!(app.screen_contains("h1: 0xa"));
+    assert!(app.screen_contains("0xffffffff"));
+    // the values are not marked as changed by the conversion
+    let highlight = app.app.theme.list_item_highlight(false).bg;
+    assert_ne!(app.style_of("h1: 0xa").unwrap().bg, highlight);
+    app.press('#').unwrap();
+    assert!(app.screen_contains("α0: 0b1010"));
+    assert!(app.screen_contains("Show decimal values [#]"));
+    // reset keeps the number system
+    app.press('t').unwrap();
+    assert!(app.screen_contains("α0: None"));
+    assert!(app.screen_contains("(was 0b1010)"));
+    app.press('#').unwrap();
+    assert!(app.screen_contains("(was 10)"));
+}
+
+#[test]
+fn test_app_value_history() {
+    let mut app = TestApp::new("a := 3\np(h1) := a\na := 7\np(h2) := a");
+    app.resize(120, 30);
+    app.press('k').unwrap();
+    assert!(app.screen_contains("Value history"));
+    app.press_all("snn").unwrap();
+    assert!(app.screen_contains("step 3: α0 3 → 7"));
+    assert!(app.screen_contains("step 2: h1 None → 3"));
+    // the machine view shares the row with the value history
+    app.press('x').unwrap();
+    assert!(app.screen_contains("Machine view"));
+    assert!(app.screen_contains("step 1: α0 None → 3"));
+    app.press('x').unwrap();
+    // manual changes are recorded as well
+    app.press('i').unwrap();
+    app.enter_instruction("a := 10").unwrap();
+    assert!(app.screen_contains("edit: α0 7 → 10"));
+    app.press('m').unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    app.press_key(KeyCode::Backspace).unwrap();
+    app.press_key(KeyCode::Backspace).unwrap();
+    app.press('9').unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    app.press_key(KeyCode::Esc).unwrap();
+    assert!(app.screen_contains("edit: α0 10 → 9"));
+    app.press('t').unwrap();
+    assert!(!app.screen_contains("step 1:"));
+    app.press('k').unwrap();
+    assert!(!app.screen_contains("Value history"));
+    // changes are only recorded while the value history is shown
+    app.press_all("snnk").unwrap();
+    assert!(app.screen_contains("Value history"));
+    assert!(!app.screen_contains("step 1:"));
+}
+
+#[test]
+fn test_app_output() {
+    let mut app = TestApp::new("a := 0\nloop: a := a + 111\nprint a\nif a < 800 then goto loop");
+    app.resize(120, 30);
+    assert!(app.screen_contains("Output"));
+    app.press_all("sr").unwrap();
+    // the output follows the printed values
+    assert!(app.screen_contains("… 4 more"));
+    assert!(app.screen_contains("888"));
+    assert!(!app.screen_contains("222"));
+    assert!(app.screen_contains("Scroll output"));
+    app.press_key(KeyCode::Home).unwrap();
+    assert!(app.screen_contains("222"));
+    assert!(!app.screen_contains("555"));
+    app.press('t').unwrap();
+    assert!(!app.screen_contains("222"));
+    assert!(app.screen_contains("Output"));
+    // programs without print instructions don't show the output
+    let mut app = TestApp::new("a := 1");
+    app.resize(120, 30);
+    assert!(!app.screen_contains("Output"));
+}
+
+#[test]
+fn test_app_remote_control() {
+    let mut app = TestApp::new("a := 3\np(h1) := a\na := 7\np(h2) := a");
+    let (sender, requests) = mpsc::channel();
+    app.app.set_remote_control(requests);
+    let request = |app: &mut TestApp, request| {
+        let (pending, response) = PendingRequest::new(request);
+        sender.send(pending).unwrap();
+        assert!(app.app.handle_control_requests().unwrap());
+        response.try_recv().unwrap()
+    };
+    let response = request(&mut app, ControlRequest::Step);
+    assert_eq!(response.status, ControlStatus::Running);
+    assert_eq!((response.line, response.steps), (2, 1));
+    assert_eq!(response.memory.accumulators[&0], Some(3));
+    let mut memory = StateDump::default();
+    memory.accumulators.insert(0, Some(5));
+    let response = request(&mut app, ControlRequest::WriteMemory { memory });
+    assert_eq!(response.error, None);
+    assert_eq!(response.memory.accumulators[&0], Some(5));
+    let response = request(&mut app, ControlRequest::Run);
+    assert_eq!(response.status, ControlStatus::Finished);
+    assert_eq!(response.memory.memory_cells["h1"], Some(5));
+    let response = request(&mut app, ControlRequest::Step);
+    assert_eq!(
+        response.error.as_deref(),
+        Some("The program is not running")
+    );
+    let response = request(&mut app, ControlRequest::Reset);
+    assert_eq!(response.status, ControlStatus::NotStarted);
+    assert_eq!(response.memory.memory_cells["h1"], None);
+    // the program can't be controlled while a popup is open
+    app.press('?').unwrap();
+    let response = request(&mut app, ControlRequest::Step);
+    assert!(response.error.is_some());
+    assert_eq!(response.status, ControlStatus::NotStarted);
+    app.press('?').unwrap();
+    app.press('d').unwrap();
+    let response = request(&mut app, ControlRequest::ReadMemory);
+    assert_eq!(response.error, None);
+    assert_eq!(response.status, ControlStatus::Busy);
+}
+
+#[test]
+fn test_app_remote_control_remapped_keys() {
+    let mut app = TestApp::new("a := 3\np(h1) := a\na := 7");
+    app.app
+        .set_keymap(Keymap::parse("s = \"C-1\"\nn = \"C-2\"\nr = \"C-3\"\nt = \"C-4\"").unwrap());
+    app.app.record_actions();
+    let (sender, requests) = mpsc::channel();
+    app.app.set_remote_control(requests);
+    let request = |app: &mut TestApp, request| {
+        let (pending, response) = PendingRequest::new(request);
+        sender.send(pending).unwrap();
+        assert!(app.app.handle_control_requests().unwrap());
+        response.try_recv().unwrap()
+    };
+    // the requests don't depend on the keys of the actions
+    assert_eq!(
+        request(&mut app, ControlRequest::Step).status,
+        ControlStatus::Running
+    );
+    assert_eq!(request(&mut app, ControlRequest::Step).steps, 2);
+    assert_eq!(
+        request(&mut app, ControlRequest::Run).status,
+        ControlStatus::Finished
+    );
+    assert_eq!(
+        request(&mut app, ControlRequest::Reset).status,
+        ControlStatus::NotStarted
+    );
+    // requests are not recorded as pressed keys
+    assert!(app.app.recorded_actions().unwrap().is_empty());
+}
+
+#[test]
+fn test_app_terminal_too_small() {
+    let mut app = TestApp::new(PROGRAM);
+    app.resize(30, 10);
+    assert!(app.screen_contains("Terminal too small"));
+    assert!(!app.screen_contains("α0"));
+    app.resize(1, 1);
+    app.screen();
+    app.resize(120, 40);
+    assert!(!app.screen_contains("Terminal too small"));
+    assert!(app.screen_contains("α0: None"));
+}
+
+#[test]
+fn test_app_bottom_panels_hidden_in_small_terminal() {
+    let mut app = TestApp::new(PROGRAM);
+    app.press_all("sx").unwrap();
+    app.resize(120, 24);
+    assert!(!app.screen_contains("Machine view"));
+    assert!(app.screen_contains("Accumulators"));
+    app.resize(120, 25);
+    assert!(app.screen_contains("Machine view"));
+}
+
+#[test]
+fn test_app_keybinding_hint_pages() {
+    let mut app = TestApp::new(PROGRAM);
+    app.resize(60, 40);
+    app.press('s').unwrap();
+    assert!(app.screen_contains("More [h] (1/6)"));
+    app.press('h').unwrap();
+    assert!(app.screen_contains("More [h] (2/6)"));
+    app.press('d').unwrap();
+    assert!(app.screen_contains("More [h] (1/"));
+}
+
+#[test]
+fn test_app_keymap() {
+    let mut app = TestApp::new(PROGRAM);
+    app.app
+        .set_keymap(Keymap::parse("s = \"S\"\nh = \"C-n\"").unwrap());
+    app.resize(60, 40);
+    assert!(app.screen_contains("Start [S]"));
+    app.press('s').unwrap();
+    assert_eq!(app.state(), &State::Default);
+    app.press('S').unwrap();
+    assert!(matches!(app.state(), State::Running(_)));
+    assert!(app.screen_contains("More [C-n] (1/6)"));
+    app.press_ctrl('n').unwrap();
+    assert!(app.screen_contains("More [C-n] (2/6)"));
+}
+
+#[test]
+fn test_app_help() {
+    let mut app = TestApp::new(PROGRAM);
+    app.press('s').unwrap();
+    assert!(app.screen_contains("Help [?]"));
+    app.press('?').unwrap();
+    assert_eq!(app.app.layers(), vec![Layer::Help]);
+    // the actions of the state are listed, with their keys
+    assert!(app.screen_contains("n  Run next instruction"));
+    assert!(app.screen_contains("Close help [?]"));
+    assert!(!app.screen_contains("Run next instruction [n]"));
+    // keys are not handled by the state while the help is open
+    app.press_all("nn").unwrap();
+    app.press_key(KeyCode::Down).unwrap();
+    assert_eq!(app.app.help, Some(1));
+    assert!(app.screen_contains("α0: 5"));
+    app.press_key(KeyCode::Esc).unwrap();
+    assert!(app.app.layers().is_empty());
+    assert!(matches!(app.state(), State::Running(_)));
+    app.press('n').unwrap();
+    assert!(app.screen_contains("α0: 6"));
+    // the help lists the remapped keys
+    app.app
+        .set_keymap(Keymap::parse("n = \"N\"\n\"?\" = \"H\"").unwrap());
+    app.press('H').unwrap();
+    assert!(app.screen_contains("N  Run next instruction"));
+    app.press('H').unwrap();
+    assert!(app.app.layers().is_empty());
+}
+
+#[test]
+fn test_app_undo_redo_breakpoints() {
+    let mut app = TestApp::new(PROGRAM);
+    app.press_all("dsb").unwrap();
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![2]);
+    app.press('u').unwrap();
+    assert!(app.breakpoints(BreakpointState::Enabled).is_empty());
+    app.press('u').unwrap();
+    assert!(app.breakpoints(BreakpointState::Enabled).is_empty());
+    app.press_ctrl('r').unwrap();
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![2]);
+    app.press('e').unwrap();
+    assert_eq!(app.breakpoints(BreakpointState::Disabled), vec![2]);
+    app.press('u').unwrap();
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![2]);
+    app.press_all("dr").unwrap();
+    assert_eq!(app.state(), &State::Running(true));
+}
+
+#[test]
+fn test_app_undo_redo_memory_edit() {
+    let mut app = TestApp::new(PROGRAM);
+    app.press_all("sn").unwrap();
+    app.press_all("dbd").unwrap();
+    assert_eq!(app.state(), &State::Running(true));
+    // the accumulator is selected first in the memory editor
+    app.press('m').unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    app.press_key(KeyCode::Backspace).unwrap();
+    app.press('9').unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    app.press_key(KeyCode::Esc).unwrap();
+    assert!(app.screen_contains("α0: 9"));
+    // changes are undone in reverse order, the breakpoint is kept while the edit is undone
+    app.press('u').unwrap();
+    assert!(app.screen_contains("α0: 6"));
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![2]);
+    app.press('u').unwrap();
+    assert!(app.breakpoints(BreakpointState::Enabled).is_empty());
+    app.press_ctrl('r').unwrap();
+    app.press_ctrl('r').unwrap();
+    assert!(app.screen_contains("α0: 9"));
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![2]);
+    app.press('n').unwrap();
+    assert!(app.screen_contains("h1: 9"));
+}
+
+#[test]
+fn test_app_loop_counter_hidden() {
+    let mut app = TestApp::new("a := 0\nloop 3 times\na := a + 1\nendloop");
+    app.press_all("snn").unwrap();
+    assert!(app.screen_contains("α0: 1"));
+    assert!(!app.memory_cell_rows().iter().any(|f| f.starts_with("loop")));
+}
+
+#[test]
+fn test_app_toggle_label_breakpoints() {
+    let mut app = TestApp::new("a := 1\nloop: a := a + 1\na := a * 2\ndone: a := 0");
+    app.press_all("dl").unwrap();
+    assert!(matches!(app.state(), State::Labels(_, _)));
+    assert!(app.screen_contains("loop  lines 2-3"));
+    assert!(app.screen_contains("done  lines 4-4"));
+    app.press_key(KeyCode::Enter).unwrap();
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![2, 3]);
+    app.press_key(KeyCode::Down).unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![2, 3, 4]);
+    app.press_key(KeyCode::Up).unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![4]);
+    app.press('u').unwrap();
+    assert_eq!(app.breakpoints(BreakpointState::Enabled), vec![2, 3, 4]);
+    app.press('l').unwrap();
+    assert!(matches!(app.state(), State::DebugSelect(_, _)));
+    app.press('l').unwrap();
+    app.press_key(KeyCode::Esc).unwrap();
+    assert!(matches!(app.state(), State::DebugSelect(_, _)));
+}
+
+#[test]
+fn test_app_jump_to_label() {
+    let mut app = TestApp::new("a := 1\nloop: a := a + 1\na := a * 2\ndone: a := 0");
+    let selected = |app: &TestApp| app.app.instruction_list_states.selected_line();
+    app.press_all("dl").unwrap();
+    assert!(app.screen_contains("Jump to label"));
+    app.press_key(KeyCode::Down).unwrap();
+    app.press('j').unwrap();
+    assert!(matches!(app.state(), State::DebugSelect(_, _)));
+    assert_eq!(selected(&app), Some(3));
+    app.press_all("lj").unwrap();
+    assert_eq!(selected(&app), Some(1));
+    app.press_ctrl('o').unwrap();
+    assert_eq!(selected(&app), Some(3));
+    // the program is not started
+    assert_eq!(app.app.runtime.next_instruction_index(), 0);
+}
+
+#[test]
+fn test_app_run_to_line() {
+    let mut app =
+        TestApp::new("a := 0\nloop: a := a + 1\nif a < 3 then goto loop\np(h1) := a\na := 5");
+    app.resize(200, 40);
+    app.press('d').unwrap();
+    assert!(app.screen_contains("Run to line"));
+    app.press_key(KeyCode::Down).unwrap();
+    // the program is started if it is not running, like breakpoints the selected line is run before stopping
+    app.press('r').unwrap();
+    assert_eq!(app.state(), &State::Running(false));
+    assert!(app.screen_contains("α0: 1"));
+    // at least one instruction is run, if the line is already reached
+    app.press_all("dr").unwrap();
+    assert!(app.screen_contains("α0: 2"));
+    app.press('d').unwrap();
+    app.press_key(KeyCode::Down).unwrap();
+    app.press_key(KeyCode::Down).unwrap();
+    app.press('r').unwrap();
+    assert!(app.screen_contains("h1: 3"));
+    // lines that are not reached again run the program to the end
+    app.press_all("dwwr").unwrap();
+    assert_eq!(app.state(), &State::Finished(true));
+    assert!(app.screen_contains("α0: 5"));
+}
+
+#[test]
+fn test_app_quit_prompt() {
+    let mut app = TestApp::new(PROGRAM);
+    app.app.enable_quit_prompt();
+    app.press_all("db").unwrap();
+    assert!(!app.press('q').unwrap());
+    assert!(app.screen_contains("Unsaved changes"));
+    app.press_key(KeyCode::Esc).unwrap();
+    assert!(!app.screen_contains("Unsaved changes"));
+    assert!(matches!(app.state(), State::DebugSelect(_, _)));
+    assert!(!app.press_key(KeyCode::Esc).unwrap());
+    assert!(app.press('q').unwrap());
+}
+
+#[test]
+fn test_app_quit_prompt_memory_edited() {
+    let mut app = TestApp::new(PROGRAM);
+    app.press_all("sm").unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    app.press('3').unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    app.press_key(KeyCode::Esc).unwrap();
+    // the prompt is shown without --session as well, the edited values can't be saved
+    assert!(!app.press('q').unwrap());
+    assert!(app.screen_contains("Memory values have been edited."));
+    assert!(!app.screen_contains("save the session"));
+    assert!(!app.press('s').unwrap());
+    app.press_key(KeyCode::Esc).unwrap();
+    // the edited values are discarded by a reset
+    app.press('t').unwrap();
+    assert!(app.press('q').unwrap());
+}
+
+#[test]
+fn test_app_quit_prompt_save_session() {
+    let path = std::env::temp_dir().join("alpha_tui_test_app_quit_prompt_save_session");
+    let mut app = TestApp::new(PROGRAM);
+    app.app.filename = path.to_str().unwrap().to_string();
+    app.app.enable_quit_prompt();
+    // no prompt, when nothing was changed
+    app.press('d').unwrap();
+    app.press('b').unwrap();
+    app.press('b').unwrap();
+    assert!(app.press('q').unwrap());
+    app.press('b').unwrap();
+    assert!(!app.press('q').unwrap());
+    assert!(app.press('s').unwrap());
+    let session_file = session_file_path(path.to_str().unwrap());
+    let session = Session::try_from_file(&session_file).unwrap().unwrap();
+    assert_eq!(session.breakpoints, vec![1]);
+    std::fs::remove_file(session_file).unwrap();
+}
+
+#[test]
+fn test_app_step_budget_gauge() {
+    let mut app = TestApp::new("# alpha_tui: max-steps=10\na := 1\na := 2\na := 3");
+    assert!(app.screen_contains("0/10"));
+    // starting the program runs the first instruction, the header line is not counted as step
+    app.press('s').unwrap();
+    assert!(app.screen_contains("1/10"));
+    app.press('n').unwrap();
+    assert!(app.screen_contains("2/10"));
+    let mut app = TestApp::new(PROGRAM);
+    app.press('s').unwrap();
+    assert!(!app.screen_contains("/10"));
+}
+
+#[test]
+fn test_app_control_handoff() {
+    let mut app = TestApp::new(PROGRAM);
+    let (sender, shared_actions) = mpsc::channel();
+    let (remote_sender, remote_actions) = mpsc::channel();
+    app.app.share_session(
+        sender,
+        remote_actions,
+        ShareLog::new(Box::new(std::io::sink())),
+    );
+    let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+    assert!(app.screen_contains("[g] to grant control"));
+    // keys of viewers are ignored until control is granted
+    remote_sender.send(key('s')).unwrap();
+    app.app.handle_remote_actions().unwrap();
+    assert_eq!(app.state(), &State::Default);
+    app.press('g').unwrap();
+    assert!(app.screen_contains("viewers have control"));
+    remote_sender.send(key('s')).unwrap();
+    remote_sender.send(key('i')).unwrap();
+    app.app.handle_remote_actions().unwrap();
+    assert_eq!(app.state(), &State::Running(false));
+    // keys of the viewer are sent back to all viewers, so that they take effect there too
+    assert_eq!(
+        shared_actions.try_iter().collect::<Vec<_>>(),
+        vec![key('g'), key('s')]
+    );
+    app.press('g').unwrap();
+    remote_sender.send(key('n')).unwrap();
+    app.app.handle_remote_actions().unwrap();
+    assert!(app.screen_contains("α0: 5"));
+}
+
+#[test]
+fn test_app_control_handoff_remapped_keys() {
+    let mut app = TestApp::new(PROGRAM);
+    app.app
+        .set_keymap(Keymap::parse("s = \"C-1\"\nn = \"C-2\"").unwrap());
+    let (sender, shared_actions) = mpsc::channel();
+    let (remote_sender, remote_actions) = mpsc::channel();
+    app.app.share_session(
+        sender,
+        remote_actions,
+        ShareLog::new(Box::new(std::io::sink())),
+    );
+    let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+    app.press('g').unwrap();
+    // viewers use the default keys, even though the host has remapped them
+    remote_sender.send(key('s')).unwrap();
+    remote_sender.send(key('n')).unwrap();
+    app.app.handle_remote_actions().unwrap();
+    assert_eq!(app.state(), &State::Running(false));
+    assert!(app.screen_contains("α0: 6"));
+    assert_eq!(
+        shared_actions.try_iter().collect::<Vec<_>>(),
+        vec![key('g'), key('s'), key('n')]
+    );
+}
+
+#[test]
+fn test_app_playground_timeline() {
+    let mut app = TestApp::playground();
+    app.enter_instruction("a := 5").unwrap();
+    app.enter_instruction("a := a * 2").unwrap();
+    app.enter_instruction("p(h1) := a").unwrap();
+    app.press_ctrl('t').unwrap();
+    assert_eq!(app.app.layers(), vec![Layer::Timeline]);
+    assert!(app.screen_contains("α0: 5 -> 10"));
+    assert!(app.screen_contains("h1: None -> 10"));
+    // roll back to the first instruction
+    app.press_key(KeyCode::Up).unwrap();
+    app.press_key(KeyCode::Up).unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    assert_eq!(
+        app.app.runtime.runtime_memory().accumulators[&0].data,
+        Some(5)
+    );
+    assert_eq!(
+        app.app.runtime.runtime_memory().memory_cells["h1"].data,
+        None
+    );
+    assert_eq!(app.app.instruction_list_states.instructions().len(), 1);
+    // rollback can be undone
+    app.press('u').unwrap();
+    assert_eq!(
+        app.app.runtime.runtime_memory().memory_cells["h1"].data,
+        Some(10)
+    );
+    app.press_ctrl('r').unwrap();
+    assert_eq!(app.app.timeline.entries().len(), 1);
+    app.press_key(KeyCode::Esc).unwrap();
+    assert!(matches!(app.state(), State::Playground(_)));
+    app.enter_instruction("a := a + 1").unwrap();
+    assert_eq!(
+        app.app.runtime.runtime_memory().accumulators[&0].data,
+        Some(6)
+    );
+}
+
+#[test]
+fn test_app_playground_save() {
+    let path = std::env::temp_dir().join(format!("alpha_tui_playground_{}", std::process::id()));
+    let path = path.to_str().unwrap().to_string();
+    let mut app = TestApp::playground();
+    app.app.set_playground_file(&path);
+    assert!(app.screen_contains("Save as program"));
+    app.enter_instruction("a := 5").unwrap();
+    app.enter_instruction("a := a * 2").unwrap();
+    app.enter_instruction("p(h1) := a").unwrap();
+    // instructions that were rolled back are not saved
+    app.press_ctrl('t').unwrap();
+    app.press_key(KeyCode::Up).unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    app.press_ctrl('s').unwrap();
+    assert!(app.screen_contains("2 instruction(s) saved"));
+    assert_eq!(
+        crate::utils::read_file(&path).unwrap(),
+        vec!["a := 5", "a := a * 2"]
+    );
+    app.press_key(KeyCode::Esc).unwrap();
+    app.enter_instruction("a := a + 1").unwrap();
+    assert!(!app.screen_contains("saved"));
+    // the file written by this session is overwritten without asking
+    app.press_ctrl('s').unwrap();
+    assert!(app.screen_contains("3 instruction(s) saved"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_app_playground_quit_prompt() {
+    let path =
+        std::env::temp_dir().join(format!("alpha_tui_playground_quit_{}", std::process::id()));
+    let path = path.to_str().unwrap().to_string();
+    let mut app = TestApp::playground();
+    app.app.set_playground_file(&path);
+    app.resize(200, 40);
+    // nothing to lose yet
+    assert!(app.press_key(KeyCode::Esc).unwrap());
+    app.press_all("a := 1").unwrap();
+    assert!(!app.press_key(KeyCode::Esc).unwrap());
+    assert!(app.screen_contains("The entered instruction has not been run."));
+    assert!(!app.press('s').unwrap());
+    app.press_key(KeyCode::Esc).unwrap();
+    app.press_key(KeyCode::Enter).unwrap();
+    assert!(!app.press_key(KeyCode::Esc).unwrap());
+    assert!(app.screen_contains("Instructions of the playground have not been"));
+    assert!(app.press('s').unwrap());
+    assert_eq!(crate::utils::read_file(&path).unwrap(), vec!["a := 1"]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_app_playground_save_existing_file() {
+    let path = std::env::temp_dir().join(format!(
+        "alpha_tui_playground_existing_{}",
+        std::process::id()
+    ));
+    let path = path.to_str().unwrap().to_string();
+    std::fs::write(&path, "a := 1\n").unwrap();
+    let mut app = TestApp::playground();
+    app.app.set_playground_file(&path);
+    app.resize(200, 40);
+    app.enter_instruction("a := 5").unwrap();
+    app.press_ctrl('s').unwrap();
+    assert!(app.screen_contains("press [C-s] again to overwrite"));
+    assert_eq!(crate::utils::read_file(&path).unwrap(), vec!["a := 1"]);
+    app.press_ctrl('s').unwrap();
+    assert!(app.screen_contains("1 instruction(s) saved"));
+    assert_eq!(crate::utils::read_file(&path).unwrap(), vec!["a := 5"]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_app_custom_instruction_written_with_position() {
+    let path = std::env::temp_dir().join(format!("alpha_tui_history_{}", std::process::id()));
+    let path = path.to_str().unwrap().to_string();
+    std::fs::write(&path, "").unwrap();
+    let mut app = TestApp::new(PROGRAM);
+    app.app.command_history_file = Some(path.clone());
+    app.press_all("sni").unwrap();
+    app.enter_instruction("a := 10").unwrap();
+    app.press('i').unwrap();
+    app.enter_instruction("a := 10").unwrap();
+    // the same instruction is written again, because it was run at a different position
+    app.press_all("ni").unwrap();
+    app.enter_instruction("a := 10").unwrap();
+    let content = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        content.lines().collect::<Vec<_>>(),
+        vec![
+            "a := 10 // test: line 3, step 2",
+            "a := 10 // test: line 3, step 2",
+            "a := 10 // test: line 4, step 3"
+        ]
+    );
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use ratatui::text::Line;
+
+use crate::runtime::RuntimeMemory;
+
+use super::undo::UndoHistory;
+
+/// Instruction that was run in the playground, together with the changes it caused.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TimelineEntry {
+    /// Line that is displayed in the list of executed instructions.
+    pub line: Line<'static>,
+    /// Instruction as it was entered.
+    pub instruction: String,
+    /// Changes of the memory, e.g. `α0: 3 -> 5`.
+    pub changes: Vec<String>,
+    /// Memory after the instruction was run, it is restored when the timeline is rolled back to this entry.
+    memory: RuntimeMemory,
+}
+
+/// Timeline of the instructions that were run in the playground.
+///
+/// The timeline can be rolled back to any entry, rollbacks can be undone until the next instruction is run.
+pub struct Timeline {
+    /// Memory before the first instruction was run.
+    initial_memory: RuntimeMemory,
+    entries: Vec<TimelineEntry>,
+    /// Stores the entries before they were rolled back, to be able to undo the rollback.
+    history: UndoHistory<Vec<TimelineEntry>>,
+    /// Names under which memory locations are displayed in the changes, see [`Runtime::aliases`](crate::Runtime::aliases).
+    aliases: HashMap<String, String>,
+}
+
+impl Timeline {
+    pub fn new(initial_memory: RuntimeMemory, aliases: HashMap<String, String>) -> Self {
+        Self {
+            initial_memory,
+            entries: Vec::new(),
+            history: UndoHistory::new(),
+            aliases,
+        }
+    }
+
+    pub fn entries(&self) -> &[TimelineEntry] {
+        &self.entries
+    }
+
+    /// Adds the instruction that was run, `before` and `after` are the memory before and after it was run.
+    pub fn push(
+        &mut self,
+        line: Line<'static>,
+        instruction: String,
+        before: &RuntimeMemory,
+        after: RuntimeMemory,
+    ) {
+        self.entries.push(TimelineEntry {
+            line,
+            instruction,
+            changes: memory_changes(before, &after, &self.aliases),
+            memory: after,
+        });
+        // rollbacks can't be undone anymore, because the entries that would be restored don't match the memory
+        self.history = UndoHistory::new();
+    }
+
+    /// Rolls the timeline back, so that only the first `len` entries are kept.
+    ///
+    /// Returns the memory that has to be restored, `None` if nothing was rolled back.
+    pub fn rollback(&mut self, len: usize) -> Option<RuntimeMemory> {
+        if len >= self.entries.len() {
+            return None;
+        }
+        self.history.record(self.entries.clone());
+        self.entries.truncate(len);
+        Some(self.memory())
+    }
+
+    /// Undoes the last rollback.
+    ///
+    /// Returns the memory that has to be restored, `None` if there is nothing to undo.
+    pub fn undo(&mut self) -> Option<RuntimeMemory> {
+        self.entries = self.history.undo(self.entries.clone())?;
+        Some(self.memory())
+    }
+
+    /// Redoes the last rollback that was undone.
+    ///
+    /// Returns the memory that has to be restored, `None` if there is nothing to redo.
+    pub fn redo(&mut self) -> Option<RuntimeMemory> {
+        self.entries = self.history.redo(self.entries.clone())?;
+        Some(self.memory())
+    }
+
+    /// Returns the instructions of the entries, instructions that were rolled back are not included.
+    pub fn program(&self) -> Vec<String> {
+        self.entries.iter().map(|f| f.instruction.clone()).collect()
+    }
+
+    /// Returns the memory after the last entry was run.
+    fn memory(&self) -> RuntimeMemory {
+        self.entries
+            .last()
+            .map_or_else(|| self.initial_memory.clone(), |f| f.memory.clone())
+    }
+}
+
+/// Returns the values that are different in `after`, formatted as `name: old -> new`.
+///
+/// The alias of a memory location is used as name, if one is set.
+fn memory_changes(
+    before: &RuntimeMemory,
+    after: &RuntimeMemory,
+    aliases: &HashMap<String, String>,
+) -> Vec<String> {
+    let format_value = |value: Option<i32>| value.map_or("None".to_string(), |f| f.to_string());
+    let name = |name: String| aliases.get(&name).cloned().unwrap_or(name);
+    let mut changes = Vec::new();
+    let mut accumulators = after.accumulators.values().collect::<Vec<_>>();
+    accumulators.sort_by_key(|f| f.id);
+    for accumulator in accumulators {
+        let old = before
+            .accumulators
+            .get(&accumulator.id)
+            .and_then(|f| f.data);
+        if old != accumulator.data {
+            changes.push(format!(
+                "{}: {} -> {}",
+                name(format!("α{}", accumulator.id)),
+                format_value(old),
+                format_value(accumulator.data)
+            ));
+        }
+    }
+    if let Some(value) = after.gamma {
+        let old = before.gamma.flatten();
+        if old != value {
+            changes.push(format!(
+                "{}: {} -> {}",
+                name("γ".to_string()),
+                format_value(old),
+                format_value(value)
+            ));
+        }
+    }
+    let mut memory_cells = after.program_memory_cells().collect::<Vec<_>>();
+    memory_cells.sort_by(|a, b| a.label.cmp(&b.label));
+    for memory_cell in memory_cells {
+        let old = before
+            .memory_cells
+            .get(&memory_cell.label)
+            .and_then(|f| f.data);
+        if old != memory_cell.data {
+            changes.push(format!(
+                "{}: {} -> {}",
+                name(memory_cell.label.clone()),
+                format_value(old),
+                format_value(memory_cell.data)
+            ));
+        }
+    }
+    let mut index_memory_cells = after.index_memory_cells.iter().collect::<Vec<_>>();
+    index_memory_cells.sort_by_key(|f| f.0);
+    for (index, value) in index_memory_cells {
+        let old = before.index_memory_cells.get(index).copied().flatten();
+        if old != *value {
+            changes.push(format!(
+                "{}: {} -> {}",
+                name(format!("[{index}]")),
+                format_value(old),
+                format_value(*value)
+            ));
+        }
+    }
+    if before.stack != after.stack {
+        changes.push(format!("stack: {:?} -> {:?}", before.stack, after.stack));
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ratatui::text::Line;
+
+    use crate::utils::test_utils::runtime_from_str;
+
+    use super::{memory_changes, Timeline};
+
+    #[test]
+    fn test_timeline_rollback() {
+        let mut rt = runtime_from_str("a := 1\np(h1) := 5\npush").unwrap();
+        let mut timeline = Timeline::new(rt.runtime_memory().clone(), HashMap::new());
+        for line in ["a := 1", "p(h1) := 5", "push"] {
+            let before = rt.runtime_memory().clone();
+            rt.step().unwrap();
+            timeline.push(
+                Line::from(line),
+                line.to_string(),
+                &before,
+                rt.runtime_memory().clone(),
+            );
+        }
+        let changes = timeline
+            .entries()
+            .iter()
+            .map(|f| f.changes.join(", "))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec!["α0: None -> 1", "h1: None -> 5", "stack: [] -> [1]"]
+        );
+        let memory = timeline.rollback(1).unwrap();
+        assert_eq!(timeline.program(), vec!["a := 1"]);
+        assert_eq!(memory.accumulators[&0].data, Some(1));
+        assert_eq!(memory.memory_cells["h1"].data, None);
+        assert!(timeline.rollback(1).is_none());
+        assert_eq!(timeline.undo().unwrap().stack, vec![1]);
+        assert_eq!(timeline.entries().len(), 3);
+        assert_eq!(timeline.redo().unwrap().stack, Vec::<i32>::new());
+        assert_eq!(timeline.rollback(0).unwrap().accumulators[&0].data, None);
+    }
+
+    #[test]
+    fn test_timeline_aliases() {
+        let mut rt = runtime_from_str("a := 1\np(h1) := 5").unwrap();
+        let before = rt.runtime_memory().clone();
+        rt.run().unwrap();
+        let aliases = HashMap::from([("h1".to_string(), "n".to_string())]);
+        assert_eq!(
+            memory_changes(&before, rt.runtime_memory(), &aliases),
+            vec!["α0: None -> 1", "n: None -> 5"]
+        );
+    }
+}
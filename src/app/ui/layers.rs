@@ -0,0 +1,408 @@
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::app::{keybindings::KeySymbol, memory_edit::MemoryLocation, App, State};
+
+use super::paragraph_with_line_wrap;
+
+/// A modal layer that is drawn above the base screen.
+///
+/// Layers are stacked, the layer that was opened last is drawn on top and receives the input.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Layer {
+    /// Popup that is displayed when the execution has finished.
+    ExecutionFinished,
+    /// Window in which a custom instruction can be entered.
+    CustomInstruction,
+    /// Popup that is displayed when a runtime error occurred.
+    RuntimeError,
+    /// Popup that is displayed when a custom instruction could not be parsed.
+    CustomInstructionError,
+    /// Popup that is displayed when a custom instruction is forbidden.
+    BuildProgramError,
+    /// Popup that asks if the session should be saved before quitting.
+    QuitPrompt,
+    /// Timeline of the instructions that were run in the playground.
+    Timeline,
+    /// List of the labels of the program, breakpoints can be set in the block of a label.
+    Labels,
+    /// List of the memory locations, the value of the selected location can be changed.
+    EditMemory,
+    /// List of the actions that are available in the current state and their keys.
+    Help,
+}
+
+impl Layer {
+    /// Returns true if pressing escape closes this layer.
+    ///
+    /// If the layer can not be closed with escape, escape exits the application.
+    pub fn closed_by_escape(&self) -> bool {
+        matches!(
+            self,
+            Self::CustomInstruction
+                | Self::QuitPrompt
+                | Self::Timeline
+                | Self::Labels
+                | Self::EditMemory
+                | Self::Help
+        )
+    }
+}
+
+impl App {
+    /// Returns the modal layers that are currently open, ordered from bottom to top.
+    pub fn layers(&self) -> Vec<Layer> {
+        let mut layers = Vec::new();
+        match &self.state {
+            State::Finished(true) => layers.push(Layer::ExecutionFinished),
+            State::CustomInstruction(_) => layers.push(Layer::CustomInstruction),
+            State::RuntimeError(_, _) => layers.push(Layer::RuntimeError),
+            State::CustomInstructionError(_, _) => layers.push(Layer::CustomInstructionError),
+            State::BuildProgramError(_) => layers.push(Layer::BuildProgramError),
+            State::QuitPrompt(state) => {
+                if let State::Finished(true) = **state {
+                    layers.push(Layer::ExecutionFinished);
+                }
+                layers.push(Layer::QuitPrompt);
+            }
+            State::Timeline(_, _) => layers.push(Layer::Timeline),
+            State::Labels(_, _) => layers.push(Layer::Labels),
+            State::EditMemory(_, _) => layers.push(Layer::EditMemory),
+            _ => (),
+        }
+        // the help is opened above the layers of the state
+        if self.help.is_some() {
+            layers.push(Layer::Help);
+        }
+        layers
+    }
+
+    /// Closes the layer, does nothing if the layer can not be closed with escape.
+    pub fn close_layer(&mut self, layer: &Layer) {
+        match (layer, &self.state) {
+            (Layer::CustomInstruction, _) => {
+                self.state = State::Running(self.instruction_list_states.breakpoints_set());
+            }
+            (Layer::QuitPrompt, State::QuitPrompt(state))
+            | (Layer::Timeline, State::Timeline(state, _))
+            | (Layer::Labels, State::Labels(state, _)) => self.state = *state.clone(),
+            (Layer::Help, _) => self.help = None,
+            // escape cancels the value that is entered, before the editor is closed
+            (Layer::EditMemory, State::EditMemory(state, edit)) => {
+                if edit.is_typing() {
+                    let mut edit = edit.clone();
+                    edit.input = None;
+                    self.state = State::EditMemory(state.clone(), edit);
+                } else {
+                    self.state = *state.clone();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Draws the layer.
+    ///
+    /// `area` is the area of the base screen, without the keybinding hints.
+    pub fn draw_layer(&mut self, f: &mut Frame, layer: &Layer, area: Rect, is_playground: bool) {
+        match layer {
+            Layer::ExecutionFinished => {
+                let block = Block::default()
+                    .title("Execution finished!")
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.execution_finished_popup_border())
+                    .style(self.theme.execution_finished_block());
+                let area = crate::app::centered_rect_abs(5, 36, f.size());
+                let text = paragraph_with_line_wrap(
+                    format!("Press [t] to reset to start.\nPress [d] to dismiss this message.\nPress [q] or [{}] to exit.", KeySymbol::Escape),
+                    area.width,
+                )
+                .block(block);
+                f.render_widget(Clear, area); //this clears out the background
+                f.render_widget(text, area);
+            }
+            Layer::CustomInstruction => {
+                if let State::CustomInstruction(single_instruction) = &mut self.state {
+                    single_instruction.draw(f, area, false);
+                }
+            }
+            Layer::RuntimeError => {
+                let State::RuntimeError(e, _) = &self.state else {
+                    return;
+                };
+                let block = Block::default()
+                    .title("Runtime error!")
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.error_block_border())
+                    .style(self.theme.error_block());
+                let area = crate::app::centered_rect(60, 30, None, f.size());
+                let text = paragraph_with_line_wrap(if is_playground {format!("This instruction could not be executed due to the following problem:\n{}\n\nPress [q] to exit and to view further information regarding this error.\nPress [ENTER] to close.", e.reason)} else {format!(
+                    "Execution can not continue due to the following problem:\n{}\n\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [t] to reset to start.",
+                    e.reason, KeySymbol::Escape)}, area.width.saturating_sub(2)).block(block);
+                f.render_widget(Clear, area); //this clears out the background
+                f.render_widget(text, area);
+            }
+            Layer::CustomInstructionError => {
+                let State::CustomInstructionError(reason, _) = &self.state else {
+                    return;
+                };
+                let block = Block::default()
+                    .title("Error: unable to parse instruction".to_string())
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.error_block_border())
+                    .style(self.theme.error_block());
+                let area = error_popup_area(f.size());
+                let text = paragraph_with_line_wrap(format!(
+                    "{}\n\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [ENTER] to close.",
+                    reason,
+                    KeySymbol::Escape
+                ), area.width)
+                .block(block);
+                f.render_widget(Clear, area); //this clears out the background
+                f.render_widget(text, area);
+            }
+            Layer::BuildProgramError => {
+                let block = Block::default()
+                    .title("Error: instruction forbidden".to_string())
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.error_block_border())
+                    .style(self.theme.error_block());
+                let area = error_popup_area(f.size());
+                let text = paragraph_with_line_wrap(format!(
+                    "The entered instruction is forbidden.\n\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [ENTER] to close.",
+                    KeySymbol::Escape
+                ), area.width)
+                .block(block);
+                f.render_widget(Clear, area); //this clears out the background
+                f.render_widget(text, area);
+            }
+            Layer::QuitPrompt => {
+                let block = Block::default()
+                    .title("Unsaved changes")
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.execution_finished_popup_border())
+                    .style(self.theme.execution_finished_block());
+                let mut lines = self
+                    .unsaved_changes()
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>();
+                match (self.breakpoints_changed(), self.playground_changed()) {
+                    (true, false) => {
+                        lines.push("Press [s] to save the session and exit.".to_string())
+                    }
+                    (false, true) => lines.push(format!(
+                        "Press [s] to save the instructions to {} and exit.",
+                        self.playground_file
+                    )),
+                    (true, true) => lines.push(format!(
+                        "Press [s] to save the session and the instructions to {} and exit.",
+                        self.playground_file
+                    )),
+                    (false, false) => (),
+                }
+                lines.push("Press [q] to exit without saving.".to_string());
+                lines.push(format!("Press [{}] to cancel.", KeySymbol::Escape));
+                // long file names are wrapped
+                let height = lines
+                    .iter()
+                    .map(|f| f.chars().count().div_ceil(46).max(1))
+                    .sum::<usize>();
+                let area = crate::app::centered_rect_abs(height as u16 + 3, 50, f.size());
+                let text = paragraph_with_line_wrap(lines.join("\n"), area.width.saturating_sub(2))
+                    .block(block);
+                f.render_widget(Clear, area); //this clears out the background
+                f.render_widget(text, area);
+            }
+            Layer::Timeline => {
+                let State::Timeline(_, selected) = &self.state else {
+                    return;
+                };
+                let block = Block::default()
+                    .title("Timeline")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(self.theme.code_block_border())
+                    .style(self.theme.code_block());
+                let mut items = vec![ListItem::new(Line::from(Span::styled(
+                    "start",
+                    self.theme.line_numbers(),
+                )))];
+                for (idx, entry) in self.timeline.entries().iter().enumerate() {
+                    let mut spans = vec![Span::styled(
+                        format!("{:>3} ", idx + 1),
+                        self.theme.line_numbers(),
+                    )];
+                    spans.extend(entry.line.spans.iter().cloned());
+                    if !entry.changes.is_empty() {
+                        spans.push(Span::styled(
+                            format!("  {}", entry.changes.join(", ")),
+                            self.theme.syntax_highlighting_theme().comment(),
+                        ));
+                    }
+                    items.push(ListItem::new(Line::from(spans)));
+                }
+                let list = List::new(items)
+                    .block(block)
+                    .highlight_style(self.theme.list_item_highlight(false))
+                    .highlight_symbol(">> ");
+                let mut list_state = ListState::default().with_selected(Some(*selected));
+                let area = crate::app::centered_rect(70, 60, None, area);
+                f.render_widget(Clear, area); //this clears out the background
+                f.render_stateful_widget(list, area, &mut list_state);
+            }
+            Layer::Labels => {
+                let State::Labels(_, selected) = &self.state else {
+                    return;
+                };
+                let block = Block::default()
+                    .title("Labels")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(self.theme.breakpoint_border())
+                    .style(self.theme.code_block());
+                let items = self
+                    .runtime
+                    .label_blocks()
+                    .into_iter()
+                    .map(|(label, block)| {
+                        let breakpoints_set = self
+                            .instruction_list_states
+                            .breakpoints_in_range(block.clone());
+                        ListItem::new(Line::from(vec![
+                            Span::styled(
+                                if breakpoints_set { "* " } else { "  " },
+                                self.theme.breakpoint(),
+                            ),
+                            Span::styled(label, self.theme.syntax_highlighting_theme().label()),
+                            Span::styled(
+                                format!("  lines {}-{}", block.start + 1, block.end),
+                                self.theme.line_numbers(),
+                            ),
+                        ]))
+                    })
+                    .collect::<Vec<_>>();
+                let list = List::new(items)
+                    .block(block)
+                    .highlight_style(self.theme.list_item_highlight(true))
+                    .highlight_symbol(">> ");
+                let mut list_state = ListState::default().with_selected(Some(*selected));
+                let area = crate::app::centered_rect(40, 60, None, area);
+                f.render_widget(Clear, area); //this clears out the background
+                f.render_stateful_widget(list, area, &mut list_state);
+            }
+            Layer::EditMemory => {
+                let State::EditMemory(_, edit) = &self.state else {
+                    return;
+                };
+                let block = Block::default()
+                    .title("Edit memory")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(self.theme.memory_block_border())
+                    .style(self.theme.memory_block());
+                let memory = self.runtime.runtime_memory();
+                let items = MemoryLocation::all(memory)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, location)| {
+                        let value = match &edit.input {
+                            Some(input) if idx == edit.selected => Span::styled(
+                                format!("{input}_"),
+                                self.theme.list_item_highlight(true),
+                            ),
+                            _ => Span::raw(
+                                location
+                                    .value(memory)
+                                    .map_or("None".to_string(), |f| f.to_string()),
+                            ),
+                        };
+                        let name = location.to_string();
+                        let name = self.runtime.aliases().get(&name).unwrap_or(&name);
+                        ListItem::new(Line::from(vec![
+                            Span::styled(format!("{name:>4}: "), self.theme.line_numbers()),
+                            value,
+                        ]))
+                    })
+                    .collect::<Vec<_>>();
+                let list = List::new(items)
+                    .block(block)
+                    .highlight_style(self.theme.list_item_highlight(false))
+                    .highlight_symbol(">> ");
+                let mut list_state = ListState::default().with_selected(Some(edit.selected));
+                let area = crate::app::centered_rect(30, 60, None, area);
+                f.render_widget(Clear, area); //this clears out the background
+                f.render_stateful_widget(list, area, &mut list_state);
+            }
+            Layer::Help => {
+                let Some(selected) = self.help else {
+                    return;
+                };
+                let block = Block::default()
+                    .title("Help")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(self.theme.code_block_border())
+                    .style(self.theme.code_block());
+                let entries = self.keybinding_hints.help_entries();
+                let width = entries
+                    .iter()
+                    .map(|(key, _)| key.chars().count())
+                    .max()
+                    .unwrap_or(0);
+                let items = entries
+                    .iter()
+                    .map(|(key, label)| {
+                        ListItem::new(Line::from(vec![
+                            Span::styled(format!("{key:>width$}  "), self.theme.line_numbers()),
+                            Span::raw(label.clone()),
+                        ]))
+                    })
+                    .collect::<Vec<_>>();
+                let list = List::new(items)
+                    .block(block)
+                    .highlight_style(self.theme.list_item_highlight(false))
+                    .highlight_symbol(">> ");
+                let mut list_state = ListState::default().with_selected(Some(selected));
+                let area = crate::app::centered_rect(40, 60, None, area);
+                f.render_widget(Clear, area); //this clears out the background
+                f.render_stateful_widget(list, area, &mut list_state);
+            }
+        }
+    }
+}
+
+/// Returns the area of popups that display an error with a custom instruction.
+fn error_popup_area(size: Rect) -> Rect {
+    crate::app::centered_rect(
+        60,
+        30,
+        if size.width <= 124 { Some(7) } else { Some(6) },
+        size,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::test_utils::TestApp;
+
+    use super::Layer;
+
+    #[test]
+    fn test_layers() {
+        let mut app = TestApp::new("a := 5\na := 6");
+        assert!(app.app.layers().is_empty());
+        app.press_all("si").unwrap();
+        assert_eq!(app.app.layers(), vec![Layer::CustomInstruction]);
+        assert!(Layer::CustomInstruction.closed_by_escape());
+        app.press_key(crossterm::event::KeyCode::Esc).unwrap();
+        assert!(app.app.layers().is_empty());
+        app.press('r').unwrap();
+        assert_eq!(app.app.layers(), vec![Layer::ExecutionFinished]);
+        assert!(!Layer::ExecutionFinished.closed_by_escape());
+    }
+}
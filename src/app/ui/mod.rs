@@ -1,39 +1,118 @@
 use ratatui::{
-    prelude::{Alignment, Constraint, Direction, Layout},
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
     text::{Line, Span, Text},
-    widgets::{Block, BorderType, Borders, Clear, List, ListDirection, ListItem, Paragraph},
+    widgets::{
+        Block, BorderType, Borders, Clear, Gauge, List, ListDirection, ListItem, Paragraph, Wrap,
+    },
     Frame,
 };
 use text_align::TextAlign;
 
-use super::{keybindings::KeySymbol, run_instruction::SingleInstruction, App, State};
+use super::{
+    content::{BreakpointState, MemoryPanel},
+    run_instruction::SingleInstruction,
+    App, State,
+};
 
+/// Modal layers that are drawn above the base screen.
+pub mod layers;
 pub mod style;
 pub mod syntax_highlighting;
 
+/// Minimum width of the terminal that is required to draw the ui.
+const MIN_WIDTH: u16 = 50;
+/// Minimum height of the terminal that is required to draw the ui.
+const MIN_HEIGHT: u16 = 18;
+/// Share of the step budget after which the steps gauge is highlighted.
+const STEP_BUDGET_WARNING: f64 = 0.9;
+/// Height of the machine view, output and value history panels, five lines and the border.
+const BOTTOM_PANEL_HEIGHT: u16 = 7;
+/// Minimum height of the terminal that is required to draw the panels below the other panels, they are hidden in
+/// smaller terminals so that the other panels keep the space they need.
+const MIN_HEIGHT_BOTTOM_PANELS: u16 = MIN_HEIGHT + BOTTOM_PANEL_HEIGHT;
+
+/// Areas of the panels that react to the mouse, set when the ui is drawn.
+///
+/// Areas of panels that are not drawn are empty.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct MouseAreas {
+    pub accumulators: Rect,
+    pub breakpoints: Rect,
+    pub code: Rect,
+    pub memory_cells: Rect,
+    pub output: Rect,
+    pub stack: Rect,
+}
+
 impl App {
     /// Draw the ui
     #[allow(clippy::too_many_lines)]
     pub fn draw(&mut self, f: &mut Frame) {
+        self.mouse_areas = MouseAreas::default();
+        // don't draw the ui when the terminal is too small, because the layout would be broken
+        if f.size().width < MIN_WIDTH || f.size().height < MIN_HEIGHT {
+            let text = Paragraph::new(format!(
+                "Terminal too small\n(need {MIN_WIDTH}x{MIN_HEIGHT}, got {}x{})",
+                f.size().width,
+                f.size().height
+            ))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+            f.render_widget(text, super::centered_rect(100, 0, Some(2), f.size()));
+            return;
+        }
+
         // when the app is in playground mode, some things are rendered differently
         let is_playground = match self.state {
-            State::Playground(_) => true,
+            State::Playground(_) | State::Timeline(_, _) => true,
             State::RuntimeError(_, is_playground) => is_playground,
             State::CustomInstructionError(_, is_playground) => is_playground,
             _ => false,
         };
 
-        let (keybinding_hints, keybinding_hints_height) = self
+        let (keybinding_hints, mut keybinding_hints_height) = self
             .keybinding_hints
             .keybinding_hint_paragraph(f.size().width);
+        if self.hints_hidden() {
+            keybinding_hints_height = 0;
+        }
 
+        // the machine view, output and value history panels are placed below the other panels, they share the full
+        // width
+        let show_bottom_panels = f.size().height >= MIN_HEIGHT_BOTTOM_PANELS;
+        let show_machine_view = self.machine_view.is_some() && !is_playground && show_bottom_panels;
+        // the output is shown when the program can print, values can also be printed by custom instructions
+        let show_output = (self.runtime.contains_print_instruction()
+            || !self.runtime.control_flow().output.is_empty())
+            && show_bottom_panels;
+        let show_value_history = self.show_value_history && !is_playground && show_bottom_panels;
+        let mut bottom_constraints = Vec::new();
+        if show_machine_view {
+            bottom_constraints.push(Constraint::Fill(1));
+        }
+        if show_output {
+            bottom_constraints.push(Constraint::Fill(1));
+        }
+        if show_value_history {
+            bottom_constraints.push(Constraint::Fill(1));
+        }
+        let bottom_height = if bottom_constraints.is_empty() {
+            0
+        } else {
+            BOTTOM_PANEL_HEIGHT
+        };
         let global_chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Fill(1),
+                Constraint::Length(bottom_height),
                 Constraint::Length(keybinding_hints_height),
             ])
             .split(f.size());
+        let bottom_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(bottom_constraints)
+            .split(global_chunks[1]);
 
         let mut chunk_constraints = if is_playground {
             // don't add chunk for breakpoints, when in playground mode
@@ -54,16 +133,29 @@ impl App {
             .split(global_chunks[0]);
 
         // draw keybinding hints
-        f.render_widget(keybinding_hints, global_chunks[1]);
+        f.render_widget(keybinding_hints, global_chunks[2]);
 
-        let mut right_chunk_constraints = vec![Constraint::Percentage(30), Constraint::Fill(1)];
+        // the gamma accumulator has its own panel above the accumulators
+        let show_gamma = self.show_gamma && self.memory_lists_manager.gamma_enabled();
+        let mut right_chunk_constraints = Vec::new();
+        if show_gamma {
+            right_chunk_constraints.push(Constraint::Length(3));
+        }
+        right_chunk_constraints.push(Constraint::Percentage(30));
+        right_chunk_constraints.push(Constraint::Fill(1));
         if !is_playground {
-            right_chunk_constraints.push(Constraint::Length(3))
+            right_chunk_constraints.push(Constraint::Length(3));
+            right_chunk_constraints.push(Constraint::Length(3));
         }
         let right_chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints(right_chunk_constraints)
             .split(chunks[if is_playground { 1 } else { 2 }]);
+        let (gamma_chunk, right_chunks) = if show_gamma {
+            (Some(right_chunks[0]), &right_chunks[1..])
+        } else {
+            (None, &right_chunks[..])
+        };
 
         let mut stack_chunks_constraints = vec![Constraint::Fill(1)];
         if self.show_call_stack {
@@ -85,6 +177,30 @@ impl App {
             .constraints(central_constraints)
             .split(chunks[if is_playground { 0 } else { 1 }]);
 
+        // the profile column is placed between the breakpoints and the code
+        let (profile_chunk, code_chunk) = match &self.line_profile {
+            Some(_) if !is_playground => {
+                let profile_chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Length(18), Constraint::Fill(1)])
+                    .split(central_chunks[0]);
+                (Some(profile_chunks[0]), profile_chunks[1])
+            }
+            _ => (None, central_chunks[0]),
+        };
+        // the hit count column is placed between the breakpoints and the code, the profile column shows the hit
+        // counts as well, so it replaces this column
+        let (hits_chunk, code_chunk) =
+            if self.show_hit_counts && self.line_profile.is_none() && !is_playground {
+                let hits_chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Length(9), Constraint::Fill(1)])
+                    .split(code_chunk);
+                (Some(hits_chunks[0]), hits_chunks[1])
+            } else {
+                (None, code_chunk)
+            };
+
         // Code area
         let mut code_area = Block::default()
             .borders(Borders::ALL)
@@ -97,17 +213,37 @@ impl App {
             .style(self.theme.code_block());
         if let State::RuntimeError(_, false) = self.state {
             code_area = code_area.border_style(self.theme.error_block_border());
-        } else if let State::DebugSelect(_, _) = self.state {
+        } else if let State::DebugSelect(_, _) | State::Labels(_, _) = self.state {
             code_area = code_area
                 .border_style(self.theme.breakpoint_border())
                 .title("Debug select mode");
+            if let State::DebugSelect(_, _) = self.state {
+                // describe the selected instruction as a learning aid while reading the code
+                if let Some(instruction) = self
+                    .instruction_list_states
+                    .selected_line()
+                    .and_then(|idx| self.runtime.instruction(idx))
+                {
+                    code_area = code_area.title_bottom(instruction.description());
+                }
+            }
         } else {
             code_area = code_area
                 .border_style(self.theme.code_block_border())
                 .title(if is_playground {
-                    "Executed instructions".to_string()
+                    match &self.playground_saved {
+                        Some(saved) => format!("Executed instructions ({saved})"),
+                        None => "Executed instructions".to_string(),
+                    }
                 } else {
-                    format!("File: {}", self.filename.clone())
+                    match self.share_role {
+                        Some(role) => format!(
+                            "File: {} ({})",
+                            self.filename,
+                            role.status(self.control_granted)
+                        ),
+                        None => format!("File: {}", self.filename.clone()),
+                    }
                 });
         }
 
@@ -117,25 +253,38 @@ impl App {
                 .as_list_items(is_playground, &self.theme),
         )
         .block(code_area)
-        .highlight_style(if let State::DebugSelect(_, _) = self.state {
-            self.theme.list_item_highlight(true)
+        .highlight_style(
+            if let State::DebugSelect(_, _) | State::Labels(_, _) = self.state {
+                self.theme.list_item_highlight(true)
+            } else if self.present_delay.is_some() {
+                self.theme.list_item_highlight_presentation()
+            } else {
+                self.theme.list_item_highlight(false)
+            },
+        )
+        .highlight_symbol(if self.present_delay.is_some() {
+            "\u{25b6}\u{25b6} "
         } else {
-            self.theme.list_item_highlight(false)
+            ">> "
         })
-        .highlight_symbol(">> ")
         .direction(if is_playground {
             ListDirection::BottomToTop
         } else {
             ListDirection::TopToBottom
         })
-        .scroll_padding(2);
+        // in presentation mode more instructions around the current one are kept visible
+        .scroll_padding(if self.present_delay.is_some() { 5 } else { 2 });
 
         // We can now render the item list
         f.render_stateful_widget(
             items,
-            central_chunks[0],
+            code_chunk,
             self.instruction_list_states.instruction_list_state_mut(),
         );
+        if !is_playground {
+            self.mouse_areas.code = code_chunk;
+            self.mouse_areas.breakpoints = chunks[0];
+        }
 
         // Breakpoint list
         if !is_playground {
@@ -154,14 +303,15 @@ impl App {
                 .instructions()
                 .iter()
                 .map(|f| {
-                    let v = if f.2 {
-                        "*".to_string()
-                    } else {
-                        " ".to_string()
+                    let (v, style) = match f.2 {
+                        BreakpointState::Enabled => ("*", self.theme.breakpoint()),
+                        BreakpointState::Disabled => ("o", self.theme.breakpoint_disabled()),
+                        BreakpointState::NotSet => (" ", self.theme.breakpoint()),
                     };
                     ListItem::new(Text::styled(
-                        v.center_align(chunks[0].width.saturating_sub(2) as usize),
-                        self.theme.breakpoint(),
+                        v.to_string()
+                            .center_align(chunks[0].width.saturating_sub(2) as usize),
+                        style,
                     ))
                 })
                 .collect();
@@ -178,6 +328,76 @@ impl App {
             );
         }
 
+        // Profile column
+        if let (Some(profile), Some(profile_chunk)) = (&self.line_profile, profile_chunk) {
+            let profile_area = Block::default()
+                .borders(Borders::ALL)
+                .title("Runs / Time")
+                .border_style(self.theme.breakpoint_border())
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Rounded)
+                .style(self.theme.breakpoint_block());
+            let profile_list_items: Vec<ListItem> =
+                (0..self.instruction_list_states.instructions().len())
+                    .map(|idx| ListItem::new(profile.column(idx, self.runtime.line_hits())))
+                    .collect();
+            let profile_list = List::new(profile_list_items)
+                .block(profile_area)
+                .scroll_padding(2);
+            // scrolled like the breakpoint list, that has the same number of lines
+            let mut state = self
+                .instruction_list_states
+                .breakpoint_list_state_mut()
+                .clone();
+            f.render_stateful_widget(profile_list, profile_chunk, &mut state);
+        }
+
+        // Hit count column
+        if let Some(hits_chunk) = hits_chunk {
+            let hits_area = Block::default()
+                .borders(Borders::ALL)
+                .title("Hits")
+                .border_style(self.theme.breakpoint_border())
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Rounded)
+                .style(self.theme.breakpoint_block());
+            let line_hits = self.runtime.line_hits();
+            let hits_list_items: Vec<ListItem> =
+                (0..self.instruction_list_states.instructions().len())
+                    .map(|idx| match line_hits.get(idx) {
+                        // lines that were never run are left empty
+                        Some(hits) if *hits > 0 => ListItem::new(format!("{hits:>7}")),
+                        _ => ListItem::new(""),
+                    })
+                    .collect();
+            let hits_list = List::new(hits_list_items)
+                .block(hits_area)
+                .scroll_padding(2);
+            // scrolled like the breakpoint list, that has the same number of lines
+            let mut state = self
+                .instruction_list_states
+                .breakpoint_list_state_mut()
+                .clone();
+            f.render_stateful_widget(hits_list, hits_chunk, &mut state);
+        }
+
+        // Gamma accumulator block
+        if let Some(gamma_chunk) = gamma_chunk {
+            let gamma_title = match gamma_chunk.width {
+                0..=8 => "γ",
+                9..=u16::MAX => "Gamma",
+            };
+            let gamma = Block::default()
+                .borders(Borders::ALL)
+                .title(gamma_title)
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Rounded)
+                .border_style(self.theme.memory_block_border())
+                .style(self.theme.memory_block());
+            let gamma_list = List::new(self.memory_lists_manager.gamma_list()).block(gamma);
+            f.render_widget(gamma_list, gamma_chunk);
+        }
+
         // Accumulator block
         let accumulator_title = match right_chunks[0].width {
             0..=13 => "Accs",
@@ -190,9 +410,14 @@ impl App {
             .border_type(BorderType::Rounded)
             .border_style(self.theme.memory_block_border())
             .style(self.theme.memory_block());
+        self.memory_lists_manager.set_height(
+            MemoryPanel::Accumulators,
+            right_chunks[0].height.saturating_sub(2) as usize,
+        );
         let accumulator_list =
             List::new(self.memory_lists_manager.accumulator_list()).block(accumulator);
         f.render_widget(accumulator_list, right_chunks[0]);
+        self.mouse_areas.accumulators = right_chunks[0];
 
         // Memory cell block
         let memory_cells_title = match right_chunks[1].width {
@@ -207,9 +432,14 @@ impl App {
             .border_type(BorderType::Rounded)
             .border_style(self.theme.memory_block_border())
             .style(self.theme.memory_block());
+        self.memory_lists_manager.set_height(
+            MemoryPanel::MemoryCells,
+            right_chunks[1].height.saturating_sub(2) as usize,
+        );
         let memory_cell_list =
             List::new(self.memory_lists_manager.memory_cell_list()).block(memory_cells);
         f.render_widget(memory_cell_list, right_chunks[1]);
+        self.mouse_areas.memory_cells = right_chunks[1];
 
         // Next instruction block
         if !is_playground {
@@ -229,6 +459,34 @@ impl App {
                 Paragraph::new(format!("{}", self.runtime.next_instruction_index() + 1))
                     .block(next_instruction_block);
             f.render_widget(next_instruction, right_chunks[2]);
+
+            // Steps block
+            let steps_block = Block::default()
+                .borders(Borders::ALL)
+                .title("Steps")
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Rounded)
+                .border_style(self.theme.internal_memory_block_border())
+                .style(self.theme.internal_memory_block());
+            let steps = self.runtime.control_flow().steps;
+            if let Some(max_steps) = self.runtime.max_steps() {
+                // display the used steps as gauge, if a step budget is set
+                let ratio = (steps as f64 / max_steps.max(1) as f64).min(1.0);
+                let gauge_style = if ratio >= STEP_BUDGET_WARNING {
+                    self.theme.error_block_border()
+                } else {
+                    self.theme.internal_memory_block_border()
+                };
+                let gauge = Gauge::default()
+                    .block(steps_block)
+                    .gauge_style(gauge_style)
+                    .ratio(ratio)
+                    .label(format!("{steps}/{max_steps}"));
+                f.render_widget(gauge, right_chunks[3]);
+            } else {
+                let steps = Paragraph::new(format!("{steps}")).block(steps_block);
+                f.render_widget(steps, right_chunks[3]);
+            }
         }
 
         // Stack block
@@ -243,8 +501,13 @@ impl App {
             .border_type(BorderType::Rounded)
             .border_style(self.theme.memory_block_border())
             .style(self.theme.memory_block());
+        self.memory_lists_manager.set_height(
+            MemoryPanel::Stack,
+            stack_chunks[0].height.saturating_sub(2) as usize,
+        );
         let stack_list = List::new(self.memory_lists_manager.stack_list()).block(stack);
         f.render_widget(stack_list, stack_chunks[0]);
+        self.mouse_areas.stack = stack_chunks[0];
 
         // Render call stack if enabled
         if self.show_call_stack {
@@ -265,31 +528,75 @@ impl App {
             f.render_widget(call_stack, stack_chunks[1]);
         }
 
-        // Popup if execution has finished
-        if self.state == State::Finished(true) {
-            let block = Block::default()
-                .title("Execution finished!")
+        // Machine view block
+        if let Some(machine_view) = self.machine_view.as_ref().filter(|_| show_machine_view) {
+            let machine_view_block = Block::default()
                 .borders(Borders::ALL)
-                .border_style(self.theme.execution_finished_popup_border())
-                .style(self.theme.execution_finished_block());
-            let area = super::centered_rect_abs(5, 36, f.size());
-            let text = paragraph_with_line_wrap(
-            format!("Press [t] to reset to start.\nPress [d] to dismiss this message.\nPress [q] or [{}] to exit.", KeySymbol::Escape),
-            area.width,
-        )
-        .block(block);
-            f.render_widget(Clear, area); //this clears out the background
-            f.render_widget(text, area);
+                .title("Machine view")
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Rounded)
+                .border_style(self.theme.internal_memory_block_border())
+                .style(self.theme.internal_memory_block());
+            let lines = machine_view
+                .lines(&self.runtime)
+                .into_iter()
+                .map(Line::from)
+                .collect::<Vec<_>>();
+            let machine_view = Paragraph::new(lines).block(machine_view_block);
+            f.render_widget(machine_view, bottom_chunks[0]);
+        }
+
+        // Output block, the value that was printed first is listed first
+        if show_output {
+            let output_chunk = bottom_chunks[usize::from(show_machine_view)];
+            let output_block = Block::default()
+                .borders(Borders::ALL)
+                .title("Output")
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Rounded)
+                .border_style(self.theme.memory_block_border())
+                .style(self.theme.memory_block());
+            self.memory_lists_manager.set_height(
+                MemoryPanel::Output,
+                output_chunk.height.saturating_sub(2) as usize,
+            );
+            let output = List::new(self.memory_lists_manager.output_list()).block(output_block);
+            f.render_widget(output, output_chunk);
+            self.mouse_areas.output = output_chunk;
         }
 
-        // Draw custom instruction popup/window
-        if let State::CustomInstruction(single_instruction) = &mut self.state {
-            single_instruction.draw(f, global_chunks[0], false)
+        // Value history block, the last change is listed first
+        if show_value_history {
+            let value_history_block = Block::default()
+                .borders(Borders::ALL)
+                .title("Value history")
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Rounded)
+                .border_style(self.theme.memory_block_border())
+                .style(self.theme.memory_block());
+            let lines = self
+                .value_history
+                .lines(
+                    self.runtime.aliases(),
+                    self.memory_lists_manager.value_format(),
+                )
+                .into_iter()
+                .map(ListItem::new)
+                .collect::<Vec<_>>();
+            let value_history = List::new(lines).block(value_history_block);
+            f.render_widget(value_history, bottom_chunks[bottom_chunks.len() - 1]);
         }
+
+        // Draw playground input window, it is part of the base screen in playground mode
         match &mut self.state {
             State::Playground(single_instruction) => {
                 single_instruction.draw(f, central_chunks[1], true);
             }
+            State::Timeline(state, _) => {
+                if let State::Playground(single_instruction) = state.as_mut() {
+                    single_instruction.draw(f, central_chunks[1], true);
+                }
+            }
             State::CustomInstructionError(_, true) | State::RuntimeError(_, true) => {
                 SingleInstruction::new(&self.executed_custom_instructions, &self.theme).draw(
                     f,
@@ -300,72 +607,48 @@ impl App {
             _ => (),
         }
 
-        // Popup if runtime error
-        if let State::RuntimeError(e, _) = &self.state {
-            let block = Block::default()
-                .title("Runtime error!")
-                .borders(Borders::ALL)
-                .border_style(self.theme.error_block_border())
-                .style(self.theme.error_block());
-            let area = super::centered_rect(60, 30, None, f.size());
-            let text = paragraph_with_line_wrap(if is_playground {format!("This instruction could not be executed due to the following problem:\n{}\n\nPress [q] to exit and to view further information regarding this error.\nPress [ENTER] to close.", e.reason)} else {format!(
-                "Execution can not continue due to the following problem:\n{}\n\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [t] to reset to start.",
-                e.reason, KeySymbol::Escape)}, area.width - 2).block(block);
-            f.render_widget(Clear, area); //this clears out the background
-            f.render_widget(text, area);
+        // Draw modal layers above the base screen, from bottom to top
+        for layer in self.layers() {
+            self.draw_layer(f, &layer, global_chunks[0], is_playground);
         }
 
-        // Draw error when instruction could not be parsed
-        if let State::CustomInstructionError(reason, _) = &self.state {
+        // Profiling overlay
+        let mut overlay_y = 0;
+        if let Some(profile) = &self.render_profile {
+            let lines = profile.lines(
+                self.instruction_list_states.instructions().len(),
+                self.memory_lists_manager.entry_count(),
+            );
             let block = Block::default()
-                .title("Error: unable to parse instruction".to_string())
+                .title("Profiling")
                 .borders(Borders::ALL)
-                .border_style(self.theme.error_block_border())
-                .style(self.theme.error_block());
-            let area = super::centered_rect(
-                60,
-                30,
-                if f.size().width <= 124 {
-                    Some(7)
-                } else {
-                    Some(6)
-                },
-                f.size(),
-            );
-            let text = paragraph_with_line_wrap(format!(
-            "{}\n\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [ENTER] to close.",
-            reason,
-            KeySymbol::Escape
-        ), area.width)
-        .block(block);
-            f.render_widget(Clear, area); //this clears out the background
-            f.render_widget(text, area);
+                .border_type(BorderType::Rounded)
+                .border_style(self.theme.internal_memory_block_border())
+                .style(self.theme.internal_memory_block());
+            let size = f.size();
+            let width = 32.min(size.width);
+            let height = (lines.len() as u16 + 2).min(size.height);
+            let area = Rect::new(size.width - width, 0, width, height);
+            f.render_widget(Clear, area);
+            f.render_widget(Paragraph::new(lines.join("\n")).block(block), area);
+            overlay_y = height;
         }
 
-        // Draw error when custom instruction could not be build
-        if let State::BuildProgramError(_) = &self.state {
+        // Key event overlay, below the profiling overlay
+        if let Some(key_event_log) = &self.key_event_log {
+            let lines = key_event_log.lines();
             let block = Block::default()
-                .title("Error: instruction forbidden".to_string())
+                .title("Key events")
                 .borders(Borders::ALL)
-                .border_style(self.theme.error_block_border())
-                .style(self.theme.error_block());
-            let area = super::centered_rect(
-                60,
-                30,
-                if f.size().width <= 124 {
-                    Some(7)
-                } else {
-                    Some(6)
-                },
-                f.size(),
-            );
-            let text = paragraph_with_line_wrap(format!(
-            "The entered instruction is forbidden.\n\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [ENTER] to close.",
-            KeySymbol::Escape
-        ), area.width)
-        .block(block);
-            f.render_widget(Clear, area); //this clears out the background
-            f.render_widget(text, area);
+                .border_type(BorderType::Rounded)
+                .border_style(self.theme.internal_memory_block_border())
+                .style(self.theme.internal_memory_block());
+            let size = f.size();
+            let width = 40.min(size.width);
+            let height = (lines.len() as u16 + 2).min(size.height.saturating_sub(overlay_y));
+            let area = Rect::new(size.width - width, overlay_y, width, height);
+            f.render_widget(Clear, area);
+            f.render_widget(Paragraph::new(lines.join("\n")).block(block), area);
         }
     }
 }
@@ -1,7 +1,7 @@
 use std::rc::Rc;
 
 use clap::ValueEnum;
-use ratatui::style::{palette::material::WHITE, Color, Style};
+use ratatui::style::{palette::material::WHITE, Color, Modifier, Style};
 use serde::{Deserialize, Serialize};
 
 pub type SharedTheme = Rc<Theme>;
@@ -40,7 +40,14 @@ impl From<&BuildInTheme> for Theme {
         match value {
             BuildInTheme::DefaultOld => Theme::default_old(),
             BuildInTheme::Dracula => Theme::dracula(),
-            BuildInTheme::Gray => serde_json::from_str(r#"{"sh_theme":{"assignment":"White","op":"White","cmp":"White","label":"White","build_in":"White","accumulator":"White","gamma":"White","memory_cell_outer":"White","memory_cell_inner":"White","index_memory_cell_outer":"White","index_memory_cell_index_outer":"White","constant":"White","comment":"White"},"background":"Black","foreground":"White","breakpoint_accent":"DarkGray","error":"White","code_area_default":"White","list_item_highlight_fg":"White","list_item_highlight_bg":"DarkGray","line_numbers":"White","execution_finished_popup_border":"White","keybindings_fg":"White","keybindings_disabled_fg":"DarkGray","keybindings_bg":"DarkGray","custom_instruction_accent_fg":"White","memory_block_border":"White","internal_memory_block_border":"White"}"#).unwrap(),
+            BuildInTheme::Light => serde_json::from_str(include_str!("../../../themes/light.json"))
+                .expect("build in theme should be valid"),
+            BuildInTheme::HighContrast => {
+                serde_json::from_str(include_str!("../../../themes/high-contrast.json"))
+                    .expect("build in theme should be valid")
+            }
+            BuildInTheme::Gray => serde_json::from_str(include_str!("../../../themes/gray.json"))
+                .expect("build in theme should be valid"),
         }
     }
 }
@@ -107,6 +114,14 @@ impl Theme {
         }
     }
 
+    /// Highlight of the current instruction in presentation mode, more prominent so that it can be followed on a
+    /// projector.
+    pub fn list_item_highlight_presentation(&self) -> Style {
+        self.list_item_highlight(false)
+            .fg(self.foreground)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+    }
+
     pub fn keybinding_hints(&self, enabled: bool) -> Style {
         let style = Style::default();
         if enabled {
@@ -140,6 +155,12 @@ impl Theme {
         Style::default().fg(self.breakpoint_accent)
     }
 
+    pub fn breakpoint_disabled(&self) -> Style {
+        Style::default()
+            .fg(self.breakpoint_accent)
+            .add_modifier(Modifier::DIM)
+    }
+
     pub fn breakpoint_block(&self) -> Style {
         Style::default()
             .bg(self.background)
@@ -190,6 +211,7 @@ impl Theme {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(default)]
 pub struct SyntaxHighlightingTheme {
     assignment: Color,
     op: Color,
@@ -207,6 +229,9 @@ pub struct SyntaxHighlightingTheme {
     index_memory_cell_index_outer: Color,
     constant: Color,
     comment: Color,
+    /// `assert` keyword, highlighted differently from the other instructions, as it does not change the program
+    /// state.
+    assertion: Color,
 }
 
 impl Default for SyntaxHighlightingTheme {
@@ -225,6 +250,7 @@ impl Default for SyntaxHighlightingTheme {
             index_memory_cell_index_outer: GREEN,
             constant: PURPLE,
             comment: COMMENT,
+            assertion: ORANGE,
         }
     }
 }
@@ -245,6 +271,7 @@ impl SyntaxHighlightingTheme {
             index_memory_cell_index_outer: WHITE,
             constant: WHITE,
             comment: WHITE,
+            assertion: WHITE,
         }
     }
 
@@ -268,6 +295,12 @@ impl SyntaxHighlightingTheme {
         Style::default().fg(self.build_in)
     }
 
+    pub fn assertion(&self) -> Style {
+        Style::default()
+            .fg(self.assertion)
+            .add_modifier(Modifier::BOLD)
+    }
+
     pub fn accumulator(&self) -> Style {
         Style::default().fg(self.accumulator)
     }
@@ -318,7 +351,15 @@ const PINK: Color = Color::Rgb(255, 121, 198);
 #[derive(Serialize, Deserialize, Clone, Debug, ValueEnum)]
 #[serde(rename_all = "snake_case")]
 pub enum BuildInTheme {
+    /// Dark theme that is used by default, can also be selected as `dark`.
+    #[value(alias = "dark")]
     Dracula,
+    /// Color scheme before v1.5.0.
     DefaultOld,
+    /// Gray and white only, without syntax highlighting.
     Gray,
+    /// Dark text on a light background, for terminals with a light color scheme.
+    Light,
+    /// Bright colors on a black background, for projectors and low vision.
+    HighContrast,
 }
@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{collections::HashMap, rc::Rc};
 
 use ratatui::{
     style::Style,
@@ -7,7 +7,8 @@ use ratatui::{
 
 use crate::{
     base::Operation,
-    instructions::{IndexMemoryCellIndexType, Instruction, TargetType, Value},
+    instructions::{Condition, IndexMemoryCellIndexType, Instruction, LoopKind, TargetType, Value},
+    runtime::macros::{parse_header, Macros},
     utils::{self, remove_comment},
 };
 
@@ -82,6 +83,40 @@ impl SyntaxHighlighter {
         Span::from(format!("{value}")).style(self.theme.constant())
     }
 
+    /// Creates the spans of an instruction of the program, `macro_parameters` contains the parameters of the macro
+    /// if the instruction belongs to the definition of a macro.
+    ///
+    /// The instructions of a macro are not highlighted, if they use a parameter, because the parameter is only
+    /// replaced when the macro is expanded, or if they can not be parsed.
+    fn instruction_spans(
+        &self,
+        instruction: &str,
+        macro_parameters: Option<&Vec<String>>,
+    ) -> miette::Result<Vec<Span<'static>>> {
+        let Some(parameters) = macro_parameters else {
+            return Ok(Instruction::try_from(instruction)?.to_spans(self));
+        };
+        if let Some(header) = instruction.strip_prefix("macro") {
+            return Ok(vec![
+                self.build_in_span("macro"),
+                Span::from(header.to_string()),
+            ]);
+        }
+        if instruction == "endmacro" {
+            return Ok(vec![self.build_in_span("endmacro")]);
+        }
+        if instruction
+            .split_whitespace()
+            .any(|part| parameters.iter().any(|f| f == part))
+        {
+            return Ok(vec![Span::from(instruction.to_string())]);
+        }
+        Ok(Instruction::try_from(instruction).map_or_else(
+            |_| vec![Span::from(instruction.to_string())],
+            |f| f.to_spans(self),
+        ))
+    }
+
     /// This function turns the input strings into a
     /// vector of lines, ready to be printed in the tui.
     ///
@@ -105,8 +140,9 @@ impl SyntaxHighlighter {
             (0, 0)
         };
 
+        let definition_lines = macro_definition_lines(input);
         let mut lines = Vec::new();
-        for line in input {
+        for (idx, line) in input.iter().enumerate() {
             let parts = match input_parts(line.clone()) {
                 Some(parts) => parts,
                 None => {
@@ -118,8 +154,9 @@ impl SyntaxHighlighter {
             let mut spans = Vec::new();
 
             // if only comment is set, write comment at beginning of line
-            if parts.label.is_none() && parts.instruction.is_none() && parts.comment.is_some() {
-                let comment = parts.comment.unwrap();
+            if let (None, None, Some(comment)) = (&parts.label, &parts.instruction, &parts.comment)
+            {
+                let comment = comment.clone();
                 // if comment starts with '#' it will not be printed
                 if comment.starts_with('#') {
                     continue;
@@ -146,9 +183,10 @@ impl SyntaxHighlighter {
 
             // handle instruction
             if let Some(instruction) = parts.instruction {
-                let instruction = Instruction::try_from(instruction.as_str())?;
-                spans.append(&mut instruction.to_spans(self));
-                let len = Line::from(instruction.to_spans(self)).width();
+                let mut instruction_spans =
+                    self.instruction_spans(&instruction, definition_lines.get(&idx))?;
+                let len = Line::from(instruction_spans.clone()).width();
+                spans.append(&mut instruction_spans);
                 // fill spaces if enabled until next part is reached
                 if enable_alignment {
                     spans.push(fill_span(max_instruction_width - len + SPACING));
@@ -197,12 +235,42 @@ impl ToSpans for Instruction {
                 spans.append(&mut v2.to_spans(sh));
                 spans
             }
+            Self::CalcChain(t, v, operations) => {
+                let mut spans = t.to_spans(sh);
+                spans.push(sh.assignment_span());
+                spans.append(&mut v.to_spans(sh));
+                for (op, v) in operations {
+                    spans.push(Span::from(" "));
+                    spans.push(sh.op_span(op));
+                    spans.push(Span::from(" "));
+                    spans.append(&mut v.to_spans(sh));
+                }
+                spans
+            }
+            Self::AssignBuiltIn(t, built_in, args) => {
+                let mut spans = t.to_spans(sh);
+                spans.push(sh.assignment_span());
+                spans.push(sh.build_in_span(&format!("{built_in}(")));
+                for (idx, arg) in args.iter().enumerate() {
+                    if idx > 0 {
+                        spans.push(sh.build_in_span(", "));
+                    }
+                    spans.append(&mut arg.to_spans(sh));
+                }
+                spans.push(sh.build_in_span(")"));
+                spans
+            }
             Self::Call(label) => {
                 vec![sh.build_in_span("call"), sh.label_span(label)]
             }
             Self::Goto(label) => {
                 vec![sh.build_in_span("goto"), sh.label_span(label)]
             }
+            Self::ComputedGoto(v) => {
+                let mut spans = vec![sh.build_in_span("goto ")];
+                spans.append(&mut v.to_spans(sh));
+                spans
+            }
             Self::JumpIf(v, cmp, v2, label) => {
                 let mut spans = vec![Span::from("if ").style(sh.theme.build_in())];
                 spans.append(&mut v.to_spans(sh));
@@ -214,11 +282,74 @@ impl ToSpans for Instruction {
                 spans.push(sh.label_span(label));
                 spans
             }
+            Self::Assert(v, cmp, v2) => {
+                let mut spans = vec![Span::from("assert ").style(sh.theme.assertion())];
+                spans.append(&mut v.to_spans(sh));
+                spans.push(Span::from(" "));
+                spans.push(Span::from(format!("{cmp}")).style(sh.theme.cmp()));
+                spans.push(Span::from(" "));
+                spans.append(&mut v2.to_spans(sh));
+                spans
+            }
+            Self::Print(v) => {
+                let mut spans = vec![sh.build_in_span("print ")];
+                spans.append(&mut v.to_spans(sh));
+                spans
+            }
+            Self::JumpIfCompound(condition, label) => {
+                let mut spans = vec![Span::from("if ").style(sh.theme.build_in())];
+                spans.append(&mut condition.to_spans(sh));
+                spans.push(Span::from(" then goto").style(sh.theme.build_in()));
+                spans.push(sh.label_span(label));
+                spans
+            }
+            Self::JumpIfElse(condition, then_label, else_label) => {
+                let mut spans = vec![Span::from("if ").style(sh.theme.build_in())];
+                spans.append(&mut condition.to_spans(sh));
+                spans.push(Span::from(" then goto").style(sh.theme.build_in()));
+                spans.push(sh.label_span(then_label));
+                spans.push(Span::from(" else goto").style(sh.theme.build_in()));
+                spans.push(sh.label_span(else_label));
+                spans
+            }
+            Self::Macro(call) => {
+                let mut spans = vec![sh.build_in_span(&format!("{}(", call.name))];
+                for (idx, arg) in call.args.iter().enumerate() {
+                    if idx > 0 {
+                        spans.push(sh.build_in_span(", "));
+                    }
+                    spans.append(&mut arg.to_spans(sh));
+                }
+                spans.push(sh.build_in_span(")"));
+                spans
+            }
+            Self::Loop(line) => match &line.kind {
+                LoopKind::While(condition) => {
+                    let mut spans = vec![sh.build_in_span("while ")];
+                    spans.append(&mut condition.to_spans(sh));
+                    spans.push(sh.build_in_span(" do"));
+                    spans
+                }
+                LoopKind::Times(value) => {
+                    let mut spans = vec![sh.build_in_span("loop ")];
+                    spans.append(&mut value.to_spans(sh));
+                    spans.push(sh.build_in_span(" times"));
+                    spans
+                }
+                LoopKind::EndWhile => vec![sh.build_in_span("endwhile")],
+                LoopKind::EndLoop => vec![sh.build_in_span("endloop")],
+            },
             Self::Noop => vec![Span::from("")],
             Self::Pop => vec![sh.build_in_span("pop")],
             Self::Push => vec![sh.build_in_span("push")],
             Self::Return => vec![sh.build_in_span("return")],
             Self::StackOp(op) => vec![sh.build_in_span("stack"), sh.op_span(op)],
+            Self::StackCommand(command) => vec![sh.build_in_span(&format!("stack {command}"))],
+            Self::Peek(t) => {
+                let mut spans = vec![sh.build_in_span("peek ")];
+                spans.append(&mut t.to_spans(sh));
+                spans
+            }
         }
     }
 }
@@ -255,6 +386,32 @@ impl ToSpans for IndexMemoryCellIndexType {
     }
 }
 
+impl ToSpans for Condition {
+    fn to_spans(&self, sh: &SyntaxHighlighter) -> Vec<Span<'static>> {
+        match self {
+            Self::Compare(v, cmp, v2) => {
+                let mut spans = v.to_spans(sh);
+                spans.push(Span::from(" "));
+                spans.push(Span::from(format!("{cmp}")).style(sh.theme.cmp()));
+                spans.push(Span::from(" "));
+                spans.append(&mut v2.to_spans(sh));
+                spans
+            }
+            Self::And(a, b) | Self::Or(a, b) => {
+                let connective = if matches!(self, Self::And(_, _)) {
+                    " && "
+                } else {
+                    " || "
+                };
+                let mut spans = a.to_spans(sh);
+                spans.push(Span::from(connective).style(sh.theme.cmp()));
+                spans.append(&mut b.to_spans(sh));
+                spans
+            }
+        }
+    }
+}
+
 impl ToSpans for Value {
     fn to_spans(&self, sh: &SyntaxHighlighter) -> Vec<Span<'static>> {
         match self {
@@ -263,6 +420,12 @@ impl ToSpans for Value {
             Self::Gamma => vec![sh.gamma_span()],
             Self::MemoryCell(label) => sh.memory_cell_spans(label),
             Self::IndexMemoryCell(imcit) => sh.index_memory_cell_spanns(imcit),
+            Self::Steps => vec![sh.build_in_span("steps")],
+            Self::Negated(v) => {
+                let mut spans = vec![sh.op_span(&Operation::Sub)];
+                spans.append(&mut v.to_spans(sh));
+                spans
+            }
         }
     }
 }
@@ -275,9 +438,11 @@ impl ToSpans for Value {
 /// Returns max width of labels in first variant and max width of instructions
 /// in second variant. Label width includes the `:`.
 fn determine_alignment(instructions: &[String]) -> (usize, usize) {
+    let definition_lines = macro_definition_lines(instructions);
+    let sh = SyntaxHighlighter::new(&Rc::new(SyntaxHighlightingTheme::default()));
     let mut max_label_width = 0;
     let mut max_instruction_width = 0;
-    for instruction in instructions {
+    for (idx, instruction) in instructions.iter().enumerate() {
         // Remove comments
         let instruction = remove_comment(instruction);
 
@@ -299,11 +464,8 @@ fn determine_alignment(instructions: &[String]) -> (usize, usize) {
         }
 
         let mut instruction_width = 0;
-        if let Ok(instruction) = Instruction::try_from(parts.join(" ").as_str()) {
-            let line = Line::from(instruction.to_spans(&SyntaxHighlighter::new(&Rc::new(
-                SyntaxHighlightingTheme::default(),
-            ))));
-            instruction_width = line.width();
+        if let Ok(spans) = sh.instruction_spans(&parts.join(" "), definition_lines.get(&idx)) {
+            instruction_width = Line::from(spans).width();
         }
         if max_instruction_width < instruction_width {
             max_instruction_width = instruction_width;
@@ -312,6 +474,29 @@ fn determine_alignment(instructions: &[String]) -> (usize, usize) {
     (max_label_width, max_instruction_width)
 }
 
+/// Returns the indices of the lines that belong to the definition of a macro, with the parameters of the macro.
+fn macro_definition_lines(input: &[String]) -> HashMap<usize, Vec<String>> {
+    // invalid definitions are reported when the program is build
+    let Ok((_, lines)) = Macros::parse(input) else {
+        return HashMap::new();
+    };
+    let mut parameters = Vec::new();
+    let mut definition_lines = HashMap::new();
+    for (idx, line) in input.iter().enumerate() {
+        if !lines.contains(&idx) {
+            continue;
+        }
+        let line = remove_comment(line);
+        if let Some(header) = line.strip_prefix("macro ") {
+            parameters = parse_header(header.trim())
+                .map(|(_, parameters)| parameters)
+                .unwrap_or_default();
+        }
+        definition_lines.insert(idx, parameters.clone());
+    }
+    definition_lines
+}
+
 #[derive(Debug, PartialEq)]
 struct InputParts {
     /// Label of the input, does not contain ':'
@@ -410,11 +595,91 @@ fn string_into_span(string: String, style: Style) -> Span<'static> {
 #[cfg(test)]
 mod tests {
 
-    use crate::app::ui::{
-        style::SharedTheme,
-        syntax_highlighting::{determine_alignment, input_parts, InputParts, SyntaxHighlighter},
+    use ratatui::text::Span;
+
+    use crate::{
+        app::ui::{
+            style::SharedTheme,
+            syntax_highlighting::{
+                determine_alignment, input_parts, macro_definition_lines, InputParts,
+                SyntaxHighlighter,
+            },
+        },
+        utils::{read_file, test_utils::string_literal_to_vec},
     };
 
+    /// Directory of the fixture corpus, programs that cover all instruction forms and alignment cases.
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/input/formatting");
+
+    /// Returns the names and lines of all programs in the fixture corpus, sorted by name.
+    fn fixtures() -> Vec<(String, Vec<String>)> {
+        let mut fixtures = std::fs::read_dir(FIXTURES)
+            .unwrap()
+            .map(|f| f.unwrap().path())
+            .filter(|f| f.extension().is_some_and(|f| f == "alpha"))
+            .map(|f| {
+                (
+                    f.file_stem().unwrap().to_string_lossy().to_string(),
+                    read_file(f.to_str().unwrap()).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        fixtures.sort();
+        fixtures
+    }
+
+    /// Describes the text and style of a span, so that changes of the highlighting show up in the snapshots.
+    fn describe_span(span: &Span) -> String {
+        let mut description = format!("{:?}", span.content);
+        if let Some(fg) = span.style.fg {
+            description.push_str(&format!(" fg={fg}"));
+        }
+        if !span.style.add_modifier.is_empty() {
+            description.push_str(&format!(" {:?}", span.style.add_modifier));
+        }
+        description
+    }
+
+    #[test]
+    fn test_snapshot_input_to_lines() {
+        let sh = SyntaxHighlighter::new(&SharedTheme::default().syntax_highlighting_theme());
+        for (name, program) in fixtures() {
+            for alignment in [true, false] {
+                let lines = sh
+                    .input_to_lines(&program, alignment)
+                    .unwrap()
+                    .iter()
+                    .map(|f| f.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let suffix = if alignment { "aligned" } else { "unaligned" };
+                insta::assert_snapshot!(format!("{name}_{suffix}"), lines);
+            }
+        }
+    }
+
+    #[test]
+    fn test_snapshot_instruction_spans() {
+        let sh = SyntaxHighlighter::new(&SharedTheme::default().syntax_highlighting_theme());
+        for (name, program) in fixtures() {
+            let definition_lines = macro_definition_lines(&program);
+            let mut description = Vec::new();
+            for (idx, line) in program.into_iter().enumerate() {
+                let Some(instruction) = input_parts(line).and_then(|f| f.instruction) else {
+                    continue;
+                };
+                description.push(instruction.clone());
+                for span in sh
+                    .instruction_spans(&instruction, definition_lines.get(&idx))
+                    .unwrap()
+                {
+                    description.push(format!("    {}", describe_span(&span)));
+                }
+            }
+            insta::assert_snapshot!(format!("{name}_spans"), description.join("\n"));
+        }
+    }
+
     #[test]
     fn test_input_to_lines_alignment_enabled_input_single_alpha() {
         let input = vec![
@@ -507,14 +772,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_input_to_lines_macro() {
+        let input = string_literal_to_vec(
+            "macro swap(x, y) // exchanges the values\n    a := x\n    x := a1\nendmacro\nmain: swap(p(h1), a1)",
+        );
+        let res = SyntaxHighlighter::new(&SharedTheme::default().syntax_highlighting_theme())
+            .input_to_lines(&input, true)
+            .unwrap();
+        assert_eq!(
+            res.iter().map(|f| f.to_string()).collect::<Vec<String>>(),
+            vec![
+                "       macro swap(x, y)  // exchanges the values",
+                "       a := x",
+                "       x := a1",
+                "       endmacro",
+                "main:  swap(\u{03c1}(h1), \u{03b1}1)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_input_to_lines_assertion() {
+        let theme = SharedTheme::default().syntax_highlighting_theme();
+        let res = SyntaxHighlighter::new(&theme)
+            .input_to_lines(&["assert a >= p(h1)".to_string()], false)
+            .unwrap();
+        assert_eq!(res[0].to_string(), "assert \u{03b1}0 >= \u{03c1}(h1)");
+        assert_eq!(res[0].spans[0].content, "assert ");
+        assert_eq!(res[0].spans[0].style, theme.assertion());
+        assert_ne!(theme.assertion(), theme.build_in());
+    }
+
     #[test]
     fn test_determine_alignment() {
         assert_eq!(
-            determine_alignment(&vec!["test_label: a := 20 // comment".to_string()]),
+            determine_alignment(&["test_label: a := 20 // comment".to_string()]),
             (11, 8)
         );
         assert_eq!(
-            determine_alignment(&vec![
+            determine_alignment(&[
                 "test_label: a := 20 // comment".to_string(),
                 "main: if a == p(h2) then goto test_label // comment".to_string()
             ]),
@@ -0,0 +1,98 @@
+use super::{content::BreakpointState, memory_edit::MemoryLocation};
+
+/// Snapshot of the part of the app state that is changed by an undoable action.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Snapshot {
+    /// States of all breakpoints.
+    Breakpoints(Vec<BreakpointState>),
+    /// Value of a memory location that was edited in the memory editor.
+    Memory(MemoryLocation, Option<i32>),
+}
+
+/// Stores snapshots of the app state that can be restored with undo and redo.
+///
+/// A snapshot has to be recorded before each action that should be undoable, execution of instructions is not undoable.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct UndoHistory<T: Clone> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+}
+
+impl<T: Clone> UndoHistory<T> {
+    pub fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Records the snapshot `before`, it is restored when the action that is performed next is undone.
+    ///
+    /// Clears all actions that could be redone.
+    pub fn record(&mut self, before: T) {
+        self.undo.push(before);
+        self.redo.clear();
+    }
+
+    /// Returns the snapshot that is restored by the next undo, used to take the matching current snapshot.
+    pub fn next_undo(&self) -> Option<&T> {
+        self.undo.last()
+    }
+
+    /// Returns the snapshot that is restored by the next redo, used to take the matching current snapshot.
+    pub fn next_redo(&self) -> Option<&T> {
+        self.redo.last()
+    }
+
+    /// Undoes the last action.
+    ///
+    /// `current` is the current snapshot, it is restored when the action is redone.
+    ///
+    /// Returns the snapshot that should be restored or `None` if there is nothing to undo.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let snapshot = self.undo.pop()?;
+        self.redo.push(current);
+        Some(snapshot)
+    }
+
+    /// Redoes the last action that was undone.
+    ///
+    /// `current` is the current snapshot, it is restored when the action is undone again.
+    ///
+    /// Returns the snapshot that should be restored or `None` if there is nothing to redo.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let snapshot = self.redo.pop()?;
+        self.undo.push(current);
+        Some(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UndoHistory;
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = UndoHistory::new();
+        assert_eq!(history.undo(0), None);
+        history.record(0);
+        history.record(1);
+        assert_eq!(history.next_undo(), Some(&1));
+        assert_eq!(history.next_redo(), None);
+        assert_eq!(history.undo(2), Some(1));
+        assert_eq!(history.undo(1), Some(0));
+        assert_eq!(history.undo(0), None);
+        assert_eq!(history.redo(0), Some(1));
+        assert_eq!(history.redo(1), Some(2));
+        assert_eq!(history.redo(2), None);
+    }
+
+    #[test]
+    fn test_record_clears_redo() {
+        let mut history = UndoHistory::new();
+        history.record(0);
+        assert_eq!(history.undo(1), Some(0));
+        history.record(0);
+        assert_eq!(history.redo(5), None);
+    }
+}
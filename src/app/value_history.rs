@@ -0,0 +1,146 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::runtime::RuntimeMemory;
+
+use super::{content::ValueFormat, memory_edit::MemoryLocation};
+
+/// Number of changes that are kept in the history, older changes are dropped.
+const HISTORY_SIZE: usize = 100;
+
+/// Change of the value of a memory location by a step of the program or by a manual edit.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValueChange {
+    /// Number of the step that changed the value, see [`ControlFlow::steps`](crate::runtime::ControlFlow::steps).
+    ///
+    /// `None` if the value was changed manually, with the memory editor or a custom instruction.
+    pub step: Option<usize>,
+    pub location: MemoryLocation,
+    pub old: Option<i32>,
+    pub new: Option<i32>,
+}
+
+/// Last changes of the accumulators and memory cells, displayed in the value history panel.
+///
+/// Helps to trace back which step wrote a wrong value.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ValueHistory {
+    changes: VecDeque<ValueChange>,
+}
+
+impl ValueHistory {
+    /// Records the values that were changed by step `step`, `before` and `after` are the memory before and after
+    /// the step was run. Manual changes are recorded with `step` set to `None`.
+    pub fn record(&mut self, step: Option<usize>, before: &RuntimeMemory, after: &RuntimeMemory) {
+        for location in MemoryLocation::all(after) {
+            let (old, new) = (location.value(before), location.value(after));
+            if old != new {
+                self.changes.push_back(ValueChange {
+                    step,
+                    location,
+                    old,
+                    new,
+                });
+            }
+        }
+        while self.changes.len() > HISTORY_SIZE {
+            self.changes.pop_front();
+        }
+    }
+
+    /// Removes all changes, called when the program is reset.
+    pub fn clear(&mut self) {
+        self.changes.clear();
+    }
+
+    /// Returns the changes formatted as `step 42: h1 3 → 7`, the last change first. Manual changes are formatted
+    /// as `edit: h1 3 → 7`.
+    ///
+    /// The alias of a memory location is used as name, if one is set, values are displayed in `value_format`.
+    pub fn lines(
+        &self,
+        aliases: &HashMap<String, String>,
+        value_format: ValueFormat,
+    ) -> Vec<String> {
+        let value =
+            |value: Option<i32>| value.map_or("None".to_string(), |f| value_format.format(f));
+        self.changes
+            .iter()
+            .rev()
+            .map(|change| {
+                let location = change.location.to_string();
+                let step = change
+                    .step
+                    .map_or("edit".to_string(), |step| format!("step {step}"));
+                format!(
+                    "{}: {} {} → {}",
+                    step,
+                    aliases.get(&location).unwrap_or(&location),
+                    value(change.old),
+                    value(change.new)
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{app::content::ValueFormat, utils::test_utils::runtime_from_str};
+
+    use super::{MemoryLocation, ValueHistory, HISTORY_SIZE};
+
+    #[test]
+    fn test_value_history() {
+        let mut rt = runtime_from_str("a := 3\np(h1) := a\na := 7\np(h1) := a\na := 7").unwrap();
+        let mut history = ValueHistory::default();
+        while !rt.finished() {
+            let before = rt.runtime_memory().clone();
+            rt.step().unwrap();
+            history.record(Some(rt.control_flow().steps), &before, rt.runtime_memory());
+        }
+        assert_eq!(
+            history.lines(&HashMap::new(), ValueFormat::Decimal),
+            [
+                "step 4: h1 3 → 7",
+                "step 3: α0 3 → 7",
+                "step 2: h1 None → 3",
+                "step 1: α0 None → 3",
+            ]
+        );
+        // manual changes are not associated with a step
+        let before = rt.runtime_memory().clone();
+        let mut after = before.clone();
+        MemoryLocation::Accumulator(0).set_value(&mut after, Some(-1));
+        history.record(None, &before, &after);
+        assert_eq!(
+            history.lines(&HashMap::new(), ValueFormat::Decimal)[0],
+            "edit: α0 7 → -1"
+        );
+        let aliases = HashMap::from([("h1".to_string(), "sum".to_string())]);
+        assert_eq!(
+            history.lines(&aliases, ValueFormat::Hexadecimal)[1],
+            "step 4: sum 0x3 → 0x7"
+        );
+        history.clear();
+        assert!(history
+            .lines(&HashMap::new(), ValueFormat::Decimal)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_value_history_size() {
+        let mut rt = runtime_from_str("a := 0\nloop: a := a + 1\ngoto loop").unwrap();
+        let mut history = ValueHistory::default();
+        // every second step changes the accumulator
+        for _ in 0..301 {
+            let before = rt.runtime_memory().clone();
+            rt.step().unwrap();
+            history.record(Some(rt.control_flow().steps), &before, rt.runtime_memory());
+        }
+        let lines = history.lines(&HashMap::new(), ValueFormat::Decimal);
+        assert_eq!(lines.len(), HISTORY_SIZE);
+        assert_eq!(lines[0], "step 300: α0 149 → 150");
+    }
+}
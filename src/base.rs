@@ -93,6 +93,18 @@ impl Comparison {
             Self::Gt => x > y,
         }
     }
+
+    /// Returns the comparison that is true exactly when this comparison is false, e.g. `>=` for `<`.
+    pub fn negated(self) -> Self {
+        match self {
+            Self::Lt => Self::Ge,
+            Self::Le => Self::Gt,
+            Self::Eq => Self::Neq,
+            Self::Neq => Self::Eq,
+            Self::Ge => Self::Lt,
+            Self::Gt => Self::Le,
+        }
+    }
 }
 
 impl TryFrom<&str> for Comparison {
@@ -103,7 +115,7 @@ impl TryFrom<&str> for Comparison {
             "<" => Ok(Self::Lt),
             "<=" | "=<" | "≤" => Ok(Self::Le),
             "=" | "==" => Ok(Self::Eq),
-            "!=" | "≠" => Ok(Self::Neq),
+            "!=" | "≠" | "<>" => Ok(Self::Neq),
             ">=" | "=>" | "≥" => Ok(Self::Ge),
             ">" => Ok(Self::Gt),
             _ => Err(()),
@@ -175,6 +187,18 @@ pub enum Operation {
     Mul,
     Div,
     Mod,
+    /// Bitwise and
+    And,
+    /// Bitwise or
+    Or,
+    /// Bitwise exclusive or
+    Xor,
+    /// Shift to the left
+    Shl,
+    /// Arithmetic shift to the right, the sign is kept
+    Shr,
+    /// Integer exponentiation, written as `**` because `^` is the exclusive or
+    Pow,
 }
 
 impl Operation {
@@ -238,6 +262,71 @@ impl Operation {
                     }
                 }
             }
+            Self::And => Ok(x & y),
+            Self::Or => Ok(x | y),
+            Self::Xor => Ok(x ^ y),
+            // shifting by a negative value or by 32 bits or more is not defined,
+            // bits that are shifted out of the value on the left are an overflow
+            Self::Shl => match u32::try_from(y)
+                .ok()
+                .and_then(|y| x.checked_shl(y))
+                .filter(|v| v >> y == x)
+            {
+                Some(v) => Ok(v),
+                None => Err(RuntimeErrorType::IllegalCalculation {
+                    cause: CalcError::AttemptToOverflow(
+                        "shift left".to_string(),
+                        "Shift to the left".to_string(),
+                    ),
+                }),
+            },
+            Self::Shr => match u32::try_from(y).ok().and_then(|y| x.checked_shr(y)) {
+                Some(v) => Ok(v),
+                None => Err(RuntimeErrorType::IllegalCalculation {
+                    cause: CalcError::AttemptToOverflow(
+                        "shift right".to_string(),
+                        "Shift to the right".to_string(),
+                    ),
+                }),
+            },
+            // the result of a negative exponent is a fraction, that can not be stored
+            Self::Pow => match u32::try_from(y) {
+                Ok(y) => match x.checked_pow(y) {
+                    Some(v) => Ok(v),
+                    None => Err(RuntimeErrorType::IllegalCalculation {
+                        cause: CalcError::AttemptToOverflow(
+                            "raise to the power".to_string(),
+                            "Exponentiation".to_string(),
+                        ),
+                    }),
+                },
+                Err(_) => Err(RuntimeErrorType::IllegalCalculation {
+                    cause: CalcError::AttemptToUseNegativeExponent(),
+                }),
+            },
+        }
+    }
+
+    /// Returns true if the operation works on the bits of the values, these operations are not part of the official
+    /// alpha notation.
+    pub fn is_bitwise(self) -> bool {
+        matches!(
+            self,
+            Self::And | Self::Or | Self::Xor | Self::Shl | Self::Shr
+        )
+    }
+
+    /// Returns how strong the operation binds in a calculation with more than two operands, operations with a higher
+    /// precedence are evaluated first. The order is the same as in C, `**` binds the strongest.
+    pub fn precedence(self) -> u8 {
+        match self {
+            Self::Pow => 6,
+            Self::Mul | Self::Div | Self::Mod => 5,
+            Self::Add | Self::Sub => 4,
+            Self::Shl | Self::Shr => 3,
+            Self::And => 2,
+            Self::Xor => 1,
+            Self::Or => 0,
         }
     }
 }
@@ -250,6 +339,12 @@ impl Display for Operation {
             Self::Mul => write!(f, "*"),
             Self::Div => write!(f, "/"),
             Self::Mod => write!(f, "%"),
+            Self::And => write!(f, "&"),
+            Self::Or => write!(f, "|"),
+            Self::Xor => write!(f, "^"),
+            Self::Shl => write!(f, "<<"),
+            Self::Shr => write!(f, ">>"),
+            Self::Pow => write!(f, "**"),
         }
     }
 }
@@ -270,6 +365,12 @@ impl TryFrom<&str> for Operation {
             "*" | "×" => Ok(Operation::Mul),
             "/" | "÷" => Ok(Operation::Div),
             "%" => Ok(Operation::Mod),
+            "&" => Ok(Operation::And),
+            "|" => Ok(Operation::Or),
+            "^" => Ok(Operation::Xor),
+            "<<" => Ok(Operation::Shl),
+            ">>" => Ok(Operation::Shr),
+            "**" => Ok(Operation::Pow),
             _ => Err(()),
         }
     }
@@ -283,6 +384,12 @@ impl ValueEnum for Operation {
             Operation::Mul,
             Operation::Div,
             Operation::Mod,
+            Operation::And,
+            Operation::Or,
+            Operation::Xor,
+            Operation::Shl,
+            Operation::Shr,
+            Operation::Pow,
         ]
     }
 
@@ -293,6 +400,12 @@ impl ValueEnum for Operation {
             Self::Mul => Some(PossibleValue::new("mul")),
             Self::Div => Some(PossibleValue::new("div")),
             Self::Mod => Some(PossibleValue::new("mod")),
+            Self::And => Some(PossibleValue::new("and")),
+            Self::Or => Some(PossibleValue::new("or")),
+            Self::Xor => Some(PossibleValue::new("xor")),
+            Self::Shl => Some(PossibleValue::new("shl")),
+            Self::Shr => Some(PossibleValue::new("shr")),
+            Self::Pow => Some(PossibleValue::new("pow")),
         }
     }
 }
@@ -305,12 +418,20 @@ impl CliHint for Operation {
             Self::Mul => String::from("mul"),
             Self::Div => String::from("div"),
             Self::Mod => String::from("mod"),
+            Self::And => String::from("and"),
+            Self::Or => String::from("or"),
+            Self::Xor => String::from("xor"),
+            Self::Shl => String::from("shl"),
+            Self::Shr => String::from("shr"),
+            Self::Pow => String::from("pow"),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use clap::ValueEnum;
+
     use crate::{
         base::{Comparison, MemoryCell, Operation},
         cli::CliHint,
@@ -349,6 +470,16 @@ mod tests {
         assert!(Comparison::Gt.cmp(10, 5));
     }
 
+    #[test]
+    fn test_comparison_negated() {
+        for cmp in Comparison::value_variants() {
+            assert_eq!(cmp.negated().negated(), *cmp);
+            for (x, y) in [(4, 5), (5, 5), (6, 5)] {
+                assert_eq!(cmp.negated().cmp(x, y), !cmp.cmp(x, y), "{cmp}");
+            }
+        }
+    }
+
     #[test]
     fn test_comparison_try_from_str() {
         assert_eq!(Comparison::try_from("<"), Ok(Comparison::Lt));
@@ -359,6 +490,7 @@ mod tests {
         assert_eq!(Comparison::try_from("=="), Ok(Comparison::Eq));
         assert_eq!(Comparison::try_from("!="), Ok(Comparison::Neq));
         assert_eq!(Comparison::try_from("≠"), Ok(Comparison::Neq));
+        assert_eq!(Comparison::try_from("<>"), Ok(Comparison::Neq));
         assert_eq!(Comparison::try_from(">="), Ok(Comparison::Ge));
         assert_eq!(Comparison::try_from("=>"), Ok(Comparison::Ge));
         assert_eq!(Comparison::try_from("≥"), Ok(Comparison::Ge));
@@ -394,6 +526,34 @@ mod tests {
         assert_eq!(Operation::Mod.calc(20, 5).unwrap(), 0)
     }
 
+    #[test]
+    fn test_bitwise_operation_calc() {
+        assert_eq!(Operation::And.calc(0b1100, 0b1010).unwrap(), 0b1000);
+        assert_eq!(Operation::Or.calc(0b1100, 0b1010).unwrap(), 0b1110);
+        assert_eq!(Operation::Xor.calc(0b1100, 0b1010).unwrap(), 0b0110);
+        assert_eq!(Operation::And.calc(-1, 5).unwrap(), 5);
+        assert_eq!(Operation::Shl.calc(3, 2).unwrap(), 12);
+        assert_eq!(Operation::Shl.calc(-1, 31).unwrap(), i32::MIN);
+        assert_eq!(Operation::Shr.calc(12, 2).unwrap(), 3);
+        assert_eq!(Operation::Shr.calc(-8, 1).unwrap(), -4);
+        assert!(Operation::Shl.calc(1, 31).is_err());
+        assert!(Operation::Shl.calc(1, 32).is_err());
+        assert!(Operation::Shl.calc(1, -1).is_err());
+        assert!(Operation::Shr.calc(1, 32).is_err());
+        assert!(Operation::Shr.calc(1, -1).is_err());
+    }
+
+    #[test]
+    fn test_pow_operation_calc() {
+        assert_eq!(Operation::Pow.calc(3, 4).unwrap(), 81);
+        assert_eq!(Operation::Pow.calc(-2, 3).unwrap(), -8);
+        assert_eq!(Operation::Pow.calc(0, 0).unwrap(), 1);
+        assert_eq!(Operation::Pow.calc(2, 30).unwrap(), 1 << 30);
+        assert!(Operation::Pow.calc(2, 31).is_err());
+        assert_eq!(Operation::Pow.calc(-2, 31).unwrap(), i32::MIN);
+        assert!(Operation::Pow.calc(2, -1).is_err());
+    }
+
     #[test]
     fn test_operation_try_from_str() {
         assert_eq!(Operation::try_from("+"), Ok(Operation::Add));
@@ -403,6 +563,12 @@ mod tests {
         assert_eq!(Operation::try_from("/"), Ok(Operation::Div));
         assert_eq!(Operation::try_from("÷"), Ok(Operation::Div));
         assert_eq!(Operation::try_from("%"), Ok(Operation::Mod));
+        assert_eq!(Operation::try_from("&"), Ok(Operation::And));
+        assert_eq!(Operation::try_from("|"), Ok(Operation::Or));
+        assert_eq!(Operation::try_from("^"), Ok(Operation::Xor));
+        assert_eq!(Operation::try_from("<<"), Ok(Operation::Shl));
+        assert_eq!(Operation::try_from(">>"), Ok(Operation::Shr));
+        assert_eq!(Operation::try_from("**"), Ok(Operation::Pow));
         assert_eq!(Operation::try_from("P"), Err(()));
     }
 
@@ -413,6 +579,7 @@ mod tests {
         assert_eq!(format!("{}", Operation::Mul), "*".to_string());
         assert_eq!(format!("{}", Operation::Div), "/".to_string());
         assert_eq!(format!("{}", Operation::Mod), "%".to_string());
+        assert_eq!(format!("{}", Operation::Shl), "<<".to_string());
     }
 
     #[test]
@@ -422,5 +589,6 @@ mod tests {
         assert_eq!(Operation::Mul.cli_hint(), "mul".to_string());
         assert_eq!(Operation::Div.cli_hint(), "div".to_string());
         assert_eq!(Operation::Mod.cli_hint(), "mod".to_string());
+        assert_eq!(Operation::Xor.cli_hint(), "xor".to_string());
     }
 }
@@ -0,0 +1,111 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::instructions::spec::Spec;
+
+/// Information about the build of this binary, printed by `--version-json`.
+///
+/// Can be attached to bug reports or used by grading infrastructure to pin the exact interpreter behavior.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    /// Hash of the commit the binary was build from, `None` if it was not build from a git repository.
+    pub git_hash: Option<&'static str>,
+    /// Date of the build, formatted as `YYYY-MM-DD`, taken from `SOURCE_DATE_EPOCH`, `None` if it was not set.
+    pub build_date: Option<&'static str>,
+    /// Target triple the binary was build for.
+    pub target: &'static str,
+    /// Cargo features that were enabled.
+    pub features: Vec<&'static str>,
+    /// Versions of the spec that can be selected with `--spec`.
+    pub spec_versions: Vec<String>,
+}
+
+impl BuildInfo {
+    /// Returns the information about the build of this binary.
+    pub fn current() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_hash: option_env!("ALPHA_TUI_GIT_HASH"),
+            build_date: option_env!("ALPHA_TUI_BUILD_DATE"),
+            target: env!("ALPHA_TUI_TARGET"),
+            features: env!("ALPHA_TUI_FEATURES")
+                .split(',')
+                .filter(|f| !f.is_empty())
+                .collect(),
+            spec_versions: Spec::value_variants()
+                .iter()
+                .filter_map(|f| f.to_possible_value())
+                .map(|f| f.get_name().to_string())
+                .collect(),
+        }
+    }
+
+    /// Returns the information formatted as json.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("build info should be serializable")
+    }
+
+    /// Returns the information formatted as one line per value, printed by `--version --verbose`.
+    pub fn to_text(&self) -> String {
+        let list = |values: &[&str]| {
+            if values.is_empty() {
+                "none".to_string()
+            } else {
+                values.join(", ")
+            }
+        };
+        let spec_versions = self
+            .spec_versions
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        format!(
+            "alpha_tui {}\ngit hash: {}\nbuild date: {}\ntarget: {}\nfeatures: {}\nspec versions: {}",
+            self.version,
+            self.git_hash.unwrap_or("unknown"),
+            self.build_date.unwrap_or("unknown"),
+            self.target,
+            list(&self.features),
+            list(&spec_versions)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildInfo;
+
+    #[test]
+    fn test_build_info_json() {
+        let info = BuildInfo::current();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        if let Some(build_date) = info.build_date {
+            assert_eq!(build_date.len(), 10);
+        }
+        let value: serde_json::Value = serde_json::from_str(&info.to_json()).unwrap();
+        assert_eq!(
+            value["spec_versions"],
+            serde_json::json!(["2022", "2023", "2024"])
+        );
+        assert!(value["features"].is_array());
+        assert!(value.get("git_hash").is_some());
+        assert!(value.get("build_date").is_some());
+    }
+
+    #[test]
+    fn test_build_info_text() {
+        let info = BuildInfo {
+            version: "1.8.0",
+            git_hash: None,
+            build_date: Some("2026-10-16"),
+            target: "x86_64-unknown-linux-gnu",
+            features: vec!["test-utils"],
+            spec_versions: vec!["2022".to_string(), "2023".to_string()],
+        };
+        assert_eq!(
+            info.to_text(),
+            "alpha_tui 1.8.0\ngit hash: unknown\nbuild date: 2026-10-16\ntarget: x86_64-unknown-linux-gnu\nfeatures: test-utils\nspec versions: 2022, 2023"
+        );
+    }
+}
@@ -3,21 +3,54 @@ use miette::{Diagnostic, Result};
 use thiserror::Error;
 
 use crate::{
-    app::ui::style::BuildInTheme,
+    app::{
+        ui::style::BuildInTheme, DEFAULT_AUTO_RUN_SPEED, DEFAULT_PLAYGROUND_FILE,
+        DEFAULT_PRESENT_DELAY, DEFAULT_TICK_RATE,
+    },
     base::{Comparison, Operation},
-    runtime::memory_config::MemoryConfig,
+    instructions::spec::Spec,
+    report::parse_report_url,
+    runtime::{memory_config::MemoryConfig, state_dump::StateFormat},
 };
 
 #[derive(Parser, Debug)]
 #[command(
     author = "LMH01",
     version,
+    disable_version_flag = true,
     about,
-    long_about = "debugger and runtime environment for the alpha notation used in my Systemnahe Informatik lecture"
+    long_about = "debugger and runtime environment for the alpha notation used in my Systemnahe Informatik lecture",
+    arg_required_else_help = true
 )]
 pub struct Cli {
+    /// Only `None` if `--version` or `--version-json` is set.
     #[command(subcommand)]
-    pub command: Command,
+    pub command: Option<Command>,
+
+    #[arg(
+        long,
+        short = 'V',
+        help = "Print version",
+        long_help = "Print the version, together with \"--verbose\" the build information is printed as well.",
+        conflicts_with = "version_json"
+    )]
+    pub version: bool,
+
+    #[arg(
+        long,
+        help = "Print the build information together with the version",
+        long_help = "Print the git hash, the build date, the target, the enabled features and the supported spec versions together with the version.",
+        requires = "version"
+    )]
+    pub verbose: bool,
+
+    #[arg(
+        long,
+        help = "Print version and build information as json",
+        long_help = "Print the version, the git hash, the build date, the target, the enabled features and the supported spec versions as json.\nCan be attached to bug reports or used to pin the exact version of the interpreter.",
+        exclusive = true
+    )]
+    pub version_json: bool,
 
     #[command(flatten)]
     pub global_args: GlobalArgs,
@@ -27,8 +60,8 @@ pub struct Cli {
 pub struct GlobalArgs {
     #[arg(
         long,
-        help = "Load memory config from a json file",
-        long_help = "Load accumulators, gamma accumulator, memory cells and index memory cells from a json file.\nThe memory config file might may include initial values alongside definitions.\n\nFurther help can be found here: https://github.com/LMH01/alpha_tui/blob/master/docs/cli.md.",
+        help = "Load memory config from a json or toml file",
+        long_help = "Load accumulators, gamma accumulator, memory cells and index memory cells from a json or toml file, files with the extension \".toml\" are read as toml.\nThe memory config file might may include initial values alongside definitions.\n\nFurther help can be found here: https://github.com/LMH01/alpha_tui/blob/master/docs/cli.md.",
         //conflicts_with_all = [ "memory_cells", "index_memory_cells", "accumulators" ],
         global = true,
         display_order = 24
@@ -37,6 +70,24 @@ pub struct GlobalArgs {
 
     #[arg(long, hide = true, global = true)]
     pub disable_instruction_limit: bool,
+
+    #[arg(
+        long,
+        help = "File in which completed exercises are recorded",
+        long_help = "File in which the exercises are recorded, that were completed with \"check run\".\nIf not set, the file progress.json in the config directory is used (~/.config/alpha_tui on linux or %APPDATA%/alpha_tui on windows).",
+        global = true,
+        display_order = 25
+    )]
+    pub progress_file: Option<String>,
+
+    #[arg(
+        long,
+        help = "Seed of the random numbers returned by rand",
+        long_help = "Seed of the random numbers that are returned by the built-in function rand, e.g. 'a := rand(1, 6)'.\nRuns with the same seed generate the same numbers. If not set, the current time is used as seed.",
+        global = true,
+        display_order = 26
+    )]
+    pub seed: Option<u64>,
 }
 
 #[derive(Args, Clone, Debug)]
@@ -48,10 +99,9 @@ pub struct LoadArgs {
     pub load_playground_args: LoadPlaygroundArgs,
 
     #[arg(
-        long_help = "Specify the input file that contains the program",
-        required = true
+        long_help = "Specify the input file that contains the program.\nIf not set, the program is read from the project manifest alpha.toml in the working directory."
     )]
-    pub file: String,
+    pub file: Option<String>,
 
     #[arg(
         short,
@@ -93,6 +143,325 @@ pub struct LoadArgs {
         display_order = 31
     )]
     pub custom_instruction_history_file: Option<String>,
+
+    #[arg(
+        long,
+        help = "Restore and save the shown panels together with the breakpoints",
+        long_help = "Restore the shown panels from the session file FILE.session.json, if it exists, and write them to that file when the program is closed.\nBreakpoints are restored from and saved to the session file even if this flag is not set.",
+        display_order = 34
+    )]
+    pub session: bool,
+
+    #[arg(
+        long,
+        help = "Record all actions and write a repro file",
+        long_help = "Record all actions and write a repro file to the provided path when the program is closed.\nThe repro file contains the program, the memory configuration and all keys that were pressed. It can be replayed with the \"repro\" command, to reproduce the session exactly.",
+        display_order = 35
+    )]
+    pub record_repro: Option<String>,
+
+    #[arg(
+        long,
+        help = "Use a linear, line oriented interface instead of the tui",
+        long_help = "Use a linear, line oriented interface instead of the tui.\nCommands are entered line by line and the state of the program is printed as text on demand, this makes alpha_tui usable with screen readers and braille displays.\nType \"help\" to list all available commands.",
+        conflicts_with_all = ["record_repro", "profile_ui", "session"],
+        display_order = 36
+    )]
+    pub plain_ui: bool,
+
+    #[arg(
+        long,
+        value_name = "SOCKET",
+        help = "Share this session over a local socket",
+        long_help = "Share this session read-only over a local socket that is created at the provided path.\nOther instances of alpha_tui can attach to the session with \"--attach SOCKET\" and watch it, press [g] to grant or revoke control of stepping and breakpoints.\nOnly supported on unix systems.",
+        conflicts_with = "plain_ui",
+        display_order = 37
+    )]
+    pub share: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write the log of the shared session to this file",
+        long_help = "Write the log of the shared session to this file, it contains all actions of the host and of the viewers and when control was granted or revoked.\nDefaults to the path of the socket with the extension \".log\".",
+        requires = "share",
+        display_order = 38
+    )]
+    pub share_log: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "SOCKET",
+        help = "Watch a session that is shared with --share",
+        long_help = "Watch a session that is shared with \"--share SOCKET\", read-only.\nThe tui mirrors every action of the shared session, it can only be closed with [q] or [ESC].\nWhen the host grants control, stepping and breakpoints can be controlled.\nNo program file is required, the program is received from the shared session.\nOnly supported on unix systems.",
+        conflicts_with_all = ["file", "share", "plain_ui", "record_repro", "session"],
+        display_order = 39
+    )]
+    pub attach: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "MS",
+        help = "Minimum time between two frames in milliseconds",
+        long_help = "Minimum time between two frames in milliseconds, the ui is only redrawn when something changed.\nKeys of viewers of a shared session are checked in the same interval.\nIncrease this value to reduce the redraw frequency on slow remote terminals, the speed of the auto-run is not affected.",
+        default_value_t = DEFAULT_TICK_RATE,
+        value_parser = clap::value_parser!(u64).range(1..),
+        display_order = 40
+    )]
+    pub tick_rate: u64,
+
+    #[arg(
+        long,
+        value_name = "STEPS",
+        help = "Number of steps per second in auto-run mode",
+        long_help = "Number of instructions that are run per second in auto-run mode, that is started and paused with [p] while the program is running.\nIf more steps are due than frames are drawn, multiple steps are run between two frames.",
+        default_value_t = DEFAULT_AUTO_RUN_SPEED,
+        value_parser = clap::value_parser!(u32).range(1..),
+        display_order = 41
+    )]
+    pub auto_run_speed: u32,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write the line profile to this file",
+        long_help = "Write the line profile, that is collected when \"--profile-ui\" is set, as csv to this file when the program is closed.\nThe file contains for each line how often it was run and the time that was spent running it in auto-run mode in milliseconds.",
+        requires = "profile_ui",
+        display_order = 42
+    )]
+    pub profile_export: Option<String>,
+
+    #[arg(
+        long,
+        help = "Start in presentation mode",
+        long_help = "Start in presentation mode, to run a program slowly in front of an audience, e.g. on a projector.\nWhen the program is started with [s], the auto-run starts automatically and runs one step every \"--present-delay\" milliseconds. The current instruction is highlighted more prominently and the keybinding hints are hidden while the auto-run is active, press [p] to pause the auto-run and show them again.",
+        conflicts_with_all = ["attach", "plain_ui"],
+        display_order = 43
+    )]
+    pub present: bool,
+
+    #[arg(
+        long,
+        value_name = "MS",
+        help = "Delay between two steps in presentation mode in milliseconds",
+        long_help = "Delay between two steps of the auto-run in presentation mode in milliseconds, replaces \"--auto-run-speed\".\nEach step is shown for at least this time, steps are not caught up when a frame is drawn late.",
+        default_value_t = DEFAULT_PRESENT_DELAY,
+        value_parser = clap::value_parser!(u64).range(1..),
+        requires = "present",
+        display_order = 44
+    )]
+    pub present_delay: u64,
+
+    #[arg(
+        long,
+        value_name = "ADDR",
+        help = "Let external tools control the debugger over tcp",
+        long_help = "Listen for commands at the provided tcp address, e.g. \"127.0.0.1:4000\", while the tui runs, so that external tools or demo scripts can drive the debugger.\nEach request is a json object on its own line, e.g. {\"command\": \"step\"}, the commands are \"step\", \"run\", \"reset\", \"read_memory\" and \"write_memory\".\nEach request is answered with a json object on its own line that contains the status of the program and its memory.\nOnly loopback addresses are accepted, because the commands are not authenticated, see \"--allow-remote-control\".",
+        conflicts_with_all = ["attach", "plain_ui"],
+        display_order = 45
+    )]
+    pub control_socket: Option<String>,
+
+    #[arg(
+        long,
+        help = "Accept addresses of the remote control that are reachable from other machines",
+        long_help = "Accept addresses of the remote control that are reachable from other machines, e.g. \"0.0.0.0:4000\".\nThe commands are not authenticated, everyone who can reach the address can control the debugger and write the memory.",
+        requires = "control_socket",
+        display_order = 46
+    )]
+    pub allow_remote_control: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ReproArgs {
+    #[arg(
+        long_help = "Specify the repro file that should be replayed",
+        required = true
+    )]
+    pub file: String,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct FormatArgs {
+    #[arg(
+        long_help = "Specify the file that contains the program that should be formatted",
+        required = true
+    )]
+    pub file: String,
+
+    #[arg(
+        long,
+        help = "Print the formatted program instead of writing it to the file",
+        long_help = "Print the formatted program to stdout instead of writing it back to the file.\nExits with status 1 if the file is not formatted."
+    )]
+    pub check: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct LintArgs {
+    #[arg(
+        long_help = "Specify the file that contains the program that should be linted",
+        required = true
+    )]
+    pub file: String,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ProgressArgs {
+    #[arg(
+        long,
+        help = "Forget all completed exercises",
+        long_help = "Forget all completed exercises, the progress file is removed."
+    )]
+    pub reset: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct StateArgs {
+    #[command(subcommand)]
+    pub command: StateCommand,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum StateCommand {
+    #[command(
+        about = "Compare two states written with \"--dump-state\"",
+        long_about = "Compare two states written with \"--dump-state\" and print the memory locations that have different values.\nReturn values:\n\n 0 - States are equal\n 1 - States are different\n10 - IO error"
+    )]
+    Diff(StateDiffArgs),
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct StateDiffArgs {
+    #[arg(long_help = "State that is compared")]
+    pub left: String,
+
+    #[arg(long_help = "State that left is compared with")]
+    pub right: String,
+
+    #[arg(
+        long,
+        help = "Print the differences as json",
+        long_help = "Print the differences as a json array of objects with the fields \"location\", \"left\" and \"right\", instead of one line per difference."
+    )]
+    pub json: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct SessionArgs {
+    #[command(subcommand)]
+    pub command: SessionCommand,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum SessionCommand {
+    #[command(
+        about = "Export the session of a program into a portable file",
+        long_about = "Export the session of a program, that was stored with \"load --session\", into a portable file.\nBreakpoints are stored together with the instruction of their line, so that they can be placed in programs in which lines were added or removed.\nReturn values:\n\n 0 - Session exported\n10 - IO error or no session stored for the program"
+    )]
+    Export(SessionExportArgs),
+
+    #[command(
+        about = "Import a session that was exported with \"session export\"",
+        long_about = "Import a session that was exported with \"session export\" for a program, it is restored when the program is loaded with \"load --session\".\nA breakpoint is placed in its line, if the line still contains its instruction, otherwise in the nearest line that contains the instruction. Breakpoints whose instruction is not contained in the program are skipped.\nThe breakpoints of an existing session of the program are replaced.\nReturn values:\n\n 0 - Session imported\n 1 - Some breakpoints could not be placed\n10 - IO error"
+    )]
+    Import(SessionImportArgs),
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct SessionExportArgs {
+    #[arg(long_help = "Program whose session is exported")]
+    pub file: String,
+
+    #[arg(long_help = "File the session is exported to")]
+    pub output: String,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct SessionImportArgs {
+    #[arg(long_help = "Program for which the session is imported")]
+    pub file: String,
+
+    #[arg(long_help = "File that was written with \"session export\"")]
+    pub setup: String,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct TestArgs {
+    #[command(subcommand)]
+    pub command: TestCommand,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum TestCommand {
+    #[command(
+        about = "Run a program and record the result as test case",
+        long_about = "Run a program without tui and write the state of the machine before and after the run into a test case file.\nThe memory is configured like in \"check run\", with the program header and \"--memory-config-file\".\nIf no file is provided, the program and the memory config file are read from the project manifest alpha.toml.\nReturn values:\n\n 0 - Test case recorded\n 1 - Compilation error\n 2 - Runtime error\n10 - IO error"
+    )]
+    Record(TestRecordArgs),
+
+    #[command(
+        about = "Run test cases recorded with \"test record\"",
+        long_about = "Run the programs of test cases recorded with \"test record\", starting with the recorded initial state, and compare the final state with the expected state.\nIf no files are provided, the test case files listed in \"tests\" of the project manifest alpha.toml are run.\nReturn values:\n\n 0 - All test cases passed\n 1 - At least one test case failed\n10 - IO error"
+    )]
+    Run(TestRunArgs),
+
+    #[command(
+        about = "Run a program against test cases written in a toml file",
+        long_about = "Run a program without tui for each test case of a toml file, starting with the initial values of the case, and compare the result with the expected values of the case.\nThe memory is configured like in \"check run\", with the program header and \"--memory-config-file\".\nReturn values:\n\n 0 - All test cases passed\n 1 - At least one test case failed or compilation error\n10 - IO error"
+    )]
+    Cases(TestCasesArgs),
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct TestRecordArgs {
+    #[arg(
+        long_help = "Specify the file that contains the program that should be recorded.\nIf not set, the program is read from the project manifest alpha.toml in the working directory."
+    )]
+    pub file: Option<String>,
+
+    #[arg(
+        long,
+        short,
+        help = "File the test case is written to",
+        long_help = "File the test case is written to, an existing file is overwritten.\nDefaults to the program file with the extension \".test.json\", e.g. \"sum.test.json\" for \"sum.alpha\"."
+    )]
+    pub output: Option<String>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ProfileArgs {
+    #[arg(long_help = "Specify the file that contains the program that should be profiled")]
+    pub file: String,
+
+    #[arg(
+        long,
+        default_value_t = 10,
+        help = "Number of lines that are listed",
+        long_help = "Number of lines that are listed, the lines are ordered by the number of times they were run."
+    )]
+    pub top: usize,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct TestCasesArgs {
+    #[arg(
+        long_help = "Specify the file that contains the program that should be tested.\nIf not set, the program is read from the project manifest alpha.toml in the working directory."
+    )]
+    pub file: Option<String>,
+
+    #[arg(
+        long_help = "Toml file that contains the test cases.\nIf not set, the file is read from \"test-cases\" in the project manifest alpha.toml."
+    )]
+    pub cases: Option<String>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct TestRunArgs {
+    #[arg(
+        long_help = "Test case files that should be run.\nIf not set, the files listed in \"tests\" in the project manifest alpha.toml in the working directory are run."
+    )]
+    pub files: Vec<String>,
 }
 
 #[derive(Args, Clone, Debug)]
@@ -101,10 +470,17 @@ pub struct CheckArgs {
     pub check_load_args: CheckLoadArgs,
 
     #[arg(
-        long_help = "Specify the input file that contains the program",
-        required = true
+        long_help = "Specify the input file that contains the program.\nIf not set, the program is read from the project manifest alpha.toml in the working directory."
     )]
-    pub file: String,
+    pub file: Option<String>,
+
+    #[arg(
+        long,
+        help = "Fix mechanical problems of the program",
+        long_help = "Fix mechanical problems of the program before it is checked and write the fixed program back to the file.\nFixed are comparisons that are not written in a spelling of the spec (only with \"--strict\" or \"--spec\"), labels without colon and ':=' without spaces.\nThe changed lines are printed as diff.",
+        global = true
+    )]
+    pub fix: bool,
 
     #[command(subcommand)]
     pub command: CheckCommand,
@@ -115,6 +491,9 @@ pub struct PlaygroundArgs {
     #[command(flatten)]
     pub load_playground_args: LoadPlaygroundArgs,
 
+    #[command(flatten)]
+    pub memory_args: MemoryArgs,
+
     #[arg(
         short,
         long,
@@ -123,6 +502,16 @@ pub struct PlaygroundArgs {
         display_order = 31
     )]
     pub custom_instruction_history_file: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "File to which the executed instructions are written with [Ctrl+s]",
+        long_help = "File to which the executed instructions are written when [Ctrl+s] is pressed, so that they can be loaded as program with the \"load\" command.\nInstructions that were rolled back in the timeline are not written. If the file exists, [Ctrl+s] has to be pressed twice to overwrite it.",
+        default_value = DEFAULT_PLAYGROUND_FILE,
+        display_order = 33
+    )]
+    pub save_file: String,
 }
 
 /// Args only allowed in playground and load.
@@ -149,21 +538,125 @@ pub struct LoadPlaygroundArgs {
 
     #[arg(
         long,
-        help = "Json file to load the theme from.",
-        long_help = "Json file to load the theme from. Overwrites theme set in config file.",
+        help = "Json or toml file to load the theme from.",
+        long_help = "Json or toml file to load the theme from, files with the extension .toml are read as toml. Overwrites theme set in config file.",
         global = true,
         conflicts_with = "theme",
         display_order = 2
     )]
     pub theme_file: Option<String>,
+
+    #[arg(
+        long,
+        help = "Display profiling overlay",
+        long_help = "Display an overlay that contains the time it took to render the last frame and to handle the last key event, as well as the sizes of the instruction and memory lists.\nCan be used to diagnose a sluggish ui.\nWhen a program is loaded, a column next to the instructions displays how often each line was run and how much time was spent running it in auto-run mode.",
+        global = true,
+        display_order = 36
+    )]
+    pub profile_ui: bool,
+
+    #[arg(
+        long,
+        help = "Display the received key events in an overlay",
+        long_help = "Display an overlay that contains the last key events as they were received from the terminal, including the kind of the event (press, repeat or release) and the modifiers.\nCan be used to diagnose keybindings that don't work or that are triggered twice with some terminals or keyboard layouts.",
+        global = true,
+        display_order = 37
+    )]
+    pub show_key_events: bool,
 }
 
 /// Args only allowed in check and load
-#[derive(Args, Clone, Debug)]
+#[derive(Args, Clone, Debug, Default)]
 pub struct CheckLoadArgs {
     #[command(flatten)]
     pub instruction_limiting_args: InstructionLimitingArgs,
 
+    #[command(flatten)]
+    pub memory_args: MemoryArgs,
+
+    #[arg(
+        long,
+        help = "Maximum number of steps the program is allowed to run",
+        long_help = "Maximum number of steps the program is allowed to run.\nIf the program runs more steps, a runtime error is raised. In the tui the used steps are displayed as a gauge.\n\nOverwrites the value set in the program header.",
+        global = true,
+        display_order = 26
+    )]
+    pub max_steps: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Raise runtime errors for inconsistent calls and returns",
+        long_help = "Raise a runtime error when return is run without a function being called or when the program finishes while a function has not returned.\nWithout this flag, return without a called function ends the program.",
+        global = true,
+        display_order = 27
+    )]
+    pub strict_calls: bool,
+
+    #[arg(
+        long,
+        help = "Start the program with a call of the function LABEL",
+        long_help = "Start the program with a call of the function LABEL, instead of at the start of the program or at the main label.\nThe arguments set with \"--args\" are pushed onto the stack before the function is run. When the function returns, the program ends.\nCan be used to test a single function in isolation.",
+        global = true,
+        conflicts_with = "strict_calls",
+        value_name = "LABEL",
+        display_order = 28
+    )]
+    pub call: Option<String>,
+
+    #[arg(
+        long,
+        help = "Arguments that are pushed onto the stack before the function is called",
+        long_help = "Arguments that are pushed onto the stack in order, before the function set with \"--call\" is run.\nExample: --call add --args 5,3",
+        value_delimiter = ',',
+        allow_negative_numbers = true,
+        global = true,
+        requires = "call",
+        display_order = 29
+    )]
+    pub args: Option<Vec<i32>>,
+
+    #[arg(
+        long,
+        help = "Write the state of the machine to FILE when the program finishes",
+        long_help = "Write the values of the accumulators, the gamma accumulator, the memory cells, the index memory cells and the stack to FILE when the program finishes.\nIn the tui the state of the last run that finished is written when the program is closed.\nCan be used to check the result of a program in scripts.",
+        global = true,
+        value_name = "FILE",
+        display_order = 30
+    )]
+    pub dump_state: Option<String>,
+
+    #[arg(
+        long,
+        help = "Format of the file written by \"--dump-state\"",
+        global = true,
+        default_value = "json",
+        requires = "dump_state",
+        display_order = 31
+    )]
+    pub format: StateFormat,
+
+    #[arg(
+        long,
+        help = "Don't allow options to override the exercise settings",
+        long_help = "Don't allow options to override the exercise settings that are read from the file \"exercise.toml\" next to the program file.\nIf an option is set to a different value than in the exercise settings or if no exercise settings are found, an error is returned. The program header is ignored.\nCan be used to make sure that the constraints of an exercise are followed, e.g. when homework is graded.",
+        global = true,
+        display_order = 32
+    )]
+    pub enforce_settings: bool,
+
+    #[arg(
+        long,
+        help = "Report internal errors as runtime errors instead of crashing",
+        long_help = "Catch panics that occur while an instruction is run and report them as runtime error, instead of crashing alpha_tui.\nPanics are bugs in alpha_tui, this flag can be used when untrusted or generated programs are run, e.g. when homework is graded automatically.",
+        global = true,
+        display_order = 33
+    )]
+    pub robust: bool,
+}
+
+/// Args that configure the available memory, allowed in check, load and playground
+#[derive(Args, Clone, Debug, Default)]
+pub struct MemoryArgs {
     #[arg(
         short,
         long,
@@ -198,6 +691,26 @@ pub struct CheckLoadArgs {
         display_order = 22
     )]
     pub memory_cells: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        help = "Disable accumulator, gamma accumulator, memory_cell and index_memory_cell detection",
+        long_help = "Set to disable accumulator, gamma accumulator, memory_cell and index_memory_cell detection.\nIf disabled, accumulators, gamma accumulator, memory cells and index memory cells won't be read from program and cannot be added by using them at runtime.\nInstead they have to be specified using \"--accumulators\", \"--enable-gamma-accumulator\", \"--memory-cells\" and \"--index-memory-cells\" or \"--memory-config-file\"",
+        global = true,
+        display_order = 25
+    )]
+    pub disable_memory_detection: bool,
+
+    #[arg(
+        short = 'g',
+        long,
+        help = "Enable the gamma accumulator",
+        long_help = "Enable the gamma accumulator, can be used to enable gamma accumulator when automatic detection is disabled by \"--disable-memory-detection\".",
+        conflicts_with = "memory_config_file",
+        global = true,
+        display_order = 21
+    )]
+    pub enable_gamma_accumulator: bool,
 }
 
 #[derive(Subcommand, Clone, Debug)]
@@ -207,15 +720,64 @@ pub enum Command {
 
     #[command(
         about = "Perform different checks on the program",
-        long_about = "Perform different checks on the program.\nReturn values:\n\n 0 - Check successful\n 1 - Compilation error\n 2 - Runtime error\n10 - IO error"
+        long_about = "Perform different checks on the program.\nReturn values:\n\n 0 - Check successful\n 1 - Compilation error\n 2 - Runtime error\n 3 - Result does not match the expected state of the exercise\n10 - IO error"
     )]
     Check(CheckArgs),
 
     #[command(
         about = "Start the tool in playground mode",
-        long_about = "Start the tool in playground mode. This allows for custom commands to be run."
+        long_about = "Start the tool in playground mode. This allows for custom commands to be run.\nNo program file is required, the memory is empty or set up with the memory options and \"--memory-config-file\".\nCan also be started with \"repl\".",
+        visible_alias = "repl"
     )]
     Playground(PlaygroundArgs),
+
+    #[command(
+        about = "Replay a repro file",
+        long_about = "Replay a repro file that was recorded with \"load --record-repro\".\nThe program is loaded and all recorded actions are performed, afterwards the tui can be used normally."
+    )]
+    Repro(ReproArgs),
+
+    #[command(
+        about = "Format a program",
+        long_about = "Format a program without running it.\nLabels, instructions and comments are aligned in columns and the file is written back, like \"load --write-alignment\" does.\nReturn values:\n\n 0 - File formatted\n 1 - File is not formatted (only with \"--check\") or program did not compile\n10 - IO error"
+    )]
+    Format(FormatArgs),
+
+    #[command(
+        about = "Check a program for likely mistakes",
+        long_about = "Check a program for likely mistakes without running it.\nReports unused labels, instructions that are never run, memory cells that are read but never written and calls of functions that can't reach a return.\nReturn values:\n\n 0 - No warnings\n 1 - Warnings found or program did not compile\n10 - IO error"
+    )]
+    Lint(LintArgs),
+
+    #[command(
+        about = "Show which exercises were completed",
+        long_about = "Show which of the bundled exercises in examples/exercises and which other exercises were completed.\nAn exercise is completed, when its program is run with \"check run\" and the program ends with the expected state of the exercise."
+    )]
+    Progress(ProgressArgs),
+
+    #[command(
+        about = "Work with states written with \"--dump-state\"",
+        long_about = "Work with states of the machine that were written with \"--dump-state\"."
+    )]
+    State(StateArgs),
+
+    #[command(
+        about = "Record and run test cases of programs",
+        long_about = "Record test cases from runs of programs and run them again, to check that changes to a program don't change its result."
+    )]
+    Test(TestArgs),
+
+    #[command(
+        about = "Share debugging sessions stored with \"load --session\"",
+        long_about = "Export the debugging session of a program (breakpoints and shown panels) into a portable file and import it for another program, e.g. to hand out a prepared debugging setup together with an exercise."
+    )]
+    Session(SessionArgs),
+
+    #[command(
+        about = "Run a program and show which lines were run most often",
+        long_about = "Run a program without the tui and print the total number of steps and the lines that were run most often, to find the parts of a program that are worth optimizing.\nReturn values:\n\n 0 - Program finished\n 1 - Program did not compile\n 2 - Runtime error while running the program\n10 - IO error"
+    )]
+    Profile(ProfileArgs),
 }
 
 #[derive(Args, Debug, Clone, Default)]
@@ -251,23 +813,21 @@ pub struct InstructionLimitingArgs {
 
     #[arg(
         long,
-        help = "Disable accumulator, gamma accumulator, memory_cell and index_memory_cell detection",
-        long_help = "Set to disable accumulator, gamma accumulator, memory_cell and index_memory_cell detection.\nIf disabled, accumulators, gamma accumulator, memory cells and index memory cells won't be read from program and cannot be added by using them at runtime.\nInstead they have to be specified using \"--accumulators\", \"--enable-gamma-accumulator\", \"--memory-cells\" and \"--index-memory-cells\" or \"--memory-config-file\"",
+        help = "Select the version of the alpha notation spec",
+        long_help = "Select the version of the alpha notation spec that the program has to follow.\nThe versions differ in the operand combinations and stack operations that are allowed, instructions that are not allowed in the selected version lead to a build_program_error.\nIf not set, no restrictions apply.\n\nFor more help see https://github.com/LMH01/alpha_tui/blob/master/docs/cli.md",
         global = true,
-        display_order = 25
+        display_order = 13
     )]
-    pub disable_memory_detection: bool,
+    pub spec: Option<Spec>,
 
     #[arg(
-        short = 'g',
         long,
-        help = "Enable the gamma accumulator",
-        long_help = "Enable the gamma accumulator, can be used to enable gamma accumulator when automatic detection is disabled by \"--disable-memory-detection\".",
-        conflicts_with = "memory_config_file",
+        help = "Enforce the operand combinations of the official grammar",
+        long_help = "Enforce the operand combinations of the official grammar, for example that the left operand of a calculation has to be an accumulator.\nThese rules are always enforced, when a version of the spec is selected with \"--spec\".",
         global = true,
-        display_order = 21
+        display_order = 14
     )]
-    pub enable_gamma_accumulator: bool,
+    pub strict: bool,
 }
 
 #[derive(Subcommand, Clone, Debug)]
@@ -275,7 +835,37 @@ pub enum CheckCommand {
     #[command(about = "Check if the program compiles")]
     Compile,
     #[command(about = "Check if the program can be run")]
-    Run,
+    Run(CheckRunArgs),
+}
+
+#[derive(Args, Clone, Debug, Default)]
+pub struct CheckRunArgs {
+    #[arg(
+        long,
+        help = "Write the values of the memory to a csv file while the program is run",
+        long_help = "Write the values of all accumulators, the gamma accumulator, memory cells and index memory cells to FILE while the program is run.\nA row is written every \"--sample-every\" steps, the first column contains the number of steps that have been run.\nCan be used to plot the behavior of a program in external tools.",
+        value_name = "FILE"
+    )]
+    pub metrics_file: Option<String>,
+
+    #[arg(
+        long,
+        help = "Number of steps between two rows of the metrics file",
+        long_help = "Number of steps between two rows of the metrics file.\nThe state before the first and after the last instruction is always written.",
+        default_value_t = 1,
+        requires = "metrics_file",
+        value_name = "N"
+    )]
+    pub sample_every: usize,
+
+    #[arg(
+        long,
+        help = "Post an anonymized summary of the run to URL",
+        long_help = "Post an anonymized summary of the run as json to URL when the run ends, e.g. to show the progress of a class on a dashboard during lab sessions.\nThe summary contains a hash of the program, the name of the exercise, the result, the number of steps and the code of the runtime error, but not the program, file names or values of the memory.\nUrls starting with http:// and https:// are supported, if the summary can not be sent, a warning is printed and the result of the check is not changed.\nThe summary is also sent, when the program can not be build.\nNothing is sent unless this option is set.",
+        value_name = "URL",
+        value_parser = parse_report_url
+    )]
+    pub report_url: Option<String>,
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -286,8 +876,8 @@ pub trait CliHint {
 /// Validates if the provided arguments are allowed.
 ///
 /// This function is used to test some additional requirements, that can't be programmed into clap.
-pub fn validate_arguments(cli: &Cli) -> Result<()> {
-    let memory_cells = match cli.global_args.memory_config_file.clone() {
+pub fn validate_arguments(command: &Command, global_args: &GlobalArgs) -> Result<()> {
+    let memory_cells = match global_args.memory_config_file.clone() {
         Some(path) => Some(
             MemoryConfig::try_from_file(&path)?
                 .memory_cells
@@ -296,10 +886,28 @@ pub fn validate_arguments(cli: &Cli) -> Result<()> {
                 .map(|f| f.0.to_owned())
                 .collect::<Vec<String>>(),
         ),
-        None => match &cli.command {
-            Command::Check(check_args) => check_args.check_load_args.memory_cells.to_owned(),
-            Command::Load(load_args) => load_args.check_load_args.memory_cells.to_owned(),
-            Command::Playground(_) => return Ok(()),
+        None => match command {
+            Command::Check(check_args) => check_args
+                .check_load_args
+                .memory_args
+                .memory_cells
+                .to_owned(),
+            Command::Load(load_args) => load_args
+                .check_load_args
+                .memory_args
+                .memory_cells
+                .to_owned(),
+            Command::Playground(playground_args) => {
+                playground_args.memory_args.memory_cells.to_owned()
+            }
+            Command::Repro(_)
+            | Command::Format(_)
+            | Command::Lint(_)
+            | Command::Progress(_)
+            | Command::State(_)
+            | Command::Test(_)
+            | Command::Session(_)
+            | Command::Profile(_) => return Ok(()),
         },
     };
     if let Some(memory_cells) = &memory_cells {
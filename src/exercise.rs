@@ -0,0 +1,313 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use miette::{miette, Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    cli::{CheckLoadArgs, Command},
+    instructions::spec::Spec,
+    runtime::state_dump::{StateDifference, StateDump},
+    utils,
+};
+
+/// Name of the exercise settings file, it is read from the directory of the program file.
+pub const SETTINGS_FILE: &str = "exercise.toml";
+
+/// Settings that are distributed with an exercise and that are applied to `load` and `check`.
+///
+/// Paths are relative to the directory of the settings file.
+#[derive(Debug, Default, PartialEq, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ExerciseSettings {
+    /// Name under which the completion of the exercise is recorded, see [`crate::progress`].
+    pub name: Option<String>,
+    /// Task of the exercise, displayed in the progress overview.
+    pub description: Option<String>,
+    /// File with the state that is expected when the program finished, written like with `--dump-state`.
+    pub expected_state: Option<String>,
+    /// Allowed instructions file, see `--allowed-instructions-file`.
+    pub allowed_instructions_file: Option<String>,
+    /// Version of the spec, see `--spec`.
+    pub spec: Option<String>,
+    /// See `--strict`.
+    #[serde(default)]
+    pub strict: bool,
+    /// See `--max-steps`.
+    pub max_steps: Option<usize>,
+    /// Memory that is available, if set the memory is not detected from the program.
+    pub memory: Option<ExerciseMemory>,
+}
+
+/// Memory that is available in an exercise.
+#[derive(Debug, Default, PartialEq, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ExerciseMemory {
+    /// See `--accumulators`.
+    pub accumulators: Option<u8>,
+    /// See `--enable-gamma-accumulator`.
+    #[serde(default)]
+    pub gamma: bool,
+    /// See `--memory-cells`.
+    pub memory_cells: Option<Vec<String>>,
+    /// See `--index-memory-cells`.
+    pub index_memory_cells: Option<Vec<usize>>,
+}
+
+impl ExerciseSettings {
+    /// Tries to parse the provided file into exercise settings.
+    pub fn try_from_file(path: &str) -> Result<Self> {
+        match toml::from_str::<ExerciseSettings>(&utils::read_file(path)?.join("\n")) {
+            Ok(settings) => Ok(settings),
+            Err(e) => Err(miette!("toml parse error: {e}")),
+        }
+    }
+
+    /// Compares `state` with the expected state of the exercise, `dir` is the directory of the settings file.
+    ///
+    /// Returns the differences to the expected state, `None` if the exercise has no expected state.
+    pub fn check_result(
+        &self,
+        state: &StateDump,
+        dir: &Path,
+    ) -> Result<Option<Vec<StateDifference>>> {
+        let Some(file) = &self.expected_state else {
+            return Ok(None);
+        };
+        let expected = StateDump::try_from_file(&dir.join(file).to_string_lossy())?;
+        Ok(Some(expected.expected_differences(state)))
+    }
+
+    /// Applies the settings to `args`, `dir` is the directory of the settings file.
+    ///
+    /// Values are only set, if the corresponding cli option is not set. Returns the cli options that are set to a
+    /// different value than in the settings.
+    fn apply(
+        &self,
+        args: &mut CheckLoadArgs,
+        memory_config_file: Option<&String>,
+        dir: &Path,
+    ) -> Result<Vec<&'static str>> {
+        let mut overridden = Vec::new();
+        let limiting_args = &mut args.instruction_limiting_args;
+        if let Some(file) = &self.allowed_instructions_file {
+            let file = dir.join(file).to_string_lossy().to_string();
+            apply_value(
+                &mut limiting_args.allowed_instructions_file,
+                file,
+                "--allowed-instructions-file",
+                &mut overridden,
+            );
+            // additional comparisons and operations would extend the allowed instructions
+            if limiting_args.allowed_comparisons.is_some() {
+                overridden.push("--allowed-comparisons");
+            }
+            if limiting_args.allowed_operations.is_some() {
+                overridden.push("--allowed-operations");
+            }
+        }
+        if let Some(spec) = &self.spec {
+            let spec = Spec::from_str(spec, true).map_err(|_| {
+                miette!("unknown spec version '{spec}', supported versions are 2022, 2023 and 2024")
+            })?;
+            apply_value(&mut limiting_args.spec, spec, "--spec", &mut overridden);
+        }
+        limiting_args.strict |= self.strict;
+        if let Some(max_steps) = self.max_steps {
+            apply_value(
+                &mut args.max_steps,
+                max_steps,
+                "--max-steps",
+                &mut overridden,
+            );
+        }
+        if let Some(memory) = &self.memory {
+            let memory_args = &mut args.memory_args;
+            if memory_config_file.is_some() {
+                overridden.push("--memory-config-file");
+            }
+            if let Some(accumulators) = memory.accumulators {
+                apply_value(
+                    &mut memory_args.accumulators,
+                    accumulators,
+                    "--accumulators",
+                    &mut overridden,
+                );
+            }
+            if memory_args.enable_gamma_accumulator && !memory.gamma {
+                overridden.push("--enable-gamma-accumulator");
+            }
+            memory_args.enable_gamma_accumulator |= memory.gamma;
+            if let Some(memory_cells) = &memory.memory_cells {
+                apply_value(
+                    &mut memory_args.memory_cells,
+                    memory_cells.clone(),
+                    "--memory-cells",
+                    &mut overridden,
+                );
+            }
+            if let Some(index_memory_cells) = &memory.index_memory_cells {
+                apply_value(
+                    &mut memory_args.index_memory_cells,
+                    index_memory_cells.clone(),
+                    "--index-memory-cells",
+                    &mut overridden,
+                );
+            }
+            memory_args.disable_memory_detection = true;
+        }
+        Ok(overridden)
+    }
+}
+
+/// Sets `value` to `setting`, if it is not set. Adds `option` to `overridden`, if it is set to a different value.
+fn apply_value<T: PartialEq>(
+    value: &mut Option<T>,
+    setting: T,
+    option: &'static str,
+    overridden: &mut Vec<&'static str>,
+) {
+    match value {
+        Some(value) if *value != setting => overridden.push(option),
+        Some(_) => (),
+        None => *value = Some(setting),
+    }
+}
+
+/// Reads the exercise settings from the directory of the program file of `load` or `check` and applies them.
+///
+/// Values of the settings are only used, if the corresponding cli option is not set. If `--enforce-settings` is set,
+/// an error is returned instead, when a cli option is set to a different value or when no settings are found.
+pub fn apply_exercise_settings(
+    command: &mut Command,
+    memory_config_file: Option<&String>,
+) -> Result<()> {
+    let (file, check_load_args) = match command {
+        // the program is received from the shared session
+        Command::Load(args) if args.attach.is_some() => return Ok(()),
+        Command::Load(args) => (&args.file, &mut args.check_load_args),
+        Command::Check(args) => (&args.file, &mut args.check_load_args),
+        Command::Playground(_)
+        | Command::Repro(_)
+        | Command::Format(_)
+        | Command::Lint(_)
+        | Command::Progress(_)
+        | Command::State(_)
+        | Command::Test(_)
+        | Command::Session(_)
+        | Command::Profile(_) => return Ok(()),
+    };
+    let Some(file) = file else {
+        return Ok(());
+    };
+    let dir = Path::new(file).parent().unwrap_or(Path::new(""));
+    let enforce = check_load_args.enforce_settings;
+    let Some(settings) = read_exercise_settings(file)? else {
+        if enforce {
+            return Err(miette!(
+                help = format!("Place the file {SETTINGS_FILE} next to the program file"),
+                "--enforce-settings is set, but no exercise settings were found at {}",
+                dir.join(SETTINGS_FILE).display()
+            ));
+        }
+        return Ok(());
+    };
+    let path = dir.join(SETTINGS_FILE).to_string_lossy().to_string();
+    let overridden = settings.apply(check_load_args, memory_config_file, dir)?;
+    if enforce && !overridden.is_empty() {
+        return Err(miette!(
+            help = "Remove these options, the exercise settings can not be overridden while they are enforced",
+            "The options {} are set by the exercise settings in {path}",
+            overridden.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Reads the exercise settings from the directory of the program `file`, `None` if the directory contains no settings.
+pub fn read_exercise_settings(file: &str) -> Result<Option<ExerciseSettings>> {
+    let dir = Path::new(file).parent().unwrap_or(Path::new(""));
+    let path = dir.join(SETTINGS_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let path = path.to_string_lossy().to_string();
+    ExerciseSettings::try_from_file(&path)
+        .wrap_err(format!("Unable to read exercise settings [{path}]"))
+        .map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        cli::{CheckLoadArgs, InstructionLimitingArgs},
+        instructions::spec::Spec,
+    };
+
+    use super::ExerciseSettings;
+
+    fn settings() -> ExerciseSettings {
+        toml::from_str::<ExerciseSettings>(
+            r#"
+            allowed-instructions-file = "instructions.json"
+            spec = "2023"
+            max-steps = 100
+
+            [memory]
+            accumulators = 2
+            memory-cells = ["h1", "h2"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apply_exercise_settings() {
+        let mut args = CheckLoadArgs::default();
+        let overridden = settings()
+            .apply(&mut args, None, Path::new("exercise"))
+            .unwrap();
+        assert!(overridden.is_empty());
+        let limiting_args = &args.instruction_limiting_args;
+        assert_eq!(
+            limiting_args.allowed_instructions_file,
+            Some(
+                Path::new("exercise")
+                    .join("instructions.json")
+                    .to_string_lossy()
+                    .to_string()
+            )
+        );
+        assert_eq!(limiting_args.spec, Some(Spec::V2023));
+        assert_eq!(args.max_steps, Some(100));
+        assert_eq!(args.memory_args.accumulators, Some(2));
+        assert!(args.memory_args.disable_memory_detection);
+        assert!(!args.memory_args.enable_gamma_accumulator);
+    }
+
+    #[test]
+    fn test_apply_exercise_settings_overridden() {
+        let mut args = CheckLoadArgs {
+            instruction_limiting_args: InstructionLimitingArgs {
+                spec: Some(Spec::V2024),
+                allowed_operations: Some(Vec::new()),
+                ..InstructionLimitingArgs::default()
+            },
+            max_steps: Some(100),
+            ..CheckLoadArgs::default()
+        };
+        let memory_config_file = "memory.json".to_string();
+        let overridden = settings()
+            .apply(&mut args, Some(&memory_config_file), Path::new(""))
+            .unwrap();
+        assert_eq!(
+            overridden,
+            vec!["--allowed-operations", "--spec", "--memory-config-file"]
+        );
+        // cli options keep precedence, if the settings are not enforced
+        assert_eq!(args.instruction_limiting_args.spec, Some(Spec::V2024));
+        assert!(toml::from_str::<ExerciseSettings>("max-step = 1").is_err());
+    }
+}
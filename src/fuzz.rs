@@ -0,0 +1,64 @@
+use crate::{
+    cli::{CheckLoadArgs, GlobalArgs},
+    instructions::Instruction,
+    runtime::builder::RuntimeBuilder,
+};
+
+/// Maximum number of steps a program is run, so that programs with infinite loops finish quickly.
+const MAX_STEPS: usize = 10_000;
+
+/// Parses each line of `data` as instruction.
+///
+/// Malformed input has to result in a parse error, panics are reported by the fuzzer.
+pub fn parse_instructions(data: &[u8]) {
+    for line in String::from_utf8_lossy(data).lines() {
+        let _ = Instruction::try_from(line);
+    }
+}
+
+/// Builds the program in `data`, like the `load` command does, and runs it until it finishes or a runtime error
+/// occurs.
+///
+/// The robust mode is not enabled, so that panics while running instructions are reported by the fuzzer.
+pub fn run_program(data: &[u8]) {
+    let program = String::from_utf8_lossy(data)
+        .lines()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    let Ok(mut rb) = RuntimeBuilder::new(&program, "fuzz") else {
+        return;
+    };
+    let args = CheckLoadArgs {
+        max_steps: Some(MAX_STEPS),
+        ..Default::default()
+    };
+    if rb.apply_global_cli_args(&GlobalArgs::default()).is_err()
+        || rb.apply_program_header(&program).is_err()
+        || rb.apply_check_load_args(&args).is_err()
+    {
+        return;
+    }
+    if let Ok(mut rt) = rb.build() {
+        let _ = rt.run();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_instructions, run_program};
+
+    #[test]
+    fn test_fuzz_entry_points() {
+        for program in [
+            "",
+            "  \n\t",
+            "a := 5\nloop: a := a + 1\ngoto loop",
+            "p(p(h1)) := -a\nif a < 0 && then goto",
+            "# alpha_tui: max-steps=\u{0}\nstack+ γ",
+        ] {
+            parse_instructions(program.as_bytes());
+            run_program(program.as_bytes());
+        }
+        parse_instructions(&[0xff, 0xfe, b'\n', b'a']);
+    }
+}
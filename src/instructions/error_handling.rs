@@ -48,6 +48,26 @@ pub enum InstructionParseError {
     )]
     UnknownInstruction((usize, usize), String),
 
+    /// Indicates that the operand is not allowed at this position by the official grammar.
+    /// Arguments specify the character index at which the error occurred, the operand and the rule that is violated.
+    #[error("operand '{1}' is not allowed here")]
+    #[diagnostic(
+        code("parse_instruction::operand_not_allowed"),
+        url("https://github.com/LMH01/alpha_tui/blob/master/docs/cli.md"),
+        help("{2}")
+    )]
+    OperandNotAllowed((usize, usize), String, String),
+
+    /// Indicates that a built-in function is called with the wrong number of arguments.
+    /// Arguments specify the character index at which the error occurred, the name of the function and the number of arguments it takes.
+    #[error("wrong number of arguments for '{1}'")]
+    #[diagnostic(
+        code("parse_instruction::wrong_argument_count"),
+        url("https://github.com/LMH01/alpha_tui/blob/master/docs/instructions.md"),
+        help("'{1}' takes {2} argument(s)")
+    )]
+    WrongArgumentCount((usize, usize), String, usize),
+
     #[error("missing expression")]
     #[diagnostic(
         code("parse_instruction::missing_expression"),
@@ -69,6 +89,8 @@ impl InstructionParseError {
             InstructionParseError::NotANumber(c, _) => *c,
             InstructionParseError::InvalidExpression(c, _) => *c,
             InstructionParseError::UnknownInstruction(c, _) => *c,
+            InstructionParseError::OperandNotAllowed(c, _, _) => *c,
+            InstructionParseError::WrongArgumentCount(c, _, _) => *c,
             InstructionParseError::MissingExpression { range: c, help: _ } => *c,
         }
     }
@@ -132,6 +154,12 @@ impl InstructionParseError {
             InstructionParseError::UnknownOperation(_, _) => {
                 self.range().1.saturating_sub(self.range().0)
             }
+            InstructionParseError::OperandNotAllowed(_, _, _) => {
+                self.range().1.saturating_sub(self.range().0)
+            }
+            InstructionParseError::WrongArgumentCount(_, _, _) => {
+                self.range().1.saturating_sub(self.range().0)
+            }
             InstructionParseError::MissingExpression { range: _, help: _ } => {
                 self.range().1.saturating_sub(self.range().0)
             }
@@ -188,6 +216,47 @@ pub enum BuildProgramErrorTypes {
         help("Make sure that you include this operation ('{1}') in the allowed operations or use a different instruction.\nTo mark this operation as allowed you can use: '--allowed-operations \"{2}\"'"),
     )]
     OperationNotAllowed(usize, String, String),
+
+    /// Indicates that this instruction is not allowed in the selected version of the spec
+    #[error("instruction '{1}' in line '{0}' is not allowed in spec {2}: {3}")]
+    #[diagnostic(
+        code("build_program::instruction_not_in_spec_error"),
+        help("Use a different instruction or select a newer version of the spec with '--spec'")
+    )]
+    InstructionNotInSpec(usize, String, String, String),
+
+    #[error("contract annotation '{1}' in line '{0}' is invalid")]
+    #[diagnostic(
+        code("build_program::contract_invalid_error"),
+        help("{2}\nContracts are declared in comments of labels with '@requires VALUE CMP VALUE' and '@ensures VALUE CMP VALUE', for example '// @requires a1 >= 0'")
+    )]
+    ContractInvalid(usize, String, String),
+
+    #[error("initial values '{1}' in line '{0}' are invalid")]
+    #[diagnostic(
+        code("build_program::initial_values_invalid_error"),
+        help("{2}\nInitial values are declared in comments with '@init LOCATION=VALUE, ...', for example '// @init h1=5, h2=10, a0=3'")
+    )]
+    InitialValuesInvalid(usize, String, String),
+
+    #[error("macro in line '{0}' is invalid")]
+    #[diagnostic(
+        code("build_program::macro_invalid_error"),
+        help("{1}\nMacros are defined with 'macro NAME(PARAMETERS)', followed by their instructions and 'endmacro', for example 'macro swap(x, y)'")
+    )]
+    MacroInvalid(usize, String),
+
+    #[error("loop in line '{0}' is invalid")]
+    #[diagnostic(
+        code("build_program::loop_invalid_error"),
+        help("{1}\nLoops are written as 'while CONDITION do' ... 'endwhile' or as 'loop VALUE times' ... 'endloop', for example 'while a > 0 do'")
+    )]
+    LoopInvalid(usize, String),
+
+    /// Indicates that an operand is not allowed by the grammar, in an instruction that has no source.
+    #[error("operand '{1}' of instruction '{2}' in line '{0}' is not allowed")]
+    #[diagnostic(code("build_program::operand_not_allowed_error"), help("{3}"))]
+    OperandNotAllowed(usize, String, String, String),
 }
 
 #[allow(clippy::match_same_arms)]
@@ -357,20 +426,6 @@ mod tests {
                 "stackxxx + p(h1)".to_string()
             ))
         );
-        assert_eq!(
-            Instruction::try_from("a0 := p(h1) + p(h2) +"),
-            Err(InstructionParseError::UnknownInstruction(
-                (0, 20),
-                "a0 := p(h1) + p(h2) +".to_string()
-            ))
-        );
-        assert_eq!(
-            Instruction::try_from("a0 := p(h1) + p(h2) + p(h3)"),
-            Err(InstructionParseError::UnknownInstruction(
-                (0, 26),
-                "a0 := p(h1) + p(h2) + p(h3)".to_string()
-            ))
-        );
         assert_eq!(
             Instruction::try_from("return xyz"),
             Err(InstructionParseError::UnknownInstruction(
@@ -431,5 +486,12 @@ mod tests {
                 help: "Try inserting an accumulator or a memory cell".to_string()
             })
         );
+        assert_eq!(
+            Instruction::try_from("a0 := p(h1) + p(h2) +"),
+            Err(InstructionParseError::MissingExpression {
+                range: (21, 21),
+                help: "Try inserting an accumulator or a memory cell".to_string()
+            })
+        );
     }
 }
@@ -8,8 +8,10 @@ use crate::{
     utils,
 };
 
+use super::spec::Spec;
+
 /// Stores information that is used to limit what instructions should be allowed.
-#[derive(Default)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InstructionConfig {
     /// Stores the ids of instructions that are allowed.
     ///
@@ -19,6 +21,10 @@ pub struct InstructionConfig {
     pub allowed_comparisons: Option<Vec<Comparison>>,
     /// Stores operations that are allowed, if value is `None`, all operations are allowed.
     pub allowed_operations: Option<Vec<Operation>>,
+    /// Version of the spec whose capability table has to be followed, if value is `None`, no restrictions apply.
+    pub spec: Option<Spec>,
+    /// If true, the operand combinations of the official grammar are enforced, even if no spec is set.
+    pub strict: bool,
 }
 
 impl InstructionConfig {
@@ -34,7 +40,14 @@ impl InstructionConfig {
                 // TODO change error return type to return RuntimeBuildError
                 Err(e) => return Err(miette::miette!("json parse error: {e}")),
             };
-        raw.into_instruction_config()
+        raw.into_instruction_config(path)
+    }
+
+    /// Returns true if the operand combinations of the official grammar have to be checked.
+    ///
+    /// This is the case when `strict` is set or when a version of the spec is selected.
+    pub fn enforces_grammar(&self) -> bool {
+        self.strict || self.spec.is_some()
     }
 }
 
@@ -50,16 +63,18 @@ struct RawInstructionConfig {
 }
 
 impl RawInstructionConfig {
-    /// Converts this instruction config file into an instruction config.
-    fn into_instruction_config(self) -> Result<InstructionConfig> {
+    /// Converts this instruction config file into an instruction config, `path` is the file it was read from.
+    fn into_instruction_config(self, path: &str) -> Result<InstructionConfig> {
         let allowed_instruction_identifiers = match self.instructions {
-            Some(aii) => Some(utils::build_instruction_whitelist(aii, "")?),
+            Some(aii) => Some(utils::build_instruction_whitelist(aii, path)?),
             None => None,
         };
         Ok(InstructionConfig {
             allowed_instruction_identifiers,
             allowed_comparisons: self.comparisons,
             allowed_operations: self.operations,
+            spec: None,
+            strict: false,
         })
     }
 }
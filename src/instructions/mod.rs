@@ -5,15 +5,22 @@ use miette::Result;
 use crate::{
     base::{Accumulator, Comparison, MemoryCell, Operation},
     instructions::error_handling::InstructionParseError,
-    runtime::{error_handling::RuntimeErrorType, ControlFlow, RuntimeMemory, RuntimeSettings},
+    runtime::{
+        error_handling::{CalcError, RuntimeErrorType},
+        ControlFlow, RuntimeMemory, RuntimeSettings,
+    },
 };
 
-use self::parsing::{parse_alpha, parse_gamma, parse_index_memory_cell, parse_memory_cell};
+use self::parsing::{
+    parse_alpha, parse_gamma, parse_index_memory_cell, parse_memory_cell, parse_steps,
+};
 
 pub mod error_handling;
 pub mod instruction_config;
 /// Functions related to instruction parsing
 mod parsing;
+/// Versions of the alpha notation spec and what they allow
+pub mod spec;
 #[cfg(test)]
 mod tests;
 
@@ -24,6 +31,7 @@ const MEMORY_CELL_IDENTIFIER: &str = "M";
 const INDEX_MEMORY_CELL_IDENTIFIER: &str = "M";
 const GAMMA_IDENTIFIER: &str = "Y";
 const CONSTANT_IDENTIFIER: &str = "C";
+const STEPS_IDENTIFIER: &str = "S";
 pub const OPERATOR_IDENTIFIER: &str = "OP";
 pub const COMPARISON_IDENTIFIER: &str = "CMP";
 
@@ -31,13 +39,37 @@ pub const COMPARISON_IDENTIFIER: &str = "CMP";
 pub enum Instruction {
     Assign(TargetType, Value),
     Calc(TargetType, Value, Operation, Value),
+    /// Calculation with more than two operands, e.g. `a := a1 + a2 * 2`, see [`Operation::precedence`] for the order
+    /// in which the operations are evaluated
+    CalcChain(TargetType, Value, Vec<(Operation, Value)>),
+    /// Assigns the result of a built-in function, e.g. `a := max(a1, p(h1))`
+    AssignBuiltIn(TargetType, BuiltIn, Vec<Value>),
     JumpIf(Value, Comparison, Value, String),
+    /// Jump that depends on comparisons that are combined with `&&` and `||`
+    JumpIfCompound(Condition, String),
+    /// Jumps to the first label if the condition is true and to the second label otherwise, e.g.
+    /// `if a > 5 then goto big else goto small`
+    JumpIfElse(Condition, String, String),
+    /// Raises a runtime error when the comparison is false, e.g. `assert a < p(h1)`
+    Assert(Value, Comparison, Value),
+    /// Appends the value to the output of the program, e.g. `print p(h1)`
+    Print(Value),
     Goto(String),
+    /// Jumps to the line whose number is the value, e.g. `goto a0` or `goto p(h1)`
+    ComputedGoto(Value),
     Push,
     Pop,
     StackOp(Operation),
+    /// Rearranges the values of the stack, e.g. `stack dup`
+    StackCommand(StackCommand),
+    /// Assigns the top value of the stack to the target without removing it, e.g. `peek p(h1)`
+    Peek(TargetType),
     Call(String),
     Return,
+    /// Call of a macro that is defined in the program, e.g. `swap(p(h1), p(h2))`
+    Macro(MacroCall),
+    /// Line of a `while` or `loop` construct, e.g. `while a > 0 do` or `endloop`
+    Loop(LoopLine),
 
     /// Dummy instruction that does nothing, is inserted in empty lines
     Noop,
@@ -51,46 +83,188 @@ impl Instruction {
         runtime_settings: &RuntimeSettings,
     ) -> Result<(), RuntimeErrorType> {
         match self {
-            Self::Assign(target, source) => {
-                run_assign(runtime_memory, runtime_settings, target, source)?
-            }
+            Self::Assign(target, source) => run_assign(
+                runtime_memory,
+                control_flow,
+                runtime_settings,
+                target,
+                source,
+            )?,
             Self::Calc(target, source_a, op, source_b) => {
-                run_calc(
-                    runtime_memory,
-                    runtime_settings,
-                    target,
-                    source_a,
-                    *op,
-                    source_b,
-                )?;
+                run_calc(runtime_memory, runtime_settings, target, |runtime_memory| {
+                    calc(runtime_memory, control_flow, source_a, &[(*op, source_b)])
+                })?;
+            }
+            Self::CalcChain(target, source_a, operations) => {
+                let operations = operations
+                    .iter()
+                    .map(|(op, v)| (*op, v))
+                    .collect::<Vec<_>>();
+                run_calc(runtime_memory, runtime_settings, target, |runtime_memory| {
+                    calc(runtime_memory, control_flow, source_a, &operations)
+                })?;
+            }
+            Self::AssignBuiltIn(target, built_in, args) => {
+                run_calc(runtime_memory, runtime_settings, target, |runtime_memory| {
+                    built_in.call(runtime_memory, control_flow, args)
+                })?;
+                // the next call of rand returns a new number
+                if *built_in == BuiltIn::Rand {
+                    control_flow.random.advance();
+                }
             }
             Self::JumpIf(value_a, cmp, value_b, label) => {
                 run_jump_if(runtime_memory, control_flow, value_a, cmp, value_b, label)?;
             }
+            Self::JumpIfCompound(condition, label) => {
+                if condition.evaluate(runtime_memory, control_flow)? {
+                    control_flow.next_instruction_index(label)?;
+                }
+            }
+            Self::JumpIfElse(condition, then_label, else_label) => {
+                if condition.evaluate(runtime_memory, control_flow)? {
+                    control_flow.next_instruction_index(then_label)?;
+                } else {
+                    control_flow.next_instruction_index(else_label)?;
+                }
+            }
+            Self::Assert(value_a, cmp, value_b) => {
+                run_assert(runtime_memory, control_flow, value_a, cmp, value_b)?;
+            }
+            Self::Print(value) => run_print(runtime_memory, control_flow, value)?,
             Self::Goto(label) => run_goto(control_flow, label)?,
+            Self::ComputedGoto(value) => {
+                let line = value.value(runtime_memory, control_flow)?;
+                control_flow.jump_to_line(line)?;
+            }
             Self::Push => run_push(runtime_memory, runtime_settings)?,
             Self::Pop => run_pop(runtime_memory, runtime_settings)?,
             Self::StackOp(op) => run_stack_op(runtime_memory, *op)?,
+            Self::StackCommand(command) => run_stack_command(runtime_memory, *command)?,
+            Self::Peek(target) => {
+                run_calc(runtime_memory, runtime_settings, target, |runtime_memory| {
+                    runtime_memory
+                        .stack
+                        .last()
+                        .copied()
+                        .ok_or(RuntimeErrorType::PeekFail)
+                })?;
+            }
             Self::Call(label) => run_call(control_flow, label)?,
-            Self::Return => run_return(control_flow)?,
+            Self::Return => run_return(control_flow, runtime_settings)?,
+            Self::Macro(call) => {
+                for instruction in &call.body {
+                    instruction.run(runtime_memory, control_flow, runtime_settings)?;
+                }
+            }
+            Self::Loop(line) => {
+                for instruction in &line.lowered {
+                    instruction.run(runtime_memory, control_flow, runtime_settings)?;
+                }
+            }
             Self::Noop => (),
         }
         Ok(())
     }
 
-    /// If an comparison is used in this instruction it is returned
-    pub fn comparison(&self) -> Option<&Comparison> {
+    /// Returns all comparisons that are used in this instruction
+    pub fn comparisons(&self) -> Vec<&Comparison> {
         match self {
-            Self::JumpIf(_, cmp, _, _) => Some(cmp),
-            _ => None,
+            Self::JumpIf(_, cmp, _, _) | Self::Assert(_, cmp, _) => vec![cmp],
+            Self::JumpIfCompound(condition, _) | Self::JumpIfElse(condition, _, _) => condition
+                .comparisons()
+                .into_iter()
+                .map(|(_, cmp, _)| cmp)
+                .collect(),
+            Self::Macro(call) => call.body.iter().flat_map(Self::comparisons).collect(),
+            Self::Loop(line) => line.lowered.iter().flat_map(Self::comparisons).collect(),
+            _ => Vec::new(),
         }
     }
 
-    /// If an operation is used in this instruction it is returned
-    pub fn operation(&self) -> Option<&Operation> {
+    /// Returns the labels this instruction jumps to, empty if it is not a jump.
+    ///
+    /// Jumps with an else branch return the label of the then branch first.
+    pub fn jump_labels(&self) -> Vec<&str> {
         match self {
-            Self::Calc(_, _, op, _) | Self::StackOp(op) => Some(op),
-            _ => None,
+            Self::JumpIf(_, _, _, label)
+            | Self::JumpIfCompound(_, label)
+            | Self::Goto(label)
+            | Self::Call(label) => vec![label],
+            Self::JumpIfElse(_, then_label, else_label) => vec![then_label, else_label],
+            Self::Loop(line) => line.lowered.iter().flat_map(Self::jump_labels).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns a one-line description of what this kind of instruction does, shown in debug select mode.
+    pub fn description(&self) -> String {
+        match self {
+            Self::Assign(_, _) => "Assigns the value on the right to the location on the left.".to_string(),
+            Self::Calc(_, _, _, _) | Self::CalcChain(_, _, _) => {
+                "Calculates the value on the right, from left to right, and assigns it to the location on the left."
+                    .to_string()
+            }
+            Self::AssignBuiltIn(_, b, _) => format!(
+                "Assigns the {} to the location on the left.",
+                b.description()
+            ),
+            Self::JumpIf(_, _, _, _) | Self::JumpIfCompound(_, _) => {
+                "Jumps to the label if the condition is true, otherwise continues with the next line.".to_string()
+            }
+            Self::JumpIfElse(_, _, _) => {
+                "Jumps to the first label if the condition is true, otherwise to the second label.".to_string()
+            }
+            Self::Assert(_, _, _) => {
+                "Stops the program with a runtime error if the comparison is false.".to_string()
+            }
+            Self::Print(_) => "Appends the value to the output of the program.".to_string(),
+            Self::Goto(_) => "Jumps to the label.".to_string(),
+            Self::ComputedGoto(_) => "Jumps to the line whose number is the value.".to_string(),
+            Self::Push => "Pushes the value of α0 onto the stack.".to_string(),
+            Self::Pop => "Removes the top value of the stack and assigns it to α0.".to_string(),
+            Self::StackOp(_) => {
+                "Replaces the two top values of the stack with the result of the operation, which is also assigned to α0."
+                    .to_string()
+            }
+            Self::StackCommand(command) => command.description().to_string(),
+            Self::Peek(_) => {
+                "Assigns the top value of the stack to the location, without removing it.".to_string()
+            }
+            Self::Call(_) => {
+                "Jumps to the label and remembers the next line, to which return jumps back.".to_string()
+            }
+            Self::Return => {
+                "Jumps back to the line after the last call, ends the program if there is none.".to_string()
+            }
+            Self::Macro(call) => format!(
+                "Runs the instructions of the macro in one step: {}",
+                call.body
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+            Self::Loop(line) => format!(
+                "Part of a loop, runs the jumps it is lowered into in one step: {}",
+                line.lowered
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+            Self::Noop => "Does nothing.".to_string(),
+        }
+    }
+
+    /// Returns all operations that are used in this instruction
+    pub fn operations(&self) -> Vec<&Operation> {
+        match self {
+            Self::Calc(_, _, op, _) | Self::StackOp(op) => vec![op],
+            Self::CalcChain(_, _, operations) => operations.iter().map(|(op, _)| op).collect(),
+            Self::Macro(call) => call.body.iter().flat_map(Self::operations).collect(),
+            Self::Loop(line) => line.lowered.iter().flat_map(Self::operations).collect(),
+            _ => Vec::new(),
         }
     }
 }
@@ -100,14 +274,47 @@ impl Display for Instruction {
         match self {
             Self::Assign(t, v) => write!(f, "{t} := {v}"),
             Self::Calc(t, v, op, v2) => write!(f, "{t} := {v} {op} {v2}"),
+            Self::CalcChain(t, v, operations) => {
+                write!(f, "{t} := {v}")?;
+                for (op, v) in operations {
+                    write!(f, " {op} {v}")?;
+                }
+                Ok(())
+            }
+            Self::AssignBuiltIn(t, b, args) => write!(
+                f,
+                "{t} := {b}({})",
+                args.iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             Self::Call(l) => write!(f, "call {l}"),
             Self::Goto(l) => write!(f, "goto {l}"),
+            Self::ComputedGoto(v) => write!(f, "goto {v}"),
             Self::JumpIf(v, cmp, v2, l) => write!(f, "if {v} {cmp} {v2} then goto {l}"),
+            Self::JumpIfCompound(c, l) => write!(f, "if {c} then goto {l}"),
+            Self::JumpIfElse(c, l, l2) => write!(f, "if {c} then goto {l} else goto {l2}"),
+            Self::Assert(v, cmp, v2) => write!(f, "assert {v} {cmp} {v2}"),
+            Self::Print(v) => write!(f, "print {v}"),
+            Self::Macro(call) => write!(
+                f,
+                "{}({})",
+                call.name,
+                call.args
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Loop(line) => write!(f, "{}", line.kind),
             Self::Noop => write!(f, ""),
             Self::Pop => write!(f, "pop"),
             Self::Push => write!(f, "push"),
             Self::Return => write!(f, "return"),
             Self::StackOp(op) => write!(f, "stack{op}"),
+            Self::StackCommand(command) => write!(f, "stack {command}"),
+            Self::Peek(t) => write!(f, "peek {t}"),
         }
     }
 }
@@ -123,25 +330,394 @@ impl Identifier for Instruction {
                 op.identifier(),
                 v2.identifier()
             ),
+            Self::CalcChain(t, v, operations) => {
+                let mut identifier = format!("{} := {}", t.identifier(), v.identifier());
+                for (op, v) in operations {
+                    identifier.push_str(&format!(" {} {}", op.identifier(), v.identifier()));
+                }
+                identifier
+            }
+            Self::AssignBuiltIn(t, b, args) => format!(
+                "{} := {b}({})",
+                t.identifier(),
+                args.iter()
+                    .map(Identifier::identifier)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             Self::Call(_) => "call".to_string(),
             Self::Goto(_) => "goto".to_string(),
+            Self::ComputedGoto(v) => format!("goto {}", v.identifier()),
             Self::JumpIf(v, cmp, v2, _) => format!(
                 "if {} {} {} then goto",
                 v.identifier(),
                 cmp.identifier(),
                 v2.identifier()
             ),
+            Self::JumpIfCompound(c, _) => format!("if {} then goto", c.identifier()),
+            Self::JumpIfElse(c, _, _) => format!("if {} then goto else goto", c.identifier()),
+            Self::Assert(v, cmp, v2) => format!(
+                "assert {} {} {}",
+                v.identifier(),
+                cmp.identifier(),
+                v2.identifier()
+            ),
+            Self::Print(v) => format!("print {}", v.identifier()),
+            Self::Macro(call) => format!(
+                "{}({})",
+                call.name,
+                call.args
+                    .iter()
+                    .map(Identifier::identifier)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Loop(line) => line.kind.identifier(),
             Self::Noop => "NOOP".to_string(),
             Self::Pop => "pop".to_string(),
             Self::Push => "push".to_string(),
             Self::Return => "return".to_string(),
             Self::StackOp(op) => format!("stack{}", op.identifier()),
+            Self::StackCommand(command) => format!("stack {command}"),
+            Self::Peek(t) => format!("peek {}", t.identifier()),
+        }
+    }
+}
+
+/// Built-in function that can be used on the right side of an assignment, e.g. `a := abs(a1)`.
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
+pub enum BuiltIn {
+    Abs,
+    Min,
+    Max,
+    /// Integer square root, the result is rounded down
+    Isqrt,
+    /// Random number between both values, both inclusive, see [`Random`](crate::runtime::random::Random)
+    Rand,
+}
+
+impl BuiltIn {
+    /// Number of arguments the function takes.
+    pub fn arguments(self) -> usize {
+        match self {
+            Self::Abs | Self::Isqrt => 1,
+            Self::Min | Self::Max | Self::Rand => 2,
+        }
+    }
+
+    /// Returns what the function calculates, e.g. `larger of both values` for `max`.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Abs => "absolute value",
+            Self::Min => "smaller of both values",
+            Self::Max => "larger of both values",
+            Self::Isqrt => "square root, rounded down,",
+            Self::Rand => "random number between both values",
+        }
+    }
+
+    /// Calls the function with the values of `args`, the number of arguments has to match [`BuiltIn::arguments`].
+    pub fn call(
+        self,
+        runtime_args: &RuntimeMemory,
+        control_flow: &ControlFlow,
+        args: &[Value],
+    ) -> Result<i32, RuntimeErrorType> {
+        let values = args
+            .iter()
+            .map(|f| f.value(runtime_args, control_flow))
+            .collect::<Result<Vec<_>, _>>()?;
+        match self {
+            Self::Abs => match values[0].checked_abs() {
+                Some(v) => Ok(v),
+                None => Err(RuntimeErrorType::IllegalCalculation {
+                    cause: CalcError::AttemptToOverflow(
+                        "calculate the absolute value".to_string(),
+                        "Calculating the absolute value".to_string(),
+                    ),
+                }),
+            },
+            Self::Min => Ok(values[0].min(values[1])),
+            Self::Max => Ok(values[0].max(values[1])),
+            Self::Isqrt => match values[0].checked_isqrt() {
+                Some(v) => Ok(v),
+                None => Err(RuntimeErrorType::IllegalCalculation {
+                    cause: CalcError::AttemptToTakeRootOfNegativeNumber(),
+                }),
+            },
+            Self::Rand => Ok(control_flow.random.value(values[0], values[1])),
+        }
+    }
+}
+
+impl TryFrom<&str> for BuiltIn {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "abs" => Ok(Self::Abs),
+            "min" => Ok(Self::Min),
+            "max" => Ok(Self::Max),
+            "isqrt" => Ok(Self::Isqrt),
+            "rand" => Ok(Self::Rand),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for BuiltIn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Abs => write!(f, "abs"),
+            Self::Min => write!(f, "min"),
+            Self::Max => write!(f, "max"),
+            Self::Isqrt => write!(f, "isqrt"),
+            Self::Rand => write!(f, "rand"),
+        }
+    }
+}
+
+/// Instruction that rearranges the values of the stack, written after `stack`, e.g. `stack swap`.
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
+pub enum StackCommand {
+    /// Pushes a copy of the top value
+    Dup,
+    /// Exchanges the two top values
+    Swap,
+    /// Removes the top value
+    Drop,
+}
+
+impl StackCommand {
+    /// Number of values the stack has to contain for the command.
+    pub fn required_values(self) -> usize {
+        match self {
+            Self::Dup | Self::Drop => 1,
+            Self::Swap => 2,
+        }
+    }
+
+    /// Returns what the command does, shown in debug select mode.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Dup => "Pushes a copy of the top value of the stack onto the stack.",
+            Self::Swap => "Exchanges the two top values of the stack.",
+            Self::Drop => "Removes the top value of the stack, α0 is not changed.",
+        }
+    }
+}
+
+impl TryFrom<&str> for StackCommand {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "dup" => Ok(Self::Dup),
+            "swap" => Ok(Self::Swap),
+            "drop" => Ok(Self::Drop),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for StackCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dup => write!(f, "dup"),
+            Self::Swap => write!(f, "swap"),
+            Self::Drop => write!(f, "drop"),
+        }
+    }
+}
+
+/// Call of a macro, see [`Macros`](crate::runtime::macros::Macros).
+///
+/// The instructions of the macro are not known when the call is parsed, they are added when the program is build.
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+pub struct MacroCall {
+    pub name: String,
+    pub args: Vec<Value>,
+    /// Instructions of the macro, in which the parameters are replaced with the arguments
+    pub body: Vec<Instruction>,
+}
+
+impl MacroCall {
+    /// Checks if `name` can be used as name of a macro or of a parameter.
+    ///
+    /// Names can contain letters, numbers and `_` and can not start with a number. `p` can not be used, as it is
+    /// used for memory cells.
+    pub fn is_valid_name(name: &str) -> bool {
+        name != "p"
+            && name
+                .chars()
+                .next()
+                .is_some_and(|f| f.is_ascii_alphabetic() || f == '_')
+            && name.chars().all(|f| f.is_ascii_alphanumeric() || f == '_')
+    }
+}
+
+/// Line of a `while` or `loop` construct, see [`lower`](crate::runtime::loops::lower).
+///
+/// The jumps the line is lowered into are not known when the line is parsed, they are added when the program is
+/// build.
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+pub struct LoopLine {
+    pub kind: LoopKind,
+    /// Instructions that are run instead of the line
+    pub lowered: Vec<Instruction>,
+}
+
+impl LoopLine {
+    /// Creates the line, its instructions are added when it is lowered.
+    pub fn new(kind: LoopKind) -> Self {
+        Self {
+            kind,
+            lowered: Vec::new(),
+        }
+    }
+
+    /// Returns the memory cell that counts the remaining iterations of a `loop N times` construct.
+    pub fn counter(&self) -> Option<&str> {
+        match (&self.kind, self.lowered.first()) {
+            (LoopKind::Times(_), Some(Instruction::Assign(TargetType::MemoryCell(name), _))) => {
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Lines of which `while` and `loop` constructs consist.
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+pub enum LoopKind {
+    /// Start of a loop that runs as long as the condition is true, e.g. `while a > 0 do`
+    While(Condition),
+    EndWhile,
+    /// Start of a loop that runs the number of times of the value, e.g. `loop 3 times`
+    Times(Value),
+    EndLoop,
+}
+
+impl Display for LoopKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::While(c) => write!(f, "while {c} do"),
+            Self::EndWhile => write!(f, "endwhile"),
+            Self::Times(v) => write!(f, "loop {v} times"),
+            Self::EndLoop => write!(f, "endloop"),
+        }
+    }
+}
+
+impl Identifier for LoopKind {
+    fn identifier(&self) -> String {
+        match self {
+            Self::While(c) => format!("while {} do", c.identifier()),
+            Self::EndWhile => "endwhile".to_string(),
+            Self::Times(v) => format!("loop {} times", v.identifier()),
+            Self::EndLoop => "endloop".to_string(),
+        }
+    }
+}
+
+/// Condition of a jump in which comparisons are combined with `&&` and `||`.
+///
+/// `&&` binds stronger than `||`, so `a < 1 || a > 5 && p(h1) == 0` is evaluated as
+/// `a < 1 || (a > 5 && p(h1) == 0)`.
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+pub enum Condition {
+    Compare(Value, Comparison, Value),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+impl Condition {
+    /// Evaluates the condition.
+    ///
+    /// The right side of `&&` and `||` is only evaluated if the result is not already decided by the left side,
+    /// so values on the right side that are not set don't cause a runtime error in that case.
+    pub fn evaluate(
+        &self,
+        runtime_args: &RuntimeMemory,
+        control_flow: &ControlFlow,
+    ) -> Result<bool, RuntimeErrorType> {
+        match self {
+            Self::Compare(value_a, cmp, value_b) => Ok(cmp.cmp(
+                value_a.value(runtime_args, control_flow)?,
+                value_b.value(runtime_args, control_flow)?,
+            )),
+            Self::And(a, b) => Ok(a.evaluate(runtime_args, control_flow)?
+                && b.evaluate(runtime_args, control_flow)?),
+            Self::Or(a, b) => Ok(a.evaluate(runtime_args, control_flow)?
+                || b.evaluate(runtime_args, control_flow)?),
+        }
+    }
+
+    /// Returns the condition that is true exactly when this condition is false, e.g. `a >= 1 && a <= 5` for
+    /// `a < 1 || a > 5`.
+    ///
+    /// Returns `None` if the negation can not be written without parentheses, which is the case if an operand of
+    /// `||` contains `&&`.
+    pub fn negated(&self) -> Option<Self> {
+        match self {
+            Self::Compare(value_a, cmp, value_b) => Some(Self::Compare(
+                value_a.clone(),
+                cmp.negated(),
+                value_b.clone(),
+            )),
+            Self::And(a, b) => Some(Self::Or(Box::new(a.negated()?), Box::new(b.negated()?))),
+            Self::Or(a, b) => {
+                let (a, b) = (a.negated()?, b.negated()?);
+                // `&&` binds stronger, so a negated `&&` can not be an operand of `&&`
+                if matches!(a, Self::Or(_, _)) || matches!(b, Self::Or(_, _)) {
+                    return None;
+                }
+                Some(Self::And(Box::new(a), Box::new(b)))
+            }
+        }
+    }
+
+    /// Returns all comparisons of this condition, in the order in which they are written.
+    pub fn comparisons(&self) -> Vec<(&Value, &Comparison, &Value)> {
+        match self {
+            Self::Compare(value_a, cmp, value_b) => vec![(value_a, cmp, value_b)],
+            Self::And(a, b) | Self::Or(a, b) => {
+                let mut comparisons = a.comparisons();
+                comparisons.append(&mut b.comparisons());
+                comparisons
+            }
+        }
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Compare(v, cmp, v2) => write!(f, "{v} {cmp} {v2}"),
+            Self::And(a, b) => write!(f, "{a} && {b}"),
+            Self::Or(a, b) => write!(f, "{a} || {b}"),
+        }
+    }
+}
+
+impl Identifier for Condition {
+    fn identifier(&self) -> String {
+        match self {
+            Self::Compare(v, cmp, v2) => format!(
+                "{} {} {}",
+                v.identifier(),
+                cmp.identifier(),
+                v2.identifier()
+            ),
+            Self::And(a, b) => format!("{} && {}", a.identifier(), b.identifier()),
+            Self::Or(a, b) => format!("{} || {}", a.identifier(), b.identifier()),
         }
     }
 }
 
 fn run_assign(
     runtime_args: &mut RuntimeMemory,
+    control_flow: &ControlFlow,
     runtime_settings: &RuntimeSettings,
     target: &TargetType,
     source: &Value,
@@ -149,68 +725,95 @@ fn run_assign(
     match target {
         TargetType::Accumulator(a) => {
             assert_accumulator_exists(runtime_args, runtime_settings, *a)?;
-            runtime_args.accumulators.get_mut(a).unwrap().data = Some(source.value(runtime_args)?);
+            runtime_args.accumulators.get_mut(a).unwrap().data =
+                Some(source.value(runtime_args, control_flow)?);
         }
         TargetType::Gamma => {
             assert_gamma_exists(runtime_args, runtime_settings)?;
-            runtime_args.gamma = Some(Some(source.value(runtime_args)?));
+            runtime_args.gamma = Some(Some(source.value(runtime_args, control_flow)?));
         }
         TargetType::MemoryCell(a) => {
             assert_memory_cell_exists(runtime_args, runtime_settings, a)?;
-            runtime_args.memory_cells.get_mut(a).unwrap().data = Some(source.value(runtime_args)?);
+            runtime_args.memory_cells.get_mut(a).unwrap().data =
+                Some(source.value(runtime_args, control_flow)?);
         }
         TargetType::IndexMemoryCell(t) => match t {
             IndexMemoryCellIndexType::Accumulator(idx) => {
                 let idx = index_from_accumulator(runtime_args, *idx)?;
-                assign_index_memory_cell_from_value(runtime_args, runtime_settings, idx, source)?;
+                assign_index_memory_cell_from_value(
+                    runtime_args,
+                    control_flow,
+                    runtime_settings,
+                    idx,
+                    source,
+                )?;
             }
             IndexMemoryCellIndexType::Direct(idx) => {
-                assign_index_memory_cell_from_value(runtime_args, runtime_settings, *idx, source)?;
+                assign_index_memory_cell_from_value(
+                    runtime_args,
+                    control_flow,
+                    runtime_settings,
+                    *idx,
+                    source,
+                )?;
             }
             IndexMemoryCellIndexType::Gamma => {
                 let idx = index_from_gamma(runtime_args)?;
-                assign_index_memory_cell_from_value(runtime_args, runtime_settings, idx, source)?;
+                assign_index_memory_cell_from_value(
+                    runtime_args,
+                    control_flow,
+                    runtime_settings,
+                    idx,
+                    source,
+                )?;
             }
             IndexMemoryCellIndexType::MemoryCell(name) => {
                 let idx = index_from_memory_cell(runtime_args, name)?;
-                assign_index_memory_cell_from_value(runtime_args, runtime_settings, idx, source)?;
+                assign_index_memory_cell_from_value(
+                    runtime_args,
+                    control_flow,
+                    runtime_settings,
+                    idx,
+                    source,
+                )?;
             }
             IndexMemoryCellIndexType::Index(idx) => {
                 let idx = index_from_index_memory_cell(runtime_args, *idx)?;
-                assign_index_memory_cell_from_value(runtime_args, runtime_settings, idx, source)?;
+                assign_index_memory_cell_from_value(
+                    runtime_args,
+                    control_flow,
+                    runtime_settings,
+                    idx,
+                    source,
+                )?;
             }
         },
     }
     Ok(())
 }
 
+/// Assigns the result of `compute` to the target, `compute` is called after it is checked that the target exists.
 fn run_calc(
     runtime_args: &mut RuntimeMemory,
     runtime_settings: &RuntimeSettings,
     target: &TargetType,
-    source_a: &Value,
-    op: Operation,
-    source_b: &Value,
+    compute: impl Fn(&RuntimeMemory) -> Result<i32, RuntimeErrorType>,
 ) -> Result<(), RuntimeErrorType> {
     match target {
         TargetType::Accumulator(a) => {
             assert_accumulator_exists(runtime_args, runtime_settings, *a)?;
-            runtime_args.accumulators.get_mut(a).unwrap().data =
-                Some(op.calc(source_a.value(runtime_args)?, source_b.value(runtime_args)?)?);
+            runtime_args.accumulators.get_mut(a).unwrap().data = Some(compute(runtime_args)?);
         }
         TargetType::Gamma => {
             assert_gamma_exists(runtime_args, runtime_settings)?;
-            runtime_args.gamma = Some(Some(
-                op.calc(source_a.value(runtime_args)?, source_b.value(runtime_args)?)?,
-            ));
+            runtime_args.gamma = Some(Some(compute(runtime_args)?));
         }
         TargetType::MemoryCell(a) => {
             assert_memory_cell_exists(runtime_args, runtime_settings, a)?;
-            runtime_args.memory_cells.get_mut(a).unwrap().data =
-                Some(op.calc(source_a.value(runtime_args)?, source_b.value(runtime_args)?)?);
+            runtime_args.memory_cells.get_mut(a).unwrap().data = Some(compute(runtime_args)?);
         }
         TargetType::IndexMemoryCell(t) => {
-            let res = op.calc(source_a.value(runtime_args)?, source_b.value(runtime_args)?)?;
+            let res = compute(runtime_args)?;
             match t {
                 IndexMemoryCellIndexType::Accumulator(idx) => {
                     let idx = index_from_accumulator(runtime_args, *idx)?;
@@ -237,6 +840,48 @@ fn run_calc(
     Ok(())
 }
 
+/// Calculates the result of `source_a` and the operations.
+///
+/// Operations with a higher [`Operation::precedence`] are evaluated first, operations with the same precedence are
+/// evaluated from left to right, except for `**` that is evaluated from right to left.
+fn calc(
+    runtime_args: &RuntimeMemory,
+    control_flow: &ControlFlow,
+    source_a: &Value,
+    operations: &[(Operation, &Value)],
+) -> Result<i32, RuntimeErrorType> {
+    let mut values = vec![source_a.value(runtime_args, control_flow)?];
+    let mut pending: Vec<Operation> = Vec::new();
+    for (op, value) in operations {
+        while let Some(prev) = pending.last() {
+            let binds_stronger = prev.precedence() > op.precedence()
+                || (prev.precedence() == op.precedence() && *op != Operation::Pow);
+            if !binds_stronger {
+                break;
+            }
+            apply_pending(&mut values, &mut pending)?;
+        }
+        pending.push(*op);
+        values.push(value.value(runtime_args, control_flow)?);
+    }
+    while !pending.is_empty() {
+        apply_pending(&mut values, &mut pending)?;
+    }
+    Ok(values[0])
+}
+
+/// Applies the last pending operation to the last two values and replaces them with the result.
+fn apply_pending(
+    values: &mut Vec<i32>,
+    pending: &mut Vec<Operation>,
+) -> Result<(), RuntimeErrorType> {
+    let op = pending.pop().unwrap();
+    let y = values.pop().unwrap();
+    let x = values.pop().unwrap();
+    values.push(op.calc(x, y)?);
+    Ok(())
+}
+
 fn run_jump_if(
     runtime_args: &mut RuntimeMemory,
     control_flow: &mut ControlFlow,
@@ -245,12 +890,46 @@ fn run_jump_if(
     value_b: &Value,
     label: &str,
 ) -> Result<(), RuntimeErrorType> {
-    if cmp.cmp(value_a.value(runtime_args)?, value_b.value(runtime_args)?) {
+    if cmp.cmp(
+        value_a.value(runtime_args, control_flow)?,
+        value_b.value(runtime_args, control_flow)?,
+    ) {
         control_flow.next_instruction_index(label)?;
     }
     Ok(())
 }
 
+/// Causes runtime error if the comparison is false.
+fn run_assert(
+    runtime_args: &RuntimeMemory,
+    control_flow: &ControlFlow,
+    value_a: &Value,
+    cmp: &Comparison,
+    value_b: &Value,
+) -> Result<(), RuntimeErrorType> {
+    let left = value_a.value(runtime_args, control_flow)?;
+    let right = value_b.value(runtime_args, control_flow)?;
+    if !cmp.cmp(left, right) {
+        return Err(RuntimeErrorType::AssertionFailed(
+            format!("{value_a} {cmp} {value_b}"),
+            left,
+            right,
+        ));
+    }
+    Ok(())
+}
+
+/// Appends the value to the output of the program.
+fn run_print(
+    runtime_args: &RuntimeMemory,
+    control_flow: &mut ControlFlow,
+    value: &Value,
+) -> Result<(), RuntimeErrorType> {
+    let value = value.value(runtime_args, control_flow)?;
+    control_flow.output.push(value);
+    Ok(())
+}
+
 fn run_goto(control_flow: &mut ControlFlow, label: &str) -> Result<(), RuntimeErrorType> {
     control_flow.next_instruction_index(label)?;
     Ok(())
@@ -300,13 +979,40 @@ fn run_stack_op(runtime_memory: &mut RuntimeMemory, op: Operation) -> Result<(),
     }
 }
 
+/// Causes runtime error if stack does not contain the values required by the command.
+fn run_stack_command(
+    runtime_memory: &mut RuntimeMemory,
+    command: StackCommand,
+) -> Result<(), RuntimeErrorType> {
+    let stack = &mut runtime_memory.stack;
+    let len = stack.len();
+    if len < command.required_values() {
+        return Err(RuntimeErrorType::StackCommandFail(
+            command,
+            command.required_values(),
+        ));
+    }
+    match command {
+        StackCommand::Dup => stack.push(stack[len - 1]),
+        StackCommand::Swap => stack.swap(len - 1, len - 2),
+        StackCommand::Drop => {
+            stack.pop();
+        }
+    }
+    Ok(())
+}
+
 fn run_call(control_flow: &mut ControlFlow, label: &str) -> Result<(), RuntimeErrorType> {
     control_flow.call_function(label)
 }
 
-fn run_return(control_flow: &mut ControlFlow) -> Result<(), RuntimeErrorType> {
+fn run_return(
+    control_flow: &mut ControlFlow,
+    runtime_settings: &RuntimeSettings,
+) -> Result<(), RuntimeErrorType> {
     match control_flow.call_stack.pop() {
         Some(i) => control_flow.next_instruction_index = i,
+        None if runtime_settings.strict_calls => return Err(RuntimeErrorType::ReturnWithoutCall),
         None => run_goto(control_flow, "END")?,
     }
     Ok(())
@@ -464,6 +1170,7 @@ fn assign_index_memory_cell(
 /// Otherwise returns an runtime error.
 fn assign_index_memory_cell_from_value(
     runtime_memory: &mut RuntimeMemory,
+    control_flow: &ControlFlow,
     runtime_settings: &RuntimeSettings,
     idx: usize,
     source: &Value,
@@ -473,7 +1180,7 @@ fn assign_index_memory_cell_from_value(
     {
         runtime_memory
             .index_memory_cells
-            .insert(idx, Some(source.value(runtime_memory)?));
+            .insert(idx, Some(source.value(runtime_memory, control_flow)?));
     } else {
         return Err(RuntimeErrorType::IndexMemoryCellDoesNotExist(idx));
     }
@@ -601,17 +1308,36 @@ pub enum Value {
     MemoryCell(String),
     Constant(i32),
     IndexMemoryCell(IndexMemoryCellIndexType),
+    /// Read-only value that contains the number of instructions that were executed since the
+    /// program was started or last reset.
+    Steps,
+    /// Negated value, e.g. `-a1`, negative constants are stored as `Constant`.
+    Negated(Box<Value>),
 }
 
 impl Value {
-    fn value(&self, runtime_args: &RuntimeMemory) -> Result<i32, RuntimeErrorType> {
+    pub fn value(
+        &self,
+        runtime_args: &RuntimeMemory,
+        control_flow: &ControlFlow,
+    ) -> Result<i32, RuntimeErrorType> {
         match self {
+            Self::Steps => Ok(i32::try_from(control_flow.steps).unwrap_or(i32::MAX)),
             Self::Accumulator(a) => {
                 assert_accumulator_contains_value(runtime_args, *a)?;
                 Ok(runtime_args.accumulators.get(a).unwrap().data.unwrap())
             }
             Self::Gamma => assert_gamma_contains_value(runtime_args),
             Self::Constant(a) => Ok(*a),
+            Self::Negated(v) => match v.value(runtime_args, control_flow)?.checked_neg() {
+                Some(v) => Ok(v),
+                None => Err(RuntimeErrorType::IllegalCalculation {
+                    cause: CalcError::AttemptToOverflow(
+                        "negate".to_string(),
+                        "Negation".to_string(),
+                    ),
+                }),
+            },
             Self::MemoryCell(a) => {
                 assert_memory_cell_contains_value(runtime_args, a)?;
                 Ok(runtime_args.memory_cells.get(a).unwrap().data.unwrap())
@@ -665,6 +1391,19 @@ impl TryFrom<(&String, (usize, usize))> for Value {
         if let Ok(v) = value.0.parse::<i32>() {
             return Ok(Self::Constant(v));
         }
+        if let Some(negated) = value.0.strip_prefix('-') {
+            let range = (value.1 .0 + 1, value.1 .1);
+            return match Self::try_from((&negated.to_string(), range))? {
+                // only single negations of values that are not constant are allowed, e.g. `-a1` but not `--a1` or `--5`
+                Self::Negated(_) | Self::Constant(_) => Err(
+                    InstructionParseError::InvalidExpression(value.1, value.0.to_string()),
+                ),
+                v => Ok(Self::Negated(Box::new(v))),
+            };
+        }
+        if parse_steps(value.0, value.1).is_ok() {
+            return Ok(Self::Steps);
+        }
         if parse_gamma(value.0, value.1).is_ok() {
             return Ok(Self::Gamma);
         }
@@ -688,6 +1427,8 @@ impl Display for Value {
             Self::Gamma => write!(f, "y"),
             Self::MemoryCell(n) => write!(f, "p({n})"),
             Self::IndexMemoryCell(t) => write!(f, "p({t})"),
+            Self::Steps => write!(f, "steps"),
+            Self::Negated(v) => write!(f, "-{v}"),
         }
     }
 }
@@ -699,6 +1440,8 @@ impl Identifier for Value {
             Self::Constant(_) => CONSTANT_IDENTIFIER.to_string(),
             Self::Gamma => GAMMA_IDENTIFIER.to_string(),
             Self::MemoryCell(_) | Self::IndexMemoryCell(_) => MEMORY_CELL_IDENTIFIER.to_string(),
+            Self::Steps => STEPS_IDENTIFIER.to_string(),
+            Self::Negated(v) => format!("-{}", v.identifier()),
         }
     }
 }
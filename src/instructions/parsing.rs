@@ -3,13 +3,22 @@ use crate::{
     instructions::error_handling::InstructionParseError,
 };
 
-use super::{IndexMemoryCellIndexType, Instruction, TargetType, Value};
+use super::{
+    BuiltIn, Condition, IndexMemoryCellIndexType, Instruction, LoopKind, LoopLine, MacroCall,
+    StackCommand, TargetType, Value,
+};
 
 #[allow(clippy::too_many_lines)]
 impl TryFrom<&Vec<&str>> for Instruction {
     type Error = InstructionParseError;
 
     fn try_from(parts: &Vec<&str>) -> Result<Self, Self::Error> {
+        if parts.is_empty() {
+            return Err(InstructionParseError::MissingExpression {
+                range: (0, 0),
+                help: "Enter an instruction, e.g. 'a := 5'".to_string(),
+            });
+        }
         // Remove ; from end of line;
         let parts: Vec<String> = parts
             .iter()
@@ -27,38 +36,141 @@ impl TryFrom<&Vec<&str>> for Instruction {
 
         // Check if instruction is comparison
         if parts[0] == "if" {
+            let (condition, idx) = parse_condition(&parts)?;
+            check_expression_missing(&parts, idx, Some("then"))?;
+            if parts[idx] != "then" {
+                return Err(InstructionParseError::InvalidExpression(
+                    part_range(&parts, idx),
+                    parts[idx].to_string(),
+                ));
+            }
+            check_expression_missing(&parts, idx + 1, Some("goto"))?;
+            if parts[idx + 1] != "goto" {
+                return Err(InstructionParseError::InvalidExpression(
+                    part_range(&parts, idx + 1),
+                    parts[idx + 1].to_string(),
+                ));
+            }
+            check_expression_missing(&parts, idx + 2, Some("a label"))?;
+            let label = parts[idx + 2].to_string();
+            // jump with an else branch, e.g. if a > 5 then goto big else goto small
+            if parts.get(idx + 3).is_some_and(|f| f == "else") {
+                check_expression_missing(&parts, idx + 4, Some("goto"))?;
+                if parts[idx + 4] != "goto" {
+                    return Err(InstructionParseError::InvalidExpression(
+                        part_range(&parts, idx + 4),
+                        parts[idx + 4].to_string(),
+                    ));
+                }
+                check_expression_missing(&parts, idx + 5, Some("a label"))?;
+                return Ok(Instruction::JumpIfElse(
+                    condition,
+                    label,
+                    parts[idx + 5].to_string(),
+                ));
+            }
+            return Ok(match condition {
+                Condition::Compare(value_a, cmp, value_b) => {
+                    Instruction::JumpIf(value_a, cmp, value_b, label)
+                }
+                condition => Instruction::JumpIfCompound(condition, label),
+            });
+        }
+
+        // Check if instruction is the start of a loop, e.g. while a > 0 do
+        if parts[0] == "while" {
+            let (condition, idx) = parse_condition(&parts)?;
+            check_expression_missing(&parts, idx, Some("do"))?;
+            if parts[idx] != "do" {
+                return Err(InstructionParseError::InvalidExpression(
+                    part_range(&parts, idx),
+                    parts[idx].to_string(),
+                ));
+            }
+            if parts.len() > idx + 1 {
+                return Err(InstructionParseError::InvalidExpression(
+                    part_range(&parts, idx + 1),
+                    parts[idx + 1].to_string(),
+                ));
+            }
+            return Ok(Instruction::Loop(LoopLine::new(LoopKind::While(condition))));
+        }
+
+        // Check if instruction is the start of a counting loop, e.g. loop 3 times
+        // loop is often used as label, so lines like 'loop a := 1' are not treated as loop
+        if parts[0] == "loop" && parts.get(2).is_some_and(|f| f == "times") {
+            let value = Value::try_from((&parts[1], part_range(&parts, 1)))?;
+            if parts.len() > 3 {
+                return Err(InstructionParseError::InvalidExpression(
+                    part_range(&parts, 3),
+                    parts[3].to_string(),
+                ));
+            }
+            return Ok(Instruction::Loop(LoopLine::new(LoopKind::Times(value))));
+        }
+
+        // Check if instruction is the end of a loop
+        if parts[0] == "endwhile" || parts[0] == "endloop" {
+            if parts.len() > 1 {
+                return Err(InstructionParseError::InvalidExpression(
+                    part_range(&parts, 1),
+                    parts[1].to_string(),
+                ));
+            }
+            let kind = if parts[0] == "endwhile" {
+                LoopKind::EndWhile
+            } else {
+                LoopKind::EndLoop
+            };
+            return Ok(Instruction::Loop(LoopLine::new(kind)));
+        }
+
+        // Check if instruction is an assertion
+        if parts[0] == "assert" {
             check_expression_missing(&parts, 1, Some("an accumulator"))?;
             let value_a = Value::try_from((&parts[1], part_range(&parts, 1)))?;
             check_expression_missing(&parts, 2, Some("a comparison"))?;
             let cmp = parse_comparison(&parts[2], part_range(&parts, 2))?;
             check_expression_missing(&parts, 3, None)?;
-            check_expression_missing(&parts, 4, Some("then"))?;
-            if parts[4] != "then" {
+            let value_b = Value::try_from((&parts[3], part_range(&parts, 3)))?;
+            if parts.len() > 4 {
                 return Err(InstructionParseError::InvalidExpression(
                     part_range(&parts, 4),
-                    parts[5].to_string(),
+                    parts[4].to_string(),
                 ));
             }
-            check_expression_missing(&parts, 5, Some("goto"))?;
-            if parts[5] != "goto" {
+            return Ok(Instruction::Assert(value_a, cmp, value_b));
+        }
+
+        // Check if instruction is print
+        if parts[0] == "print" {
+            check_expression_missing(&parts, 1, Some("a value"))?;
+            let value = Value::try_from((&parts[1], part_range(&parts, 1)))?;
+            if parts.len() > 2 {
                 return Err(InstructionParseError::InvalidExpression(
-                    part_range(&parts, 5),
-                    parts[5].to_string(),
+                    part_range(&parts, 2),
+                    parts[2].to_string(),
                 ));
             }
-            check_expression_missing(&parts, 6, Some("a label"))?;
-            let value_b = Value::try_from((&parts[3], part_range(&parts, 3)))?;
-            return Ok(Instruction::JumpIf(
-                value_a,
-                cmp,
-                value_b,
-                parts[6].to_string(),
-            ));
+            return Ok(Instruction::Print(value));
         }
 
         // Check if instruction is goto
         if parts[0] == "goto" {
             check_expression_missing(&parts, 1, Some("a label"))?;
+            // operands that are written like a memory location contain the line to jump to, the short forms 'a'
+            // and 'y' are used as labels
+            if parts[1] != "a" && parts[1] != "y" {
+                if let Ok(
+                    value @ (Value::Accumulator(_)
+                    | Value::Gamma
+                    | Value::MemoryCell(_)
+                    | Value::IndexMemoryCell(_)),
+                ) = Value::try_from((&parts[1], part_range(&parts, 1)))
+                {
+                    return Ok(Instruction::ComputedGoto(value));
+                }
+            }
             return Ok(Instruction::Goto(parts[1].to_string()));
         }
 
@@ -72,6 +184,21 @@ impl TryFrom<&Vec<&str>> for Instruction {
             return Ok(Instruction::Pop);
         }
 
+        // Check if instruction is peek, the value is assigned to a0 if no target is set
+        if parts[0] == "peek" {
+            let target = match parts.get(1) {
+                Some(target) => TargetType::try_from((target, part_range(&parts, 1)))?,
+                None => TargetType::Accumulator(0),
+            };
+            if parts.len() > 2 {
+                return Err(InstructionParseError::InvalidExpression(
+                    part_range(&parts, 2),
+                    parts[2].to_string(),
+                ));
+            }
+            return Ok(Instruction::Peek(target));
+        }
+
         // Check if instruction is call
         if parts[0] == "call" && parts.len() == 2 {
             return Ok(Instruction::Call(parts[1].to_string()));
@@ -93,6 +220,9 @@ impl TryFrom<&Vec<&str>> for Instruction {
                     )?));
                 }
                 2 => {
+                    if let Ok(command) = StackCommand::try_from(parts[1].as_str()) {
+                        return Ok(Instruction::StackCommand(command));
+                    }
                     return Ok(Instruction::StackOp(parse_operation(
                         &parts[1],
                         (6, 5 + parts[1].len()),
@@ -107,6 +237,11 @@ impl TryFrom<&Vec<&str>> for Instruction {
             };
         }
 
+        // Check if instruction is the call of a macro
+        if let Some(call) = parse_macro_call(&parts)? {
+            return Ok(Instruction::Macro(call));
+        }
+
         // At this point only instructions follow that require := at second position
         if parts.len() < 2 {
             return Err(InstructionParseError::MissingExpression {
@@ -129,25 +264,35 @@ impl TryFrom<&Vec<&str>> for Instruction {
                 help: "Try inserting an accumulator or a memory cell".to_string(),
             });
         }
+        if let Some((built_in, args)) = parse_built_in(&parts)? {
+            return Ok(Instruction::AssignBuiltIn(target, built_in, args));
+        }
         let source_a = Value::try_from((&parts[2], part_range(&parts, 2)))?;
         if parts.len() == 3 {
             // instruction is of type a := b
             return Ok(Instruction::Assign(target, source_a));
-        } else if parts.len() == 4 {
+        } else if parts.len().is_multiple_of(2) {
+            let last = parts.len() - 1;
             return Err(InstructionParseError::MissingExpression {
-                range: (part_range(&parts, 3).1 + 1, part_range(&parts, 3).1 + 1),
+                range: (
+                    part_range(&parts, last).1 + 1,
+                    part_range(&parts, last).1 + 1,
+                ),
                 help: "Try inserting an accumulator or a memory cell".to_string(),
             });
-        } else if parts.len() == 5 {
-            // instruction is of type a := b op c
-            let op = parse_operation(&parts[3], part_range(&parts, 3))?;
-            let source_b = Value::try_from((&parts[4], part_range(&parts, 4)))?;
+        }
+        // instruction is of type a := b op c, or a := b op c op d ... if more operands follow
+        let mut operations = Vec::new();
+        for idx in (3..parts.len()).step_by(2) {
+            let op = parse_operation(&parts[idx], part_range(&parts, idx))?;
+            let source = Value::try_from((&parts[idx + 1], part_range(&parts, idx + 1)))?;
+            operations.push((op, source));
+        }
+        if operations.len() == 1 {
+            let (op, source_b) = operations.remove(0);
             return Ok(Instruction::Calc(target, source_a, op, source_b));
         }
-        Err(InstructionParseError::UnknownInstruction(
-            whole_range(&parts),
-            parts.join(" "),
-        ))
+        Ok(Instruction::CalcChain(target, source_a, operations))
     }
 }
 
@@ -207,6 +352,18 @@ pub fn parse_gamma(s: &str, part_range: (usize, usize)) -> Result<(), Instructio
     }
 }
 
+/// Checks if the input string is the read-only step counter.
+pub fn parse_steps(s: &str, part_range: (usize, usize)) -> Result<(), InstructionParseError> {
+    if s.eq("steps") {
+        Ok(())
+    } else {
+        Err(InstructionParseError::InvalidExpression(
+            part_range,
+            s.to_string(),
+        ))
+    }
+}
+
 /// Tries to parse the operation.
 ///
 /// `part_range` indicates the area that is affected.
@@ -230,6 +387,124 @@ pub fn parse_operation(
     }
 }
 
+/// Parses the condition of an `if` instruction, that starts at the second part.
+///
+/// The comparisons of the condition can be combined with `&&` and `||`, where `&&` binds stronger.
+/// Returns the condition and the index of the part that follows it.
+fn parse_condition(parts: &[String]) -> Result<(Condition, usize), InstructionParseError> {
+    let mut or_terms = Vec::new();
+    let mut and_terms = Vec::new();
+    let mut idx = 1;
+    loop {
+        check_expression_missing(parts, idx, Some("an accumulator"))?;
+        let value_a = Value::try_from((&parts[idx], part_range(parts, idx)))?;
+        check_expression_missing(parts, idx + 1, Some("a comparison"))?;
+        let cmp = parse_comparison(&parts[idx + 1], part_range(parts, idx + 1))?;
+        check_expression_missing(parts, idx + 2, None)?;
+        let value_b = Value::try_from((&parts[idx + 2], part_range(parts, idx + 2)))?;
+        and_terms.push(Condition::Compare(value_a, cmp, value_b));
+        idx += 3;
+        match parts.get(idx).map(String::as_str) {
+            Some("&&") => (),
+            Some("||") => or_terms.push(fold_condition(and_terms.drain(..), Condition::And)),
+            _ => break,
+        }
+        idx += 1;
+    }
+    or_terms.push(fold_condition(and_terms.into_iter(), Condition::And));
+    Ok((fold_condition(or_terms.into_iter(), Condition::Or), idx))
+}
+
+/// Combines the conditions from left to right with `combine`, there has to be at least one condition.
+fn fold_condition(
+    mut conditions: impl Iterator<Item = Condition>,
+    combine: fn(Box<Condition>, Box<Condition>) -> Condition,
+) -> Condition {
+    let first = conditions.next().expect("condition should not be empty");
+    conditions.fold(first, |acc, f| combine(Box::new(acc), Box::new(f)))
+}
+
+/// Parses the call of a built-in function on the right side of an assignment, e.g. `max(a1, p(h1))`.
+///
+/// The call starts at the third part and may contain whitespaces.
+/// Returns `None` if the right side is not the call of a built-in function.
+fn parse_built_in(
+    parts: &[String],
+) -> Result<Option<(BuiltIn, Vec<Value>)>, InstructionParseError> {
+    let Some((name, _)) = parts[2].split_once('(') else {
+        return Ok(None);
+    };
+    let Ok(built_in) = BuiltIn::try_from(name) else {
+        return Ok(None);
+    };
+    let (args, range) = parse_arguments(parts, 2, name)?;
+    if args.len() != built_in.arguments() {
+        return Err(InstructionParseError::WrongArgumentCount(
+            range,
+            built_in.to_string(),
+            built_in.arguments(),
+        ));
+    }
+    Ok(Some((built_in, args)))
+}
+
+/// Parses the call of a macro, e.g. `swap(p(h1), p(h2))`, the call may contain whitespaces.
+///
+/// Returns `None` if the instruction is not the call of a macro.
+fn parse_macro_call(parts: &[String]) -> Result<Option<MacroCall>, InstructionParseError> {
+    if matches!(parts.get(1).map(String::as_str), Some(":=" | "=")) {
+        return Ok(None);
+    }
+    let Some((name, _)) = parts[0].split_once('(') else {
+        return Ok(None);
+    };
+    if !MacroCall::is_valid_name(name) {
+        return Ok(None);
+    }
+    let (args, _) = parse_arguments(parts, 0, name)?;
+    Ok(Some(MacroCall {
+        name: name.to_string(),
+        args,
+        body: Vec::new(),
+    }))
+}
+
+/// Parses the comma separated arguments of the call of `name` that starts at part `idx` and ends the instruction.
+///
+/// Returns the arguments and the range of the call.
+fn parse_arguments(
+    parts: &[String],
+    idx: usize,
+    name: &str,
+) -> Result<(Vec<Value>, (usize, usize)), InstructionParseError> {
+    let start = part_range(parts, idx).0;
+    let call = parts[idx..].join(" ");
+    let range = (start, start + call.len() - 1);
+    let Some(inner) = call[name.len() + 1..].strip_suffix(')') else {
+        return Err(InstructionParseError::InvalidExpression(range, call));
+    };
+    let mut args = Vec::new();
+    if !inner.trim().is_empty() {
+        let mut offset = start + name.len() + 1;
+        for arg in inner.split(',') {
+            let arg_start = offset + arg.len() - arg.trim_start().len();
+            let arg_trimmed = arg.trim();
+            if arg_trimmed.is_empty() {
+                return Err(InstructionParseError::MissingExpression {
+                    range: (arg_start, arg_start),
+                    help: "Try inserting an accumulator or a memory cell".to_string(),
+                });
+            }
+            args.push(Value::try_from((
+                &arg_trimmed.to_string(),
+                (arg_start, arg_start + arg_trimmed.len() - 1),
+            ))?);
+            offset += arg.len() + 1;
+        }
+    }
+    Ok((args, range))
+}
+
 /// Tries to parse the comparison.
 ///
 /// `part_range` indicates the area that is affected.
@@ -397,7 +672,9 @@ fn check_expression_missing(
 mod tests {
     use crate::instructions::{
         error_handling::InstructionParseError,
-        parsing::{parse_alpha, parse_gamma, parse_index_memory_cell, parse_memory_cell},
+        parsing::{
+            parse_alpha, parse_gamma, parse_index_memory_cell, parse_memory_cell, parse_steps,
+        },
         IndexMemoryCellIndexType,
     };
 
@@ -495,6 +772,18 @@ mod tests {
         assert_eq!(parse_gamma("γ", (0, 0)), Ok(()));
     }
 
+    #[test]
+    fn test_parse_steps() {
+        assert_eq!(parse_steps("steps", (0, 4)), Ok(()));
+        assert_eq!(
+            parse_steps("step", (0, 3)),
+            Err(InstructionParseError::InvalidExpression(
+                (0, 3),
+                "step".to_string()
+            ))
+        );
+    }
+
     #[test]
     fn test_parse_alpha() {
         assert_eq!(parse_alpha("a0", (0, 1), true), Ok(0));
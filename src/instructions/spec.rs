@@ -0,0 +1,423 @@
+use std::fmt::Display;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::base::{Comparison, Operation};
+
+use super::{
+    error_handling::InstructionParseError, parsing::part_range, Condition,
+    IndexMemoryCellIndexType, Instruction, TargetType, Value,
+};
+
+/// Published version of the alpha notation spec of the lecture.
+///
+/// The versions differ in the instructions and operand combinations that are allowed, see [`Capabilities`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, ValueEnum, Serialize, Deserialize)]
+pub enum Spec {
+    #[value(name = "2022")]
+    #[serde(rename = "2022")]
+    V2022,
+    #[value(name = "2023")]
+    #[serde(rename = "2023")]
+    V2023,
+    #[value(name = "2024")]
+    #[serde(rename = "2024")]
+    V2024,
+}
+
+/// Capability table of a spec version, lists what is allowed in programs.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Capabilities {
+    /// The gamma accumulator can be used.
+    pub gamma_accumulator: bool,
+    /// Operations that can be used with `stackOP`, the stack can always be used with `push` and `pop`.
+    pub stack_operations: &'static [Operation],
+    /// Functions can be used with `call` and `return`.
+    pub call: bool,
+    /// The result of a calculation can be stored in a memory cell, e.g. `p(h1) := a + 1`.
+    pub calc_into_memory_cell: bool,
+    /// The value of a memory cell can be assigned to a memory cell, e.g. `p(h1) := p(h2)`.
+    pub memory_cell_to_memory_cell: bool,
+    /// Spellings of the comparisons that are accepted, e.g. `<=` but not `=<`.
+    pub comparison_tokens: &'static [&'static str],
+}
+
+const CAPABILITIES_2022: Capabilities = Capabilities {
+    gamma_accumulator: false,
+    stack_operations: &[Operation::Add, Operation::Sub],
+    call: false,
+    calc_into_memory_cell: false,
+    memory_cell_to_memory_cell: false,
+    comparison_tokens: &["<", "<=", "=", "==", "!=", ">=", ">"],
+};
+
+const CAPABILITIES_2023: Capabilities = Capabilities {
+    gamma_accumulator: true,
+    stack_operations: &[
+        Operation::Add,
+        Operation::Sub,
+        Operation::Mul,
+        Operation::Div,
+    ],
+    call: true,
+    calc_into_memory_cell: true,
+    memory_cell_to_memory_cell: false,
+    comparison_tokens: &["<", "<=", "≤", "=", "==", "!=", "≠", ">=", "≥", ">"],
+};
+
+const CAPABILITIES_2024: Capabilities = Capabilities {
+    gamma_accumulator: true,
+    stack_operations: &[
+        Operation::Add,
+        Operation::Sub,
+        Operation::Mul,
+        Operation::Div,
+        Operation::Mod,
+    ],
+    call: true,
+    calc_into_memory_cell: true,
+    memory_cell_to_memory_cell: true,
+    comparison_tokens: &["<", "<=", "≤", "=", "==", "!=", "≠", "<>", ">=", "≥", ">"],
+};
+
+impl Spec {
+    /// Returns the capability table of this spec version.
+    pub fn capabilities(self) -> &'static Capabilities {
+        match self {
+            Self::V2022 => &CAPABILITIES_2022,
+            Self::V2023 => &CAPABILITIES_2023,
+            Self::V2024 => &CAPABILITIES_2024,
+        }
+    }
+
+    /// Checks if the instruction is allowed in this spec version, returns the reason if it is not.
+    pub fn check(self, instruction: &Instruction) -> Result<(), String> {
+        let capabilities = self.capabilities();
+        if !capabilities.gamma_accumulator && uses_gamma(instruction) {
+            return Err("the gamma accumulator can not be used".to_string());
+        }
+        match instruction {
+            Instruction::JumpIfCompound(_, _)
+            | Instruction::JumpIfElse(Condition::And(_, _) | Condition::Or(_, _), _, _) => {
+                Err("comparisons can not be combined with '&&' and '||'".to_string())
+            }
+            Instruction::StackOp(op) if !capabilities.stack_operations.contains(op) => {
+                Err(format!("the stack operation 'stack{op}' can not be used"))
+            }
+            Instruction::StackCommand(command) => Err(format!(
+                "the stack instruction 'stack {command}' can not be used"
+            )),
+            Instruction::Peek(_) => Err("'peek' can not be used".to_string()),
+            Instruction::Call(_) | Instruction::Return if !capabilities.call => {
+                Err("functions ('call' and 'return') can not be used".to_string())
+            }
+            Instruction::CalcChain(_, _, _) => {
+                Err("a calculation can only have two operands".to_string())
+            }
+            Instruction::AssignBuiltIn(_, built_in, _) => Err(format!(
+                "the built-in function '{built_in}' can not be used"
+            )),
+            Instruction::Assert(_, _, _) => Err("assertions can not be used".to_string()),
+            Instruction::Print(_) => {
+                Err("the output instruction 'print' can not be used".to_string())
+            }
+            Instruction::Macro(_) => Err("macros can not be used".to_string()),
+            Instruction::ComputedGoto(_) => {
+                Err("jumps to the line in a value, e.g. 'goto a0', can not be used".to_string())
+            }
+            Instruction::Loop(_) => Err("loops ('while' and 'loop') can not be used".to_string()),
+            Instruction::Calc(_, _, op, _) if op.is_bitwise() => {
+                Err(format!("the bitwise operation '{op}' can not be used"))
+            }
+            Instruction::Calc(_, _, Operation::Pow, _) => {
+                Err("the power operation '**' can not be used".to_string())
+            }
+            Instruction::Calc(target, _, _, _)
+                if !capabilities.calc_into_memory_cell && is_memory_cell_target(target) =>
+            {
+                Err("the result of a calculation can not be stored in a memory cell".to_string())
+            }
+            Instruction::Assign(target, value)
+                if !capabilities.memory_cell_to_memory_cell
+                    && is_memory_cell_target(target)
+                    && is_memory_cell_value(value) =>
+            {
+                Err("a memory cell can not be assigned to a memory cell".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Display for Spec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::V2022 => write!(f, "2022"),
+            Self::V2023 => write!(f, "2023"),
+            Self::V2024 => write!(f, "2024"),
+        }
+    }
+}
+
+/// Checks if the operands of the instruction are combined in a way that is allowed by the official grammar,
+/// these rules apply to all versions of the spec.
+///
+/// The spelling of comparisons is checked against the capability table of `spec`, if no version is set
+/// the spellings of the newest version are accepted.
+///
+/// `parts` are the whitespace separated parts from which the instruction was parsed, they are used to mark
+/// the operand that is not allowed.
+pub fn check_operands(
+    instruction: &Instruction,
+    parts: &[&str],
+    spec: Option<Spec>,
+) -> Result<(), InstructionParseError> {
+    let spec = spec.unwrap_or(Spec::V2024);
+    if matches!(
+        instruction,
+        Instruction::JumpIf(_, _, _, _)
+            | Instruction::JumpIfCompound(_, _)
+            | Instruction::JumpIfElse(_, _, _)
+            | Instruction::Assert(_, _, _)
+    ) {
+        let comparison_tokens = spec.capabilities().comparison_tokens;
+        if let Some(idx) = parts
+            .iter()
+            .position(|f| Comparison::try_from(*f).is_ok() && !comparison_tokens.contains(f))
+        {
+            let parts = parts.iter().map(ToString::to_string).collect::<Vec<_>>();
+            return Err(InstructionParseError::OperandNotAllowed(
+                part_range(&parts, idx),
+                parts[idx].clone(),
+                format!(
+                    "The comparison can not be written like this in version {spec} of the spec, use one of: {}",
+                    comparison_tokens.join(" ")
+                ),
+            ));
+        }
+    }
+    let (part_idx, rule) = match instruction {
+        Instruction::Calc(_, value_a, _, _) | Instruction::CalcChain(_, value_a, _)
+            if !is_accumulator(value_a) =>
+        {
+            (
+                2,
+                "The left operand of a calculation has to be an accumulator, e.g. 'a := a + p(h1)'",
+            )
+        }
+        Instruction::CalcChain(_, _, _) => (
+            5,
+            "A calculation can only have two operands, e.g. 'a := a + p(h1)'",
+        ),
+        Instruction::AssignBuiltIn(_, _, _) => (
+            2,
+            "Built-in functions are not part of the grammar, e.g. 'a := abs(a1)' is not allowed",
+        ),
+        Instruction::Assert(_, _, _) => (
+            0,
+            "Assertions are not part of the grammar, e.g. 'assert a < 5' is not allowed",
+        ),
+        Instruction::Print(_) => (
+            0,
+            "Output is not part of the grammar, e.g. 'print a' is not allowed",
+        ),
+        Instruction::Macro(_) => (
+            0,
+            "Macros are not part of the grammar, e.g. 'swap(a, a1)' is not allowed",
+        ),
+        Instruction::StackCommand(_) => (
+            1,
+            "Only operations can be used with 'stack', e.g. 'stack dup' is not allowed",
+        ),
+        Instruction::Peek(_) => (
+            0,
+            "The stack can only be read with 'pop', e.g. 'peek a' is not allowed",
+        ),
+        Instruction::ComputedGoto(_) => (
+            1,
+            "The target of a jump has to be a label, e.g. 'goto loop'",
+        ),
+        Instruction::Loop(_) => (
+            0,
+            "Loops are not part of the grammar, e.g. 'while a > 0 do' is not allowed, use jumps instead",
+        ),
+        Instruction::JumpIf(value_a, _, _, _) if !is_accumulator(value_a) => (
+            1,
+            "The left operand of a comparison has to be an accumulator, e.g. 'if a < p(h1) then goto loop'",
+        ),
+        // the comparisons of a compound condition are separated by one part that contains '&&' or '||'
+        Instruction::JumpIfCompound(condition, _) | Instruction::JumpIfElse(condition, _, _) => match condition
+            .comparisons()
+            .iter()
+            .position(|(value_a, _, _)| !is_accumulator(value_a))
+        {
+            Some(idx) => (
+                1 + idx * 4,
+                "The left operand of a comparison has to be an accumulator, e.g. 'if a < p(h1) then goto loop'",
+            ),
+            None => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+    let parts = parts.iter().map(ToString::to_string).collect::<Vec<_>>();
+    Err(InstructionParseError::OperandNotAllowed(
+        part_range(&parts, part_idx),
+        parts[part_idx].clone(),
+        rule.to_string(),
+    ))
+}
+
+fn is_accumulator(value: &Value) -> bool {
+    matches!(value, Value::Accumulator(_) | Value::Gamma)
+}
+
+fn is_memory_cell_target(target: &TargetType) -> bool {
+    matches!(
+        target,
+        TargetType::MemoryCell(_) | TargetType::IndexMemoryCell(_)
+    )
+}
+
+fn is_memory_cell_value(value: &Value) -> bool {
+    matches!(value, Value::MemoryCell(_) | Value::IndexMemoryCell(_))
+}
+
+/// Returns true if the gamma accumulator is used in the instruction, either directly or as index.
+fn uses_gamma(instruction: &Instruction) -> bool {
+    let target_uses_gamma = |target: &TargetType| {
+        matches!(
+            target,
+            TargetType::Gamma | TargetType::IndexMemoryCell(IndexMemoryCellIndexType::Gamma)
+        )
+    };
+    let value_uses_gamma = |value: &Value| {
+        let value = match value {
+            Value::Negated(v) => v,
+            v => v,
+        };
+        matches!(
+            value,
+            Value::Gamma | Value::IndexMemoryCell(IndexMemoryCellIndexType::Gamma)
+        )
+    };
+    match instruction {
+        Instruction::Assign(target, value) => target_uses_gamma(target) || value_uses_gamma(value),
+        Instruction::Calc(target, value_a, _, value_b) => {
+            target_uses_gamma(target) || value_uses_gamma(value_a) || value_uses_gamma(value_b)
+        }
+        Instruction::AssignBuiltIn(target, _, args) => {
+            target_uses_gamma(target) || args.iter().any(value_uses_gamma)
+        }
+        Instruction::CalcChain(target, value_a, operations) => {
+            target_uses_gamma(target)
+                || value_uses_gamma(value_a)
+                || operations.iter().any(|(_, value)| value_uses_gamma(value))
+        }
+        Instruction::JumpIf(value_a, _, value_b, _) | Instruction::Assert(value_a, _, value_b) => {
+            value_uses_gamma(value_a) || value_uses_gamma(value_b)
+        }
+        Instruction::Print(value) | Instruction::ComputedGoto(value) => value_uses_gamma(value),
+        Instruction::Peek(target) => target_uses_gamma(target),
+        Instruction::Macro(call) => call.body.iter().any(uses_gamma),
+        Instruction::Loop(line) => line.lowered.iter().any(uses_gamma),
+        Instruction::JumpIfCompound(condition, _) | Instruction::JumpIfElse(condition, _, _) => {
+            condition
+                .comparisons()
+                .iter()
+                .any(|(value_a, _, value_b)| value_uses_gamma(value_a) || value_uses_gamma(value_b))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::{error_handling::InstructionParseError, Instruction};
+
+    use super::{check_operands, Spec};
+
+    fn check(spec: Spec, instruction: &str) -> Result<(), String> {
+        spec.check(&Instruction::try_from(instruction).unwrap())
+    }
+
+    #[test]
+    fn test_spec_check() {
+        assert!(check(Spec::V2022, "a := p(h1) + 5").is_ok());
+        assert!(check(Spec::V2022, "stack+").is_ok());
+        assert!(check(Spec::V2022, "stack*").is_err());
+        assert!(check(Spec::V2023, "stack*").is_ok());
+        assert!(check(Spec::V2023, "stack%").is_err());
+        assert!(check(Spec::V2022, "y := 5").is_err());
+        assert!(check(Spec::V2022, "a := p(y)").is_err());
+        assert!(check(Spec::V2022, "a := -y").is_err());
+        assert!(check(Spec::V2023, "y := 5").is_ok());
+        assert!(check(Spec::V2022, "call f").is_err());
+        assert!(check(Spec::V2022, "p(h1) := a + 1").is_err());
+        assert!(check(Spec::V2023, "p(h1) := a + 1").is_ok());
+        assert!(check(Spec::V2023, "p(h1) := p(h2)").is_err());
+        assert!(check(Spec::V2024, "p(h1) := p(h2)").is_ok());
+        assert!(check(Spec::V2024, "stack%").is_ok());
+        assert!(check(Spec::V2024, "if a > 0 && a < 5 then goto loop").is_err());
+        assert!(check(Spec::V2024, "a := a1 + a2 + 1").is_err());
+        assert!(check(Spec::V2024, "a := max(a1, 5)").is_err());
+        assert!(check(Spec::V2024, "a := a1 << 2").is_err());
+        assert!(check(Spec::V2024, "stack&").is_err());
+        assert!(check(Spec::V2024, "a := a1 ** 2").is_err());
+        assert!(check(Spec::V2024, "stack**").is_err());
+        assert!(check(Spec::V2024, "a := isqrt(a1)").is_err());
+        assert!(check(Spec::V2024, "assert a >= 0").is_err());
+        assert!(check(Spec::V2024, "print a").is_err());
+        assert!(check(Spec::V2024, "swap(a, a1)").is_err());
+        assert!(check(Spec::V2024, "while a > 0 do").is_err());
+        assert!(check(Spec::V2024, "goto a0").is_err());
+        assert!(check(Spec::V2024, "stack dup").is_err());
+        assert!(check(Spec::V2024, "peek a").is_err());
+        assert!(check(Spec::V2022, "if a > 5 then goto big else goto small").is_ok());
+        assert!(check(
+            Spec::V2024,
+            "if a > 5 && a < 9 then goto big else goto small"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_check_operands() {
+        let check_parts = |instruction: &str| {
+            let parts = instruction.split_whitespace().collect::<Vec<_>>();
+            check_operands(&Instruction::try_from(&parts).unwrap(), &parts, None)
+        };
+        assert!(check_parts("a := a + p(h1)").is_ok());
+        assert!(check_parts("p(h1) := y * 2").is_ok());
+        assert!(check_parts("if a1 < 5 then goto loop").is_ok());
+        assert!(check_parts("p(h1) := p(h2)").is_ok());
+        assert!(matches!(
+            check_parts("a := p(h1) + a"),
+            Err(InstructionParseError::OperandNotAllowed((5, 9), operand, _)) if operand == "p(h1)"
+        ));
+        assert!(matches!(
+            check_parts("if 5 < a then goto loop"),
+            Err(InstructionParseError::OperandNotAllowed((3, 3), operand, _)) if operand == "5"
+        ));
+        assert!(matches!(
+            check_parts("a := a1 + a2 + 1"),
+            Err(InstructionParseError::OperandNotAllowed((13, 13), operand, _)) if operand == "+"
+        ));
+        let check_spec = |instruction: &str, spec: Option<Spec>| {
+            let parts = instruction.split_whitespace().collect::<Vec<_>>();
+            check_operands(&Instruction::try_from(&parts).unwrap(), &parts, spec)
+        };
+        assert!(check_spec("if a <> 5 then goto loop", None).is_ok());
+        assert!(check_spec("if a ≤ 5 then goto loop", Some(Spec::V2023)).is_ok());
+        assert!(check_spec("if a ≤ 5 then goto loop", Some(Spec::V2022)).is_err());
+        assert!(check_spec("if a <> 5 then goto loop", Some(Spec::V2023)).is_err());
+        assert!(matches!(
+            check_spec("if a > 0 && a =< 5 then goto loop", None),
+            Err(InstructionParseError::OperandNotAllowed((14, 15), operand, _)) if operand == "=<"
+        ));
+        assert!(matches!(
+            check_parts("if a > 0 && p(h1) < a then goto loop"),
+            Err(InstructionParseError::OperandNotAllowed((12, 16), operand, _)) if operand == "p(h1)"
+        ));
+    }
+}
@@ -3,12 +3,17 @@ use std::collections::HashMap;
 use crate::{
     base::{Accumulator, Comparison, MemoryCell, Operation},
     instructions::{
-        assign_index_memory_cell, assign_index_memory_cell_from_value, Identifier,
-        IndexMemoryCellIndexType, Instruction, TargetType, Value, ACCUMULATOR_IDENTIFIER,
-        COMPARISON_IDENTIFIER, CONSTANT_IDENTIFIER, GAMMA_IDENTIFIER, INDEX_MEMORY_CELL_IDENTIFIER,
-        MEMORY_CELL_IDENTIFIER, OPERATOR_IDENTIFIER,
+        assign_index_memory_cell, assign_index_memory_cell_from_value,
+        error_handling::InstructionParseError, BuiltIn, Condition, Identifier,
+        IndexMemoryCellIndexType, Instruction, LoopKind, LoopLine, MacroCall, StackCommand,
+        TargetType, Value, ACCUMULATOR_IDENTIFIER, COMPARISON_IDENTIFIER, CONSTANT_IDENTIFIER,
+        GAMMA_IDENTIFIER, INDEX_MEMORY_CELL_IDENTIFIER, MEMORY_CELL_IDENTIFIER,
+        OPERATOR_IDENTIFIER,
+    },
+    runtime::{
+        error_handling::{CalcError, RuntimeErrorType},
+        ControlFlow, RuntimeMemory, RuntimeSettings,
     },
-    runtime::{error_handling::RuntimeErrorType, ControlFlow, RuntimeMemory, RuntimeSettings},
     utils::test_utils,
 };
 
@@ -31,13 +36,18 @@ fn test_instruction_comparison() {
             Value::Constant(0),
             "label".to_string()
         )
-        .comparison(),
-        Some(&Comparison::Eq)
+        .comparisons(),
+        vec![&Comparison::Eq]
     );
     assert_eq!(
-        Instruction::Assign(TargetType::Gamma, Value::Constant(0)).comparison(),
-        None
+        Instruction::try_from("if a > 0 && a < 5 || p(h1) != 0 then goto label")
+            .unwrap()
+            .comparisons(),
+        vec![&Comparison::Gt, &Comparison::Lt, &Comparison::Neq]
     );
+    assert!(Instruction::Assign(TargetType::Gamma, Value::Constant(0))
+        .comparisons()
+        .is_empty());
 }
 
 #[test]
@@ -49,17 +59,22 @@ fn test_instruction_operation() {
             Operation::Add,
             Value::Constant(0)
         )
-        .operation(),
-        Some(&Operation::Add)
+        .operations(),
+        vec![&Operation::Add]
     );
     assert_eq!(
-        Instruction::StackOp(Operation::Add).operation(),
-        Some(&Operation::Add)
+        Instruction::StackOp(Operation::Add).operations(),
+        vec![&Operation::Add]
     );
     assert_eq!(
-        Instruction::Assign(TargetType::Gamma, Value::Constant(0)).operation(),
-        None
+        Instruction::try_from("a := a1 + a2 * 3")
+            .unwrap()
+            .operations(),
+        vec![&Operation::Add, &Operation::Mul]
     );
+    assert!(Instruction::Assign(TargetType::Gamma, Value::Constant(0))
+        .operations()
+        .is_empty());
 }
 
 #[test]
@@ -167,6 +182,152 @@ fn test_run_assign_accumulator_from_memory_cell() {
     );
 }
 
+#[test]
+fn test_parse_built_in() {
+    assert_eq!(
+        Instruction::try_from("a0 := max(a1, p(h1))"),
+        Ok(Instruction::AssignBuiltIn(
+            TargetType::Accumulator(0),
+            BuiltIn::Max,
+            vec![Value::Accumulator(1), Value::MemoryCell("h1".to_string())]
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("p(h1) := min(a1,-5)"),
+        Ok(Instruction::AssignBuiltIn(
+            TargetType::MemoryCell("h1".to_string()),
+            BuiltIn::Min,
+            vec![Value::Accumulator(1), Value::Constant(-5)]
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("a := abs(p(h1))")
+            .unwrap()
+            .to_string(),
+        "a0 := abs(p(h1))"
+    );
+    assert_eq!(
+        Instruction::try_from("a := min(a1, 5)")
+            .unwrap()
+            .identifier(),
+        format!("{ACCUMULATOR_IDENTIFIER} := min({ACCUMULATOR_IDENTIFIER}, {CONSTANT_IDENTIFIER})")
+    );
+    assert_eq!(
+        Instruction::try_from("a := abs(a1, a2)"),
+        Err(InstructionParseError::WrongArgumentCount(
+            (5, 15),
+            "abs".to_string(),
+            1
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("a := max(a1, xyz)"),
+        Err(InstructionParseError::InvalidExpression(
+            (13, 15),
+            "xyz".to_string()
+        ))
+    );
+    assert!(matches!(
+        Instruction::try_from("a := max(a1, )"),
+        Err(InstructionParseError::MissingExpression {
+            range: (13, 13),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_run_built_in() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    runtime_memory.accumulators.get_mut(&1).unwrap().data = Some(-7);
+    runtime_memory.memory_cells.get_mut("h1").unwrap().data = Some(3);
+    let mut run = |instruction: &str| {
+        Instruction::try_from(instruction)
+            .unwrap()
+            .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+            .map(|()| runtime_memory.accumulators[&0].data)
+    };
+    assert_eq!(run("a := abs(a1)"), Ok(Some(7)));
+    assert_eq!(run("a := min(a1, p(h1))"), Ok(Some(-7)));
+    assert_eq!(run("a := max(a1, p(h1))"), Ok(Some(3)));
+    assert!(run("a := max(a2, 5)").is_err());
+    assert_eq!(run("a := isqrt(p(h1))"), Ok(Some(1)));
+    assert_eq!(run("a := isqrt(49)"), Ok(Some(7)));
+    assert_eq!(run("a := isqrt(50)"), Ok(Some(7)));
+    assert!(run("a := isqrt(a1)").is_err());
+    let value = run("a := rand(a1, p(h1))").unwrap().unwrap();
+    assert!((-7..=3).contains(&value));
+    assert_eq!(run("a := rand(4, 4)"), Ok(Some(4)));
+}
+
+#[test]
+fn test_parse_negated_value() {
+    assert_eq!(
+        Instruction::try_from("a0 := -5"),
+        Ok(Instruction::Assign(
+            TargetType::Accumulator(0),
+            Value::Constant(-5)
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("a0 := -p(h1)"),
+        Ok(Instruction::Assign(
+            TargetType::Accumulator(0),
+            Value::Negated(Box::new(Value::MemoryCell("h1".to_string())))
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("a0 := a1 - -a2").unwrap().to_string(),
+        "a0 := a1 - -a2"
+    );
+    assert_eq!(
+        Instruction::try_from("a0 := --a1"),
+        Err(InstructionParseError::InvalidExpression(
+            (6, 9),
+            "--a1".to_string()
+        ))
+    );
+    assert!(Instruction::try_from("a0 := -xyz").is_err());
+}
+
+#[test]
+fn test_run_negated_value() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    runtime_memory.accumulators.get_mut(&1).unwrap().data = Some(7);
+    Instruction::try_from("a0 := -a1")
+        .unwrap()
+        .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+        .unwrap();
+    assert_eq!(runtime_memory.accumulators[&0].data, Some(-7));
+    runtime_memory.accumulators.get_mut(&1).unwrap().data = Some(i32::MIN);
+    assert!(Instruction::try_from("a0 := -a1")
+        .unwrap()
+        .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+        .is_err());
+}
+
+#[test]
+fn test_run_negative_constant() {
+    let program = r#"
+a0 := -5
+a0 := a0 - 10
+p(h1) := -3
+if a0 < -14 then goto done
+a0 := 0
+done: p(h2) := p(h1) * -2
+    "#;
+    let mut rt = test_utils::runtime_from_str(program).unwrap();
+    rt.run().unwrap();
+    let memory = rt.runtime_memory();
+    assert_eq!(memory.accumulators[&0].data, Some(-15));
+    assert_eq!(memory.memory_cells["h1"].data, Some(-3));
+    assert_eq!(memory.memory_cells["h2"].data, Some(6));
+}
+
 #[test]
 fn test_parse_assign_gamma() {
     assert_eq!(
@@ -601,6 +762,184 @@ fn test_parse_calc_accumulator_with_accumulator_constant() {
     );
 }
 
+#[test]
+fn test_calc_accumulator_modulo() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    let instruction = Instruction::try_from("a0 := a1 % 5").unwrap();
+    assert_eq!(
+        instruction,
+        Instruction::Calc(
+            TargetType::Accumulator(0),
+            Value::Accumulator(1),
+            Operation::Mod,
+            Value::Constant(5)
+        )
+    );
+    runtime_memory.accumulators.get_mut(&1).unwrap().data = Some(17);
+    instruction
+        .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+        .unwrap();
+    assert_eq!(
+        runtime_memory.accumulators.get(&0).unwrap().data.unwrap(),
+        2
+    );
+}
+
+#[test]
+fn test_calc_accumulator_bitwise() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    runtime_memory.accumulators.get_mut(&1).unwrap().data = Some(0b1100);
+    for (instruction, op, operand, result) in [
+        ("a0 := a1 & 10", Operation::And, 10, 0b1000),
+        ("a0 := a1 | 10", Operation::Or, 10, 0b1110),
+        ("a0 := a1 ^ 10", Operation::Xor, 10, 0b0110),
+        ("a0 := a1 << 2", Operation::Shl, 2, 0b110000),
+        ("a0 := a1 >> 2", Operation::Shr, 2, 0b11),
+    ] {
+        let instruction = Instruction::try_from(instruction).unwrap();
+        assert_eq!(
+            instruction,
+            Instruction::Calc(
+                TargetType::Accumulator(0),
+                Value::Accumulator(1),
+                op,
+                Value::Constant(operand)
+            )
+        );
+        instruction
+            .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+            .unwrap();
+        assert_eq!(runtime_memory.accumulators[&0].data, Some(result));
+    }
+    assert_eq!(
+        Instruction::try_from("stack<<"),
+        Ok(Instruction::StackOp(Operation::Shl))
+    );
+}
+
+#[test]
+fn test_calc_accumulator_pow() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    runtime_memory.accumulators.get_mut(&1).unwrap().data = Some(12);
+    let instruction = Instruction::try_from("a0 := a1 ** 2").unwrap();
+    assert_eq!(
+        instruction,
+        Instruction::Calc(
+            TargetType::Accumulator(0),
+            Value::Accumulator(1),
+            Operation::Pow,
+            Value::Constant(2)
+        )
+    );
+    instruction
+        .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+        .unwrap();
+    assert_eq!(runtime_memory.accumulators[&0].data, Some(144));
+    assert_eq!(
+        Instruction::try_from("a0 := a1 ** -1").unwrap().run(
+            &mut runtime_memory,
+            &mut control_flow,
+            &runtime_settings
+        ),
+        Err(RuntimeErrorType::IllegalCalculation {
+            cause: CalcError::AttemptToUseNegativeExponent()
+        })
+    );
+    assert_eq!(
+        Instruction::try_from("stack**"),
+        Ok(Instruction::StackOp(Operation::Pow))
+    );
+}
+
+#[test]
+fn test_calc_chain() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    let instruction = Instruction::try_from("a0 := a1 + a2 * 2 - p(h1)").unwrap();
+    assert_eq!(
+        instruction,
+        Instruction::CalcChain(
+            TargetType::Accumulator(0),
+            Value::Accumulator(1),
+            vec![
+                (Operation::Add, Value::Accumulator(2)),
+                (Operation::Mul, Value::Constant(2)),
+                (Operation::Sub, Value::MemoryCell("h1".to_string())),
+            ]
+        )
+    );
+    assert_eq!(instruction.to_string(), "a0 := a1 + a2 * 2 - p(h1)");
+    assert_eq!(
+        instruction.identifier(),
+        format!(
+            "{ACCUMULATOR_IDENTIFIER} := {ACCUMULATOR_IDENTIFIER} {OPERATOR_IDENTIFIER} {ACCUMULATOR_IDENTIFIER} {OPERATOR_IDENTIFIER} {CONSTANT_IDENTIFIER} {OPERATOR_IDENTIFIER} {MEMORY_CELL_IDENTIFIER}"
+        )
+    );
+    runtime_memory.accumulators.get_mut(&1).unwrap().data = Some(3);
+    runtime_memory.accumulators.get_mut(&2).unwrap().data = Some(4);
+    runtime_memory.memory_cells.get_mut("h1").unwrap().data = Some(1);
+    instruction
+        .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+        .unwrap();
+    // `*` binds stronger than `+` and `-`: 3 + (4 * 2) - 1
+    assert_eq!(
+        runtime_memory.accumulators.get(&0).unwrap().data.unwrap(),
+        10
+    );
+    assert!(matches!(
+        Instruction::try_from("a0 := a1 + a2 +"),
+        Err(InstructionParseError::MissingExpression {
+            range: (15, 15),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_calc_chain_precedence() {
+    let runtime_settings = setup_runtime_settings();
+    for (input, expected) in [
+        ("a0 := 5 + 2 + 3 * 2", 13),
+        ("a0 := 5 * 2 + 3 * 2", 16),
+        ("a0 := 20 - 6 / 3 - 1", 17),
+        ("a0 := 10 - 4 - 3", 3),
+        ("a0 := 2 * 3 ** 2", 18),
+        ("a0 := 2 ** 3 ** 2", 512),
+        ("a0 := 1 + 1 << 2", 8),
+        ("a0 := 6 & 3 | 8", 10),
+        ("a0 := 1 | 2 ^ 3", 1),
+    ] {
+        let mut runtime_memory = setup_runtime_memory();
+        let mut control_flow = ControlFlow::new();
+        Instruction::try_from(input)
+            .unwrap()
+            .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+            .unwrap();
+        assert_eq!(
+            runtime_memory.accumulators.get(&0).unwrap().data,
+            Some(expected),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn test_parse_empty_instruction() {
+    for input in ["", "   "] {
+        assert!(matches!(
+            Instruction::try_from(input),
+            Err(InstructionParseError::MissingExpression { range: (0, 0), .. })
+        ));
+    }
+}
+
 #[test]
 fn test_run_calc_accumulator_with_accumulator_constant() {
     let mut runtime_memory = setup_runtime_memory();
@@ -716,7 +1055,306 @@ fn test_parse_cmp() {
     );
 }
 
-//Add run test for cmp
+#[test]
+fn test_parse_assert() {
+    assert_eq!(
+        Instruction::try_from("assert a0 <= p(h1)"),
+        Ok(Instruction::Assert(
+            Value::Accumulator(0),
+            Comparison::Le,
+            Value::MemoryCell("h1".to_string())
+        ))
+    );
+    assert!(Instruction::try_from("assert a0 <=").is_err());
+    assert!(Instruction::try_from("assert a0 <= 5 then").is_err());
+}
+
+#[test]
+fn test_run_assert() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    Instruction::Assign(TargetType::Accumulator(0), Value::Constant(20))
+        .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+        .unwrap();
+    let assert = |cmp| Instruction::Assert(Value::Accumulator(0), cmp, Value::Constant(40));
+    assert_eq!(
+        assert(Comparison::Lt).run(&mut runtime_memory, &mut control_flow, &runtime_settings),
+        Ok(())
+    );
+    assert_eq!(
+        assert(Comparison::Eq).run(&mut runtime_memory, &mut control_flow, &runtime_settings),
+        Err(RuntimeErrorType::AssertionFailed(
+            "a0 == 40".to_string(),
+            20,
+            40
+        ))
+    );
+    assert_eq!(control_flow.next_instruction_index, 0);
+}
+
+#[test]
+fn test_parse_print() {
+    assert_eq!(
+        Instruction::try_from("print p(h1)"),
+        Ok(Instruction::Print(Value::MemoryCell("h1".to_string())))
+    );
+    assert_eq!(
+        Instruction::try_from("print -5"),
+        Ok(Instruction::Print(Value::Constant(-5)))
+    );
+    assert!(Instruction::try_from("print").is_err());
+    assert!(Instruction::try_from("print a0 a1").is_err());
+}
+
+#[test]
+fn test_parse_macro_call() {
+    assert_eq!(
+        Instruction::try_from("swap(p(h1), a)"),
+        Ok(Instruction::Macro(MacroCall {
+            name: "swap".to_string(),
+            args: vec![Value::MemoryCell("h1".to_string()), Value::Accumulator(0)],
+            body: Vec::new(),
+        }))
+    );
+    assert_eq!(
+        Instruction::try_from("reset()").unwrap().to_string(),
+        "reset()"
+    );
+    assert!(Instruction::try_from("swap(p(h1), a").is_err());
+    assert!(Instruction::try_from("swap(p(h1),)").is_err());
+    // memory cells are not mistaken for the call of a macro
+    assert!(matches!(
+        Instruction::try_from("p(h1)"),
+        Err(InstructionParseError::MissingExpression { .. })
+    ));
+}
+
+#[test]
+fn test_run_print() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    Instruction::Assign(TargetType::Accumulator(0), Value::Constant(20))
+        .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+        .unwrap();
+    for instruction in ["print a0", "print 5"] {
+        Instruction::try_from(instruction)
+            .unwrap()
+            .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+            .unwrap();
+    }
+    assert_eq!(control_flow.output, [20, 5]);
+    control_flow.reset_soft();
+    assert!(control_flow.output.is_empty());
+}
+
+#[test]
+fn test_parse_cmp_compound() {
+    let cmp = |value_a, cmp, value_b| Box::new(Condition::Compare(value_a, cmp, value_b));
+    assert_eq!(
+        Instruction::try_from("if a0 > 0 && p(h1) != 0 then goto loop"),
+        Ok(Instruction::JumpIfCompound(
+            Condition::And(
+                cmp(Value::Accumulator(0), Comparison::Gt, Value::Constant(0)),
+                cmp(
+                    Value::MemoryCell("h1".to_string()),
+                    Comparison::Neq,
+                    Value::Constant(0)
+                )
+            ),
+            "loop".to_string()
+        ))
+    );
+    // && binds stronger than ||
+    assert_eq!(
+        Instruction::try_from("if a < 1 || a > 5 && a1 == 0 then goto loop"),
+        Ok(Instruction::JumpIfCompound(
+            Condition::Or(
+                cmp(Value::Accumulator(0), Comparison::Lt, Value::Constant(1)),
+                Box::new(Condition::And(
+                    cmp(Value::Accumulator(0), Comparison::Gt, Value::Constant(5)),
+                    cmp(Value::Accumulator(1), Comparison::Eq, Value::Constant(0))
+                ))
+            ),
+            "loop".to_string()
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("if a < 1 || a > 5 && a1 == 0 then goto loop")
+            .unwrap()
+            .to_string(),
+        "if a0 < 1 || a0 > 5 && a1 == 0 then goto loop"
+    );
+    assert_eq!(
+        Instruction::try_from("if a0 > 0 && p(h1) != 0 then goto loop")
+            .unwrap()
+            .identifier(),
+        format!(
+            "if {ACCUMULATOR_IDENTIFIER} {COMPARISON_IDENTIFIER} {CONSTANT_IDENTIFIER} && {MEMORY_CELL_IDENTIFIER} {COMPARISON_IDENTIFIER} {CONSTANT_IDENTIFIER} then goto"
+        )
+    );
+    assert!(Instruction::try_from("if a0 > 0 && then goto loop").is_err());
+    assert!(matches!(
+        Instruction::try_from("if a0 > 0 && a1 < 2 goto loop"),
+        Err(InstructionParseError::InvalidExpression((20, 23), s)) if s == "goto"
+    ));
+}
+
+#[test]
+fn test_run_cmp_compound() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    control_flow
+        .instruction_labels
+        .insert("loop".to_string(), 20);
+    let mut run = |instruction: &str, control_flow: &mut ControlFlow| {
+        control_flow.next_instruction_index = 0;
+        Instruction::try_from(instruction)
+            .unwrap()
+            .run(&mut runtime_memory, control_flow, &runtime_settings)
+            .map(|()| control_flow.next_instruction_index)
+    };
+    assert_eq!(run("a := 3", &mut control_flow), Ok(0));
+    assert_eq!(
+        run("if a > 0 && a < 5 then goto loop", &mut control_flow),
+        Ok(20)
+    );
+    assert_eq!(
+        run("if a > 0 && a > 5 then goto loop", &mut control_flow),
+        Ok(0)
+    );
+    assert_eq!(
+        run("if a > 5 || a == 3 then goto loop", &mut control_flow),
+        Ok(20)
+    );
+    assert_eq!(
+        run(
+            "if a > 5 || a < 0 && a == 3 then goto loop",
+            &mut control_flow
+        ),
+        Ok(0)
+    );
+    // the right side is not evaluated, when the result is already known
+    assert_eq!(
+        run("if a < 5 || p(h1) == 0 then goto loop", &mut control_flow),
+        Ok(20)
+    );
+    assert!(run("if a > 5 || p(h1) == 0 then goto loop", &mut control_flow).is_err());
+}
+
+#[test]
+fn test_parse_jump_if_else() {
+    assert_eq!(
+        Instruction::try_from("if a0 > 5 then goto big else goto small"),
+        Ok(Instruction::JumpIfElse(
+            Condition::Compare(Value::Accumulator(0), Comparison::Gt, Value::Constant(5)),
+            "big".to_string(),
+            "small".to_string()
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("if a > 0 && a < 5 then goto in else goto out")
+            .unwrap()
+            .to_string(),
+        "if a0 > 0 && a0 < 5 then goto in else goto out"
+    );
+    assert!(Instruction::try_from("if a0 > 5 then goto big else").is_err());
+    assert!(Instruction::try_from("if a0 > 5 then goto big else small").is_err());
+    assert!(Instruction::try_from("if a0 > 5 then goto big else goto").is_err());
+}
+
+#[test]
+fn test_run_jump_if_else() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    control_flow
+        .instruction_labels
+        .insert("big".to_string(), 20);
+    control_flow
+        .instruction_labels
+        .insert("small".to_string(), 30);
+    let mut run = |instruction: &str, control_flow: &mut ControlFlow| {
+        control_flow.next_instruction_index = 0;
+        Instruction::try_from(instruction)
+            .unwrap()
+            .run(&mut runtime_memory, control_flow, &runtime_settings)
+            .map(|()| control_flow.next_instruction_index)
+    };
+    assert_eq!(run("a := 7", &mut control_flow), Ok(0));
+    assert_eq!(
+        run("if a > 5 then goto big else goto small", &mut control_flow),
+        Ok(20)
+    );
+    assert_eq!(
+        run("if a > 10 then goto big else goto small", &mut control_flow),
+        Ok(30)
+    );
+    assert!(run(
+        "if a > 10 then goto big else goto missing",
+        &mut control_flow
+    )
+    .is_err());
+}
+
+#[test]
+fn test_parse_loop() {
+    assert_eq!(
+        Instruction::try_from("while a0 > 0 do"),
+        Ok(Instruction::Loop(LoopLine::new(LoopKind::While(
+            Condition::Compare(Value::Accumulator(0), Comparison::Gt, Value::Constant(0))
+        ))))
+    );
+    assert_eq!(
+        Instruction::try_from("loop p(h1) times"),
+        Ok(Instruction::Loop(LoopLine::new(LoopKind::Times(
+            Value::MemoryCell("h1".to_string())
+        ))))
+    );
+    for line in [
+        "while a > 0 && a < 5 do",
+        "loop 3 times",
+        "endwhile",
+        "endloop",
+    ] {
+        assert_eq!(
+            Instruction::try_from(line).unwrap().to_string(),
+            line.replace("a ", "a0 ")
+        );
+    }
+    assert!(Instruction::try_from("while a > 0").is_err());
+    assert!(Instruction::try_from("while a > 0 then").is_err());
+    assert!(Instruction::try_from("while a > 0 do a := 1").is_err());
+    assert!(Instruction::try_from("loop 3").is_err());
+    assert!(Instruction::try_from("loop 3 times a").is_err());
+    assert!(Instruction::try_from("endloop 3").is_err());
+}
+
+#[test]
+fn test_condition_negated() {
+    let negated = |condition: &str| {
+        let Ok(Instruction::Loop(line)) = Instruction::try_from(condition) else {
+            panic!("expected loop");
+        };
+        let LoopKind::While(condition) = line.kind else {
+            panic!("expected while");
+        };
+        condition.negated().map(|f| f.to_string())
+    };
+    assert_eq!(negated("while a < 1 do"), Some("a0 >= 1".to_string()));
+    assert_eq!(
+        negated("while a < 1 || a > 5 do"),
+        Some("a0 >= 1 && a0 <= 5".to_string())
+    );
+    assert_eq!(
+        negated("while a > 1 && a1 == 5 && a2 != 0 do"),
+        Some("a0 <= 1 || a1 != 5 || a2 == 0".to_string())
+    );
+    // the negation would need parentheses
+    assert_eq!(negated("while a < 1 || a > 5 && a1 == 0 do"), None);
+}
 
 #[test]
 fn test_parse_goto() {
@@ -726,6 +1364,55 @@ fn test_parse_goto() {
     );
 }
 
+#[test]
+fn test_parse_computed_goto() {
+    assert_eq!(
+        Instruction::try_from("goto a0"),
+        Ok(Instruction::ComputedGoto(Value::Accumulator(0)))
+    );
+    assert_eq!(
+        Instruction::try_from("goto p(h1)"),
+        Ok(Instruction::ComputedGoto(Value::MemoryCell(
+            "h1".to_string()
+        )))
+    );
+    assert_eq!(
+        Instruction::try_from("goto p(a1)").unwrap().to_string(),
+        "goto p(a1)"
+    );
+    // the short forms and constants are labels
+    for label in ["a", "y", "5", "steps"] {
+        assert_eq!(
+            Instruction::try_from(format!("goto {label}").as_str()),
+            Ok(Instruction::Goto(label.to_string()))
+        );
+    }
+}
+
+#[test]
+fn test_run_computed_goto() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    control_flow.source_lines = (1..=10).collect();
+    let mut run = |instruction: &str, control_flow: &mut ControlFlow| {
+        Instruction::try_from(instruction)
+            .unwrap()
+            .run(&mut runtime_memory, control_flow, &runtime_settings)
+            .map(|()| control_flow.next_instruction_index)
+    };
+    assert_eq!(run("p(h1) := 4", &mut control_flow), Ok(0));
+    // the value is the line, which is counted from 1
+    assert_eq!(run("goto p(h1)", &mut control_flow), Ok(3));
+    assert_eq!(run("a := 10", &mut control_flow), Ok(3));
+    assert_eq!(run("goto a0", &mut control_flow), Ok(9));
+    run("a := 11", &mut control_flow).unwrap();
+    assert_eq!(
+        run("goto a0", &mut control_flow),
+        Err(RuntimeErrorType::JumpTargetOutOfRange(11, 10))
+    );
+}
+
 #[test]
 fn test_run_goto() {
     let mut runtime_memory = setup_empty_runtime_memory();
@@ -785,6 +1472,77 @@ fn test_parse_pop() {
     assert_eq!(Instruction::try_from("pop"), Ok(Instruction::Pop));
 }
 
+#[test]
+fn test_parse_stack_command() {
+    assert_eq!(
+        Instruction::try_from("stack dup"),
+        Ok(Instruction::StackCommand(StackCommand::Dup))
+    );
+    assert_eq!(
+        Instruction::try_from("stack swap"),
+        Ok(Instruction::StackCommand(StackCommand::Swap))
+    );
+    assert_eq!(
+        Instruction::try_from("stack drop").unwrap().to_string(),
+        "stack drop"
+    );
+    assert!(Instruction::try_from("stack rotate").is_err());
+    assert!(Instruction::try_from("stackdup").is_err());
+}
+
+#[test]
+fn test_parse_peek() {
+    assert_eq!(
+        Instruction::try_from("peek"),
+        Ok(Instruction::Peek(TargetType::Accumulator(0)))
+    );
+    assert_eq!(
+        Instruction::try_from("peek p(h1)"),
+        Ok(Instruction::Peek(TargetType::MemoryCell("h1".to_string())))
+    );
+    assert_eq!(
+        Instruction::try_from("peek a").unwrap().to_string(),
+        "peek a0"
+    );
+    assert!(Instruction::try_from("peek 5").is_err());
+    assert!(Instruction::try_from("peek a a1").is_err());
+}
+
+#[test]
+fn test_run_stack_command_and_peek() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    runtime_memory.stack = vec![1, 2];
+    let mut run = |instruction: &str, runtime_memory: &mut RuntimeMemory| {
+        Instruction::try_from(instruction)
+            .unwrap()
+            .run(runtime_memory, &mut control_flow, &runtime_settings)
+            .map(|()| runtime_memory.stack.clone())
+    };
+    assert_eq!(run("stack dup", &mut runtime_memory), Ok(vec![1, 2, 2]));
+    assert_eq!(run("stack drop", &mut runtime_memory), Ok(vec![1, 2]));
+    assert_eq!(run("stack swap", &mut runtime_memory), Ok(vec![2, 1]));
+    assert_eq!(run("peek p(h1)", &mut runtime_memory), Ok(vec![2, 1]));
+    assert_eq!(runtime_memory.memory_cells["h1"].data, Some(1));
+    // the accumulator is not changed by the commands
+    assert_eq!(runtime_memory.accumulators[&0].data, None);
+    run("stack drop", &mut runtime_memory).unwrap();
+    assert_eq!(
+        run("stack swap", &mut runtime_memory),
+        Err(RuntimeErrorType::StackCommandFail(StackCommand::Swap, 2))
+    );
+    run("stack drop", &mut runtime_memory).unwrap();
+    assert_eq!(
+        run("stack dup", &mut runtime_memory),
+        Err(RuntimeErrorType::StackCommandFail(StackCommand::Dup, 1))
+    );
+    assert_eq!(
+        run("peek", &mut runtime_memory),
+        Err(RuntimeErrorType::PeekFail)
+    );
+}
+
 #[test]
 fn test_run_stack_op() {
     run_stack_op(Operation::Add, 15);
@@ -974,7 +1732,7 @@ p(h4) := p(d) * p(z)
 p(c) := p(h1) + p(h2)
 p(d) := p(h3) + p(h4)
     "#;
-    let mut runtime = test_utils::runtime_from_str(&instructions).unwrap();
+    let mut runtime = test_utils::runtime_from_str(instructions).unwrap();
     runtime.run().unwrap();
     assert_eq!(
         runtime
@@ -1028,7 +1786,7 @@ p(a) := p(a) - 1
 a1 := p(a)
 if a1 > 0 then goto loop
     "#;
-    let mut rt = test_utils::runtime_from_str(&program).unwrap();
+    let mut rt = test_utils::runtime_from_str(program).unwrap();
     rt.run().unwrap();
     assert_eq!(
         rt.runtime_memory()
@@ -1055,7 +1813,7 @@ call func
 a := p(h3)
 return
     "#;
-    let mut rt = test_utils::runtime_from_str(&program).unwrap();
+    let mut rt = test_utils::runtime_from_str(program).unwrap();
     rt.run().unwrap();
     assert_eq!(
         rt.runtime_memory()
@@ -1068,6 +1826,65 @@ return
     );
 }
 
+#[test]
+fn test_parse_steps() {
+    assert_eq!(
+        Instruction::try_from("a0 := steps"),
+        Ok(Instruction::Assign(
+            TargetType::Accumulator(0),
+            Value::Steps
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("if steps > 5 then goto END"),
+        Ok(Instruction::JumpIf(
+            Value::Steps,
+            Comparison::Gt,
+            Value::Constant(5),
+            "END".to_string()
+        ))
+    );
+    assert!(Instruction::try_from("steps := 5").is_err());
+}
+
+#[test]
+fn test_run_steps() {
+    let program = r#"
+a := 0
+loop: a := a + 1
+if a < 3 then goto loop
+p(h1) := steps
+    "#;
+    let mut rt = test_utils::runtime_from_str(program).unwrap();
+    rt.run().unwrap();
+    // the empty first line is not counted
+    assert_eq!(
+        rt.runtime_memory()
+            .memory_cells
+            .get("h1")
+            .unwrap()
+            .data
+            .unwrap(),
+        7
+    );
+    rt.reset();
+    assert_eq!(rt.control_flow().steps, 0);
+}
+
+#[test]
+fn test_run_line_hits() {
+    let program = r#"
+a := 0
+loop: a := a + 1
+if a < 3 then goto loop
+    "#;
+    let mut rt = test_utils::runtime_from_str(program).unwrap();
+    rt.run().unwrap();
+    assert_eq!(rt.line_hits()[1..4], [1, 3, 3]);
+    rt.reset();
+    assert!(rt.line_hits().iter().all(|f| *f == 0));
+}
+
 /// Sets up runtime runtime_memory in a consistent way because the default implementation for memory cells and accumulators is configgurable.
 fn setup_runtime_memory() -> RuntimeMemory {
     let mut runtime_memory = RuntimeMemory::new_debug(TEST_MEMORY_CELL_LABELS);
@@ -1155,7 +1972,7 @@ fn test_try_value_from_string_usize_usize_tuple() {
 
 #[test]
 fn test_assign_index_memory_cell() {
-    let mut runtime_memory = RuntimeMemory::new_debug(&[&""]);
+    let mut runtime_memory = RuntimeMemory::new_debug(&[""]);
     let mut runtime_settings = setup_runtime_settings();
     runtime_settings.autodetect_index_memory_cells = true;
     assert_eq!(
@@ -1171,12 +1988,13 @@ fn test_assign_index_memory_cell() {
 
 #[test]
 fn test_assign_index_memory_cell_from_value() {
-    let mut runtime_memory = RuntimeMemory::new_debug(&[&""]);
+    let mut runtime_memory = RuntimeMemory::new_debug(&[""]);
     let mut runtime_settings = setup_runtime_settings();
     runtime_settings.autodetect_index_memory_cells = true;
     assert_eq!(
         assign_index_memory_cell_from_value(
             &mut runtime_memory,
+            &ControlFlow::new(),
             &runtime_settings,
             0,
             &Value::Constant(5)
@@ -1187,6 +2005,7 @@ fn test_assign_index_memory_cell_from_value() {
     assert_eq!(
         assign_index_memory_cell_from_value(
             &mut runtime_memory,
+            &ControlFlow::new(),
             &runtime_settings,
             1,
             &Value::Constant(5)
@@ -1526,3 +2345,18 @@ fn test_instruction_identifier_imc() {
         "M(M) := A".to_string()
     );
 }
+
+#[test]
+fn test_instruction_description() {
+    assert_eq!(
+        Instruction::try_from("a := max(a1, 5)")
+            .unwrap()
+            .description(),
+        "Assigns the larger of both values to the location on the left."
+    );
+    assert_eq!(
+        Instruction::try_from("stack+").unwrap().description(),
+        "Replaces the two top values of the stack with the result of the operation, which is also assigned to α0."
+    );
+    assert_eq!(Instruction::Noop.description(), "Does nothing.");
+}
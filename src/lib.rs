@@ -0,0 +1,54 @@
+//! Runtime environment for the alpha notation.
+//!
+//! The crate is used by the `alpha_tui` binary, but the runtime can also be embedded into other tools, e.g. to run
+//! and grade programs without starting the tui:
+//!
+//! ```
+//! use alpha_tui::{GlobalArgs, RuntimeBuilder};
+//!
+//! let program = vec!["a := 5".to_string(), "p(h1) := a * 2".to_string()];
+//! let mut builder = RuntimeBuilder::new(&program, "program.alpha").unwrap();
+//! builder.apply_global_cli_args(&GlobalArgs::default()).unwrap();
+//! let mut runtime = builder.build().unwrap();
+//! runtime.run().unwrap();
+//! assert_eq!(runtime.runtime_memory().memory_cells["h1"].data, Some(10));
+//! ```
+
+/// The application itself
+#[doc(hidden)]
+pub mod app;
+/// Contains all required data types used to run programs
+pub mod base;
+/// Version and build information
+pub mod build_info;
+/// Command line parsing
+pub mod cli;
+/// Settings that are distributed with an exercise
+#[doc(hidden)]
+pub mod exercise;
+/// Entry points of the fuzz targets, compiled in tests as well so that they are tested without the feature
+#[cfg(any(feature = "fuzz", test))]
+#[doc(hidden)]
+pub mod fuzz;
+/// Supported instructions
+pub mod instructions;
+/// Project manifest
+#[doc(hidden)]
+pub mod manifest;
+/// Progress of the exercises the user completed
+#[doc(hidden)]
+pub mod progress;
+/// Anonymized summaries of runs for dashboards
+#[doc(hidden)]
+pub mod report;
+/// Program execution
+pub mod runtime;
+/// Utility functions
+pub mod utils;
+
+pub use cli::{CheckLoadArgs, GlobalArgs, InstructionLimitingArgs, MemoryArgs};
+pub use instructions::Instruction;
+pub use runtime::{
+    builder::{RuntimeBuilder, RuntimeLimits, Span},
+    Runtime, RuntimeMemory, RuntimeSettings,
+};
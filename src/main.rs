@@ -1,33 +1,54 @@
-use app::commands;
-use clap::Parser;
-use cli::Cli;
+use alpha_tui::{
+    app::commands,
+    build_info::BuildInfo,
+    cli::{self, Cli, Command},
+    exercise, manifest, utils,
+};
+use clap::{error::ErrorKind, CommandFactory, Parser};
 use miette::Result;
 
-use crate::cli::Command;
+fn main() -> Result<()> {
+    let mut cli = Cli::parse();
+
+    if cli.version_json {
+        println!("{}", BuildInfo::current().to_json());
+        return Ok(());
+    }
+    if cli.version {
+        if cli.verbose {
+            println!("{}", BuildInfo::current().to_text());
+        } else {
+            println!("alpha_tui {}", env!("CARGO_PKG_VERSION"));
+        }
+        return Ok(());
+    }
+    let Some(mut command) = cli.command.take() else {
+        Cli::command()
+            .error(ErrorKind::MissingSubcommand, "a subcommand is required")
+            .exit();
+    };
 
-/// The application itself
-mod app;
-/// Contains all required data types used to run programs
-mod base;
-/// Command line parsing
-mod cli;
-/// Supported instructions
-mod instructions;
-/// Program execution
-mod runtime;
-/// Utility functions
-mod utils;
+    // read program file and options from the project manifest, if no program file is provided
+    manifest::apply_manifest(&mut command, &mut cli.global_args.memory_config_file)?;
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+    // apply the settings of the exercise, they can't be overridden if --enforce-settings is set
+    exercise::apply_exercise_settings(&mut command, cli.global_args.memory_config_file.as_ref())?;
 
     // perform additional validation checks on provided cli arguments
-    cli::validate_arguments(&cli)?;
+    cli::validate_arguments(&command, &cli.global_args)?;
 
-    let input_file = match cli.command {
-        Command::Load(ref args) => Some(args.file.clone()),
-        Command::Check(ref args) => Some(args.file.clone()),
-        Command::Playground(_) => None,
+    let input_file = match command {
+        Command::Load(ref args) => args.file.clone(),
+        Command::Check(ref args) => args.file.clone(),
+        Command::Playground(_)
+        | Command::Repro(_)
+        | Command::Format(_)
+        | Command::Lint(_)
+        | Command::Progress(_)
+        | Command::State(_)
+        | Command::Test(_)
+        | Command::Session(_)
+        | Command::Profile(_) => None,
     };
 
     if cli.global_args.disable_instruction_limit {
@@ -36,13 +57,16 @@ fn main() -> Result<()> {
         );
     }
 
-    match &cli.command {
+    match &command {
         Command::Check(check_args) => commands::check::check(
             &cli.global_args,
             check_args,
             read_file(input_file.as_ref().unwrap())?,
             &input_file.unwrap(),
         ),
+        Command::Load(load_args) if load_args.attach.is_some() => {
+            commands::attach::attach(&cli.global_args, load_args)?;
+        }
         Command::Load(load_args) => commands::load::load(
             &cli.global_args,
             load_args,
@@ -52,6 +76,18 @@ fn main() -> Result<()> {
         Command::Playground(playground_args) => {
             commands::playground::playground(&cli.global_args, playground_args)?
         }
+        Command::Repro(repro_args) => commands::repro::repro(&cli.global_args, repro_args)?,
+        Command::Format(format_args) => commands::format::format(format_args),
+        Command::Lint(lint_args) => commands::lint::lint(&cli.global_args, lint_args),
+        Command::Progress(progress_args) => {
+            commands::progress::progress(&cli.global_args, progress_args)?
+        }
+        Command::State(state_args) => commands::state::state(state_args),
+        Command::Test(test_args) => commands::test::test(&cli.global_args, test_args),
+        Command::Session(session_args) => commands::session::session(session_args),
+        Command::Profile(profile_args) => {
+            commands::profile::profile(&cli.global_args, profile_args)
+        }
     }
     Ok(())
 }
@@ -59,6 +95,6 @@ fn main() -> Result<()> {
 fn read_file(path: &str) -> Result<Vec<String>> {
     match utils::read_file(path) {
         Ok(i) => Ok(i),
-        Err(e) => Err(miette::miette!("Unable to read file [{}]: {}", &path, e)),
+        Err(e) => Err(e.wrap_err(format!("Unable to read file [{}]", &path))),
     }
 }
@@ -0,0 +1,249 @@
+use std::path::Path;
+
+use miette::{miette, Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    cli::{Command, TestArgs, TestCommand},
+    utils,
+};
+
+/// Name of the project manifest file, it is read from the working directory.
+pub const MANIFEST_FILE: &str = "alpha.toml";
+
+/// Project manifest that declares the program and the files that should be used to load it.
+///
+/// Paths are relative to the working directory.
+#[derive(Debug, Default, PartialEq, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Manifest {
+    /// Program that is loaded.
+    pub program: Option<String>,
+    /// Memory config file, see `--memory-config-file`.
+    pub memory_config_file: Option<String>,
+    /// Allowed instructions file, see `--allowed-instructions-file`.
+    pub allowed_instructions_file: Option<String>,
+    /// Test case files recorded with `test record`, they are run by `test run` if no files are provided.
+    #[serde(default)]
+    pub tests: Vec<String>,
+    /// Toml file that contains test cases for the program, used by `test cases` if no file is provided.
+    pub test_cases: Option<String>,
+}
+
+impl Manifest {
+    /// Tries to parse the provided file into a manifest.
+    pub fn try_from_file(path: &str) -> Result<Self> {
+        match toml::from_str::<Manifest>(&utils::read_file(path)?.join("\n")) {
+            Ok(manifest) => Ok(manifest),
+            Err(e) => Err(miette!("toml parse error: {e}")),
+        }
+    }
+}
+
+/// Reads the project manifest from the working directory, if no program file was provided for `load`, `check` or
+/// `test`, or if no test case files were provided for `test run`.
+///
+/// Values of the manifest are only used, if the corresponding cli option is not set.
+pub fn apply_manifest(
+    command: &mut Command,
+    memory_config_file: &mut Option<String>,
+) -> Result<()> {
+    let (file, check_load_args) = match command {
+        // the program is received from the shared session
+        Command::Load(args) if args.attach.is_some() => return Ok(()),
+        Command::Load(args) => (&mut args.file, &mut args.check_load_args),
+        Command::Check(args) => (&mut args.file, &mut args.check_load_args),
+        Command::Test(args) => return apply_test_manifest(args, memory_config_file),
+        Command::Playground(_)
+        | Command::Repro(_)
+        | Command::Format(_)
+        | Command::Lint(_)
+        | Command::Progress(_)
+        | Command::State(_)
+        | Command::Session(_)
+        | Command::Profile(_) => return Ok(()),
+    };
+    if file.is_some() {
+        return Ok(());
+    }
+    let manifest = read_manifest("program file", "program = \"FILE\"")?;
+    *file = Some(manifest_program(&manifest)?);
+    // memory config file conflicts with these options
+    if memory_config_file.is_none()
+        && check_load_args.memory_args.accumulators.is_none()
+        && check_load_args.memory_args.memory_cells.is_none()
+        && check_load_args.memory_args.index_memory_cells.is_none()
+        && !check_load_args.memory_args.enable_gamma_accumulator
+    {
+        *memory_config_file = manifest.memory_config_file;
+    }
+    let instruction_limiting_args = &mut check_load_args.instruction_limiting_args;
+    if instruction_limiting_args
+        .allowed_instructions_file
+        .is_none()
+    {
+        instruction_limiting_args.allowed_instructions_file = manifest.allowed_instructions_file;
+    }
+    Ok(())
+}
+
+/// Reads the program, the test case files and the memory config file of the `test` subcommands from the project
+/// manifest, if they are not provided.
+fn apply_test_manifest(args: &mut TestArgs, memory_config_file: &mut Option<String>) -> Result<()> {
+    let manifest = match &args.command {
+        TestCommand::Record(args) if args.file.is_some() => return Ok(()),
+        TestCommand::Run(args) if !args.files.is_empty() => return Ok(()),
+        TestCommand::Cases(args) if args.file.is_some() && args.cases.is_some() => return Ok(()),
+        TestCommand::Run(_) => read_manifest("test case files", "tests = [\"FILE\"]")?,
+        TestCommand::Record(_) | TestCommand::Cases(_) => {
+            read_manifest("program file", "program = \"FILE\"")?
+        }
+    };
+    set_test_args(args, memory_config_file, manifest)
+}
+
+/// Sets the arguments of the `test` subcommands that were not provided to the values of `manifest`.
+fn set_test_args(
+    args: &mut TestArgs,
+    memory_config_file: &mut Option<String>,
+    manifest: Manifest,
+) -> Result<()> {
+    match &mut args.command {
+        TestCommand::Record(args) => args.file = Some(manifest_program(&manifest)?),
+        TestCommand::Run(args) => {
+            if manifest.tests.is_empty() {
+                return Err(miette!(
+                    help = format!("Specify the test case files or list them in {MANIFEST_FILE}: tests = [\"FILE\"]"),
+                    "No test case files specified and {MANIFEST_FILE} does not list any tests"
+                ));
+            }
+            args.files = manifest.tests;
+        }
+        TestCommand::Cases(args) => {
+            if args.file.is_none() {
+                args.file = Some(manifest_program(&manifest)?);
+            }
+            if args.cases.is_none() {
+                let Some(cases) = manifest.test_cases else {
+                    return Err(miette!(
+                        help = format!("Specify the test case file or set it in {MANIFEST_FILE}: test-cases = \"FILE\""),
+                        "No test case file specified and {MANIFEST_FILE} does not specify test cases"
+                    ));
+                };
+                args.cases = Some(cases);
+            }
+        }
+    }
+    if memory_config_file.is_none() {
+        *memory_config_file = manifest.memory_config_file;
+    }
+    Ok(())
+}
+
+/// Reads the project manifest from the working directory, returns an error if it does not exist.
+///
+/// `missing` names the cli argument that was not provided and is read from the manifest instead, e.g. `program file`,
+/// `entry` is the entry of the manifest that provides it.
+fn read_manifest(missing: &str, entry: &str) -> Result<Manifest> {
+    if !Path::new(MANIFEST_FILE).exists() {
+        return Err(miette!(
+            help = format!(
+                "Specify the {missing} or create the file {MANIFEST_FILE} that contains: {entry}"
+            ),
+            "No {missing} specified and no {MANIFEST_FILE} found in the working directory"
+        ));
+    }
+    Manifest::try_from_file(MANIFEST_FILE)
+        .wrap_err(format!("Unable to read project manifest [{MANIFEST_FILE}]"))
+}
+
+/// Returns the program of the manifest, returns an error if it does not specify a program.
+fn manifest_program(manifest: &Manifest) -> Result<String> {
+    manifest
+        .program
+        .clone()
+        .ok_or_else(|| miette!("{MANIFEST_FILE} does not specify a program"))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::cli::{Cli, Command, TestArgs, TestCommand};
+
+    use super::{set_test_args, Manifest};
+
+    fn test_args(args: &[&str]) -> TestArgs {
+        let cli = Cli::try_parse_from([&["alpha_tui", "test"], args].concat()).unwrap();
+        match cli.command {
+            Some(Command::Test(args)) => args,
+            _ => panic!("not a test command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = toml::from_str::<Manifest>(
+            r#"
+            program = "main.alpha"
+            memory-config-file = "memory.json"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            manifest,
+            Manifest {
+                program: Some("main.alpha".to_string()),
+                memory_config_file: Some("memory.json".to_string()),
+                ..Default::default()
+            }
+        );
+        assert!(toml::from_str::<Manifest>("libraries = [\"lib.alpha\"]").is_err());
+    }
+
+    #[test]
+    fn test_manifest_test_suite() {
+        let manifest = toml::from_str::<Manifest>(
+            r#"
+            program = "main.alpha"
+            memory-config-file = "memory.json"
+            tests = ["tests/a.test.json", "tests/b.test.json"]
+            test-cases = "cases.toml"
+            "#,
+        )
+        .unwrap();
+        let mut memory_config_file = None;
+        let mut args = test_args(&["run"]);
+        set_test_args(&mut args, &mut memory_config_file, manifest.clone()).unwrap();
+        let TestCommand::Run(run_args) = args.command else {
+            panic!("not test run");
+        };
+        assert_eq!(
+            run_args.files,
+            vec!["tests/a.test.json", "tests/b.test.json"]
+        );
+        assert_eq!(memory_config_file, Some("memory.json".to_string()));
+
+        // the arguments on the command line take precedence
+        let mut memory_config_file = Some("other.json".to_string());
+        let mut args = test_args(&["cases", "other.alpha"]);
+        set_test_args(&mut args, &mut memory_config_file, manifest.clone()).unwrap();
+        let TestCommand::Cases(cases_args) = args.command else {
+            panic!("not test cases");
+        };
+        assert_eq!(cases_args.file, Some("other.alpha".to_string()));
+        assert_eq!(cases_args.cases, Some("cases.toml".to_string()));
+        assert_eq!(memory_config_file, Some("other.json".to_string()));
+
+        let mut args = test_args(&["record"]);
+        set_test_args(&mut args, &mut None, manifest).unwrap();
+        let TestCommand::Record(record_args) = args.command else {
+            panic!("not test record");
+        };
+        assert_eq!(record_args.file, Some("main.alpha".to_string()));
+
+        // a suite without tests can't be run
+        let mut args = test_args(&["run"]);
+        assert!(set_test_args(&mut args, &mut None, Manifest::default()).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use std::{collections::BTreeMap, path::Path};
+
+use directories::UserDirs;
+use miette::{miette, IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{exercise::ExerciseSettings, utils};
+
+/// Settings of the exercises that are bundled with alpha_tui in `examples/exercises`.
+const BUNDLED_EXERCISES: [&str; 3] = [
+    include_str!("../examples/exercises/sum/exercise.toml"),
+    include_str!("../examples/exercises/factorial/exercise.toml"),
+    include_str!("../examples/exercises/power/exercise.toml"),
+];
+
+/// Exercises that were completed by the user, recorded when `check run` finishes with the expected state of an
+/// exercise.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    /// Completed exercises, by the name of the exercise.
+    pub completed: BTreeMap<String, CompletedExercise>,
+}
+
+/// Solution with which an exercise was completed.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct CompletedExercise {
+    /// Program file of the solution.
+    pub program: String,
+    /// Number of steps the solution took, the solution with the least steps is kept.
+    pub steps: usize,
+}
+
+impl Progress {
+    /// Returns the path of the progress file in the config directory (`~/.config/alpha_tui` in linux or
+    /// `%APPDATA%/alpha_tui` in windows), `None` if the home directory can not be determined.
+    pub fn default_path() -> Option<String> {
+        let user_dirs = UserDirs::new()?;
+        let base_dir = user_dirs.home_dir().to_str()?;
+        Some(format!("{base_dir}/.config/alpha_tui/progress.json"))
+    }
+
+    /// Tries to read the progress from the file at `path`, if the file does not exist, no exercise is completed.
+    pub fn try_from_file(path: &str) -> Result<Self> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(&utils::read_file(path)?.join("\n"))
+            .map_err(|e| miette!("Unable to parse progress from {path}: {e}"))
+    }
+
+    /// Writes the progress to the file at `path`, missing directories are created.
+    pub fn write_to_file(&self, path: &str) -> Result<()> {
+        if let Some(dir) = Path::new(path).parent() {
+            std::fs::create_dir_all(dir).into_diagnostic()?;
+        }
+        let json = serde_json::to_string_pretty(self).into_diagnostic()?;
+        utils::write_file_atomic(path, json.as_bytes())
+            .map_err(|e| miette!("Unable to write progress to {path}: {e}"))
+    }
+
+    /// Records that the exercise `name` was completed by `program` in `steps` steps.
+    ///
+    /// Returns true if the exercise was not completed before.
+    pub fn complete(&mut self, name: &str, program: &str, steps: usize) -> bool {
+        let solution = CompletedExercise {
+            program: program.to_string(),
+            steps,
+        };
+        match self.completed.get_mut(name) {
+            Some(completed) => {
+                if steps < completed.steps {
+                    *completed = solution;
+                }
+                false
+            }
+            None => {
+                self.completed.insert(name.to_string(), solution);
+                true
+            }
+        }
+    }
+
+    /// Returns the lines of the progress overview, the bundled exercises are listed first, followed by the other
+    /// exercises that were completed.
+    pub fn overview(&self) -> Vec<String> {
+        let bundled = bundled_exercises();
+        let completed_bundled = bundled
+            .iter()
+            .filter(|(name, _)| self.completed.contains_key(name))
+            .count();
+        let mut lines = vec![
+            format!(
+                "Progress: {completed_bundled}/{} bundled exercises completed",
+                bundled.len()
+            ),
+            String::new(),
+        ];
+        let width = bundled
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        for (name, description) in &bundled {
+            lines.push(format!(
+                "{} {name:width$}  {description}{}",
+                self.marker(name),
+                self.steps(name)
+            ));
+        }
+        let others = self
+            .completed
+            .keys()
+            .filter(|name| !bundled.iter().any(|(bundled, _)| bundled == *name))
+            .collect::<Vec<_>>();
+        if !others.is_empty() {
+            lines.push(String::new());
+            lines.push("Other completed exercises:".to_string());
+            for name in others {
+                lines.push(format!("{} {name}{}", self.marker(name), self.steps(name)));
+            }
+        }
+        lines
+    }
+
+    fn marker(&self, name: &str) -> &'static str {
+        if self.completed.contains_key(name) {
+            "[x]"
+        } else {
+            "[ ]"
+        }
+    }
+
+    fn steps(&self, name: &str) -> String {
+        self.completed
+            .get(name)
+            .map(|f| format!(" ({} steps)", f.steps))
+            .unwrap_or_default()
+    }
+}
+
+/// Returns the names and descriptions of the bundled exercises.
+fn bundled_exercises() -> Vec<(String, String)> {
+    BUNDLED_EXERCISES
+        .iter()
+        .map(|settings| {
+            let settings = toml::from_str::<ExerciseSettings>(settings)
+                .expect("bundled exercise settings should be valid");
+            (
+                settings.name.expect("bundled exercise should have a name"),
+                settings.description.unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bundled_exercises, Progress};
+
+    #[test]
+    fn test_progress_complete() {
+        let mut progress = Progress::default();
+        assert!(progress.complete("sum", "a.alpha", 30));
+        assert!(!progress.complete("sum", "b.alpha", 40));
+        assert_eq!(progress.completed["sum"].program, "a.alpha");
+        assert!(!progress.complete("sum", "c.alpha", 20));
+        assert_eq!(progress.completed["sum"].program, "c.alpha");
+    }
+
+    #[test]
+    fn test_progress_overview() {
+        assert_eq!(bundled_exercises().len(), 3);
+        let mut progress = Progress::default();
+        progress.complete("factorial", "factorial.alpha", 25);
+        progress.complete("homework_1", "homework.alpha", 7);
+        let overview = progress.overview();
+        assert_eq!(overview[0], "Progress: 1/3 bundled exercises completed");
+        assert!(overview[2].starts_with("[ ] sum        Calculate the sum"));
+        assert!(overview[3].starts_with("[x] factorial  Calculate the factorial"));
+        assert!(overview[3].ends_with("use a loop (25 steps)"));
+        assert_eq!(
+            overview[6..],
+            ["Other completed exercises:", "[x] homework_1 (7 steps)"]
+        );
+    }
+}
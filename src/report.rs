@@ -0,0 +1,191 @@
+use std::time::Duration;
+
+use miette::{miette, Diagnostic, IntoDiagnostic, Result};
+use serde::Serialize;
+
+/// Time after which connecting to, writing to and reading from the report url is aborted, so that a dashboard that
+/// is not reachable does not block the check.
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Anonymized summary of a run, posted as json to `--report-url` when `check run` ends.
+///
+/// The summary does not contain the program, file names or values of the memory, only a hash of the program, so
+/// that runs of the same program can be grouped by a dashboard.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RunReport {
+    /// Hash of the program, see [`program_hash`].
+    pub program_hash: String,
+    /// Name of the exercise the program belongs to, if it has one.
+    pub exercise: Option<String>,
+    pub result: RunResult,
+    /// Number of steps that were run.
+    pub steps: usize,
+    /// Code of the runtime error, e.g. `runtime_error::accumulator_uninitialized`, the message is not sent as it
+    /// could contain parts of the program.
+    pub error: Option<String>,
+}
+
+/// Result of a run, see [`RunReport`].
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunResult {
+    /// The program finished, if it belongs to an exercise, it ended with the expected state.
+    Finished,
+    /// The program finished, but did not end with the expected state of the exercise.
+    WrongResult,
+    /// The program stopped because of a runtime error.
+    RuntimeError,
+    /// The program could not be build, e.g. because it could not be parsed or uses instructions that are not allowed.
+    BuildError,
+}
+
+impl RunReport {
+    /// Creates the summary of a run, `error` is the runtime error or the error of the build that stopped the run.
+    ///
+    /// Only the code of the error is used, the most specific one, if the error has a diagnostic source.
+    pub fn new(
+        program: &[String],
+        exercise: Option<String>,
+        result: RunResult,
+        steps: usize,
+        error: Option<&dyn Diagnostic>,
+    ) -> Self {
+        Self {
+            program_hash: program_hash(program),
+            exercise,
+            result,
+            steps,
+            error: error_code(error),
+        }
+    }
+
+    /// Posts the report as json to `url`, `http` and `https` urls are supported.
+    ///
+    /// Returns an error if the report could not be sent or the server did not answer with a status of `2xx`.
+    pub fn send(&self, url: &str) -> Result<()> {
+        let url = parse_report_url(url).map_err(|e| miette!(e))?;
+        let body = serde_json::to_string(self).into_diagnostic()?;
+        let agent = ureq::AgentBuilder::new()
+            .timeout(TIMEOUT)
+            // the summary should only be sent to the url that was set
+            .redirects(0)
+            .build();
+        match agent
+            .post(&url)
+            .set("Content-Type", "application/json")
+            .send_string(&body)
+        {
+            Ok(response) if response.status() < 300 => Ok(()),
+            Ok(response) => Err(miette!(
+                "Server responded with: {} {}",
+                response.status(),
+                response.status_text()
+            )),
+            Err(ureq::Error::Status(status, response)) => Err(miette!(
+                "Server responded with: {status} {}",
+                response.status_text()
+            )),
+            Err(e) => Err(miette!("{e}")),
+        }
+    }
+}
+
+/// Returns the code of the last error in the chain of diagnostic sources that has a code.
+fn error_code(error: Option<&dyn Diagnostic>) -> Option<String> {
+    let mut code = None;
+    let mut current = error;
+    while let Some(e) = current {
+        if let Some(c) = e.code() {
+            code = Some(c.to_string());
+        }
+        current = e.diagnostic_source();
+    }
+    code
+}
+
+/// Returns the hash of the program as hex string, computed with 64 bit FNV-1a over the lines of the program.
+///
+/// The hash is stable between versions and platforms, in contrast to the hasher of the standard library.
+pub fn program_hash(program: &[String]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in program.join("\n").bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// Checks that `url` can be used as `--report-url`, it has to be an absolute url with the scheme `http` or `https`,
+/// e.g. `https://dashboard.example/runs` or `http://[::1]:8080/runs`.
+///
+/// Returns the url, so that it can be used as value parser of the cli argument.
+pub fn parse_report_url(url: &str) -> std::result::Result<String, String> {
+    let parsed = ureq::post(url)
+        .request_url()
+        .map_err(|e| format!("Invalid report url {url}: {e}"))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(url.to_string()),
+        scheme => Err(format!(
+            "Unsupported report url {url}, the scheme {scheme} is not supported, use http or https"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+    };
+
+    use super::{parse_report_url, program_hash, RunReport, RunResult};
+
+    #[test]
+    fn test_program_hash() {
+        let program = ["a := 5".to_string(), "p(h1) := a".to_string()];
+        assert_eq!(program_hash(&program), program_hash(&program.clone()));
+        assert_ne!(program_hash(&program), program_hash(&program[..1]));
+        assert_eq!(program_hash(&[]), "cbf29ce484222325");
+    }
+
+    #[test]
+    fn test_parse_report_url() {
+        for url in [
+            "http://localhost:8080/runs",
+            "http://dashboard",
+            "https://dashboard/runs",
+            "http://[::1]:8080/runs",
+        ] {
+            assert_eq!(parse_report_url(url), Ok(url.to_string()));
+        }
+        assert!(parse_report_url("ftp://dashboard/runs").is_err());
+        assert!(parse_report_url("http://").is_err());
+        assert!(parse_report_url("dashboard/runs").is_err());
+        assert!(parse_report_url("http://[::1/runs").is_err());
+    }
+
+    #[test]
+    fn test_send_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/runs", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            for status in ["200 OK", "500 Internal Server Error"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"}") {
+                    let n = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                stream
+                    .write_all(format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n").as_bytes())
+                    .unwrap();
+            }
+        });
+        let report = RunReport::new(&[], None, RunResult::BuildError, 0, None);
+        assert!(report.send(&url).is_ok());
+        let err = report.send(&url).unwrap_err();
+        assert!(err.to_string().contains("500"), "{err}");
+        server.join().unwrap();
+    }
+}
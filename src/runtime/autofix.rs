@@ -0,0 +1,276 @@
+use crate::{
+    base::Comparison,
+    instructions::{
+        error_handling::InstructionParseError,
+        spec::{self, Spec},
+        Instruction,
+    },
+};
+
+/// Maximum number of problems that are fixed in one line, every fix is checked by parsing the line again.
+const MAX_FIXES_PER_LINE: usize = 3;
+
+/// Words that start an instruction, they are not treated as label with missing colon.
+const KEYWORDS: [&str; 7] = ["if", "goto", "call", "return", "push", "pop", "peek"];
+
+/// Fix for the problems of one line of a program, suggested by [`suggest_fixes`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct Fix {
+    /// Index of the line in the file, lines that start with `#` are counted.
+    pub line_idx: usize,
+    pub original: String,
+    pub fixed: String,
+    /// Descriptions of the problems that are fixed.
+    pub descriptions: Vec<String>,
+}
+
+impl Fix {
+    /// Returns the fix as diff of the line, the first line contains the line number and the descriptions.
+    pub fn diff(&self) -> String {
+        format!(
+            "line {}: {}\n- {}\n+ {}",
+            self.line_idx + 1,
+            self.descriptions.join(", "),
+            self.original,
+            self.fixed
+        )
+    }
+}
+
+/// Suggests fixes for mechanical problems of the program that prevent it from being build:
+/// - comparisons that are not written in a spelling of the spec, when `enforce_grammar` is set
+/// - labels without colon, e.g. `loop a := a - 1`
+/// - `:=` without spaces, e.g. `a:=5`
+///
+/// The problems are located with the positions of the errors that are returned by the parser.
+pub fn suggest_fixes(
+    instructions_input: &[String],
+    spec: Option<Spec>,
+    enforce_grammar: bool,
+) -> Vec<Fix> {
+    let mut fixes = Vec::new();
+    for (line_idx, line) in instructions_input.iter().enumerate() {
+        if line.trim().starts_with('#') {
+            continue;
+        }
+        let mut fixed = line.clone();
+        let mut descriptions = Vec::new();
+        for _ in 0..MAX_FIXES_PER_LINE {
+            match suggest_fix(&fixed, spec, enforce_grammar) {
+                Some((line, description)) => {
+                    fixed = line;
+                    descriptions.push(description);
+                }
+                None => break,
+            }
+        }
+        if !descriptions.is_empty() {
+            fixes.push(Fix {
+                line_idx,
+                original: line.clone(),
+                fixed,
+                descriptions,
+            });
+        }
+    }
+    fixes
+}
+
+/// Returns the program with the fixes applied, fixes of lines that were changed in the meantime are skipped.
+pub fn apply_fixes(instructions_input: &[String], fixes: &[Fix]) -> Vec<String> {
+    let mut program = instructions_input.to_vec();
+    for fix in fixes {
+        if program.get(fix.line_idx) == Some(&fix.original) {
+            program[fix.line_idx] = fix.fixed.clone();
+        }
+    }
+    program
+}
+
+/// Returns the line with its first problem fixed and a description of the fix,
+/// `None` if the line has no problem that can be fixed.
+fn suggest_fix(line: &str, spec: Option<Spec>, enforce_grammar: bool) -> Option<(String, String)> {
+    let parts = code_parts(line);
+    // the label is not part of the instruction
+    let has_label = parts.first().is_some_and(|f| f.1.ends_with(':'));
+    let instruction = &parts[usize::from(has_label)..];
+    let tokens = instruction.iter().map(|f| f.1).collect::<Vec<_>>();
+    if tokens.is_empty() {
+        return None;
+    }
+    let error = match Instruction::try_from(&tokens) {
+        Ok(parsed) if enforce_grammar => spec::check_operands(&parsed, &tokens, spec).err()?,
+        Ok(_) => return None,
+        Err(e) => e,
+    };
+    if let InstructionParseError::OperandNotAllowed(range, operand, _) = &error {
+        // only the spelling of comparisons can be fixed, the display spelling is accepted by all versions
+        let comparison = Comparison::try_from(operand.as_str()).ok()?;
+        let (offset, part) = instruction.get(part_at(&tokens, range.0))?;
+        if part != operand {
+            return None;
+        }
+        return Some((
+            replace(line, *offset, part.len(), &comparison.to_string()),
+            format!("write comparison '{operand}' as '{comparison}'"),
+        ));
+    }
+    // the spaces around := are fixed first, the label might be missing its colon too
+    if let Some(fixed) = fix_assignment_spacing(&tokens) {
+        let start = instruction[0].0;
+        let (end_offset, end_part) = instruction[instruction.len() - 1];
+        let fixed_tokens = fixed.split_whitespace().collect::<Vec<_>>();
+        if parses(&fixed_tokens) || (!has_label && parses(&fixed_tokens[1..])) {
+            return Some((
+                replace(line, start, end_offset + end_part.len() - start, &fixed),
+                "add spaces around ':='".to_string(),
+            ));
+        }
+    }
+    // the parser fails at the first part, if it is a label without colon
+    let (offset, first) = instruction[0];
+    if !has_label
+        && part_at(&tokens, error.range().0) == 0
+        && tokens.len() > 1
+        && !KEYWORDS.contains(&first)
+        && first.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && parses(&tokens[1..])
+    {
+        return Some((
+            replace(line, offset + first.len(), 0, ":"),
+            format!("add missing colon after label '{first}'"),
+        ));
+    }
+    None
+}
+
+/// Returns the instruction with exactly one space before and after `:=`, `None` if the spacing is already correct.
+fn fix_assignment_spacing(tokens: &[&str]) -> Option<String> {
+    if !tokens.iter().any(|f| f.contains(":=") && *f != ":=") {
+        return None;
+    }
+    let (left, right) = tokens
+        .join(" ")
+        .split_once(":=")
+        .map(|(left, right)| (left.trim_end().to_string(), right.trim_start().to_string()))?;
+    if left.is_empty() || right.is_empty() {
+        return None;
+    }
+    Some(format!("{left} := {right}"))
+}
+
+fn parses(tokens: &[&str]) -> bool {
+    !tokens.is_empty() && Instruction::try_from(&tokens.to_vec()).is_ok()
+}
+
+/// Returns the whitespace separated parts of the line, without comment, together with their byte offset in the line.
+fn code_parts(line: &str) -> Vec<(usize, &str)> {
+    let code_end = line
+        .find("//")
+        .or_else(|| line.find('#'))
+        .unwrap_or(line.len());
+    let mut parts = Vec::new();
+    let mut rest = &line[..code_end];
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        let end = rest[start..]
+            .find(char::is_whitespace)
+            .map_or(rest.len(), |f| start + f);
+        parts.push((code_end - rest.len() + start, &rest[start..end]));
+        rest = &rest[end..];
+    }
+    parts
+}
+
+/// Returns the index of the part that contains `position`.
+///
+/// The parser reports positions in the instruction as if the parts were separated by single spaces.
+fn part_at(tokens: &[&str], position: usize) -> usize {
+    let mut start = 0;
+    for (idx, token) in tokens.iter().enumerate() {
+        start += token.len() + 1;
+        if position < start {
+            return idx;
+        }
+    }
+    tokens.len().saturating_sub(1)
+}
+
+fn replace(line: &str, start: usize, len: usize, replacement: &str) -> String {
+    format!("{}{replacement}{}", &line[..start], &line[start + len..])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::spec::Spec;
+
+    use super::{apply_fixes, suggest_fixes};
+
+    fn fixed(line: &str, spec: Option<Spec>, enforce_grammar: bool) -> Option<String> {
+        suggest_fixes(&[line.to_string()], spec, enforce_grammar)
+            .pop()
+            .map(|f| f.fixed)
+    }
+
+    #[test]
+    fn test_fix_comparison_spelling() {
+        assert_eq!(
+            fixed("if a =>  0 then goto loop // comment", None, true),
+            Some("if a >=  0 then goto loop // comment".to_string())
+        );
+        assert_eq!(
+            fixed("if a ≤ 0 then goto loop", Some(Spec::V2022), true),
+            Some("if a <= 0 then goto loop".to_string())
+        );
+        // the spelling is only checked when the grammar is enforced
+        assert_eq!(fixed("if a => 0 then goto loop", None, false), None);
+        // operands that are not comparisons can't be fixed
+        assert_eq!(fixed("if 0 < a then goto loop", None, true), None);
+    }
+
+    #[test]
+    fn test_fix_missing_label_colon() {
+        assert_eq!(
+            fixed("loop  a := a - 1 // comment", None, false),
+            Some("loop:  a := a - 1 // comment".to_string())
+        );
+        assert_eq!(fixed("return a := 1", None, false), None);
+        assert_eq!(fixed("loop a := ", None, false), None);
+    }
+
+    #[test]
+    fn test_fix_assignment_spacing() {
+        assert_eq!(fixed("a:=5", None, false), Some("a := 5".to_string()));
+        assert_eq!(
+            fixed("x: p(h1):= a // comment", None, false),
+            Some("x: p(h1) := a // comment".to_string())
+        );
+        // multiple problems are fixed in one line
+        let fixes = suggest_fixes(&["loop a :=a".to_string()], None, false);
+        assert_eq!(fixes[0].fixed, "loop: a := a");
+        assert_eq!(
+            fixes[0].diff(),
+            "line 1: add spaces around ':=', add missing colon after label 'loop'\n- loop a :=a\n+ loop: a := a"
+        );
+        assert_eq!(fixed("a0  :=  a0 + 1", None, false), None);
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let program =
+            ["# alpha_tui: max-steps=10", "a:=1", "a := 2", "loop a := 3"].map(String::from);
+        let fixes = suggest_fixes(&program, None, false);
+        assert_eq!(
+            fixes.iter().map(|f| f.line_idx).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_eq!(
+            apply_fixes(&program, &fixes),
+            vec![
+                "# alpha_tui: max-steps=10",
+                "a := 1",
+                "a := 2",
+                "loop: a := 3"
+            ]
+        );
+    }
+}
@@ -1,16 +1,29 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
 use crate::{
     base::{Accumulator, MemoryCell},
-    cli::{CheckLoadArgs, CliHint, GlobalArgs, InstructionLimitingArgs},
+    cli::{CheckLoadArgs, CliHint, GlobalArgs, InstructionLimitingArgs, MemoryArgs},
     instructions::{
-        error_handling::{BuildProgramError, BuildProgramErrorTypes},
+        error_handling::{BuildProgramError, BuildProgramErrorTypes, InstructionParseError},
         instruction_config::InstructionConfig,
-        Identifier, IndexMemoryCellIndexType, Instruction, TargetType, Value,
+        spec::{self, Spec},
+        Condition, Identifier, IndexMemoryCellIndexType, Instruction, TargetType, Value,
     },
+    utils::get_comment,
 };
 
 use super::{
-    error_handling::RuntimeBuildError, memory_config::MemoryConfig, ControlFlow, Runtime,
-    RuntimeMemory, RuntimeSettings,
+    contracts::Contract,
+    error_handling::{catch_panic, BuildError, RuntimeBuildError},
+    header::ProgramHeader,
+    initial_values::InitialValues,
+    loops,
+    macros::Macros,
+    memory_config::MemoryConfig,
+    random::Random,
+    ControlFlow, Runtime, RuntimeMemory, RuntimeSettings,
 };
 
 pub struct RuntimeBuilder {
@@ -19,6 +32,54 @@ pub struct RuntimeBuilder {
     memory_config: Option<MemoryConfig>,
     runtime_settings: Option<RuntimeSettings>,
     instruction_config: InstructionConfig,
+    /// Label of the function that is called when the program starts and the arguments that are pushed onto the stack.
+    entry_call: Option<(String, Vec<i32>)>,
+    /// Lines of the program and name of the file, used to mark operands that are not allowed by the grammar.
+    source: (Vec<String>, String),
+    /// Indices of the instructions that were added with [`RuntimeBuilder::add_instruction`] without span, their
+    /// line in `source` is generated from the instruction.
+    spanless: HashSet<usize>,
+    /// Values that are set before the program is started, declared with `@init` annotations in the program.
+    initial_values: InitialValues,
+}
+
+/// Source text of an instruction that is added with [`RuntimeBuilder::add_instruction`], e.g. the line of an editor
+/// from which the instruction was created.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    /// Text of the instruction, without label and comment.
+    pub text: String,
+}
+
+impl From<&str> for Span {
+    fn from(value: &str) -> Self {
+        Self {
+            text: value.to_string(),
+        }
+    }
+}
+
+/// Limits of the runtime, set with [`RuntimeBuilder::with_limits`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RuntimeLimits {
+    /// Maximum number of steps the program is allowed to run, like `--max-steps`.
+    pub max_steps: Option<usize>,
+    /// If true, programs are not stopped after `MAX_INSTRUCTION_RUNS` instructions, like
+    /// `--disable-instruction-limit`.
+    pub disable_instruction_limit: bool,
+}
+
+/// Configuration of a runtime builder that is not read from the program, e.g. the memory config and the limits that
+/// are set on the command line, see [`RuntimeBuilder::config`].
+///
+/// Stored in repro files, so that the program is built the same way when the repro is replayed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuildConfig {
+    pub memory_config: Option<MemoryConfig>,
+    pub runtime_settings: Option<RuntimeSettings>,
+    pub instruction_config: InstructionConfig,
+    /// Label of the function that is called when the program starts and its arguments, like `--call`.
+    pub entry_call: Option<(String, Vec<i32>)>,
 }
 
 impl RuntimeBuilder {
@@ -31,12 +92,14 @@ impl RuntimeBuilder {
         instructions_input_file_name: &'a str,
     ) -> Result<Self, BuildProgramError> {
         let mut control_flow = ControlFlow::new();
+        let mut initial_values = InitialValues::default();
 
         // build instructions (also updated control flow with detected labels)
         let instructions = match build_instructions(
             instructions_input,
             instructions_input_file_name,
             &mut control_flow,
+            &mut initial_values,
         ) {
             Ok(instructions) => instructions,
             Err(e) => return Err(*e),
@@ -48,7 +111,143 @@ impl RuntimeBuilder {
             memory_config: None,
             runtime_settings: None,
             instruction_config: InstructionConfig::default(),
+            entry_call: None,
+            source: (
+                instructions_input.to_vec(),
+                instructions_input_file_name.to_string(),
+            ),
+            spanless: HashSet::new(),
+            initial_values,
+        })
+    }
+
+    /// Creates a new runtime builder like [`RuntimeBuilder::new`], but a panic while the instructions are build is
+    /// returned as [`RuntimeBuildError::InternalError`], used when `--robust` is set.
+    pub fn new_robust(
+        instructions_input: &[String],
+        instructions_input_file_name: &str,
+    ) -> Result<Self, BuildError> {
+        catch_panic(|| {
+            Self::new(instructions_input, instructions_input_file_name).map_err(BuildError::from)
         })
+        .map_err(RuntimeBuildError::InternalError)?
+    }
+
+    /// Creates a new runtime builder without instructions, they can be added with
+    /// [`RuntimeBuilder::add_instruction`], without parsing the text of a program.
+    ///
+    /// `name` is used as file name in error messages.
+    pub fn empty(name: &str) -> Self {
+        Self {
+            instructions: Vec::new(),
+            control_flow: ControlFlow::new(),
+            memory_config: None,
+            runtime_settings: None,
+            instruction_config: InstructionConfig::default(),
+            entry_call: None,
+            source: (Vec::new(), name.to_string()),
+            spanless: HashSet::new(),
+            initial_values: InitialValues::default(),
+        }
+    }
+
+    /// Adds the instruction to the end of the program, if `label` is set the instruction can be jumped to with it.
+    ///
+    /// `span` is the source text of the instruction, it is used to mark the operand that is not allowed when the
+    /// grammar is enforced. Errors of instructions without span don't refer to a source.
+    ///
+    /// Returns an error if the label is already defined.
+    #[allow(clippy::result_large_err)]
+    pub fn add_instruction(
+        &mut self,
+        instruction: Instruction,
+        label: Option<&str>,
+        span: Option<Span>,
+    ) -> Result<&mut Self, BuildProgramError> {
+        let index = self.instructions.len();
+        // the builder is left unchanged, if the label can't be added
+        if let Some(label) = label {
+            let labels = &mut self.control_flow.instruction_labels;
+            if labels.contains_key(label) {
+                return Err(BuildProgramError {
+                    reason: BuildProgramErrorTypes::LabelDefinedMultipleTimes(label.to_string()),
+                });
+            }
+            if (label == "main" && labels.contains_key("MAIN"))
+                || (label == "MAIN" && labels.contains_key("main"))
+            {
+                return Err(BuildProgramError {
+                    reason: BuildProgramErrorTypes::MainLabelDefinedMultipleTimes,
+                });
+            }
+            labels.insert(label.to_string(), index);
+        }
+        let text = match span {
+            Some(span) => span.text,
+            None => {
+                self.spanless.insert(index);
+                instruction.to_string()
+            }
+        };
+        self.source.0.push(match label {
+            Some(label) => format!("{label}: {text}"),
+            None => text,
+        });
+        self.control_flow.source_lines.push(self.source.0.len());
+        self.instructions.push(instruction);
+        Ok(self)
+    }
+
+    /// Sets the memory config that is used to build the runtime, the autodetection of the memory types is set up
+    /// according to the config.
+    pub fn with_memory(&mut self, memory_config: MemoryConfig) -> &mut Self {
+        self.apply_autodetection(&memory_config);
+        self.memory_config = Some(memory_config);
+        self
+    }
+
+    /// Sets the limits of the runtime, see [`RuntimeLimits`].
+    pub fn with_limits(&mut self, limits: RuntimeLimits) -> &mut Self {
+        let mut runtime_settings = self.runtime_settings.take().unwrap_or_default();
+        runtime_settings.max_steps = limits.max_steps;
+        runtime_settings.disable_instruction_limit = limits.disable_instruction_limit;
+        self.runtime_settings = Some(runtime_settings);
+        self
+    }
+
+    /// Returns the memory config that is currently set.
+    pub fn memory_config(&self) -> Option<&MemoryConfig> {
+        self.memory_config.as_ref()
+    }
+
+    /// Returns the configuration that is currently set, after the cli args and the program header are applied it
+    /// contains everything that is required to build the runtime again from the program.
+    pub fn config(&self) -> BuildConfig {
+        BuildConfig {
+            memory_config: self.memory_config.clone(),
+            runtime_settings: self.runtime_settings.clone(),
+            instruction_config: self.instruction_config.clone(),
+            entry_call: self.entry_call.clone(),
+        }
+    }
+
+    /// Replaces the configuration of this builder with `config`, that was returned by [`Self::config`].
+    ///
+    /// The cli args and the program header don't have to be applied afterwards.
+    pub fn set_config(&mut self, config: BuildConfig) -> &mut Self {
+        self.memory_config = config.memory_config;
+        self.runtime_settings = config.runtime_settings;
+        self.instruction_config = config.instruction_config;
+        self.entry_call = config.entry_call;
+        self
+    }
+
+    /// Sets the memory config that should be used to build the runtime.
+    ///
+    /// The config is used in `apply_global_cli_args` instead of the memory config file, if that function is called afterwards.
+    pub fn set_memory_config(&mut self, memory_config: MemoryConfig) -> &mut Self {
+        self.memory_config = Some(memory_config);
+        self
     }
 
     /// Applies the parameters in global args to this runtime builder.
@@ -59,10 +258,11 @@ impl RuntimeBuilder {
     pub fn apply_global_cli_args(
         &mut self,
         global_args: &GlobalArgs,
-    ) -> miette::Result<&mut Self, RuntimeBuildError> {
+    ) -> Result<&mut Self, BuildError> {
         // set disable instruction limit value
         let mut settings = self.runtime_settings.take().unwrap_or_default();
         settings.disable_instruction_limit = global_args.disable_instruction_limit;
+        settings.seed = global_args.seed;
         self.runtime_settings = Some(settings);
 
         let memory_config = match self.memory_config.take() {
@@ -71,12 +271,17 @@ impl RuntimeBuilder {
                 // check if memory config file is provided, from which the memory config can be build
                 if let Some(path) = &global_args.memory_config_file {
                     match MemoryConfig::try_from_file(path) {
-                        Ok(config) => config,
+                        Ok(config) => {
+                            // values that are set explicitly are not replaced by the initial values of the program
+                            self.initial_values.remove_set_in(&config);
+                            config
+                        }
                         Err(e) => {
                             return Err(RuntimeBuildError::MemoryConfigFileInvalid(
                                 path.to_string(),
                                 e.to_string(),
-                            ))
+                            )
+                            .into())
                         }
                     }
                 } else {
@@ -84,7 +289,13 @@ impl RuntimeBuilder {
                 }
             }
         };
-        // update runtime settings
+        self.apply_autodetection(&memory_config);
+        self.memory_config = Some(memory_config);
+        Ok(self)
+    }
+
+    /// Updates the autodetection of the runtime settings with the values that are set in the memory config.
+    fn apply_autodetection(&mut self, memory_config: &MemoryConfig) {
         let mut runtime_settings = self.runtime_settings.take().unwrap_or_default();
         if let Some(value) = memory_config.accumulators.autodetection {
             runtime_settings.autodetect_accumulators = value;
@@ -99,16 +310,99 @@ impl RuntimeBuilder {
             runtime_settings.autodetect_index_memory_cells = value;
         }
         self.runtime_settings = Some(runtime_settings);
+    }
+
+    /// Reads the header of the program and applies it to this runtime builder, see [`ProgramHeader`].
+    ///
+    /// Accumulators and memory cells are added to the memory config, already existing values are kept.
+    /// The maximum number of steps is only set, if it was not set already.
+    ///
+    /// The initial values that are declared with `@init` annotations in the program are set as well, they replace
+    /// the values of the memory config, except the values of a memory config file that is set with
+    /// `--memory-config-file`, see [`InitialValues`].
+    pub fn apply_program_header(&mut self, program: &[String]) -> Result<&mut Self, BuildError> {
+        if !self.initial_values.is_empty() {
+            let mut memory_config = self.memory_config.take().unwrap_or_default();
+            self.initial_values.apply(&mut memory_config);
+            self.memory_config = Some(memory_config);
+        }
+        let header = match ProgramHeader::try_from_program(program) {
+            Ok(Some(header)) => header,
+            Ok(None) => return Ok(self),
+            Err(e) => return Err(RuntimeBuildError::ProgramHeaderInvalid(e).into()),
+        };
+        let mut memory_config = self.memory_config.take().unwrap_or_default();
+        if let Some(accumulators) = header.accumulators {
+            for value in 0..accumulators {
+                memory_config
+                    .accumulators
+                    .values
+                    .entry(value)
+                    .or_insert(None);
+            }
+        }
+        if let Some(memory_cells) = header.memory_cells {
+            for memory_cell in memory_cells {
+                memory_config
+                    .memory_cells
+                    .values
+                    .entry(memory_cell)
+                    .or_insert(None);
+            }
+        }
+        if let Some(index_memory_cells) = header.index_memory_cells {
+            for imc in index_memory_cells {
+                memory_config
+                    .index_memory_cells
+                    .values
+                    .entry(imc)
+                    .or_insert(None);
+            }
+        }
+        if let Some(gamma) = header.gamma {
+            memory_config.gamma_accumulator.enabled = gamma;
+        }
         self.memory_config = Some(memory_config);
+        let mut runtime_settings = self.runtime_settings.take().unwrap_or_default();
+        if runtime_settings.max_steps.is_none() {
+            runtime_settings.max_steps = header.max_steps;
+        }
+        self.runtime_settings = Some(runtime_settings);
         Ok(self)
     }
 
     /// Applies the parameters in check load args to this runtime builder.
     ///
     /// In essence this means that the amount of accumulators, memory_cells and index_memory_cells
-    /// is set/updated.
+    /// is set/updated and that the maximum number of steps, call checking and the function that is called
+    /// when the program starts are set.
     /// If a memory config already exists, the values supplemented.
-    pub fn apply_check_load_args(&mut self, args: &CheckLoadArgs) -> miette::Result<&mut Self> {
+    pub fn apply_check_load_args(&mut self, args: &CheckLoadArgs) -> Result<&mut Self, BuildError> {
+        self.apply_memory_args(&args.memory_args);
+        // set maximum number of steps and call checking
+        let mut runtime_settings = self.runtime_settings.take().unwrap_or_default();
+        if let Some(max_steps) = args.max_steps {
+            runtime_settings.max_steps = Some(max_steps);
+        }
+        if args.strict_calls {
+            runtime_settings.strict_calls = true;
+        }
+        if args.robust {
+            runtime_settings.robust = true;
+        }
+        self.runtime_settings = Some(runtime_settings);
+        // set function that is called when the program starts
+        if let Some(label) = &args.call {
+            self.entry_call = Some((label.clone(), args.args.clone().unwrap_or_default()));
+        }
+        Ok(self)
+    }
+
+    /// Applies the memory args to this runtime builder.
+    ///
+    /// The memory values are added to the already existing `MemoryConfig`, if `--disable-memory-detection` is set
+    /// the autodetection of all memory types is disabled.
+    pub fn apply_memory_args(&mut self, args: &MemoryArgs) -> &mut Self {
         let mut memory_config = self.memory_config.take().unwrap_or_default();
         // set/overwrite memory config values
         // set accumulator config
@@ -136,7 +430,27 @@ impl RuntimeBuilder {
             }
         }
         self.memory_config = Some(memory_config);
-        Ok(self)
+        // set/override memory autodetection values to false, if `--disable-memory-detection` is set
+        if args.disable_memory_detection {
+            let mut memory_config = self.memory_config.take().unwrap_or_default();
+            memory_config.accumulators.autodetection = Some(false);
+            memory_config.gamma_accumulator.autodetection = Some(false);
+            memory_config.memory_cells.autodetection = Some(false);
+            memory_config.index_memory_cells.autodetection = Some(false);
+            // enable gamma accumulator, if specified
+            if args.enable_gamma_accumulator {
+                memory_config.gamma_accumulator.enabled = true;
+            }
+            self.memory_config = Some(memory_config);
+            // update runtime settings
+            let mut runtime_settings = self.runtime_settings.take().unwrap_or_default();
+            runtime_settings.autodetect_accumulators = false;
+            runtime_settings.autodetect_gamma_accumulator = false;
+            runtime_settings.autodetect_memory_cells = false;
+            runtime_settings.autodetect_index_memory_cells = false;
+            self.runtime_settings = Some(runtime_settings);
+        }
+        self
     }
 
     /// Applies the provided instruction limiting args to this runtime builder.
@@ -148,7 +462,7 @@ impl RuntimeBuilder {
     pub fn apply_instruction_limiting_args(
         &mut self,
         instruction_limiting_args: &InstructionLimitingArgs,
-    ) -> miette::Result<&mut Self> {
+    ) -> Result<&mut Self, BuildError> {
         // if allowed instructions file is set, parse instructions and set the ids as allowed
         if let Some(path) = &instruction_limiting_args.allowed_instructions_file {
             match InstructionConfig::try_from_file(path) {
@@ -165,10 +479,20 @@ impl RuntimeBuilder {
                     }
                 }
                 Err(e) => {
-                    return Err(e);
+                    return Err(RuntimeBuildError::InstructionConfigFileInvalid(
+                        path.to_string(),
+                        e.to_string(),
+                    )
+                    .into());
                 }
             }
         }
+        if let Some(spec) = instruction_limiting_args.spec {
+            self.instruction_config.spec = Some(spec);
+        }
+        if instruction_limiting_args.strict {
+            self.instruction_config.strict = true;
+        }
         if let Some(ac) = &instruction_limiting_args.allowed_comparisons {
             // if allowed_comparisons are already set, merge with additional allowed comparisons
             let mut allowed_comparisons = self
@@ -188,35 +512,25 @@ impl RuntimeBuilder {
             allowed_operations.append(&mut ao.clone());
             self.instruction_config.allowed_operations = Some(allowed_operations);
         }
-        // set/override memory autodetection values to false, if `--disable-memory-detection` is set
-        if instruction_limiting_args.disable_memory_detection {
-            let mut memory_config = self.memory_config.take().unwrap_or_default();
-            memory_config.accumulators.autodetection = Some(false);
-            memory_config.gamma_accumulator.autodetection = Some(false);
-            memory_config.memory_cells.autodetection = Some(false);
-            memory_config.index_memory_cells.autodetection = Some(false);
-            // enable gamma accumulator, if specified
-            if instruction_limiting_args.enable_gamma_accumulator {
-                memory_config.gamma_accumulator.enabled = true;
-            }
-            self.memory_config = Some(memory_config);
-            // update runtime settings
-            let mut runtime_settings = self.runtime_settings.take().unwrap_or_default();
-            runtime_settings.autodetect_accumulators = false;
-            runtime_settings.autodetect_gamma_accumulator = false;
-            runtime_settings.autodetect_memory_cells = false;
-            runtime_settings.autodetect_index_memory_cells = false;
-            self.runtime_settings = Some(runtime_settings);
-        }
         Ok(self)
     }
 
     /// Builds a new runtime by consuming this `RuntimeBuilder`.
     ///
     /// Prints status messages into stdout.
-    pub fn build(mut self) -> miette::Result<Runtime> {
+    ///
+    /// In robust mode, a panic while the runtime is build is returned as [`RuntimeBuildError::InternalError`].
+    pub fn build(self) -> Result<Runtime, BuildError> {
+        if !self.runtime_settings.as_ref().is_some_and(|f| f.robust) {
+            return self.build_runtime();
+        }
+        catch_panic(|| self.build_runtime())
+            .unwrap_or_else(|message| Err(RuntimeBuildError::InternalError(message).into()))
+    }
+
+    fn build_runtime(mut self) -> Result<Runtime, BuildError> {
         // set runtime settings
-        let settings = self.runtime_settings.unwrap_or_default();
+        let mut settings = self.runtime_settings.unwrap_or_default();
 
         // build memory
         let mut memory = match &self.memory_config {
@@ -235,15 +549,22 @@ impl RuntimeBuilder {
         }
 
         // check if instructions are used that are not allowed
-        if let Err(e) = check_instructions(&self.instructions, &self.instruction_config) {
-            return Err(miette::Report::new(*e));
+        check_instructions(&self.instructions, &self.instruction_config)?;
+        if self.instruction_config.enforces_grammar() {
+            check_grammar(
+                &self.instructions,
+                &self.source.0,
+                &self.source.1,
+                &self.spanless,
+                self.instruction_config.spec,
+            )?;
         }
 
         // inject end labels to give option to end program using goto END
         inject_end_labels(&mut self.control_flow, self.instructions.len());
 
         if let Err(e) = check_labels(&self.control_flow, &self.instructions) {
-            return Err(miette::Report::new(RuntimeBuildError::LabelUndefined(e)));
+            return Err(RuntimeBuildError::LabelUndefined(e).into());
         }
 
         // Check if all used accumulators and memory_cells exist
@@ -265,13 +586,34 @@ impl RuntimeBuilder {
             self.control_flow.initial_instruction = *i;
         }
 
+        // the same seed generates the same random numbers, a seed is always set, so that it can be stored to
+        // repeat the run
+        let seed = settings.seed.unwrap_or_else(Random::time_seed);
+        settings.seed = Some(seed);
+        self.control_flow.random = Random::new(seed);
+
+        // start with a call of the function, if set, the program ends when the function returns
+        if let Some((label, args)) = &self.entry_call {
+            let Some(i) = self.control_flow.instruction_labels.get(label) else {
+                return Err(RuntimeBuildError::LabelUndefined(label.clone()).into());
+            };
+            self.control_flow.next_instruction_index = *i;
+            self.control_flow.initial_instruction = *i;
+            memory.stack.extend(args);
+        }
+
         Ok(Runtime {
             memory: memory.clone(),
             initial_memory: memory,
+            line_hits: vec![0; self.instructions.len()],
             instructions: self.instructions,
             control_flow: self.control_flow,
             instruction_runs: 0,
             settings,
+            aliases: self
+                .memory_config
+                .map(|f| f.display_aliases())
+                .unwrap_or_default(),
         })
     }
 }
@@ -281,31 +623,66 @@ impl RuntimeBuilder {
 /// Updates the provided control flow with labels.
 ///
 /// If a line starts with `#` it is skipped and no noop operation is created.
+///
+/// The lines of macro definitions are build as noop operations, calls of macros are expanded. The lines of `while`
+/// and `loop` constructs are lowered into jumps, whose labels are added to the control flow.
 fn build_instructions(
     instructions_input: &[String],
     file_name: &str,
     control_flow: &mut ControlFlow,
+    initial_values: &mut InitialValues,
 ) -> Result<Vec<Instruction>, Box<BuildProgramError>> {
     let mut instructions = Vec::new();
     // remove lines starting with # to not make them count towards jump addresses, as they are not displayed in the program
-    let instructions_input = instructions_input
+    let (source_lines, instructions_input): (Vec<usize>, Vec<String>) = instructions_input
         .iter()
-        .filter(|f| !f.trim().starts_with('#'))
-        .map(|f| f.to_string())
-        .collect::<Vec<String>>();
+        .enumerate()
+        .filter(|(_, f)| !f.trim().starts_with('#'))
+        .map(|(index, f)| (index + 1, f.to_string()))
+        .unzip();
+    control_flow.source_lines = source_lines;
+    let (macros, definition_lines) =
+        Macros::parse(&instructions_input).map_err(|(index, reason)| BuildProgramError {
+            reason: BuildProgramErrorTypes::MacroInvalid(index + 1, reason),
+        })?;
+    // comments of the lines directly before the current line, they can contain contract annotations of a label
+    let mut preceding_comments = Vec::new();
     for (index, instruction) in instructions_input.iter().enumerate() {
         if instruction.trim().starts_with('#') {
             continue;
         }
+        if definition_lines.contains(&index) {
+            preceding_comments.clear();
+            instructions.push(Instruction::Noop);
+            continue;
+        }
         // Remove comments
+        let comment = get_comment(instruction);
         let instruction = remove_comment(instruction);
+        // read initial values from the comment, they can be declared in any line
+        if let Some(comment) = &comment {
+            if let Err((annotation, reason)) = initial_values.parse_annotations(comment) {
+                Err(BuildProgramError {
+                    reason: BuildProgramErrorTypes::InitialValuesInvalid(
+                        control_flow.source_lines[index],
+                        annotation,
+                        reason,
+                    ),
+                })?;
+            }
+        }
         // Check for labels
         let mut splits = instruction.split_whitespace().collect::<Vec<&str>>();
         if splits.is_empty() {
             // Line is empty / line contains comment, add dummy instruction
+            match comment {
+                Some(comment) => preceding_comments.push(comment),
+                None => preceding_comments.clear(),
+            }
             instructions.push(Instruction::Noop);
             continue;
         }
+        let preceding_comments = std::mem::take(&mut preceding_comments);
         if splits[0].ends_with(':') {
             let label = splits.remove(0).replace(':', "");
             if control_flow
@@ -321,9 +698,25 @@ fn build_instructions(
                 }
                 // label defined multiple times
                 Err(BuildProgramError {
-                    reason: BuildProgramErrorTypes::LabelDefinedMultipleTimes(label),
+                    reason: BuildProgramErrorTypes::LabelDefinedMultipleTimes(label.clone()),
                 })?;
             }
+            // read contract from the comments of the label
+            let mut contract = Contract::default();
+            for comment in preceding_comments.iter().chain(comment.iter()) {
+                if let Err((annotation, reason)) = contract.parse_annotations(comment) {
+                    Err(BuildProgramError {
+                        reason: BuildProgramErrorTypes::ContractInvalid(
+                            index + 1,
+                            annotation,
+                            reason,
+                        ),
+                    })?;
+                }
+            }
+            if !contract.is_empty() {
+                control_flow.contracts.insert(label, contract);
+            }
             if splits.is_empty() {
                 // line contains only label
                 instructions.push(Instruction::Noop);
@@ -332,6 +725,12 @@ fn build_instructions(
         }
 
         match Instruction::try_from(&splits) {
+            Ok(Instruction::Macro(mut call)) => {
+                call.body = macros.expand(&call).map_err(|reason| BuildProgramError {
+                    reason: BuildProgramErrorTypes::MacroInvalid(index + 1, reason),
+                })?;
+                instructions.push(Instruction::Macro(call));
+            }
             Ok(i) => instructions.push(i),
             Err(e) => {
                 Err(e.into_build_program_error(
@@ -342,6 +741,31 @@ fn build_instructions(
             }
         }
     }
+    let labels = loops::lower(&mut instructions, &control_flow.source_lines).map_err(
+        |(index, reason)| BuildProgramError {
+            reason: BuildProgramErrorTypes::LoopInvalid(control_flow.source_lines[index], reason),
+        },
+    )?;
+    for (label, index) in labels {
+        if control_flow
+            .instruction_labels
+            .insert(label.clone(), index)
+            .is_some()
+        {
+            Err(BuildProgramError {
+                reason: BuildProgramErrorTypes::LabelDefinedMultipleTimes(label),
+            })?;
+        }
+    }
+    // labels that are named like a memory location, e.g. a1, are jumped to directly
+    for instruction in &mut instructions {
+        if let Instruction::ComputedGoto(value) = instruction {
+            let label = value.to_string();
+            if control_flow.instruction_labels.contains_key(&label) {
+                *instruction = Instruction::Goto(label);
+            }
+        }
+    }
     if control_flow.instruction_labels.contains_key("main")
         && control_flow.instruction_labels.contains_key("MAIN")
     {
@@ -349,9 +773,20 @@ fn build_instructions(
             reason: BuildProgramErrorTypes::MainLabelDefinedMultipleTimes,
         }));
     }
+    control_flow.macros = macros;
     Ok(instructions)
 }
 
+/// Removes the lines that start with `#`, they are not build into instructions. The indices of the remaining lines
+/// are the indices of the instructions.
+pub fn remove_special_commented_lines(instructions_input: &[String]) -> Vec<String> {
+    instructions_input
+        .iter()
+        .filter(|f| !f.trim().starts_with('#'))
+        .map(|f| f.to_string())
+        .collect()
+}
+
 /// Removes everything behind # or // from the string
 pub fn remove_comment(instruction: &str) -> String {
     instruction
@@ -382,7 +817,16 @@ pub fn check_instructions(
 ) -> Result<(), Box<BuildProgramError>> {
     for (idx, i) in instructions.iter().enumerate() {
         if let Some(whitelist) = &instruction_config.allowed_instruction_identifiers {
-            if !whitelist.contains(&i.identifier()) && i.identifier() != "NOOP" {
+            // the instructions of a macro and the jumps of a loop are checked instead of the line
+            let checked = match i {
+                Instruction::Macro(call) => call.body.iter().collect(),
+                Instruction::Loop(line) => line.lowered.iter().collect(),
+                i => vec![i],
+            };
+            for i in checked {
+                if whitelist.contains(&i.identifier()) || i.identifier() == "NOOP" {
+                    continue;
+                }
                 // Instruction found, that is forbidden
                 let mut allowed_instructions = whitelist
                     .iter()
@@ -399,9 +843,22 @@ pub fn check_instructions(
                 }));
             }
         }
+        // Check if the instruction is allowed in the selected version of the spec
+        if let Some(spec) = instruction_config.spec {
+            if let Err(reason) = spec.check(i) {
+                return Err(Box::new(BuildProgramError {
+                    reason: BuildProgramErrorTypes::InstructionNotInSpec(
+                        idx + 1,
+                        format!("{i}"),
+                        spec.to_string(),
+                        reason,
+                    ),
+                }));
+            }
+        }
         // Check if all comparisons are allowed
         if let Some(ac) = &instruction_config.allowed_comparisons {
-            if let Some(c) = i.comparison() {
+            for c in i.comparisons() {
                 if !ac.contains(c) {
                     return Err(Box::new(BuildProgramError {
                         reason: BuildProgramErrorTypes::ComparisonNotAllowed(
@@ -415,7 +872,7 @@ pub fn check_instructions(
         }
         // Check if all operations are allowed
         if let Some(ao) = &instruction_config.allowed_operations {
-            if let Some(o) = i.operation() {
+            for o in i.operations() {
                 if !ao.contains(o) {
                     return Err(Box::new(BuildProgramError {
                         reason: BuildProgramErrorTypes::OperationNotAllowed(
@@ -431,6 +888,54 @@ pub fn check_instructions(
     Ok(())
 }
 
+/// Checks if the operands of all instructions are combined in a way that is allowed by the official grammar.
+///
+/// `instructions_input` has to be the input from which `instructions` were built, the error marks the operand that
+/// is not allowed.
+fn check_grammar(
+    instructions: &[Instruction],
+    instructions_input: &[String],
+    file_name: &str,
+    spanless: &HashSet<usize>,
+    spec: Option<Spec>,
+) -> Result<(), Box<BuildProgramError>> {
+    // lines are filtered the same way as when the instructions are build, so that the indices match
+    let instructions_input = remove_special_commented_lines(instructions_input);
+    for (index, (instruction, line)) in instructions.iter().zip(&instructions_input).enumerate() {
+        let line = remove_comment(line);
+        let mut parts = line.split_whitespace().collect::<Vec<&str>>();
+        // the operand is marked in the line including the label
+        let mut label_offset = 0;
+        if parts.first().is_some_and(|f| f.ends_with(':')) {
+            label_offset = parts.remove(0).len() + 1;
+        }
+        if let Err(mut e) = spec::check_operands(instruction, &parts, spec) {
+            // the line was generated from the instruction, so it can't be shown as source
+            if let (true, InstructionParseError::OperandNotAllowed(_, operand, reason)) =
+                (spanless.contains(&index), &e)
+            {
+                return Err(Box::new(BuildProgramError {
+                    reason: BuildProgramErrorTypes::OperandNotAllowed(
+                        index + 1,
+                        operand.clone(),
+                        instruction.to_string(),
+                        reason.clone(),
+                    ),
+                }));
+            }
+            if let InstructionParseError::OperandNotAllowed(range, _, _) = &mut e {
+                *range = (range.0 + label_offset, range.1 + label_offset);
+            }
+            return Err(Box::new(e.into_build_program_error(
+                instructions_input.join("\n"),
+                file_name,
+                index + 1,
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// Replaces all index accesses with gamma for memory cells with normal memory cell access.
 ///
 /// So `p(y)` (where y is used as index for the index memory cell) is now changed to a normal
@@ -469,6 +974,26 @@ fn replace_gamma_as_index_instructions(instructions: &mut Vec<Instruction>) {
                 };
                 *instruction = Instruction::Calc(target, value_a, *op, value_b);
             }
+            Instruction::AssignBuiltIn(target, _, args) => {
+                if target.is_imc_gamma() {
+                    *target = TargetType::MemoryCell("y".to_string());
+                }
+                for value in args {
+                    if value.is_imc_gamma() {
+                        *value = Value::MemoryCell("y".to_string());
+                    }
+                }
+            }
+            Instruction::CalcChain(target, value_a, operations) => {
+                if target.is_imc_gamma() {
+                    *target = TargetType::MemoryCell("y".to_string());
+                }
+                for value in std::iter::once(value_a).chain(operations.iter_mut().map(|(_, v)| v)) {
+                    if value.is_imc_gamma() {
+                        *value = Value::MemoryCell("y".to_string());
+                    }
+                }
+            }
             Instruction::JumpIf(value_a, cmp, value_b, label) => {
                 let value_a = if value_a.is_imc_gamma() {
                     Value::MemoryCell("y".to_string())
@@ -482,11 +1007,49 @@ fn replace_gamma_as_index_instructions(instructions: &mut Vec<Instruction>) {
                 };
                 *instruction = Instruction::JumpIf(value_a, *cmp, value_b, label.clone());
             }
+            Instruction::JumpIfCompound(condition, _)
+            | Instruction::JumpIfElse(condition, _, _) => {
+                replace_gamma_as_index_condition(condition);
+            }
+            Instruction::Assert(value_a, _, value_b) => {
+                for value in [value_a, value_b] {
+                    if value.is_imc_gamma() {
+                        *value = Value::MemoryCell("y".to_string());
+                    }
+                }
+            }
+            Instruction::Peek(target) if target.is_imc_gamma() => {
+                *target = TargetType::MemoryCell("y".to_string());
+            }
+            Instruction::Print(value) | Instruction::ComputedGoto(value)
+                if value.is_imc_gamma() =>
+            {
+                *value = Value::MemoryCell("y".to_string());
+            }
+            Instruction::Macro(call) => replace_gamma_as_index_instructions(&mut call.body),
+            Instruction::Loop(line) => replace_gamma_as_index_instructions(&mut line.lowered),
             _ => (),
         }
     }
 }
 
+/// Replaces the index accesses with gamma in all comparisons of the condition, see [`replace_gamma_as_index_instructions`].
+fn replace_gamma_as_index_condition(condition: &mut Condition) {
+    match condition {
+        Condition::Compare(value_a, _, value_b) => {
+            for value in [value_a, value_b] {
+                if value.is_imc_gamma() {
+                    *value = Value::MemoryCell("y".to_string());
+                }
+            }
+        }
+        Condition::And(a, b) | Condition::Or(a, b) => {
+            replace_gamma_as_index_condition(a);
+            replace_gamma_as_index_condition(b);
+        }
+    }
+}
+
 fn inject_end_labels(control_flow: &mut ControlFlow, last_instruction_index: usize) {
     control_flow
         .instruction_labels
@@ -521,9 +1084,16 @@ fn check_label(control_flow: &ControlFlow, label: &str) -> Result<(), String> {
 fn check_labels(control_flow: &ControlFlow, instructions: &Vec<Instruction>) -> Result<(), String> {
     for instruction in instructions {
         match instruction {
-            Instruction::Goto(label) | Instruction::JumpIf(_, _, _, label) => {
+            Instruction::Goto(label)
+            | Instruction::JumpIf(_, _, _, label)
+            | Instruction::JumpIfCompound(_, label) => {
                 check_label(control_flow, label)?;
             }
+            Instruction::JumpIfElse(_, then_label, else_label) => {
+                check_label(control_flow, then_label)?;
+                check_label(control_flow, else_label)?;
+            }
+            Instruction::Loop(line) => check_labels(control_flow, &line.lowered)?,
             _ => (),
         };
     }
@@ -551,6 +1121,30 @@ fn check_missing_vars(
                 value_a.check_new(runtime_memory, memory_config)?;
                 value_b.check_new(runtime_memory, memory_config)?;
             }
+            Instruction::AssignBuiltIn(target, _, args) => {
+                target.check_new(runtime_memory, memory_config)?;
+                for value in args {
+                    value.check_new(runtime_memory, memory_config)?;
+                }
+            }
+            Instruction::CalcChain(target, value_a, operations) => {
+                target.check_new(runtime_memory, memory_config)?;
+                value_a.check_new(runtime_memory, memory_config)?;
+                for (_, value) in operations {
+                    value.check_new(runtime_memory, memory_config)?;
+                }
+            }
+            Instruction::Peek(target) => target.check_new(runtime_memory, memory_config)?,
+            Instruction::Macro(call) => {
+                check_missing_vars(memory_config, &call.body, runtime_memory)?;
+            }
+            Instruction::Loop(line) => {
+                // the counter of a loop is created by the program, even if autodetection is disabled
+                if let Some(counter) = line.counter() {
+                    check_memory_cell(runtime_memory, counter, true)?;
+                }
+                check_missing_vars(memory_config, &line.lowered, runtime_memory)?;
+            }
             _ => (),
         }
     }
@@ -690,7 +1284,8 @@ impl Value {
                 name,
                 memory_config.memory_cells.autodetection.unwrap_or(true),
             )?,
-            Self::Constant(_) => (),
+            Self::Constant(_) | Self::Steps => (),
+            Self::Negated(v) => v.check_new(runtime_args, memory_config)?,
             Self::IndexMemoryCell(t) => check_index_memory_cell(
                 runtime_args,
                 t,
@@ -716,18 +1311,24 @@ mod tests {
     use std::collections::HashSet;
 
     use crate::{
+        base::{Comparison, Operation},
+        cli::{CheckLoadArgs, GlobalArgs, InstructionLimitingArgs, MemoryArgs},
         instructions::{
-            error_handling::{BuildProgramError, BuildProgramErrorTypes},
-            IndexMemoryCellIndexType, Instruction,
+            error_handling::{BuildProgramError, BuildProgramErrorTypes, InstructionParseError},
+            spec::Spec,
+            IndexMemoryCellIndexType, Instruction, TargetType, Value,
         },
         runtime::{
             builder::{
-                build_instructions, check_index_memory_cell, check_instructions, InstructionConfig,
+                build_instructions, check_grammar, check_index_memory_cell, check_instructions,
+                InstructionConfig, RuntimeBuilder, RuntimeLimits, Span,
             },
-            error_handling::RuntimeBuildError,
+            error_handling::{BuildError, RuntimeBuildError, RuntimeErrorType},
+            initial_values::InitialValues,
+            memory_config::MemoryConfig,
             ControlFlow, RuntimeMemory,
         },
-        utils::test_utils,
+        utils::test_utils::{self, string_literal_to_vec},
     };
 
     #[test]
@@ -774,6 +1375,235 @@ mod tests {
         assert!(!rt.memory.accumulators.contains_key(&4));
     }
 
+    #[test]
+    fn test_program_header_applied() {
+        let instructions = r#"
+            # alpha_tui: accumulators=3 memory=h1,h2 index-memory=4 gamma=true
+            a1 := 5
+        "#;
+        let mut rb = RuntimeBuilder::new(&string_literal_to_vec(instructions), "test").unwrap();
+        rb.apply_program_header(&string_literal_to_vec(instructions))
+            .unwrap()
+            .apply_memory_args(&MemoryArgs {
+                disable_memory_detection: true,
+                ..Default::default()
+            });
+        let rt = rb.build().unwrap();
+        assert_eq!(rt.memory.accumulators.len(), 3);
+        assert!(rt.memory.memory_cells.contains_key("h1"));
+        assert!(rt.memory.memory_cells.contains_key("h2"));
+        assert!(rt.memory.index_memory_cells.contains_key(&4));
+        assert!(rt.memory.gamma.is_some());
+    }
+
+    #[test]
+    fn test_initial_values_applied() {
+        let instructions = string_literal_to_vec(
+            "// input of the program @init h1=5, a0=3\n# alpha_tui: memory=h1\na := a + p(h1) // @init p(h2)=10",
+        );
+        let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        let mut memory_config = MemoryConfig::default();
+        memory_config
+            .memory_cells
+            .values
+            .insert("h1".to_string(), Some(1));
+        rb.set_memory_config(memory_config);
+        rb.apply_program_header(&instructions).unwrap();
+        let mut rt = rb.build().unwrap();
+        assert_eq!(rt.memory.memory_cells["h1"].data, Some(5));
+        assert_eq!(rt.memory.memory_cells["h2"].data, Some(10));
+        rt.run().unwrap();
+        assert_eq!(rt.memory.accumulators[&0].data, Some(8));
+        // the initial values are restored when the program is reset
+        rt.reset();
+        assert_eq!(rt.memory.accumulators[&0].data, Some(3));
+        let rb = RuntimeBuilder::new(&string_literal_to_vec("a := 1 // @init h1=five"), "test");
+        assert!(matches!(
+            rb.err().unwrap().reason,
+            BuildProgramErrorTypes::InitialValuesInvalid(1, _, _)
+        ));
+        // the line is counted in the source file
+        let rb = RuntimeBuilder::new(
+            &string_literal_to_vec("# alpha_tui: memory=h1\na := 1 // @init h1=five"),
+            "test",
+        );
+        assert!(matches!(
+            rb.err().unwrap().reason,
+            BuildProgramErrorTypes::InitialValuesInvalid(2, _, _)
+        ));
+    }
+
+    #[test]
+    fn test_initial_values_memory_config_file() {
+        // the values of an explicit memory config file are kept
+        let instructions = string_literal_to_vec("a := a // @init a0=1, h1=7, h2=8, h4=2");
+        let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        rb.apply_global_cli_args(&GlobalArgs {
+            memory_config_file: Some(
+                "tests/input/test_memory_config_toml/memory_config.toml".to_string(),
+            ),
+            ..Default::default()
+        })
+        .unwrap();
+        rb.apply_program_header(&instructions).unwrap();
+        let rt = rb.build().unwrap();
+        assert_eq!(rt.memory.accumulators[&0].data, Some(4));
+        assert_eq!(rt.memory.memory_cells["h1"].data, Some(3));
+        // locations without value in the file get the initial value
+        assert_eq!(rt.memory.memory_cells["h2"].data, Some(8));
+        assert_eq!(rt.memory.memory_cells["h4"].data, Some(2));
+    }
+
+    #[test]
+    fn test_macros_expanded() {
+        let instructions = string_literal_to_vec(
+            "macro swap(x, y)\n    a := x\n    x := y\n    y := a\nendmacro\np(h1) := 1\np(h2) := 2\nswap(p(h1), p(h2))",
+        );
+        let mut rt = RuntimeBuilder::new(&instructions, "test")
+            .unwrap()
+            .build()
+            .unwrap();
+        // the lines of the definition are kept, so that the instructions match the lines of the program
+        assert_eq!(rt.instructions.len(), 8);
+        assert_eq!(rt.instructions[0], Instruction::Noop);
+        rt.run().unwrap();
+        assert_eq!(rt.memory.memory_cells["h1"].data, Some(2));
+        assert_eq!(rt.memory.memory_cells["h2"].data, Some(1));
+        // the macro is run in a single step, the lines of the definition are not counted
+        assert_eq!(rt.control_flow.steps, 3);
+        for (program, line) in [
+            ("swap(a, a1)", 1),
+            ("macro swap(x, y)\nendmacro\nswap(a)", 3),
+            ("macro swap(x, y)\na := x\nendmacro\na := 1\nendmacro", 5),
+        ] {
+            let rb = RuntimeBuilder::new(&string_literal_to_vec(program), "test");
+            assert!(
+                matches!(
+                    rb.err().unwrap().reason,
+                    BuildProgramErrorTypes::MacroInvalid(l, _) if l == line
+                ),
+                "{program}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_loops_lowered() {
+        let instructions = string_literal_to_vec(
+            "a := 3\nwhile a > 0 do\n    p(h1) := p(h1) + a\n    a := a - 1\nendwhile\nloop 4 times\n    p(h2) := p(h2) + 2\nendloop\nloop 0 times\n    a := 9\nendloop",
+        );
+        let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        let mut memory_config = MemoryConfig::default();
+        for name in ["h1", "h2"] {
+            memory_config
+                .memory_cells
+                .values
+                .insert(name.to_string(), Some(0));
+        }
+        memory_config.memory_cells.autodetection = Some(false);
+        rb.set_memory_config(memory_config);
+        let mut rt = rb.build().unwrap();
+        assert_eq!(rt.instructions.len(), 11);
+        assert_eq!(
+            rt.instructions[1].description(),
+            "Part of a loop, runs the jumps it is lowered into in one step: if a0 <= 0 then goto endwhile 2"
+        );
+        assert_eq!(rt.control_flow.instruction_labels["endwhile 2"], 5);
+        rt.run().unwrap();
+        assert_eq!(rt.memory.memory_cells["h1"].data, Some(6));
+        assert_eq!(rt.memory.memory_cells["h2"].data, Some(8));
+        // the counter is created even though autodetection is disabled
+        assert_eq!(rt.memory.memory_cells["loop 6"].data, Some(0));
+        assert_eq!(rt.memory.accumulators[&0].data, Some(0));
+        for (program, line) in [
+            ("while a > 0 do\na := 1", 1),
+            ("a := 1\nendloop", 2),
+            ("loop 3 times\nwhile a > 0 do\nendloop\nendwhile", 3),
+        ] {
+            let rb = RuntimeBuilder::new(&string_literal_to_vec(program), "test");
+            assert!(
+                matches!(
+                    rb.err().unwrap().reason,
+                    BuildProgramErrorTypes::LoopInvalid(l, _) if l == line
+                ),
+                "{program}"
+            );
+        }
+        // the lines of the loops are counted in the source file
+        let rb = RuntimeBuilder::new(
+            &string_literal_to_vec("# alpha_tui: accumulators=1\nloop 3 times\nendwhile"),
+            "test",
+        );
+        assert!(matches!(
+            rb.err().unwrap().reason,
+            BuildProgramErrorTypes::LoopInvalid(3, reason) if reason.contains("line 2")
+        ));
+        // the generated names don't clash with labels and memory cells of the program
+        let mut rt = test_utils::runtime_from_str(
+            "# alpha_tui: accumulators=1\nloop2: p(loop2) := 7\na := 0\nloop 2 times\na := a + 1\nendloop\nwhile2: goto END",
+        )
+        .unwrap();
+        assert_eq!(rt.control_flow.instruction_labels["loop2"], 0);
+        assert_eq!(rt.control_flow.instruction_labels["loop 4"], 3);
+        rt.run().unwrap();
+        assert_eq!(rt.memory.memory_cells["loop2"].data, Some(7));
+        assert_eq!(rt.memory.accumulators[&0].data, Some(2));
+    }
+
+    #[test]
+    fn test_seed_fixed_when_build() {
+        // without seed, a seed is generated and stored, so that the run can be repeated
+        let rt = test_utils::runtime_from_str("a := rand(1, 100)").unwrap();
+        let seed = rt.settings.seed.unwrap();
+        assert_eq!(rt.control_flow.random.seed(), seed);
+    }
+
+    #[test]
+    fn test_computed_goto() {
+        // jump table, the value of a0 selects the line that is run
+        let mut rt = test_utils::runtime_from_str(
+            "a := 2\na := a + 4\ngoto a0\np(h1) := 1\ngoto END\np(h1) := 2\ngoto END",
+        )
+        .unwrap();
+        rt.run().unwrap();
+        assert_eq!(rt.memory.memory_cells["h1"].data, Some(2));
+        // labels that are named like a memory location are still jumped to
+        let rt = test_utils::runtime_from_str("goto a1\na := 1\na1: a := 2").unwrap();
+        assert_eq!(rt.instructions[0], Instruction::Goto("a1".to_string()));
+        // the value is the line in the file, lines starting with # are counted too
+        let mut rt = test_utils::runtime_from_str(
+            "# alpha_tui: accumulators=1\na := 5\ngoto a0\np(h1) := 1\np(h1) := 2",
+        )
+        .unwrap();
+        rt.run().unwrap();
+        assert_eq!(rt.memory.memory_cells["h1"].data, Some(2));
+        // the header line contains no instruction
+        let mut rt =
+            test_utils::runtime_from_str("# alpha_tui: accumulators=1\na := 1\ngoto a0").unwrap();
+        assert_eq!(
+            rt.run().unwrap_err().reason,
+            RuntimeErrorType::JumpTargetOutOfRange(1, 3)
+        );
+    }
+
+    #[test]
+    fn test_entry_call() {
+        let instructions = string_literal_to_vec("main: a := 1\nf: pop\nreturn\na := 2");
+        let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        rb.apply_check_load_args(&CheckLoadArgs {
+            call: Some("f".to_string()),
+            args: Some(vec![4, 7]),
+            ..Default::default()
+        })
+        .unwrap();
+        let mut rt = rb.build().unwrap();
+        assert_eq!(rt.next_instruction_index(), 1);
+        assert_eq!(rt.runtime_memory().stack, vec![4, 7]);
+        rt.run().unwrap();
+        assert_eq!(rt.runtime_memory().accumulators[&0].data, Some(7));
+        assert_eq!(rt.runtime_memory().stack, vec![4]);
+    }
+
     #[test]
     fn test_check_index_memory_cell() {
         let mut args = RuntimeMemory::new_empty();
@@ -820,7 +1650,12 @@ mod tests {
             .split('\n')
             .map(|f| f.to_string())
             .collect::<Vec<String>>();
-        build_instructions(&lines, "test", &mut ControlFlow::new())
+        build_instructions(
+            &lines,
+            "test",
+            &mut ControlFlow::new(),
+            &mut InitialValues::default(),
+        )
     }
 
     #[test]
@@ -861,6 +1696,8 @@ mod tests {
             allowed_instruction_identifiers: Some(allowed_instruction_identifiers),
             allowed_comparisons: None,
             allowed_operations: None,
+            spec: None,
+            strict: false,
         };
         let res = check_instructions(&instructions, &allowed_instructions);
         assert_eq!(
@@ -883,6 +1720,8 @@ mod tests {
             allowed_instruction_identifiers: None,
             allowed_comparisons: Some(Vec::new()),
             allowed_operations: None,
+            spec: None,
+            strict: false,
         };
         assert!(check_instructions(&instructions, &allowed_instructions).is_err());
     }
@@ -894,7 +1733,167 @@ mod tests {
             allowed_instruction_identifiers: None,
             allowed_comparisons: None,
             allowed_operations: Some(Vec::new()),
+            spec: None,
+            strict: false,
         };
         assert!(check_instructions(&instructions, &allowed_instructions).is_err());
     }
+
+    #[test]
+    fn test_bpe_instruction_not_in_spec() {
+        let instructions = build_instructions_test("a := 5\nstack*").unwrap();
+        let mut instruction_config = InstructionConfig {
+            spec: Some(Spec::V2022),
+            ..Default::default()
+        };
+        assert_eq!(
+            check_instructions(&instructions, &instruction_config),
+            Err(Box::new(BuildProgramError {
+                reason: BuildProgramErrorTypes::InstructionNotInSpec(
+                    2,
+                    "stack*".to_string(),
+                    "2022".to_string(),
+                    "the stack operation 'stack*' can not be used".to_string()
+                )
+            }))
+        );
+        instruction_config.spec = Some(Spec::V2023);
+        assert!(check_instructions(&instructions, &instruction_config).is_ok());
+    }
+
+    #[test]
+    fn test_bpe_operand_not_allowed() {
+        let lines = "# comment\na := 5\nloop: a := p(h1) + a"
+            .split('\n')
+            .map(|f| f.to_string())
+            .collect::<Vec<String>>();
+        let instructions = build_instructions(
+            &lines,
+            "test",
+            &mut ControlFlow::new(),
+            &mut InitialValues::default(),
+        )
+        .unwrap();
+        assert!(check_grammar(&instructions[..1], &lines, "test", &HashSet::new(), None).is_ok());
+        let res = check_grammar(&instructions, &lines, "test", &HashSet::new(), None);
+        assert!(matches!(
+            res.map_err(|e| e.reason),
+            Err(BuildProgramErrorTypes::ParseError {
+                reason: InstructionParseError::OperandNotAllowed((11, 15), operand, _),
+                ..
+            }) if operand == "p(h1)"
+        ));
+    }
+
+    #[test]
+    fn test_add_instruction() {
+        let mut rb = RuntimeBuilder::empty("generated");
+        rb.add_instruction(
+            Instruction::Assign(TargetType::Accumulator(0), Value::Constant(3)),
+            None,
+            None,
+        )
+        .unwrap()
+        .add_instruction(
+            Instruction::Calc(
+                TargetType::Accumulator(0),
+                Value::Accumulator(0),
+                Operation::Sub,
+                Value::Constant(1),
+            ),
+            Some("loop"),
+            Some(Span::from("a := a - 1")),
+        )
+        .unwrap()
+        .add_instruction(
+            Instruction::JumpIf(
+                Value::Accumulator(0),
+                Comparison::Gt,
+                Value::Constant(0),
+                "loop".to_string(),
+            ),
+            None,
+            None,
+        )
+        .unwrap()
+        .with_memory(MemoryConfig::default())
+        .with_limits(RuntimeLimits {
+            max_steps: Some(100),
+            ..Default::default()
+        });
+        assert!(matches!(
+            rb.add_instruction(Instruction::Noop, Some("loop"), None)
+                .map(|_| ())
+                .map_err(|e| e.reason),
+            Err(BuildProgramErrorTypes::LabelDefinedMultipleTimes(label)) if label == "loop"
+        ));
+        let mut rt = rb.build().unwrap();
+        rt.run().unwrap();
+        assert_eq!(rt.runtime_memory().accumulators[&0].data, Some(0));
+        assert_eq!(rt.control_flow().steps, 7);
+    }
+
+    #[test]
+    fn test_add_instruction_operand_not_allowed() {
+        let instruction = || {
+            Instruction::Calc(
+                TargetType::Accumulator(0),
+                Value::MemoryCell("h1".to_string()),
+                Operation::Add,
+                Value::Accumulator(0),
+            )
+        };
+        // errors of instructions without span don't refer to a source
+        let mut rb = RuntimeBuilder::empty("generated");
+        rb.add_instruction(instruction(), None, None).unwrap();
+        rb.apply_instruction_limiting_args(&InstructionLimitingArgs {
+            strict: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let err = rb.build().unwrap_err();
+        assert!(matches!(
+            err,
+            BuildError::Program(e) if matches!(
+                &e.reason,
+                BuildProgramErrorTypes::OperandNotAllowed(1, operand, _, _) if operand == "p(h1)"
+            )
+        ));
+        // the operand is marked in the span
+        let mut rb = RuntimeBuilder::empty("generated");
+        rb.add_instruction(instruction(), None, Some(Span::from("a := p(h1) + a")))
+            .unwrap();
+        rb.apply_instruction_limiting_args(&InstructionLimitingArgs {
+            strict: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let err = rb.build().unwrap_err();
+        assert!(matches!(
+            err,
+            BuildError::Program(e) if matches!(e.reason, BuildProgramErrorTypes::ParseError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_apply_errors() {
+        let mut rb = RuntimeBuilder::empty("generated");
+        let result = rb.apply_instruction_limiting_args(&InstructionLimitingArgs {
+            allowed_instructions_file: Some("tests/input/missing.json".to_string()),
+            ..Default::default()
+        });
+        assert!(matches!(
+            result,
+            Err(BuildError::Runtime(RuntimeBuildError::InstructionConfigFileInvalid(path, _)))
+                if path == "tests/input/missing.json"
+        ));
+        let result =
+            rb.apply_program_header(&string_literal_to_vec("# alpha_tui: stack=5\na := 5"));
+        assert!(matches!(
+            result,
+            Err(BuildError::Runtime(
+                RuntimeBuildError::ProgramHeaderInvalid(_)
+            ))
+        ));
+    }
 }
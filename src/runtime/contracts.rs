@@ -0,0 +1,130 @@
+use std::fmt::Display;
+
+use crate::{base::Comparison, instructions::Value};
+
+use super::{error_handling::RuntimeErrorType, ControlFlow, RuntimeMemory};
+
+/// Condition of a contract, of the form `VALUE CMP VALUE`, e.g. `a1 >= 0`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    value_a: Value,
+    cmp: Comparison,
+    value_b: Value,
+}
+
+impl Condition {
+    /// Tries to parse the condition, returns the reason if the condition is invalid.
+    fn try_parse(condition: &str) -> Result<Self, String> {
+        let parts = condition
+            .split_whitespace()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        if parts.len() != 3 {
+            return Err(format!(
+                "'{}' is not a condition of the form 'VALUE CMP VALUE'",
+                condition.trim()
+            ));
+        }
+        let value_a =
+            Value::try_from((&parts[0], (0, parts[0].len()))).map_err(|e| e.to_string())?;
+        let cmp = Comparison::try_from(parts[1].as_str())
+            .map_err(|()| format!("unknown comparison '{}'", parts[1]))?;
+        let value_b =
+            Value::try_from((&parts[2], (0, parts[2].len()))).map_err(|e| e.to_string())?;
+        Ok(Self {
+            value_a,
+            cmp,
+            value_b,
+        })
+    }
+
+    /// Returns true if the condition holds.
+    ///
+    /// Returns an error if a value of the condition can not be read.
+    pub fn holds(
+        &self,
+        runtime_memory: &RuntimeMemory,
+        control_flow: &ControlFlow,
+    ) -> Result<bool, RuntimeErrorType> {
+        Ok(self.cmp.cmp(
+            self.value_a.value(runtime_memory, control_flow)?,
+            self.value_b.value(runtime_memory, control_flow)?,
+        ))
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.value_a, self.cmp, self.value_b)
+    }
+}
+
+/// Contract of a function, declared with annotations in the comments of the label.
+///
+/// `@requires` conditions are checked when the function is called, `@ensures` conditions when it returns.
+///
+/// Example: `fac: // @requires a1 >= 0 @ensures a2 > 0`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Contract {
+    pub requires: Vec<Condition>,
+    pub ensures: Vec<Condition>,
+}
+
+impl Contract {
+    /// Parses the annotations in `comment`, other annotations than `@requires` and `@ensures` are ignored.
+    ///
+    /// Returns the annotation and the reason, if an annotation is invalid.
+    pub fn parse_annotations(&mut self, comment: &str) -> Result<(), (String, String)> {
+        for annotation in comment.split('@').skip(1) {
+            let annotation = annotation.trim();
+            let (conditions, condition) =
+                if let Some(condition) = annotation.strip_prefix("requires") {
+                    (&mut self.requires, condition)
+                } else if let Some(condition) = annotation.strip_prefix("ensures") {
+                    (&mut self.ensures, condition)
+                } else {
+                    continue;
+                };
+            match Condition::try_parse(condition) {
+                Ok(condition) => conditions.push(condition),
+                Err(e) => return Err((format!("@{annotation}"), e)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if the contract does not contain any condition.
+    pub fn is_empty(&self) -> bool {
+        self.requires.is_empty() && self.ensures.is_empty()
+    }
+}
+
+impl Display for Contract {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let conditions = self
+            .requires
+            .iter()
+            .map(|f| format!("requires {f}"))
+            .chain(self.ensures.iter().map(|f| format!("ensures {f}")))
+            .collect::<Vec<_>>();
+        write!(f, "{}", conditions.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Contract;
+
+    #[test]
+    fn test_parse_annotations() {
+        let mut contract = Contract::default();
+        contract
+            .parse_annotations(
+                "// computes a2 := a1! @requires a1 >= 0  @ensures a2 > 0 @author me",
+            )
+            .unwrap();
+        assert_eq!(contract.to_string(), "requires a1 >= 0, ensures a2 > 0");
+        assert!(contract.parse_annotations("// @requires a1 >= ").is_err());
+        assert!(contract.parse_annotations("// @ensures a1 ! 0").is_err());
+    }
+}
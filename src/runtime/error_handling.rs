@@ -1,7 +1,38 @@
+use std::{
+    cell::Cell,
+    panic::{self, AssertUnwindSafe},
+    sync::Once,
+};
+
 use miette::Diagnostic;
 use thiserror::Error;
 
-use crate::base::Operation;
+use crate::{
+    base::Operation,
+    instructions::{error_handling::BuildProgramError, StackCommand},
+};
+
+/// Errors that can occur when a program is built into a runtime by a `RuntimeBuilder`.
+///
+/// Either the program could not be built or the runtime could not be constructed from it, the diagnostic of the
+/// wrapped error is passed through, so that its code and help are kept.
+#[derive(Debug, PartialEq, Error, Diagnostic)]
+pub enum BuildError {
+    /// The program could not be parsed or uses instructions that are not allowed.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Program(#[from] Box<BuildProgramError>),
+    /// The program is valid, but the memory or labels it uses are missing.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Runtime(#[from] RuntimeBuildError),
+}
+
+impl From<BuildProgramError> for BuildError {
+    fn from(value: BuildProgramError) -> Self {
+        Self::Program(Box::new(value))
+    }
+}
 
 /// Errors that can occur when a runtime is constructed from a `RuntimeBuilder`.
 #[derive(Debug, PartialEq, Error, Diagnostic)]
@@ -37,6 +68,27 @@ pub enum RuntimeBuildError {
         help("Make sure that the provided file is formatted correctly.\nSee https://github.com/LMH01/alpha_tui/blob/master/examples/memory_config.json for an example.")
     )]
     MemoryConfigFileInvalid(String, String),
+
+    #[error("Instruction config file '{0}' is invalid: {1}")]
+    #[diagnostic(
+        code("runtime_build_error::instruction_config_file_invalid"),
+        help("Make sure that the provided file is formatted correctly.\nSee https://github.com/LMH01/alpha_tui/blob/master/examples/allowed_instructions.json for an example.")
+    )]
+    InstructionConfigFileInvalid(String, String),
+
+    #[error("Program header is invalid: {0}")]
+    #[diagnostic(
+        code("runtime_build_error::program_header_invalid"),
+        help("The program header consists of lines of the form '# alpha_tui: key=value key=value'.\nSupported keys are: accumulators, memory, index-memory, gamma and max-steps.\nExample: '# alpha_tui: accumulators=4 memory=h1,h2 max-steps=10000'")
+    )]
+    ProgramHeaderInvalid(String),
+
+    #[error("Internal error while building the program: {0}")]
+    #[diagnostic(
+        code("runtime_build_error::internal_error"),
+        help("Building the program caused a panic, that was caught because '--robust' is set.\nThis is a bug in alpha_tui, please report it together with the program.")
+    )]
+    InternalError(String),
 }
 
 #[derive(Debug, Error, Diagnostic, Clone, PartialEq)]
@@ -137,6 +189,22 @@ pub enum RuntimeErrorType {
     )]
     StackOpFail(Operation),
 
+    #[error("Attempt to perform 'stack {0}' while stack does not contain {1} values")]
+    #[diagnostic(
+        code("runtime_error::stack_command_fail"),
+        help("Make sure to only use 'stack {0}' when you know that the stack contains at least {1} values")
+    )]
+    StackCommandFail(StackCommand, usize),
+
+    #[error("Attempt to peek at the top value of the stack while stack is empty")]
+    #[diagnostic(
+        code("runtime_error::peek_fail"),
+        help(
+            "Make sure to only use peek when you know that the stack contains at least one value"
+        )
+    )]
+    PeekFail,
+
     #[error("Stack Overflow")]
     #[diagnostic(
         code("runtime_error::stack_overflow_error"),
@@ -151,6 +219,13 @@ pub enum RuntimeErrorType {
     )]
     LabelMissing(String),
 
+    #[error("Attempt to jump to line '{0}' that does not exist")]
+    #[diagnostic(
+        code("runtime_error::jump_target_out_of_range"),
+        help("A jump to a value can only jump to the lines 1 to {1} of the program, lines starting with '#' are skipped.\nMake sure that the value contains a line of the program, use 'goto END' to end the program.")
+    )]
+    JumpTargetOutOfRange(i32, usize),
+
     //#[error("Attempt to divide by zero")]
     //#[diagnostic(
     //    code("runtime_error::attempt_to_divide_by_zero"),
@@ -170,6 +245,103 @@ pub enum RuntimeErrorType {
         help("You have run over {0} instructions, this tool is not build for that.\nIf you know exactly what you are doing and would like to circumvent this limit use the '--disable-instruction-limit' option\nWarning: This can cause the progrmm to freeze!")
     )]
     DesignLimitReached(usize),
+
+    #[error("Step limit reached")]
+    #[diagnostic(
+        code("runtime_error::step_limit_reached"),
+        help("The program is only allowed to run {0} steps, check if the program contains an infinite loop.")
+    )]
+    StepLimitReached(usize),
+
+    #[error("Return without call")]
+    #[diagnostic(
+        code("runtime_error::return_without_call"),
+        help("The return instruction was run, but no function was called that could be returned from.\nUse 'goto END' to end the program or call the function with 'call LABEL'.")
+    )]
+    ReturnWithoutCall,
+
+    #[error("Program finished before all functions returned")]
+    #[diagnostic(
+        code("runtime_error::call_not_returned"),
+        help("The program finished, while the functions called in the following lines did not return: {0}\nMake sure that each function ends with a return instruction.")
+    )]
+    CallNotReturned(String),
+
+    #[error("Precondition of '{0}' violated: {1}")]
+    #[diagnostic(
+        code("runtime_error::precondition_violated"),
+        help("The function '{0}' requires '{1}' when it is called, as declared with '@requires'.\nMake sure that the condition holds before the function is called.")
+    )]
+    PreconditionViolated(String, String),
+
+    #[error("Postcondition of '{0}' violated: {1}")]
+    #[diagnostic(
+        code("runtime_error::postcondition_violated"),
+        help("The function '{0}' ensures '{1}' when it returns, as declared with '@ensures'.\nCheck the implementation of the function.")
+    )]
+    PostconditionViolated(String, String),
+
+    #[error("Assertion failed: {0}")]
+    #[diagnostic(
+        code("runtime_error::assertion_failed"),
+        help("The left side of the assertion has the value {1} and the right side the value {2}.\nCheck why the values differ from what the assertion expects at this point of the program.")
+    )]
+    AssertionFailed(String, i32, i32),
+
+    #[error("Internal error: {0}")]
+    #[diagnostic(
+        code("runtime_error::internal_error"),
+        help("Running the instruction caused a panic, that was caught because '--robust' is set.\nThis is a bug in alpha_tui, please report it together with the program.")
+    )]
+    InternalError(String),
+}
+
+impl RuntimeError {
+    /// Runs `f` and returns a panic that occurs in it as [`RuntimeErrorType::InternalError`] in line `line_number`.
+    ///
+    /// Used by the robust mode, see [`RuntimeSettings::robust`](super::RuntimeSettings::robust).
+    pub fn catch_panic<T>(
+        line_number: usize,
+        f: impl FnOnce() -> Result<T, RuntimeError>,
+    ) -> Result<T, RuntimeError> {
+        catch_panic(f).unwrap_or_else(|message| {
+            Err(RuntimeError {
+                reason: RuntimeErrorType::InternalError(message),
+                line_number,
+            })
+        })
+    }
+}
+
+thread_local! {
+    /// Set while [`catch_panic`] runs a function, panics of this thread are not printed then.
+    static CATCHING_PANIC: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` and returns the message of a panic that occurs in it as error.
+///
+/// The panic is not printed, so that it does not garble the tui. Panics that are not caught by this function are
+/// printed like before.
+pub fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    static SILENT_PANIC_HOOK: Once = Once::new();
+    SILENT_PANIC_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING_PANIC.with(Cell::get) {
+                previous(info);
+            }
+        }));
+    });
+    let catching = CATCHING_PANIC.with(|f| f.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING_PANIC.with(|f| f.set(catching));
+    result.map_err(|payload| {
+        payload
+            .downcast_ref::<&str>()
+            .map(ToString::to_string)
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string())
+    })
 }
 
 #[derive(Debug, Clone, PartialEq, Error, Diagnostic)]
@@ -187,16 +359,37 @@ pub enum CalcError {
         help("{1} would have resulted in an overflow leading to a wrong value.\nMake sure the integer never leaves the following rmnge: [{},{}]", i32::MIN, i32::MAX)
     )]
     AttemptToOverflow(String, String),
+
+    #[error("Attempt to raise to a negative power")]
+    #[diagnostic(
+        code("calc_error::attempt_to_use_negative_exponent"),
+        help("The result of a negative exponent is a fraction, that can not be stored as integer")
+    )]
+    AttemptToUseNegativeExponent(),
+
+    #[error("Attempt to calculate the square root of a negative number")]
+    #[diagnostic(
+        code("calc_error::attempt_to_take_root_of_negative_number"),
+        help("The square root of a negative number is not a real number")
+    )]
+    AttemptToTakeRootOfNegativeNumber(),
 }
 
 #[cfg(test)]
 mod tests {
+    use miette::Diagnostic;
+
     use crate::{
         base::{MemoryCell, Operation},
+        cli::CheckLoadArgs,
         instructions::{IndexMemoryCellIndexType, Instruction, TargetType, Value},
         runtime::{
-            error_handling::{CalcError, RuntimeBuildError, RuntimeErrorType},
-            ControlFlow, RuntimeMemory, RuntimeSettings, MAX_INSTRUCTION_RUNS,
+            builder::RuntimeBuilder,
+            error_handling::{
+                catch_panic, BuildError, CalcError, RuntimeBuildError, RuntimeError,
+                RuntimeErrorType,
+            },
+            ControlFlow, Runtime, RuntimeMemory, RuntimeSettings, MAX_INSTRUCTION_RUNS,
         },
         utils::test_utils,
     };
@@ -205,11 +398,8 @@ mod tests {
     fn test_rbe_label_undefined_error() {
         let rt = test_utils::runtime_from_str("goto loop").unwrap_err();
         assert_eq!(
-            format!("{:?}", rt.root_cause()),
-            format!(
-                "{:?}",
-                RuntimeBuildError::LabelUndefined("loop".to_string())
-            ),
+            rt,
+            BuildError::Runtime(RuntimeBuildError::LabelUndefined("loop".to_string()))
         );
     }
 
@@ -218,11 +408,8 @@ mod tests {
         let rt =
             test_utils::runtime_from_str_with_disable_memory_detection("p(h1) := 10").unwrap_err();
         assert_eq!(
-            format!("{:?}", rt.root_cause()),
-            format!(
-                "{:?}",
-                RuntimeBuildError::MemoryCellMissing("h1".to_string())
-            ),
+            rt,
+            BuildError::Runtime(RuntimeBuildError::MemoryCellMissing("h1".to_string()))
         );
     }
 
@@ -230,21 +417,15 @@ mod tests {
     fn test_rbe_accumulator_missing() {
         let rt = test_utils::runtime_from_str_with_disable_memory_detection("a := 10").unwrap_err();
         assert_eq!(
-            format!("{:?}", rt.root_cause()),
-            format!(
-                "{:?}",
-                RuntimeBuildError::AccumulatorMissing("0".to_string())
-            ),
+            rt,
+            BuildError::Runtime(RuntimeBuildError::AccumulatorMissing("0".to_string()))
         );
     }
 
     #[test]
     fn test_rbe_gamma_disabled() {
         let rt = test_utils::runtime_from_str_with_disable_memory_detection("y := 10").unwrap_err();
-        assert_eq!(
-            format!("{:?}", rt.root_cause()),
-            format!("{:?}", RuntimeBuildError::GammaDisabled),
-        );
+        assert_eq!(rt, BuildError::Runtime(RuntimeBuildError::GammaDisabled));
     }
 
     #[test]
@@ -424,6 +605,183 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_re_step_limit_reached() {
+        let mut rt = test_utils::runtime_from_str_with_program_header(
+            "# alpha_tui: max-steps=10\nloop: a := 1\ngoto loop",
+        )
+        .unwrap();
+        assert_eq!(
+            rt.run().unwrap_err().reason,
+            RuntimeErrorType::StepLimitReached(10)
+        );
+    }
+
+    #[test]
+    fn test_build_error_code() {
+        let code = |e: BuildError| e.code().map(|f| f.to_string());
+        assert_eq!(
+            code(BuildError::from(RuntimeBuildError::GammaDisabled)),
+            Some("runtime_build_error::gamma_disabled".to_string())
+        );
+        let err = RuntimeBuilder::new(&["a := a +".to_string()], "test")
+            .err()
+            .unwrap();
+        assert_eq!(
+            code(BuildError::from(err)),
+            Some("build_program_error".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rbe_program_header_invalid() {
+        let rt = test_utils::runtime_from_str_with_program_header("# alpha_tui: stack=5\na := 5")
+            .unwrap_err();
+        assert_eq!(
+            rt,
+            BuildError::Runtime(RuntimeBuildError::ProgramHeaderInvalid(
+                "unknown key 'stack'".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_re_internal_error() {
+        let err = RuntimeError::catch_panic::<()>(3, || panic!("index out of bounds"));
+        assert_eq!(
+            err.unwrap_err(),
+            RuntimeError {
+                reason: RuntimeErrorType::InternalError("index out of bounds".to_string()),
+                line_number: 3,
+            }
+        );
+        assert_eq!(RuntimeError::catch_panic(1, || Ok(5)), Ok(5));
+        // panics can be caught while others are caught
+        assert_eq!(
+            catch_panic(|| catch_panic(|| panic!("inner")).unwrap_err() + "!"),
+            Ok("inner!".to_string())
+        );
+        // the robust mode does not change the result of programs that don't panic
+        let program = test_utils::string_literal_to_vec("a := 5\npop");
+        let mut rb = RuntimeBuilder::new(&program, "test").unwrap();
+        rb.apply_check_load_args(&CheckLoadArgs {
+            robust: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut rt = rb.build().unwrap();
+        assert_eq!(rt.run().unwrap_err().reason, RuntimeErrorType::PopFail);
+    }
+
+    #[test]
+    fn test_robust_smoke() {
+        // malformed programs are reported as errors in every stage of the robust mode
+        for program in [
+            "",
+            "a := 5\nloop: a := a + 1\ngoto loop",
+            "p(p(h1)) := -a\nif a < 0 && then goto",
+            "# alpha_tui: max-steps=\u{0}\nstack+ γ",
+            "while a > 0 do\nendloop",
+            "# alpha_tui: accumulators=1\ngoto a0",
+        ] {
+            let program = test_utils::string_literal_to_vec(program);
+            let Ok(mut rb) = RuntimeBuilder::new_robust(&program, "test") else {
+                continue;
+            };
+            rb.apply_check_load_args(&CheckLoadArgs {
+                robust: true,
+                max_steps: Some(100),
+                ..Default::default()
+            })
+            .unwrap();
+            if let Ok(mut rt) = rb.build() {
+                _ = rt.run();
+            }
+        }
+    }
+
+    fn runtime_with_strict_calls(program: &str) -> Runtime {
+        let program = test_utils::string_literal_to_vec(program);
+        let mut rb = RuntimeBuilder::new(&program, "test").unwrap();
+        rb.apply_check_load_args(&CheckLoadArgs {
+            strict_calls: true,
+            ..Default::default()
+        })
+        .unwrap();
+        rb.build().unwrap()
+    }
+
+    #[test]
+    fn test_re_return_without_call() {
+        let mut rt = runtime_with_strict_calls("a := 5\nreturn");
+        assert_eq!(
+            rt.run().unwrap_err().reason,
+            RuntimeErrorType::ReturnWithoutCall
+        );
+        // without strict calls return ends the program
+        let mut rt = test_utils::runtime_from_str("a := 5\nreturn\na := 6").unwrap();
+        assert!(rt.run().is_ok());
+    }
+
+    #[test]
+    fn test_re_call_not_returned() {
+        let mut rt =
+            runtime_with_strict_calls("main: call f\ngoto END\nf: call g\nreturn\ng: goto END");
+        let err = rt.run().unwrap_err();
+        assert_eq!(
+            err.reason,
+            RuntimeErrorType::CallNotReturned("line 1 -> line 3".to_string())
+        );
+        assert_eq!(err.line_number, 5);
+        let mut rt = runtime_with_strict_calls("main: call f\ngoto END\nf: return");
+        assert!(rt.run().is_ok());
+    }
+
+    #[test]
+    fn test_re_contract_violated() {
+        let program = "main: a1 := 3
+call double
+goto END
+// doubles a1
+// @requires a1 >= 0
+double: a2 := a1 * 2 // @ensures a2 > a1
+return";
+        let mut rt = test_utils::runtime_from_str(program).unwrap();
+        assert!(rt.run().is_ok());
+        let mut rt = test_utils::runtime_from_str(&program.replace("a1 := 3", "a1 := -3")).unwrap();
+        let err = rt.run().unwrap_err();
+        assert_eq!(
+            err.reason,
+            RuntimeErrorType::PreconditionViolated("double".to_string(), "a1 >= 0".to_string())
+        );
+        assert_eq!(err.line_number, 2);
+        let mut rt = test_utils::runtime_from_str(&program.replace("a1 * 2", "a1 - 2")).unwrap();
+        let err = rt.run().unwrap_err();
+        assert_eq!(
+            err.reason,
+            RuntimeErrorType::PostconditionViolated("double".to_string(), "a2 > a1".to_string())
+        );
+        assert_eq!(err.line_number, 7);
+    }
+
+    #[test]
+    fn test_re_assertion_failed() {
+        let program = "a := 3
+p(h1) := 5
+assert a >= 2
+assert a == p(h1)";
+        let mut rt = test_utils::runtime_from_str(program).unwrap();
+        let err = rt.run().unwrap_err();
+        assert_eq!(
+            err.reason,
+            RuntimeErrorType::AssertionFailed("a0 == p(h1)".to_string(), 3, 5)
+        );
+        assert_eq!(err.line_number, 4);
+        let mut rt =
+            test_utils::runtime_from_str(&program.replace("p(h1) := 5", "p(h1) := 3")).unwrap();
+        assert!(rt.run().is_ok());
+    }
+
     #[test]
     fn test_re_label_missing() {
         let mut rm = RuntimeMemory::new(1, vec!["a".to_string()], None, true);
@@ -435,6 +793,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_re_jump_target_out_of_range() {
+        for (line, error_line) in [(0, 0), (4, 4), (-1, -1)] {
+            let mut rt =
+                test_utils::runtime_from_str(&format!("p(h1) := {line}\ngoto p(h1)\na := 1"))
+                    .unwrap();
+            let err = rt.run().unwrap_err();
+            assert_eq!(
+                err.reason,
+                RuntimeErrorType::JumpTargetOutOfRange(error_line, 3)
+            );
+            assert_eq!(err.line_number, 2);
+        }
+    }
+
     #[test]
     fn test_re_ce_attempt_to_divide_by_zero() {
         let mut rm = RuntimeMemory::new(2, vec![], None, true);
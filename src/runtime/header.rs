@@ -0,0 +1,125 @@
+use std::str::FromStr;
+
+/// Configuration that is stored in the header of a program file.
+///
+/// The header consists of comment lines at the beginning of the file that start with `# alpha_tui:`,
+/// followed by `key=value` pairs, for example `# alpha_tui: accumulators=4 memory=h1,h2 max-steps=10000`.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ProgramHeader {
+    /// Number of accumulators that should be available.
+    pub accumulators: Option<usize>,
+    /// Memory cells that should be available.
+    pub memory_cells: Option<Vec<String>>,
+    /// Index memory cells that should be available.
+    pub index_memory_cells: Option<Vec<usize>>,
+    /// Determines if the gamma accumulator should be available.
+    pub gamma: Option<bool>,
+    /// Maximum number of steps that the program is allowed to run.
+    pub max_steps: Option<usize>,
+}
+
+/// Prefix that marks a comment line as part of the header.
+const HEADER_PREFIX: &str = "alpha_tui:";
+
+impl ProgramHeader {
+    /// Parses the header of the program.
+    ///
+    /// Only comment lines and empty lines at the beginning of the program are considered,
+    /// the header ends at the first line that contains an instruction or label.
+    ///
+    /// Returns `None` if the program does not contain a header or an error if a value of the header is invalid.
+    pub fn try_from_program(program: &[String]) -> Result<Option<Self>, String> {
+        let mut header = None;
+        for line in program {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some(comment) = line.strip_prefix("//").or_else(|| line.strip_prefix('#')) else {
+                break;
+            };
+            if let Some(values) = comment.trim().strip_prefix(HEADER_PREFIX) {
+                header
+                    .get_or_insert_with(Self::default)
+                    .apply_values(values)?;
+            }
+        }
+        Ok(header)
+    }
+
+    /// Applies the `key=value` pairs of one header line.
+    fn apply_values(&mut self, values: &str) -> Result<(), String> {
+        for pair in values.split_whitespace() {
+            let Some((key, value)) = pair.split_once('=') else {
+                return Err(format!("'{pair}' is not of the form key=value"));
+            };
+            match key {
+                "accumulators" => self.accumulators = Some(parse_value(key, value)?),
+                "memory" => {
+                    self.memory_cells = Some(value.split(',').map(ToString::to_string).collect());
+                }
+                "index-memory" => self.index_memory_cells = Some(parse_list(key, value)?),
+                "gamma" => self.gamma = Some(parse_value(key, value)?),
+                "max-steps" => self.max_steps = Some(parse_value(key, value)?),
+                _ => return Err(format!("unknown key '{key}'")),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{value}' for key '{key}'"))
+}
+
+fn parse_list<T: FromStr>(key: &str, value: &str) -> Result<Vec<T>, String> {
+    value.split(',').map(|f| parse_value(key, f)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils::string_literal_to_vec;
+
+    use super::ProgramHeader;
+
+    #[test]
+    fn test_parse_program_header() {
+        let program = string_literal_to_vec(
+            "# some description
+
+# alpha_tui: accumulators=4 memory=h1,h2
+// alpha_tui: max-steps=10000 index-memory=0,1 gamma=true
+a := 5
+# alpha_tui: accumulators=10",
+        );
+        assert_eq!(
+            ProgramHeader::try_from_program(&program).unwrap(),
+            Some(ProgramHeader {
+                accumulators: Some(4),
+                memory_cells: Some(vec!["h1".to_string(), "h2".to_string()]),
+                index_memory_cells: Some(vec![0, 1]),
+                gamma: Some(true),
+                max_steps: Some(10000),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_program_header_none() {
+        let program = string_literal_to_vec("# some description\na := 5");
+        assert_eq!(ProgramHeader::try_from_program(&program).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_program_header_invalid() {
+        for header in [
+            "# alpha_tui: accumulators=four",
+            "# alpha_tui: stack=5",
+            "# alpha_tui: max-steps",
+        ] {
+            assert!(ProgramHeader::try_from_program(&string_literal_to_vec(header)).is_err());
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::instructions::{IndexMemoryCellIndexType, TargetType};
+
+use super::memory_config::MemoryConfig;
+
+/// Values of memory locations that are set before the program is started, declared with `@init` annotations in
+/// the comments of the program.
+///
+/// Example: `// @init h1=5, h2=10, a0=3`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InitialValues {
+    values: Vec<(TargetType, i32)>,
+}
+
+impl InitialValues {
+    /// Parses the `@init` annotations in `comment`, other annotations are ignored.
+    ///
+    /// Returns the annotation and the reason, if an annotation is invalid.
+    pub fn parse_annotations(&mut self, comment: &str) -> Result<(), (String, String)> {
+        for annotation in comment.split('@').skip(1) {
+            let annotation = annotation.trim();
+            // words that start with init, e.g. in an email address, are not an annotation
+            let Some(values) = annotation
+                .strip_prefix("init")
+                .filter(|f| f.starts_with(char::is_whitespace))
+            else {
+                continue;
+            };
+            for value in values.split(',') {
+                let value = parse_value(value.trim())
+                    .map_err(|reason| (format!("@{annotation}"), reason))?;
+                self.values.push(value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if no value is declared.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Removes the values of the locations that have a value in `memory_config`, so that these values are kept when
+    /// the initial values are applied, e.g. the values of a memory config file that is set explicitly.
+    pub fn remove_set_in(&mut self, memory_config: &MemoryConfig) {
+        self.values.retain(|(location, _)| {
+            let value = match location {
+                TargetType::Accumulator(id) => memory_config.accumulators.values.get(id),
+                TargetType::Gamma => Some(&memory_config.gamma_accumulator.value),
+                TargetType::MemoryCell(label) => memory_config.memory_cells.values.get(label),
+                TargetType::IndexMemoryCell(IndexMemoryCellIndexType::Direct(index)) => {
+                    memory_config.index_memory_cells.values.get(index)
+                }
+                TargetType::IndexMemoryCell(_) => None,
+            };
+            !matches!(value, Some(Some(_)))
+        });
+    }
+
+    /// Sets the declared values in the memory config, missing memory locations are created.
+    ///
+    /// Values of the memory config are replaced, if a location is declared more than once the last value is used.
+    pub fn apply(&self, memory_config: &mut MemoryConfig) {
+        for (location, value) in &self.values {
+            match location {
+                TargetType::Accumulator(id) => {
+                    memory_config.accumulators.values.insert(*id, Some(*value));
+                }
+                TargetType::Gamma => {
+                    memory_config.gamma_accumulator.enabled = true;
+                    memory_config.gamma_accumulator.value = Some(*value);
+                }
+                TargetType::MemoryCell(label) => {
+                    memory_config
+                        .memory_cells
+                        .values
+                        .insert(label.clone(), Some(*value));
+                }
+                TargetType::IndexMemoryCell(IndexMemoryCellIndexType::Direct(index)) => {
+                    memory_config
+                        .index_memory_cells
+                        .values
+                        .insert(*index, Some(*value));
+                }
+                // other index types are rejected when the value is parsed
+                TargetType::IndexMemoryCell(_) => (),
+            }
+        }
+    }
+}
+
+/// Parses a value of the form `LOCATION=VALUE`, e.g. `a0=3` or `p(h1)=5`.
+///
+/// Memory cells can be written without `p()`, e.g. `h1=5`.
+fn parse_value(value: &str) -> Result<(TargetType, i32), String> {
+    let Some((location, number)) = value.split_once('=') else {
+        return Err(format!("'{value}' is not of the form LOCATION=VALUE"));
+    };
+    let (location, number) = (location.trim(), number.trim());
+    let target = TargetType::try_from((&location.to_string(), (0, location.len())))
+        .or_else(|_| {
+            let memory_cell = format!("p({location})");
+            TargetType::try_from((&memory_cell, (0, memory_cell.len())))
+        })
+        .map_err(|_| format!("'{location}' is not a memory location"))?;
+    if let TargetType::IndexMemoryCell(index) = &target {
+        if !matches!(index, IndexMemoryCellIndexType::Direct(_)) {
+            return Err(format!(
+                "the index of '{location}' has to be a number, e.g. p(3)"
+            ));
+        }
+    }
+    let number = number
+        .parse()
+        .map_err(|_| format!("invalid value '{number}' for '{location}'"))?;
+    Ok((target, number))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::memory_config::MemoryConfig;
+
+    use super::InitialValues;
+
+    #[test]
+    fn test_parse_initial_values() {
+        let mut initial_values = InitialValues::default();
+        initial_values
+            .parse_annotations("input of the program @init h1=5, p(h2) = -10, a0=3")
+            .unwrap();
+        initial_values
+            .parse_annotations("@requires a0 > 0 @init y=1, p(3)=7")
+            .unwrap();
+        let mut memory_config = MemoryConfig::default();
+        memory_config
+            .memory_cells
+            .values
+            .insert("h1".to_string(), Some(1));
+        initial_values.apply(&mut memory_config);
+        assert_eq!(memory_config.memory_cells.values["h1"], Some(5));
+        assert_eq!(memory_config.memory_cells.values["h2"], Some(-10));
+        assert_eq!(memory_config.accumulators.values[&0], Some(3));
+        assert_eq!(memory_config.index_memory_cells.values[&3], Some(7));
+        assert!(memory_config.gamma_accumulator.enabled);
+        assert_eq!(memory_config.gamma_accumulator.value, Some(1));
+    }
+
+    #[test]
+    fn test_parse_initial_values_invalid() {
+        for comment in [
+            "@init h1",
+            "@init h1=five",
+            "@init h1=5,",
+            "@init p(a0)=5",
+            "@init a0 := 5",
+        ] {
+            let mut initial_values = InitialValues::default();
+            assert!(
+                initial_values.parse_annotations(comment).is_err(),
+                "{comment}"
+            );
+        }
+        let mut initial_values = InitialValues::default();
+        initial_values
+            .parse_annotations("@requires a0 > 0")
+            .unwrap();
+        assert!(initial_values.is_empty());
+    }
+}
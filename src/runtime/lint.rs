@@ -0,0 +1,597 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use miette::{Diagnostic, NamedSource, SourceSpan};
+use thiserror::Error;
+
+use crate::{
+    instructions::{Condition, IndexMemoryCellIndexType, Instruction, TargetType, Value},
+    utils::remove_comment,
+};
+
+use super::Runtime;
+
+/// Problem of a program that was found by [`Runtime::lint`].
+///
+/// Lines are counted without the lines that start with `#`, like the lines of runtime errors.
+#[derive(Debug, PartialEq, Clone)]
+pub enum LintWarning {
+    /// The label is defined but never jumped to or called.
+    UnusedLabel { line: usize, label: String },
+    /// The instruction in the line, and the instructions directly following it, are never run.
+    UnreachableInstruction { line: usize },
+    /// The memory cell is read, but no instruction writes to it and it is not initialized by the memory config.
+    ReadOfUnwrittenMemoryCell { line: usize, memory_cell: String },
+    /// The function is called, but no path of it reaches a `return`.
+    CallWithoutReturn { line: usize, label: String },
+}
+
+impl Display for LintWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnusedLabel { line, label } => {
+                write!(f, "line {line}: label '{label}' is never used")
+            }
+            Self::UnreachableInstruction { line } => {
+                write!(f, "line {line}: instruction is never run")
+            }
+            Self::ReadOfUnwrittenMemoryCell { line, memory_cell } => write!(
+                f,
+                "line {line}: memory cell '{memory_cell}' is read but never written"
+            ),
+            Self::CallWithoutReturn { line, label } => write!(
+                f,
+                "line {line}: function '{label}' is called but can't reach a return"
+            ),
+        }
+    }
+}
+
+/// Warning of the linter, with the part of the program that caused it, see [`LintWarning::into_diagnostic`].
+#[derive(Debug, Error, Diagnostic, Clone)]
+pub enum LintDiagnostic {
+    #[error("label '{label}' is never used")]
+    #[diagnostic(
+        code("lint::unused_label"),
+        severity(Warning),
+        help("Remove the label or jump to it, e.g. with 'goto {label}'")
+    )]
+    UnusedLabel {
+        label: String,
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("here")]
+        bad_bit: SourceSpan,
+    },
+
+    #[error("instruction is never run")]
+    #[diagnostic(
+        code("lint::unreachable_instruction"),
+        severity(Warning),
+        help("No path of the program reaches this instruction, e.g. because it follows a 'goto' or 'return'.\nRemove the instruction or add a label and jump to it")
+    )]
+    UnreachableInstruction {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("here")]
+        bad_bit: SourceSpan,
+    },
+
+    #[error("memory cell '{memory_cell}' is read but never written")]
+    #[diagnostic(
+        code("lint::read_of_unwritten_memory_cell"),
+        severity(Warning),
+        help("Try assigning a value before accessing it.\nExample: p({memory_cell}) := 5")
+    )]
+    ReadOfUnwrittenMemoryCell {
+        memory_cell: String,
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("here")]
+        bad_bit: SourceSpan,
+    },
+
+    #[error("function '{label}' is called but can't reach a return")]
+    #[diagnostic(
+        code("lint::call_without_return"),
+        severity(Warning),
+        help("Make sure that every path of the function '{label}' ends with 'return'")
+    )]
+    CallWithoutReturn {
+        label: String,
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("here")]
+        bad_bit: SourceSpan,
+    },
+}
+
+impl LintDiagnostic {
+    /// Returns the part of the program that caused the warning.
+    pub fn bad_bit(&self) -> SourceSpan {
+        match self {
+            Self::UnusedLabel { bad_bit, .. }
+            | Self::UnreachableInstruction { bad_bit, .. }
+            | Self::ReadOfUnwrittenMemoryCell { bad_bit, .. }
+            | Self::CallWithoutReturn { bad_bit, .. } => *bad_bit,
+        }
+    }
+}
+
+impl LintWarning {
+    /// Line of the program in which the problem was found.
+    pub fn line(&self) -> usize {
+        match self {
+            Self::UnusedLabel { line, .. }
+            | Self::UnreachableInstruction { line }
+            | Self::ReadOfUnwrittenMemoryCell { line, .. }
+            | Self::CallWithoutReturn { line, .. } => *line,
+        }
+    }
+
+    /// Converts the warning into a diagnostic that marks the affected part of the line in `instructions_input`.
+    pub fn into_diagnostic(self, instructions_input: &[String], file_name: &str) -> LintDiagnostic {
+        // lines that start with # are not counted, see `Runtime::lint`
+        let line_idx = instructions_input
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| !f.trim().starts_with('#'))
+            .nth(self.line() - 1)
+            .map_or(0, |(idx, _)| idx);
+        let offset = instructions_input
+            .iter()
+            .take(line_idx)
+            .map(|f| f.len() + 1)
+            .sum::<usize>();
+        let line = instructions_input.get(line_idx).map_or("", String::as_str);
+        let (start, len) = match &self {
+            Self::UnusedLabel { label, .. } => {
+                (line.find(label.as_str()).unwrap_or(0), label.len())
+            }
+            Self::ReadOfUnwrittenMemoryCell { memory_cell, .. } => find_word(line, memory_cell)
+                .map_or_else(|| instruction_span(line), |f| (f, memory_cell.len())),
+            Self::UnreachableInstruction { .. } | Self::CallWithoutReturn { .. } => {
+                instruction_span(line)
+            }
+        };
+        let src = NamedSource::new(file_name, instructions_input.join("\n"));
+        let bad_bit = SourceSpan::new((offset + start).into(), len);
+        match self {
+            Self::UnusedLabel { label, .. } => LintDiagnostic::UnusedLabel {
+                label,
+                src,
+                bad_bit,
+            },
+            Self::UnreachableInstruction { .. } => {
+                LintDiagnostic::UnreachableInstruction { src, bad_bit }
+            }
+            Self::ReadOfUnwrittenMemoryCell { memory_cell, .. } => {
+                LintDiagnostic::ReadOfUnwrittenMemoryCell {
+                    memory_cell,
+                    src,
+                    bad_bit,
+                }
+            }
+            Self::CallWithoutReturn { label, .. } => LintDiagnostic::CallWithoutReturn {
+                label,
+                src,
+                bad_bit,
+            },
+        }
+    }
+}
+
+/// Returns the start and length of the instruction in `line`, without label and comment.
+fn instruction_span(line: &str) -> (usize, usize) {
+    let code = remove_comment(line);
+    let code = code
+        .split_once(':')
+        .filter(|(label, _)| !label.contains(char::is_whitespace))
+        .map_or(code.as_str(), |(_, instruction)| instruction)
+        .trim();
+    line.find(code)
+        .map_or((0, line.trim_end().len()), |f| (f, code.len()))
+}
+
+/// Returns the position of `word` in the code of `line`, only matches that are not part of a longer name are returned.
+fn find_word(line: &str, word: &str) -> Option<usize> {
+    let code = remove_comment(line);
+    let is_name = |c: char| c.is_alphanumeric() || c == '_';
+    code.match_indices(word)
+        .map(|(idx, _)| idx)
+        .find(|idx| {
+            !code[..*idx].ends_with(is_name) && !code[idx + word.len()..].starts_with(is_name)
+        })
+        .and_then(|idx| {
+            // the comment is removed and the code trimmed, the offset of the code in the line has to be added
+            let start = line.find(code.as_str())?;
+            Some(start + idx)
+        })
+}
+
+impl Runtime {
+    /// Analyzes the program for problems that don't prevent it from being run, but are likely mistakes.
+    ///
+    /// Like in [`Runtime::stack_analysis`], conditions of `if` instructions are not evaluated, both branches
+    /// are assumed to be possible. The warnings are sorted by line.
+    pub fn lint(&self) -> Vec<LintWarning> {
+        let mut warnings = Vec::new();
+        self.lint_unused_labels(&mut warnings);
+        self.lint_unreachable_instructions(&mut warnings);
+        self.lint_memory_cell_reads(&mut warnings);
+        self.lint_calls(&mut warnings);
+        warnings.sort_by_key(LintWarning::line);
+        warnings
+    }
+
+    fn lint_unused_labels(&self, warnings: &mut Vec<LintWarning>) {
+        let used = self
+            .instructions
+            .iter()
+            .flat_map(Instruction::jump_labels)
+            .collect::<HashSet<_>>();
+        for (label, idx) in &self.control_flow.instruction_labels {
+            // the main label marks the start of the program, it is not jumped to
+            if *idx < self.instructions.len()
+                && label != "main"
+                && label != "MAIN"
+                && !used.contains(label.as_str())
+            {
+                warnings.push(LintWarning::UnusedLabel {
+                    line: idx + 1,
+                    label: label.clone(),
+                });
+            }
+        }
+    }
+
+    fn lint_unreachable_instructions(&self, warnings: &mut Vec<LintWarning>) {
+        let len = self.instructions.len();
+        // instructions that are run after a function returns
+        let return_sites = self
+            .instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, instruction)| matches!(instruction, Instruction::Call(_)))
+            .map(|(idx, _)| idx + 1)
+            .collect::<Vec<_>>();
+        let mut reachable = vec![false; len];
+        let mut worklist = vec![self.control_flow.initial_instruction];
+        while let Some(idx) = worklist.pop() {
+            if idx >= len || reachable[idx] {
+                continue;
+            }
+            reachable[idx] = true;
+            match jump_of(&self.instructions[idx]) {
+                Instruction::Goto(label) | Instruction::Call(label) => {
+                    worklist.extend(self.control_flow.instruction_labels.get(label));
+                }
+                Instruction::JumpIf(_, _, _, label) | Instruction::JumpIfCompound(_, label) => {
+                    worklist.push(idx + 1);
+                    worklist.extend(self.control_flow.instruction_labels.get(label));
+                }
+                Instruction::JumpIfElse(_, then_label, else_label) => {
+                    for label in [then_label, else_label] {
+                        worklist.extend(self.control_flow.instruction_labels.get(label));
+                    }
+                }
+                // without calls, return ends the program
+                Instruction::Return => worklist.extend(&return_sites),
+                // the target is only known when the program is run, so every instruction can be reached
+                Instruction::ComputedGoto(_) => worklist.extend(0..len),
+                _ => worklist.push(idx + 1),
+            }
+        }
+        // only the first instruction of a block of unreachable instructions is reported
+        let mut in_block = false;
+        for (idx, instruction) in self.instructions.iter().enumerate() {
+            if reachable[idx] {
+                in_block = false;
+            } else if !in_block && *instruction != Instruction::Noop {
+                in_block = true;
+                warnings.push(LintWarning::UnreachableInstruction { line: idx + 1 });
+            }
+        }
+    }
+
+    fn lint_memory_cell_reads(&self, warnings: &mut Vec<LintWarning>) {
+        let mut written = HashSet::new();
+        let mut reads = Vec::new();
+        for (idx, instruction) in self.instructions.iter().enumerate() {
+            let mut read = Vec::new();
+            accesses(instruction, &mut read, &mut written);
+            reads.extend(read.into_iter().map(|name| (idx, name)));
+        }
+        let mut reported = HashSet::new();
+        for (idx, name) in reads {
+            // memory cells can be set to a value with a memory config file
+            let initialized = self
+                .initial_memory
+                .memory_cells
+                .get(name)
+                .is_some_and(|f| f.data.is_some());
+            if !initialized && !written.contains(name) && reported.insert((idx, name)) {
+                warnings.push(LintWarning::ReadOfUnwrittenMemoryCell {
+                    line: idx + 1,
+                    memory_cell: name.clone(),
+                });
+            }
+        }
+    }
+
+    fn lint_calls(&self, warnings: &mut Vec<LintWarning>) {
+        let mut returns: HashMap<&str, bool> = HashMap::new();
+        for (idx, instruction) in self.instructions.iter().enumerate() {
+            let Instruction::Call(label) = instruction else {
+                continue;
+            };
+            let reaches_return = *returns
+                .entry(label)
+                .or_insert_with(|| self.reaches_return(label));
+            if !reaches_return {
+                warnings.push(LintWarning::CallWithoutReturn {
+                    line: idx + 1,
+                    label: label.clone(),
+                });
+            }
+        }
+    }
+
+    /// Returns true if a `return` can be reached from the function starting at `label`.
+    ///
+    /// Calls inside the function are assumed to return.
+    fn reaches_return(&self, label: &str) -> bool {
+        let len = self.instructions.len();
+        let mut visited = vec![false; len];
+        let mut worklist = self
+            .control_flow
+            .instruction_labels
+            .get(label)
+            .copied()
+            .into_iter()
+            .collect::<Vec<_>>();
+        while let Some(idx) = worklist.pop() {
+            if idx >= len || visited[idx] {
+                continue;
+            }
+            visited[idx] = true;
+            match jump_of(&self.instructions[idx]) {
+                Instruction::Return => return true,
+                Instruction::Goto(label) => {
+                    worklist.extend(self.control_flow.instruction_labels.get(label));
+                }
+                Instruction::JumpIf(_, _, _, label) | Instruction::JumpIfCompound(_, label) => {
+                    worklist.push(idx + 1);
+                    worklist.extend(self.control_flow.instruction_labels.get(label));
+                }
+                Instruction::JumpIfElse(_, then_label, else_label) => {
+                    for label in [then_label, else_label] {
+                        worklist.extend(self.control_flow.instruction_labels.get(label));
+                    }
+                }
+                Instruction::ComputedGoto(_) => worklist.extend(0..len),
+                _ => worklist.push(idx + 1),
+            }
+        }
+        false
+    }
+}
+
+/// Returns the instruction that decides where the program continues after `instruction`, which is the jump a loop
+/// is lowered into.
+pub(super) fn jump_of(instruction: &Instruction) -> &Instruction {
+    match instruction {
+        Instruction::Loop(line) => line.lowered.last().unwrap_or(instruction),
+        instruction => instruction,
+    }
+}
+
+/// Adds the memory cells that are read by `instruction` to `read` and the memory cells that are written to `written`.
+fn accesses<'a>(
+    instruction: &'a Instruction,
+    read: &mut Vec<&'a String>,
+    written: &mut HashSet<&'a String>,
+) {
+    match instruction {
+        Instruction::Assign(target, value) => {
+            target_reads(target, read);
+            value_reads(value, read);
+            if let TargetType::MemoryCell(name) = target {
+                written.insert(name);
+            }
+        }
+        Instruction::Calc(target, value_a, _, value_b) => {
+            target_reads(target, read);
+            value_reads(value_a, read);
+            value_reads(value_b, read);
+            if let TargetType::MemoryCell(name) = target {
+                written.insert(name);
+            }
+        }
+        Instruction::CalcChain(target, value, operations) => {
+            target_reads(target, read);
+            value_reads(value, read);
+            for (_, value) in operations {
+                value_reads(value, read);
+            }
+            if let TargetType::MemoryCell(name) = target {
+                written.insert(name);
+            }
+        }
+        Instruction::AssignBuiltIn(target, _, args) => {
+            target_reads(target, read);
+            for value in args {
+                value_reads(value, read);
+            }
+            if let TargetType::MemoryCell(name) = target {
+                written.insert(name);
+            }
+        }
+        Instruction::JumpIf(value_a, _, value_b, _) | Instruction::Assert(value_a, _, value_b) => {
+            value_reads(value_a, read);
+            value_reads(value_b, read);
+        }
+        Instruction::Print(value) | Instruction::ComputedGoto(value) => value_reads(value, read),
+        Instruction::Peek(target) => {
+            target_reads(target, read);
+            if let TargetType::MemoryCell(name) = target {
+                written.insert(name);
+            }
+        }
+        Instruction::Macro(call) => {
+            for instruction in &call.body {
+                accesses(instruction, read, written);
+            }
+        }
+        Instruction::Loop(line) => {
+            for instruction in &line.lowered {
+                accesses(instruction, read, written);
+            }
+        }
+        Instruction::JumpIfCompound(condition, _) | Instruction::JumpIfElse(condition, _, _) => {
+            condition_reads(condition, read);
+        }
+        _ => (),
+    }
+}
+
+/// Adds the memory cells that are read when `target` is written to `read`.
+fn target_reads<'a>(target: &'a TargetType, read: &mut Vec<&'a String>) {
+    if let TargetType::IndexMemoryCell(IndexMemoryCellIndexType::MemoryCell(name)) = target {
+        read.push(name);
+    }
+}
+
+/// Adds the memory cells that are read when `value` is evaluated to `read`.
+fn value_reads<'a>(value: &'a Value, read: &mut Vec<&'a String>) {
+    match value {
+        Value::MemoryCell(name)
+        | Value::IndexMemoryCell(IndexMemoryCellIndexType::MemoryCell(name)) => read.push(name),
+        Value::Negated(value) => value_reads(value, read),
+        _ => (),
+    }
+}
+
+/// Adds the memory cells that are read when `condition` is evaluated to `read`.
+fn condition_reads<'a>(condition: &'a Condition, read: &mut Vec<&'a String>) {
+    match condition {
+        Condition::Compare(value_a, _, value_b) => {
+            value_reads(value_a, read);
+            value_reads(value_b, read);
+        }
+        Condition::And(a, b) | Condition::Or(a, b) => {
+            condition_reads(a, read);
+            condition_reads(b, read);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils::runtime_from_str;
+
+    use super::LintWarning;
+
+    #[test]
+    fn test_lint_unused_label_and_unreachable_instruction() {
+        let rt = runtime_from_str(
+            "a := 1\nunused: a := a + 1\ngoto done\na := 2\n\na := 3\ndone: a := a * 2",
+        )
+        .unwrap();
+        assert_eq!(
+            rt.lint(),
+            vec![
+                LintWarning::UnusedLabel {
+                    line: 2,
+                    label: "unused".to_string()
+                },
+                LintWarning::UnreachableInstruction { line: 4 },
+            ]
+        );
+        // both branches of a jump with an else branch are used, the next line is not reached
+        let rt = runtime_from_str(
+            "if a > 1 then goto big else goto small
+a := 0
+big: a := 1
+small: a := 2",
+        )
+        .unwrap();
+        assert_eq!(
+            rt.lint(),
+            vec![LintWarning::UnreachableInstruction { line: 2 }]
+        );
+        // the labels of loops are used by their jumps and the loops can be left
+        let rt = runtime_from_str(
+            "a := 3\nwhile a > 0 do\na := a - 1\nendwhile\nloop 2 times\nwhile a < 1 || a > 5 && a1 == 0 do\nendwhile\nendloop\na := 1",
+        )
+        .unwrap();
+        assert_eq!(rt.lint(), Vec::new());
+    }
+
+    #[test]
+    fn test_lint_read_of_unwritten_memory_cell() {
+        let rt = runtime_from_str(
+            "p(h1) := 1\na := p(h1) + p(h2)\nif -p(h2) < p(h1) then goto done\np(p(h3)) := 2\ndone: a := a",
+        )
+        .unwrap();
+        assert_eq!(
+            rt.lint(),
+            vec![
+                LintWarning::ReadOfUnwrittenMemoryCell {
+                    line: 2,
+                    memory_cell: "h2".to_string()
+                },
+                LintWarning::ReadOfUnwrittenMemoryCell {
+                    line: 3,
+                    memory_cell: "h2".to_string()
+                },
+                LintWarning::ReadOfUnwrittenMemoryCell {
+                    line: 4,
+                    memory_cell: "h3".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_call_without_return() {
+        let rt = runtime_from_str(
+            "call ok\ncall loop\ngoto done\nok: return\nloop: a := 1\ngoto loop\ndone: a := 2",
+        )
+        .unwrap();
+        assert_eq!(
+            rt.lint(),
+            vec![LintWarning::CallWithoutReturn {
+                line: 2,
+                label: "loop".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_lint_diagnostic_span() {
+        let input = [
+            "# alpha_tui: max-steps=100",
+            "a := 1",
+            "unused:  a := p(h12) + p(h1) // comment",
+        ]
+        .map(String::from);
+        let diagnostic = LintWarning::UnusedLabel {
+            line: 2,
+            label: "unused".to_string(),
+        }
+        .into_diagnostic(&input, "test");
+        assert_eq!(diagnostic.bad_bit(), (34, 6).into());
+        let diagnostic = LintWarning::ReadOfUnwrittenMemoryCell {
+            line: 2,
+            memory_cell: "h1".to_string(),
+        }
+        .into_diagnostic(&input, "test");
+        assert_eq!(diagnostic.bad_bit(), (34 + 25, 2).into());
+        let diagnostic =
+            LintWarning::UnreachableInstruction { line: 2 }.into_diagnostic(&input, "test");
+        assert_eq!(diagnostic.bad_bit(), (34 + 9, 19).into());
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use crate::{
+    base::{Comparison, Operation},
+    instructions::{Condition, Instruction, LoopKind, TargetType, Value},
+};
+
+/// Lowers the lines of `while` and `loop` constructs into jumps, the instructions are stored in the lines.
+///
+/// `while CONDITION do` jumps behind `endwhile` if the condition is false, `endwhile` jumps back to `while`.
+/// `loop VALUE times` sets a counter to the value and jumps behind `endloop` if it is not positive, `endloop`
+/// decreases the counter and jumps back to the first line of the loop until it is zero. The counter is the memory
+/// cell `loop N`, where `N` is the line of `loop`, it is internal to the runtime, see [`is_counter`].
+///
+/// The jumps use labels that are named after the line of the start of the loop, e.g. `while 3` and `endwhile 3`.
+/// The names contain a space, so that they can't clash with labels and memory cells of the program. `source_lines`
+/// contains the line of each instruction in the source file.
+///
+/// Returns these labels with the index of the instruction they point to. Returns the index of the line and the
+/// reason, if a loop is not closed or is closed with the end of another kind of loop.
+///
+/// Example:
+/// ```text
+/// while a > 0 do          // if a0 <= 0 then goto endwhile 1
+///     a := a - 1
+/// endwhile                // goto while 1
+/// ```
+pub fn lower(
+    instructions: &mut [Instruction],
+    source_lines: &[usize],
+) -> Result<HashMap<String, usize>, (usize, String)> {
+    let mut labels = HashMap::new();
+    // index and kind of the starts of the loops that are not closed yet
+    let mut open: Vec<(usize, LoopKind)> = Vec::new();
+    for idx in 0..instructions.len() {
+        let Instruction::Loop(line) = &instructions[idx] else {
+            continue;
+        };
+        let end = line.kind.clone();
+        if matches!(end, LoopKind::While(_) | LoopKind::Times(_)) {
+            open.push((idx, end));
+            continue;
+        }
+        let Some((start, kind)) = open.pop() else {
+            return Err((idx, format!("'{end}' is used without the start of a loop")));
+        };
+        let n = source_lines.get(start).copied().unwrap_or(start + 1);
+        let (start_lowered, end_lowered) = match (kind, end) {
+            (LoopKind::While(condition), LoopKind::EndWhile) => {
+                let end_label = format!("endwhile {n}");
+                let jump = match condition.negated() {
+                    Some(Condition::Compare(value_a, cmp, value_b)) => {
+                        Instruction::JumpIf(value_a, cmp, value_b, end_label.clone())
+                    }
+                    Some(negated) => Instruction::JumpIfCompound(negated, end_label.clone()),
+                    // the condition can not be negated, so the else branch leaves the loop
+                    None => {
+                        labels.insert(format!("do {n}"), start + 1);
+                        Instruction::JumpIfElse(condition, format!("do {n}"), end_label.clone())
+                    }
+                };
+                labels.insert(format!("while {n}"), start);
+                labels.insert(end_label, idx + 1);
+                (vec![jump], vec![Instruction::Goto(format!("while {n}"))])
+            }
+            (LoopKind::Times(value), LoopKind::EndLoop) => {
+                let counter = format!("loop {n}");
+                let end_label = format!("endloop {n}");
+                let counter_value = Value::MemoryCell(counter.clone());
+                let start_lowered = vec![
+                    Instruction::Assign(TargetType::MemoryCell(counter.clone()), value),
+                    Instruction::JumpIf(
+                        counter_value.clone(),
+                        Comparison::Le,
+                        Value::Constant(0),
+                        end_label.clone(),
+                    ),
+                ];
+                let end_lowered = vec![
+                    Instruction::Calc(
+                        TargetType::MemoryCell(counter.clone()),
+                        counter_value.clone(),
+                        Operation::Sub,
+                        Value::Constant(1),
+                    ),
+                    Instruction::JumpIf(
+                        counter_value,
+                        Comparison::Gt,
+                        Value::Constant(0),
+                        counter.clone(),
+                    ),
+                ];
+                labels.insert(counter, start + 1);
+                labels.insert(end_label, idx + 1);
+                (start_lowered, end_lowered)
+            }
+            (kind, end) => {
+                return Err((
+                    idx,
+                    format!("'{end}' can not close the loop '{kind}' in line {n}"),
+                ))
+            }
+        };
+        for (i, lowered) in [(start, start_lowered), (idx, end_lowered)] {
+            if let Instruction::Loop(line) = &mut instructions[i] {
+                line.lowered = lowered;
+            }
+        }
+    }
+    if let Some((start, kind)) = open.pop() {
+        let end = match kind {
+            LoopKind::While(_) => "endwhile",
+            _ => "endloop",
+        };
+        return Err((start, format!("'{end}' is missing for the loop '{kind}'")));
+    }
+    Ok(labels)
+}
+
+/// Returns true if `label` is the memory cell that counts the iterations of a `loop` construct.
+///
+/// The counters are not part of the state of the program, they are left out when the memory is displayed, dumped or
+/// compared. Memory cells of the program can't contain spaces, so they are never mistaken for a counter.
+pub fn is_counter(label: &str) -> bool {
+    label.starts_with("loop ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::Instruction;
+
+    use super::lower;
+
+    /// Parses the lines of `program` and returns the lowered instructions of each line.
+    fn lowered(program: &str) -> Result<Vec<String>, (usize, String)> {
+        let mut instructions = program
+            .lines()
+            .map(|f| Instruction::try_from(f).unwrap())
+            .collect::<Vec<_>>();
+        let source_lines = (1..=instructions.len()).collect::<Vec<_>>();
+        lower(&mut instructions, &source_lines)?;
+        Ok(instructions
+            .iter()
+            .map(|f| match f {
+                Instruction::Loop(line) => line
+                    .lowered
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; "),
+                f => f.to_string(),
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_lower() {
+        assert_eq!(
+            lowered("while a < 1 || a > 5 do\na := a + 1\nendwhile").unwrap(),
+            [
+                "if a0 >= 1 && a0 <= 5 then goto endwhile 1",
+                "a0 := a0 + 1",
+                "goto while 1"
+            ]
+        );
+        assert_eq!(
+            lowered("a := 1\nloop a times\npush\nendloop").unwrap(),
+            [
+                "a0 := 1",
+                "p(loop 2) := a0; if p(loop 2) <= 0 then goto endloop 2",
+                "push",
+                "p(loop 2) := p(loop 2) - 1; if p(loop 2) > 0 then goto loop 2"
+            ]
+        );
+        // conditions that can not be negated leave the loop with an else branch
+        assert_eq!(
+            lowered("while a < 1 || a > 5 && a1 == 0 do\nendwhile").unwrap()[0],
+            "if a0 < 1 || a0 > 5 && a1 == 0 then goto do 1 else goto endwhile 1"
+        );
+        assert_eq!(lowered("endwhile").map_err(|e| e.0), Err(0));
+        assert_eq!(
+            lowered("loop 3 times\nwhile a > 0 do\nendloop").map_err(|e| e.0),
+            Err(2)
+        );
+        assert_eq!(
+            lowered("loop 3 times\nwhile a > 0 do\nendwhile").map_err(|e| e.0),
+            Err(0)
+        );
+    }
+}
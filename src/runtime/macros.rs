@@ -0,0 +1,269 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::instructions::{Instruction, MacroCall};
+
+use super::builder::remove_comment;
+
+/// Macro that is defined in the program, see [`Macros`].
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Macro {
+    parameters: Vec<String>,
+    /// Instructions of the macro, the parameters are replaced when the macro is expanded
+    body: Vec<String>,
+}
+
+/// Macros that are defined in the program with `macro NAME(PARAMETERS)` and `endmacro`, the lines in between are
+/// the instructions of the macro.
+///
+/// A call of a macro, e.g. `swap(p(h1), p(h2))`, is expanded when the program is build and its instructions are run
+/// in a single step. Parameters are replaced where they are used as a whole operand.
+///
+/// Example:
+/// ```text
+/// macro swap(x, y)
+///     a := x
+///     x := y
+///     y := a
+/// endmacro
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Macros {
+    macros: HashMap<String, Macro>,
+}
+
+impl Macros {
+    /// Reads the macro definitions of the program.
+    ///
+    /// Returns the macros and the indices of the lines that belong to a definition, these lines don't contain
+    /// instructions of the program. Returns the index of the line and the reason, if a definition is invalid.
+    pub fn parse(lines: &[String]) -> Result<(Self, HashSet<usize>), (usize, String)> {
+        let mut macros = HashMap::new();
+        let mut definition_lines = HashSet::new();
+        // start line, name and content of the definition that is currently read
+        let mut current: Option<(usize, String, Macro)> = None;
+        for (idx, line) in lines.iter().enumerate() {
+            let line = remove_comment(line);
+            let first = line.split_whitespace().next();
+            match (&mut current, first) {
+                (None, Some("macro")) => {
+                    let (name, parameters) =
+                        parse_header(line["macro".len()..].trim()).map_err(|e| (idx, e))?;
+                    if macros.contains_key(&name) {
+                        return Err((idx, format!("the macro '{name}' is defined multiple times")));
+                    }
+                    current = Some((
+                        idx,
+                        name,
+                        Macro {
+                            parameters,
+                            body: Vec::new(),
+                        },
+                    ));
+                }
+                (None, Some("endmacro")) => {
+                    return Err((idx, "'endmacro' is used without 'macro'".to_string()));
+                }
+                (None, _) => continue,
+                (Some(_), Some("macro")) => {
+                    return Err((
+                        idx,
+                        "a macro can not be defined inside of a macro".to_string(),
+                    ));
+                }
+                (Some(_), Some("endmacro")) => {
+                    if line != "endmacro" {
+                        return Err((idx, "'endmacro' has to be alone in its line".to_string()));
+                    }
+                    if let Some((_, name, definition)) = current.take() {
+                        macros.insert(name, definition);
+                    }
+                }
+                (Some(_), Some(first)) if first.ends_with(':') => {
+                    return Err((idx, "labels can not be used inside of a macro".to_string()));
+                }
+                (Some((_, _, definition)), Some(_)) => definition.body.push(line),
+                // empty lines are allowed inside of a macro
+                (Some(_), None) => (),
+            }
+            definition_lines.insert(idx);
+        }
+        if let Some((idx, name, _)) = current {
+            return Err((idx, format!("'endmacro' is missing for the macro '{name}'")));
+        }
+        Ok((Self { macros }, definition_lines))
+    }
+
+    /// Returns the instructions of the called macro, in which the parameters are replaced with the arguments of
+    /// `call`.
+    ///
+    /// Returns the reason, if the macro is not defined or the instructions are invalid.
+    pub fn expand(&self, call: &MacroCall) -> Result<Vec<Instruction>, String> {
+        let Some(definition) = self.macros.get(&call.name) else {
+            return Err(format!("the macro '{}' is not defined", call.name));
+        };
+        if definition.parameters.len() != call.args.len() {
+            return Err(format!(
+                "the macro '{}' takes {} arguments, but {} are given",
+                call.name,
+                definition.parameters.len(),
+                call.args.len()
+            ));
+        }
+        let args = definition
+            .parameters
+            .iter()
+            .map(String::as_str)
+            .zip(call.args.iter().map(ToString::to_string))
+            .collect::<HashMap<_, _>>();
+        let mut instructions = Vec::new();
+        for line in &definition.body {
+            let parts = line
+                .split_whitespace()
+                .map(|f| args.get(f).map_or(f, String::as_str))
+                .collect::<Vec<_>>();
+            let instruction = Instruction::try_from(&parts).map_err(|e| {
+                format!(
+                    "'{}' in the macro '{}' is not a valid instruction: {e}",
+                    parts.join(" "),
+                    call.name
+                )
+            })?;
+            match instruction {
+                Instruction::Assign(_, _)
+                | Instruction::Calc(_, _, _, _)
+                | Instruction::CalcChain(_, _, _)
+                | Instruction::AssignBuiltIn(_, _, _)
+                | Instruction::Assert(_, _, _)
+                | Instruction::Print(_)
+                | Instruction::Push
+                | Instruction::Pop
+                | Instruction::StackOp(_)
+                | Instruction::StackCommand(_)
+                | Instruction::Peek(_) => instructions.push(instruction),
+                _ => {
+                    return Err(format!(
+                        "'{instruction}' can not be used inside of a macro, only instructions that don't jump can be used"
+                    ))
+                }
+            }
+        }
+        Ok(instructions)
+    }
+}
+
+/// Parses the name and the parameters of a macro, e.g. `swap(x, y)`.
+pub fn parse_header(header: &str) -> Result<(String, Vec<String>), String> {
+    let Some((name, parameters)) = header.split_once('(') else {
+        return Err("the parameters are missing, e.g. 'macro swap(x, y)'".to_string());
+    };
+    let name = name.trim();
+    if !MacroCall::is_valid_name(name) {
+        return Err(format!(
+            "'{name}' can not be used as name, names can only contain letters, numbers and '_'"
+        ));
+    }
+    let Some(parameters) = parameters.strip_suffix(')') else {
+        return Err("')' is missing after the parameters".to_string());
+    };
+    if parameters.trim().is_empty() {
+        return Ok((name.to_string(), Vec::new()));
+    }
+    let mut names: Vec<String> = Vec::new();
+    for parameter in parameters.split(',') {
+        let parameter = parameter.trim();
+        if !MacroCall::is_valid_name(parameter) {
+            return Err(format!(
+                "'{parameter}' can not be used as parameter, names can only contain letters, numbers and '_'"
+            ));
+        }
+        if names.iter().any(|f| f == parameter) {
+            return Err(format!(
+                "the parameter '{parameter}' is used multiple times"
+            ));
+        }
+        names.push(parameter.to_string());
+    }
+    Ok((name.to_string(), names))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        instructions::{Instruction, MacroCall, TargetType, Value},
+        utils::test_utils::string_literal_to_vec,
+    };
+
+    use super::Macros;
+
+    fn call(name: &str, args: Vec<Value>) -> MacroCall {
+        MacroCall {
+            name: name.to_string(),
+            args,
+            body: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_macros() {
+        let lines = string_literal_to_vec(
+            "macro swap(x, y) // exchanges both values\n    a := x\n\n    x := y\n    y := a\nendmacro\nswap(p(h1), p(h2))\nmacro reset()\na := 0\nendmacro",
+        );
+        let (macros, definition_lines) = Macros::parse(&lines).unwrap();
+        let mut definition_lines = definition_lines.into_iter().collect::<Vec<_>>();
+        definition_lines.sort_unstable();
+        assert_eq!(definition_lines, [0, 1, 2, 3, 4, 5, 7, 8, 9]);
+        let h1 = Value::MemoryCell("h1".to_string());
+        let h2 = Value::MemoryCell("h2".to_string());
+        assert_eq!(
+            macros.expand(&call("swap", vec![h1.clone(), h2.clone()])),
+            Ok(vec![
+                Instruction::Assign(TargetType::Accumulator(0), h1),
+                Instruction::Assign(TargetType::MemoryCell("h1".to_string()), h2),
+                Instruction::Assign(
+                    TargetType::MemoryCell("h2".to_string()),
+                    Value::Accumulator(0)
+                ),
+            ])
+        );
+        assert_eq!(
+            macros.expand(&call("reset", Vec::new())),
+            Ok(vec![Instruction::Assign(
+                TargetType::Accumulator(0),
+                Value::Constant(0)
+            )])
+        );
+        assert!(macros
+            .expand(&call("swap", vec![Value::Constant(1)]))
+            .is_err());
+        // the target of an assignment can not be a constant
+        assert!(macros
+            .expand(&call("swap", vec![Value::Constant(1), Value::Constant(2)]))
+            .is_err());
+        assert!(macros.expand(&call("rotate", Vec::new())).is_err());
+    }
+
+    #[test]
+    fn test_macros_invalid() {
+        for (program, line) in [
+            ("macro swap(x, y)\na := x", 0),
+            ("a := 1\nendmacro", 1),
+            ("macro swap(x, x)\nendmacro", 0),
+            ("macro swap x, y\nendmacro", 0),
+            ("macro p(x)\nendmacro", 0),
+            ("macro swap(x)\nmacro inner()\nendmacro\nendmacro", 1),
+            ("macro swap(x)\nloop: a := x\nendmacro", 1),
+            ("macro swap(x)\nendmacro\nmacro swap(y)\nendmacro", 2),
+        ] {
+            assert_eq!(
+                Macros::parse(&string_literal_to_vec(program)).map_err(|e| e.0),
+                Err(line),
+                "{program}"
+            );
+        }
+        let (macros, _) =
+            Macros::parse(&string_literal_to_vec("macro jump(x)\ngoto x\nendmacro")).unwrap();
+        assert!(macros
+            .expand(&call("jump", vec![Value::Constant(1)]))
+            .is_err());
+    }
+}
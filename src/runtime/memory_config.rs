@@ -1,58 +1,205 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display, hash::Hash, path::Path, str::FromStr};
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 
-use crate::utils;
+use crate::{
+    instructions::{IndexMemoryCellIndexType, TargetType},
+    utils,
+};
 
 /// Contains configuration values on how the memory layout should be configured, meaning what memory locations should be
 /// available and pre initialized. Also stores if memory locations should be created if the are accessed but they don't exist already.
 ///
 /// Can be used in the runtime builder to configure the memory values that should be available in the build runtime.
+///
+/// Sections that are missing in the file are empty.
 #[derive(PartialEq, Debug, Deserialize, Serialize, Default, Clone)]
+#[serde(default)]
 pub struct MemoryConfig {
     pub accumulators: AccumulatorConfig,
     pub gamma_accumulator: GammaAccumulatorConfig,
     pub memory_cells: MemoryCellConfig,
     pub index_memory_cells: IndexMemoryCellConfig,
+    /// Names under which memory locations are displayed in the tui, e.g. `"p(h1)" = "n"`.
+    ///
+    /// The memory locations are written like in programs, e.g. `a0`, `y`, `p(h1)` or `p(3)`.
+    pub aliases: HashMap<String, String>,
 }
 
 #[derive(PartialEq, Debug, Deserialize, Serialize, Default, Clone)]
+#[serde(default)]
 pub struct AccumulatorConfig {
+    #[serde(deserialize_with = "deserialize_values")]
     pub values: HashMap<usize, Option<i32>>,
     pub autodetection: Option<bool>,
 }
 
 #[derive(PartialEq, Debug, Deserialize, Serialize, Default, Clone)]
+#[serde(default)]
 pub struct GammaAccumulatorConfig {
     pub enabled: bool,
+    #[serde(deserialize_with = "deserialize_value")]
     pub value: Option<i32>,
     pub autodetection: Option<bool>,
 }
 
 #[derive(PartialEq, Debug, Deserialize, Serialize, Default, Clone)]
+#[serde(default)]
 pub struct MemoryCellConfig {
+    #[serde(deserialize_with = "deserialize_values")]
     pub values: HashMap<String, Option<i32>>,
     pub autodetection: Option<bool>,
 }
 
 #[derive(PartialEq, Debug, Deserialize, Serialize, Default, Clone)]
+#[serde(default)]
 pub struct IndexMemoryCellConfig {
+    #[serde(deserialize_with = "deserialize_values")]
     pub values: HashMap<usize, Option<i32>>,
     pub autodetection: Option<bool>,
 }
 
 impl MemoryConfig {
     /// Tries to parse the provided file into a memory config.
+    ///
+    /// Files with the extension `.toml` are parsed as toml, all other files as json.
     pub fn try_from_file(path: &str) -> miette::Result<Self> {
-        match serde_json::from_str::<MemoryConfig>(&utils::read_file(path)?.join("\n")) {
-            Ok(mut config) => {
-                // check if autodetection for gamma is enabled, because that implies that gamma is enabled
-                if let Some(true) = config.gamma_accumulator.autodetection {
-                    config.gamma_accumulator.enabled = true;
-                };
-                Ok(config)
-            }
-            Err(e) => Err(miette::miette!("json parse error: {e}")),
+        let content = utils::read_file(path)?.join("\n");
+        let config = if Path::new(path).extension().is_some_and(|f| f == "toml") {
+            toml::from_str::<MemoryConfig>(&content)
+                .map_err(|e| miette::miette!("toml parse error: {e}"))
+        } else {
+            serde_json::from_str::<MemoryConfig>(&content)
+                .map_err(|e| miette::miette!("json parse error: {e}"))
+        };
+        let mut config = config?;
+        // check if autodetection for gamma is enabled, because that implies that gamma is enabled
+        if let Some(true) = config.gamma_accumulator.autodetection {
+            config.gamma_accumulator.enabled = true;
+        };
+        if let Some(location) = config.aliases.keys().find(|f| display_name(f).is_none()) {
+            return Err(miette::miette!(
+                "invalid memory location '{location}' in aliases, expected e.g. a0, y, p(h1) or p(3)"
+            ));
+        }
+        Ok(config)
+    }
+
+    /// Returns the aliases by the name under which the memory location is displayed in the tui, e.g. `α0` for `a0`.
+    ///
+    /// Aliases of memory locations that can't be parsed are skipped.
+    pub fn display_aliases(&self) -> HashMap<String, String> {
+        self.aliases
+            .iter()
+            .filter_map(|(location, alias)| Some((display_name(location)?, alias.clone())))
+            .collect()
+    }
+}
+
+/// Returns the name under which the memory location, written like in programs, is displayed in the tui,
+/// `None` if it is not a memory location or an index memory cell that is not accessed by a direct index.
+fn display_name(location: &str) -> Option<String> {
+    match TargetType::try_from((&location.to_string(), (0, location.len()))).ok()? {
+        TargetType::Accumulator(id) => Some(format!("α{id}")),
+        TargetType::Gamma => Some("γ".to_string()),
+        TargetType::MemoryCell(label) => Some(label),
+        TargetType::IndexMemoryCell(IndexMemoryCellIndexType::Direct(index)) => {
+            Some(format!("[{index}]"))
+        }
+        TargetType::IndexMemoryCell(_) => None,
+    }
+}
+
+/// Initial value of a memory location in the config file.
+///
+/// Toml has no `null`, `"none"` is used instead to create a memory location without value.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum InitialValue {
+    Value(i32),
+    Keyword(String),
+}
+
+impl InitialValue {
+    fn value<E: de::Error>(self) -> Result<Option<i32>, E> {
+        match self {
+            Self::Value(value) => Ok(Some(value)),
+            Self::Keyword(keyword) if keyword == "none" => Ok(None),
+            Self::Keyword(keyword) => Err(E::custom(format!(
+                "invalid value '{keyword}', expected a number, null or \"none\""
+            ))),
         }
     }
 }
+
+fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i32>, D::Error> {
+    match Option::<InitialValue>::deserialize(deserializer)? {
+        Some(value) => value.value(),
+        None => Ok(None),
+    }
+}
+
+/// Deserializes the values of memory locations, the keys are parsed from strings, because keys in toml are always
+/// strings.
+fn deserialize_values<'de, D, K>(deserializer: D) -> Result<HashMap<K, Option<i32>>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: FromStr + Eq + Hash,
+    K::Err: Display,
+{
+    let mut values = HashMap::new();
+    for (key, value) in HashMap::<String, Option<InitialValue>>::deserialize(deserializer)? {
+        let key = key
+            .parse()
+            .map_err(|e| de::Error::custom(format!("invalid key '{key}': {e}")))?;
+        let value = match value {
+            Some(value) => value.value()?,
+            None => None,
+        };
+        values.insert(key, value);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryConfig;
+
+    #[test]
+    fn test_memory_config_toml_matches_json() {
+        let json = MemoryConfig::try_from_file("examples/memory_config.json").unwrap();
+        let toml = MemoryConfig::try_from_file("examples/memory_config.toml").unwrap();
+        assert_eq!(json, toml);
+        assert_eq!(toml.accumulators.values[&0], Some(10));
+        assert_eq!(toml.memory_cells.values["h2"], None);
+    }
+
+    #[test]
+    fn test_memory_config_missing_sections() {
+        let config = toml::from_str::<MemoryConfig>("[memory_cells.values]\nh1 = -3").unwrap();
+        assert_eq!(config.memory_cells.values["h1"], Some(-3));
+        assert_eq!(config.memory_cells.autodetection, None);
+        assert!(config.accumulators.values.is_empty());
+        assert!(!config.gamma_accumulator.enabled);
+        let err = toml::from_str::<MemoryConfig>("[memory_cells.values]\nh1 = \"null\"")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("invalid value 'null'"), "{err}");
+        assert!(toml::from_str::<MemoryConfig>("[accumulators.values]\na = 1").is_err());
+    }
+
+    #[test]
+    fn test_memory_config_aliases() {
+        let config = toml::from_str::<MemoryConfig>(
+            "[aliases]\na0 = \"result\"\ny = \"tmp\"\n\"p(h1)\" = \"n\"\n\"p(3)\" = \"last\"\n\"p(a0)\" = \"x\"",
+        )
+        .unwrap();
+        let aliases = config.display_aliases();
+        assert_eq!(aliases["α0"], "result");
+        assert_eq!(aliases["γ"], "tmp");
+        assert_eq!(aliases["h1"], "n");
+        assert_eq!(aliases["[3]"], "last");
+        // index memory cells can only be named by a direct index
+        assert_eq!(aliases.len(), 4);
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+
+use miette::Result;
+
+use crate::utils;
+
+use super::RuntimeMemory;
+
+/// Values of the memory that are sampled every few steps while a program is run, written as csv by
+/// `check run --metrics-file`.
+#[derive(Debug, PartialEq)]
+pub struct Metrics {
+    /// Number of steps between two samples.
+    every: usize,
+    /// Step count and values of the memory locations, by the name of the location.
+    samples: Vec<(usize, BTreeMap<String, Option<i32>>)>,
+}
+
+impl Metrics {
+    /// Creates new metrics that record a sample every `every` steps, 0 is treated as 1.
+    pub fn new(every: usize) -> Self {
+        Self {
+            every: every.max(1),
+            samples: Vec::new(),
+        }
+    }
+
+    /// Records the values of `memory`, if `steps` is a multiple of the sampling interval.
+    pub fn sample(&mut self, steps: usize, memory: &RuntimeMemory) {
+        if steps.is_multiple_of(self.every) {
+            self.record(steps, memory);
+        }
+    }
+
+    /// Records the values of `memory`, if no sample was recorded at `steps` yet.
+    ///
+    /// Used to always include the state of the memory when the program stopped.
+    pub fn finish(&mut self, steps: usize, memory: &RuntimeMemory) {
+        if self.samples.last().map(|f| f.0) != Some(steps) {
+            self.record(steps, memory);
+        }
+    }
+
+    fn record(&mut self, steps: usize, memory: &RuntimeMemory) {
+        let mut values = BTreeMap::new();
+        for accumulator in memory.accumulators.values() {
+            values.insert(format!("a{}", accumulator.id), accumulator.data);
+        }
+        if let Some(value) = memory.gamma {
+            values.insert("y".to_string(), value);
+        }
+        for memory_cell in memory.program_memory_cells() {
+            values.insert(memory_cell.label.clone(), memory_cell.data);
+        }
+        for (index, value) in &memory.index_memory_cells {
+            values.insert(format!("[{index}]"), *value);
+        }
+        self.samples.push((steps, values));
+    }
+
+    /// Returns the samples as csv, the first column contains the step count, followed by one column for every memory
+    /// location. Values that are not set are left empty.
+    pub fn to_csv(&self) -> String {
+        // memory locations can be created while the program is run, so all samples are checked
+        let mut columns = self
+            .samples
+            .iter()
+            .flat_map(|f| f.1.keys())
+            .collect::<Vec<_>>();
+        columns.sort_by(|a, b| column_order(a).cmp(&column_order(b)).then(a.cmp(b)));
+        columns.dedup();
+        let mut csv = String::from("step");
+        for column in &columns {
+            csv.push(',');
+            csv.push_str(column);
+        }
+        csv.push('\n');
+        for (steps, values) in &self.samples {
+            csv.push_str(&steps.to_string());
+            for column in &columns {
+                csv.push(',');
+                if let Some(Some(value)) = values.get(*column) {
+                    csv.push_str(&value.to_string());
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Writes the samples as csv to the file at `path`, the file is overwritten if it exists.
+    pub fn write_to_file(&self, path: &str) -> Result<()> {
+        utils::write_file_atomic(path, self.to_csv().as_bytes())
+            .map_err(|e| miette::miette!("Unable to write metrics to {path}: {e}"))
+    }
+}
+
+/// Accumulators are placed first, followed by the gamma accumulator, memory cells and index memory cells.
+fn column_order(column: &str) -> (u8, usize) {
+    if let Some(id) = column.strip_prefix('a').and_then(|f| f.parse().ok()) {
+        (0, id)
+    } else if column == "y" {
+        (1, 0)
+    } else if let Some(index) = column
+        .strip_prefix('[')
+        .and_then(|f| f.strip_suffix(']'))
+        .and_then(|f| f.parse().ok())
+    {
+        (3, index)
+    } else {
+        (2, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils::runtime_from_str;
+
+    use super::Metrics;
+
+    #[test]
+    fn test_metrics_csv() {
+        let mut rt = runtime_from_str(
+            "a0 := 0\nloop: a0 := a0 + 1\np(h1) := a0 * 2\nif a0 < 3 then goto loop\np(1) := a0",
+        )
+        .unwrap();
+        let mut metrics = Metrics::new(3);
+        rt.run_with_metrics(&mut metrics).unwrap();
+        assert_eq!(
+            metrics.to_csv(),
+            "step,a0,a1,a2,a3,h0,h1,h2,h3,[1]\n0,,,,,,,,,\n3,1,,,,,2,,,\n6,2,,,,,4,,,\n9,3,,,,,6,,,\n11,3,,,,,6,,,3\n"
+        );
+    }
+
+    #[test]
+    fn test_metrics_finish_does_not_duplicate_sample() {
+        let mut rt = runtime_from_str("a := 1\na := 2").unwrap();
+        let mut metrics = Metrics::new(1);
+        rt.run_with_metrics(&mut metrics).unwrap();
+        assert_eq!(metrics.to_csv().lines().count(), 4);
+    }
+}
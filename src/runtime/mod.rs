@@ -1,6 +1,7 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range};
 
 use miette::Result;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     base::{Accumulator, MemoryCell},
@@ -8,14 +9,42 @@ use crate::{
 };
 
 use self::{
+    contracts::Contract,
     error_handling::{RuntimeError, RuntimeErrorType},
+    macros::Macros,
     memory_config::MemoryConfig,
+    metrics::Metrics,
+    random::Random,
 };
 
+/// Suggested fixes for mechanical problems of a program
+pub mod autofix;
 /// Structs related to building a runtime
 pub mod builder;
+/// Contracts of functions, declared with annotations
+pub mod contracts;
 pub mod error_handling;
+/// Configuration stored in the header of a program file
+pub mod header;
+/// Initial values of memory locations, declared with annotations
+pub mod initial_values;
+/// Static analysis of the program
+pub mod lint;
+/// Lowering of `while` and `loop` constructs into jumps
+pub mod loops;
+/// Macros that are defined in programs
+pub mod macros;
 pub mod memory_config;
+/// Sampling of the memory while a program is run
+pub mod metrics;
+/// Random numbers of the `rand` built-in function
+pub mod random;
+/// Static analysis of the stack usage
+pub mod stack_analysis;
+/// Export of the state of the machine
+pub mod state_dump;
+/// Test cases recorded from runs of a program
+pub mod test_case;
 
 const MAX_CALL_STACK_SIZE: usize = u16::MAX as usize;
 const MAX_INSTRUCTION_RUNS: usize = 1_000_000;
@@ -29,12 +58,17 @@ pub struct Runtime {
     initial_memory: RuntimeMemory,
     instructions: Vec<Instruction>,
     control_flow: ControlFlow,
-    /// Used to count how many instructions where executed.
+    /// Used to count how many instructions were executed.
     ///
     /// If the `MAX_INSTRUCTION_RUNS` instruction has been executed a runtime error is thrown to indicate
     /// that the runtime has reached its design limit. This is among other things to protect from misuse and infinite loops.
     instruction_runs: usize,
     settings: RuntimeSettings,
+    /// Number of times each instruction was run, by the index of the instruction, reset when the runtime is reset.
+    line_hits: Vec<u64>,
+    /// Names under which memory locations are displayed, by the name of the location in the tui, see
+    /// [`MemoryConfig::display_aliases`](memory_config::MemoryConfig::display_aliases).
+    aliases: HashMap<String, String>,
 }
 
 impl Runtime {
@@ -46,11 +80,44 @@ impl Runtime {
         Ok(true)
     }
 
+    /// Runs the complete program, like [`Self::run`], the memory is sampled into `metrics` after each step.
+    ///
+    /// The memory is sampled before the first and after the last instruction too, even when a runtime error occurs.
+    pub fn run_with_metrics(&mut self, metrics: &mut Metrics) -> Result<bool, RuntimeError> {
+        metrics.sample(self.control_flow.steps, &self.memory);
+        let mut res = Ok(true);
+        while self.control_flow.next_instruction_index < self.instructions.len() {
+            if let Err(e) = self.step() {
+                res = Err(e);
+                break;
+            }
+            metrics.sample(self.control_flow.steps, &self.memory);
+        }
+        metrics.finish(self.control_flow.steps, &self.memory);
+        res
+    }
+
     /// Runs the next instruction only.
     ///
     /// Returns true when no instruction was run because the last instruction was already run.
+    ///
+    /// In robust mode, a panic while running the instruction is returned as runtime error, the memory might be
+    /// partially updated in that case.
     pub fn step(&mut self) -> Result<bool, RuntimeError> {
+        if !self.settings.robust {
+            return self.step_instruction();
+        }
+        let line_number = self.control_flow.next_instruction_index + 1;
+        RuntimeError::catch_panic(line_number, || self.step_instruction())
+    }
+
+    fn step_instruction(&mut self) -> Result<bool, RuntimeError> {
         let current_instruction = self.control_flow.next_instruction_index;
+        // function that is returned from, if the instruction is a return
+        let returning_function = match self.instructions.get(current_instruction) {
+            Some(Instruction::Return) => self.current_function(),
+            _ => None,
+        };
         self.control_flow.next_instruction_index += 1;
         if let Some(i) = self.instructions.get(current_instruction) {
             if let Err(e) = i.run(&mut self.memory, &mut self.control_flow, &self.settings) {
@@ -59,19 +126,78 @@ impl Runtime {
                     line_number: current_instruction + 1,
                 })?;
             }
+            // check contracts of called and returning functions
+            let contract_check = match (i, returning_function) {
+                (Instruction::Call(label), _) => self.check_contract(label, true),
+                (_, Some(label)) => self.check_contract(&label, false),
+                _ => Ok(()),
+            };
+            if let Err(e) = contract_check {
+                return Err(RuntimeError {
+                    reason: e,
+                    line_number: current_instruction + 1,
+                });
+            }
             self.verify(current_instruction + 1)?;
             self.instruction_runs += 1;
+            // empty lines, comments and the lines of macro definitions are not counted as steps
+            if *i != Instruction::Noop {
+                self.control_flow.steps += 1;
+            }
+            if let Some(hits) = self.line_hits.get_mut(current_instruction) {
+                *hits += 1;
+            }
         } else {
             return Ok(true);
         }
         Ok(false)
     }
 
+    /// Returns the label of the function that is currently run, if a function was called.
+    fn current_function(&self) -> Option<String> {
+        // the call stack contains the index of the instruction after the call
+        let idx = self.control_flow.call_stack.last()?.checked_sub(1)?;
+        match self.instructions.get(idx) {
+            Some(Instruction::Call(label)) => Some(label.clone()),
+            _ => None,
+        }
+    }
+
+    /// Checks the contract of the function with label `label`, if it has one.
+    ///
+    /// If `call` is true, the function was just called and the `@requires` conditions are checked,
+    /// otherwise the function just returned and the `@ensures` conditions are checked.
+    fn check_contract(&self, label: &str, call: bool) -> Result<(), RuntimeErrorType> {
+        let Some(contract) = self.control_flow.contracts.get(label) else {
+            return Ok(());
+        };
+        let conditions = if call {
+            &contract.requires
+        } else {
+            &contract.ensures
+        };
+        for condition in conditions {
+            if !condition.holds(&self.memory, &self.control_flow)? {
+                return Err(if call {
+                    RuntimeErrorType::PreconditionViolated(label.to_string(), condition.to_string())
+                } else {
+                    RuntimeErrorType::PostconditionViolated(
+                        label.to_string(),
+                        condition.to_string(),
+                    )
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Verifies that the current runtime is legal.
     ///
     /// The runtime is illegal, if specific conditions are met:
     /// - The maximum stack size is exceeded
-    /// - 1mil instructions where executed (this is to protect from infinite loops and because the runtime is to build to run so many instructions)
+    /// - The maximum number of steps, set in the settings, is exceeded
+    /// - The program finished while a function has not returned and strict calls are enabled
+    /// - 1mil instructions were executed (this is to protect from infinite loops and because the runtime is to build to run so many instructions)
     fn verify(&self, line_number: usize) -> Result<(), RuntimeError> {
         if self.control_flow.call_stack.len() >= MAX_CALL_STACK_SIZE {
             return Err(RuntimeError {
@@ -79,6 +205,21 @@ impl Runtime {
                 line_number,
             });
         }
+        if let Some(max_steps) = self.settings.max_steps {
+            if self.control_flow.steps >= max_steps {
+                return Err(RuntimeError {
+                    reason: RuntimeErrorType::StepLimitReached(max_steps),
+                    line_number,
+                });
+            }
+        }
+        if self.settings.strict_calls && self.finished() && !self.control_flow.call_stack.is_empty()
+        {
+            return Err(RuntimeError {
+                reason: RuntimeErrorType::CallNotReturned(self.control_flow.call_chain()),
+                line_number,
+            });
+        }
         if !self.settings.disable_instruction_limit && self.instruction_runs > MAX_INSTRUCTION_RUNS
         {
             return Err(RuntimeError {
@@ -106,11 +247,97 @@ impl Runtime {
         self.control_flow.next_instruction_index
     }
 
+    /// Returns the maximum number of steps the program is allowed to run, if set.
+    pub fn max_steps(&self) -> Option<usize> {
+        self.settings.max_steps
+    }
+
+    /// Returns the settings with which this runtime was built.
+    pub fn settings(&self) -> &RuntimeSettings {
+        &self.settings
+    }
+
+    /// Returns the label of the instruction with index `idx`.
+    ///
+    /// If the instruction has no label but directly follows a line that only contains a label, that label is returned.
+    pub fn instruction_label(&self, idx: usize) -> Option<&str> {
+        let label_of = |idx: usize| {
+            self.control_flow
+                .instruction_labels
+                .iter()
+                .filter(|(_, i)| **i == idx)
+                .map(|(label, _)| label.as_str())
+                .min()
+        };
+        label_of(idx).or_else(|| match idx.checked_sub(1) {
+            Some(prev) if matches!(self.instructions.get(prev), Some(Instruction::Noop)) => {
+                label_of(prev)
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns the instruction with index `idx`, `None` if the program has less instructions.
+    pub fn instruction(&self, idx: usize) -> Option<&Instruction> {
+        self.instructions.get(idx)
+    }
+
+    /// Returns the index of the instruction that the instruction with index `idx` jumps to.
+    ///
+    /// Returns `None` if that instruction is not a jump or if it jumps to the end of the program. Jumps with an else
+    /// branch return the target of the then branch.
+    pub fn jump_target(&self, idx: usize) -> Option<usize> {
+        let label = *self.instructions.get(idx)?.jump_labels().first()?;
+        self.control_flow
+            .instruction_labels
+            .get(label)
+            .copied()
+            .filter(|f| *f < self.instructions.len())
+    }
+
+    /// Returns the labels of the program, sorted by the index of the instruction they point to, together with the
+    /// indices of the instructions that belong to the block of the label.
+    ///
+    /// The block of a label reaches until the next label, labels that point to the same instruction share their
+    /// block. The labels that are injected to end the program are not included.
+    pub fn label_blocks(&self) -> Vec<(String, Range<usize>)> {
+        let mut labels = self
+            .control_flow
+            .instruction_labels
+            .iter()
+            .filter(|(_, idx)| **idx < self.instructions.len())
+            .map(|(label, idx)| (label.clone(), *idx))
+            .collect::<Vec<_>>();
+        labels.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        labels
+            .iter()
+            .map(|(label, idx)| {
+                let end = labels
+                    .iter()
+                    .map(|f| f.1)
+                    .find(|f| f > idx)
+                    .unwrap_or(self.instructions.len());
+                (label.clone(), *idx..end)
+            })
+            .collect()
+    }
+
     /// Returns reference to **`runtime_args`**.
     pub fn runtime_memory(&self) -> &RuntimeMemory {
         &self.memory
     }
 
+    /// Returns the aliases of the memory locations, set in the memory config, by the name under which the location is
+    /// displayed in the tui, e.g. `α0`.
+    pub fn aliases(&self) -> &HashMap<String, String> {
+        &self.aliases
+    }
+
+    /// Replaces the current memory, used to restore a previous state of the memory.
+    pub fn set_runtime_memory(&mut self, memory: RuntimeMemory) {
+        self.memory = memory;
+    }
+
     /// Returns a reference to **`control_flow`**.
     pub fn control_flow(&self) -> &ControlFlow {
         &self.control_flow
@@ -121,6 +348,12 @@ impl Runtime {
     pub fn reset(&mut self) {
         self.control_flow.reset_soft();
         self.memory = self.initial_memory.clone();
+        self.line_hits.fill(0);
+    }
+
+    /// Returns how often each instruction was run since the runtime was last reset, by the index of the instruction.
+    pub fn line_hits(&self) -> &[u64] {
+        &self.line_hits
     }
 
     /// Returns the index of the instruction that is executed first
@@ -154,10 +387,17 @@ impl Runtime {
         }
         res
     }
+
+    /// Checks if this runtime contains at least one print instruction.
+    pub fn contains_print_instruction(&self) -> bool {
+        self.instructions
+            .iter()
+            .any(|f| matches!(f, Instruction::Print(_)))
+    }
 }
 
 /// Used to control what instruction should be executed next.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct ControlFlow {
     /// The index of the instruction that should be executed next in the **instructions** vector.
     pub next_instruction_index: usize,
@@ -169,17 +409,33 @@ pub struct ControlFlow {
     pub instruction_labels: HashMap<String, usize>,
     /// Stores the index of the next instruction after a function returns
     pub call_stack: Vec<usize>,
+    /// Contracts of functions.
+    ///
+    /// Key = label of the function
+    pub contracts: HashMap<String, Contract>,
+    /// Macros that are defined in the program, calls of macros that are run while debugging are expanded with them.
+    pub macros: Macros,
+    /// Number of instructions that were executed since the program was started or last reset, lines without an
+    /// instruction are not counted.
+    ///
+    /// Can be accessed in programs by using the read-only value `steps`.
+    pub steps: usize,
+    /// Values that were printed with `print` since the program was started or last reset, in the order in which
+    /// they were printed.
+    pub output: Vec<i32>,
+    /// Generator of the numbers returned by `rand`, it is restarted at its seed when the program is reset.
+    pub random: Random,
+    /// Line of each instruction in the source file, counted from 1.
+    ///
+    /// Lines starting with `#` are not build into instructions, so the index of an instruction can differ from
+    /// its line. Computed jumps use this to jump to the instruction in a line.
+    pub source_lines: Vec<usize>,
     initial_instruction: usize,
 }
 
 impl ControlFlow {
     pub fn new() -> Self {
-        Self {
-            next_instruction_index: 0,
-            instruction_labels: HashMap::new(),
-            call_stack: Vec::new(),
-            initial_instruction: 0,
-        }
+        Self::default()
     }
 
     /// Updates **`next_instruction_index`** if **label** is contained in **`instruction_labels`**,
@@ -193,6 +449,26 @@ impl ControlFlow {
         }
     }
 
+    /// Updates **`next_instruction_index`** to the instruction in `line` of the source file, the lines are counted
+    /// from 1.
+    ///
+    /// Returns an error if the program has no instruction in that line.
+    pub fn jump_to_line(&mut self, line: i32) -> Result<(), RuntimeErrorType> {
+        let index = usize::try_from(line)
+            .ok()
+            .and_then(|line| self.source_lines.iter().position(|f| *f == line));
+        match index {
+            Some(index) => {
+                self.next_instruction_index = index;
+                Ok(())
+            }
+            None => Err(RuntimeErrorType::JumpTargetOutOfRange(
+                line,
+                self.source_lines.last().copied().unwrap_or_default(),
+            )),
+        }
+    }
+
     /// Updates the call stack with the instruction index from which the function was called
     /// and sets the next instruction index.
     /// Returns `StackOverflowError` when call stack exceeds size of `i16::max` elements (= the maximum size is ~2MB).
@@ -202,10 +478,26 @@ impl ControlFlow {
         Ok(())
     }
 
-    /// Resets the `next_instruction_index` to 0 and clears the call stack.
+    /// Returns the lines of the call instructions of the functions that have not returned yet, formatted as call chain.
+    ///
+    /// Example: `line 17 -> line 8`
+    pub fn call_chain(&self) -> String {
+        // the call stack contains the index of the instruction after the call, which is the line of the call
+        self.call_stack
+            .iter()
+            .map(|f| format!("line {f}"))
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+
+    /// Resets the `next_instruction_index` to 0, clears the call stack and the output, resets the step counter and
+    /// restarts the random numbers.
     pub fn reset_soft(&mut self) {
         self.next_instruction_index = self.initial_instruction;
         self.call_stack.clear();
+        self.output.clear();
+        self.random.reset();
+        self.steps = 0;
     }
 }
 
@@ -261,6 +553,14 @@ impl RuntimeMemory {
         }
         false
     }
+
+    /// Returns the memory cells of the program, the counters of `loop` constructs are left out, see
+    /// [`loops::is_counter`].
+    pub fn program_memory_cells(&self) -> impl Iterator<Item = &MemoryCell> {
+        self.memory_cells
+            .values()
+            .filter(|f| !loops::is_counter(&f.label))
+    }
 }
 
 impl From<MemoryConfig> for RuntimeMemory {
@@ -298,7 +598,7 @@ impl From<MemoryConfig> for RuntimeMemory {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 /// Settings that may be required during runtime
 pub struct RuntimeSettings {
     pub disable_instruction_limit: bool,
@@ -310,6 +610,15 @@ pub struct RuntimeSettings {
     pub autodetect_memory_cells: bool,
     // If true, index memory cells will be created automatically, if they are accessed and the don't already exist.
     pub autodetect_index_memory_cells: bool,
+    /// If set, a runtime error is returned when more steps than this are run.
+    pub max_steps: Option<usize>,
+    /// If true, a runtime error is returned when `return` is run without a function being called
+    /// or when the program finishes while a function has not returned.
+    pub strict_calls: bool,
+    /// If true, panics while running an instruction are returned as runtime error, instead of crashing the program.
+    pub robust: bool,
+    /// Seed of the numbers returned by `rand`, if not set the current time is used when the runtime is build.
+    pub seed: Option<u64>,
 }
 
 impl Default for RuntimeSettings {
@@ -320,11 +629,16 @@ impl Default for RuntimeSettings {
             autodetect_gamma_accumulator: true,
             autodetect_memory_cells: true,
             autodetect_index_memory_cells: true,
+            max_steps: None,
+            strict_calls: false,
+            robust: false,
+            seed: None,
         }
     }
 }
 
-#[cfg(test)]
+/// Constructors of the runtime memory for tests, available with the `test-utils` feature.
+#[cfg(any(feature = "test-utils", test))]
 pub mod test_utils {
     use std::collections::HashMap;
 
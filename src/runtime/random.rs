@@ -0,0 +1,83 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Increment of the state of the generator, the golden ratio as 64 bit fraction.
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Pseudo random number generator of the `rand` built-in function (splitmix64).
+///
+/// The numbers only depend on the seed, so that a run can be reproduced by using the same seed with `--seed`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Random {
+    seed: u64,
+    state: u64,
+}
+
+impl Random {
+    /// Creates a generator whose numbers are determined by `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// Returns a seed that is generated from the current time, used when no seed is set.
+    pub fn time_seed() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |f| f.as_nanos() as u64)
+    }
+
+    /// Returns the seed of the generator.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the next number between `a` and `b`, both inclusive, without advancing the generator.
+    ///
+    /// The order of `a` and `b` does not matter. Use [`Self::advance`] after the number was used, so that the
+    /// next call returns a new number.
+    pub fn value(&self, a: i32, b: i32) -> i32 {
+        let (min, max) = (i64::from(a.min(b)), i64::from(a.max(b)));
+        let range = (max - min + 1) as u64;
+        let mut z = self.state.wrapping_add(GOLDEN_GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        // the result is between min and max, so it fits into an i32
+        (min + (z % range) as i64) as i32
+    }
+
+    /// Advances the generator to the next number.
+    pub fn advance(&mut self) {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+    }
+
+    /// Restarts the generator at its seed, so that the same numbers are generated again.
+    pub fn reset(&mut self) {
+        self.state = self.seed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Random;
+
+    #[test]
+    fn test_random() {
+        let mut random = Random::new(42);
+        let mut values = Vec::new();
+        for _ in 0..100 {
+            let value = random.value(1, 6);
+            assert_eq!(value, random.value(6, 1));
+            assert!((1..=6).contains(&value));
+            values.push(value);
+            random.advance();
+        }
+        // all values of the range are generated
+        assert!((1..=6).all(|f| values.contains(&f)));
+        random.reset();
+        assert_eq!(random.value(1, 6), values[0]);
+        assert_eq!(Random::new(42).value(1, 6), values[0]);
+        assert_eq!(random.value(5, 5), 5);
+        let value = random.value(i32::MIN, i32::MAX);
+        assert_ne!(value, Random::new(43).value(i32::MIN, i32::MAX));
+    }
+}
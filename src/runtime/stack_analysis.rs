@@ -0,0 +1,230 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use crate::instructions::{Instruction, StackCommand};
+
+use super::{lint::jump_of, Runtime};
+
+/// Stack heights above this value are not tracked, they are treated as this value.
+///
+/// Required for the analysis to terminate when values are pushed inside a loop.
+const MAX_TRACKED_HEIGHT: usize = 32;
+
+/// Problem with the stack usage of a program, that was found by [`Runtime::stack_analysis`].
+#[derive(Debug, PartialEq, Clone)]
+pub enum StackWarning {
+    /// The instruction in the line is run with too few values on the stack on every path that reaches it.
+    StackUnderflow {
+        line: usize,
+        instruction: String,
+        required: usize,
+    },
+    /// On every path that ends the program in the line, values are left on the stack.
+    UnbalancedAtEnd { line: usize, values: usize },
+}
+
+impl Display for StackWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StackUnderflow {
+                line,
+                instruction,
+                required: 1,
+            } => write!(f, "line {line}: '{instruction}' is run with an empty stack"),
+            Self::StackUnderflow {
+                line,
+                instruction,
+                required,
+            } => write!(
+                f,
+                "line {line}: '{instruction}' is run with less than {required} values on the stack"
+            ),
+            Self::UnbalancedAtEnd { line, values } => write!(
+                f,
+                "line {line}: the program ends with at least {values} value(s) left on the stack"
+            ),
+        }
+    }
+}
+
+impl Runtime {
+    /// Analyzes the push/pop balance along all control flow paths of the program.
+    ///
+    /// Conditions of `if` instructions are not evaluated, both branches are assumed to be possible.
+    /// To not report false positives, warnings are only returned when a problem occurs on every path
+    /// that reaches the instruction.
+    pub fn stack_analysis(&self) -> Vec<StackWarning> {
+        let len = self.instructions.len();
+        // instructions that are run after a function returns
+        let return_sites = self
+            .instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, instruction)| matches!(instruction, Instruction::Call(_)))
+            .map(|(idx, _)| idx + 1)
+            .collect::<Vec<_>>();
+        // possible stack heights before the instruction is run
+        let mut heights = vec![BTreeSet::new(); len];
+        // possible stack heights when the program ends after the instruction
+        let mut end_heights: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+        let mut worklist = vec![(
+            self.control_flow.initial_instruction,
+            self.initial_memory.stack.len().min(MAX_TRACKED_HEIGHT),
+        )];
+        while let Some((idx, height)) = worklist.pop() {
+            let Some(instruction) = self.instructions.get(idx) else {
+                continue;
+            };
+            if !heights[idx].insert(height) {
+                continue;
+            }
+            // execution stops with a runtime error, if the stack contains not enough values
+            let Some(height) = height_after(instruction, height) else {
+                continue;
+            };
+            let successors = match jump_of(instruction) {
+                Instruction::Goto(label) | Instruction::Call(label) => self
+                    .control_flow
+                    .instruction_labels
+                    .get(label)
+                    .copied()
+                    .into_iter()
+                    .collect(),
+                Instruction::JumpIf(_, _, _, label) | Instruction::JumpIfCompound(_, label) => {
+                    let mut successors = vec![idx + 1];
+                    successors.extend(self.control_flow.instruction_labels.get(label));
+                    successors
+                }
+                Instruction::JumpIfElse(_, then_label, else_label) => [then_label, else_label]
+                    .into_iter()
+                    .filter_map(|label| self.control_flow.instruction_labels.get(label))
+                    .copied()
+                    .collect(),
+                // without calls, return ends the program
+                Instruction::Return if return_sites.is_empty() => vec![len],
+                Instruction::Return => return_sites.clone(),
+                // the target is only known when the program is run
+                Instruction::ComputedGoto(_) => (0..len).collect(),
+                _ => vec![idx + 1],
+            };
+            for successor in successors {
+                if successor >= len {
+                    end_heights.entry(idx).or_default().insert(height);
+                } else {
+                    worklist.push((successor, height));
+                }
+            }
+        }
+
+        let mut warnings = Vec::new();
+        for (idx, instruction) in self.instructions.iter().enumerate() {
+            let required = match instruction {
+                Instruction::Pop | Instruction::Peek(_) => 1,
+                Instruction::StackOp(_) => 2,
+                Instruction::StackCommand(command) => command.required_values(),
+                _ => continue,
+            };
+            if !heights[idx].is_empty() && heights[idx].iter().all(|h| *h < required) {
+                warnings.push(StackWarning::StackUnderflow {
+                    line: idx + 1,
+                    instruction: instruction.to_string(),
+                    required,
+                });
+            }
+        }
+        for (idx, heights) in end_heights {
+            if let Some(values) = heights.first().filter(|f| **f > 0) {
+                warnings.push(StackWarning::UnbalancedAtEnd {
+                    line: idx + 1,
+                    values: *values,
+                });
+            }
+        }
+        warnings.sort_by_key(|f| match f {
+            StackWarning::StackUnderflow { line, .. }
+            | StackWarning::UnbalancedAtEnd { line, .. } => *line,
+        });
+        warnings
+    }
+}
+
+/// Returns the height of the stack after `instruction` was run, `None` if the stack contains not enough values.
+fn height_after(instruction: &Instruction, height: usize) -> Option<usize> {
+    match instruction {
+        Instruction::Push => Some((height + 1).min(MAX_TRACKED_HEIGHT)),
+        Instruction::Pop => height.checked_sub(1),
+        Instruction::StackOp(_) if height < 2 => None,
+        Instruction::StackOp(_) => Some(height - 1),
+        Instruction::StackCommand(command) if height < command.required_values() => None,
+        Instruction::StackCommand(StackCommand::Dup) => Some((height + 1).min(MAX_TRACKED_HEIGHT)),
+        Instruction::StackCommand(StackCommand::Drop) => Some(height - 1),
+        Instruction::Peek(_) if height < 1 => None,
+        Instruction::Macro(call) => call
+            .body
+            .iter()
+            .try_fold(height, |height, f| height_after(f, height)),
+        _ => Some(height),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils::runtime_from_str;
+
+    use super::StackWarning;
+
+    #[test]
+    fn test_stack_analysis_balanced() {
+        for program in [
+            "a := 5\npush\npop",
+            "a := 5\nloop: push\na := a - 1\nif a > 0 then goto loop\nloop2: pop\nif a > 0 then goto loop2",
+            "main: a := 5\npush\ncall f\ngoto END\nf: pop\nreturn",
+        ] {
+            let rt = runtime_from_str(program).unwrap();
+            assert_eq!(rt.stack_analysis(), Vec::new(), "{program}");
+        }
+    }
+
+    #[test]
+    fn test_stack_analysis_underflow() {
+        let rt = runtime_from_str("a := 5\npush\nif a > 0 then goto skip\npop\nskip: pop").unwrap();
+        assert_eq!(rt.stack_analysis(), Vec::new());
+        let rt =
+            runtime_from_str("a := 5\npush\nstack dup\npeek p(h1)\nstack swap\nstack drop\npop")
+                .unwrap();
+        assert_eq!(rt.stack_analysis(), Vec::new());
+        let rt = runtime_from_str("a := 5\npush\nstack swap\npop").unwrap();
+        assert_eq!(
+            rt.stack_analysis(),
+            vec![StackWarning::StackUnderflow {
+                line: 3,
+                instruction: "stack swap".to_string(),
+                required: 2
+            }]
+        );
+        let rt = runtime_from_str("a := 5\npush\npop\nstack+").unwrap();
+        assert_eq!(
+            rt.stack_analysis(),
+            vec![StackWarning::StackUnderflow {
+                line: 4,
+                instruction: "stack+".to_string(),
+                required: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn test_stack_analysis_unbalanced_at_end() {
+        let rt = runtime_from_str("a := 5\npush\nif a > 0 then goto END\npush\npop\npop").unwrap();
+        assert_eq!(
+            rt.stack_analysis(),
+            vec![StackWarning::UnbalancedAtEnd { line: 3, values: 1 }]
+        );
+        assert_eq!(
+            rt.stack_analysis()[0].to_string(),
+            "line 3: the program ends with at least 1 value(s) left on the stack"
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+use miette::{IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    base::{Accumulator, MemoryCell},
+    utils,
+};
+
+use super::RuntimeMemory;
+
+/// Format in which the state of the machine is written by `--dump-state`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, ValueEnum)]
+pub enum StateFormat {
+    #[default]
+    Json,
+}
+
+/// State of the machine after the program finished, written by `--dump-state`.
+///
+/// Maps are sorted by key, so that the output is stable and can be compared in scripts.
+///
+/// When a state is read, missing maps are treated as empty and a missing stack as empty stack, so that the expected
+/// state of an exercise only has to contain the checked memory locations.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StateDump {
+    #[serde(default)]
+    pub accumulators: BTreeMap<usize, Option<i32>>,
+    /// Only written when the gamma accumulator is enabled.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_gamma"
+    )]
+    pub gamma: Option<Option<i32>>,
+    #[serde(default)]
+    pub memory_cells: BTreeMap<String, Option<i32>>,
+    #[serde(default)]
+    pub index_memory_cells: BTreeMap<usize, Option<i32>>,
+    /// Values of the stack, the top of the stack is the last value.
+    #[serde(default)]
+    pub stack: Vec<i32>,
+}
+
+impl From<&RuntimeMemory> for StateDump {
+    fn from(memory: &RuntimeMemory) -> Self {
+        Self {
+            accumulators: memory
+                .accumulators
+                .values()
+                .map(|f| (f.id, f.data))
+                .collect(),
+            gamma: memory.gamma,
+            memory_cells: memory
+                .program_memory_cells()
+                .map(|f| (f.label.clone(), f.data))
+                .collect(),
+            index_memory_cells: memory
+                .index_memory_cells
+                .iter()
+                .map(|(index, value)| (*index, *value))
+                .collect(),
+            stack: memory.stack.clone(),
+        }
+    }
+}
+
+impl From<&StateDump> for RuntimeMemory {
+    fn from(state: &StateDump) -> Self {
+        Self {
+            accumulators: state
+                .accumulators
+                .iter()
+                .map(|(id, data)| {
+                    (
+                        *id,
+                        Accumulator {
+                            id: *id,
+                            data: *data,
+                        },
+                    )
+                })
+                .collect(),
+            gamma: state.gamma,
+            memory_cells: state
+                .memory_cells
+                .iter()
+                .map(|(label, data)| {
+                    (
+                        label.clone(),
+                        MemoryCell {
+                            label: label.clone(),
+                            data: *data,
+                        },
+                    )
+                })
+                .collect(),
+            index_memory_cells: state
+                .index_memory_cells
+                .iter()
+                .map(|(index, value)| (*index, *value))
+                .collect(),
+            stack: state.stack.clone(),
+        }
+    }
+}
+
+/// Difference of a memory location between two states, see [`StateDump::diff`].
+#[derive(Debug, PartialEq, Serialize)]
+pub struct StateDifference {
+    /// Name of the memory location, e.g. `α0`, `h1` or `[2]`.
+    pub location: String,
+    pub left: Value,
+    pub right: Value,
+}
+
+impl StateDump {
+    /// Tries to read a state that was written with `--dump-state` from the file at `path`.
+    pub fn try_from_file(path: &str) -> Result<Self> {
+        serde_json::from_str(&utils::read_file(path)?.join("\n"))
+            .map_err(|e| miette::miette!("Unable to parse state from {path}: {e}"))
+    }
+
+    /// Returns the memory locations that have different values in `self` and `other`.
+    ///
+    /// Locations that only exist in one of the states are treated as not set in the other state,
+    /// the stack is compared as a whole.
+    pub fn diff(&self, other: &Self) -> Vec<StateDifference> {
+        let mut differences = Vec::new();
+        diff_maps(
+            &self.accumulators,
+            &other.accumulators,
+            |id| format!("α{id}"),
+            &mut differences,
+        );
+        let (left, right) = (self.gamma.flatten(), other.gamma.flatten());
+        if left != right {
+            differences.push(StateDifference {
+                location: "γ".to_string(),
+                left: left.into(),
+                right: right.into(),
+            });
+        }
+        diff_maps(
+            &self.memory_cells,
+            &other.memory_cells,
+            ToString::to_string,
+            &mut differences,
+        );
+        diff_maps(
+            &self.index_memory_cells,
+            &other.index_memory_cells,
+            |index| format!("[{index}]"),
+            &mut differences,
+        );
+        if self.stack != other.stack {
+            differences.push(StateDifference {
+                location: "stack".to_string(),
+                left: self.stack.clone().into(),
+                right: other.stack.clone().into(),
+            });
+        }
+        differences
+    }
+
+    /// Returns the differences of `actual` to the memory locations that have a value in `self`, used to check the
+    /// result of an exercise. The stack is always compared.
+    pub fn expected_differences(&self, actual: &Self) -> Vec<StateDifference> {
+        self.diff(actual)
+            .into_iter()
+            .filter(|f| !f.left.is_null() || f.location == "stack")
+            .collect()
+    }
+
+    /// Sets the memory locations that are contained in `values` to their value in `values`, the stack is replaced
+    /// if `values` contains a stack.
+    pub fn update(&mut self, values: &Self) {
+        self.accumulators.extend(&values.accumulators);
+        if values.gamma.is_some() {
+            self.gamma = values.gamma;
+        }
+        self.memory_cells.extend(values.memory_cells.clone());
+        self.index_memory_cells.extend(&values.index_memory_cells);
+        if !values.stack.is_empty() {
+            self.stack.clone_from(&values.stack);
+        }
+    }
+
+    /// Returns the state formatted in `format`.
+    pub fn format(&self, format: StateFormat) -> Result<String> {
+        match format {
+            StateFormat::Json => serde_json::to_string_pretty(self).into_diagnostic(),
+        }
+    }
+
+    /// Writes the state formatted in `format` to the file at `path`, the file is overwritten if it exists.
+    pub fn write_to_file(&self, path: &str, format: StateFormat) -> Result<()> {
+        utils::write_file_atomic(path, self.format(format)?.as_bytes())
+            .map_err(|e| miette::miette!("Unable to write state to {path}: {e}"))
+    }
+}
+
+/// A gamma accumulator without value is written as `null`, it has to be read as enabled without value,
+/// instead of as disabled.
+fn deserialize_gamma<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Option<i32>>, D::Error> {
+    Option::<i32>::deserialize(deserializer).map(Some)
+}
+
+/// Adds the keys of `left` and `right` that have different values to `differences`, `name` formats the key.
+fn diff_maps<K: Ord>(
+    left: &BTreeMap<K, Option<i32>>,
+    right: &BTreeMap<K, Option<i32>>,
+    name: impl Fn(&K) -> String,
+    differences: &mut Vec<StateDifference>,
+) {
+    let mut keys = left.keys().chain(right.keys()).collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let left = left.get(key).copied().flatten();
+        let right = right.get(key).copied().flatten();
+        if left != right {
+            differences.push(StateDifference {
+                location: name(key),
+                left: left.into(),
+                right: right.into(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils::runtime_from_str;
+
+    use super::{StateDump, StateFormat};
+
+    #[test]
+    fn test_state_dump_json() {
+        let mut rt = runtime_from_str("a := 5\np(h1) := a * 2\np(1) := 3\npush\ny := 4").unwrap();
+        rt.run().unwrap();
+        let json = StateDump::from(rt.runtime_memory())
+            .format(StateFormat::Json)
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["accumulators"]["0"], 5);
+        assert_eq!(value["gamma"], 4);
+        assert_eq!(value["memory_cells"]["h1"], 10);
+        assert_eq!(value["index_memory_cells"]["1"], 3);
+        assert_eq!(value["stack"], serde_json::json!([5]));
+    }
+
+    #[test]
+    fn test_state_dump_without_gamma() {
+        let mut rt = runtime_from_str("a := 1").unwrap();
+        rt.run().unwrap();
+        let json = StateDump::from(rt.runtime_memory())
+            .format(StateFormat::Json)
+            .unwrap();
+        assert!(!json.contains("gamma"));
+        assert!(json.contains("\"0\": 1"));
+    }
+
+    #[test]
+    fn test_state_dump_without_loop_counters() {
+        let mut rt =
+            runtime_from_str("a := 0\nloop 3 times\na := a + 2\nendloop\np(h1) := a").unwrap();
+        rt.run().unwrap();
+        assert!(rt.runtime_memory().memory_cells.contains_key("loop 2"));
+        let dump = StateDump::from(rt.runtime_memory());
+        assert_eq!(dump.memory_cells["h1"], Some(6));
+        assert!(!dump.memory_cells.keys().any(|f| f.starts_with("loop")));
+        assert!(!dump.format(StateFormat::Json).unwrap().contains("loop"));
+    }
+
+    #[test]
+    fn test_state_dump_diff() {
+        let state = |json: &str| serde_json::from_str::<StateDump>(json).unwrap();
+        let left = state(
+            r#"{"accumulators": {"0": 1, "1": null}, "gamma": null, "memory_cells": {"h1": 2}, "index_memory_cells": {}, "stack": [1]}"#,
+        );
+        let right = state(
+            r#"{"accumulators": {"0": 1, "1": 3}, "gamma": 4, "memory_cells": {}, "index_memory_cells": {"2": 5}, "stack": [1]}"#,
+        );
+        assert_eq!(left.gamma, Some(None));
+        assert!(left.diff(&left).is_empty());
+        let diff = left
+            .diff(&right)
+            .into_iter()
+            .map(|f| format!("{}: {} -> {}", f.location, f.left, f.right))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diff,
+            vec![
+                "α1: null -> 3",
+                "γ: null -> 4",
+                "h1: 2 -> null",
+                "[2]: null -> 5"
+            ]
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use miette::{miette, IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::utils;
+
+use super::state_dump::StateDump;
+
+/// Test case of a program, recorded by `test record` from a run that is known to be correct.
+///
+/// The program is started with the initial state and has to end with the expected state, when the test case is run.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestCase {
+    /// Program file, relative to the directory of the test case file if it is located inside of it.
+    pub program: String,
+    /// State of the machine before the first instruction was run.
+    pub initial_state: StateDump,
+    /// State of the machine after the program finished.
+    pub expected_state: StateDump,
+    /// Number of steps the recorded run took.
+    #[serde(default)]
+    pub steps: usize,
+}
+
+impl TestCase {
+    /// Tries to read a test case from the json file at `path`.
+    pub fn try_from_file(path: &str) -> Result<Self> {
+        serde_json::from_str(&utils::read_file(path)?.join("\n"))
+            .map_err(|e| miette!("Unable to parse test case from {path}: {e}"))
+    }
+
+    /// Writes the test case as json to the file at `path`.
+    pub fn write_to_file(&self, path: &str) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).into_diagnostic()?;
+        utils::write_file_atomic(path, json.as_bytes())
+            .map_err(|e| miette!("Unable to write test case to {path}: {e}"))
+    }
+
+    /// Returns the path of the program, relative paths are resolved from the directory of the test case file at
+    /// `path`.
+    pub fn program_path(&self, path: &str) -> String {
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        dir.join(&self.program).to_string_lossy().to_string()
+    }
+}
+
+/// Test cases of a program that are written by hand, run by `test cases`.
+///
+/// The cases are listed in a toml file as array of tables named `case`.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestSpec {
+    #[serde(rename = "case", default)]
+    pub cases: Vec<SpecCase>,
+}
+
+/// Test case of a [`TestSpec`].
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpecCase {
+    /// Name of the case in the report, the number of the case is used if it is not set.
+    pub name: Option<String>,
+    /// Values that are set before the program is started, the other memory locations keep the values of the program
+    /// header and memory config.
+    #[serde(default)]
+    pub initial: StateDump,
+    /// Values that the memory locations need to have when the program finished, like the expected state of an
+    /// exercise only the listed locations and the stack are checked.
+    pub expected: StateDump,
+}
+
+impl TestSpec {
+    /// Tries to read the test cases from the toml file at `path`.
+    pub fn try_from_file(path: &str) -> Result<Self> {
+        Self::parse(&utils::read_file(path)?.join("\n"))
+            .map_err(|e| miette!("Unable to parse test cases from {path}: {e}"))
+    }
+
+    /// Parses the test cases from toml.
+    ///
+    /// The toml is converted to json first, because keys of toml tables can't be read as numbers, which is
+    /// required for accumulators and index memory cells.
+    fn parse(content: &str) -> Result<Self> {
+        let value = toml::from_str::<toml::Value>(content).into_diagnostic()?;
+        let value = serde_json::to_value(value).into_diagnostic()?;
+        serde_json::from_value(value).into_diagnostic()
+    }
+}
+
+/// Returns the path under which the test case file at `path` refers to `program`.
+///
+/// The path is relative, if the program is located in the directory of the test case file or one of its
+/// subdirectories, so that the files can be moved together. Otherwise the absolute path of the program is returned.
+pub fn relative_program_path(path: &str, program: &str) -> Result<String> {
+    let program = std::fs::canonicalize(program).into_diagnostic()?;
+    let dir = match Path::new(path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let dir = std::fs::canonicalize(dir).into_diagnostic()?;
+    let program = program.strip_prefix(&dir).unwrap_or(&program);
+    Ok(program.to_string_lossy().to_string())
+}
+
+/// Returns the default path of the test case file that is recorded for `program`, `sum.alpha` is recorded to
+/// `sum.test.json`.
+pub fn default_test_case_path(program: &str) -> String {
+    Path::new(program)
+        .with_extension("test.json")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{default_test_case_path, TestCase, TestSpec};
+
+    #[test]
+    fn test_test_case_paths() {
+        assert_eq!(
+            default_test_case_path("tests/sum.alpha"),
+            "tests/sum.test.json"
+        );
+        assert_eq!(default_test_case_path("sum"), "sum.test.json");
+        let case: TestCase = serde_json::from_str(
+            r#"{"program": "sum.alpha", "initial_state": {}, "expected_state": {}}"#,
+        )
+        .unwrap();
+        assert_eq!(case.program_path("tests/sum.test.json"), "tests/sum.alpha");
+        assert_eq!(case.program_path("sum.test.json"), "sum.alpha");
+    }
+
+    #[test]
+    fn test_test_spec() {
+        let spec = TestSpec::parse(
+            r#"
+[[case]]
+name = "five"
+initial = { memory_cells = { h1 = 5 }, accumulators = { 0 = 1 } }
+expected = { memory_cells = { h2 = 15 } }
+
+[[case]]
+expected.gamma = 3
+expected.stack = [1, 2]
+"#,
+        )
+        .unwrap();
+        assert_eq!(spec.cases.len(), 2);
+        assert_eq!(spec.cases[0].name.as_deref(), Some("five"));
+        assert_eq!(spec.cases[0].initial.memory_cells["h1"], Some(5));
+        assert_eq!(spec.cases[0].initial.accumulators[&0], Some(1));
+        assert_eq!(spec.cases[0].expected.memory_cells["h2"], Some(15));
+        assert_eq!(spec.cases[1].expected.gamma, Some(Some(3)));
+        assert_eq!(spec.cases[1].expected.stack, [1, 2]);
+        assert!(TestSpec::parse("[[case]]\nexpect = {}").is_err());
+    }
+}
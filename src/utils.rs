@@ -1,10 +1,12 @@
 use std::{
     collections::HashSet,
     fs::{remove_file, File},
-    io::{BufRead, BufReader, LineWriter, Write},
+    io::Write,
+    path::Path,
 };
 
-use miette::{miette, IntoDiagnostic, NamedSource, Result, SourceOffset, SourceSpan};
+use miette::{miette, Diagnostic, IntoDiagnostic, NamedSource, Result, SourceOffset, SourceSpan};
+use thiserror::Error;
 
 use crate::instructions::{
     error_handling::{BuildAllowedInstructionsError, InstructionParseError},
@@ -13,35 +15,153 @@ use crate::instructions::{
 
 /// Reads a file into a string vector.
 ///
-/// Each  line is a new entry.
+/// Each  line is a new entry. A UTF-8 byte order mark is removed and line endings are normalized,
+/// so files created on Windows can be read as well.
 pub fn read_file(path: &str) -> Result<Vec<String>> {
-    let mut content = Vec::new();
-    let file = match File::open(path) {
-        Ok(f) => f,
+    let content = match std::fs::read(path) {
+        Ok(content) => content,
         Err(e) => return Err(miette::miette!(e)),
     };
-    let reader = BufReader::new(file);
+    Ok(decode_file_content(&content)?)
+}
 
-    for line in reader.lines() {
-        match line {
-            Ok(l) => content.push(l),
-            Err(e) => return Err(miette::miette!(e)),
-        }
+/// Errors that can occur when the content of a file is decoded.
+#[derive(Debug, PartialEq, Error, Diagnostic)]
+pub enum ReadFileError {
+    #[error(
+        "file is not valid UTF-8, invalid byte at offset {offset} (line {line}, column {column})"
+    )]
+    #[diagnostic(
+        code("read_file_error::invalid_utf8"),
+        help("Make sure that the file is saved with UTF-8 encoding.")
+    )]
+    InvalidUtf8 {
+        offset: usize,
+        line: usize,
+        column: usize,
+    },
+
+    #[error("file is UTF-16 encoded")]
+    #[diagnostic(
+        code("read_file_error::utf16"),
+        help("Only UTF-8 encoded files are supported, save the file with UTF-8 encoding.")
+    )]
+    Utf16,
+}
+
+/// Decodes the content of a file into lines.
+///
+/// A UTF-8 byte order mark is removed and `\r\n` and `\r` line endings are treated as `\n`.
+pub fn decode_file_content(content: &[u8]) -> Result<Vec<String>, ReadFileError> {
+    if content.starts_with(&[0xFF, 0xFE]) || content.starts_with(&[0xFE, 0xFF]) {
+        return Err(ReadFileError::Utf16);
     }
-    Ok(content)
+    // the length of the BOM is added to the offset of invalid bytes, so that it points into the file
+    let (bom_len, content) = match content.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        Some(content) => (3, content),
+        None => (0, content),
+    };
+    let text = match std::str::from_utf8(content) {
+        Ok(text) => text,
+        Err(e) => {
+            let valid = &content[..e.valid_up_to()];
+            let line_start = valid
+                .iter()
+                .rposition(|f| *f == b'\n' || *f == b'\r')
+                .map_or(0, |f| f + 1);
+            return Err(ReadFileError::InvalidUtf8 {
+                offset: bom_len + e.valid_up_to(),
+                line: valid
+                    .iter()
+                    .enumerate()
+                    .filter(|(idx, f)| {
+                        **f == b'\n' || (**f == b'\r' && valid.get(idx + 1) != Some(&b'\n'))
+                    })
+                    .count()
+                    + 1,
+                column: String::from_utf8_lossy(&valid[line_start..])
+                    .chars()
+                    .count()
+                    + 1,
+            });
+        }
+    };
+    Ok(text
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .lines()
+        .map(ToString::to_string)
+        .collect())
 }
 
+/// Writes the lines to the file at `path`, the file is overwritten if it exists.
+///
+/// See [`write_file_atomic`].
 pub fn write_file(contet: &Vec<String>, path: &str) -> Result<()> {
-    remove_file(path).into_diagnostic()?;
-    let file = File::create(path).into_diagnostic()?;
-
-    let mut writer = LineWriter::new(file);
+    let mut content = String::new();
     for line in contet {
-        writer.write_all(line.as_bytes()).into_diagnostic()?;
-        writer.write_all("\n".as_bytes()).into_diagnostic()?;
+        content.push_str(line);
+        content.push('\n');
+    }
+    write_file_atomic(path, content.as_bytes())
+}
+
+/// Writes the lines to the file at `path` like [`write_file`], but only if the file still contains the lines
+/// `expected`, that were read from it before.
+///
+/// Used when a source file is written back, so that changes another program made in the meantime, e.g. an editor
+/// that has the file open, are not overwritten. Returns false if the file was modified and nothing was written.
+pub fn write_file_if_unchanged(
+    contet: &Vec<String>,
+    path: &str,
+    expected: &[String],
+) -> Result<bool> {
+    if read_file(path)? != expected {
+        return Ok(false);
     }
-    writer.flush().into_diagnostic()?;
-    Ok(())
+    write_file(contet, path)?;
+    Ok(true)
+}
+
+/// Writes `content` to the file at `path`, the file is overwritten if it exists.
+///
+/// The content is written to a temporary file next to the file first, that is then renamed to `path`.
+/// If writing is interrupted, the file is left unchanged instead of being truncated. Concurrent sessions that
+/// write the same file use different temporary files, so that the file always contains the content of one of them.
+/// The permissions of an existing file are kept.
+///
+/// Symlinks, devices and FIFOs (e.g. `/dev/stdout`) are written in place, so that they are not replaced by a
+/// regular file.
+pub fn write_file_atomic(path: &str, content: &[u8]) -> Result<()> {
+    let metadata = std::fs::symlink_metadata(path).ok();
+    if metadata.as_ref().is_some_and(|f| !f.is_file()) {
+        return std::fs::write(path, content).into_diagnostic();
+    }
+    let path = Path::new(path);
+    let Some(file_name) = path.file_name() else {
+        return Err(miette!(
+            "Unable to write file {}: not a file",
+            path.display()
+        ));
+    };
+    let tmp_path = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+    let res = File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(content)?;
+            if let Some(metadata) = &metadata {
+                file.set_permissions(metadata.permissions())?;
+            }
+            file.sync_all()
+        })
+        .and_then(|()| std::fs::rename(&tmp_path, path));
+    if res.is_err() {
+        _ = remove_file(&tmp_path);
+    }
+    res.into_diagnostic()
 }
 
 /// Writes the specified line to the end of the file.
@@ -97,13 +217,25 @@ pub fn get_comment(instruction: &str) -> Option<String> {
 
 /// Builds a hash set of allowed instruction identifiers, by parsing each line in the input instructions as instruction
 /// and storing the id.
+///
+/// Comments (`//` and `#`) and empty lines are ignored, like in programs.
 pub fn build_instruction_whitelist(
     instructions: Vec<String>,
     path: &str,
 ) -> Result<HashSet<String>> {
-    let instructions = prepare_whitelist_file(instructions);
+    // comments are removed before the placeholders are replaced, empty lines are kept so that errors point to the
+    // correct line
+    let instructions = prepare_whitelist_file(
+        instructions
+            .iter()
+            .map(|f| remove_comment(f))
+            .collect::<Vec<_>>(),
+    );
     let mut whitelisted_instructions = HashSet::new();
     for (idx, s) in instructions.iter().enumerate() {
+        if s.is_empty() {
+            continue;
+        }
         match Instruction::try_from(s.as_str()) {
             Ok(i) => {
                 let _ = whitelisted_instructions.insert(i.identifier());
@@ -119,6 +251,10 @@ pub fn build_instruction_whitelist(
                     InstructionParseError::NotANumber(_, _) => e.range().1 - e.range().0,
                     InstructionParseError::UnknownComparison(_, _) => e.range().1 - e.range().0,
                     InstructionParseError::UnknownOperation(_, _) => e.range().1 - e.range().0,
+                    InstructionParseError::OperandNotAllowed(_, _, _)
+                    | InstructionParseError::WrongArgumentCount(_, _, _) => {
+                        e.range().1 - e.range().0
+                    }
                     InstructionParseError::MissingExpression { range: _, help: _ } => {
                         e.range().1 - e.range().0
                     }
@@ -149,6 +285,7 @@ pub fn build_instruction_whitelist(
 /// M - p(h1)
 /// C - 0
 /// Y - y
+/// S - steps
 /// OP - +
 /// CMP - ==
 pub fn prepare_whitelist_file(content: Vec<String>) -> Vec<String> {
@@ -178,6 +315,7 @@ pub fn prepare_whitelist_file(content: Vec<String>) -> Vec<String> {
                 "M(M)" => new_chunks.push("p(p(h1))"),
                 "C" => new_chunks.push("0"),
                 "Y" => new_chunks.push("y"),
+                "S" => new_chunks.push("steps"),
                 "OP" => new_chunks.push("+"),
                 "stackOP" => new_chunks.push("stack+"),
                 "CMP" => new_chunks.push("=="),
@@ -190,11 +328,12 @@ pub fn prepare_whitelist_file(content: Vec<String>) -> Vec<String> {
     prepared
 }
 
-#[cfg(test)]
+/// Helpers to build runtimes from strings for tests, available with the `test-utils` feature.
+#[cfg(any(feature = "test-utils", test))]
 pub mod test_utils {
     use crate::{
-        cli::{GlobalArgs, InstructionLimitingArgs},
-        runtime::{builder::RuntimeBuilder, Runtime},
+        cli::{GlobalArgs, MemoryArgs},
+        runtime::{builder::RuntimeBuilder, error_handling::BuildError, Runtime},
     };
 
     /// Creates a string vector from a &str.
@@ -203,33 +342,93 @@ pub mod test_utils {
     }
 
     /// Constructs a runtime using the input string.
-    pub fn runtime_from_str(input: &str) -> miette::Result<Runtime> {
+    pub fn runtime_from_str(input: &str) -> Result<Runtime, BuildError> {
         RuntimeBuilder::new(&string_literal_to_vec(input), "test")
             .unwrap()
             .build()
     }
 
+    /// Constructs a new runtime using the input string and applies the program header.
+    pub fn runtime_from_str_with_program_header(input: &str) -> Result<Runtime, BuildError> {
+        let program = string_literal_to_vec(input);
+        let mut rb = RuntimeBuilder::new(&program, "test").unwrap();
+        rb.apply_program_header(&program)?;
+        rb.build()
+    }
+
     /// Constructs a new runtime using the input string and applies default global args.
-    pub fn runtime_from_str_with_default_cli_args(input: &str) -> miette::Result<Runtime> {
+    pub fn runtime_from_str_with_default_cli_args(input: &str) -> Result<Runtime, BuildError> {
         let mut rb = RuntimeBuilder::new(&string_literal_to_vec(input), "test").unwrap();
         rb.apply_global_cli_args(&GlobalArgs::default()).unwrap();
         rb.build()
     }
 
     /// Constructs a runtime using the input string.
-    pub fn runtime_from_str_with_disable_memory_detection(input: &str) -> miette::Result<Runtime> {
+    pub fn runtime_from_str_with_disable_memory_detection(
+        input: &str,
+    ) -> Result<Runtime, BuildError> {
         let mut rb = RuntimeBuilder::new(&string_literal_to_vec(input), "test").unwrap();
 
-        let mut ila = InstructionLimitingArgs::default();
-        ila.disable_memory_detection = true;
-        rb.apply_instruction_limiting_args(&ila).unwrap();
+        rb.apply_memory_args(&MemoryArgs {
+            disable_memory_detection: true,
+            ..Default::default()
+        });
         rb.build()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::utils::{get_comment, prepare_whitelist_file, remove_comment};
+    use crate::utils::{
+        build_instruction_whitelist, decode_file_content, get_comment, prepare_whitelist_file,
+        read_file, remove_comment, write_file, write_file_atomic, write_file_if_unchanged,
+        write_line_to_file, ReadFileError,
+    };
+
+    #[test]
+    fn test_decode_file_content() {
+        assert_eq!(
+            decode_file_content(b"a := 5\nb := 6\n").unwrap(),
+            vec!["a := 5", "b := 6"]
+        );
+        assert_eq!(
+            decode_file_content(b"\xEF\xBB\xBFa := 5\r\nb := 6\r\n").unwrap(),
+            vec!["a := 5", "b := 6"]
+        );
+        assert_eq!(
+            decode_file_content(b"a := 5\rb := 6").unwrap(),
+            vec!["a := 5", "b := 6"]
+        );
+        assert_eq!(
+            decode_file_content(b"\xFF\xFEa").unwrap_err(),
+            ReadFileError::Utf16
+        );
+        assert_eq!(
+            decode_file_content(b"a := 5\r\nb := \xE4").unwrap_err(),
+            ReadFileError::InvalidUtf8 {
+                offset: 13,
+                line: 2,
+                column: 6
+            }
+        );
+        // the offset includes the BOM, the column doesn't
+        assert_eq!(
+            decode_file_content(b"\xEF\xBB\xBFa := 5\r\nb := \xE4").unwrap_err(),
+            ReadFileError::InvalidUtf8 {
+                offset: 16,
+                line: 2,
+                column: 6
+            }
+        );
+        assert_eq!(
+            decode_file_content(b"\xEF\xBB\xBF\xE4").unwrap_err(),
+            ReadFileError::InvalidUtf8 {
+                offset: 3,
+                line: 1,
+                column: 1
+            }
+        );
+    }
 
     #[test]
     fn test_remove_comments() {
@@ -264,6 +463,31 @@ mod tests {
         assert_eq!(get_comment("a := 5"), None);
     }
 
+    #[test]
+    fn test_build_instruction_whitelist_with_comments() {
+        let contents = ["// loads", "A := M", "", "# stores", "M := A // store"]
+            .map(String::from)
+            .to_vec();
+        let whitelist = build_instruction_whitelist(contents, "whitelist").unwrap();
+        assert_eq!(whitelist.len(), 2);
+        assert!(whitelist.contains("A := M"));
+        assert!(whitelist.contains("M := A"));
+    }
+
+    #[test]
+    fn test_build_instruction_whitelist_error_line() {
+        let contents = ["// loads", "A := M", "", "A := M xx"]
+            .map(String::from)
+            .to_vec();
+        let err = build_instruction_whitelist(contents, "whitelist").unwrap_err();
+        let err = err
+            .downcast_ref::<crate::instructions::error_handling::BuildAllowedInstructionsError>()
+            .unwrap();
+        // comments are removed from the source, the error has to be in the last line
+        let span = miette::SourceCode::read_span(&err.src, &err.bad_bit, 0, 0).unwrap();
+        assert_eq!(span.line() + 1, 4);
+    }
+
     #[test]
     fn test_prepare_whitelist_file() {
         let contents = "A := M\nA := C\nM := A\nY := A OP M\nif A CMP M then goto\ngoto\ncall";
@@ -284,4 +508,56 @@ mod tests {
         ];
         assert_eq!(*contents, after);
     }
+
+    #[test]
+    fn test_write_file_atomic() {
+        let dir = std::env::temp_dir().join(format!("alpha_tui_write_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("history").to_str().unwrap().to_string();
+        // the file is created if it does not exist
+        write_file(&vec!["a := 1".to_string()], &path).unwrap();
+        write_line_to_file("a := 2", &path).unwrap();
+        assert_eq!(read_file(&path).unwrap(), vec!["a := 1", "a := 2"]);
+        // no temporary files are left behind
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_write_file_atomic_keeps_permissions_and_symlinks() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = std::env::temp_dir().join(format!("alpha_tui_write_mode_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("program.alpha");
+        std::fs::write(&path, "a := 1\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o750)).unwrap();
+        write_file_atomic(path.to_str().unwrap(), b"a := 2\n").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+        // the symlink is written through instead of being replaced
+        let link = dir.join("link.alpha");
+        symlink(&path, &link).unwrap();
+        write_file_atomic(link.to_str().unwrap(), b"a := 3\n").unwrap();
+        assert!(std::fs::symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a := 3\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_file_if_unchanged() {
+        let dir = std::env::temp_dir().join(format!("alpha_tui_unchanged_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("program.alpha").to_str().unwrap().to_string();
+        let program = vec!["a := 1".to_string()];
+        write_file(&program, &path).unwrap();
+        let aligned = vec!["α0 := 1".to_string()];
+        assert!(write_file_if_unchanged(&aligned, &path, &program).unwrap());
+        assert_eq!(read_file(&path).unwrap(), aligned);
+        // the file was modified since the program was read, e.g. by an editor
+        assert!(!write_file_if_unchanged(&program, &path, &program).unwrap());
+        assert_eq!(read_file(&path).unwrap(), aligned);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
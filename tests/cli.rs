@@ -1,3 +1,5 @@
+use std::io::{Read, Write};
+
 use assert_cmd::Command;
 
 #[test]
@@ -25,3 +27,405 @@ fn test_cmd_check_compile_with_allowed_instructions_2() {
         .assert();
     assert.success();
 }
+
+#[test]
+fn test_cmd_load_plain_ui() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("load")
+        .arg("examples/programs/faculty.alpha")
+        .arg("--plain-ui")
+        .write_stdin("run\nquit\n")
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Execution finished."));
+}
+
+#[test]
+fn test_cmd_check_compile_with_manifest() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .current_dir("tests/input/test_cmd_check_manifest")
+        .arg("check")
+        .arg("compile")
+        .assert();
+    assert.success();
+}
+
+#[test]
+fn test_cmd_check_run_with_call() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_call/program.alpha")
+        .arg("run")
+        .arg("--call")
+        .arg("add")
+        .arg("--args")
+        .arg("5,-3")
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Result of add: α0 = 2, stack (top first): [2]"));
+}
+
+#[test]
+fn test_cmd_version_json() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd.arg("--version-json").assert().success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains(&format!("\"version\": \"{}\"", env!("CARGO_PKG_VERSION"))));
+    assert!(stdout.contains("\"build_date\""));
+    assert!(stdout.contains("\"2024\""));
+}
+
+#[test]
+fn test_cmd_check_run_with_exercise_settings() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_exercise/program.alpha")
+        .arg("run")
+        .arg("--enforce-settings")
+        .assert()
+        .success();
+    // the program header can add memory, if the settings are not enforced
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_exercise/header.alpha")
+        .arg("run")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_cmd_check_run_with_seed() {
+    let run = |seed: &str| {
+        let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+        let assert = cmd
+            .arg("check")
+            .arg("tests/input/test_cmd_check_seed/program.alpha")
+            .arg("run")
+            .arg("--seed")
+            .arg(seed)
+            .assert()
+            .success();
+        String::from_utf8(assert.get_output().stdout.clone()).unwrap()
+    };
+    // the output contains the printed random numbers
+    assert_eq!(run("7"), run("7"));
+    assert_ne!(run("7"), run("8"));
+}
+
+#[test]
+fn test_cmd_check_run_with_enforced_exercise_settings() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_exercise/program.alpha")
+        .arg("run")
+        .arg("--enforce-settings")
+        .arg("--max-steps")
+        .arg("5")
+        .assert()
+        .failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("--max-steps"));
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_exercise/header.alpha")
+        .arg("run")
+        .arg("--enforce-settings")
+        .assert()
+        .failure();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_call/program.alpha")
+        .arg("compile")
+        .arg("--enforce-settings")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cmd_lint() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("lint")
+        .arg("tests/input/test_cmd_lint/program.alpha")
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("label 'unused' is never used"));
+    assert!(stdout.contains("memory cell 'h1' is read but never written"));
+    assert!(stdout.contains("instruction is never run"));
+    assert!(stdout.contains("Lint found 3 warning(s)"));
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("lint")
+        .arg("examples/programs/faculty.alpha")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_cmd_check_fix() {
+    // the fixed program is written back, so the check runs on a copy
+    let path = std::env::temp_dir().join(format!("alpha_tui_check_fix_{}", std::process::id()));
+    std::fs::copy("tests/input/test_cmd_check_fix/program.alpha", &path).unwrap();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg(&path)
+        .arg("run")
+        .arg("--fix")
+        .arg("--strict")
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Applying 3 fix(es):"));
+    assert!(stdout.contains("line 4: write comparison '=>' as '>='\n- if a => 1 then goto loop\n+ if a >= 1 then goto loop"));
+    let program = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        program.lines().collect::<Vec<_>>(),
+        vec![
+            "# alpha_tui: max-steps=100",
+            "a := 3",
+            "loop: a := a - 1",
+            "if a >= 1 then goto loop"
+        ]
+    );
+}
+
+#[test]
+fn test_cmd_progress() {
+    let path = std::env::temp_dir().join(format!("alpha_tui_progress_{}", std::process::id()));
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_progress/wrong.alpha")
+        .arg("run")
+        .arg("--progress-file")
+        .arg(&path)
+        .assert()
+        .code(3);
+    assert!(!path.exists());
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_progress/program.alpha")
+        .arg("run")
+        .arg("--progress-file")
+        .arg(&path)
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Exercise sum completed for the first time"));
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("progress")
+        .arg("--progress-file")
+        .arg(&path)
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Progress: 1/3 bundled exercises completed"));
+    assert!(stdout.contains("[x] sum"));
+    assert!(stdout.contains("[ ] factorial"));
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("progress")
+        .arg("--reset")
+        .arg("--progress-file")
+        .arg(&path)
+        .assert()
+        .success();
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_cmd_profile() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("profile")
+        .arg("tests/input/test_cmd_profile/program.alpha")
+        .arg("--top")
+        .arg("2")
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Total steps: 8"));
+    assert!(stdout.contains("    2         3   37.50  loop: a := a + 1"));
+    assert!(stdout.contains("    3         3   37.50  if a < 3 then goto loop"));
+    assert!(!stdout.contains("p(h1) := a"));
+}
+
+#[test]
+fn test_cmd_test() {
+    let path =
+        std::env::temp_dir().join(format!("alpha_tui_test_{}.test.json", std::process::id()));
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("test")
+        .arg("record")
+        .arg("tests/input/test_cmd_test/program.alpha")
+        .arg("--output")
+        .arg(&path)
+        .arg("--memory-config-file")
+        .arg("tests/input/test_cmd_test/memory_config.toml")
+        .assert()
+        .success();
+    // the recorded initial state is used, the memory config is not needed to run the test case
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("test").arg("run").arg(&path).assert().success();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("test")
+        .arg("run")
+        .arg("tests/input/test_cmd_test/wrong.test.json")
+        .arg(&path)
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("h2: 42 (expected 40)"));
+    assert!(stdout.contains("1 of 2 test case(s) passed"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_cmd_test_cases() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("test")
+        .arg("cases")
+        .arg("tests/input/test_cmd_test_cases/program.alpha")
+        .arg("tests/input/test_cmd_test_cases/cases.toml")
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("case 1 (sum to 4): passed"));
+    assert!(stdout.contains("case 2 (sum to 0): passed"));
+    assert!(stdout.contains("case 3: failed"));
+    assert!(stdout.contains("h2: 6 (expected 7)"));
+    assert!(stdout.contains("2 of 3 test case(s) passed"));
+}
+
+/// Starts a server that accepts a single report, returns the url of the server and the handle of the thread that
+/// returns the received request.
+fn report_server() -> (String, std::thread::JoinHandle<String>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/runs", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        // the body is the last part of the request
+        while !request.ends_with(b"}") {
+            let n = stream.read(&mut buf).unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        String::from_utf8(request).unwrap()
+    });
+    (url, server)
+}
+
+#[test]
+fn test_cmd_check_report() {
+    let (url, server) = report_server();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_report/program.alpha")
+        .arg("run")
+        .arg("--report-url")
+        .arg(&url)
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Summary of the run sent to"));
+    let request = server.join().unwrap();
+    assert!(request.starts_with("POST /runs HTTP/1.1"));
+    assert!(request.contains(r#""result":"runtime_error","steps":1"#));
+    assert!(request.contains(r#""error":"runtime_error::memory_cell_uninitialized""#));
+    assert!(!request.contains("program.alpha"));
+}
+
+#[test]
+fn test_cmd_check_report_build_error() {
+    let (url, server) = report_server();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_report/build_error.alpha")
+        .arg("run")
+        .arg("--report-url")
+        .arg(&url)
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Summary of the run sent to"));
+    let request = server.join().unwrap();
+    assert!(request.contains(r#""result":"build_error","steps":0"#));
+    assert!(
+        request.contains(r#""error":"parse_instruction::"#),
+        "{request}"
+    );
+    // invalid urls are rejected before the program is run
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_report/program.alpha")
+        .arg("run")
+        .arg("--report-url")
+        .arg("ftp://dashboard/runs")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_cmd_session() {
+    let dir = std::env::temp_dir().join(format!("alpha_tui_session_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let setup = dir.join("setup.json");
+    let solution = dir.join("solution.alpha");
+    std::fs::copy("tests/input/test_cmd_session/solution.alpha", &solution).unwrap();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("session")
+        .arg("export")
+        .arg("tests/input/test_cmd_session/program.alpha")
+        .arg(&setup)
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("session")
+        .arg("import")
+        .arg(&solution)
+        .arg(&setup)
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("breakpoint in line 4 skipped"));
+    let session: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(dir.join("solution.alpha.session.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(session["breakpoints"], serde_json::json!([3]));
+    assert_eq!(session["panels"]["show_call_stack"], true);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cmd_repl() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    // the playground is started without a program file, but the tui can't be used when the output is captured
+    let assert = cmd
+        .arg("repl")
+        .arg("--accumulators")
+        .arg("2")
+        .assert()
+        .failure();
+    let output = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(output.contains("Building runtime"));
+    let error = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(error.contains("The tui can not be used in this terminal"));
+}
@@ -0,0 +1,51 @@
+use alpha_tui::{
+    runtime::error_handling::RuntimeErrorType, CheckLoadArgs, GlobalArgs, Instruction,
+    RuntimeBuilder,
+};
+
+fn lines(program: &str) -> Vec<String> {
+    program.lines().map(ToString::to_string).collect()
+}
+
+#[test]
+fn test_library_run_program() {
+    let program = lines("a := 0\nloop: a := a + 1\nif a < 5 then goto loop\np(h1) := a");
+    let mut builder = RuntimeBuilder::new(&program, "program.alpha").unwrap();
+    builder
+        .apply_global_cli_args(&GlobalArgs::default())
+        .unwrap();
+    let mut runtime = builder.build().unwrap();
+    assert!(runtime.run().unwrap());
+    assert_eq!(runtime.runtime_memory().accumulators[&0].data, Some(5));
+    assert_eq!(runtime.runtime_memory().memory_cells["h1"].data, Some(5));
+    assert_eq!(runtime.control_flow().steps, 12);
+}
+
+#[test]
+fn test_library_check_load_args() {
+    let program = lines("loop: goto loop");
+    let mut builder = RuntimeBuilder::new(&program, "program.alpha").unwrap();
+    builder
+        .apply_global_cli_args(&GlobalArgs::default())
+        .unwrap()
+        .apply_check_load_args(&CheckLoadArgs {
+            max_steps: Some(10),
+            ..CheckLoadArgs::default()
+        })
+        .unwrap();
+    let mut runtime = builder.build().unwrap();
+    let error = runtime.run().unwrap_err();
+    assert!(matches!(
+        error.reason,
+        RuntimeErrorType::StepLimitReached(10)
+    ));
+}
+
+#[test]
+fn test_library_parse_instruction() {
+    assert_eq!(
+        Instruction::try_from("goto loop").unwrap(),
+        Instruction::Goto("loop".to_string())
+    );
+    assert!(Instruction::try_from("a :=").is_err());
+}
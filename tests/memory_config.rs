@@ -26,3 +26,17 @@ fn test_memory_config_no_gamma_2() {
         .assert();
     assert.success();
 }
+
+#[test]
+fn test_memory_config_toml() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_memory_config_toml/program.alpha")
+        .arg("run")
+        .arg("--disable-memory-detection")
+        .arg("--memory-config-file")
+        .arg("tests/input/test_memory_config_toml/memory_config.toml")
+        .assert();
+    assert.success();
+}